@@ -0,0 +1,38 @@
+//! Learning performance regression suite
+//!
+//! Trains the [standard matrix](Benchmark::standard) and writes the comparison to `target/bench/learning.md` and
+//! `target/bench/learning.csv`. If `RL_BENCH_BASELINE` points to the CSV of an earlier run, exits with an error when
+//! a case's final return regressed.
+//!
+//! ```sh
+//! cargo bench --features config --bench learning
+//! ```
+
+use std::{env, error::Error, fs, path::Path, process::ExitCode};
+
+use rl::bench::Benchmark;
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    let out = Path::new("target/bench");
+    let results = Benchmark::standard().run();
+    println!("{results}");
+
+    // Compare before writing, in case the baseline is the output of the previous run
+    let regressions = match env::var_os("RL_BENCH_BASELINE") {
+        Some(baseline) => results.regressions(baseline)?,
+        None => Vec::new(),
+    };
+
+    fs::create_dir_all(out)?;
+    fs::write(out.join("learning.md"), results.to_markdown())?;
+    results.write_csv(out.join("learning.csv"))?;
+
+    if regressions.is_empty() {
+        return Ok(ExitCode::SUCCESS);
+    }
+    eprintln!("Regressions:");
+    for regression in &regressions {
+        eprintln!("  {regression}");
+    }
+    Ok(ExitCode::FAILURE)
+}
@@ -1,38 +1,79 @@
-use burn::backend::{wgpu::WgpuDevice, Autodiff, Wgpu};
+use burn::tensor::backend::AutodiffBackend;
 use gym_rs::utils::renderer::RenderMode;
 use model::ModelConfig;
-use once_cell::sync::Lazy;
 use rl::{
     algo::dqn::{DQNAgent, DQNAgentConfig},
+    device::{self, WithBackend},
     gym::CartPole,
-    viz,
+    logger::MetricSink,
+    traits::Agent,
+    viz::{self, Alert, Axis, Condition, Control, PolicySlice, Update, VizConfig},
 };
+use std::sync::mpsc;
 
 mod model;
 
-type DQNBackend = Autodiff<Wgpu>;
+const NUM_EPISODES: u64 = 256;
 
-static DEVICE: Lazy<WgpuDevice> = Lazy::new(WgpuDevice::default);
+/// Train on the best backend enabled with the `backend-*` features, or the one named by `RL_BACKEND`
+fn main() {
+    device::auto().run(Train);
+}
 
-const NUM_EPISODES: u16 = 256;
+struct Train;
 
-fn main() {
+impl WithBackend for Train {
+    type Output = ();
+
+    fn run<B: AutodiffBackend>(self, device: &'static B::Device) {
+        train::<B>(device);
+    }
+}
+
+fn train<B: AutodiffBackend>(device: &'static B::Device) {
     let mut env = CartPole::new(RenderMode::Human);
 
-    let model = ModelConfig::new(64, 128).init::<DQNBackend>(&*DEVICE);
+    let model = ModelConfig::new(64, 128).init::<B>(device);
     let agent_config = DQNAgentConfig::default();
-    let mut agent = DQNAgent::new(model, agent_config, &*DEVICE);
+    let mut agent = DQNAgent::new(model, agent_config, device).unwrap();
 
-    let (handle, tx) = viz::init(env.report.keys(), NUM_EPISODES);
+    let (control_tx, control_rx) = mpsc::channel();
+    let viz_config = VizConfig {
+        alerts: vec![Alert::new(
+            "reward",
+            Condition::MeanAtLeast {
+                window: 100,
+                threshold: 475.0,
+            },
+        )
+        .stop()],
+        control: Some(control_tx),
+        ..Default::default()
+    };
+    let (handle, mut tx) = viz::init_with_config(env.report.keys(), NUM_EPISODES, viz_config);
 
     for i in 0..NUM_EPISODES {
         agent.go(&mut env);
         let report = env.report.take();
-        tx.send(viz::Update {
-            episode: i,
-            data: report.values().copied().collect(),
-        })
-        .unwrap();
+        tx.log_episode(i, &report.into_iter().collect::<Vec<_>>())
+            .unwrap();
+
+        if i % 16 == 0 {
+            // The greedy action over pole angle and angular velocity, with the cart at rest in the center
+            let slice = PolicySlice::sweep(
+                &agent,
+                &env,
+                Axis::new("pole angle", -0.2, 0.2, 32),
+                Axis::new("pole angular velocity", -2.0, 2.0, 16),
+                |angle, velocity| [0.0, 0.0, angle, velocity],
+            );
+            let _ = tx.send(Update::PolicySlice(slice));
+        }
+
+        if let Ok(Control::Stop { reason }) = control_rx.try_recv() {
+            log::info!("Stopping early: {reason}");
+            break;
+        }
     }
 
     let _ = handle.join();
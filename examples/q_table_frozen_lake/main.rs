@@ -2,11 +2,13 @@ use rl::{
     algo::tabular::q_table::{QTableAgent, QTableAgentConfig},
     decay,
     exploration::EpsilonGreedy,
-    gym::FrozenLake,
-    viz,
+    gym::{frozen_lake::FLAction, FrozenLake},
+    logger::MetricSink,
+    traits::Agent,
+    viz::{self, Arrow, QSnapshot},
 };
 
-const NUM_EPISODES: u16 = 10000;
+const NUM_EPISODES: u64 = 10000;
 
 fn main() {
     let mut env = FrozenLake::new();
@@ -14,18 +16,31 @@ fn main() {
         exploration: EpsilonGreedy::new(decay::Exponential::new(1e-3, 1.0, 0.01).unwrap()),
         ..Default::default()
     };
-    let mut agent = QTableAgent::new(config);
+    let mut agent = QTableAgent::new(config).unwrap();
 
-    let (handle, tx) = viz::init(env.report.keys(), NUM_EPISODES);
+    let (handle, mut tx) = viz::init(env.report.keys(), NUM_EPISODES);
 
     for i in 0..NUM_EPISODES {
         agent.go(&mut env);
         let report = env.report.take();
-        tx.send(viz::Update {
-            episode: i,
-            data: report.values().copied().collect(),
-        })
-        .unwrap();
+        tx.log_episode(i, &report.into_iter().collect::<Vec<_>>())
+            .unwrap();
+
+        if i % 100 == 0 {
+            let snapshot = QSnapshot::from_q_table(
+                4,
+                4,
+                agent.get_q_table(),
+                |&state| (state % 4, state / 4),
+                |action| match action {
+                    FLAction::Left => Arrow::Left,
+                    FLAction::Down => Arrow::Down,
+                    FLAction::Right => Arrow::Right,
+                    FLAction::Up => Arrow::Up,
+                },
+            );
+            tx.send(viz::Update::QSnapshot(snapshot)).unwrap();
+        }
     }
 
     let _ = handle.join();
@@ -5,7 +5,7 @@ use rl::gym::WindyGridworld;
 
 mod agent;
 
-const NUM_EPISODES: u16 = 500;
+const NUM_EPISODES: u64 = 500;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let path = Path::new("examples/sarsa_windy_gridworld");
@@ -7,6 +7,7 @@ use rl::{
     },
     decay,
     gym::KArmedBandit,
+    traits::Agent,
 };
 
 const STEP_LIMIT: usize = 2000;
@@ -0,0 +1,166 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields};
+
+/// Derive `ToTensor` for an observation struct, flattening its fields into a tensor in declaration order
+///
+/// Fields can be `f32`, arrays of them, or other structs deriving `ToTensor`. The struct converts to a 1D tensor and
+/// a `Vec` of it to a 2D tensor with one row per observation, so it can be the state of neural agents. The derived
+/// code refers to `burn`, which has to be a dependency of the crate using it.
+///
+/// ```ignore
+/// #[derive(Clone, Debug, ToTensor)]
+/// struct Observation {
+///     position: [f32; 2],
+///     velocity: [f32; 2],
+///     fuel: f32,
+/// }
+/// ```
+#[proc_macro_derive(ToTensor)]
+pub fn derive_to_tensor(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_to_tensor(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_to_tensor(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "ToTensor can only be derived for structs",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "ToTensor can't be derived for generic structs",
+        ));
+    }
+
+    let name = &input.ident;
+    let members = data.fields.members();
+    let types = data.fields.iter().map(|field| &field.ty);
+    Ok(quote! {
+        impl ::rl::traits::Features for #name {
+            const SIZE: usize = 0 #(+ <#types as ::rl::traits::Features>::SIZE)*;
+
+            fn features(&self, out: &mut ::std::vec::Vec<f32>) {
+                #(::rl::traits::Features::features(&self.#members, out);)*
+            }
+        }
+
+        impl ::rl::traits::Observation for #name {}
+
+        impl<B: ::burn::tensor::backend::Backend>
+            ::rl::traits::ToTensor<B, 1, ::burn::tensor::Float> for #name
+        {
+            fn to_tensor(self, device: &B::Device) -> ::burn::tensor::Tensor<B, 1, ::burn::tensor::Float> {
+                let mut values = ::std::vec::Vec::with_capacity(
+                    <Self as ::rl::traits::Features>::SIZE,
+                );
+                ::rl::traits::Features::features(&self, &mut values);
+                ::burn::tensor::Tensor::from_floats(values.as_slice(), device)
+            }
+        }
+    })
+}
+
+/// Derive `DiscreteAction` for a fieldless enum, indexing its variants in declaration order
+///
+/// Also implements `From<i32>`, which panics for invalid indices, and `From<Self> for [i32; 1]`, so the enum can be
+/// the action of DQN agents without manual conversions. Explicit discriminants don't change the indices.
+///
+/// ```ignore
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DiscreteAction)]
+/// enum Move {
+///     Up,
+///     Down,
+///     Left,
+///     Right,
+/// }
+/// ```
+#[proc_macro_derive(DiscreteAction)]
+pub fn derive_discrete_action(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_discrete_action(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_discrete_action(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "DiscreteAction can only be derived for enums",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "DiscreteAction can't be derived for generic enums",
+        ));
+    }
+    if data.variants.is_empty() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "DiscreteAction needs at least one variant",
+        ));
+    }
+    if let Some(variant) = data
+        .variants
+        .iter()
+        .find(|variant| !matches!(variant.fields, Fields::Unit))
+    {
+        return Err(Error::new_spanned(
+            variant,
+            "DiscreteAction can only be derived for enums without fields",
+        ));
+    }
+
+    let name = &input.ident;
+    let variants = data
+        .variants
+        .iter()
+        .map(|variant| &variant.ident)
+        .collect::<Vec<_>>();
+    let count = variants.len();
+    let indices = 0..count;
+    let from_indices = indices.clone();
+    Ok(quote! {
+        impl ::rl::env::DiscreteAction for #name {
+            const COUNT: usize = #count;
+
+            fn index(&self) -> usize {
+                match self {
+                    #(Self::#variants => #indices,)*
+                }
+            }
+
+            fn from_index(index: usize) -> ::std::option::Option<Self> {
+                match index {
+                    #(#from_indices => ::std::option::Option::Some(Self::#variants),)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+
+        impl ::std::convert::From<i32> for #name {
+            fn from(index: i32) -> Self {
+                usize::try_from(index)
+                    .ok()
+                    .and_then(<Self as ::rl::env::DiscreteAction>::from_index)
+                    .unwrap_or_else(|| {
+                        panic!("{} is not the index of a {}", index, stringify!(#name))
+                    })
+            }
+        }
+
+        impl ::std::convert::From<#name> for [i32; 1] {
+            fn from(action: #name) -> Self {
+                [<#name as ::rl::env::DiscreteAction>::index(&action) as i32]
+            }
+        }
+    })
+}
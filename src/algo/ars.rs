@@ -0,0 +1,358 @@
+use std::marker::PhantomData;
+#[cfg(feature = "serde")]
+use std::{io, path::Path};
+
+use rand_distr::{Distribution, StandardNormal};
+
+#[cfg(feature = "serde")]
+use crate::traits::{checkpoint, Checkpoint};
+use crate::{
+    env::{ContinuousAction, Environment},
+    error::{check_interval, Result, RlError},
+    memory::Exp,
+    seed::{self, Stream},
+    stats::RunningMeanVar,
+    traits::{Agent, Features},
+};
+
+/// Standard deviations below this are treated as `1` when normalizing observations, e.g. of constant features
+const MIN_STD: f64 = 1e-8;
+
+/// Configuration for the [`ArsAgent`]
+#[derive(Debug, Clone)]
+pub struct ArsAgentConfig {
+    /// The step size of the policy updates
+    ///
+    /// **Default:** `0.02`
+    pub step_size: f32,
+    /// The standard deviation of the perturbations of the policy weights explored in each direction
+    ///
+    /// **Default:** `0.03`
+    pub noise: f32,
+    /// The number of random directions per update, each evaluated with one training episode per sign
+    ///
+    /// **Default:** `8`
+    pub directions: usize,
+    /// The number of directions with the highest returns that the update is computed from
+    ///
+    /// **Default:** `4`
+    pub top_directions: usize,
+    /// Normalize observations with the running mean and standard deviation of the observations of training episodes
+    ///
+    /// **Default:** `true`
+    pub normalize_observations: bool,
+    /// The step limit of episodes run with [`go`](Agent::go)
+    ///
+    /// **Default:** `None`
+    pub max_episode_steps: Option<u64>,
+}
+
+impl Default for ArsAgentConfig {
+    fn default() -> Self {
+        Self {
+            step_size: 0.02,
+            noise: 0.03,
+            directions: 8,
+            top_directions: 4,
+            normalize_observations: true,
+            max_episode_steps: None,
+        }
+    }
+}
+
+/// An augmented random search (ARS) agent, which learns a linear policy without gradients
+///
+/// Every update perturbs the weights of the policy in [`directions`](ArsAgentConfig::directions) random directions
+/// and runs one training episode with the weights moved in each direction and one with them moved against it. The
+/// weights then follow the directions whose episodes had the highest returns, weighted by the difference of their
+/// returns and scaled by the standard deviation of the returns. Actions are the product of the weights and the
+/// normalized observation, clamped to `[-1, 1]`.
+///
+/// Despite its simplicity, ARS is a strong baseline for continuous control, see Mania et al., 2018, *Simple random
+/// search provides a competitive approach to reinforcement learning*.
+///
+/// ### Generics
+/// - `E` - The [`Environment`] in which the agent will learn, with [`Features`] as states and continuous actions
+/// - `A` - The number of values of an action
+#[derive(Debug, Clone)]
+pub struct ArsAgent<E, const A: usize>
+where
+    E: Environment<Action = ContinuousAction<A>>,
+    E::State: Features,
+{
+    config: ArsAgentConfig,
+    /// Row-major matrix with one row per action value and one column per feature
+    weights: Vec<f32>,
+    deltas: Vec<Vec<f32>>,
+    /// The returns of the episodes of the current update, in the order `+delta`, `-delta` for every direction
+    returns: Vec<f32>,
+    episode_return: f32,
+    observations: Vec<RunningMeanVar>,
+    return_std: Option<f64>,
+    _env: PhantomData<fn() -> E>,
+}
+
+impl<E, const A: usize> ArsAgent<E, A>
+where
+    E: Environment<Action = ContinuousAction<A>>,
+    E::State: Features,
+{
+    /// Initialize an agent with zero weights
+    ///
+    /// ### Returns
+    /// - [`RlError::OutOfRange`] if `step_size` or `noise` is negative
+    /// - [`RlError::InvalidHyperparameters`] if there are no directions or `top_directions` is not in
+    ///   `1..=directions`
+    pub fn new(config: ArsAgentConfig) -> Result<Self> {
+        check_interval("step_size", config.step_size, 0.0, f32::INFINITY)?;
+        check_interval("noise", config.noise, 0.0, f32::INFINITY)?;
+        if !(1..=config.directions).contains(&config.top_directions) {
+            return Err(RlError::InvalidHyperparameters(format!(
+                "`top_directions` must be between 1 and `directions` ({}), got {}",
+                config.directions, config.top_directions
+            )));
+        }
+
+        let mut agent = Self {
+            weights: vec![0.0; A * <E::State as Features>::SIZE],
+            deltas: Vec::new(),
+            returns: Vec::new(),
+            episode_return: 0.0,
+            observations: vec![RunningMeanVar::new(); <E::State as Features>::SIZE],
+            return_std: None,
+            config,
+            _env: PhantomData,
+        };
+        agent.sample_deltas();
+        Ok(agent)
+    }
+
+    /// The weights of the linear policy, a row-major matrix with one row per action value and one column per feature
+    /// of the normalized observation
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    fn sample_deltas(&mut self) {
+        let mut rng = seed::rng(Stream::Exploration);
+        self.deltas = (0..self.config.directions)
+            .map(|_| {
+                (0..self.weights.len())
+                    .map(|_| StandardNormal.sample(&mut rng))
+                    .collect()
+            })
+            .collect();
+    }
+
+    /// The features of `state`, normalized with the current statistics
+    fn normalized(&self, state: &E::State) -> Vec<f32> {
+        let mut features = Vec::with_capacity(<E::State as Features>::SIZE);
+        state.features(&mut features);
+        if self.config.normalize_observations {
+            for (x, stats) in features.iter_mut().zip(&self.observations) {
+                let std = stats.std();
+                let std = if std < MIN_STD { 1.0 } else { std };
+                *x = ((*x as f64 - stats.mean()) / std) as f32;
+            }
+        }
+        features
+    }
+
+    /// The action of the policy with its weights moved by `scale` times `delta`
+    fn act_with(&self, features: &[f32], delta: Option<(&[f32], f32)>) -> ContinuousAction<A> {
+        ContinuousAction(std::array::from_fn(|i| {
+            let row = i * features.len()..(i + 1) * features.len();
+            let value = match delta {
+                Some((delta, scale)) => self.weights[row.clone()]
+                    .iter()
+                    .zip(&delta[row])
+                    .zip(features)
+                    .map(|((w, d), x)| (w + scale * d) * x)
+                    .sum::<f32>(),
+                None => self.weights[row]
+                    .iter()
+                    .zip(features)
+                    .map(|(w, x)| w * x)
+                    .sum(),
+            };
+            value.clamp(-1.0, 1.0)
+        }))
+    }
+
+    /// Move the weights towards the best directions and sample new ones
+    fn update(&mut self) {
+        let mut ranked = (0..self.deltas.len()).collect::<Vec<_>>();
+        let best = |k: usize| self.returns[2 * k].max(self.returns[2 * k + 1]);
+        ranked.sort_by(|&a, &b| best(b).total_cmp(&best(a)));
+        ranked.truncate(self.config.top_directions);
+
+        let std = ranked
+            .iter()
+            .flat_map(|&k| [self.returns[2 * k], self.returns[2 * k + 1]])
+            .map(f64::from)
+            .collect::<RunningMeanVar>()
+            .std();
+        self.return_std = Some(std);
+        let scale = self.config.step_size
+            / (ranked.len() as f32 * if std < MIN_STD { 1.0 } else { std as f32 });
+        for &k in &ranked {
+            let difference = self.returns[2 * k] - self.returns[2 * k + 1];
+            for (w, d) in self.weights.iter_mut().zip(&self.deltas[k]) {
+                *w += scale * difference * d;
+            }
+        }
+
+        self.returns.clear();
+        self.sample_deltas();
+    }
+}
+
+impl<E, const A: usize> Agent<E> for ArsAgent<E, A>
+where
+    E: Environment<Action = ContinuousAction<A>>,
+    E::State: Features,
+{
+    /// Act with the weights perturbed in the direction of the current episode
+    fn act(&mut self, _env: &E, state: &E::State) -> E::Action {
+        if self.config.normalize_observations {
+            let mut features = Vec::with_capacity(<E::State as Features>::SIZE);
+            state.features(&mut features);
+            for (stats, &x) in self.observations.iter_mut().zip(&features) {
+                stats.push(x as f64);
+            }
+        }
+
+        let rollout = self.returns.len();
+        let sign = if rollout % 2 == 0 { 1.0 } else { -1.0 };
+        let delta = &self.deltas[rollout / 2];
+        self.act_with(
+            &self.normalized(state),
+            Some((delta, sign * self.config.noise)),
+        )
+    }
+
+    fn learn(&mut self, _env: &E, experience: Exp<E>) {
+        self.episode_return += experience.reward;
+    }
+
+    /// Record the return of the episode, updating the policy once every direction was evaluated
+    fn on_episode_end(&mut self) {
+        self.returns.push(std::mem::take(&mut self.episode_return));
+        if self.returns.len() == 2 * self.deltas.len() {
+            self.update();
+        }
+    }
+
+    /// The standard deviation of the returns the last update was computed from, `return_std`, and the norm of the
+    /// weights, `param_norm`, if the policy was updated since the last call
+    fn metrics(&mut self) -> Vec<(&'static str, f64)> {
+        let Some(return_std) = self.return_std.take() else {
+            return Vec::new();
+        };
+        let param_norm = self
+            .weights
+            .iter()
+            .map(|w| (w * w) as f64)
+            .sum::<f64>()
+            .sqrt();
+        vec![("return_std", return_std), ("param_norm", param_norm)]
+    }
+
+    fn policy(&self, _env: &E, state: &E::State) -> E::Action {
+        self.act_with(&self.normalized(state), None)
+    }
+
+    fn max_episode_steps(&self) -> Option<u64> {
+        self.config.max_episode_steps
+    }
+}
+
+/// The learned state of an [`ArsAgent`], as written by [`Checkpoint::save`]
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ArsAgentState {
+    weights: Vec<f32>,
+    /// `(count, mean, variance)` of every feature
+    observations: Vec<(u64, f64, f64)>,
+}
+
+/// Checkpoints are single JSON files
+///
+/// The episodes of the update in progress are not saved, so a restored agent starts a new update.
+#[cfg(feature = "serde")]
+impl<E, const A: usize> Checkpoint for ArsAgent<E, A>
+where
+    E: Environment<Action = ContinuousAction<A>>,
+    E::State: Features,
+{
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let state = ArsAgentState {
+            weights: self.weights.clone(),
+            observations: self
+                .observations
+                .iter()
+                .map(|stats| (stats.count(), stats.mean(), stats.var()))
+                .collect(),
+        };
+        checkpoint::write_json(path, &state)
+    }
+
+    fn load(&mut self, path: &Path) -> io::Result<()> {
+        let state: ArsAgentState = checkpoint::read_json(path)?;
+        if state.weights.len() != self.weights.len()
+            || state.observations.len() != self.observations.len()
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the checkpoint is of an agent with different state or action dimensions",
+            ));
+        }
+        self.weights = state.weights;
+        self.observations = state
+            .observations
+            .into_iter()
+            .map(|(count, mean, var)| RunningMeanVar::from_parts(count, mean, var))
+            .collect();
+        self.returns.clear();
+        self.episode_return = 0.0;
+        self.sample_deltas();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seed::Seeds, testing::Point};
+
+    #[test]
+    fn ars_learns_linear_policy() {
+        Seeds::new(0).apply();
+        // Moving by twice the negative position reaches the origin in one step
+        let mut env = Point::new(0.4);
+        let mut agent = ArsAgent::new(ArsAgentConfig {
+            step_size: 0.02,
+            noise: 0.05,
+            directions: 4,
+            top_directions: 2,
+            normalize_observations: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+        for _ in 0..2000 {
+            agent.go(&mut env);
+        }
+        assert_eq!(agent.metrics().len(), 2, "Updated after the last episode");
+        assert!(agent.metrics().is_empty());
+
+        let action = agent.policy(&env, &[0.4]).0[0];
+        assert!((action + 0.8).abs() < 0.1, "{action} is close to -0.8");
+
+        assert!(ArsAgent::<Point, 1>::new(ArsAgentConfig {
+            top_directions: 9,
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
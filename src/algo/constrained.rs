@@ -0,0 +1,263 @@
+use crate::{
+    env::ConstrainedEnvironment,
+    error::{check_interval, Result},
+    memory::Exp,
+    traits::{Agent, Hyperparam},
+};
+
+/// Configuration for a [`Constrained`] agent
+#[derive(Debug, Clone)]
+pub struct ConstrainedConfig {
+    /// The largest accepted expected cost of an episode
+    ///
+    /// **Default:** `0.0`
+    pub budget: f32,
+    /// The step size of the updates of the Lagrange multiplier after every episode
+    ///
+    /// **Default:** `0.01`
+    pub multiplier_lr: f32,
+    /// The Lagrange multiplier when training starts
+    ///
+    /// **Default:** `0.0`
+    pub initial_multiplier: f32,
+    /// The largest value of the Lagrange multiplier, which bounds the penalty of costs while the constraint is
+    /// violated
+    ///
+    /// **Default:** `100.0`
+    pub max_multiplier: f32,
+}
+
+impl Default for ConstrainedConfig {
+    fn default() -> Self {
+        Self {
+            budget: 0.0,
+            multiplier_lr: 0.01,
+            initial_multiplier: 0.0,
+            max_multiplier: 100.0,
+        }
+    }
+}
+
+/// An agent that learns to keep the expected cost of its episodes under a budget, by Lagrangian relaxation of the
+/// constraint
+///
+/// The wrapped agent learns from rewards penalized with the costs of the [`ConstrainedEnvironment`], `r - λc`. The
+/// Lagrange multiplier `λ` follows the violation of the constraint with dual gradient ascent after every episode,
+/// `λ ← clamp(λ + lr * (C - budget), 0, max)` where `C` is the total cost of the episode. It grows while episodes cost
+/// more than the budget, until avoiding costs pays off for the agent, and shrinks back to `0` once they cost less.
+/// Wrapping a policy gradient agent gives the Lagrangian variants of safe RL, e.g. PPO-Lagrangian, see Ray et al.,
+/// 2019, *Benchmarking Safe Exploration in Deep Reinforcement Learning*.
+///
+/// The total cost of each episode and the multiplier after it are reported with the wrapped agent's
+/// [`metrics`](Agent::metrics), named `episode_cost` and `lagrange_multiplier`.
+///
+/// ```ignore
+/// let agent = Constrained::new(agent, ConstrainedConfig { budget: 25.0, ..Default::default() })?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Constrained<A> {
+    agent: A,
+    budget: f32,
+    multiplier_lr: f32,
+    max_multiplier: f32,
+    multiplier: f32,
+    episode_cost: f32,
+    finished_costs: Vec<f32>,
+}
+
+impl<A> Constrained<A> {
+    /// Wrap `agent` to keep the cost of its episodes under the budget of `config`
+    ///
+    /// **Returns** [`RlError::OutOfRange`](crate::error::RlError::OutOfRange) if `budget`, `multiplier_lr` or
+    /// `max_multiplier` is negative, or `initial_multiplier` is not in the interval `[0, max_multiplier]`
+    pub fn new(agent: A, config: ConstrainedConfig) -> Result<Self> {
+        check_interval("budget", config.budget, 0.0, f32::INFINITY)?;
+        check_interval("multiplier_lr", config.multiplier_lr, 0.0, f32::INFINITY)?;
+        check_interval("max_multiplier", config.max_multiplier, 0.0, f32::INFINITY)?;
+        check_interval(
+            "initial_multiplier",
+            config.initial_multiplier,
+            0.0,
+            config.max_multiplier,
+        )?;
+
+        Ok(Self {
+            agent,
+            budget: config.budget,
+            multiplier_lr: config.multiplier_lr,
+            max_multiplier: config.max_multiplier,
+            multiplier: config.initial_multiplier,
+            episode_cost: 0.0,
+            finished_costs: Vec::new(),
+        })
+    }
+
+    /// The current Lagrange multiplier, the penalty of one unit of cost
+    pub fn multiplier(&self) -> f32 {
+        self.multiplier
+    }
+
+    /// Get the wrapped agent
+    pub fn agent(&self) -> &A {
+        &self.agent
+    }
+
+    /// Take back the wrapped agent
+    pub fn into_inner(self) -> A {
+        self.agent
+    }
+}
+
+impl<E, A> Agent<E> for Constrained<A>
+where
+    E: ConstrainedEnvironment,
+    A: Agent<E>,
+{
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action {
+        self.agent.act(env, state)
+    }
+
+    /// Pass the experience on with its reward penalized by the cost of the step
+    fn learn(&mut self, env: &E, mut experience: Exp<E>) {
+        let cost = env.cost();
+        self.episode_cost += cost;
+        experience.reward -= self.multiplier * cost;
+        self.agent.learn(env, experience);
+    }
+
+    /// Update the Lagrange multiplier with the cost of the episode
+    fn on_episode_end(&mut self) {
+        let violation = self.episode_cost - self.budget;
+        self.multiplier =
+            (self.multiplier + self.multiplier_lr * violation).clamp(0.0, self.max_multiplier);
+        self.finished_costs
+            .push(std::mem::take(&mut self.episode_cost));
+        self.agent.on_episode_end();
+    }
+
+    fn metrics(&mut self) -> Vec<(&'static str, f64)> {
+        let mut metrics = self.agent.metrics();
+        let costs = std::mem::take(&mut self.finished_costs);
+        if !costs.is_empty() {
+            let mean = costs.iter().sum::<f32>() / costs.len() as f32;
+            metrics.push(("episode_cost", mean as f64));
+        }
+        metrics.push(("lagrange_multiplier", self.multiplier as f64));
+        metrics
+    }
+
+    fn hyperparams(&self) -> Vec<Hyperparam> {
+        self.agent.hyperparams()
+    }
+
+    fn set_hyperparam(&mut self, name: &str, value: f64) -> bool {
+        self.agent.set_hyperparam(name, value)
+    }
+
+    fn reset_policy(&self) {
+        self.agent.reset_policy();
+    }
+
+    fn policy(&self, env: &E, state: &E::State) -> E::Action {
+        self.agent.policy(env, state)
+    }
+
+    fn max_episode_steps(&self) -> Option<u64> {
+        self.agent.max_episode_steps()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algo::tabular::q_table::{QTableAgent, QTableAgentConfig},
+        env::{DiscreteActionSpace, Environment},
+        seed::Seeds,
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Route {
+        /// Reward 0.5 without cost
+        Safe,
+        /// Reward 1 with cost 1
+        Risky,
+    }
+
+    /// Episodes of one step, choosing between a safe and a risky route
+    struct Routes {
+        cost: f32,
+    }
+
+    impl Environment for Routes {
+        type State = ();
+        type Action = Route;
+
+        fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
+            let (reward, cost) = match action {
+                Route::Safe => (0.5, 0.0),
+                Route::Risky => (1.0, 1.0),
+            };
+            self.cost = cost;
+            (None, reward)
+        }
+
+        fn reset(&mut self) -> Self::State {
+            self.cost = 0.0;
+        }
+
+        fn random_action(&self) -> Self::Action {
+            Route::Risky
+        }
+    }
+
+    impl DiscreteActionSpace for Routes {
+        fn actions(&self) -> Vec<Self::Action> {
+            vec![Route::Safe, Route::Risky]
+        }
+    }
+
+    impl ConstrainedEnvironment for Routes {
+        fn cost(&self) -> f32 {
+            self.cost
+        }
+    }
+
+    #[test]
+    fn constrained_avoids_costs() {
+        Seeds::new(0).apply();
+        let mut env = Routes { cost: 0.0 };
+        let q_table = QTableAgent::new(QTableAgentConfig {
+            alpha: 0.5,
+            ..Default::default()
+        })
+        .unwrap();
+        let mut agent = Constrained::new(
+            q_table,
+            ConstrainedConfig {
+                budget: 0.0,
+                multiplier_lr: 0.05,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        for _ in 0..200 {
+            agent.go(&mut env);
+        }
+
+        assert!(agent.multiplier() > 0.5, "{}", agent.multiplier());
+        assert_eq!(agent.policy(&env, &()), Route::Safe);
+        let metrics = agent.metrics();
+        assert!(metrics.iter().any(|&(name, _)| name == "episode_cost"));
+        assert_eq!(metrics.last().unwrap().0, "lagrange_multiplier");
+
+        assert!(Constrained::new(
+            (),
+            ConstrainedConfig {
+                initial_multiplier: 200.0,
+                ..Default::default()
+            }
+        )
+        .is_err());
+    }
+}
@@ -1,18 +1,35 @@
+use std::{
+    fmt::{self, Debug},
+    io,
+    marker::PhantomData,
+    path::PathBuf,
+};
+#[cfg(feature = "serde")]
+use std::{fs, path::Path};
+
+#[cfg(feature = "serde")]
+use burn::record::{FullPrecisionSettings, NamedMpkFileRecorder, Recorder};
 use burn::{
     grad_clipping::GradientClippingConfig,
     module::AutodiffModule,
-    optim::{AdamWConfig, GradientsParams, Optimizer},
+    optim::{adaptor::OptimizerAdaptor, AdamW, AdamWConfig, GradientsParams, Optimizer},
     prelude::*,
-    tensor::backend::AutodiffBackend,
+    tensor::{backend::AutodiffBackend, ElementConversion},
 };
-use nn::loss::{MseLoss, Reduction};
 
+#[cfg(feature = "serde")]
+use crate::traits::{checkpoint, Checkpoint};
 use crate::{
     decay::{self, Decay},
     env::Environment,
-    exploration::{Choice, EpsilonGreedy},
-    memory::{Exp, Memory, PrioritizedReplayMemory, ReplayMemory},
-    traits::ToTensor,
+    error::{check_interval, Result},
+    exploration::{max_by_random, Choice, EpsilonGreedy},
+    export,
+    losses::ValueLoss,
+    memory::{Collate, Exp, Memory, PrioritizedReplayMemory, ReplayMemory},
+    nn::{self, TargetNetwork, TargetUpdate},
+    train::{Actor, ParallelAgent},
+    traits::{Agent, Hyperparam, Step, ToTensor},
 };
 
 /// A burn module used with a Deep Q network agent
@@ -23,15 +40,6 @@ use crate::{
 pub trait DQNModel<B: AutodiffBackend, const D: usize>: AutodiffModule<B> {
     /// Forward pass through the model
     fn forward(&self, input: Tensor<B, D>) -> Tensor<B, 2>;
-
-    /// Soft update the parameters of the target network
-    ///
-    /// θ′ ← τθ + (1 − τ)θ′
-    ///
-    /// ```ignore
-    /// target_net = target_net.soft_update(policy_net, tau);
-    /// ```
-    fn soft_update(self, other: &Self, tau: f32) -> Self;
 }
 
 /// Configuration for the [`DQNAgent`]
@@ -78,21 +86,87 @@ pub struct DQNAgentConfig<D> {
     ///
     /// **Default:** `0.999`
     pub gamma: f32,
-    /// The interval at which to perform soft updates on the target network
+    /// How the target network follows the policy network, counted in learning steps
     ///
-    /// **Default:** `1`
-    pub target_update_interval: usize,
-    /// The rate at which the target network's parameters are soft updated with the policy network's parameters
+    /// **Default:** [soft updates](TargetUpdate::Soft) with `tau` `5e-3` after every learning step
+    pub target_update: TargetUpdate,
+    /// The loss between the Q values of the chosen actions and their bootstrapped targets
     ///
-    /// **Default:** `5e-3`
-    pub tau: f32,
+    /// **Default:** [`ValueLoss::Mse`]
+    pub loss: ValueLoss,
     /// The learning rate for the optimizer
     ///
     /// **Default:** `1e-3`
     pub lr: f32,
+    /// Clip the gradient of each parameter to a maximum value or L2 norm in every optimizer step
+    ///
+    /// **Default:** clipping to values in `[-100, 100]`
+    pub grad_clipping: Option<GradientClippingConfig>,
+    /// Report the mean loss, the mean and maximum gradient norm before clipping, and the parameter norm of the
+    /// learning steps of each episode as the agent's [`metrics`](Agent::metrics), named `loss`, `grad_norm`,
+    /// `grad_norm_max` and `param_norm`
+    ///
+    /// Reading them synchronizes with the device after every learning step, which slows down training on a GPU.
+    ///
+    /// **Default:** `false`
+    pub diagnostics: bool,
+    /// Keep an exponential moving average of the policy network's parameters with this rate τ, updated after every
+    /// learning step, and act with it in [`policy`](Agent::policy) and evaluations
+    ///
+    /// The averaged network usually evaluates better than the noisier policy network it follows. It is only used
+    /// for evaluation and saved with checkpoints, while training keeps acting and learning with the policy network.
+    ///
+    /// **Default:** `None`
+    pub policy_average: Option<f32>,
 }
 
-// type AdamWOptimizer<M, B> = OptimizerAdaptor<AdamW<<B as AutodiffBackend>::InnerBackend>, M, B>;
+pub(super) type AdamWOptimizer<M, B> =
+    OptimizerAdaptor<AdamW<<B as AutodiffBackend>::InnerBackend>, M, B>;
+
+/// The hyperparameters of the Q network agents that can be changed while training, see [`Agent::hyperparams`]
+pub(super) fn hyperparams<DEC: Decay>(
+    lr: f32,
+    gamma: f32,
+    exploration: &EpsilonGreedy<DEC>,
+) -> Vec<Hyperparam> {
+    vec![
+        Hyperparam::gamma(gamma),
+        Hyperparam {
+            name: "lr",
+            value: lr as f64,
+            step: Step::Scale(1.25),
+        },
+        Hyperparam {
+            name: "epsilon_floor",
+            value: exploration.floor() as f64,
+            step: Step::Add(0.01),
+        },
+    ]
+}
+
+/// Change one of the [`hyperparams`] of a Q network agent, see [`Agent::set_hyperparam`]
+pub(super) fn set_hyperparam<DEC: Decay>(
+    lr: &mut f32,
+    gamma: &mut f32,
+    exploration: &mut EpsilonGreedy<DEC>,
+    name: &str,
+    value: f64,
+) -> bool {
+    match name {
+        "lr" if value >= 0.0 => *lr = value as f32,
+        "gamma" if (0.0..=1.0).contains(&value) => *gamma = value as f32,
+        "epsilon_floor" if (0.0..=1.0).contains(&value) => exploration.set_floor(value as f32),
+        _ => return false,
+    }
+    true
+}
+
+/// Initialize the [`AdamW`] optimizer used to train the policy network
+pub(super) fn adamw<B: AutodiffBackend, M: AutodiffModule<B>>(
+    grad_clipping: Option<GradientClippingConfig>,
+) -> AdamWOptimizer<M, B> {
+    AdamWConfig::new().with_grad_clipping(grad_clipping).init()
+}
 
 impl Default for DQNAgentConfig<decay::Exponential> {
     fn default() -> Self {
@@ -106,9 +180,15 @@ impl Default for DQNAgentConfig<decay::Exponential> {
             // optimizer: AdamWConfig::new().init(),
             epsilon_decay_strategy: decay::Exponential::new(1e-3, 1.0, 0.05).unwrap(),
             gamma: 0.999,
-            target_update_interval: 1,
-            tau: 5e-3,
+            target_update: TargetUpdate::Soft {
+                tau: 5e-3,
+                interval: 1,
+            },
+            loss: ValueLoss::default(),
             lr: 1e-3,
+            grad_clipping: Some(GradientClippingConfig::Value(100.0)),
+            diagnostics: false,
+            policy_average: None,
         }
     }
 }
@@ -116,7 +196,8 @@ impl Default for DQNAgentConfig<decay::Exponential> {
 /// A Deep Q Network agent
 ///
 /// ### Generics
-/// - `B` - A burn backend
+/// - `B` - A burn backend, with any float and integer element types, e.g. `f64` for more precise values or a half
+///   precision float to save memory
 /// - `M` - The [`DQNModel`] used for the policy and target networks
 /// - `E` - The [`Environment`] in which the agent will learn
 ///     - The environment's action space must be discrete, since the policy network produces a Q value for each action.
@@ -126,30 +207,32 @@ impl Default for DQNAgentConfig<decay::Exponential> {
 /// - `D` - The dimension of the input
 ///
 /// A generic optimizer will be added when burn v0.14.0 releases, until then the [`AdamW`](burn::optim::AdamW) optimizer will be used
-#[derive(Debug, Clone)]
 pub struct DQNAgent<B, M, E, DEC, const D: usize>
 where
     B: AutodiffBackend,
+    M: AutodiffModule<B>,
     E: Environment,
     DEC: Decay,
 {
     policy_net: Option<M>,
-    target_net: Option<M>,
+    target_net: TargetNetwork<M>,
+    average_net: Option<TargetNetwork<M>>,
     device: &'static B::Device,
     memory: Memory<E>,
-    // optimizer: O,
+    optimizer: AdamWOptimizer<M, B>,
+    grad_clipping: Option<GradientClippingConfig>,
     exploration: EpsilonGreedy<DEC>,
     gamma: f32,
-    target_update_interval: usize,
-    tau: f32,
+    loss: ValueLoss,
     lr: f32,
-    total_steps: u32,
+    total_steps: u64,
     episodes_elapsed: usize,
+    diagnostics: Option<Diagnostics>,
 }
 
 impl<B, M, E, DEC, const D: usize> DQNAgent<B, M, E, DEC, D>
 where
-    B: AutodiffBackend<FloatElem = f32, IntElem = i32>,
+    B: AutodiffBackend,
     M: DQNModel<B, D>,
     E: Environment,
     DEC: Decay,
@@ -163,8 +246,18 @@ where
     /// - `model` A [`DQNModel`] to be used as the policy and target networks
     /// - `config` A [`DQNAgentConfig`] containing components and hyperparameters for the agent
     /// - `device` A static reference to the device used for the `model`
-    pub fn new(model: M, config: DQNAgentConfig<DEC>, device: &'static B::Device) -> Self {
-        let model_clone = model.clone();
+    ///
+    /// **Returns** an [`RlError`](crate::error::RlError) if `gamma` is not in the interval `[0, 1]`, `lr` is
+    /// negative, or the target update or prioritized memory hyperparameters are invalid
+    pub fn new(model: M, config: DQNAgentConfig<DEC>, device: &'static B::Device) -> Result<Self> {
+        check_interval("gamma", config.gamma, 0.0, 1.0)?;
+        check_interval("lr", config.lr, 0.0, f32::INFINITY)?;
+
+        let target_net = TargetNetwork::new(&model, config.target_update)?;
+        let average_net = config
+            .policy_average
+            .map(|tau| TargetNetwork::new(&model, TargetUpdate::Soft { tau, interval: 1 }))
+            .transpose()?;
         let memory = if config.use_prioritized_memory {
             Memory::Prioritized(PrioritizedReplayMemory::new(
                 config.memory_capacity,
@@ -172,7 +265,7 @@ where
                 config.prioritized_memory_alpha,
                 config.prioritized_memory_beta_0,
                 config.num_episodes,
-            ))
+            )?)
         } else {
             Memory::Base(ReplayMemory::new(
                 config.memory_capacity,
@@ -180,42 +273,89 @@ where
             ))
         };
 
-        Self {
+        Ok(Self {
             policy_net: Some(model),
-            target_net: Some(model_clone),
+            target_net,
+            average_net,
             device,
             memory,
-            // optimizer: config.optimizer,
+            optimizer: adamw(config.grad_clipping.clone()),
+            grad_clipping: config.grad_clipping,
             exploration: EpsilonGreedy::new(config.epsilon_decay_strategy),
             gamma: config.gamma,
-            target_update_interval: config.target_update_interval,
-            tau: config.tau,
+            loss: config.loss,
             lr: config.lr,
             total_steps: 0,
             episodes_elapsed: 0,
-        }
+            diagnostics: config.diagnostics.then(Diagnostics::default),
+        })
     }
 
-    /// Invoke the agent's policy along with the exploration strategy to choose an action from the given state
-    fn act(&self, env: &E, state: E::State) -> E::Action {
-        match self.exploration.choose(self.total_steps) {
-            Choice::Explore => env.random_action(),
-            Choice::Exploit => {
-                let input = vec![state].to_tensor(self.device);
-                let output = self
-                    .policy_net
-                    .as_ref()
-                    .unwrap()
-                    .forward(input)
-                    .argmax(1)
-                    .into_scalar();
-                E::Action::from(output)
+    /// Export the network the agent evaluates with, the averaged policy network if
+    /// [`policy_average`](DQNAgentConfig::policy_average) is set and the policy network otherwise, to `<path>.mpk`
+    ///
+    /// The network is exported without autodiff, see [`export_net`](crate::export::export_net).
+    pub fn export(&self, path: impl Into<PathBuf>) -> io::Result<()> {
+        let net = match &self.average_net {
+            Some(average_net) => average_net.net().valid(),
+            None => self
+                .policy_net
+                .as_ref()
+                .expect("the policy network is only taken during a learning step")
+                .valid(),
+        };
+        export::export_net(net, path)
+    }
+
+    /// Choose the action with the highest Q value in the given state according to the policy network
+    fn greedy(&self, state: E::State) -> E::Action {
+        greedy::<B, M, E, D>(self.policy_net.as_ref().unwrap(), state, self.device)
+    }
+
+    /// Perform an optimizer step on the policy network, recording the diagnostics of the learning step
+    ///
+    /// The loss, epsilon, replay memory size and, with diagnostics enabled, the gradient and parameter norms are
+    /// emitted as a debug event. Reading them synchronizes with the device, so it only happens if diagnostics or the
+    /// event are enabled.
+    fn optimize(&mut self, policy_net: M, loss: Tensor<B, 1>, buffer_size: usize) {
+        let grads = GradientsParams::from_grads(loss.backward(), &policy_net);
+        let grad_norm = self
+            .diagnostics
+            .is_some()
+            .then(|| nn::grad_norm(&policy_net, &grads));
+        let policy_net = self.optimizer.step(self.lr.into(), policy_net, grads);
+
+        if self.diagnostics.is_some() || tracing::enabled!(tracing::Level::DEBUG) {
+            let loss = loss.into_scalar().elem::<f32>();
+            let param_norm = self
+                .diagnostics
+                .is_some()
+                .then(|| nn::param_norm(&policy_net));
+            if let (Some(diagnostics), Some(grad_norm), Some(param_norm)) =
+                (&mut self.diagnostics, grad_norm, param_norm)
+            {
+                diagnostics.record(loss, grad_norm, param_norm);
             }
+            tracing::debug!(
+                loss,
+                grad_norm,
+                param_norm,
+                epsilon = self.exploration.epsilon(self.total_steps),
+                buffer_size,
+                "learning step"
+            );
         }
+
+        if let Some(average_net) = &mut self.average_net {
+            average_net.step(&policy_net);
+        }
+        self.policy_net = Some(policy_net);
     }
 
     /// Perform one DQN learning step
-    fn learn(&mut self, optimizer: &mut impl Optimizer<M, B>) {
+    fn learn_base(&mut self) {
+        let _span = tracing::debug_span!("batch", step = self.total_steps).entered();
+
         // Sample a batch of memories to train on
         let Memory::Base(memory) = &mut self.memory else {
             return;
@@ -223,63 +363,45 @@ where
         let Some(batch) = memory.sample_zipped() else {
             return;
         };
-        let batch_size = memory.batch_size;
-
-        // Create a boolean mask for non-terminal next states so tensor shapes can match in the Bellman Equation
-        let non_terminal_mask = batch
-            .next_states
-            .iter()
-            .map(Option::is_some)
-            .collect::<Vec<_>>()
-            .to_tensor(self.device)
-            .unsqueeze_dim(1);
-
-        // Tensor conversions
-        let states = batch.states.to_tensor(self.device);
-        let actions = batch
-            .actions
-            .into_iter()
-            .map(|a| a.into())
-            .collect::<Vec<_>>()
-            .to_tensor(self.device);
-        let next_states = batch
-            .next_states
-            .into_iter()
-            .flatten()
-            .collect::<Vec<_>>()
-            .to_tensor(self.device);
-        let rewards = batch.rewards.to_tensor(self.device).unsqueeze_dim(1);
+        let buffer_size = memory.len();
+
+        // Tensor conversions, with a row for every next state so tensor shapes match in the Bellman Equation
+        let batch = batch.collate::<B, D, Int, [i32; 1]>(self.device);
 
         let policy_net = self.policy_net.take().unwrap();
-        let target_net = self.target_net.take().unwrap();
 
         // Compute the Q values of the chosen actions in each state
-        let q_values = policy_net.forward(states).gather(1, actions);
+        let q_values = policy_net
+            .forward(batch.states.clone())
+            .gather(1, batch.actions.clone());
 
         // Compute the maximum Q values obtainable from each next state
-        let expected_q_values = Tensor::zeros([batch_size, 1], self.device).mask_where(
-            non_terminal_mask,
-            target_net.forward(next_states).max_dim(1).detach(),
-        );
+        let next_q_values = self
+            .target_net
+            .net()
+            .forward(batch.next_states.clone())
+            .max_dim(1)
+            .detach();
 
-        let discounted_expected_return = rewards + (expected_q_values * self.gamma);
+        let discounted_expected_return = batch.targets(next_q_values, self.gamma);
 
-        // Compute loss (mean sqared temporal difference error)
-        let loss = MseLoss::new().forward(q_values, discounted_expected_return, Reduction::Mean);
+        // Compute the mean loss of the temporal difference errors
+        let loss = self
+            .loss
+            .forward(q_values, discounted_expected_return)
+            .mean();
 
         // Perform backpropagation on policy net
-        let grads = GradientsParams::from_grads(loss.backward(), &policy_net);
-        self.policy_net = Some(optimizer.step(self.lr.into(), policy_net, grads));
+        self.optimize(policy_net, loss, buffer_size);
 
-        // Perform a periodic soft update on the parameters of the target network for stable convergence
-        self.target_net = if self.episodes_elapsed % self.target_update_interval == 0 {
-            Some(target_net.soft_update(self.policy_net.as_ref().unwrap(), self.tau))
-        } else {
-            Some(target_net)
-        };
+        // Periodically update the target network towards the policy network for stable convergence
+        self.target_net.step(self.policy_net.as_ref().unwrap());
     }
 
-    fn learn_prioritized(&mut self, optimizer: &mut impl Optimizer<M, B>) {
+    /// Perform one DQN learning step with prioritized experience replay
+    fn learn_prioritized(&mut self) {
+        let _span = tracing::debug_span!("batch", step = self.total_steps).entered();
+
         // Sample a batch of memories to train on
         let Memory::Prioritized(memory) = &mut self.memory else {
             return;
@@ -287,103 +409,525 @@ where
         let Some((batch, weights, indices)) = memory.sample_zipped(self.episodes_elapsed) else {
             return;
         };
-        let batch_size = memory.batch_size;
-
-        // Create a boolean mask for non-terminal next states so tensor shapes can match in the Bellman Equation
-        let non_terminal_mask = batch
-            .next_states
-            .iter()
-            .map(Option::is_some)
-            .collect::<Vec<_>>()
-            .to_tensor(self.device)
-            .unsqueeze_dim(1);
-
-        // Tensor conversions
-        let states = batch.states.to_tensor(self.device);
-        let actions = batch
-            .actions
-            .into_iter()
-            .map(|a| a.into())
-            .collect::<Vec<_>>()
-            .to_tensor(self.device);
-        let next_states = batch
-            .next_states
-            .into_iter()
-            .flatten()
-            .collect::<Vec<_>>()
-            .to_tensor(self.device);
-        let rewards = batch.rewards.to_tensor(self.device).unsqueeze_dim(1);
+        let buffer_size = memory.len();
+
+        // Tensor conversions, with a row for every next state so tensor shapes match in the Bellman Equation
+        let batch = batch.collate::<B, D, Int, [i32; 1]>(self.device);
 
         let policy_net = self.policy_net.take().unwrap();
-        let target_net = self.target_net.take().unwrap();
 
         // Compute the Q values of the chosen actions in each state
-        let q_values = policy_net.forward(states).gather(1, actions);
+        let q_values = policy_net
+            .forward(batch.states.clone())
+            .gather(1, batch.actions.clone());
 
         // Compute the maximum Q values obtainable from each next state
-        let expected_q_values = Tensor::zeros([batch_size, 1], self.device).mask_where(
-            non_terminal_mask,
-            target_net.forward(next_states).max_dim(1).detach(),
-        );
+        let next_q_values = self
+            .target_net
+            .net()
+            .forward(batch.next_states.clone())
+            .max_dim(1)
+            .detach();
 
-        let discounted_expected_return = rewards + (expected_q_values * self.gamma);
+        let discounted_expected_return = batch.targets(next_q_values, self.gamma);
 
         // Compute temporal difference errors
-        let tde: Tensor<B, 1> = (discounted_expected_return - q_values).squeeze(1);
+        let tde: Tensor<B, 1> = (discounted_expected_return.clone() - q_values.clone()).squeeze(1);
 
         // Update priorities of sampled experiences
-        let td_errors = tde.to_data().value;
+        let td_errors = tde.to_data().convert::<f32>().value;
         memory.update_priorities(&indices, &td_errors);
 
-        // Apply importance sampling weights from prioritized memory replay and compute mean squared weighted TD error
+        // Apply importance sampling weights from prioritized memory replay and compute the mean weighted loss
         let weights = weights.to_tensor(self.device);
-        let loss = (weights * tde.powf_scalar(2.0)).mean();
+        let loss = self
+            .loss
+            .forward(q_values, discounted_expected_return)
+            .squeeze(1);
+        let loss = (weights * loss).mean();
 
         // Perform backpropagation on policy net
-        let grads = GradientsParams::from_grads(loss.backward(), &policy_net);
-        self.policy_net = Some(optimizer.step(self.lr.into(), policy_net, grads));
+        self.optimize(policy_net, loss, buffer_size);
 
-        // Perform a periodic soft update on the parameters of the target network for stable convergence
-        self.target_net = if self.episodes_elapsed % self.target_update_interval == 0 {
-            Some(target_net.soft_update(self.policy_net.as_ref().unwrap(), self.tau))
-        } else {
-            Some(target_net)
+        // Periodically update the target network towards the policy network for stable convergence
+        self.target_net.step(self.policy_net.as_ref().unwrap());
+    }
+}
+
+impl<B, M, E, DEC, const D: usize> Agent<E> for DQNAgent<B, M, E, DEC, D>
+where
+    B: AutodiffBackend,
+    M: DQNModel<B, D>,
+    E: Environment,
+    DEC: Decay,
+    Vec<E::State>: ToTensor<B, D, Float>,
+    E::Action: From<i32> + Into<[i32; 1]>,
+{
+    /// Invoke the agent's policy along with the exploration strategy to choose an action from the given state
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action {
+        match self.exploration.choose(self.total_steps) {
+            Choice::Explore => env.random_action(),
+            Choice::Exploit => self.greedy(state.clone()),
+        }
+    }
+
+    /// Store the experience in replay memory and perform one learning step
+    fn learn(&mut self, _env: &E, experience: Exp<E>) {
+        match &mut self.memory {
+            Memory::Base(memory) => {
+                memory.push(experience);
+                self.learn_base();
+            }
+            Memory::Prioritized(memory) => {
+                memory.push(experience);
+                self.learn_prioritized();
+            }
+        }
+
+        self.total_steps += 1;
+    }
+
+    fn on_episode_end(&mut self) {
+        self.episodes_elapsed += 1;
+    }
+
+    fn metrics(&mut self) -> Vec<(&'static str, f64)> {
+        self.diagnostics
+            .as_mut()
+            .map_or_else(Vec::new, Diagnostics::take)
+    }
+
+    /// The discount factor `gamma`, the learning rate `lr` and the lowest exploration probability `epsilon_floor`
+    fn hyperparams(&self) -> Vec<Hyperparam> {
+        hyperparams(self.lr, self.gamma, &self.exploration)
+    }
+
+    fn set_hyperparam(&mut self, name: &str, value: f64) -> bool {
+        set_hyperparam(
+            &mut self.lr,
+            &mut self.gamma,
+            &mut self.exploration,
+            name,
+            value,
+        )
+    }
+
+    /// Act greedily with the averaged policy network if [`policy_average`](DQNAgentConfig::policy_average) is set
+    fn policy(&self, _env: &E, state: &E::State) -> E::Action {
+        match &self.average_net {
+            Some(average_net) => {
+                greedy::<B, M, E, D>(average_net.net(), state.clone(), self.device)
+            }
+            None => self.greedy(state.clone()),
+        }
+    }
+}
+
+/// A snapshot of a [`DQNAgent`]'s policy network and exploration, acting in a
+/// [`ParallelTrainer`](crate::train::ParallelTrainer) actor thread
+///
+/// Each actor counts its own steps from the agent's step count at the time of the snapshot to decay epsilon.
+pub struct DQNActor<B, M, E, DEC, const D: usize>
+where
+    B: AutodiffBackend,
+    E: Environment,
+{
+    policy_net: M,
+    device: &'static B::Device,
+    exploration: EpsilonGreedy<DEC>,
+    steps: u64,
+    env: PhantomData<fn() -> E>,
+}
+
+impl<B, M, E, DEC, const D: usize> Actor<E> for DQNActor<B, M, E, DEC, D>
+where
+    B: AutodiffBackend,
+    M: DQNModel<B, D>,
+    E: Environment,
+    DEC: Decay,
+    Vec<E::State>: ToTensor<B, D, Float>,
+    E::Action: From<i32>,
+{
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action {
+        let action = match self.exploration.choose(self.steps) {
+            Choice::Explore => env.random_action(),
+            Choice::Exploit => greedy::<B, M, E, D>(&self.policy_net, state.clone(), self.device),
         };
+        self.steps += 1;
+        action
     }
+}
+
+/// Actors get a copy of the policy network, and the learner trains on the shared replay memory
+impl<B, M, E, DEC, const D: usize> ParallelAgent<E> for DQNAgent<B, M, E, DEC, D>
+where
+    B: AutodiffBackend,
+    M: DQNModel<B, D>,
+    E: Environment,
+    DEC: Decay + Clone + Send,
+    Vec<E::State>: ToTensor<B, D, Float>,
+    E::Action: From<i32> + Into<[i32; 1]>,
+{
+    type Actor = DQNActor<B, M, E, DEC, D>;
+
+    fn actor(&self) -> Self::Actor {
+        DQNActor {
+            policy_net: self
+                .policy_net
+                .clone()
+                .expect("the policy network is only taken during a learning step"),
+            device: self.device,
+            exploration: self.exploration.clone(),
+            steps: self.total_steps,
+            env: PhantomData,
+        }
+    }
+
+    fn remember(&mut self, experience: Exp<E>) {
+        match &mut self.memory {
+            Memory::Base(memory) => memory.push(experience),
+            Memory::Prioritized(memory) => memory.push(experience),
+        }
+        self.total_steps += 1;
+    }
+
+    fn train_step(&mut self) {
+        match self.memory {
+            Memory::Base(_) => self.learn_base(),
+            Memory::Prioritized(_) => self.learn_prioritized(),
+        }
+    }
+}
+
+/// Learning step statistics of a [`DQNAgent`] accumulated over an episode
+#[derive(Debug, Clone, Default)]
+struct Diagnostics {
+    steps: u32,
+    loss: f64,
+    grad_norm: f64,
+    grad_norm_max: f64,
+    param_norm: f64,
+}
+
+impl Diagnostics {
+    fn record(&mut self, loss: f32, grad_norm: f32, param_norm: f32) {
+        self.steps += 1;
+        self.loss += loss as f64;
+        self.grad_norm += grad_norm as f64;
+        self.grad_norm_max = self.grad_norm_max.max(grad_norm as f64);
+        self.param_norm = param_norm as f64;
+    }
+
+    /// The metrics of the recorded learning steps, resetting the statistics
+    fn take(&mut self) -> Vec<(&'static str, f64)> {
+        let Self {
+            steps,
+            loss,
+            grad_norm,
+            grad_norm_max,
+            param_norm,
+        } = std::mem::take(self);
+        if steps == 0 {
+            return Vec::new();
+        }
+        vec![
+            ("loss", loss / steps as f64),
+            ("grad_norm", grad_norm / steps as f64),
+            ("grad_norm_max", grad_norm_max),
+            ("param_norm", param_norm),
+        ]
+    }
+}
+
+/// Choose the action with the highest Q value in `state` according to `net`
+fn greedy<B, M, E, const D: usize>(net: &M, state: E::State, device: &B::Device) -> E::Action
+where
+    B: AutodiffBackend,
+    M: DQNModel<B, D>,
+    E: Environment,
+    Vec<E::State>: ToTensor<B, D, Float>,
+    E::Action: From<i32>,
+{
+    let input = vec![state].to_tensor(device);
+    greedy_action(net.forward(input))
+}
+
+/// The action with the highest of the Q values of a batch of one state, breaking ties at random with
+/// [`max_by_random`] like the tabular agents
+pub(super) fn greedy_action<B: Backend, A: From<i32>>(q_values: Tensor<B, 2>) -> A {
+    let q_values = q_values.into_data().convert::<f32>().value;
+    let action = max_by_random(0..q_values.len(), |&a, &b| {
+        q_values[a].total_cmp(&q_values[b])
+    })
+    .expect("There is at least one action");
+    A::from(action as i32)
+}
+
+impl<B, M, E, DEC, const D: usize> Debug for DQNAgent<B, M, E, DEC, D>
+where
+    B: AutodiffBackend,
+    M: AutodiffModule<B> + Debug,
+    E: Environment,
+    DEC: Decay + Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DQNAgent")
+            .field("policy_net", &self.policy_net)
+            .field("target_net", self.target_net.net())
+            .field("target_update", &self.target_net.update())
+            .field(
+                "average_net",
+                &self.average_net.as_ref().map(|net| net.net()),
+            )
+            .field("memory", &self.memory)
+            .field("exploration", &self.exploration)
+            .field("gamma", &self.gamma)
+            .field("loss", &self.loss)
+            .field("lr", &self.lr)
+            .field("grad_clipping", &self.grad_clipping)
+            .field("total_steps", &self.total_steps)
+            .field("episodes_elapsed", &self.episodes_elapsed)
+            .finish_non_exhaustive()
+    }
+}
 
-    /// Deploy the `DQNAgent` into the environment for one episode
-    pub fn go(&mut self, env: &mut E) {
-        let mut optimizer = AdamWConfig::new()
-            .with_grad_clipping(Some(GradientClippingConfig::Value(100.0)))
-            .init();
-        let mut next_state = Some(env.reset());
+/// Counters and exploration state of a [`DQNAgent`], stored next to the network and optimizer records
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct DQNAgentState<DEC: Decay> {
+    exploration: EpsilonGreedy<DEC>,
+    total_steps: u64,
+    episodes_elapsed: usize,
+    #[serde(default)]
+    target_steps: u64,
+}
 
-        while let Some(state) = next_state {
-            let action = self.act(env, state.clone());
-            let (next, reward) = env.step(action.clone());
-            next_state = next;
+/// Checkpoints are directories holding burn records of the policy network, target network, averaged policy network
+/// if any, and optimizer, along with a JSON file of counters and exploration state
+///
+/// The replay memory is not saved, so it is refilled after loading.
+#[cfg(feature = "serde")]
+impl<B, M, E, DEC, const D: usize> Checkpoint for DQNAgent<B, M, E, DEC, D>
+where
+    B: AutodiffBackend,
+    M: AutodiffModule<B>,
+    E: Environment,
+    DEC: Decay + Clone + serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn save(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)?;
+        let recorder = NamedMpkFileRecorder::<FullPrecisionSettings>::new();
+
+        let policy_net = self
+            .policy_net
+            .clone()
+            .expect("the policy network is only taken during a learning step");
+        let average_net = self.average_net.as_ref().map(|net| net.net().clone());
+        let nets = [
+            ("policy", Some(policy_net)),
+            ("target", Some(self.target_net.net().clone())),
+            ("average", average_net),
+        ];
+        for (name, net) in nets {
+            let Some(net) = net else { continue };
+            net.save_file(path.join(name), &recorder)
+                .map_err(recorder_error)?;
+        }
+        Recorder::<B>::record(
+            &recorder,
+            self.optimizer.to_record(),
+            path.join("optimizer"),
+        )
+        .map_err(recorder_error)?;
+
+        let state = DQNAgentState {
+            exploration: self.exploration.clone(),
+            total_steps: self.total_steps,
+            episodes_elapsed: self.episodes_elapsed,
+            target_steps: self.target_net.steps(),
+        };
+        checkpoint::write_json(&path.join("state.json"), &state)
+    }
 
-            let exp = Exp {
-                state,
-                action,
-                reward,
-                next_state: next_state.clone(),
+    fn load(&mut self, path: &Path) -> io::Result<()> {
+        let recorder = NamedMpkFileRecorder::<FullPrecisionSettings>::new();
+
+        let policy_net = self
+            .policy_net
+            .take()
+            .expect("the policy network is only taken during a learning step")
+            .load_file(path.join("policy"), &recorder, self.device);
+        self.policy_net = Some(policy_net.map_err(recorder_error)?);
+        let target_net = self.target_net.net().clone();
+        *self.target_net.net_mut() = target_net
+            .load_file(path.join("target"), &recorder, self.device)
+            .map_err(recorder_error)?;
+        if let Some(average_net) = &mut self.average_net {
+            // Checkpoints saved without averaging start the average from the loaded policy network
+            let net = if path.join("average.mpk").exists() {
+                let net = average_net.net().clone();
+                net.load_file(path.join("average"), &recorder, self.device)
+                    .map_err(recorder_error)?
+            } else {
+                self.policy_net.clone().unwrap()
             };
+            *average_net.net_mut() = net;
+        }
+        let record = Recorder::<B>::load(&recorder, path.join("optimizer"), self.device)
+            .map_err(recorder_error)?;
+        self.optimizer = adamw(self.grad_clipping.clone()).load_record(record);
+
+        let state: DQNAgentState<DEC> = checkpoint::read_json(&path.join("state.json"))?;
+        self.exploration = state.exploration;
+        self.total_steps = state.total_steps;
+        self.episodes_elapsed = state.episodes_elapsed;
+        self.target_net.set_steps(state.target_steps);
+        Ok(())
+    }
+}
 
-            match &mut self.memory {
-                Memory::Base(memory) => {
-                    memory.push(exp);
-                    self.learn(&mut optimizer);
-                }
-                Memory::Prioritized(memory) => {
-                    memory.push(exp);
-                    self.learn_prioritized(&mut optimizer);
-                }
-            }
+#[cfg(feature = "serde")]
+fn recorder_error(e: burn::record::RecorderError) -> io::Error {
+    io::Error::other(format!("{e:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use burn::backend::{ndarray::NdArrayDevice, Autodiff, NdArray};
+    use rand::Rng;
+
+    use super::*;
+    use crate::{
+        export::PolicyRunner,
+        nn::builders::Mlp,
+        seed::{self, Seeds, Stream},
+    };
+
+    type B = Autodiff<NdArray>;
 
-            self.total_steps += 1;
+    /// A single step episode in a single state, where arm `1` pays `1` and arm `0` nothing
+    struct Bandit;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Arm(i32);
+
+    impl From<i32> for Arm {
+        fn from(value: i32) -> Self {
+            Self(value)
         }
+    }
 
-        self.episodes_elapsed += 1;
+    impl From<Arm> for [i32; 1] {
+        fn from(arm: Arm) -> Self {
+            [arm.0]
+        }
+    }
+
+    impl Environment for Bandit {
+        type State = [f32; 1];
+        type Action = Arm;
+
+        fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
+            (None, action.0 as f32)
+        }
+
+        fn reset(&mut self) -> Self::State {
+            [1.0]
+        }
+
+        fn random_action(&self) -> Self::Action {
+            Arm(seed::rng(Stream::Env).gen_range(0..2))
+        }
+    }
+
+    fn agent(policy_average: Option<f32>) -> DQNAgent<B, Mlp<B>, Bandit, decay::Exponential, 2> {
+        DQNAgent::new(
+            Mlp::new(&[1, 8, 2], &NdArrayDevice::Cpu),
+            DQNAgentConfig {
+                memory_batch_size: 4,
+                lr: 1e-2,
+                policy_average,
+                ..Default::default()
+            },
+            &NdArrayDevice::Cpu,
+        )
+        .unwrap()
+    }
+
+    /// The Q values of both arms according to `net`
+    fn scores(net: &Mlp<B>) -> Vec<f32> {
+        let input = Tensor::from_floats([[1.0]], &NdArrayDevice::Cpu);
+        net.valid()
+            .forward(input)
+            .into_data()
+            .convert::<f32>()
+            .value
+    }
+
+    #[test]
+    fn policy_average() {
+        Seeds::new(0).apply();
+        B::seed(0);
+        let mut env = Bandit;
+        let mut agent = agent(Some(0.01));
+        for _ in 0..50 {
+            agent.go(&mut env);
+        }
+
+        let average = scores(agent.average_net.as_ref().unwrap().net());
+        assert_ne!(
+            average,
+            scores(agent.policy_net.as_ref().unwrap()),
+            "The average lags behind the policy network"
+        );
+        let best = Arm((average[1] > average[0]) as i32);
+        assert_eq!(agent.policy(&env, &[1.0]), best);
+
+        let path = std::env::temp_dir().join("rl_dqn_policy_average");
+        agent.export(&path).unwrap();
+        let net = Mlp::new(&[1, 8, 2], &NdArrayDevice::Cpu);
+        let runner = PolicyRunner::<NdArray, _, 2>::load(net, &path, NdArrayDevice::Cpu).unwrap();
+        std::fs::remove_file(path.with_extension("mpk")).unwrap();
+        assert_eq!(runner.scores([1.0f32]), average, "Exports the average");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn load_without_average() {
+        Seeds::new(0).apply();
+        B::seed(0);
+        let mut env = Bandit;
+        let mut trained = agent(None);
+        for _ in 0..10 {
+            trained.go(&mut env);
+        }
+        let dir = std::env::temp_dir().join("rl_dqn_load_without_average");
+        trained.save(&dir).unwrap();
+        assert!(!dir.join("average.mpk").exists());
+
+        let mut agent = agent(Some(0.01));
+        agent.load(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            scores(agent.average_net.as_ref().unwrap().net()),
+            scores(trained.policy_net.as_ref().unwrap()),
+            "The average starts from the loaded policy network"
+        );
+    }
+
+    #[test]
+    fn greedy_ties() {
+        Seeds::new(0).apply();
+        let device = NdArrayDevice::Cpu;
+        let mut counts = [0; 3];
+        for _ in 0..300 {
+            let q_values = Tensor::<NdArray, 2>::from_floats([[1.0, 2.0, 2.0]], &device);
+            counts[greedy_action::<_, i32>(q_values) as usize] += 1;
+        }
+        assert_eq!(counts[0], 0);
+        assert!(
+            counts[1] > 100 && counts[2] > 100,
+            "Ties are broken at random, {counts:?}"
+        );
     }
 }
@@ -0,0 +1,634 @@
+use std::fmt::{self, Debug};
+
+use burn::{
+    grad_clipping::GradientClippingConfig,
+    nn::gru::{Gru, GruConfig},
+    optim::{GradientsParams, Optimizer},
+    prelude::*,
+    tensor::{activation, backend::AutodiffBackend, ElementConversion},
+};
+
+use super::dqn::{adamw, AdamWOptimizer};
+use crate::{
+    distributions::{DiagGaussian, PolicyStats},
+    env::{ContinuousAction, Environment},
+    error::{check_interval, Result, RlError},
+    memory::{Exp, SequenceBatch, SequenceReplayMemory},
+    nn::builders::{Activation, Mlp, MlpConfig},
+    traits::{Agent, FromTensor, Hyperparam, ToTensor},
+};
+
+/// The bounds of the log standard deviations of the actor's action distributions
+const LOG_STD_RANGE: (f32, f32) = (-5.0, 2.0);
+
+/// Configuration for the [`DreamerAgent`]
+#[derive(Debug, Clone)]
+pub struct DreamerAgentConfig {
+    /// The capacity of the replay memory, in experiences
+    ///
+    /// **Default:** `100000`
+    pub memory_capacity: usize,
+    /// The number of sequences in the batches sampled from the replay memory
+    ///
+    /// **Default:** `16`
+    pub memory_batch_size: usize,
+    /// The number of experiences in each sampled sequence, over which the dynamics are rolled out
+    ///
+    /// **Default:** `16`
+    pub sequence_length: usize,
+    /// The number of features of the latent states
+    ///
+    /// **Default:** `32`
+    pub latent_size: usize,
+    /// The sizes of the hidden layers of the encoder, decoder, heads, actor and critic
+    ///
+    /// **Default:** `[128, 128]`
+    pub hidden: Vec<usize>,
+    /// The activation function after each hidden layer
+    ///
+    /// **Default:** [`Activation::Relu`]
+    pub activation: Activation,
+    /// The number of imagined steps the actor and critic learn from
+    ///
+    /// **Default:** `15`
+    pub horizon: usize,
+    /// The discount factor
+    ///
+    /// **Default:** `0.99`
+    pub gamma: f32,
+    /// The weight of longer bootstrapped returns in the λ-returns of imagined trajectories, `0` for one-step targets
+    /// and `1` for the returns of the whole horizon
+    ///
+    /// **Default:** `0.95`
+    pub lambda: f32,
+    /// The weight of the entropy of the actor's action distributions in its objective
+    ///
+    /// **Default:** `1e-3`
+    pub entropy_coef: f32,
+    /// The learning rate of the world model
+    ///
+    /// **Default:** `1e-3`
+    pub model_lr: f32,
+    /// The learning rate of the actor
+    ///
+    /// **Default:** `1e-4`
+    pub actor_lr: f32,
+    /// The learning rate of the critic
+    ///
+    /// **Default:** `3e-4`
+    pub critic_lr: f32,
+    /// The number of steps with random actions before learning starts, to fill the replay memory
+    ///
+    /// **Default:** `1000`
+    pub learning_starts: u64,
+    /// The number of steps between learning steps
+    ///
+    /// **Default:** `1`
+    pub train_every: u64,
+    /// Clip the gradients of each optimizer step to a maximum value or L2 norm
+    ///
+    /// **Default:** clipping to an L2 norm of `100`
+    pub grad_clipping: Option<GradientClippingConfig>,
+    /// The step limit of episodes run with [`go`](Agent::go)
+    ///
+    /// **Default:** `None`
+    pub max_episode_steps: Option<u64>,
+}
+
+impl Default for DreamerAgentConfig {
+    fn default() -> Self {
+        Self {
+            memory_capacity: 100000,
+            memory_batch_size: 16,
+            sequence_length: 16,
+            latent_size: 32,
+            hidden: vec![128, 128],
+            activation: Activation::Relu,
+            horizon: 15,
+            gamma: 0.99,
+            lambda: 0.95,
+            entropy_coef: 1e-3,
+            model_lr: 1e-3,
+            actor_lr: 1e-4,
+            critic_lr: 3e-4,
+            learning_starts: 1000,
+            train_every: 1,
+            grad_clipping: Some(GradientClippingConfig::Norm(100.0)),
+            max_episode_steps: None,
+        }
+    }
+}
+
+/// A learned model of an environment in a latent space: an encoder of observations, recurrent dynamics driven by the
+/// actions, and heads predicting the observations, rewards and terminations from latent states
+#[derive(Module, Debug)]
+struct WorldModel<B: Backend> {
+    encoder: Mlp<B>,
+    dynamics: Gru<B>,
+    decoder: Mlp<B>,
+    reward: Mlp<B>,
+    continuation: Mlp<B>,
+}
+
+impl<B: Backend> WorldModel<B> {
+    /// The latent states of `states` of shape `[batch, features]`, in `(-1, 1)` like the states of the dynamics
+    fn encode(&self, states: Tensor<B, 2>) -> Tensor<B, 2> {
+        activation::tanh(self.encoder.forward(states))
+    }
+
+    /// The latent states after taking `actions` of shape `[batch, A]` in `latents`
+    fn step(&self, latents: Tensor<B, 2>, actions: Tensor<B, 2>) -> Tensor<B, 2> {
+        let [batch, size] = latents.dims();
+        self.dynamics
+            .forward(actions.unsqueeze_dim(1), Some(latents))
+            .reshape([batch, size])
+    }
+
+    /// The latent states after each of the `actions` of shape `[batch, length, A]`, starting from `latents`, with
+    /// shape `[batch, length, latent]`
+    fn rollout(&self, latents: Tensor<B, 2>, actions: Tensor<B, 3>) -> Tensor<B, 3> {
+        self.dynamics.forward(actions, Some(latents))
+    }
+
+    /// The predicted rewards of reaching `latents` and the logits of the episode continuing from them, both with
+    /// shape `[batch, 1]`
+    fn heads(&self, latents: Tensor<B, 2>) -> (Tensor<B, 2>, Tensor<B, 2>) {
+        (
+            self.reward.forward(latents.clone()),
+            self.continuation.forward(latents),
+        )
+    }
+}
+
+/// The summed losses of the learning steps since the last [`metrics`](Agent::metrics), and their number
+#[derive(Debug, Clone, Copy, Default)]
+struct Losses {
+    model: f64,
+    actor: f64,
+    critic: f64,
+    imagined_return: f64,
+    steps: u64,
+}
+
+/// A simplified Dreamer agent, which learns a model of the environment in a latent space and trains an actor-critic
+/// on trajectories imagined by the model
+///
+/// The world model encodes observations into latent states and rolls them forward with a GRU driven by the actions,
+/// trained on sequences from a [`SequenceReplayMemory`] to predict the next observations, the rewards and the ends of
+/// episodes over the whole sequence. The actor and critic never learn from real experience: they start from the
+/// encoded states of the batch and imagine [`horizon`](DreamerAgentConfig::horizon) steps ahead with the model. The
+/// critic regresses the λ-returns of the imagined trajectories, and the actor maximizes them by backpropagating
+/// through the learned dynamics, see Hafner et al., 2020, *Dream to Control: Learning Behaviors by Latent
+/// Imagination*.
+///
+/// Unlike Dreamer, latent states are deterministic and acting encodes the current observation alone, so the
+/// environment should be fully observable. Actions are sampled from a tanh-squashed Gaussian while learning, and the
+/// [`policy`](Agent::policy) takes its mode.
+///
+/// The mean losses of the world model, actor and critic and the mean imagined return since the last call are
+/// reported as the agent's [`metrics`](Agent::metrics), named `model_loss`, `actor_loss`, `critic_loss` and
+/// `imagined_return`, together with the [`PolicyStats`] of the actor's updates in the encoded states of the batch.
+///
+/// ```ignore
+/// let mut agent = DreamerAgent::<B, Pendulum, 1>::new(3, DreamerAgentConfig::default(), &DEVICE)?;
+/// ```
+///
+/// ### Generics
+/// - `B` - A burn backend
+/// - `E` - The [`Environment`] in which the agent will learn, with continuous actions and states converted to
+///   tensors of shape `[batch, features]`
+/// - `A` - The number of values of an action
+pub struct DreamerAgent<B, E, const A: usize>
+where
+    B: AutodiffBackend,
+    E: Environment<Action = ContinuousAction<A>>,
+{
+    world_model: Option<WorldModel<B>>,
+    actor: Option<Mlp<B>>,
+    critic: Option<Mlp<B>>,
+    model_optimizer: AdamWOptimizer<WorldModel<B>, B>,
+    actor_optimizer: AdamWOptimizer<Mlp<B>, B>,
+    critic_optimizer: AdamWOptimizer<Mlp<B>, B>,
+    device: &'static B::Device,
+    memory: SequenceReplayMemory<E>,
+    latent_size: usize,
+    horizon: usize,
+    gamma: f32,
+    lambda: f32,
+    entropy_coef: f32,
+    model_lr: f32,
+    actor_lr: f32,
+    critic_lr: f32,
+    learning_starts: u64,
+    train_every: u64,
+    max_episode_steps: Option<u64>,
+    total_steps: u64,
+    losses: Losses,
+    policy_stats: PolicyStats,
+}
+
+impl<B, E, const A: usize> DreamerAgent<B, E, A>
+where
+    B: AutodiffBackend,
+    E: Environment<Action = ContinuousAction<A>>,
+    Vec<E::State>: ToTensor<B, 2, Float>,
+{
+    /// Initialize an untrained agent
+    ///
+    /// ### Arguments
+    /// - `state_size` - The number of features of a state
+    /// - `config` - A [`DreamerAgentConfig`] containing the hyperparameters of the agent
+    /// - `device` - A static reference to the device of the networks
+    ///
+    /// ### Returns
+    /// - [`RlError::OutOfRange`] if `gamma` or `lambda` is not in the interval `[0, 1]`, or a learning rate or
+    ///   `entropy_coef` is negative
+    /// - [`RlError::InvalidHyperparameters`] if the latent size, horizon, sequence length or `train_every` is zero
+    pub fn new(
+        state_size: usize,
+        config: DreamerAgentConfig,
+        device: &'static B::Device,
+    ) -> Result<Self> {
+        check_interval("gamma", config.gamma, 0.0, 1.0)?;
+        check_interval("lambda", config.lambda, 0.0, 1.0)?;
+        check_interval("entropy_coef", config.entropy_coef, 0.0, f32::INFINITY)?;
+        check_interval("model_lr", config.model_lr, 0.0, f32::INFINITY)?;
+        check_interval("actor_lr", config.actor_lr, 0.0, f32::INFINITY)?;
+        check_interval("critic_lr", config.critic_lr, 0.0, f32::INFINITY)?;
+        if config.latent_size == 0
+            || config.horizon == 0
+            || config.sequence_length == 0
+            || config.train_every == 0
+        {
+            return Err(RlError::InvalidHyperparameters(String::from(
+                "Dreamer needs a positive latent size, horizon, sequence length and `train_every`",
+            )));
+        }
+
+        let mlp = |inputs: usize, outputs: usize| {
+            let sizes = [&[inputs][..], &config.hidden, &[outputs]].concat();
+            MlpConfig::new(&sizes)
+                .with_activation(config.activation)
+                .init(device)
+        };
+        let latent = config.latent_size;
+        let world_model = WorldModel {
+            encoder: mlp(state_size, latent),
+            dynamics: GruConfig::new(A, latent, true).init(device),
+            decoder: mlp(latent, state_size),
+            reward: mlp(latent, 1),
+            continuation: mlp(latent, 1),
+        };
+
+        Ok(Self {
+            world_model: Some(world_model),
+            actor: Some(mlp(latent, 2 * A)),
+            critic: Some(mlp(latent, 1)),
+            model_optimizer: adamw(config.grad_clipping.clone()),
+            actor_optimizer: adamw(config.grad_clipping.clone()),
+            critic_optimizer: adamw(config.grad_clipping),
+            device,
+            memory: SequenceReplayMemory::new(
+                config.memory_capacity,
+                config.memory_batch_size,
+                config.sequence_length,
+            ),
+            latent_size: latent,
+            horizon: config.horizon,
+            gamma: config.gamma,
+            lambda: config.lambda,
+            entropy_coef: config.entropy_coef,
+            model_lr: config.model_lr,
+            actor_lr: config.actor_lr,
+            critic_lr: config.critic_lr,
+            learning_starts: config.learning_starts,
+            train_every: config.train_every,
+            max_episode_steps: config.max_episode_steps,
+            total_steps: 0,
+            losses: Losses::default(),
+            policy_stats: PolicyStats::new(),
+        })
+    }
+
+    /// The actor's distributions of the actions in `latents`, before squashing
+    fn distribution(actor: &Mlp<B>, latents: Tensor<B, 2>) -> DiagGaussian<B> {
+        let output = actor.forward(latents);
+        let [rows, _] = output.dims();
+        let mean = output.clone().slice([0..rows, 0..A]);
+        let log_std = output
+            .slice([0..rows, A..2 * A])
+            .clamp(LOG_STD_RANGE.0, LOG_STD_RANGE.1);
+        DiagGaussian::new(mean, log_std)
+    }
+
+    /// The actor's distribution of the actions in `state`
+    fn state_distribution(&self, state: &E::State) -> DiagGaussian<B> {
+        let world_model = self.world_model.as_ref().unwrap();
+        let latent = world_model
+            .encode(vec![state.clone()].to_tensor(self.device))
+            .detach();
+        Self::distribution(self.actor.as_ref().unwrap(), latent)
+    }
+
+    /// Train the world model on a batch of sequences, then the actor and critic on trajectories imagined from them
+    fn train(&mut self) {
+        let _span = tracing::debug_span!("batch", step = self.total_steps).entered();
+
+        let Some(sequences) = self.memory.sample() else {
+            return;
+        };
+        let length = self.memory.sequence_length;
+        let batch = SequenceBatch::<B, Float>::new::<E, [f32; A]>(&sequences, length, self.device);
+        let [batch_size, _, features] = batch.states.dims();
+        let rows = batch_size * length;
+
+        // World model: roll the dynamics out from the first state of each sequence and predict what follows
+        let world_model = self.world_model.take().unwrap();
+        let first = batch
+            .states
+            .clone()
+            .slice([0..batch_size, 0..1, 0..features])
+            .reshape([batch_size, features]);
+        let start = world_model.encode(first.clone());
+        let latents = world_model
+            .rollout(start.clone(), batch.actions.clone())
+            .reshape([rows, self.latent_size]);
+        let (rewards, continues) = world_model.heads(latents.clone());
+
+        let weights = batch.weights().reshape([rows, 1]);
+        let non_terminal = batch.non_terminal.clone().float().reshape([rows, 1]);
+        let next_states = batch.next_states.clone().reshape([rows, features]);
+        let reconstruction = (world_model.decoder.forward(latents) - next_states)
+            .powf_scalar(2.0)
+            .sum_dim(1)
+            * non_terminal.clone();
+        let first_reconstruction = (world_model.decoder.forward(start) - first)
+            .powf_scalar(2.0)
+            .sum_dim(1)
+            .mean();
+        let reward_loss = (rewards - batch.rewards.clone().reshape([rows, 1])).powf_scalar(2.0);
+        let continue_loss = binary_cross_entropy(continues, non_terminal);
+        let model_loss = ((reconstruction + reward_loss + continue_loss) * weights.clone()).sum()
+            / weights.sum().clamp_min(1.0)
+            + first_reconstruction;
+        self.losses.model += model_loss.clone().into_scalar().elem::<f64>();
+
+        let grads = GradientsParams::from_grads(model_loss.backward(), &world_model);
+        let world_model = self
+            .model_optimizer
+            .step(self.model_lr.into(), world_model, grads);
+
+        // Imagination from every encoded state of the batch, with gradients through the dynamics to the actions
+        let actor = self.actor.take().unwrap();
+        let critic = self.critic.take().unwrap();
+        let mut latent = world_model
+            .encode(batch.states.reshape([rows, features]))
+            .detach();
+        let mut latents = vec![latent.clone()];
+        let mut rewards = Vec::with_capacity(self.horizon);
+        let mut discounts = Vec::with_capacity(self.horizon);
+        let mut entropies = Vec::with_capacity(self.horizon);
+        let old_distribution = Self::distribution(&actor, latent.clone());
+        for _ in 0..self.horizon {
+            let distribution = Self::distribution(&actor, latent.clone());
+            let (actions, _) = distribution.sample_squashed();
+            entropies.push(distribution.entropy());
+            latent = world_model.step(latent, actions);
+            let (reward, continues) = world_model.heads(latent.clone());
+            rewards.push(reward);
+            discounts.push(activation::sigmoid(continues) * self.gamma);
+            latents.push(latent.clone());
+        }
+        let values = latents
+            .iter()
+            .map(|latent| critic.forward(latent.clone()))
+            .collect::<Vec<_>>();
+
+        // λ-returns, computed backwards from the value of the last imagined state
+        let mut returns = Vec::with_capacity(self.horizon);
+        let mut next = values[self.horizon].clone();
+        for t in (0..self.horizon).rev() {
+            next = rewards[t].clone()
+                + discounts[t].clone()
+                    * (values[t + 1].clone() * (1.0 - self.lambda) + next * self.lambda);
+            returns.push(next.clone());
+        }
+        returns.reverse();
+
+        // Later steps count less, by the probability of reaching them and the discount
+        let mut weight = Tensor::<B, 2>::ones([rows, 1], self.device);
+        let mut objectives = Vec::with_capacity(self.horizon);
+        let mut weights = Vec::with_capacity(self.horizon);
+        for t in 0..self.horizon {
+            objectives.push(
+                (returns[t].clone() + entropies[t].clone() * self.entropy_coef) * weight.clone(),
+            );
+            weights.push(weight.clone());
+            weight = weight * discounts[t].clone().detach();
+        }
+        let actor_loss = Tensor::cat(objectives, 0).mean().neg();
+        self.losses.actor += actor_loss.clone().into_scalar().elem::<f64>();
+        self.losses.imagined_return += returns[0].clone().mean().into_scalar().elem::<f64>();
+
+        let grads = GradientsParams::from_grads(actor_loss.backward(), &actor);
+        let actor = self
+            .actor_optimizer
+            .step(self.actor_lr.into(), actor, grads);
+        let new_distribution = Self::distribution(&actor, latents[0].clone());
+        self.policy_stats
+            .record_gaussian(&old_distribution, &new_distribution);
+        self.actor = Some(actor);
+
+        // The critic regresses the returns from the imagined states, without gradients to the imagination
+        let inputs = Tensor::cat(
+            latents[..self.horizon]
+                .iter()
+                .map(|latent| latent.clone().detach())
+                .collect(),
+            0,
+        );
+        let targets = Tensor::cat(returns, 0).detach();
+        let critic_loss =
+            ((critic.forward(inputs) - targets).powf_scalar(2.0) * Tensor::cat(weights, 0)).mean()
+                * 0.5;
+        self.losses.critic += critic_loss.clone().into_scalar().elem::<f64>();
+        self.losses.steps += 1;
+
+        let grads = GradientsParams::from_grads(critic_loss.backward(), &critic);
+        self.critic = Some(
+            self.critic_optimizer
+                .step(self.critic_lr.into(), critic, grads),
+        );
+        self.world_model = Some(world_model);
+    }
+}
+
+/// The binary cross entropy of the probabilities given by `logits` with the `targets` in `[0, 1]`, elementwise
+///
+/// Computed as `softplus(x) - y * x`, with a softplus that doesn't overflow for large logits.
+fn binary_cross_entropy<B: Backend>(logits: Tensor<B, 2>, targets: Tensor<B, 2>) -> Tensor<B, 2> {
+    let softplus =
+        logits.clone().clamp_min(0.0) + logits.clone().abs().neg().exp().add_scalar(1.0).log();
+    softplus - targets * logits
+}
+
+impl<B, E, const A: usize> Agent<E> for DreamerAgent<B, E, A>
+where
+    B: AutodiffBackend,
+    E: Environment<Action = ContinuousAction<A>>,
+    Vec<E::State>: ToTensor<B, 2, Float>,
+{
+    /// Take random actions until learning starts, then sample from the actor's distribution
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action {
+        if self.total_steps < self.learning_starts {
+            return env.random_action();
+        }
+        let (actions, _) = self.state_distribution(state).sample_squashed();
+        first(Vec::<E::Action>::from_tensor(actions))
+    }
+
+    /// Store the experience in replay memory and learn every `train_every` steps once learning starts
+    fn learn(&mut self, _env: &E, experience: Exp<E>) {
+        self.memory.push(experience);
+        self.total_steps += 1;
+        if self.total_steps >= self.learning_starts && self.total_steps % self.train_every == 0 {
+            self.train();
+        }
+    }
+
+    /// End the episode in the replay memory, which also covers truncated episodes
+    fn on_episode_end(&mut self) {
+        self.memory.end_episode();
+    }
+
+    fn metrics(&mut self) -> Vec<(&'static str, f64)> {
+        match std::mem::take(&mut self.losses) {
+            Losses { steps: 0, .. } => Vec::new(),
+            losses => {
+                let steps = losses.steps as f64;
+                let mut metrics = vec![
+                    ("model_loss", losses.model / steps),
+                    ("actor_loss", losses.actor / steps),
+                    ("critic_loss", losses.critic / steps),
+                    ("imagined_return", losses.imagined_return / steps),
+                ];
+                metrics.extend(self.policy_stats.take());
+                metrics
+            }
+        }
+    }
+
+    /// The discount factor `gamma`
+    fn hyperparams(&self) -> Vec<Hyperparam> {
+        vec![Hyperparam::gamma(self.gamma)]
+    }
+
+    fn set_hyperparam(&mut self, name: &str, value: f64) -> bool {
+        match name {
+            "gamma" if (0.0..=1.0).contains(&value) => self.gamma = value as f32,
+            _ => return false,
+        }
+        true
+    }
+
+    /// The mode of the actor's distribution
+    fn policy(&self, _env: &E, state: &E::State) -> E::Action {
+        first(Vec::<E::Action>::from_tensor(
+            self.state_distribution(state).mode_squashed(),
+        ))
+    }
+
+    fn max_episode_steps(&self) -> Option<u64> {
+        self.max_episode_steps
+    }
+}
+
+/// The action of a batch of one
+fn first<A>(actions: Vec<A>) -> A {
+    actions
+        .into_iter()
+        .next()
+        .expect("There is one action per state")
+}
+
+impl<B, E, const A: usize> Debug for DreamerAgent<B, E, A>
+where
+    B: AutodiffBackend,
+    E: Environment<Action = ContinuousAction<A>>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DreamerAgent")
+            .field("world_model", &self.world_model)
+            .field("actor", &self.actor)
+            .field("critic", &self.critic)
+            .field("memory", &self.memory)
+            .field("horizon", &self.horizon)
+            .field("gamma", &self.gamma)
+            .field("lambda", &self.lambda)
+            .field("entropy_coef", &self.entropy_coef)
+            .field("total_steps", &self.total_steps)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use burn::backend::{ndarray::NdArrayDevice, Autodiff, NdArray};
+
+    use super::*;
+    use crate::{seed::Seeds, testing::Point};
+
+    type B = Autodiff<NdArray>;
+
+    #[test]
+    fn dreamer_learns_world_model() {
+        Seeds::new(0).apply();
+        B::seed(0);
+        let mut env = Point::random();
+        let mut agent = DreamerAgent::<B, Point, 1>::new(
+            1,
+            DreamerAgentConfig {
+                memory_batch_size: 8,
+                sequence_length: 8,
+                latent_size: 8,
+                hidden: vec![32],
+                horizon: 5,
+                model_lr: 3e-3,
+                learning_starts: 50,
+                ..Default::default()
+            },
+            &NdArrayDevice::Cpu,
+        )
+        .unwrap();
+
+        let mut model_losses = Vec::new();
+        for _ in 0..30 {
+            agent.go(&mut env);
+            let metrics = agent.metrics();
+            if let Some(&(_, loss)) = metrics.iter().find(|(name, _)| *name == "model_loss") {
+                model_losses.push(loss);
+                for name in ["policy_entropy", "policy_kl", "policy_kl_max"] {
+                    assert!(
+                        metrics.iter().any(|(metric, _)| *metric == name),
+                        "{name} in {metrics:?}"
+                    );
+                }
+            }
+        }
+        let (first, last) = (model_losses[0], *model_losses.last().unwrap());
+        assert!(last < first, "model loss {first} -> {last}");
+
+        let action = agent.policy(&env, &[1.0]);
+        assert!((-1.0..=1.0).contains(&action.0[0]));
+
+        assert!(DreamerAgent::<B, Point, 1>::new(
+            1,
+            DreamerAgentConfig {
+                horizon: 0,
+                ..Default::default()
+            },
+            &NdArrayDevice::Cpu
+        )
+        .is_err());
+    }
+}
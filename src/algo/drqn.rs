@@ -0,0 +1,341 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Debug},
+};
+
+use burn::{
+    grad_clipping::GradientClippingConfig,
+    module::AutodiffModule,
+    optim::{GradientsParams, Optimizer},
+    prelude::*,
+    tensor::backend::AutodiffBackend,
+};
+
+use super::dqn::{adamw, greedy_action, hyperparams, set_hyperparam, AdamWOptimizer};
+use crate::{
+    decay::{self, Decay},
+    env::Environment,
+    error::{check_interval, Result, RlError},
+    exploration::{Choice, EpsilonGreedy},
+    losses::ValueLoss,
+    memory::{Exp, SequenceBatch, SequenceReplayMemory},
+    nn::{recurrent::Hidden, TargetNetwork, TargetUpdate},
+    traits::{Agent, Hyperparam, ToTensor},
+};
+
+/// A recurrent burn module used with a [`DRQNAgent`]
+///
+/// ### Generics
+/// - `B` - A burn backend
+pub trait RecurrentQModel<B: AutodiffBackend>: AutodiffModule<B> {
+    /// Forward pass through a batch of sequences of shape `[batch, length, features]`, starting from `hidden` or
+    /// from zeros if it is `None`
+    ///
+    /// **Returns** the Q values of every step, with shape `[batch, length, actions]`, and the state after the last
+    /// step
+    fn forward(&self, input: Tensor<B, 3>, hidden: Option<Hidden<B>>) -> (Tensor<B, 3>, Hidden<B>);
+}
+
+/// Configuration for the [`DRQNAgent`]
+#[derive(Debug, Clone)]
+pub struct DRQNAgentConfig<D> {
+    /// The capacity of the replay memory, in experiences
+    ///
+    /// **Default:** `16384`
+    pub memory_capacity: usize,
+    /// The number of sequences in the batches sampled from the replay memory
+    ///
+    /// **Default:** `32`
+    pub memory_batch_size: usize,
+    /// The number of experiences in each sampled sequence, including the burn-in
+    ///
+    /// **Default:** `16`
+    pub sequence_length: usize,
+    /// The number of steps at the start of each sequence that only warm up the hidden state and are left out of the
+    /// loss, as in R2D2
+    ///
+    /// **Default:** `4`
+    pub burn_in: usize,
+    /// The epsilon decay strategy
+    ///
+    /// **Default:** [`Exponential`](decay::Exponential) decay with decay rate `1e-3`, start value `1.0`, and end value `0.05`
+    pub epsilon_decay_strategy: D,
+    /// The discount factor
+    ///
+    /// **Default:** `0.99`
+    pub gamma: f32,
+    /// How the target network follows the policy network, counted in learning steps
+    ///
+    /// **Default:** [soft updates](TargetUpdate::Soft) with `tau` `5e-3` after every learning step
+    pub target_update: TargetUpdate,
+    /// The loss between the Q values of the chosen actions and their bootstrapped targets
+    ///
+    /// **Default:** [`ValueLoss::Mse`]
+    pub loss: ValueLoss,
+    /// The learning rate for the optimizer
+    ///
+    /// **Default:** `1e-3`
+    pub lr: f32,
+    /// Clip the gradient of each parameter to a maximum value or L2 norm in every optimizer step
+    ///
+    /// **Default:** clipping to values in `[-100, 100]`
+    pub grad_clipping: Option<GradientClippingConfig>,
+}
+
+impl Default for DRQNAgentConfig<decay::Exponential> {
+    fn default() -> Self {
+        Self {
+            memory_capacity: 16384,
+            memory_batch_size: 32,
+            sequence_length: 16,
+            burn_in: 4,
+            epsilon_decay_strategy: decay::Exponential::new(1e-3, 1.0, 0.05).unwrap(),
+            gamma: 0.99,
+            target_update: TargetUpdate::Soft {
+                tau: 5e-3,
+                interval: 1,
+            },
+            loss: ValueLoss::default(),
+            lr: 1e-3,
+            grad_clipping: Some(GradientClippingConfig::Value(100.0)),
+        }
+    }
+}
+
+/// A Deep Recurrent Q Network agent for partially observable environments
+///
+/// The agent carries the hidden state of its [`RecurrentQModel`] from step to step and resets it at the end of each
+/// episode. It learns from sequences sampled from a [`SequenceReplayMemory`], which start from a zero state and warm
+/// it up over the [`burn_in`](DRQNAgentConfig::burn_in) steps without gradients before the loss is computed, as in
+/// R2D2. The greedy [`policy`](Agent::policy) keeps a separate hidden state, reset by
+/// [`reset_policy`](Agent::reset_policy) before each evaluation episode.
+///
+/// ### Generics
+/// - `B` - A burn backend
+/// - `M` - The [`RecurrentQModel`] used for the policy and target networks
+/// - `E` - The [`Environment`] in which the agent will learn, with a discrete action space and states converted to
+///   tensors of shape `[batch, features]`
+/// - `DEC` - The decay strategy for epsilon-greedy exploration
+pub struct DRQNAgent<B, M, E, DEC>
+where
+    B: AutodiffBackend,
+    M: AutodiffModule<B>,
+    E: Environment,
+    DEC: Decay,
+{
+    policy_net: Option<M>,
+    target_net: TargetNetwork<M>,
+    device: &'static B::Device,
+    memory: SequenceReplayMemory<E>,
+    optimizer: AdamWOptimizer<M, B>,
+    exploration: EpsilonGreedy<DEC>,
+    gamma: f32,
+    loss: ValueLoss,
+    lr: f32,
+    burn_in: usize,
+    total_steps: u64,
+    hidden: Option<Hidden<B>>,
+    policy_hidden: RefCell<Option<Hidden<B>>>,
+}
+
+impl<B, M, E, DEC> DRQNAgent<B, M, E, DEC>
+where
+    B: AutodiffBackend,
+    M: RecurrentQModel<B>,
+    E: Environment,
+    DEC: Decay,
+    Vec<E::State>: ToTensor<B, 2, Float>,
+    E::Action: From<i32> + Into<[i32; 1]>,
+{
+    /// Initialize a new `DRQNAgent`
+    ///
+    /// ### Arguments
+    /// - `model` A [`RecurrentQModel`] to be used as the policy and target networks
+    /// - `config` A [`DRQNAgentConfig`] containing components and hyperparameters for the agent
+    /// - `device` A static reference to the device used for the `model`
+    ///
+    /// **Returns** an [`RlError`] if `gamma` is not in the interval `[0, 1]`, `lr` is negative, the target update is
+    /// invalid, or the burn-in doesn't leave any steps of a sequence to learn from
+    pub fn new(model: M, config: DRQNAgentConfig<DEC>, device: &'static B::Device) -> Result<Self> {
+        check_interval("gamma", config.gamma, 0.0, 1.0)?;
+        check_interval("lr", config.lr, 0.0, f32::INFINITY)?;
+        if config.burn_in >= config.sequence_length {
+            return Err(RlError::InvalidHyperparameters(format!(
+                "a burn-in of {} steps leaves nothing to learn from sequences of length {}",
+                config.burn_in, config.sequence_length
+            )));
+        }
+
+        Ok(Self {
+            target_net: TargetNetwork::new(&model, config.target_update)?,
+            policy_net: Some(model),
+            device,
+            memory: SequenceReplayMemory::new(
+                config.memory_capacity,
+                config.memory_batch_size,
+                config.sequence_length,
+            ),
+            optimizer: adamw(config.grad_clipping),
+            exploration: EpsilonGreedy::new(config.epsilon_decay_strategy),
+            gamma: config.gamma,
+            loss: config.loss,
+            lr: config.lr,
+            burn_in: config.burn_in,
+            total_steps: 0,
+            hidden: None,
+            policy_hidden: RefCell::new(None),
+        })
+    }
+
+    /// Perform one learning step on a batch of sequences
+    fn learn_sequences(&mut self) {
+        let _span = tracing::debug_span!("batch", step = self.total_steps).entered();
+
+        let Some(sequences) = self.memory.sample() else {
+            return;
+        };
+        let batch = SequenceBatch::<B, Int>::new::<E, [i32; 1]>(
+            &sequences,
+            self.memory.sequence_length,
+            self.device,
+        );
+        let length = self.memory.sequence_length;
+
+        let policy_net = self.policy_net.take().unwrap();
+
+        // Warm up the hidden states over the burn-in steps without gradients
+        let (hidden, target_hidden) = match self.burn_in {
+            0 => (None, None),
+            burn_in => {
+                let warm_up = batch.slice(0..burn_in);
+                let (_, hidden) = policy_net.forward(warm_up.states, None);
+                let (_, target_hidden) = self.target_net.net().forward(warm_up.next_states, None);
+                (Some(hidden.detach()), Some(target_hidden.detach()))
+            }
+        };
+        let batch = batch.slice(self.burn_in..length);
+
+        // Compute the Q values of the chosen actions in each step
+        let (q_values, _) = policy_net.forward(batch.states.clone(), hidden);
+        let q_values = q_values.gather(2, batch.actions.clone());
+
+        // Compute the maximum Q values obtainable from each next state
+        let (next_q_values, _) = self
+            .target_net
+            .net()
+            .forward(batch.next_states.clone(), target_hidden);
+        let next_q_values = next_q_values.max_dim(2).detach();
+
+        let targets = batch.targets(next_q_values, self.gamma);
+
+        // Mean loss of the temporal difference errors over the steps of the sequences, without the padding
+        let weights = batch.weights();
+        let loss = (self.loss.forward(q_values, targets) * weights.clone()).sum()
+            / weights.sum().clamp_min(1.0);
+
+        let grads = GradientsParams::from_grads(loss.backward(), &policy_net);
+        let policy_net = self.optimizer.step(self.lr.into(), policy_net, grads);
+        self.policy_net = Some(policy_net);
+
+        self.target_net.step(self.policy_net.as_ref().unwrap());
+    }
+
+    /// The Q values of `state` and the hidden state after it, according to `net` starting from `hidden`
+    fn step(
+        &self,
+        net: &M,
+        state: E::State,
+        hidden: Option<Hidden<B>>,
+    ) -> (Tensor<B, 2>, Hidden<B>) {
+        let input = vec![state].to_tensor(self.device).unsqueeze_dim(1);
+        let (q_values, hidden) = net.forward(input, hidden);
+        (q_values.squeeze(1), hidden.detach())
+    }
+}
+
+impl<B, M, E, DEC> Agent<E> for DRQNAgent<B, M, E, DEC>
+where
+    B: AutodiffBackend,
+    M: RecurrentQModel<B>,
+    E: Environment,
+    DEC: Decay,
+    Vec<E::State>: ToTensor<B, 2, Float>,
+    E::Action: From<i32> + Into<[i32; 1]>,
+{
+    /// Advance the hidden state with `state` and choose an action with the exploration strategy
+    ///
+    /// The hidden state is advanced for random actions too, so it always summarizes the whole episode.
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action {
+        let hidden = self.hidden.take();
+        let (q_values, hidden) =
+            self.step(self.policy_net.as_ref().unwrap(), state.clone(), hidden);
+        self.hidden = Some(hidden);
+        match self.exploration.choose(self.total_steps) {
+            Choice::Explore => env.random_action(),
+            Choice::Exploit => greedy_action(q_values),
+        }
+    }
+
+    /// Store the experience in replay memory and perform one learning step
+    fn learn(&mut self, _env: &E, experience: Exp<E>) {
+        self.memory.push(experience);
+        self.learn_sequences();
+        self.total_steps += 1;
+    }
+
+    /// Reset the hidden state and end the episode in the replay memory, which also covers truncated episodes
+    fn on_episode_end(&mut self) {
+        self.hidden = None;
+        self.memory.end_episode();
+    }
+
+    /// The discount factor `gamma`, the learning rate `lr` and the lowest exploration probability `epsilon_floor`
+    fn hyperparams(&self) -> Vec<Hyperparam> {
+        hyperparams(self.lr, self.gamma, &self.exploration)
+    }
+
+    fn set_hyperparam(&mut self, name: &str, value: f64) -> bool {
+        set_hyperparam(
+            &mut self.lr,
+            &mut self.gamma,
+            &mut self.exploration,
+            name,
+            value,
+        )
+    }
+
+    fn reset_policy(&self) {
+        self.policy_hidden.take();
+    }
+
+    fn policy(&self, _env: &E, state: &E::State) -> E::Action {
+        let net = self.policy_net.as_ref().unwrap();
+        let hidden = self.policy_hidden.take();
+        let (q_values, hidden) = self.step(net, state.clone(), hidden);
+        self.policy_hidden.replace(Some(hidden));
+        greedy_action(q_values)
+    }
+}
+
+impl<B, M, E, DEC> Debug for DRQNAgent<B, M, E, DEC>
+where
+    B: AutodiffBackend,
+    M: AutodiffModule<B> + Debug,
+    E: Environment,
+    DEC: Decay + Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DRQNAgent")
+            .field("policy_net", &self.policy_net)
+            .field("target_net", self.target_net.net())
+            .field("target_update", &self.target_net.update())
+            .field("memory", &self.memory)
+            .field("exploration", &self.exploration)
+            .field("gamma", &self.gamma)
+            .field("loss", &self.loss)
+            .field("lr", &self.lr)
+            .field("burn_in", &self.burn_in)
+            .field("total_steps", &self.total_steps)
+            .finish_non_exhaustive()
+    }
+}
@@ -1,4 +1,14 @@
+/// Augmented Random Search, a gradient-free baseline with linear policies
+pub mod ars;
+/// Lagrangian constraints on the expected costs of episodes, for safe RL
+pub mod constrained;
 /// Deep Q Network
 pub mod dqn;
+/// A simplified Dreamer, learning a latent world model and acting from imagined trajectories
+pub mod dreamer;
+/// Deep Recurrent Q Network, for partially observable environments
+pub mod drqn;
+/// Model-predictive control with random shooting and cross-entropy method planners
+pub mod mpc;
 
 pub mod tabular;
@@ -0,0 +1,417 @@
+use std::marker::PhantomData;
+
+use burn::{prelude::*, tensor::backend::AutodiffBackend};
+use rand::Rng;
+use rand_distr::{Distribution, StandardNormal};
+
+use crate::{
+    env::{ContinuousAction, Environment},
+    error::{check_interval, Result, RlError},
+    memory::Exp,
+    model::EnsembleModel,
+    seed::{self, Stream},
+    traits::Agent,
+};
+
+/// A model of an environment that an [`MpcAgent`] plans with, predicting the outcomes of many actions at once
+///
+/// Implemented for closures `Fn(&E::State, &E::Action) -> (Option<E::State>, f32)`, e.g. the ground truth model of a
+/// [`DeterministicModel`](crate::env::DeterministicModel) or a simulator, and for learned [`EnsembleModel`]s of
+/// environments with arrays of features as states.
+///
+/// ### Generics
+/// - `E` - The modeled [`Environment`]
+pub trait Dynamics<E: Environment> {
+    /// Predict the next state, `None` if terminal, and the reward of taking each of `actions` in the state of the same
+    /// index in `states`
+    fn predict(&self, states: &[E::State], actions: &[E::Action]) -> Vec<(Option<E::State>, f32)>;
+}
+
+impl<E, F> Dynamics<E> for F
+where
+    E: Environment,
+    F: Fn(&E::State, &E::Action) -> (Option<E::State>, f32),
+{
+    fn predict(&self, states: &[E::State], actions: &[E::Action]) -> Vec<(Option<E::State>, f32)> {
+        states
+            .iter()
+            .zip(actions)
+            .map(|(state, action)| self(state, action))
+            .collect()
+    }
+}
+
+/// Plans with the mean prediction of the members, which never ends an episode
+impl<B, E, const S: usize, const A: usize> Dynamics<E> for EnsembleModel<B>
+where
+    B: AutodiffBackend,
+    E: Environment<State = [f32; S], Action = ContinuousAction<A>>,
+{
+    fn predict(&self, states: &[E::State], actions: &[E::Action]) -> Vec<(Option<E::State>, f32)> {
+        let device = self.device();
+        let rows = states.len();
+        let states =
+            Tensor::<B, 1>::from_floats(states.concat().as_slice(), device).reshape([rows, S]);
+        let actions = actions
+            .iter()
+            .flat_map(|action| action.0)
+            .collect::<Vec<_>>();
+        let actions = Tensor::<B, 1>::from_floats(actions.as_slice(), device).reshape([rows, A]);
+
+        let prediction = EnsembleModel::predict(self, states, actions);
+        let next_states = prediction.next_states.into_data().convert::<f32>().value;
+        let rewards = prediction.rewards.into_data().convert::<f32>().value;
+        next_states
+            .chunks(S)
+            .zip(rewards)
+            .map(|(next_state, reward)| {
+                let next_state = next_state
+                    .try_into()
+                    .expect("Predictions have `S` features");
+                (Some(next_state), reward)
+            })
+            .collect()
+    }
+}
+
+/// How an [`MpcAgent`] searches for the action sequence with the highest predicted return
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Planner {
+    /// Sample the candidate sequences uniformly from the action space and take the best one
+    RandomShooting,
+    /// Refine a Gaussian distribution over action sequences with the cross-entropy method
+    ///
+    /// Every iteration samples the candidates from the distribution and fits it to the `elites` best of them. The plan
+    /// is the mean after the last iteration. Each step starts from the plan of the previous step shifted by one, so
+    /// the search continues where it left off.
+    Cem {
+        iterations: usize,
+        elites: usize,
+        /// The standard deviation of every action value at the start of each search
+        init_std: f32,
+    },
+}
+
+impl Default for Planner {
+    fn default() -> Self {
+        Self::Cem {
+            iterations: 5,
+            elites: 50,
+            init_std: 0.5,
+        }
+    }
+}
+
+/// Configuration for the [`MpcAgent`]
+#[derive(Debug, Clone)]
+pub struct MpcAgentConfig {
+    /// The number of steps of the planned action sequences
+    ///
+    /// **Default:** `15`
+    pub horizon: usize,
+    /// The number of action sequences evaluated in every search, or in every iteration of the CEM
+    ///
+    /// **Default:** `500`
+    pub candidates: usize,
+    /// **Default:** [`Planner::Cem`] with `5` iterations, `50` elites and an initial standard deviation of `0.5`
+    pub planner: Planner,
+    /// The discount factor of the predicted returns
+    ///
+    /// **Default:** `1.0`
+    pub gamma: f32,
+    /// The step limit of episodes run with [`go`](Agent::go)
+    ///
+    /// **Default:** `None`
+    pub max_episode_steps: Option<u64>,
+}
+
+impl Default for MpcAgentConfig {
+    fn default() -> Self {
+        Self {
+            horizon: 15,
+            candidates: 500,
+            planner: Planner::default(),
+            gamma: 1.0,
+            max_episode_steps: None,
+        }
+    }
+}
+
+/// A model-predictive control (MPC) agent, which plans a sequence of actions with a model of the environment in every
+/// step and takes the first of them
+///
+/// Re-planning after every step corrects the errors of the model and of the plan as the episode unfolds, so even
+/// rough models control well, see Nagabandi et al., 2018, *Neural Network Dynamics for Model-Based Deep Reinforcement
+/// Learning with Model-Free Fine-Tuning*. The agent doesn't learn from experience itself: a learned model is trained
+/// separately, through [`model_mut`](MpcAgent::model_mut).
+///
+/// The mean predicted return of the chosen plans of each episode is reported as the agent's
+/// [`metrics`](Agent::metrics), named `planned_return`.
+///
+/// ### Generics
+/// - `E` - The [`Environment`] in which the agent acts, with continuous actions
+/// - `M` - The [`Dynamics`] model it plans with
+/// - `A` - The number of values of an action
+#[derive(Debug, Clone)]
+pub struct MpcAgent<E, M, const A: usize>
+where
+    E: Environment<Action = ContinuousAction<A>>,
+    M: Dynamics<E>,
+{
+    model: M,
+    horizon: usize,
+    candidates: usize,
+    planner: Planner,
+    gamma: f32,
+    max_episode_steps: Option<u64>,
+    /// The rest of the last plan, the starting point of the next search
+    plan: Vec<[f32; A]>,
+    /// The summed predicted returns of the plans of the current episode, and their number
+    planned: (f64, u64),
+    _env: PhantomData<E>,
+}
+
+impl<E, M, const A: usize> MpcAgent<E, M, A>
+where
+    E: Environment<Action = ContinuousAction<A>>,
+    M: Dynamics<E>,
+{
+    /// Initialize an agent that plans with `model`
+    ///
+    /// ### Returns
+    /// - [`RlError::OutOfRange`] if `gamma` is not in the interval `[0, 1]` or the initial standard deviation of the
+    ///   CEM is negative
+    /// - [`RlError::InvalidHyperparameters`] if the horizon, the number of candidates or the number of CEM iterations
+    ///   is zero, or the elites are not between one and the number of candidates
+    pub fn new(model: M, config: MpcAgentConfig) -> Result<Self> {
+        check_interval("gamma", config.gamma, 0.0, 1.0)?;
+        if config.horizon == 0 || config.candidates == 0 {
+            return Err(RlError::InvalidHyperparameters(String::from(
+                "MPC needs a positive horizon and number of candidates",
+            )));
+        }
+        if let Planner::Cem {
+            iterations,
+            elites,
+            init_std,
+        } = config.planner
+        {
+            check_interval("init_std", init_std, 0.0, f32::INFINITY)?;
+            if iterations == 0 || !(1..=config.candidates).contains(&elites) {
+                return Err(RlError::InvalidHyperparameters(format!(
+                    "the CEM needs at least one iteration and between 1 and {} elites",
+                    config.candidates
+                )));
+            }
+        }
+
+        Ok(Self {
+            model,
+            horizon: config.horizon,
+            candidates: config.candidates,
+            planner: config.planner,
+            gamma: config.gamma,
+            max_episode_steps: config.max_episode_steps,
+            plan: Vec::new(),
+            planned: (0.0, 0),
+            _env: PhantomData,
+        })
+    }
+
+    /// Get the model
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// Get the model mutably, e.g. to train a learned model between episodes
+    pub fn model_mut(&mut self) -> &mut M {
+        &mut self.model
+    }
+
+    /// Search for the best action sequence from `state`, starting from `warm`, the rest of the previous plan
+    ///
+    /// **Returns** the plan and its predicted return
+    fn search(&self, state: &E::State, warm: &[[f32; A]]) -> (Vec<[f32; A]>, f32) {
+        let mut rng = seed::rng(Stream::Agent);
+        match self.planner {
+            Planner::RandomShooting => {
+                let candidates = (0..self.candidates)
+                    .map(|_| {
+                        (0..self.horizon)
+                            .map(|_| std::array::from_fn(|_| rng.gen_range(-1.0..=1.0)))
+                            .collect()
+                    })
+                    .collect::<Vec<Vec<_>>>();
+                let returns = self.evaluate(state, &candidates);
+                let best = (0..candidates.len())
+                    .max_by(|&a, &b| returns[a].total_cmp(&returns[b]))
+                    .expect("There is at least one candidate");
+                (candidates[best].clone(), returns[best])
+            }
+            Planner::Cem {
+                iterations,
+                elites,
+                init_std,
+            } => {
+                let mut mean = (0..self.horizon)
+                    .map(|t| warm.get(t).copied().unwrap_or([0.0; A]))
+                    .collect::<Vec<_>>();
+                let mut std = vec![[init_std; A]; self.horizon];
+                let mut best_return = f32::NEG_INFINITY;
+                for _ in 0..iterations {
+                    let candidates = (0..self.candidates)
+                        .map(|_| {
+                            mean.iter()
+                                .zip(&std)
+                                .map(|(mean, std)| {
+                                    std::array::from_fn(|i| {
+                                        let noise: f32 = StandardNormal.sample(&mut rng);
+                                        (mean[i] + std[i] * noise).clamp(-1.0, 1.0)
+                                    })
+                                })
+                                .collect()
+                        })
+                        .collect::<Vec<Vec<_>>>();
+                    let returns = self.evaluate(state, &candidates);
+                    let mut order = (0..candidates.len()).collect::<Vec<_>>();
+                    order.sort_by(|&a, &b| returns[b].total_cmp(&returns[a]));
+                    let elite = &order[..elites];
+                    best_return = returns[elite[0]];
+
+                    for t in 0..self.horizon {
+                        for i in 0..A {
+                            let values = elite.iter().map(|&c| candidates[c][t][i]);
+                            let m = values.clone().sum::<f32>() / elites as f32;
+                            let var = values.map(|v| (v - m).powi(2)).sum::<f32>() / elites as f32;
+                            mean[t][i] = m;
+                            std[t][i] = var.sqrt();
+                        }
+                    }
+                }
+                (mean, best_return)
+            }
+        }
+    }
+
+    /// The discounted returns predicted by the model for each of the `candidates` action sequences from `state`
+    fn evaluate(&self, state: &E::State, candidates: &[Vec<[f32; A]>]) -> Vec<f32> {
+        let mut returns = vec![0.0; candidates.len()];
+        let mut states = vec![Some(state.clone()); candidates.len()];
+        let mut discount = 1.0;
+        for t in 0..self.horizon {
+            let alive = (0..candidates.len())
+                .filter(|&c| states[c].is_some())
+                .collect::<Vec<_>>();
+            if alive.is_empty() {
+                break;
+            }
+            let batch_states = alive
+                .iter()
+                .map(|&c| {
+                    states[c]
+                        .clone()
+                        .expect("Only candidates in non-terminal states")
+                })
+                .collect::<Vec<_>>();
+            let actions = alive
+                .iter()
+                .map(|&c| ContinuousAction(candidates[c][t]))
+                .collect::<Vec<_>>();
+
+            let outcomes = self.model.predict(&batch_states, &actions);
+            for (&c, (next_state, reward)) in alive.iter().zip(outcomes) {
+                returns[c] += discount * reward;
+                states[c] = next_state;
+            }
+            discount *= self.gamma;
+        }
+        returns
+    }
+}
+
+impl<E, M, const A: usize> Agent<E> for MpcAgent<E, M, A>
+where
+    E: Environment<Action = ContinuousAction<A>>,
+    M: Dynamics<E>,
+{
+    /// Plan from `state`, continuing the previous plan, and take its first action
+    fn act(&mut self, _env: &E, state: &E::State) -> E::Action {
+        let (mut plan, planned_return) = self.search(state, &self.plan);
+        self.planned.0 += planned_return as f64;
+        self.planned.1 += 1;
+
+        let action = plan.remove(0);
+        self.plan = plan;
+        ContinuousAction(action)
+    }
+
+    /// The agent doesn't learn from experience, its model is trained separately
+    fn learn(&mut self, _env: &E, _experience: Exp<E>) {}
+
+    fn on_episode_end(&mut self) {
+        self.plan.clear();
+    }
+
+    fn metrics(&mut self) -> Vec<(&'static str, f64)> {
+        match std::mem::take(&mut self.planned) {
+            (_, 0) => Vec::new(),
+            (total, plans) => vec![("planned_return", total / plans as f64)],
+        }
+    }
+
+    /// Plan from `state` from scratch and take the first action
+    fn policy(&self, _env: &E, state: &E::State) -> E::Action {
+        ContinuousAction(self.search(state, &[]).0[0])
+    }
+
+    fn max_episode_steps(&self) -> Option<u64> {
+        self.max_episode_steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seed::Seeds, testing::Point};
+
+    #[test]
+    fn mpc_reaches_target() {
+        for planner in [Planner::RandomShooting, Planner::default()] {
+            Seeds::new(0).apply();
+            let mut env = Point::new(2.0);
+            let mut agent = MpcAgent::new(
+                Point::model,
+                MpcAgentConfig {
+                    horizon: 6,
+                    candidates: 200,
+                    planner,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+            let summary = agent.go(&mut env);
+            assert!(env.x().abs() < 0.25, "{planner:?} ended at {}", env.x());
+            // Moving at full speed for 4 steps and staying at 0 costs 1.5 + 1 + 0.5
+            assert!(
+                summary.total_reward > -4.0,
+                "{planner:?}: {}",
+                summary.total_reward
+            );
+            assert_eq!(agent.metrics()[0].0, "planned_return");
+        }
+
+        assert!(MpcAgent::<Point, _, 1>::new(
+            Point::model,
+            MpcAgentConfig {
+                planner: Planner::Cem {
+                    iterations: 1,
+                    elites: 501,
+                    init_std: 0.5
+                },
+                ..Default::default()
+            }
+        )
+        .is_err());
+    }
+}
@@ -1,10 +1,15 @@
 use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use std::{io, path::Path};
 
+#[cfg(feature = "serde")]
+use crate::traits::{checkpoint, Checkpoint};
 use crate::{
     decay::{self, Decay},
     env::{DiscreteActionSpace, Environment},
     exploration::{Choice, EpsilonGreedy},
     memory::Exp,
+    traits::Agent,
 };
 
 use super::Hashable;
@@ -38,6 +43,7 @@ impl Default for ActionOccurrenceAgentConfig<decay::Constant> {
 
 /// An entry in the table
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Entry {
     value: f32,
     count: u32,
@@ -63,7 +69,7 @@ where
     exploration: EpsilonGreedy<D>,
     default_action_value: f32,
     alpha_fn: fn(u32) -> f32,
-    episode: u32,
+    episode: u64,
 }
 
 impl<E, D> ActionOccurrenceAgent<E, D>
@@ -84,39 +90,41 @@ where
         }
     }
 
+    /// Get the value of a state-action pair, or the default value if it has not been visited yet
+    fn value(&self, state: E::State, action: E::Action) -> f32 {
+        self.table
+            .get(&(state, action))
+            .map_or(self.default_action_value, |e| e.value)
+    }
+
+    /// Choose the action with the highest value in `state`, breaking ties at random
+    fn greedy(&self, state: E::State, actions: &[E::Action]) -> E::Action {
+        *max_by_random(actions, |&a, &b| {
+            let a_value = self.value(state, *a);
+            let b_value = self.value(state, *b);
+            a_value.total_cmp(&b_value)
+        })
+        .expect("There is always at least one action available")
+    }
+}
+
+impl<E, D> Agent<E> for ActionOccurrenceAgent<E, D>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable,
+    E::Action: Hashable,
+    D: Decay,
+{
     /// Choose an action based on the current state and exploration policy
-    fn act(&self, env: &E, state: E::State, actions: &[E::Action]) -> E::Action {
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action {
         match self.exploration.choose(self.episode) {
             Choice::Explore => env.random_action(),
-            Choice::Exploit => *actions
-                .iter()
-                .max_by(|&a, &b| {
-                    let a_value = self
-                        .table
-                        .get(&(state, *a))
-                        .copied()
-                        .unwrap_or(Entry {
-                            value: self.default_action_value,
-                            count: 0,
-                        })
-                        .value;
-                    let b_value = self
-                        .table
-                        .get(&(state, *b))
-                        .copied()
-                        .unwrap_or(Entry {
-                            value: self.default_action_value,
-                            count: 0,
-                        })
-                        .value;
-                    a_value.partial_cmp(&b_value).unwrap()
-                })
-                .expect("There is always at least one action available"),
+            Choice::Exploit => self.greedy(*state, &env.actions()),
         }
     }
 
     /// Learn from a given experience and update the table
-    fn learn(&mut self, experience: Exp<E>) {
+    fn learn(&mut self, _env: &E, experience: Exp<E>) {
         let Exp {
             state,
             action,
@@ -136,24 +144,57 @@ where
             });
     }
 
-    /// Run the agent in the given environment
-    pub fn go(&mut self, env: &mut E) {
-        let mut next_state = Some(env.reset());
-        let mut actions = env.actions();
-        while let Some(state) = next_state {
-            let action = self.act(env, state, &actions);
-            let (next, reward) = env.step(action);
-            next_state = next;
-            actions = env.actions();
-
-            self.learn(Exp {
-                state,
-                action,
-                next_state,
-                reward,
-            });
-        }
-
+    fn on_episode_end(&mut self) {
+        tracing::debug!(
+            episode = self.episode,
+            epsilon = self.exploration.epsilon(self.episode),
+            "episode end"
+        );
         self.episode += 1;
     }
+
+    fn policy(&self, env: &E, state: &E::State) -> E::Action {
+        self.greedy(*state, &env.actions())
+    }
+}
+
+/// The learned state of an [`ActionOccurrenceAgent`], as written by [`Checkpoint::save`]
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ActionOccurrenceAgentState<S, A, D: Decay> {
+    table: Vec<(S, A, Entry)>,
+    exploration: EpsilonGreedy<D>,
+    episode: u64,
+}
+
+/// Checkpoints are single JSON files
+#[cfg(feature = "serde")]
+impl<E, D> Checkpoint for ActionOccurrenceAgent<E, D>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable + serde::Serialize + serde::de::DeserializeOwned,
+    E::Action: Hashable + serde::Serialize + serde::de::DeserializeOwned,
+    D: Decay + Clone + serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let state = ActionOccurrenceAgentState {
+            table: self.table.iter().map(|(&(s, a), &e)| (s, a, e)).collect(),
+            exploration: self.exploration.clone(),
+            episode: self.episode,
+        };
+        checkpoint::write_json(path, &state)
+    }
+
+    fn load(&mut self, path: &Path) -> io::Result<()> {
+        let state: ActionOccurrenceAgentState<E::State, E::Action, D> =
+            checkpoint::read_json(path)?;
+        self.table = state
+            .table
+            .into_iter()
+            .map(|(s, a, e)| ((s, a), e))
+            .collect();
+        self.exploration = state.exploration;
+        self.episode = state.episode;
+        Ok(())
+    }
 }
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use std::{io, path::Path};
+
+#[cfg(feature = "serde")]
+use crate::traits::{checkpoint, Checkpoint};
+use crate::{
+    decay,
+    env::{AfterstateEnvironment, DiscreteActionSpace},
+    error::{check_interval, Result},
+    exploration::{max_by_random, Choice, EpsilonGreedy},
+    memory::Exp,
+    traits::{Agent, Hyperparam},
+};
+
+use super::{Hashable, Real};
+
+/// Configuration for the [`AfterstateAgent`]
+#[derive(Debug, Clone)]
+pub struct AfterstateAgentConfig {
+    pub exploration: EpsilonGreedy<decay::Exponential>,
+    pub alpha: f32,
+    pub gamma: f32,
+}
+
+impl Default for AfterstateAgentConfig {
+    fn default() -> Self {
+        Self {
+            exploration: EpsilonGreedy::new(decay::Exponential::new(0.1, 1.0, 0.01).unwrap()),
+            alpha: 0.1,
+            gamma: 1.0,
+        }
+    }
+}
+
+/// A TD(0) agent that learns the values of afterstates instead of state action pairs
+///
+/// The value of an action is the reward of its deterministic effect plus the value of its afterstate, see
+/// [`AfterstateEnvironment`]. After a step, the value of the afterstate moves towards the reward of the environment's
+/// response plus the discounted value of the best action in the next state. In games like 2048, where the random
+/// response dominates the outcome and many moves lead to the same position, this learns much faster than Q-learning.
+///
+/// ### Generics
+/// - `E` - The [`AfterstateEnvironment`] in which the agent will learn
+///     - The afterstate type must be `Copy`, `Eq`, and `Hash` to be used as a key in a [`HashMap`]
+/// - `F` - The float type of the values, `f64` to keep small differences between values in long episodes
+#[derive(Debug, Clone)]
+pub struct AfterstateAgent<E, F = f32>
+where
+    E: AfterstateEnvironment + DiscreteActionSpace,
+    E::Afterstate: Hashable,
+{
+    values: HashMap<E::Afterstate, F>,
+    exploration: EpsilonGreedy<decay::Exponential>,
+    alpha: f32,   // learning rate
+    gamma: f32,   // discount factor
+    episode: u64, // current episode
+}
+
+impl<E> AfterstateAgent<E>
+where
+    E: AfterstateEnvironment + DiscreteActionSpace,
+    E::Afterstate: Hashable,
+{
+    /// Initialize a new `AfterstateAgent` with `f32` values
+    ///
+    /// ### Parameters
+    /// - `alpha` - The learning rate - must be between 0 and 1
+    /// - `gamma` - The discount factor - must be between 0 and 1
+    /// - `exploration` - A customized [EpsilonGreedy] policy
+    ///
+    /// **Returns** an [`RlError::OutOfRange`](crate::error::RlError::OutOfRange) if `alpha` or `gamma` is not in
+    /// the interval `[0,1]`
+    pub fn new(config: AfterstateAgentConfig) -> Result<Self> {
+        Self::with_precision(config)
+    }
+}
+
+impl<E, F> AfterstateAgent<E, F>
+where
+    E: AfterstateEnvironment + DiscreteActionSpace,
+    E::Afterstate: Hashable,
+    F: Real,
+{
+    /// Initialize a new `AfterstateAgent` with values of type `F`, e.g.
+    /// `AfterstateAgent::<E, f64>::with_precision(config)`
+    ///
+    /// **Returns** an [`RlError::OutOfRange`](crate::error::RlError::OutOfRange) if `alpha` or `gamma` is not in
+    /// the interval `[0,1]`
+    pub fn with_precision(config: AfterstateAgentConfig) -> Result<Self> {
+        check_interval("alpha", config.alpha, 0.0, 1.0)?;
+        check_interval("gamma", config.gamma, 0.0, 1.0)?;
+        Ok(Self {
+            values: HashMap::new(),
+            exploration: config.exploration,
+            alpha: config.alpha,
+            gamma: config.gamma,
+            episode: 0,
+        })
+    }
+
+    /// Get the values of the afterstates visited so far
+    pub fn get_values(&self) -> &HashMap<E::Afterstate, F> {
+        &self.values
+    }
+
+    /// The value of taking `action` in `state`, the reward of the action plus the value of its afterstate
+    fn action_value(&self, env: &E, state: &E::State, action: &E::Action) -> F {
+        let (afterstate, reward) = env.afterstate(state, action);
+        let value = self.values.get(&afterstate).copied().unwrap_or_default();
+        F::from_f32(reward) + value
+    }
+
+    /// Choose the action with the highest value in `state`, breaking ties at random
+    ///
+    /// **Returns** the action and its value
+    fn greedy(&self, env: &E, state: &E::State) -> (E::Action, F) {
+        let values = env.actions().into_iter().map(|action| {
+            let value = self.action_value(env, state, &action);
+            (action, value)
+        });
+        max_by_random(values, |(_, a), (_, b)| a.total_cmp(b))
+            .expect("There is always at least one action available")
+    }
+}
+
+impl<E, F> Agent<E> for AfterstateAgent<E, F>
+where
+    E: AfterstateEnvironment + DiscreteActionSpace,
+    E::Afterstate: Hashable,
+    F: Real,
+{
+    /// Choose an action based on the current state and exploration policy
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action {
+        match self.exploration.choose(self.episode) {
+            Choice::Explore => env.random_action(),
+            Choice::Exploit => self.greedy(env, state).0,
+        }
+    }
+
+    /// Learn from a given experience and update the value of its afterstate
+    fn learn(&mut self, env: &E, experience: Exp<E>) {
+        let Exp {
+            state,
+            action,
+            next_state,
+            reward,
+        } = experience;
+
+        let (afterstate, action_reward) = env.afterstate(&state, &action);
+        let value = self.values.get(&afterstate).copied().unwrap_or_default();
+        let next_value = next_state
+            .map(|next_state| self.greedy(env, &next_state).1)
+            .unwrap_or_default();
+        let target = F::from_f32(reward - action_reward) + F::from_f32(self.gamma) * next_value;
+        let new_value = F::from_f32(1.0 - self.alpha) * value + F::from_f32(self.alpha) * target;
+
+        self.values.insert(afterstate, new_value);
+    }
+
+    fn on_episode_end(&mut self) {
+        tracing::debug!(
+            episode = self.episode,
+            epsilon = self.exploration.epsilon(self.episode),
+            "episode end"
+        );
+        self.episode += 1;
+    }
+
+    /// The discount factor `gamma`
+    fn hyperparams(&self) -> Vec<Hyperparam> {
+        vec![Hyperparam::gamma(self.gamma)]
+    }
+
+    fn set_hyperparam(&mut self, name: &str, value: f64) -> bool {
+        match name {
+            "gamma" if (0.0..=1.0).contains(&value) => self.gamma = value as f32,
+            _ => return false,
+        }
+        true
+    }
+
+    fn policy(&self, env: &E, state: &E::State) -> E::Action {
+        self.greedy(env, state).0
+    }
+}
+
+/// The learned state of an [`AfterstateAgent`], as written by [`Checkpoint::save`]
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct AfterstateAgentState<S, F> {
+    values: Vec<(S, F)>,
+    exploration: EpsilonGreedy<decay::Exponential>,
+    episode: u64,
+}
+
+/// Checkpoints are single JSON files
+#[cfg(feature = "serde")]
+impl<E, F> Checkpoint for AfterstateAgent<E, F>
+where
+    E: AfterstateEnvironment + DiscreteActionSpace,
+    E::Afterstate: Hashable + serde::Serialize + serde::de::DeserializeOwned,
+    F: Real + serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let state = AfterstateAgentState {
+            values: self.values.iter().map(|(&s, &v)| (s, v)).collect(),
+            exploration: self.exploration.clone(),
+            episode: self.episode,
+        };
+        checkpoint::write_json(path, &state)
+    }
+
+    fn load(&mut self, path: &Path) -> io::Result<()> {
+        let state: AfterstateAgentState<E::Afterstate, F> = checkpoint::read_json(path)?;
+        self.values = state.values.into_iter().collect();
+        self.exploration = state.exploration;
+        self.episode = state.episode;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        seed::Seeds,
+        testing::{assert_converges_to, Corridor},
+    };
+
+    #[test]
+    fn afterstate_agent_converges() {
+        Seeds::new(0).apply();
+        let mut env = Corridor::default();
+        let expected = env.optimal_return();
+        let mut agent = AfterstateAgent::new(AfterstateAgentConfig {
+            exploration: EpsilonGreedy::new(decay::Exponential::new(0.01, 1.0, 0.1).unwrap()),
+            alpha: 0.5,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_converges_to(&mut agent, &mut env, 500, expected, 0.0);
+
+        // Moving left from the first cell ends up one step further from the goal than moving right
+        let values = agent.get_values();
+        assert!(values[&0] < values[&1]);
+    }
+}
@@ -0,0 +1,293 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    path::Path,
+};
+
+use serde_json::Value;
+
+use super::{q_table::QTableAgent, Hashable, Real};
+use crate::{
+    env::{DiscreteActionSpace, Environment},
+    traits::checkpoint,
+};
+
+/// The Q values of a [`QTableAgent`] keyed by the JSON of their state and action, for comparing Q-tables
+///
+/// Loading Q-tables without their state and action types lets any two files be compared, e.g. with the `rl diff`
+/// command, as long as they serialize states and actions the same way.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QTableSnapshot {
+    states: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+impl QTableSnapshot {
+    /// Create a snapshot of `(state, action, value)` entries
+    pub fn from_entries(entries: impl IntoIterator<Item = (String, String, f64)>) -> Self {
+        let mut states = BTreeMap::<_, BTreeMap<_, _>>::new();
+        for (state, action, value) in entries {
+            states.entry(state).or_default().insert(action, value);
+        }
+        Self { states }
+    }
+
+    /// Take a snapshot of the Q-table of `agent`
+    pub fn from_agent<E, F>(agent: &QTableAgent<E, F>) -> io::Result<Self>
+    where
+        E: Environment + DiscreteActionSpace,
+        E::State: Hashable + Ord + serde::Serialize,
+        E::Action: Hashable + Ord + serde::Serialize,
+        F: Real + Into<f64>,
+    {
+        let entries = agent
+            .sorted_q_table()
+            .into_iter()
+            .map(|(state, action, value)| -> io::Result<_> {
+                Ok((
+                    serde_json::to_string(&state)?,
+                    serde_json::to_string(&action)?,
+                    value.into(),
+                ))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self::from_entries(entries))
+    }
+
+    /// Load the Q-table of a file written by [`QTableAgent::save_q_table`] or of a [`QTableAgent`] checkpoint
+    ///
+    /// **Returns** an error if the file can't be read or holds neither
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json: Value = checkpoint::read_json(path.as_ref())?;
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} is neither a saved Q-table nor a Q-table checkpoint",
+                    path.as_ref().display()
+                ),
+            )
+        };
+
+        let entries = match &json {
+            Value::Array(entries) => entries
+                .iter()
+                .map(|entry| entry_of(&entry["state"], &entry["action"], &entry["value"]))
+                .collect::<Option<Vec<_>>>(),
+            Value::Object(_) => json["q_table"].as_array().and_then(|entries| {
+                entries
+                    .iter()
+                    .map(|entry| entry_of(&entry[0], &entry[1], &entry[2]))
+                    .collect()
+            }),
+            _ => None,
+        };
+        entries.map(Self::from_entries).ok_or_else(invalid)
+    }
+
+    /// The states with at least one Q value
+    pub fn states(&self) -> impl Iterator<Item = &str> {
+        self.states.keys().map(String::as_str)
+    }
+
+    /// The greedy action of `state` and its value, the first of the highest valued actions if several tie
+    pub fn greedy(&self, state: &str) -> Option<(&str, f64)> {
+        self.states
+            .get(state)?
+            .iter()
+            .fold(None, |best, (action, &value)| match best {
+                Some((_, best_value)) if best_value >= value => best,
+                _ => Some((action.as_str(), value)),
+            })
+    }
+
+    /// Compare with the `newer` snapshot of the same Q-table
+    pub fn diff(&self, newer: &QTableSnapshot) -> QTableDiff {
+        let mut diff = QTableDiff::default();
+        for state in newer.states() {
+            if !self.states.contains_key(state) {
+                diff.new_states.push(state.to_string());
+            }
+        }
+        for state in self.states() {
+            if !newer.states.contains_key(state) {
+                diff.removed_states.push(state.to_string());
+            }
+        }
+
+        for (state, actions) in &self.states {
+            let Some(new_actions) = newer.states.get(state) else {
+                continue;
+            };
+            let max_change = actions
+                .iter()
+                .filter_map(|(action, old)| Some((new_actions.get(action)? - old).abs()))
+                .fold(0.0, f64::max);
+            let (Some((old_action, old_value)), Some((new_action, new_value))) =
+                (self.greedy(state), newer.greedy(state))
+            else {
+                continue;
+            };
+
+            diff.changes.push(StateChange {
+                state: state.clone(),
+                old_value,
+                new_value,
+                max_change,
+            });
+            if old_action != new_action {
+                diff.flips.push(PolicyFlip {
+                    state: state.clone(),
+                    old_action: old_action.to_string(),
+                    new_action: new_action.to_string(),
+                });
+            }
+        }
+        diff.changes
+            .sort_by(|a, b| b.max_change.total_cmp(&a.max_change));
+
+        diff
+    }
+}
+
+/// The value of a state in two snapshots of a Q-table
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateChange {
+    pub state: String,
+    /// The value of the greedy action in the older snapshot
+    pub old_value: f64,
+    /// The value of the greedy action in the newer snapshot
+    pub new_value: f64,
+    /// The largest absolute change of the Q value of any action in both snapshots
+    pub max_change: f64,
+}
+
+/// A state whose greedy action differs between two snapshots of a Q-table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyFlip {
+    pub state: String,
+    pub old_action: String,
+    pub new_action: String,
+}
+
+/// The differences between two snapshots of a Q-table, see [`QTableSnapshot::diff`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QTableDiff {
+    /// The states in both snapshots, by decreasing [`max_change`](StateChange::max_change)
+    pub changes: Vec<StateChange>,
+    /// The states in both snapshots whose greedy action changed
+    pub flips: Vec<PolicyFlip>,
+    /// The states only in the newer snapshot, i.e. visited in between
+    pub new_states: Vec<String>,
+    /// The states only in the older snapshot
+    pub removed_states: Vec<String>,
+}
+
+impl QTableDiff {
+    /// Write a human readable report, listing at most `limit` entries of each section
+    pub fn write_report(&self, mut writer: impl Write, limit: usize) -> io::Result<()> {
+        let changed = self.changes.iter().filter(|c| c.max_change > 0.0).count();
+        writeln!(
+            writer,
+            "{changed} of {} shared states changed, {} greedy actions flipped, {} new states, {} removed states",
+            self.changes.len(),
+            self.flips.len(),
+            self.new_states.len(),
+            self.removed_states.len()
+        )?;
+
+        if changed > 0 {
+            writeln!(writer, "\nLargest value changes:")?;
+            for change in self.changes.iter().take(changed.min(limit)) {
+                writeln!(
+                    writer,
+                    "  {}: {:.4} -> {:.4} (max Q change {:.4})",
+                    change.state, change.old_value, change.new_value, change.max_change
+                )?;
+            }
+        }
+        if !self.flips.is_empty() {
+            writeln!(writer, "\nPolicy flips:")?;
+            for flip in self.flips.iter().take(limit) {
+                writeln!(
+                    writer,
+                    "  {}: {} -> {}",
+                    flip.state, flip.old_action, flip.new_action
+                )?;
+            }
+        }
+        for (title, states) in [
+            ("New states", &self.new_states),
+            ("Removed states", &self.removed_states),
+        ] {
+            if !states.is_empty() {
+                writeln!(writer, "\n{title}:")?;
+                for state in states.iter().take(limit) {
+                    writeln!(writer, "  {state}")?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An entry of a Q-table file, with the state and action as JSON
+fn entry_of(state: &Value, action: &Value, value: &Value) -> Option<(String, String, f64)> {
+    if state.is_null() || action.is_null() {
+        return None;
+    }
+    Some((state.to_string(), action.to_string(), value.as_f64()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(entries: &[(&str, &str, f64)]) -> QTableSnapshot {
+        QTableSnapshot::from_entries(
+            entries
+                .iter()
+                .map(|&(s, a, v)| (s.to_string(), a.to_string(), v)),
+        )
+    }
+
+    #[test]
+    fn q_table_diff() {
+        let old = snapshot(&[
+            ("0", "Left", 1.0),
+            ("0", "Right", 0.5),
+            ("1", "Left", 0.0),
+            ("2", "Left", 0.0),
+        ]);
+        let new = snapshot(&[
+            ("0", "Left", 1.0),
+            ("0", "Right", 2.0),
+            ("1", "Left", 0.25),
+            ("3", "Right", 1.0),
+        ]);
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.changes[0].state, "0", "largest change first");
+        assert_eq!(diff.changes[0].old_value, 1.0);
+        assert_eq!(diff.changes[0].new_value, 2.0);
+        assert_eq!(diff.changes[0].max_change, 1.5);
+        assert_eq!(diff.changes[1].max_change, 0.25);
+        assert_eq!(
+            diff.flips,
+            [PolicyFlip {
+                state: String::from("0"),
+                old_action: String::from("Left"),
+                new_action: String::from("Right"),
+            }]
+        );
+        assert_eq!(diff.new_states, ["3"]);
+        assert_eq!(diff.removed_states, ["2"]);
+
+        let mut report = Vec::new();
+        diff.write_report(&mut report, 10).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.starts_with(
+            "2 of 2 shared states changed, 1 greedy actions flipped, 1 new states, 1 removed states"
+        ));
+    }
+}
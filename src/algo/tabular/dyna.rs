@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::{
+    decay,
+    env::{DiscreteActionSpace, Environment},
+    error::{check_interval, Result, RlError},
+    exploration::{max_by_random, Choice, EpsilonGreedy},
+    memory::Exp,
+    seed::{self, Stream},
+    traits::{Agent, Hyperparam},
+};
+
+use super::Hashable;
+
+/// Which simulated experiences a [`DynaQAgent`] plans with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Planning {
+    /// Simulate transitions of state action pairs drawn uniformly from the model, as in the original Dyna-Q
+    #[default]
+    Uniform,
+    /// Simulate trajectories from the current state, following the epsilon-greedy policy through the model
+    ///
+    /// Planning focuses on the states the policy actually reaches, which pays off in large state spaces where most
+    /// modeled states are irrelevant, see Sutton & Barto, 2018, section 8.6. Trajectories end after `max_depth`
+    /// transitions, in terminal states and in states without modeled actions.
+    TrajectorySampling { max_depth: usize },
+}
+
+/// Configuration for the [`DynaQAgent`]
+#[derive(Debug, Clone)]
+pub struct DynaQAgentConfig {
+    pub exploration: EpsilonGreedy<decay::Exponential>,
+    pub alpha: f32,
+    pub gamma: f32,
+    /// The number of simulated updates after every real step
+    ///
+    /// **Default:** `5`
+    pub planning_steps: usize,
+    /// **Default:** [`Planning::Uniform`]
+    pub planning: Planning,
+    /// The step limit of episodes run with [`go`](Agent::go)
+    ///
+    /// **Default:** `None`
+    pub max_episode_steps: Option<u64>,
+}
+
+impl Default for DynaQAgentConfig {
+    fn default() -> Self {
+        Self {
+            exploration: EpsilonGreedy::new(decay::Exponential::new(0.1, 1.0, 0.01).unwrap()),
+            alpha: 0.1,
+            gamma: 0.95,
+            planning_steps: 5,
+            planning: Planning::Uniform,
+            max_episode_steps: None,
+        }
+    }
+}
+
+/// The planning work of a [`DynaQAgent`] since its metrics were last taken
+#[derive(Debug, Clone, Copy, Default)]
+struct PlanningStats {
+    updates: u64,
+    rollouts: u64,
+}
+
+/// A Dyna-Q agent, which learns a Q-table from real experience and from experience simulated with a learned model
+///
+/// The model remembers the last observed next state and reward of every state action pair, so it suits
+/// deterministic environments. After every real step, the agent makes [`planning_steps`](DynaQAgentConfig::planning_steps)
+/// Q-learning updates with transitions simulated as chosen by [`Planning`].
+///
+/// The planning work of each episode is reported as the agent's [`metrics`](Agent::metrics): `planning_updates`,
+/// `planning_rollouts` and `rollout_length` for trajectory sampling, and the number of modeled state action pairs,
+/// `model_size`.
+///
+/// ### Generics
+/// - `E` - The [`Environment`] in which the agent will learn, with discrete states and actions as for a
+///   [`QTableAgent`](super::q_table::QTableAgent)
+#[derive(Debug, Clone)]
+pub struct DynaQAgent<E>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable,
+    E::Action: Hashable,
+{
+    q_table: HashMap<(E::State, E::Action), f32>,
+    model: HashMap<(E::State, E::Action), (Option<E::State>, f32)>,
+    /// The keys of the model in insertion order, for uniform sampling
+    modeled: Vec<(E::State, E::Action)>,
+    exploration: EpsilonGreedy<decay::Exponential>,
+    alpha: f32,
+    gamma: f32,
+    planning_steps: usize,
+    planning: Planning,
+    max_episode_steps: Option<u64>,
+    episode: u64,
+    stats: PlanningStats,
+}
+
+impl<E> DynaQAgent<E>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable,
+    E::Action: Hashable,
+{
+    /// Initialize an agent with an empty Q-table and model
+    ///
+    /// ### Returns
+    /// - [`RlError::OutOfRange`] if `alpha` or `gamma` is not in the interval `[0,1]`
+    /// - [`RlError::InvalidHyperparameters`] if trajectory sampling has a `max_depth` of zero
+    pub fn new(config: DynaQAgentConfig) -> Result<Self> {
+        check_interval("alpha", config.alpha, 0.0, 1.0)?;
+        check_interval("gamma", config.gamma, 0.0, 1.0)?;
+        if config.planning == (Planning::TrajectorySampling { max_depth: 0 }) {
+            return Err(RlError::InvalidHyperparameters(String::from(
+                "trajectory sampling needs a `max_depth` of at least 1",
+            )));
+        }
+
+        Ok(Self {
+            q_table: HashMap::new(),
+            model: HashMap::new(),
+            modeled: Vec::new(),
+            exploration: config.exploration,
+            alpha: config.alpha,
+            gamma: config.gamma,
+            planning_steps: config.planning_steps,
+            planning: config.planning,
+            max_episode_steps: config.max_episode_steps,
+            episode: 0,
+            stats: PlanningStats::default(),
+        })
+    }
+
+    /// Get the Q-table
+    pub fn get_q_table(&self) -> &HashMap<(E::State, E::Action), f32> {
+        &self.q_table
+    }
+
+    /// The modeled next state and reward of `(state, action)`, if it was experienced
+    pub fn model(&self, state: E::State, action: E::Action) -> Option<(Option<E::State>, f32)> {
+        self.model.get(&(state, action)).copied()
+    }
+
+    /// The Q value of `(state, action)`, `0` if it hasn't been learned yet
+    pub fn q_value(&self, state: E::State, action: E::Action) -> f32 {
+        self.q_table
+            .get(&(state, action))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Choose the action with the highest Q value in `state`, breaking ties at random
+    fn greedy(&self, state: E::State, actions: &[E::Action]) -> E::Action {
+        *max_by_random(actions, |&a, &b| {
+            self.q_value(state, *a).total_cmp(&self.q_value(state, *b))
+        })
+        .expect("There is always at least one action available")
+    }
+
+    /// Move the Q value of `(state, action)` towards the reward and the discounted value of `next_state`
+    fn update(
+        &mut self,
+        state: E::State,
+        action: E::Action,
+        next_state: Option<E::State>,
+        reward: f32,
+        actions: &[E::Action],
+    ) {
+        // Terminal states have no future rewards
+        let max_next_q = next_state
+            .and_then(|s| {
+                actions
+                    .iter()
+                    .map(|&a| self.q_value(s, a))
+                    .max_by(|a, b| a.total_cmp(b))
+            })
+            .unwrap_or_default();
+        let q_value = self.q_value(state, action);
+        let target = reward + self.gamma * max_next_q;
+        self.q_table
+            .insert((state, action), q_value + self.alpha * (target - q_value));
+    }
+
+    /// Update with transitions of state action pairs drawn uniformly from the model
+    fn plan_uniform(&mut self, actions: &[E::Action]) {
+        let mut rng = seed::rng(Stream::Agent);
+        for _ in 0..self.planning_steps {
+            let (state, action) = self.modeled[rng.gen_range(0..self.modeled.len())];
+            let (next_state, reward) = self.model[&(state, action)];
+            self.update(state, action, next_state, reward, actions);
+            self.stats.updates += 1;
+        }
+    }
+
+    /// Update with trajectories simulated from `start`, until the planning steps are used up
+    fn plan_trajectories(&mut self, start: E::State, max_depth: usize, actions: &[E::Action]) {
+        let mut budget = self.planning_steps;
+        while budget > 0 {
+            let mut state = start;
+            let mut depth = 0;
+            while budget > 0 && depth < max_depth {
+                let Some(action) = self.rollout_action(state, actions) else {
+                    break;
+                };
+                let (next_state, reward) = self.model[&(state, action)];
+                self.update(state, action, next_state, reward, actions);
+                budget -= 1;
+                depth += 1;
+                match next_state {
+                    Some(next_state) => state = next_state,
+                    None => break,
+                }
+            }
+
+            // Nothing is modeled from `start`, so every trajectory would end immediately
+            if depth == 0 {
+                break;
+            }
+            self.stats.rollouts += 1;
+            self.stats.updates += depth as u64;
+        }
+    }
+
+    /// The epsilon-greedy choice among the modeled actions of `state`, `None` if it has none
+    fn rollout_action(&self, state: E::State, actions: &[E::Action]) -> Option<E::Action> {
+        let modeled = actions
+            .iter()
+            .copied()
+            .filter(|&a| self.model.contains_key(&(state, a)))
+            .collect::<Vec<_>>();
+        if modeled.is_empty() {
+            return None;
+        }
+
+        Some(match self.exploration.choose(self.episode) {
+            Choice::Explore => modeled[seed::rng(Stream::Agent).gen_range(0..modeled.len())],
+            Choice::Exploit => self.greedy(state, &modeled),
+        })
+    }
+}
+
+impl<E> Agent<E> for DynaQAgent<E>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable,
+    E::Action: Hashable,
+{
+    /// Choose an action based on the current state and exploration policy
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action {
+        match self.exploration.choose(self.episode) {
+            Choice::Explore => env.random_action(),
+            Choice::Exploit => self.greedy(*state, &env.actions()),
+        }
+    }
+
+    /// Learn from a given experience, remember it in the model and plan with simulated experience
+    fn learn(&mut self, env: &E, experience: Exp<E>) {
+        let Exp {
+            state,
+            action,
+            next_state,
+            reward,
+        } = experience;
+        let actions = env.actions();
+
+        self.update(state, action, next_state, reward, &actions);
+        if self
+            .model
+            .insert((state, action), (next_state, reward))
+            .is_none()
+        {
+            self.modeled.push((state, action));
+        }
+
+        match self.planning {
+            Planning::Uniform => self.plan_uniform(&actions),
+            Planning::TrajectorySampling { max_depth } => {
+                // The episode continues from the next state, or is over and planning revisits where it ended
+                self.plan_trajectories(next_state.unwrap_or(state), max_depth, &actions)
+            }
+        }
+    }
+
+    fn on_episode_end(&mut self) {
+        self.episode += 1;
+    }
+
+    fn metrics(&mut self) -> Vec<(&'static str, f64)> {
+        let PlanningStats { updates, rollouts } = std::mem::take(&mut self.stats);
+        let mut metrics = vec![
+            ("planning_updates", updates as f64),
+            ("model_size", self.model.len() as f64),
+        ];
+        if matches!(self.planning, Planning::TrajectorySampling { .. }) {
+            metrics.push(("planning_rollouts", rollouts as f64));
+            if rollouts > 0 {
+                metrics.push(("rollout_length", updates as f64 / rollouts as f64));
+            }
+        }
+        metrics
+    }
+
+    /// The discount factor `gamma`
+    fn hyperparams(&self) -> Vec<Hyperparam> {
+        vec![Hyperparam::gamma(self.gamma)]
+    }
+
+    fn set_hyperparam(&mut self, name: &str, value: f64) -> bool {
+        match name {
+            "gamma" if (0.0..=1.0).contains(&value) => self.gamma = value as f32,
+            _ => return false,
+        }
+        true
+    }
+
+    fn policy(&self, env: &E, state: &E::State) -> E::Action {
+        self.greedy(*state, &env.actions())
+    }
+
+    fn max_episode_steps(&self) -> Option<u64> {
+        self.max_episode_steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        seed::Seeds,
+        testing::{assert_converges_to, Corridor},
+    };
+
+    #[test]
+    fn dyna_q_converges() {
+        for planning in [
+            Planning::Uniform,
+            Planning::TrajectorySampling { max_depth: 4 },
+        ] {
+            Seeds::new(0).apply();
+            let mut env = Corridor::new(4);
+            let expected = env.optimal_return();
+            let mut agent = DynaQAgent::new(DynaQAgentConfig {
+                exploration: EpsilonGreedy::new(decay::Exponential::new(0.01, 1.0, 0.1).unwrap()),
+                alpha: 0.5,
+                planning_steps: 10,
+                planning,
+                ..Default::default()
+            })
+            .unwrap();
+            assert_converges_to(&mut agent, &mut env, 50, expected, 0.0);
+
+            let metrics = agent.metrics();
+            assert!(metrics.contains(&("model_size", 6.0)), "{metrics:?}");
+            assert_eq!(
+                agent.metrics()[0],
+                ("planning_updates", 0.0),
+                "Reset after taking"
+            );
+        }
+
+        assert!(DynaQAgent::<Corridor>::new(DynaQAgentConfig {
+            planning: Planning::TrajectorySampling { max_depth: 0 },
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
@@ -1,8 +1,110 @@
 pub mod action_occurrence;
+pub mod afterstate;
+#[cfg(feature = "serde")]
+pub mod diff;
+pub mod dyna;
 pub mod q_table;
+pub mod quantile;
+pub mod soft_q;
 pub mod ucb;
 
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    ops::{Add, Mul},
+};
+
+use crate::{
+    error::{Result, RlError},
+    seed::StableHasher,
+};
+
 /// A trait for state and action types that can be used as keys in a [`HashMap`](std::collections::HashMap)
 pub trait Hashable: Copy + Eq + std::hash::Hash {}
 
 impl<T> Hashable for T where T: Copy + Eq + std::hash::Hash {}
+
+/// A trait for the float types of tabular values, implemented for `f32` and `f64`
+pub trait Real: Copy + Default + Debug + Add<Output = Self> + Mul<Output = Self> {
+    /// Convert an `f32`, e.g. a reward or a hyperparameter
+    fn from_f32(value: f32) -> Self;
+
+    /// The total order of [`f32::total_cmp`]
+    fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+impl Real for f32 {
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f32::total_cmp(self, other)
+    }
+}
+
+impl Real for f64 {
+    fn from_f32(value: f32) -> Self {
+        value as f64
+    }
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+}
+
+/// The value of table entries that haven't been learned yet
+///
+/// Optimistic values above any achievable return make greedy agents try every action in every state they reach,
+/// since each untried action looks better than the ones they learned about, the textbook way to explore
+/// deterministic environments without epsilon-greedy.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", rename_all = "kebab-case", deny_unknown_fields)
+)]
+pub enum InitialValue {
+    /// `0`
+    #[default]
+    Zero,
+    /// The same value for every entry, e.g. an optimistic one
+    Constant { value: f32 },
+    /// A value drawn uniformly from `[low, high)` for each entry, which breaks ties between untried actions
+    Uniform { low: f32, high: f32 },
+}
+
+impl InitialValue {
+    /// Check that the range of [`Uniform`](InitialValue::Uniform) values isn't empty
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            Self::Uniform { low, high } if low.partial_cmp(&high) != Some(Ordering::Less) => {
+                Err(RlError::InvalidHyperparameters(format!(
+                    "uniform initial values need `low` < `high`, got [{low}, {high})"
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The initial value of the entry `key`
+    ///
+    /// Uniform values are derived from a hash of `key` and `seed`, so an entry has the same value every time it is
+    /// read before it is learned, without storing it. The hash has a fixed algorithm, so the values only depend on
+    /// the seed and not on the Rust release or platform.
+    pub fn value<K: Hash>(&self, key: &K, seed: u64) -> f32 {
+        match *self {
+            Self::Zero => 0.0,
+            Self::Constant { value } => value,
+            Self::Uniform { low, high } => {
+                let mut hasher = StableHasher::new();
+                seed.hash(&mut hasher);
+                key.hash(&mut hasher);
+                // The top 24 bits, which an f32 represents exactly
+                let unit = (hasher.finish() >> 40) as f32 / (1u64 << 24) as f32;
+                low + (high - low) * unit
+            }
+        }
+    }
+}
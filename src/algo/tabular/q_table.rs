@@ -1,13 +1,27 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use rand::Rng;
 
+#[cfg(feature = "serde")]
+use crate::traits::{checkpoint, Checkpoint};
 use crate::{
-    assert_interval, decay,
+    csv::escape_csv,
+    decay,
     env::{DiscreteActionSpace, Environment},
-    exploration::{Choice, EpsilonGreedy},
+    error::{check_interval, Result},
+    exploration::{max_by_random, Choice, EpsilonGreedy},
+    export::TablePolicy,
     memory::Exp,
+    seed::{self, Stream},
+    traits::{Agent, Hyperparam},
 };
 
-use super::Hashable;
+use super::{Hashable, InitialValue, Real};
 
 /// Configuration for the [`QTableAgent`]
 #[derive(Debug, Clone)]
@@ -15,6 +29,18 @@ pub struct QTableAgentConfig {
     pub exploration: EpsilonGreedy<decay::Exponential>,
     pub alpha: f32,
     pub gamma: f32,
+    /// The Q values of state action pairs that haven't been learned yet
+    ///
+    /// **Default:** [`InitialValue::Zero`]
+    pub initial_value: InitialValue,
+    /// The step limit of episodes run with [`go`](Agent::go)
+    ///
+    /// **Default:** `None`
+    pub max_episode_steps: Option<u64>,
+    /// Count the visits of each state action pair, see [`QTableAgent::get_visits`]
+    ///
+    /// **Default:** `false`
+    pub track_visits: bool,
 }
 
 impl Default for QTableAgentConfig {
@@ -23,28 +49,48 @@ impl Default for QTableAgentConfig {
             exploration: EpsilonGreedy::new(decay::Exponential::new(0.1, 1.0, 0.01).unwrap()),
             alpha: 0.7,
             gamma: 0.99,
+            initial_value: InitialValue::Zero,
+            max_episode_steps: None,
+            track_visits: false,
         }
     }
 }
 
+/// The visits of a state action pair, tracked by a [`QTableAgent`] with
+/// [`track_visits`](QTableAgentConfig::track_visits)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Visits {
+    /// The number of experiences the agent learned from in the state action pair
+    pub count: u64,
+    /// The step of the last visit, counting every experience the agent learned from
+    pub last_step: u64,
+}
+
 /// A simple Q-learning agent that utilizes a Q-table to learn its environment
 ///
 /// ### Generics
 /// - `E` - The [`Environment`] in which the agent will learn
 ///     - The environment's state and action spaces must both be discrete because a Q value will be recorded for each state action pair
 ///     - For the same reason, the state and action types must be `Copy`, `Eq`, and `Hash` to be used as keys in a [`HashMap`]
+/// - `F` - The float type of the Q values, `f64` to keep small differences between values in long episodes
 #[derive(Debug, Clone)]
-pub struct QTableAgent<E>
+pub struct QTableAgent<E, F = f32>
 where
     E: Environment + DiscreteActionSpace,
     E::State: Hashable,
     E::Action: Hashable,
 {
-    q_table: HashMap<(E::State, E::Action), f32>,
+    q_table: HashMap<(E::State, E::Action), F>,
+    initial_value: InitialValue,
+    seed: u64, // of uniform initial values
+    max_episode_steps: Option<u64>,
+    visits: Option<HashMap<(E::State, E::Action), Visits>>,
     exploration: EpsilonGreedy<decay::Exponential>,
     alpha: f32,   // learning rate
     gamma: f32,   // discount factor
-    episode: u32, // current episode
+    episode: u64, // current episode
+    step: u64,    // experiences learned from
 }
 
 impl<E> QTableAgent<E>
@@ -53,48 +99,252 @@ where
     E::State: Hashable,
     E::Action: Hashable,
 {
-    /// Initialize a new `QAgent` in a given environment
+    /// Initialize a new `QAgent` in a given environment, with `f32` Q values
     ///
     /// ### Parameters
     /// - `alpha` - The learning rate - must be between 0 and 1
     /// - `gamma` - The discount factor - must be between 0 and 1
     /// - `exploration` - A customized [EpsilonGreedy] policy
+    /// - `initial_value` - The Q values of unseen state action pairs
     ///
-    /// **Panics** if `alpha` or `gamma` is not in the interval `[0,1]`
-    pub fn new(config: QTableAgentConfig) -> Self {
-        assert_interval!(config.alpha, 0.0, 1.0);
-        assert_interval!(config.gamma, 0.0, 1.0);
-        Self {
+    /// **Returns** an error in the cases of [`with_precision`](QTableAgent::with_precision)
+    pub fn new(config: QTableAgentConfig) -> Result<Self> {
+        Self::with_precision(config)
+    }
+}
+
+impl<E, F> QTableAgent<E, F>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable,
+    E::Action: Hashable,
+    F: Real,
+{
+    /// Initialize a new `QAgent` with Q values of type `F`, e.g. `QTableAgent::<E, f64>::with_precision(config)`
+    ///
+    /// ### Returns
+    /// - [`RlError::OutOfRange`](crate::error::RlError::OutOfRange) if `alpha` or `gamma` is not in the interval
+    ///   `[0,1]`
+    /// - [`RlError::InvalidHyperparameters`](crate::error::RlError::InvalidHyperparameters) if the range of
+    ///   uniform initial values is empty
+    pub fn with_precision(config: QTableAgentConfig) -> Result<Self> {
+        check_interval("alpha", config.alpha, 0.0, 1.0)?;
+        check_interval("gamma", config.gamma, 0.0, 1.0)?;
+        config.initial_value.validate()?;
+        Ok(Self {
             q_table: HashMap::new(),
+            initial_value: config.initial_value,
+            seed: seed::rng(Stream::Agent).gen(),
+            max_episode_steps: config.max_episode_steps,
+            visits: config.track_visits.then(HashMap::new),
             exploration: config.exploration,
             alpha: config.alpha,
             gamma: config.gamma,
             episode: 0,
-        }
+            step: 0,
+        })
     }
 
     /// Get the Q-table
-    pub fn get_q_table(&self) -> &HashMap<(E::State, E::Action), f32> {
+    pub fn get_q_table(&self) -> &HashMap<(E::State, E::Action), F> {
         &self.q_table
     }
 
+    /// Get the visits of every state action pair learned from, or `None` if visits aren't
+    /// [tracked](QTableAgentConfig::track_visits)
+    pub fn get_visits(&self) -> Option<&HashMap<(E::State, E::Action), Visits>> {
+        self.visits.as_ref()
+    }
+
+    /// The number of visits of `(state, action)`, or `None` if visits aren't tracked
+    pub fn visit_count(&self, state: E::State, action: E::Action) -> Option<u64> {
+        let visits = self.visits.as_ref()?;
+        Some(
+            visits
+                .get(&(state, action))
+                .map_or(0, |visits| visits.count),
+        )
+    }
+
+    /// The number of steps since the last visit of `(state, action)`, where `0` means it was visited in the last step
+    ///
+    /// **Returns** `None` if visits aren't tracked or the pair was never visited
+    pub fn staleness(&self, state: E::State, action: E::Action) -> Option<u64> {
+        let visits = self.visits.as_ref()?.get(&(state, action))?;
+        Some(self.step - visits.last_step)
+    }
+
+    /// Write the visits and Q values of every visited state action pair to a CSV file at `path`
+    ///
+    /// The columns are `state,action,count,staleness,q_value`, with states and actions in their [`Debug`] format.
+    ///
+    /// **Returns** an [`io::ErrorKind::InvalidInput`] error if visits aren't tracked
+    pub fn write_visits_csv(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let visits = self
+            .visits
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "visits are not tracked"))?;
+        let mut rows = visits.iter().collect::<Vec<_>>();
+        rows.sort_by(|(_, a), (_, b)| b.count.cmp(&a.count));
+
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        writeln!(writer, "state,action,count,staleness,q_value")?;
+        for (&(state, action), visits) in rows {
+            let q_value = self
+                .q_table
+                .get(&(state, action))
+                .copied()
+                .unwrap_or_default();
+            writeln!(
+                writer,
+                "{},{},{},{},{:?}",
+                escape_csv(&format!("{state:?}")),
+                escape_csv(&format!("{action:?}")),
+                visits.count,
+                self.step - visits.last_step,
+                q_value
+            )?;
+        }
+        writer.flush()
+    }
+
+    /// The greedy policy over every state in the Q-table, for inference without the agent
+    ///
+    /// `env` provides the available actions, as in [`policy`](Agent::policy).
+    pub fn greedy_policy(&self, env: &E) -> TablePolicy<E::State, E::Action> {
+        let actions = env.actions();
+        let table = self
+            .q_table
+            .keys()
+            .map(|&(state, _)| (state, self.greedy(state, &actions)))
+            .collect();
+        TablePolicy::new(table)
+    }
+
+    /// The Q value of `(state, action)`, or its [initial value](QTableAgentConfig::initial_value) if it hasn't
+    /// been learned yet
+    pub fn q_value(&self, state: E::State, action: E::Action) -> F {
+        match self.q_table.get(&(state, action)) {
+            Some(&value) => value,
+            None => F::from_f32(self.initial_value.value(&(state, action), self.seed)),
+        }
+    }
+
+    /// Choose the action with the highest Q value in `state`, breaking ties at random
+    fn greedy(&self, state: E::State, actions: &[E::Action]) -> E::Action {
+        *max_by_random(actions, |&a, &b| {
+            let a_value = self.q_value(state, *a);
+            let b_value = self.q_value(state, *b);
+            a_value.total_cmp(&b_value)
+        })
+        .expect("There is always at least one action available") // Maybe make this more lenient by providing a default?
+    }
+}
+
+impl<E, F> QTableAgent<E, F>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable + Ord,
+    E::Action: Hashable + Ord,
+    F: Real,
+{
+    /// The entries of the Q-table as `(state, action, value)`, sorted by state and then action
+    ///
+    /// Unlike the iteration order of [`get_q_table`](QTableAgent::get_q_table), the order is the same in every run,
+    /// so exports of different runs can be diffed.
+    pub fn sorted_q_table(&self) -> Vec<(E::State, E::Action, F)> {
+        let mut entries = self
+            .q_table
+            .iter()
+            .map(|(&(state, action), &value)| (state, action, value))
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|&(state, action, _)| (state, action));
+        entries
+    }
+
+    /// Write the Q-table to a CSV file at `path`, sorted by state and then action
+    ///
+    /// The columns are `state,action,value`, with states and actions in their [`Debug`] format.
+    pub fn write_q_table_csv(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        writeln!(writer, "state,action,value")?;
+        for (state, action, value) in self.sorted_q_table() {
+            writeln!(
+                writer,
+                "{},{},{value:?}",
+                escape_csv(&format!("{state:?}")),
+                escape_csv(&format!("{action:?}")),
+            )?;
+        }
+        writer.flush()
+    }
+
+    /// Save the Q-table to a pretty-printed JSON file at `path`, sorted by state and then action
+    ///
+    /// The file is an array of `{ "state": ..., "action": ..., "value": ... }` objects, which can be read back with
+    /// [`load_q_table`](QTableAgent::load_q_table). Unlike a [checkpoint](Checkpoint), it only holds the learned
+    /// values, so it is suited for versioning and comparing policies.
+    #[cfg(feature = "serde")]
+    pub fn save_q_table(&self, path: impl AsRef<Path>) -> io::Result<()>
+    where
+        E::State: serde::Serialize,
+        E::Action: serde::Serialize,
+        F: serde::Serialize,
+    {
+        let entries = self
+            .sorted_q_table()
+            .into_iter()
+            .map(|(state, action, value)| QTableEntry {
+                state,
+                action,
+                value,
+            })
+            .collect::<Vec<_>>();
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, &entries)?;
+        writer.flush()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<E, F> QTableAgent<E, F>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable + serde::de::DeserializeOwned,
+    E::Action: Hashable + serde::de::DeserializeOwned,
+    F: Real + serde::de::DeserializeOwned,
+{
+    /// Replace the Q-table with one saved by [`save_q_table`](QTableAgent::save_q_table)
+    ///
+    /// The rest of the agent, e.g. its exploration schedule and visits, is left as it is.
+    pub fn load_q_table(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let entries: Vec<QTableEntry<E::State, E::Action, F>> =
+            checkpoint::read_json(path.as_ref())?;
+        self.q_table = entries
+            .into_iter()
+            .map(|entry| ((entry.state, entry.action), entry.value))
+            .collect();
+        Ok(())
+    }
+}
+
+impl<E, F> Agent<E> for QTableAgent<E, F>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable,
+    E::Action: Hashable,
+    F: Real,
+{
     /// Choose an action based on the current state and exploration policy
-    fn act(&self, env: &E, state: E::State, actions: &[E::Action]) -> E::Action {
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action {
         match self.exploration.choose(self.episode) {
             Choice::Explore => env.random_action(),
-            Choice::Exploit => *actions
-                .iter()
-                .max_by(|&a, &b| {
-                    let a_value = *self.q_table.get(&(state, *a)).unwrap_or(&0.0);
-                    let b_value = *self.q_table.get(&(state, *b)).unwrap_or(&0.0);
-                    a_value.partial_cmp(&b_value).unwrap()
-                })
-                .expect("There is always at least one action available"), // Maybe make this more lenient by providing a default?
+            Choice::Exploit => self.greedy(*state, &env.actions()),
         }
     }
 
     /// Learn from a given experience and update the Q-table
-    fn learn(&mut self, experience: Exp<E>, next_actions: &[E::Action]) {
+    fn learn(&mut self, env: &E, experience: Exp<E>) {
         let Exp {
             state,
             action,
@@ -102,43 +352,253 @@ where
             reward,
         } = experience;
 
-        let q_value = *self.q_table.get(&(state, action)).unwrap_or(&0.0);
-        let max_next_q = next_actions
-            .iter()
-            .map(|&a| {
-                *next_state
-                    .and_then(|s| self.q_table.get(&(s, a)))
-                    .unwrap_or(&0.0)
+        let q_value = self.q_value(state, action);
+        // Terminal states have no future rewards
+        let max_next_q = next_state
+            .and_then(|s| {
+                env.actions()
+                    .into_iter()
+                    .map(|a| self.q_value(s, a))
+                    .max_by(|a, b| a.total_cmp(b))
             })
-            .max_by(|a, b| a.partial_cmp(b).unwrap())
-            .unwrap_or(0.0);
-        let new_q_value = reward + self.gamma * max_next_q;
-        let weighted_q_value = (1.0 - self.alpha) * q_value + self.alpha * new_q_value;
+            .unwrap_or_default();
+        let new_q_value = F::from_f32(reward) + F::from_f32(self.gamma) * max_next_q;
+        let weighted_q_value =
+            F::from_f32(1.0 - self.alpha) * q_value + F::from_f32(self.alpha) * new_q_value;
 
         self.q_table.insert((state, action), weighted_q_value);
-    }
 
-    /// Run the agent in the given environment
-    pub fn go(&mut self, env: &mut E) {
-        let mut next_state = Some(env.reset());
-        let mut actions = env.actions();
-        while let Some(state) = next_state {
-            let action = self.act(env, state, &actions);
-            let (next, reward) = env.step(action);
-            next_state = next;
-            actions = env.actions();
-
-            self.learn(
-                Exp {
-                    state,
-                    action,
-                    next_state,
-                    reward,
-                },
-                &actions,
-            );
+        self.step += 1;
+        if let Some(visits) = &mut self.visits {
+            let visits = visits.entry((state, action)).or_default();
+            visits.count += 1;
+            visits.last_step = self.step;
         }
+    }
 
+    fn on_episode_end(&mut self) {
+        tracing::debug!(
+            episode = self.episode,
+            epsilon = self.exploration.epsilon(self.episode),
+            "episode end"
+        );
         self.episode += 1;
     }
+
+    /// The discount factor `gamma`
+    fn hyperparams(&self) -> Vec<Hyperparam> {
+        vec![Hyperparam::gamma(self.gamma)]
+    }
+
+    fn set_hyperparam(&mut self, name: &str, value: f64) -> bool {
+        match name {
+            "gamma" if (0.0..=1.0).contains(&value) => self.gamma = value as f32,
+            _ => return false,
+        }
+        true
+    }
+
+    fn policy(&self, env: &E, state: &E::State) -> E::Action {
+        self.greedy(*state, &env.actions())
+    }
+
+    fn max_episode_steps(&self) -> Option<u64> {
+        self.max_episode_steps
+    }
+}
+
+/// An entry of the Q-table, as written by [`QTableAgent::save_q_table`]
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct QTableEntry<S, A, F> {
+    state: S,
+    action: A,
+    value: F,
+}
+
+/// The learned state of a [`QTableAgent`], as written by [`Checkpoint::save`]
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct QTableAgentState<S, A, F> {
+    q_table: Vec<(S, A, F)>,
+    #[serde(default)]
+    visits: Option<Vec<(S, A, Visits)>>,
+    exploration: EpsilonGreedy<decay::Exponential>,
+    episode: u64,
+    #[serde(default)]
+    step: u64,
+    #[serde(default)]
+    seed: u64,
+}
+
+/// Checkpoints are single JSON files
+#[cfg(feature = "serde")]
+impl<E, F> Checkpoint for QTableAgent<E, F>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable + serde::Serialize + serde::de::DeserializeOwned,
+    E::Action: Hashable + serde::Serialize + serde::de::DeserializeOwned,
+    F: Real + serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let state = QTableAgentState {
+            q_table: self.q_table.iter().map(|(&(s, a), &q)| (s, a, q)).collect(),
+            visits: self
+                .visits
+                .as_ref()
+                .map(|visits| visits.iter().map(|(&(s, a), &v)| (s, a, v)).collect()),
+            exploration: self.exploration.clone(),
+            episode: self.episode,
+            step: self.step,
+            seed: self.seed,
+        };
+        checkpoint::write_json(path, &state)
+    }
+
+    fn load(&mut self, path: &Path) -> io::Result<()> {
+        let state: QTableAgentState<E::State, E::Action, F> = checkpoint::read_json(path)?;
+        self.q_table = state
+            .q_table
+            .into_iter()
+            .map(|(s, a, q)| ((s, a), q))
+            .collect();
+        if let Some(visits) = state.visits {
+            self.visits = Some(visits.into_iter().map(|(s, a, v)| ((s, a), v)).collect());
+        }
+        self.exploration = state.exploration;
+        self.episode = state.episode;
+        self.step = state.step;
+        self.seed = state.seed;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        seed::Seeds,
+        testing::{assert_converges_to, Corridor, CorridorAction, TwoState, TwoStateAction},
+    };
+
+    fn q_table() -> QTableAgent<Corridor> {
+        QTableAgent::new(QTableAgentConfig {
+            exploration: EpsilonGreedy::new(decay::Exponential::new(0.01, 1.0, 0.1).unwrap()),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn optimistic_q_table_converges() {
+        Seeds::new(0).apply();
+        let mut env = TwoState::new();
+        let expected = env.optimal_return();
+        // Almost greedy, so exploration comes from the optimistic initial values
+        let mut agent = QTableAgent::new(QTableAgentConfig {
+            exploration: EpsilonGreedy::new(decay::Exponential::new(1.0, 1e-6, 0.0).unwrap()),
+            initial_value: InitialValue::Constant { value: 2.0 },
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(agent.q_value(1, TwoStateAction::Stay), 2.0);
+        assert_converges_to(&mut agent, &mut env, 500, expected, 0.0);
+
+        let uniform = InitialValue::Uniform {
+            low: -1.0,
+            high: 1.0,
+        };
+        let agent = QTableAgent::<TwoState>::new(QTableAgentConfig {
+            initial_value: uniform,
+            ..Default::default()
+        })
+        .unwrap();
+        let value = agent.q_value(0, TwoStateAction::Stay);
+        assert!((-1.0..1.0).contains(&value));
+        assert_eq!(
+            agent.q_value(0, TwoStateAction::Stay),
+            value,
+            "Stable until learned"
+        );
+        assert_ne!(agent.q_value(0, TwoStateAction::Switch), value);
+
+        let empty = InitialValue::Uniform {
+            low: 1.0,
+            high: 1.0,
+        };
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn q_table_visits() {
+        Seeds::new(0).apply();
+        let env = Corridor::new(3);
+        assert_eq!(q_table().visit_count(0, CorridorAction::Right), None);
+
+        let mut agent = QTableAgent::new(QTableAgentConfig {
+            track_visits: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let experience = |state, action, next_state| Exp {
+            state,
+            action,
+            reward: -1.0,
+            next_state,
+        };
+        agent.learn(&env, experience(0, CorridorAction::Right, Some(1)));
+        agent.learn(&env, experience(1, CorridorAction::Left, Some(0)));
+        agent.learn(&env, experience(0, CorridorAction::Right, Some(1)));
+        assert_eq!(agent.visit_count(0, CorridorAction::Right), Some(2));
+        assert_eq!(agent.visit_count(1, CorridorAction::Right), Some(0));
+        assert_eq!(agent.staleness(0, CorridorAction::Right), Some(0));
+        assert_eq!(agent.staleness(1, CorridorAction::Left), Some(1));
+        assert_eq!(agent.staleness(1, CorridorAction::Right), None);
+
+        let path = std::env::temp_dir().join("rl_q_table_visits.csv");
+        agent.write_visits_csv(&path).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "state,action,count,staleness,q_value");
+        assert!(lines[1].starts_with("0,Right,2,0,"), "Most visited first");
+        assert_eq!(lines.len(), 3);
+        assert!(q_table().write_visits_csv(&path).is_err());
+    }
+
+    #[test]
+    fn q_table_export() {
+        Seeds::new(0).apply();
+        let mut env = Corridor::new(4);
+        let expected = env.optimal_return();
+        let mut agent = q_table();
+        assert_converges_to(&mut agent, &mut env, 200, expected, 0.0);
+
+        let path = std::env::temp_dir().join("rl_q_table.csv");
+        agent.write_q_table_csv(&path).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let keys = csv
+            .lines()
+            .skip(1)
+            .map(|line| line.rsplit_once(',').unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            ["0,Left", "0,Right", "1,Left", "1,Right", "2,Left", "2,Right"]
+        );
+
+        let policy = agent.greedy_policy(&env);
+        assert_eq!(policy.len(), 3);
+        for state in 0..3 {
+            assert_eq!(policy.act(&state), Some(CorridorAction::Right));
+        }
+
+        #[cfg(feature = "serde")]
+        {
+            let path = std::env::temp_dir().join("rl_q_table.json");
+            agent.save_q_table(&path).unwrap();
+            let mut loaded = q_table();
+            loaded.load_q_table(&path).unwrap();
+            assert_eq!(loaded.get_q_table(), agent.get_q_table());
+        }
+    }
 }
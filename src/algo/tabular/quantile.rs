@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+
+use crate::{
+    decay,
+    env::{DiscreteActionSpace, Environment},
+    error::{check_interval, Result, RlError},
+    exploration::{max_by_random, Choice, EpsilonGreedy},
+    memory::Exp,
+    risk::Risk,
+    traits::{Agent, Hyperparam},
+};
+
+use super::Hashable;
+
+/// Configuration for the [`QuantileAgent`]
+#[derive(Debug, Clone)]
+pub struct QuantileAgentConfig {
+    pub exploration: EpsilonGreedy<decay::Exponential>,
+    /// The learning rate of the quantiles
+    ///
+    /// **Default:** `0.1`
+    pub alpha: f32,
+    pub gamma: f32,
+    /// The number of quantiles of the return distribution of each state action pair
+    ///
+    /// **Default:** `32`
+    pub quantiles: usize,
+    /// How the return distribution of an action is turned into the value of the action, e.g.
+    /// [`Risk::Cvar`] for a risk-averse agent
+    ///
+    /// **Default:** [`Risk::Neutral`]
+    pub risk: Risk,
+    /// The step limit of episodes run with [`go`](Agent::go)
+    ///
+    /// **Default:** `None`
+    pub max_episode_steps: Option<u64>,
+}
+
+impl Default for QuantileAgentConfig {
+    fn default() -> Self {
+        Self {
+            exploration: EpsilonGreedy::new(decay::Exponential::new(0.1, 1.0, 0.01).unwrap()),
+            alpha: 0.1,
+            gamma: 0.99,
+            quantiles: 32,
+            risk: Risk::Neutral,
+            max_episode_steps: None,
+        }
+    }
+}
+
+/// A tabular distributional agent, which learns the distribution of the return of every state action pair as
+/// equally likely quantiles
+///
+/// Every quantile `θ_i` at the level `τ_i = (2i + 1) / 2n` moves towards each sample `r + γ θ_j(s', a')` of the
+/// target distribution by `α (τ_i - 1{sample < θ_i})`, the tabular quantile temporal-difference update of Rowland et
+/// al., 2023, *An Analysis of Quantile Temporal-Difference Learning*. The next action `a'` and the greedy actions
+/// maximize the [`Risk`] of the distributions, so with [`Risk::Cvar`] the agent optimizes the conditional
+/// value-at-risk of its return instead of the expected return, preferring actions whose bad outcomes are least bad.
+///
+/// ### Generics
+/// - `E` - The [`Environment`] in which the agent will learn, with discrete states and actions as for a
+///   [`QTableAgent`](super::q_table::QTableAgent)
+#[derive(Debug, Clone)]
+pub struct QuantileAgent<E>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable,
+    E::Action: Hashable,
+{
+    table: HashMap<(E::State, E::Action), Vec<f32>>,
+    exploration: EpsilonGreedy<decay::Exponential>,
+    alpha: f32,
+    gamma: f32,
+    quantiles: usize,
+    risk: Risk,
+    max_episode_steps: Option<u64>,
+    episode: u64,
+}
+
+impl<E> QuantileAgent<E>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable,
+    E::Action: Hashable,
+{
+    /// Initialize an agent with an empty table
+    ///
+    /// ### Returns
+    /// - [`RlError::OutOfRange`] if `alpha` or `gamma` is not in the interval `[0,1]`
+    /// - [`RlError::InvalidHyperparameters`] if there are no quantiles
+    pub fn new(config: QuantileAgentConfig) -> Result<Self> {
+        check_interval("alpha", config.alpha, 0.0, 1.0)?;
+        check_interval("gamma", config.gamma, 0.0, 1.0)?;
+        if config.quantiles == 0 {
+            return Err(RlError::InvalidHyperparameters(String::from(
+                "a return distribution needs at least one quantile",
+            )));
+        }
+        Ok(Self {
+            table: HashMap::new(),
+            exploration: config.exploration,
+            alpha: config.alpha,
+            gamma: config.gamma,
+            quantiles: config.quantiles,
+            risk: config.risk,
+            max_episode_steps: config.max_episode_steps,
+            episode: 0,
+        })
+    }
+
+    /// The quantiles of the return of `(state, action)`, all `0` if it hasn't been learned yet
+    pub fn quantiles(&self, state: E::State, action: E::Action) -> Vec<f32> {
+        self.table
+            .get(&(state, action))
+            .cloned()
+            .unwrap_or_else(|| vec![0.0; self.quantiles])
+    }
+
+    /// The value of `(state, action)` under the agent's [`Risk`]
+    pub fn value(&self, state: E::State, action: E::Action) -> f32 {
+        self.risk.value(&self.quantiles(state, action))
+    }
+
+    /// The risk measure that the agent maximizes
+    pub fn risk(&self) -> Risk {
+        self.risk
+    }
+
+    fn greedy(&self, state: E::State, actions: &[E::Action]) -> E::Action {
+        *max_by_random(actions, |&a, &b| {
+            self.value(state, *a).total_cmp(&self.value(state, *b))
+        })
+        .expect("There is always at least one action available")
+    }
+}
+
+impl<E> Agent<E> for QuantileAgent<E>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable,
+    E::Action: Hashable,
+{
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action {
+        match self.exploration.choose(self.episode) {
+            Choice::Explore => env.random_action(),
+            Choice::Exploit => self.greedy(*state, &env.actions()),
+        }
+    }
+
+    /// Move every quantile of the experience towards the samples of the reward plus the discounted distribution of
+    /// the next state's greedy action
+    fn learn(&mut self, env: &E, experience: Exp<E>) {
+        let Exp {
+            state,
+            action,
+            next_state,
+            reward,
+        } = experience;
+
+        // Terminal states have no future rewards
+        let targets = match next_state {
+            Some(next) => {
+                let next_action = self.greedy(next, &env.actions());
+                self.quantiles(next, next_action)
+                    .into_iter()
+                    .map(|q| reward + self.gamma * q)
+                    .collect()
+            }
+            None => vec![reward],
+        };
+
+        let n = self.quantiles as f32;
+        let alpha = self.alpha;
+        let mut quantiles = self.quantiles(state, action);
+        for (i, quantile) in quantiles.iter_mut().enumerate() {
+            let tau = (2 * i + 1) as f32 / (2.0 * n);
+            let below = targets.iter().filter(|&&target| target < *quantile).count() as f32;
+            *quantile += alpha * (tau - below / targets.len() as f32);
+        }
+        self.table.insert((state, action), quantiles);
+    }
+
+    fn on_episode_end(&mut self) {
+        self.episode += 1;
+    }
+
+    /// The discount factor `gamma`
+    fn hyperparams(&self) -> Vec<Hyperparam> {
+        vec![Hyperparam::gamma(self.gamma)]
+    }
+
+    fn set_hyperparam(&mut self, name: &str, value: f64) -> bool {
+        match name {
+            "gamma" if (0.0..=1.0).contains(&value) => self.gamma = value as f32,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Choose the action with the highest value under the agent's [`Risk`], breaking ties at random
+    fn policy(&self, env: &E, state: &E::State) -> E::Action {
+        self.greedy(*state, &env.actions())
+    }
+
+    fn max_episode_steps(&self) -> Option<u64> {
+        self.max_episode_steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::seed::{self, Seeds, Stream};
+
+    /// A safe action with a reward of `1` and a gamble with a reward of `-3` or `7`, better on average
+    struct Gamble;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Bet {
+        Safe,
+        Risky,
+    }
+
+    impl Environment for Gamble {
+        type State = ();
+        type Action = Bet;
+
+        fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
+            let reward = match action {
+                Bet::Safe => 1.0,
+                Bet::Risky if seed::rng(Stream::Env).gen_bool(0.5) => 7.0,
+                Bet::Risky => -3.0,
+            };
+            (None, reward)
+        }
+
+        fn reset(&mut self) -> Self::State {}
+
+        fn random_action(&self) -> Self::Action {
+            Bet::Safe
+        }
+    }
+
+    impl DiscreteActionSpace for Gamble {
+        fn actions(&self) -> Vec<Self::Action> {
+            vec![Bet::Safe, Bet::Risky]
+        }
+    }
+
+    fn trained(risk: Risk) -> QuantileAgent<Gamble> {
+        Seeds::new(0).apply();
+        let mut env = Gamble;
+        let mut agent = QuantileAgent::new(QuantileAgentConfig {
+            quantiles: 4,
+            risk,
+            ..Default::default()
+        })
+        .unwrap();
+        for _ in 0..2000 {
+            for action in env.actions() {
+                let (next_state, reward) = env.step(action);
+                agent.learn(
+                    &env,
+                    Exp {
+                        state: (),
+                        action,
+                        next_state,
+                        reward,
+                    },
+                );
+            }
+        }
+        agent
+    }
+
+    #[test]
+    fn cvar_prefers_the_safe_action() {
+        let neutral = trained(Risk::Neutral);
+        let quantiles = neutral.quantiles((), Bet::Risky);
+        assert!((quantiles[0] + 3.0).abs() < 0.5, "{quantiles:?}");
+        assert!((quantiles[3] - 7.0).abs() < 0.5, "{quantiles:?}");
+        assert!((neutral.value((), Bet::Safe) - 1.0).abs() < 0.5);
+        assert_eq!(neutral.policy(&Gamble, &()), Bet::Risky);
+
+        let averse = trained(Risk::cvar(0.5).unwrap());
+        assert_eq!(averse.policy(&Gamble, &()), Bet::Safe);
+        assert!(QuantileAgent::<Gamble>::new(QuantileAgentConfig {
+            quantiles: 0,
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+
+use crate::{
+    decay::{self, Decay},
+    distributions::PolicyStats,
+    env::{DiscreteActionSpace, Environment},
+    error::{check_interval, Result},
+    exploration::{max_by_random, Softmax},
+    memory::Exp,
+    traits::{Agent, Hyperparam},
+};
+
+use super::Hashable;
+
+/// The lowest temperature used, so soft values stay finite as a schedule decays towards zero
+const MIN_TEMPERATURE: f32 = 1e-6;
+
+/// Configuration for the [`SoftQAgent`]
+#[derive(Debug, Clone)]
+pub struct SoftQAgentConfig<D: Decay = decay::Exponential> {
+    /// The temperature over episodes, which weighs the entropy of the policy against its return
+    ///
+    /// **Default:** [`Exponential`](decay::Exponential) decay with decay rate `1e-2`, start value `1.0`, and end value
+    /// `0.05`
+    pub temperature: D,
+    pub alpha: f32,
+    pub gamma: f32,
+    /// The step limit of episodes run with [`go`](Agent::go)
+    ///
+    /// **Default:** `None`
+    pub max_episode_steps: Option<u64>,
+}
+
+impl Default for SoftQAgentConfig {
+    fn default() -> Self {
+        Self {
+            temperature: decay::Exponential::new(0.01, 1.0, 0.05).unwrap(),
+            alpha: 0.5,
+            gamma: 0.99,
+            max_episode_steps: None,
+        }
+    }
+}
+
+/// A tabular soft Q-learning agent, which learns the Q values of an entropy-regularized objective
+///
+/// Instead of the maximum Q value of the next state, targets bootstrap from its soft value, the log-sum-exp
+/// `τ ln Σ exp(Q(s', a) / τ)` at temperature `τ`, which adds the entropy of the Boltzmann policy to the return.
+/// Actions are sampled from that policy, `π(a | s) ∝ exp(Q(s, a) / τ)`, so exploration follows the differences
+/// between the values instead of being uniform as with epsilon-greedy. As the temperature decays the soft value
+/// approaches the maximum and the agent approaches Q-learning. This is the tabular counterpart of the soft
+/// Bellman backup of SAC.
+///
+/// The temperature and the [`PolicyStats`] of the updates, the entropy of the policy in the updated state and its
+/// KL divergence from the policy before the update, are reported as the agent's [`metrics`](Agent::metrics).
+///
+/// ### Generics
+/// - `E` - The [`Environment`] in which the agent will learn, with discrete states and actions as for a
+///   [`QTableAgent`](super::q_table::QTableAgent)
+/// - `D` - The [`Decay`] of the temperature
+#[derive(Debug, Clone)]
+pub struct SoftQAgent<E, D: Decay = decay::Exponential>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable,
+    E::Action: Hashable,
+{
+    q_table: HashMap<(E::State, E::Action), f32>,
+    softmax: Softmax<D>,
+    alpha: f32,
+    gamma: f32,
+    max_episode_steps: Option<u64>,
+    episode: u64,
+    policy_stats: PolicyStats,
+}
+
+impl<E, D> SoftQAgent<E, D>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable,
+    E::Action: Hashable,
+    D: Decay,
+{
+    /// Initialize an agent with an empty Q-table
+    ///
+    /// **Returns** [`RlError::OutOfRange`](crate::error::RlError::OutOfRange) if `alpha` or `gamma` is not in the
+    /// interval `[0,1]`
+    pub fn new(config: SoftQAgentConfig<D>) -> Result<Self> {
+        check_interval("alpha", config.alpha, 0.0, 1.0)?;
+        check_interval("gamma", config.gamma, 0.0, 1.0)?;
+        Ok(Self {
+            q_table: HashMap::new(),
+            softmax: Softmax::new(config.temperature),
+            alpha: config.alpha,
+            gamma: config.gamma,
+            max_episode_steps: config.max_episode_steps,
+            episode: 0,
+            policy_stats: PolicyStats::new(),
+        })
+    }
+
+    /// Get the Q-table
+    pub fn get_q_table(&self) -> &HashMap<(E::State, E::Action), f32> {
+        &self.q_table
+    }
+
+    /// The Q value of `(state, action)`, `0` if it hasn't been learned yet
+    pub fn q_value(&self, state: E::State, action: E::Action) -> f32 {
+        self.q_table
+            .get(&(state, action))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The current temperature
+    pub fn temperature(&self) -> f32 {
+        self.softmax
+            .temperature(self.episode as f32)
+            .max(MIN_TEMPERATURE)
+    }
+
+    /// The soft value of `state`, `τ ln Σ exp(Q(state, a) / τ)` over `actions`
+    pub fn soft_value(&self, state: E::State, actions: &[E::Action]) -> f32 {
+        let tau = self.temperature();
+        let q_values = actions.iter().map(|&a| self.q_value(state, a));
+        let max = q_values.clone().fold(f32::NEG_INFINITY, f32::max);
+        let sum = q_values.map(|q| ((q - max) / tau).exp()).sum::<f32>();
+        max + tau * sum.ln()
+    }
+
+    /// The probability of each of `actions` in `state` under the Boltzmann policy
+    pub fn action_probs(&self, state: E::State, actions: &[E::Action]) -> Vec<f32> {
+        let tau = self.temperature();
+        let soft_value = self.soft_value(state, actions);
+        actions
+            .iter()
+            .map(|&a| ((self.q_value(state, a) - soft_value) / tau).exp())
+            .collect()
+    }
+
+    /// The log-probability of each of `actions` in `state`, which stays finite where the probability underflows
+    fn log_probs(&self, state: E::State, actions: &[E::Action]) -> Vec<f64> {
+        let tau = self.temperature() as f64;
+        let soft_value = self.soft_value(state, actions) as f64;
+        actions
+            .iter()
+            .map(|&a| (self.q_value(state, a) as f64 - soft_value) / tau)
+            .collect()
+    }
+}
+
+impl<E, D> Agent<E> for SoftQAgent<E, D>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable,
+    E::Action: Hashable,
+    D: Decay,
+{
+    /// Sample an action from the Boltzmann policy of the current temperature
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action {
+        let actions = env.actions();
+        let q_values = actions
+            .iter()
+            .map(|&a| self.q_value(*state, a))
+            .collect::<Vec<_>>();
+        actions[self.softmax.choose(self.episode as f32, &q_values)]
+    }
+
+    /// Move the Q value of the experience towards the reward and the discounted soft value of the next state
+    fn learn(&mut self, env: &E, experience: Exp<E>) {
+        let Exp {
+            state,
+            action,
+            next_state,
+            reward,
+        } = experience;
+
+        let actions = env.actions();
+        // Terminal states have no future rewards
+        let next_value = next_state.map_or(0.0, |s| self.soft_value(s, &actions));
+        let old = self.log_probs(state, &actions);
+        let q_value = self.q_value(state, action);
+        let target = reward + self.gamma * next_value;
+        self.q_table
+            .insert((state, action), q_value + self.alpha * (target - q_value));
+
+        // The update only changes the policy in the updated state
+        let new = self.log_probs(state, &actions);
+        let entropy = -new.iter().map(|&l| l.exp() * l).sum::<f64>();
+        let kl = old
+            .iter()
+            .zip(&new)
+            .map(|(&l_old, &l_new)| l_old.exp() * (l_old - l_new))
+            .sum::<f64>();
+        self.policy_stats.record(entropy, kl);
+    }
+
+    fn on_episode_end(&mut self) {
+        self.episode += 1;
+    }
+
+    fn metrics(&mut self) -> Vec<(&'static str, f64)> {
+        let mut metrics = vec![("temperature", self.temperature() as f64)];
+        metrics.extend(self.policy_stats.take());
+        metrics
+    }
+
+    /// The discount factor `gamma`
+    fn hyperparams(&self) -> Vec<Hyperparam> {
+        vec![Hyperparam::gamma(self.gamma)]
+    }
+
+    fn set_hyperparam(&mut self, name: &str, value: f64) -> bool {
+        match name {
+            "gamma" if (0.0..=1.0).contains(&value) => self.gamma = value as f32,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Choose the action with the highest Q value, breaking ties at random
+    fn policy(&self, env: &E, state: &E::State) -> E::Action {
+        *max_by_random(&env.actions(), |&a, &b| {
+            self.q_value(*state, *a)
+                .total_cmp(&self.q_value(*state, *b))
+        })
+        .expect("There is always at least one action available")
+    }
+
+    fn max_episode_steps(&self) -> Option<u64> {
+        self.max_episode_steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        seed::Seeds,
+        testing::{assert_converges_to, Corridor},
+    };
+
+    #[test]
+    fn soft_q_converges() {
+        Seeds::new(0).apply();
+        let mut env = Corridor::new(4);
+        let expected = env.optimal_return();
+        let mut agent = SoftQAgent::new(SoftQAgentConfig::default()).unwrap();
+        assert_converges_to(&mut agent, &mut env, 400, expected, 0.0);
+
+        let metrics = agent.metrics();
+        let metric = |name: &str| {
+            metrics
+                .iter()
+                .find(|(metric, _)| *metric == name)
+                .unwrap_or_else(|| panic!("{name} in {metrics:?}"))
+                .1
+        };
+        assert!(
+            metric("temperature") < 0.1,
+            "temperature decayed: {metrics:?}"
+        );
+        let entropy = metric("policy_entropy");
+        assert!(entropy > 0.0 && entropy < 2f64.ln(), "{metrics:?}");
+        assert!(metric("policy_kl") >= 0.0 && metric("policy_kl_max") >= metric("policy_kl"));
+    }
+}
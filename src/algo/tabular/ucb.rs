@@ -1,8 +1,14 @@
 use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use std::{io, path::Path};
 
+#[cfg(feature = "serde")]
+use crate::traits::{checkpoint, Checkpoint};
 use crate::{
     env::{DiscreteActionSpace, Environment},
+    exploration::max_by_random,
     memory::Exp,
+    traits::Agent,
 };
 
 use super::Hashable;
@@ -36,6 +42,7 @@ impl Default for UCBAgentConfig {
 
 /// An entry in the table
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Entry {
     value: f32,
     count: u32,
@@ -56,8 +63,8 @@ where
     ucb_c: f32,
     default_action_value: f32,
     alpha_fn: fn(u32) -> f32,
-    t: u32,
-    episode: u32,
+    t: u64,
+    episode: u64,
 }
 
 impl<E> UCBAgent<E>
@@ -78,21 +85,32 @@ where
         }
     }
 
+    /// Get the table entry for a state-action pair, or a default entry if it has not been visited yet
+    fn entry(&self, state: E::State, action: E::Action) -> Entry {
+        self.table.get(&(state, action)).copied().unwrap_or(Entry {
+            value: self.default_action_value,
+            count: 0,
+        })
+    }
+}
+
+impl<E> Agent<E> for UCBAgent<E>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable,
+    E::Action: Hashable + From<usize>,
+{
     /// Choose an action based on the current state
-    fn act(&self, state: E::State, actions: &[E::Action]) -> E::Action {
-        let action_entries = actions
-            .iter()
-            .map(|&action| {
-                self.table.get(&(state, action)).copied().unwrap_or(Entry {
-                    value: self.default_action_value,
-                    count: 0,
-                })
-            })
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action {
+        let action_entries = env
+            .actions()
+            .into_iter()
+            .map(|action| self.entry(*state, action))
             .collect::<Vec<_>>();
 
         let t = (self.t + 1) as f32;
         let k = self.ucb_c * t.ln().sqrt();
-        let choice = action_entries
+        let bounds = action_entries
             .iter()
             .enumerate()
             .map(|(i, &Entry { value: q, count })| {
@@ -101,8 +119,9 @@ where
                     return (i, f32::MAX);
                 }
                 (i, q + k * n.powf(-0.5))
-            })
-            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            });
+        // Unvisited actions are tied at the maximum, so they are tried in a random order
+        let choice = max_by_random(bounds, |(_, a), (_, b)| a.total_cmp(b))
             .map(|(i, _)| i)
             .expect("`q_values` is not empty");
 
@@ -110,7 +129,7 @@ where
     }
 
     /// Learn from a given experience and update the table
-    fn learn(&mut self, experience: Exp<E>) {
+    fn learn(&mut self, _env: &E, experience: Exp<E>) {
         let Exp {
             state,
             action,
@@ -128,28 +147,59 @@ where
                 value: reward,
                 count: 1,
             });
+
+        self.t += 1;
     }
 
-    /// Run the agent in the given environment
-    pub fn go(&mut self, env: &mut E) {
-        let mut next_state = Some(env.reset());
-        let mut actions = env.actions();
-        while let Some(state) = next_state {
-            let action = self.act(state, &actions);
-            let (next, reward) = env.step(action);
-            next_state = next;
-            actions = env.actions();
-
-            self.learn(Exp {
-                state,
-                action,
-                next_state,
-                reward,
-            });
+    fn on_episode_end(&mut self) {
+        self.episode += 1;
+    }
 
-            self.t += 1;
-        }
+    fn policy(&self, env: &E, state: &E::State) -> E::Action {
+        max_by_random(env.actions(), |&a, &b| {
+            let a_value = self.entry(*state, a).value;
+            let b_value = self.entry(*state, b).value;
+            a_value.total_cmp(&b_value)
+        })
+        .expect("There is always at least one action available")
+    }
+}
 
-        self.episode += 1;
+/// The learned state of a [`UCBAgent`], as written by [`Checkpoint::save`]
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct UCBAgentState<S, A> {
+    table: Vec<(S, A, Entry)>,
+    t: u64,
+    episode: u64,
+}
+
+/// Checkpoints are single JSON files
+#[cfg(feature = "serde")]
+impl<E> Checkpoint for UCBAgent<E>
+where
+    E: Environment + DiscreteActionSpace,
+    E::State: Hashable + serde::Serialize + serde::de::DeserializeOwned,
+    E::Action: Hashable + serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let state = UCBAgentState {
+            table: self.table.iter().map(|(&(s, a), &e)| (s, a, e)).collect(),
+            t: self.t,
+            episode: self.episode,
+        };
+        checkpoint::write_json(path, &state)
+    }
+
+    fn load(&mut self, path: &Path) -> io::Result<()> {
+        let state: UCBAgentState<E::State, E::Action> = checkpoint::read_json(path)?;
+        self.table = state
+            .table
+            .into_iter()
+            .map(|(s, a, e)| ((s, a), e))
+            .collect();
+        self.t = state.t;
+        self.episode = state.episode;
+        Ok(())
     }
 }
@@ -0,0 +1,280 @@
+use std::cell::Cell;
+
+use crate::{
+    env::{DiscreteActionSpace, Environment},
+    memory::Exp,
+    traits::Agent,
+};
+
+/// An agent that always takes a [random action](Environment::random_action), the lower reference line of a
+/// learning curve
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomAgent;
+
+impl<E: Environment> Agent<E> for RandomAgent {
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action {
+        self.policy(env, state)
+    }
+
+    fn learn(&mut self, _env: &E, _experience: Exp<E>) {}
+
+    fn policy(&self, env: &E, _state: &E::State) -> E::Action {
+        env.random_action()
+    }
+}
+
+/// An agent that always takes the same action, e.g. always pushing a [`CartPole`](crate::gym::CartPole) left
+///
+/// ```ignore
+/// let always_left = ConstantAgent(CPAction::Left);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConstantAgent<A>(pub A);
+
+impl<E: Environment<Action = A>, A: Clone> Agent<E> for ConstantAgent<A> {
+    fn act(&mut self, _env: &E, _state: &E::State) -> E::Action {
+        self.0.clone()
+    }
+
+    fn learn(&mut self, _env: &E, _experience: Exp<E>) {}
+
+    fn policy(&self, _env: &E, _state: &E::State) -> E::Action {
+        self.0.clone()
+    }
+}
+
+/// Configuration for the [`PidAgent`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PidConfig {
+    /// The gain of the error
+    ///
+    /// **Default:** `1.0`
+    pub kp: f32,
+    /// The gain of the sum of the errors over time
+    ///
+    /// **Default:** `0.0`
+    pub ki: f32,
+    /// The gain of the change of the error over time
+    ///
+    /// **Default:** `0.0`
+    pub kd: f32,
+    /// The time between two steps of the environment
+    ///
+    /// **Default:** `1.0`
+    pub dt: f32,
+}
+
+impl Default for PidConfig {
+    fn default() -> Self {
+        Self {
+            kp: 1.0,
+            ki: 0.0,
+            kd: 0.0,
+            dt: 1.0,
+        }
+    }
+}
+
+/// A proportional-integral-derivative controller, the classic scripted policy of control tasks
+///
+/// The controller computes an error from each state, e.g. the angle of a pole, and the control signal
+/// `kp * e + ki * ∫e dt + kd * de/dt`, which is mapped to an action, e.g. a force or its sign. The integral and the
+/// previous error are reset at the start of every episode.
+///
+/// ```ignore
+/// // A torque towards the upright position of a pendulum with the state [cos θ, sin θ, θ̇]
+/// let agent = PidAgent::new(
+///     PidConfig { kp: 10.0, kd: 1.0, dt: 0.05, ..Default::default() },
+///     |&[cos, sin, _]: &[f32; 3]| -sin.atan2(cos),
+///     |u| ContinuousAction([u.clamp(-2.0, 2.0)]),
+/// );
+/// ```
+///
+/// ### Generics
+/// - `E` - The controlled [`Environment`]
+#[derive(Debug, Clone)]
+pub struct PidAgent<E: Environment> {
+    config: PidConfig,
+    error: fn(&E::State) -> f32,
+    control: fn(f32) -> E::Action,
+    /// The integral of the error and the previous error of the episode
+    memory: Cell<(f32, Option<f32>)>,
+}
+
+impl<E: Environment> PidAgent<E> {
+    /// A controller of the error computed by `error`, mapping the control signal to an action with `control`
+    pub fn new(
+        config: PidConfig,
+        error: fn(&E::State) -> f32,
+        control: fn(f32) -> E::Action,
+    ) -> Self {
+        Self {
+            config,
+            error,
+            control,
+            memory: Cell::new((0.0, None)),
+        }
+    }
+
+    /// The control signal in `state`, updating the integral and previous error
+    fn signal(&self, state: &E::State) -> f32 {
+        let PidConfig { kp, ki, kd, dt } = self.config;
+        let error = (self.error)(state);
+        let (integral, previous) = self.memory.get();
+        let integral = integral + error * dt;
+        let derivative = previous.map_or(0.0, |previous| (error - previous) / dt);
+        self.memory.set((integral, Some(error)));
+        kp * error + ki * integral + kd * derivative
+    }
+}
+
+#[cfg(feature = "gym")]
+impl PidAgent<crate::gym::CartPole> {
+    /// A controller pushing the cart under the pole, from the angle of the pole and its change
+    pub fn cart_pole() -> Self {
+        use crate::gym::cart_pole::CPAction;
+
+        Self::new(
+            PidConfig {
+                kp: 1.0,
+                kd: 0.5,
+                dt: 0.02,
+                ..Default::default()
+            },
+            |state| state[2],
+            |u| {
+                if u > 0.0 {
+                    CPAction::Right
+                } else {
+                    CPAction::Left
+                }
+            },
+        )
+    }
+}
+
+impl<E: Environment> Agent<E> for PidAgent<E> {
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action {
+        self.policy(env, state)
+    }
+
+    fn learn(&mut self, _env: &E, _experience: Exp<E>) {}
+
+    fn on_episode_end(&mut self) {
+        self.reset_policy();
+    }
+
+    fn reset_policy(&self) {
+        self.memory.set((0.0, None));
+    }
+
+    fn policy(&self, _env: &E, state: &E::State) -> E::Action {
+        (self.control)(self.signal(state))
+    }
+}
+
+/// An agent that takes the action with the highest score of a heuristic, e.g. the one moving closest to the goal of
+/// a gridworld
+///
+/// Ties go to the first of the [actions](DiscreteActionSpace::actions).
+///
+/// ### Generics
+/// - `E` - The [`Environment`] in which the agent acts
+#[derive(Debug, Clone)]
+pub struct GreedyAgent<E: Environment> {
+    score: fn(&E, &E::State, &E::Action) -> f32,
+}
+
+impl<E: DiscreteActionSpace> GreedyAgent<E> {
+    /// Take the action with the highest `score(env, state, action)`
+    pub fn new(score: fn(&E, &E::State, &E::Action) -> f32) -> Self {
+        Self { score }
+    }
+}
+
+#[cfg(feature = "gym")]
+impl GreedyAgent<crate::gym::WindyGridworld> {
+    /// Move as close to the goal as possible, ignoring the wind
+    pub fn windy_gridworld() -> Self {
+        Self::new(|env, &(x, y), action| {
+            let (dx, dy) = action.delta();
+            let (goal_x, goal_y) = env.goal();
+            -((x + dx - goal_x).abs().max((y + dy - goal_y).abs()) as f32)
+        })
+    }
+}
+
+impl<E: DiscreteActionSpace> Agent<E> for GreedyAgent<E> {
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action {
+        self.policy(env, state)
+    }
+
+    fn learn(&mut self, _env: &E, _experience: Exp<E>) {}
+
+    /// **Panics** if the environment has no actions
+    fn policy(&self, env: &E, state: &E::State) -> E::Action {
+        env.actions()
+            .into_iter()
+            .map(|action| ((self.score)(env, state, &action), action))
+            .reduce(|best, next| if next.0 > best.0 { next } else { best })
+            .expect("There is at least one action")
+            .1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        env::ContinuousAction,
+        seed::Seeds,
+        testing::{greedy_return, Corridor, CorridorAction, Point},
+    };
+
+    #[test]
+    fn baselines() {
+        Seeds::new(0).apply();
+        let mut env = Corridor::new(4);
+        let greedy =
+            GreedyAgent::new(|_, _, action| (*action == CorridorAction::Right) as u8 as f32);
+        assert_eq!(greedy_return(&greedy, &mut env), Some(env.optimal_return()));
+        assert_eq!(
+            greedy_return(&ConstantAgent(CorridorAction::Left), &mut env),
+            None
+        );
+        let random = greedy_return(&RandomAgent, &mut env).expect("A random walk reaches the end");
+        assert!(random <= env.optimal_return());
+
+        let pid = PidAgent::<Point>::new(
+            PidConfig {
+                kp: 1.0,
+                ..Default::default()
+            },
+            |state| -state[0],
+            |u| ContinuousAction([u]),
+        );
+        assert_eq!(
+            greedy_return(&pid, &mut Point::new(1.0)),
+            Some(-(1.0 - 0.5f32.powi(10))),
+            "Halves the distance every step"
+        );
+        let pd = PidAgent::<Point>::new(
+            PidConfig {
+                kp: 0.5,
+                kd: 0.25,
+                ..Default::default()
+            },
+            |state| -state[0],
+            |u| ContinuousAction([u]),
+        );
+        let env = Point::new(0.0);
+        assert_eq!(pd.policy(&env, &[1.0]).0[0], -0.5);
+        assert_eq!(
+            pd.policy(&env, &[0.5]).0[0],
+            -0.125,
+            "Damped by the shrinking error"
+        );
+        pd.reset_policy();
+        assert_eq!(pd.policy(&env, &[0.5]).0[0], -0.25);
+    }
+}
@@ -0,0 +1,643 @@
+use std::{
+    fmt, fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use crate::{
+    config::{AlgoConfig, EnvConfig, EvalConfig, ExperimentConfig, LoggingConfig, TrainConfig},
+    csv::{escape_csv, split_csv},
+    error::RlError,
+    logger::MetricSink,
+    stats::RunningMeanVar,
+    train::SampleEfficiency,
+};
+
+/// One algorithm and environment pair of a [`Benchmark`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Case {
+    /// The name of the case in the comparison
+    pub name: String,
+    /// The experiment to train, whose seed is replaced in each run
+    pub config: ExperimentConfig,
+    /// The mean training return over the [window](Benchmark::with_window) at which the task counts as learned
+    pub target: Option<f64>,
+}
+
+impl Case {
+    /// A case for the experiment of `config`, named after its algorithm and environment, e.g.
+    /// `q-learning/frozen-lake`
+    pub fn new(config: ExperimentConfig) -> Self {
+        Self {
+            name: format!("{}/{}", config.algo.name(), config.env.name()),
+            config,
+            target: None,
+        }
+    }
+
+    /// Set the name of the case
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Report the number of steps until the mean training return reaches `target`
+    pub fn with_target(mut self, target: f64) -> Self {
+        self.target = Some(target);
+        self
+    }
+}
+
+/// Trains a fixed matrix of algorithms and environments with several seeds and compares their learning performance
+///
+/// Each case is trained once per seed, one run after the other so wall-clock times are comparable. The results
+/// report sample efficiency, as the mean training return over all episodes and the steps until a case's target is
+/// reached, the final and best evaluated returns, and the wall-clock time, each as the mean and standard deviation
+/// over the seeds. Writing the results to CSV and passing the file of an earlier run to
+/// [`BenchResults::regressions`] turns a benchmark into a regression suite for learning performance.
+///
+/// ### Example
+/// ```no_run
+/// use rl::bench::Benchmark;
+///
+/// let results = Benchmark::standard().with_seeds(0..3).run();
+/// println!("{results}");
+/// results.write_csv("bench.csv").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Benchmark {
+    cases: Vec<Case>,
+    seeds: Vec<u64>,
+    window: usize,
+}
+
+impl Default for Benchmark {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Benchmark {
+    /// Create a benchmark without cases
+    ///
+    /// **Default:** seeds `0..5` and a window of 100 episodes
+    pub fn new() -> Self {
+        Self {
+            cases: Vec::new(),
+            seeds: (0..5).collect(),
+            window: 100,
+        }
+    }
+
+    /// The built-in matrix: every tabular algorithm in every environment it supports, with default hyperparameters,
+    /// 1000 training episodes of at most 1000 steps and an evaluation of 20 episodes every 100 episodes
+    pub fn standard() -> Self {
+        let envs = [
+            EnvConfig::FrozenLake,
+            EnvConfig::GrassyField,
+            EnvConfig::KArmedBandit {
+                step_limit: 100,
+                stationary: true,
+            },
+            EnvConfig::WindyGridworld,
+        ];
+        let algos = ["q-learning", "action-occurrence", "ucb"].map(|name| {
+            name.parse::<AlgoConfig>()
+                .expect("built-in algorithms have names")
+        });
+        let train = TrainConfig {
+            episodes: 1000,
+            max_episode_steps: Some(1000),
+            eval: Some(EvalConfig {
+                interval: 100,
+                episodes: 20,
+            }),
+            ..Default::default()
+        };
+
+        let mut benchmark = Self::new();
+        for env in &envs {
+            for algo in &algos {
+                // UCB only supports environments whose actions are indices
+                if matches!(algo, AlgoConfig::Ucb { .. })
+                    && !matches!(env, EnvConfig::KArmedBandit { .. })
+                {
+                    continue;
+                }
+                benchmark = benchmark.with_case(Case::new(experiment(
+                    env.clone(),
+                    algo.clone(),
+                    train.clone(),
+                )));
+            }
+        }
+        benchmark
+    }
+
+    /// Add a case to the matrix
+    pub fn with_case(mut self, case: Case) -> Self {
+        self.cases.push(case);
+        self
+    }
+
+    /// Add a case for every combination of `algos` and `envs`, trained with `train`
+    ///
+    /// Combinations the algorithm doesn't support are reported as failed runs.
+    pub fn with_matrix(
+        mut self,
+        algos: &[AlgoConfig],
+        envs: &[EnvConfig],
+        train: &TrainConfig,
+    ) -> Self {
+        for env in envs {
+            for algo in algos {
+                let config = experiment(env.clone(), algo.clone(), train.clone());
+                self.cases.push(Case::new(config));
+            }
+        }
+        self
+    }
+
+    /// Set the seeds each case is trained with
+    ///
+    /// **Panics** if there are no seeds
+    pub fn with_seeds(mut self, seeds: impl IntoIterator<Item = u64>) -> Self {
+        self.seeds = seeds.into_iter().collect();
+        assert!(
+            !self.seeds.is_empty(),
+            "A benchmark needs at least one seed"
+        );
+        self
+    }
+
+    /// Set the number of training episodes averaged for the final return and the steps to target
+    ///
+    /// **Panics** if `episodes` is 0
+    pub fn with_window(mut self, episodes: usize) -> Self {
+        assert!(episodes > 0, "The window must contain at least one episode");
+        self.window = episodes;
+        self
+    }
+
+    /// Train every case with every seed
+    ///
+    /// Runs that fail, e.g. because the algorithm doesn't support the environment, are recorded with their error
+    /// instead of stopping the benchmark.
+    pub fn run(&self) -> BenchResults {
+        let cases = self
+            .cases
+            .iter()
+            .map(|case| CaseResult {
+                name: case.name.clone(),
+                algorithm: case.config.algo.name(),
+                env: case.config.env.name(),
+                runs: self
+                    .seeds
+                    .iter()
+                    .map(|&seed| self.run_case(case, seed).map_err(|e| Arc::new(e.into())))
+                    .collect(),
+            })
+            .collect();
+
+        BenchResults { cases }
+    }
+
+    /// Train `case` with `seed`, without any of the outputs of its config
+    fn run_case(&self, case: &Case, seed: u64) -> io::Result<Run> {
+        let mut config = case.config.clone();
+        config.seed = Some(seed);
+        config.logging = LoggingConfig::default();
+        config.train.checkpoint = None;
+        config.run_dir = None;
+        config.train.sample_efficiency = case
+            .target
+            .map(|target| SampleEfficiency::new(vec![target]).with_window(self.window));
+
+        let episodes = Arc::new(Mutex::new(Vec::new()));
+        let sink = Episodes(Arc::clone(&episodes));
+        let mut experiment = config.build()?.with_sink(Box::new(sink));
+
+        let start = Instant::now();
+        let summary = experiment.train()?;
+        let seconds = start.elapsed().as_secs_f64();
+
+        let returns = episodes.lock().unwrap();
+        let window = &returns[returns.len().saturating_sub(self.window)..];
+
+        Ok(Run {
+            seed,
+            episodes: summary.episodes,
+            steps: summary.steps,
+            mean_return: mean(&returns),
+            final_return: mean(window),
+            best_eval: summary.best.map(|(_, eval)| eval.mean),
+            steps_to_target: summary.steps_to_threshold.first().map(|&(_, steps)| steps),
+            seconds,
+        })
+    }
+}
+
+/// The training run of one case with one seed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run {
+    /// The seed of the run
+    pub seed: u64,
+    /// The number of training episodes
+    pub episodes: u64,
+    /// The number of training steps
+    pub steps: u64,
+    /// The mean return over all training episodes, i.e. the normalized area under the learning curve
+    pub mean_return: f64,
+    /// The mean return of the training episodes in the window at the end of the run
+    pub final_return: f64,
+    /// The mean return of the best evaluation, if evaluation is enabled
+    pub best_eval: Option<f64>,
+    /// The number of steps after which the mean return of the last window of episodes first reached the target of
+    /// the case, if it did
+    pub steps_to_target: Option<u64>,
+    /// The wall-clock time of training in seconds
+    pub seconds: f64,
+}
+
+/// The runs of one [`Case`]
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    /// The name of the case
+    pub name: String,
+    /// The name of the algorithm, e.g. `q-learning`
+    pub algorithm: &'static str,
+    /// The name of the environment, e.g. `frozen-lake`
+    pub env: &'static str,
+    /// The run or the error that stopped it for every seed, in order
+    ///
+    /// The errors are shared so results can be cloned.
+    pub runs: Vec<Result<Run, Arc<RlError>>>,
+}
+
+impl CaseResult {
+    /// The mean and standard deviation of `metric` over the successful runs that have it
+    pub fn stats(&self, metric: impl Fn(&Run) -> Option<f64>) -> Option<(f64, f64)> {
+        let stats = self
+            .runs
+            .iter()
+            .flatten()
+            .filter_map(metric)
+            .collect::<RunningMeanVar>();
+        (stats.count() > 0).then(|| (stats.mean(), stats.std()))
+    }
+
+    /// The number of successful runs
+    pub fn succeeded(&self) -> usize {
+        self.runs.iter().flatten().count()
+    }
+}
+
+/// The outcome of [`Benchmark::run`]
+///
+/// Displays as a markdown table comparing the cases.
+#[derive(Debug, Clone)]
+pub struct BenchResults {
+    /// Every case, in order
+    pub cases: Vec<CaseResult>,
+}
+
+/// The metrics compared by [`BenchResults`], with the column names of their mean and standard deviation
+const METRICS: [(&str, fn(&Run) -> Option<f64>); 5] = [
+    ("final_return", |run| Some(run.final_return)),
+    ("mean_return", |run| Some(run.mean_return)),
+    ("best_eval", |run| run.best_eval),
+    ("steps_to_target", |run| {
+        run.steps_to_target.map(|s| s as f64)
+    }),
+    ("seconds", |run| Some(run.seconds)),
+];
+
+impl BenchResults {
+    /// The comparison as a markdown table, with `mean ± std` cells
+    ///
+    /// The runs column counts the successful runs and the ones that reached the target, if the case has one.
+    pub fn to_markdown(&self) -> String {
+        let mut table = String::from(
+            "| case | runs | final return | mean return | best eval | steps to target | seconds |\n\
+             |---|---|---|---|---|---|---|\n",
+        );
+        for case in &self.cases {
+            let mut cells = vec![case.name.clone(), runs_cell(case)];
+            cells.extend(METRICS.iter().map(|(_, metric)| match case.stats(metric) {
+                Some((mean, std)) => format!("{} ± {}", format_stat(mean), format_stat(std)),
+                None => "-".to_string(),
+            }));
+            table.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+        table
+    }
+
+    /// Write the comparison to a CSV file at `path`
+    ///
+    /// The columns are the case, algorithm and environment, the numbers of runs, failed runs and runs that reached
+    /// the target, then the mean and standard deviation of each metric, e.g. `final_return_mean` and
+    /// `final_return_std`. Metrics without a value are left empty.
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        let mut header = ["case", "algorithm", "env", "runs", "failed", "reached"]
+            .map(String::from)
+            .to_vec();
+        for (name, _) in METRICS {
+            header.extend([format!("{name}_mean"), format!("{name}_std")]);
+        }
+        writeln!(writer, "{}", header.join(","))?;
+
+        for case in &self.cases {
+            let reached = case
+                .runs
+                .iter()
+                .flatten()
+                .filter(|run| run.steps_to_target.is_some());
+            let mut row = vec![
+                escape_csv(&case.name),
+                case.algorithm.to_string(),
+                case.env.to_string(),
+                case.runs.len().to_string(),
+                (case.runs.len() - case.succeeded()).to_string(),
+                reached.count().to_string(),
+            ];
+            for (_, metric) in METRICS {
+                match case.stats(metric) {
+                    Some((mean, std)) => row.extend([mean.to_string(), std.to_string()]),
+                    None => row.extend([String::new(), String::new()]),
+                }
+            }
+            writeln!(writer, "{}", row.join(","))?;
+        }
+        writer.flush()
+    }
+
+    /// Compare the final returns with those of an earlier run, written to `baseline` with
+    /// [`write_csv`](BenchResults::write_csv)
+    ///
+    /// A case has regressed if its final return is lower than in the baseline by more than the sum of both standard
+    /// deviations, so differences within the noise between seeds don't count. Cases missing from either run are
+    /// skipped.
+    ///
+    /// **Returns** the regressed cases, or an error if the baseline can't be read
+    pub fn regressions(&self, baseline: impl AsRef<Path>) -> io::Result<Vec<Regression>> {
+        let contents = fs::read_to_string(baseline)?;
+        let mut lines = contents.lines();
+        let header = lines
+            .next()
+            .unwrap_or_default()
+            .split(',')
+            .collect::<Vec<_>>();
+        let column = |name: &str| {
+            header.iter().position(|&c| c == name).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("the baseline has no `{name}` column"),
+                )
+            })
+        };
+        let (mean_col, std_col) = (column("final_return_mean")?, column("final_return_std")?);
+
+        let mut regressions = Vec::new();
+        for line in lines {
+            let cells = split_csv(line);
+            let baseline = cells
+                .get(mean_col)
+                .zip(cells.get(std_col))
+                .and_then(|(mean, std)| {
+                    Some((mean.parse::<f64>().ok()?, std.parse::<f64>().ok()?))
+                });
+            let Some(baseline) = baseline else {
+                continue;
+            };
+            let Some(case) = self.cases.iter().find(|case| case.name == cells[0]) else {
+                continue;
+            };
+            let Some(current) = case.stats(|run| Some(run.final_return)) else {
+                continue;
+            };
+            if current.0 < baseline.0 - baseline.1 - current.1 {
+                regressions.push(Regression {
+                    case: case.name.clone(),
+                    current,
+                    baseline,
+                });
+            }
+        }
+        Ok(regressions)
+    }
+}
+
+impl fmt::Display for BenchResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_markdown())
+    }
+}
+
+/// A case whose final return dropped compared to a baseline, see [`BenchResults::regressions`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    /// The name of the case
+    pub case: String,
+    /// The mean and standard deviation of the final return
+    pub current: (f64, f64),
+    /// The mean and standard deviation of the final return in the baseline
+    pub baseline: (f64, f64),
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: final return {} ± {}, baseline {} ± {}",
+            self.case,
+            format_stat(self.current.0),
+            format_stat(self.current.1),
+            format_stat(self.baseline.0),
+            format_stat(self.baseline.1),
+        )
+    }
+}
+
+/// Records the return of every training episode
+struct Episodes(Arc<Mutex<Vec<f64>>>);
+
+impl MetricSink for Episodes {
+    fn log_scalar(&mut self, _name: &str, _value: f64, _step: u64) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn log_episode(&mut self, _episode: u64, metrics: &[(&str, f64)]) -> io::Result<()> {
+        if let Some(&(_, ret)) = metrics.iter().find(|(name, _)| *name == "return") {
+            self.0.lock().unwrap().push(ret);
+        }
+        Ok(())
+    }
+}
+
+/// An experiment without outputs
+fn experiment(env: EnvConfig, algo: AlgoConfig, train: TrainConfig) -> ExperimentConfig {
+    ExperimentConfig {
+        env,
+        algo,
+        train,
+        logging: LoggingConfig::default(),
+        run_dir: None,
+        seed: None,
+        deterministic: false,
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len().max(1) as f64
+}
+
+/// Successful runs out of all runs, followed by the runs that reached the target if any did
+fn runs_cell(case: &CaseResult) -> String {
+    let reached = case
+        .runs
+        .iter()
+        .flatten()
+        .filter(|run| run.steps_to_target.is_some())
+        .count();
+    match reached {
+        0 => format!("{}/{}", case.succeeded(), case.runs.len()),
+        reached => format!(
+            "{}/{} ({reached} reached)",
+            case.succeeded(),
+            case.runs.len()
+        ),
+    }
+}
+
+/// Format a statistic with 3 significant digits
+fn format_stat(value: f64) -> String {
+    match value.abs() {
+        v if v == 0.0 || !v.is_finite() => value.to_string(),
+        v if v >= 100.0 => format!("{value:.0}"),
+        v => {
+            let decimals = (2 - v.log10().floor() as i32).max(0) as usize;
+            format!("{value:.decimals$}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bandit_benchmark() -> Benchmark {
+        let train = TrainConfig {
+            episodes: 20,
+            eval: Some(EvalConfig {
+                interval: 10,
+                episodes: 2,
+            }),
+            ..Default::default()
+        };
+        let algos = ["action-occurrence", "ucb"].map(|name| name.parse::<AlgoConfig>().unwrap());
+        let envs = [EnvConfig::KArmedBandit {
+            step_limit: 10,
+            stationary: true,
+        }];
+        Benchmark::new()
+            .with_matrix(&algos, &envs, &train)
+            .with_case(
+                Case::new(experiment(
+                    envs[0].clone(),
+                    "q-learning".parse().unwrap(),
+                    train,
+                ))
+                .with_name("q-learning, reaching 0")
+                .with_target(f64::MIN),
+            )
+            .with_seeds([1, 2])
+            .with_window(5)
+    }
+
+    #[test]
+    fn benchmark_functional() {
+        let results = bandit_benchmark().run();
+
+        assert_eq!(results.cases.len(), 3);
+        for case in &results.cases {
+            assert_eq!(case.succeeded(), 2, "{:?}", case.runs);
+            let run = case.runs[0].as_ref().unwrap();
+            assert_eq!(run.episodes, 20);
+            assert_eq!(run.steps, 200);
+            assert!(run.best_eval.is_some());
+        }
+        assert_eq!(results.cases[1].name, "ucb/k-armed-bandit");
+        assert_eq!(
+            results.cases[2].runs[0].as_ref().unwrap().steps_to_target,
+            Some(50),
+            "Reached at the end of the first window"
+        );
+        assert!(results
+            .to_markdown()
+            .contains("| ucb/k-armed-bandit | 2/2 |"));
+        assert!(results.to_markdown().contains("| 2/2 (2 reached) |"));
+
+        // The same seeds give the same returns
+        let again = bandit_benchmark().run();
+        for (a, b) in results.cases.iter().zip(&again.cases) {
+            assert_eq!(
+                a.stats(|run| Some(run.final_return)),
+                b.stats(|run| Some(run.final_return))
+            );
+        }
+    }
+
+    #[test]
+    fn regressions_against_baseline() {
+        let dir = std::env::temp_dir().join("rl_bench_regressions");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bench.csv");
+        let results = bandit_benchmark().with_seeds([3]).run();
+        results.write_csv(&path).unwrap();
+
+        let csv = fs::read_to_string(&path).unwrap();
+        assert!(csv.starts_with("case,algorithm,env,runs,failed,reached,final_return_mean"));
+        assert!(csv.contains("\"q-learning, reaching 0\",q-learning,k-armed-bandit,1,0,1,"));
+        assert!(results.regressions(&path).unwrap().is_empty());
+
+        let mut worse = results.clone();
+        for run in worse
+            .cases
+            .iter_mut()
+            .flat_map(|case| &mut case.runs)
+            .flatten()
+        {
+            run.final_return -= 1.0;
+        }
+        let regressions = worse.regressions(&path).unwrap();
+        assert_eq!(regressions.len(), 3);
+        assert_eq!(regressions[2].case, "q-learning, reaching 0");
+    }
+
+    #[test]
+    fn unsupported_case_fails() {
+        let results = Benchmark::new()
+            .with_matrix(
+                &["ucb".parse().unwrap()],
+                &[EnvConfig::FrozenLake],
+                &TrainConfig::default(),
+            )
+            .with_seeds([0])
+            .run();
+
+        assert_eq!(results.cases[0].succeeded(), 0);
+        assert!(results
+            .to_markdown()
+            .contains("| ucb/frozen-lake | 0/1 | - |"));
+    }
+
+    #[test]
+    fn format_stats() {
+        assert_eq!(format_stat(0.012345), "0.0123");
+        assert_eq!(format_stat(-1.5), "-1.50");
+        assert_eq!(format_stat(1234.4), "1234");
+    }
+}
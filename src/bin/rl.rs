@@ -0,0 +1,291 @@
+use std::{
+    error::Error,
+    io,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::{Args, Parser, Subcommand};
+use crossterm::event::KeyCode;
+use rl::{
+    algo::tabular::diff::QTableSnapshot,
+    config::{
+        AlgoConfig, CheckpointConfig, EnvConfig, EvalConfig, ExperimentConfig, TrainConfig,
+        BANDIT_ARMS, GRASSY_FIELD_SIZE,
+    },
+    device::BackendKind,
+    env::Environment,
+    gym::{
+        frozen_lake::FLAction, grassy_field::Dir, windy_gridworld::Action, FrozenLake, GrassyField,
+        KArmedBandit, WindyGridworld,
+    },
+    train::{RunDir, Trajectory},
+    viz::{self, play::Play, replay::Replay},
+};
+
+/// Train reinforcement learning agents
+#[derive(Parser)]
+#[command(name = "rl", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Train an agent and write its metrics, checkpoints, final model and config to a run directory
+    Train(TrainArgs),
+    /// Drive an environment from the keyboard, e.g. to record demonstrations
+    ///
+    /// The arrow keys move in grid environments, the number keys pick the actions of the others.
+    Play {
+        /// The environment, e.g. FrozenLake, GrassyField, KArmedBandit or WindyGridworld
+        env: EnvConfig,
+        /// Write every finished episode to this directory, in the format of `rl replay`
+        #[arg(long, value_name = "DIR")]
+        record: Option<PathBuf>,
+    },
+    /// Play back an evaluation episode recorded by a `TrajectoryRecorder` frame by frame
+    Replay {
+        /// The trajectory file, e.g. `trajectories/eval-0-0.json`
+        file: PathBuf,
+    },
+    /// Compare two Q-tables, saved with `save_q_table` or as checkpoints, e.g. to debug a regression between runs
+    Diff {
+        /// The older Q-table
+        old: PathBuf,
+        /// The newer Q-table
+        new: PathBuf,
+        /// The number of states listed in each section of the report
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+#[derive(Args)]
+struct TrainArgs {
+    /// Experiment config file, TOML or YAML. Other flags override its values
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+    /// The environment, e.g. FrozenLake, GrassyField, KArmedBandit or WindyGridworld
+    #[arg(long)]
+    env: Option<EnvConfig>,
+    /// The algorithm with default hyperparameters, e.g. q-learning, action-occurrence or ucb
+    #[arg(long)]
+    algo: Option<AlgoConfig>,
+    /// The number of training episodes
+    #[arg(long)]
+    episodes: Option<u64>,
+    /// Truncate episodes after this many steps
+    #[arg(long)]
+    max_episode_steps: Option<u64>,
+    /// Evaluate the greedy policy every N episodes
+    #[arg(long, value_name = "N")]
+    eval_interval: Option<u64>,
+    /// The number of episodes per evaluation
+    #[arg(long, default_value_t = 10)]
+    eval_episodes: u64,
+    /// Save a checkpoint of the agent every N episodes
+    #[arg(long, value_name = "N")]
+    checkpoint_interval: Option<u64>,
+    /// The backend of neural network agents: auto, wgpu, cuda or cpu
+    #[arg(long)]
+    backend: Option<BackendKind>,
+    /// The seed of the run
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Pin the determinism of the backend as well, implies `--seed 0` if no seed is given
+    #[arg(long)]
+    deterministic: bool,
+    /// The output directory, `runs/<env>-<algo>-<timestamp>` by default
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+    /// Show the training dashboard
+    #[arg(long)]
+    tui: bool,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    match Cli::parse().command {
+        Command::Train(args) => train(args),
+        Command::Play { env, record } => play(env, record),
+        Command::Replay { file } => {
+            let trajectory = Trajectory::load(&file)
+                .map_err(|e| format!("failed to read {}: {e}", file.display()))?;
+            Replay::new(trajectory).run()?;
+            Ok(())
+        }
+        Command::Diff { old, new, limit } => {
+            let load = |path: &PathBuf| {
+                QTableSnapshot::load(path)
+                    .map_err(|e| format!("failed to read {}: {e}", path.display()))
+            };
+            let diff = load(&old)?.diff(&load(&new)?);
+            diff.write_report(io::stdout().lock(), limit)?;
+            Ok(())
+        }
+    }
+}
+
+fn train(args: TrainArgs) -> Result<(), Box<dyn Error>> {
+    let mut config = match (&args.config, args.env, args.algo) {
+        (Some(path), env, algo) => {
+            let mut config = ExperimentConfig::from_file(path)?;
+            config.env = env.unwrap_or(config.env);
+            config.algo = algo.unwrap_or(config.algo);
+            config
+        }
+        (None, Some(env), Some(algo)) => ExperimentConfig {
+            env,
+            algo,
+            train: TrainConfig::default(),
+            logging: Default::default(),
+            run_dir: None,
+            seed: None,
+            deterministic: false,
+        },
+        (None, _, _) => return Err("either --config or both --env and --algo are required".into()),
+    };
+
+    let train = &mut config.train;
+    if let Some(episodes) = args.episodes {
+        train.episodes = episodes;
+    }
+    if let Some(steps) = args.max_episode_steps {
+        train.max_episode_steps = Some(steps);
+    }
+    if let Some(interval) = args.eval_interval {
+        train.eval = Some(EvalConfig {
+            interval,
+            episodes: args.eval_episodes,
+        });
+    }
+    if let Some(backend) = args.backend {
+        train.backend = backend;
+    }
+    config.seed = args.seed.or(config.seed);
+    config.deterministic |= args.deterministic;
+
+    let out = args.out.unwrap_or_else(|| default_out_dir(&config));
+    let run = RunDir::new(&out);
+    if let Some(interval) = args.checkpoint_interval {
+        config.train.checkpoint = Some(CheckpointConfig {
+            interval,
+            dir: run.checkpoints(),
+        });
+    }
+    config.run_dir = Some(out.clone());
+
+    let mut experiment = config.build()?;
+    let summary = if args.tui {
+        let (handle, tx) = viz::init(&["return", "steps"], config.train.episodes);
+        experiment = experiment.with_viz(tx);
+        let summary = experiment.train();
+        let _ = handle.join();
+        summary?
+    } else {
+        experiment.train()?
+    };
+
+    println!(
+        "Trained for {} episodes ({} steps)",
+        summary.episodes, summary.steps
+    );
+    if let Some(reason) = &summary.stopped {
+        println!("Stopped early: {reason}");
+    }
+    if let Some((episode, eval)) = summary.best {
+        println!(
+            "Best evaluation after episode {episode}: {:.3} ± {:.3}",
+            eval.mean, eval.std
+        );
+    }
+    println!("Results written to {}", run.manifest().display());
+
+    Ok(())
+}
+
+fn play(env: EnvConfig, record: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    let written = match env {
+        EnvConfig::FrozenLake => {
+            let keys = arrows([
+                FLAction::Left,
+                FLAction::Down,
+                FLAction::Right,
+                FLAction::Up,
+            ]);
+            let env = FrozenLake::new();
+            run_play(Play::new(env).with_keys(keys).with_frames(), record)?
+        }
+        EnvConfig::GrassyField => {
+            let keys = arrows([Dir::Left, Dir::Down, Dir::Right, Dir::Up]);
+            let env = GrassyField::<GRASSY_FIELD_SIZE>::new();
+            run_play(Play::new(env).with_keys(keys).with_frames(), record)?
+        }
+        EnvConfig::KArmedBandit {
+            step_limit,
+            stationary,
+        } => {
+            let env = KArmedBandit::<BANDIT_ARMS>::new(step_limit, stationary);
+            run_play(Play::new(env), record)?
+        }
+        EnvConfig::WindyGridworld => {
+            let mut keys = arrows([Action::Left, Action::Down, Action::Right, Action::Up]).to_vec();
+            keys.extend([
+                (KeyCode::Char('y'), Action::UpLeft),
+                (KeyCode::Char('u'), Action::UpRight),
+                (KeyCode::Char('b'), Action::DownLeft),
+                (KeyCode::Char('n'), Action::DownRight),
+                (KeyCode::Char('.'), Action::Stay),
+            ]);
+            let play = Play::new(WindyGridworld::new())
+                .with_keys(keys)
+                .with_frames();
+            run_play(play, record)?
+        }
+    };
+
+    if !written.is_empty() {
+        println!("Recorded {} episodes:", written.len());
+        for path in written {
+            println!("  {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Bind the arrow keys to the actions moving left, down, right and up
+fn arrows<A>([left, down, right, up]: [A; 4]) -> [(KeyCode, A); 4] {
+    [
+        (KeyCode::Left, left),
+        (KeyCode::Down, down),
+        (KeyCode::Right, right),
+        (KeyCode::Up, up),
+    ]
+}
+
+/// Run `play`, recording to `record` if given
+///
+/// **Returns** the recorded episode files
+fn run_play<E: Environment>(
+    mut play: Play<E>,
+    record: Option<PathBuf>,
+) -> io::Result<Vec<PathBuf>> {
+    if let Some(dir) = record {
+        play = play.with_recording(dir);
+    }
+    play.run()?;
+    Ok(play.written().to_vec())
+}
+
+/// `runs/<env>-<algo>-<unix time>`
+fn default_out_dir(config: &ExperimentConfig) -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    PathBuf::from("runs").join(format!(
+        "{}-{}-{timestamp}",
+        config.env.name(),
+        config.algo.name()
+    ))
+}
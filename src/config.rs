@@ -0,0 +1,792 @@
+#[cfg(feature = "viz")]
+use std::sync::mpsc::Sender;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+#[cfg(feature = "viz")]
+use crate::viz::Update;
+use crate::{
+    algo::tabular::{
+        action_occurrence::{ActionOccurrenceAgent, ActionOccurrenceAgentConfig},
+        q_table::{QTableAgent, QTableAgentConfig},
+        ucb::{UCBAgent, UCBAgentConfig},
+        Hashable, InitialValue,
+    },
+    decay::{self, Decay},
+    device::{self, BackendKind, Device},
+    env::{DiscreteActionSpace, Environment},
+    error::RlError,
+    exploration::EpsilonGreedy,
+    gym::{FrozenLake, GrassyField, KArmedBandit, WindyGridworld},
+    logger::{CsvSink, MetricSink, StdoutSink, TensorBoardWriter},
+    seed::Seeds,
+    train::{Callback, EarlyStopping, Evaluation, RunDir, SampleEfficiency, TrainSummary, Trainer},
+    traits::{Agent, Checkpoint},
+};
+
+/// The default number of steps per episode of [`KArmedBandit`] environments parsed from a name
+pub const BANDIT_STEP_LIMIT: usize = 1000;
+/// The field size of [`GrassyField`] environments built from a config
+pub const GRASSY_FIELD_SIZE: usize = 20;
+/// The number of arms of [`KArmedBandit`] environments built from a config
+pub const BANDIT_ARMS: usize = 10;
+
+/// A complete, reproducible description of a training run
+///
+/// Loaded from TOML or YAML files with [`from_file`](ExperimentConfig::from_file), e.g.
+///
+/// ```toml
+/// seed = 7
+///
+/// [env]
+/// name = "frozen-lake"
+///
+/// [algo]
+/// name = "q-learning"
+/// alpha = 0.5
+/// epsilon = { kind = "exponential", rate = 1e-3, start = 1.0, end = 0.01 }
+///
+/// [train]
+/// episodes = 5000
+/// eval = { interval = 100, episodes = 20 }
+///
+/// [logging]
+/// csv = "out/metrics.csv"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExperimentConfig {
+    /// The environment to train in
+    pub env: EnvConfig,
+    /// The algorithm and its hyperparameters
+    pub algo: AlgoConfig,
+    /// Training loop options
+    #[serde(default)]
+    pub train: TrainConfig,
+    /// Where to report metrics
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    /// The directory to write the run to, see [`RunDir`]
+    ///
+    /// The config itself is copied to `config.toml` in the directory when the trainer is built.
+    ///
+    /// **Default:** `None`
+    #[serde(default)]
+    pub run_dir: Option<PathBuf>,
+    /// The seed of the run, from which the [`Seeds`] of every random stream are derived
+    ///
+    /// Recorded with the run so it can be reproduced. Without a seed, the streams are seeded from entropy.
+    ///
+    /// **Default:** `None`
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Whether to also pin the determinism of the burn backend, see [`Seeds::deterministic`]
+    ///
+    /// Implies a seed of 0 if `seed` isn't set.
+    ///
+    /// **Default:** `false`
+    #[serde(default)]
+    pub deterministic: bool,
+}
+
+/// The environment of an [`ExperimentConfig`], selected by `name`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "name", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum EnvConfig {
+    /// [`FrozenLake`]
+    FrozenLake,
+    /// [`GrassyField`] with a field size of [`GRASSY_FIELD_SIZE`]
+    GrassyField,
+    /// [`KArmedBandit`] with [`BANDIT_ARMS`] arms
+    KArmedBandit {
+        /// The number of steps per episode
+        step_limit: usize,
+        /// Whether the reward distributions stay fixed
+        #[serde(default = "default_true")]
+        stationary: bool,
+    },
+    /// [`WindyGridworld`]
+    WindyGridworld,
+}
+
+/// The algorithm of an [`ExperimentConfig`], selected by `name`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "name", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum AlgoConfig {
+    /// [`QTableAgent`]
+    QLearning {
+        /// The learning rate
+        ///
+        /// **Default:** `0.7`
+        #[serde(default = "default_alpha")]
+        alpha: f32,
+        /// The discount factor
+        ///
+        /// **Default:** `0.99`
+        #[serde(default = "default_gamma")]
+        gamma: f32,
+        /// The epsilon schedule, which must be [exponential](ScheduleConfig::Exponential)
+        ///
+        /// **Default:** exponential decay with rate `0.1` from `1.0` to `0.01`
+        #[serde(default = "default_q_learning_epsilon")]
+        epsilon: ScheduleConfig,
+        /// The Q values of state action pairs that haven't been learned yet
+        ///
+        /// **Default:** [`InitialValue::Zero`]
+        #[serde(default)]
+        initial_value: InitialValue,
+    },
+    /// [`ActionOccurrenceAgent`]
+    ActionOccurrence {
+        /// The epsilon schedule
+        ///
+        /// **Default:** a constant `0.1`
+        #[serde(default = "default_action_occurrence_epsilon")]
+        epsilon: ScheduleConfig,
+        /// The value of actions that have not been visited yet
+        ///
+        /// **Default:** `0.0`
+        #[serde(default)]
+        default_action_value: f32,
+    },
+    /// [`UCBAgent`], only for environments whose actions are indices
+    Ucb {
+        /// The exploration coefficient
+        ///
+        /// **Default:** `1.0`
+        #[serde(default = "default_ucb_c")]
+        c: f32,
+        /// The value of actions that have not been visited yet
+        ///
+        /// **Default:** `0.0`
+        #[serde(default)]
+        default_action_value: f32,
+    },
+}
+
+/// A time-decaying hyperparameter, selected by `kind`, see [`decay`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum ScheduleConfig {
+    /// [`decay::Constant`]
+    Constant { value: f32 },
+    /// [`decay::Exponential`]
+    Exponential { rate: f32, start: f32, end: f32 },
+    /// [`decay::InverseTime`]
+    InverseTime { rate: f32, start: f32, end: f32 },
+    /// [`decay::Linear`]
+    Linear { rate: f32, start: f32, end: f32 },
+    /// [`decay::Step`]
+    Step {
+        rate: f32,
+        start: f32,
+        end: f32,
+        step: f32,
+    },
+}
+
+/// Training loop options of an [`ExperimentConfig`], see [`Trainer`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrainConfig {
+    /// The number of training episodes
+    ///
+    /// **Default:** `1000`
+    pub episodes: u64,
+    /// The total step limit
+    ///
+    /// **Default:** `None`
+    pub max_steps: Option<u64>,
+    /// The step limit per episode
+    ///
+    /// **Default:** `None`
+    pub max_episode_steps: Option<u64>,
+    /// Periodic evaluation in a separate copy of the environment
+    ///
+    /// **Default:** `None`
+    pub eval: Option<EvalConfig>,
+    /// Stop when evaluations reach a target or plateau, requires `eval`
+    ///
+    /// **Default:** `None`
+    pub early_stopping: Option<EarlyStopping>,
+    /// Report the steps until the mean training return reaches thresholds
+    ///
+    /// **Default:** `None`
+    pub sample_efficiency: Option<SampleEfficiency>,
+    /// Periodic checkpoints of the agent
+    ///
+    /// **Default:** `None`
+    pub checkpoint: Option<CheckpointConfig>,
+    /// The backend of neural network agents, see [`device`](TrainConfig::device). Tabular agents ignore it
+    ///
+    /// **Default:** [`BackendKind::Auto`]
+    pub backend: BackendKind,
+}
+
+impl Default for TrainConfig {
+    fn default() -> Self {
+        Self {
+            episodes: 1000,
+            max_steps: None,
+            max_episode_steps: None,
+            eval: None,
+            early_stopping: None,
+            sample_efficiency: None,
+            checkpoint: None,
+            backend: BackendKind::Auto,
+        }
+    }
+}
+
+impl TrainConfig {
+    /// The device of the configured [`backend`](TrainConfig::backend), where [`BackendKind::Auto`] defers to
+    /// [`device::auto`]
+    pub fn device(&self) -> Result<Device, RlError> {
+        match self.backend {
+            BackendKind::Auto => Ok(device::auto()),
+            kind => device::select(kind),
+        }
+    }
+}
+
+/// See [`Trainer::with_eval`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvalConfig {
+    /// The number of training episodes between evaluations
+    pub interval: u64,
+    /// The number of episodes per evaluation
+    pub episodes: u64,
+}
+
+/// See [`Trainer::with_checkpoints`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CheckpointConfig {
+    /// The number of training episodes between checkpoints
+    pub interval: u64,
+    /// The directory to write checkpoints to
+    pub dir: PathBuf,
+}
+
+/// Metric sinks of an [`ExperimentConfig`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// Write metrics to this CSV file, see [`CsvSink`]
+    ///
+    /// **Default:** `None`
+    pub csv: Option<PathBuf>,
+    /// Write TensorBoard event files to this directory, see [`TensorBoardWriter`]
+    ///
+    /// **Default:** `None`
+    pub tensorboard: Option<PathBuf>,
+    /// Print metrics to stdout, see [`StdoutSink`]
+    ///
+    /// **Default:** `false`
+    pub stdout: bool,
+}
+
+/// Parse an environment from its name, ignoring case, `-` and `_`, e.g. `FrozenLake` or `frozen-lake`
+///
+/// [`KArmedBandit`] environments get a step limit of [`BANDIT_STEP_LIMIT`].
+impl FromStr for EnvConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match normalize_name(s).as_str() {
+            "frozenlake" => Ok(Self::FrozenLake),
+            "grassyfield" => Ok(Self::GrassyField),
+            "karmedbandit" => Ok(Self::KArmedBandit {
+                step_limit: BANDIT_STEP_LIMIT,
+                stationary: true,
+            }),
+            "windygridworld" => Ok(Self::WindyGridworld),
+            _ => Err(format!(
+                "unknown environment `{s}`, expected one of FrozenLake, GrassyField, KArmedBandit, WindyGridworld"
+            )),
+        }
+    }
+}
+
+/// Parse an algorithm with default hyperparameters from its name, ignoring case, `-` and `_`, e.g. `q-learning`
+impl FromStr for AlgoConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match normalize_name(s).as_str() {
+            "qlearning" | "qtable" => Ok(Self::QLearning {
+                alpha: default_alpha(),
+                gamma: default_gamma(),
+                epsilon: default_q_learning_epsilon(),
+                initial_value: InitialValue::Zero,
+            }),
+            "actionoccurrence" => Ok(Self::ActionOccurrence {
+                epsilon: default_action_occurrence_epsilon(),
+                default_action_value: 0.0,
+            }),
+            "ucb" => Ok(Self::Ucb {
+                c: default_ucb_c(),
+                default_action_value: 0.0,
+            }),
+            _ => Err(format!(
+                "unknown algorithm `{s}`, expected one of q-learning, action-occurrence, ucb"
+            )),
+        }
+    }
+}
+
+impl EnvConfig {
+    /// The name of the environment as written in config files, e.g. `frozen-lake`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::FrozenLake => "frozen-lake",
+            Self::GrassyField => "grassy-field",
+            Self::KArmedBandit { .. } => "k-armed-bandit",
+            Self::WindyGridworld => "windy-gridworld",
+        }
+    }
+}
+
+impl AlgoConfig {
+    /// The name of the algorithm as written in config files, e.g. `q-learning`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::QLearning { .. } => "q-learning",
+            Self::ActionOccurrence { .. } => "action-occurrence",
+            Self::Ucb { .. } => "ucb",
+        }
+    }
+}
+
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_alpha() -> f32 {
+    QTableAgentConfig::default().alpha
+}
+
+fn default_gamma() -> f32 {
+    QTableAgentConfig::default().gamma
+}
+
+fn default_q_learning_epsilon() -> ScheduleConfig {
+    ScheduleConfig::Exponential {
+        rate: 0.1,
+        start: 1.0,
+        end: 0.01,
+    }
+}
+
+fn default_action_occurrence_epsilon() -> ScheduleConfig {
+    ScheduleConfig::Constant { value: 0.1 }
+}
+
+fn default_ucb_c() -> f32 {
+    UCBAgentConfig::default().ucb_c
+}
+
+/// A [`Trainer`] whose environment and agent types are chosen at runtime
+pub trait Experiment {
+    /// See [`Trainer::train`]
+    fn train(&mut self) -> Result<TrainSummary, RlError>;
+
+    /// See [`Trainer::with_sink`]
+    fn with_sink(self: Box<Self>, sink: Box<dyn MetricSink>) -> Box<dyn Experiment>;
+
+    /// Add a [`Callback`] that only implements [`on_eval`](Callback::on_eval), see [`Trainer::with_callback`]
+    fn with_eval_callback(self: Box<Self>, on_eval: Box<EvalFn>) -> Box<dyn Experiment>;
+
+    /// See [`Trainer::resume`]
+    fn resume(self: Box<Self>, dir: &Path) -> io::Result<Box<dyn Experiment>>;
+
+    /// See [`Trainer::with_viz`]
+    #[cfg(feature = "viz")]
+    fn with_viz(self: Box<Self>, tx: Sender<Update>) -> Box<dyn Experiment>;
+}
+
+/// A function called after every evaluation of an [`Experiment`], see [`Callback::on_eval`]
+pub type EvalFn = dyn FnMut(u64, Evaluation) -> ControlFlow<String>;
+
+/// Adapts an [`EvalFn`] to the [`Callback`] trait
+struct OnEval(Box<EvalFn>);
+
+impl<E: Environment> Callback<E> for OnEval {
+    fn on_eval(&mut self, episode: u64, eval: Evaluation) -> ControlFlow<String> {
+        (self.0)(episode, eval)
+    }
+}
+
+impl<E: Environment + 'static, A: Agent<E> + Checkpoint + 'static> Experiment for Trainer<E, A> {
+    fn train(&mut self) -> Result<TrainSummary, RlError> {
+        Trainer::train(self)
+    }
+
+    fn with_sink(self: Box<Self>, sink: Box<dyn MetricSink>) -> Box<dyn Experiment> {
+        Box::new(Trainer::with_sink(*self, sink))
+    }
+
+    fn with_eval_callback(self: Box<Self>, on_eval: Box<EvalFn>) -> Box<dyn Experiment> {
+        Box::new(Trainer::with_callback(*self, OnEval(on_eval)))
+    }
+
+    fn resume(self: Box<Self>, dir: &Path) -> io::Result<Box<dyn Experiment>> {
+        Ok(Box::new(Trainer::resume(*self, dir)?))
+    }
+
+    #[cfg(feature = "viz")]
+    fn with_viz(self: Box<Self>, tx: Sender<Update>) -> Box<dyn Experiment> {
+        Box::new(Trainer::with_viz(*self, tx))
+    }
+}
+
+impl ExperimentConfig {
+    /// Read a config from a TOML file, or a YAML file if the extension is `yaml` or `yml`
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => Self::from_yaml(&contents),
+            _ => Self::from_toml(&contents),
+        }
+    }
+
+    /// Parse a config from TOML
+    pub fn from_toml(contents: &str) -> io::Result<Self> {
+        toml::from_str(contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Parse a config from YAML
+    pub fn from_yaml(contents: &str) -> io::Result<Self> {
+        serde_yaml::from_str(contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Write the config to a TOML file, e.g. to keep a copy next to the results of a run
+    pub fn write_toml(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let contents = toml::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, contents)
+    }
+
+    /// The seeds of the run, if it is seeded
+    pub fn seeds(&self) -> Option<Seeds> {
+        match (self.seed, self.deterministic) {
+            (Some(seed), false) => Some(Seeds::new(seed)),
+            (seed, true) => Some(Seeds::new(seed.unwrap_or(0)).deterministic()),
+            (None, false) => None,
+        }
+    }
+
+    /// Construct a ready-to-run [`Trainer`] with the configured environment, agent, options and sinks
+    ///
+    /// If the run is seeded, the seeds are applied to the current thread before the environments and agent are
+    /// created.
+    ///
+    /// **Returns** an error if a sink can't be created, a schedule is invalid, or the algorithm doesn't support the
+    /// environment
+    pub fn build(&self) -> io::Result<Box<dyn Experiment>> {
+        if let Some(seeds) = self.seeds() {
+            seeds.apply();
+        }
+        if let Some(dir) = &self.run_dir {
+            fs::create_dir_all(dir)?;
+            self.write_toml(RunDir::new(dir).config())?;
+        }
+        match self.env {
+            EnvConfig::FrozenLake => self.build_tabular(FrozenLake::new, |env| env.report.take()),
+            EnvConfig::GrassyField => self
+                .build_tabular(GrassyField::<GRASSY_FIELD_SIZE>::new, |env| {
+                    env.report.take()
+                }),
+            EnvConfig::KArmedBandit {
+                step_limit,
+                stationary,
+            } => {
+                let new = move || KArmedBandit::<BANDIT_ARMS>::new(step_limit, stationary);
+                let report = |env: &mut KArmedBandit<BANDIT_ARMS>| env.report.take();
+                match self.algo {
+                    AlgoConfig::Ucb {
+                        c,
+                        default_action_value,
+                    } => {
+                        let agent = UCBAgent::new(UCBAgentConfig {
+                            ucb_c: c,
+                            default_action_value,
+                            ..Default::default()
+                        });
+                        self.trainer(new, agent, report)
+                    }
+                    _ => self.build_tabular(new, report),
+                }
+            }
+            EnvConfig::WindyGridworld => {
+                self.build_tabular(WindyGridworld::new, |env| env.report.take())
+            }
+        }
+    }
+
+    /// Build a trainer for one of the epsilon-greedy tabular agents
+    fn build_tabular<E>(
+        &self,
+        new: impl Fn() -> E,
+        report: fn(&mut E) -> BTreeMap<&'static str, f64>,
+    ) -> io::Result<Box<dyn Experiment>>
+    where
+        E: DiscreteActionSpace + 'static,
+        E::State: Hashable + Serialize + DeserializeOwned,
+        E::Action: Hashable + Serialize + DeserializeOwned,
+    {
+        match self.algo {
+            AlgoConfig::QLearning {
+                alpha,
+                gamma,
+                ref epsilon,
+                initial_value,
+            } => {
+                let ScheduleConfig::Exponential { rate, start, end } = *epsilon else {
+                    return Err(invalid_input(
+                        "q-learning requires an exponential epsilon schedule",
+                    ));
+                };
+                let exploration = EpsilonGreedy::new(decay::Exponential::new(rate, start, end)?);
+                let agent = QTableAgent::new(QTableAgentConfig {
+                    exploration,
+                    alpha,
+                    gamma,
+                    initial_value,
+                    ..Default::default()
+                })?;
+                self.trainer(new, agent, report)
+            }
+            AlgoConfig::ActionOccurrence {
+                ref epsilon,
+                default_action_value,
+            } => {
+                fn agent<E, D>(decay: D, default_action_value: f32) -> ActionOccurrenceAgent<E, D>
+                where
+                    E: DiscreteActionSpace,
+                    E::State: Hashable,
+                    E::Action: Hashable,
+                    D: Decay,
+                {
+                    ActionOccurrenceAgent::new(ActionOccurrenceAgentConfig {
+                        epsilon_decay_strategy: decay,
+                        default_action_value,
+                        alpha_fn: ActionOccurrenceAgentConfig::default().alpha_fn,
+                    })
+                }
+
+                let v = default_action_value;
+                match *epsilon {
+                    ScheduleConfig::Constant { value } => {
+                        self.trainer(new, agent(decay::Constant::new(value), v), report)
+                    }
+                    ScheduleConfig::Exponential { rate, start, end } => {
+                        let decay = decay::Exponential::new(rate, start, end)?;
+                        self.trainer(new, agent(decay, v), report)
+                    }
+                    ScheduleConfig::InverseTime { rate, start, end } => {
+                        let decay = decay::InverseTime::new(rate, start, end)?;
+                        self.trainer(new, agent(decay, v), report)
+                    }
+                    ScheduleConfig::Linear { rate, start, end } => {
+                        let decay = decay::Linear::new(rate, start, end)?;
+                        self.trainer(new, agent(decay, v), report)
+                    }
+                    ScheduleConfig::Step {
+                        rate,
+                        start,
+                        end,
+                        step,
+                    } => {
+                        let decay = decay::Step::new(rate, start, end, step)?;
+                        self.trainer(new, agent(decay, v), report)
+                    }
+                }
+            }
+            AlgoConfig::Ucb { .. } => Err(invalid_input(
+                "ucb is only supported in environments whose actions are indices",
+            )),
+        }
+    }
+
+    /// Apply the training options and sinks to a trainer for `agent`
+    fn trainer<E, A>(
+        &self,
+        new: impl Fn() -> E,
+        agent: A,
+        report: fn(&mut E) -> BTreeMap<&'static str, f64>,
+    ) -> io::Result<Box<dyn Experiment>>
+    where
+        E: Environment + 'static,
+        A: Agent<E> + Checkpoint + 'static,
+    {
+        let train = &self.train;
+        let mut trainer = Trainer::new(new(), agent)
+            .with_episodes(train.episodes)
+            .with_episode_metrics(move |env| report(env).into_iter().collect());
+
+        if let Some(steps) = train.max_steps {
+            trainer = trainer.with_max_steps(steps);
+        }
+        if let Some(steps) = train.max_episode_steps {
+            trainer = trainer.with_max_episode_steps(steps);
+        }
+        if let Some(eval) = &train.eval {
+            trainer = trainer.with_eval(eval.interval, eval.episodes, new());
+        }
+        if let Some(early_stopping) = &train.early_stopping {
+            trainer = trainer.with_early_stopping(early_stopping.clone());
+        }
+        if let Some(sample_efficiency) = &train.sample_efficiency {
+            trainer = trainer.with_sample_efficiency(sample_efficiency.clone());
+        }
+        if let Some(checkpoint) = &train.checkpoint {
+            trainer = trainer.with_checkpoints(checkpoint.interval, &checkpoint.dir);
+        }
+        if let Some(seeds) = self.seeds() {
+            trainer = trainer.with_seeds(seeds);
+        }
+        if let Some(dir) = &self.run_dir {
+            trainer = trainer.with_run_dir(dir);
+        }
+
+        let logging = &self.logging;
+        if let Some(path) = &logging.csv {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            trainer = trainer.with_sink(CsvSink::new(path)?);
+        }
+        if let Some(dir) = &logging.tensorboard {
+            trainer = trainer.with_sink(TensorBoardWriter::new(dir)?);
+        }
+        if logging.stdout {
+            trainer = trainer.with_sink(StdoutSink::new());
+        }
+
+        Ok(Box::new(trainer))
+    }
+}
+
+/// Read an [`ExperimentConfig`] from `path` and construct its [`Trainer`]
+pub fn trainer_from_file(path: impl AsRef<Path>) -> io::Result<Box<dyn Experiment>> {
+    ExperimentConfig::from_file(path)?.build()
+}
+
+fn invalid_input(message: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_formats() {
+        let toml = r#"
+            seed = 7
+
+            [env]
+            name = "k-armed-bandit"
+            step_limit = 100
+
+            [algo]
+            name = "action-occurrence"
+            epsilon = { kind = "linear", rate = 0.01, start = 1.0, end = 0.1 }
+
+            [train]
+            episodes = 50
+            eval = { interval = 10, episodes = 5 }
+        "#;
+        let yaml = r#"
+            seed: 7
+            env:
+              name: k-armed-bandit
+              step_limit: 100
+            algo:
+              name: action-occurrence
+              epsilon: { kind: linear, rate: 0.01, start: 1.0, end: 0.1 }
+            train:
+              episodes: 50
+              eval: { interval: 10, episodes: 5 }
+        "#;
+
+        let config = ExperimentConfig::from_toml(toml).unwrap();
+        assert_eq!(config, ExperimentConfig::from_yaml(yaml).unwrap());
+        assert_eq!(
+            config.env,
+            EnvConfig::KArmedBandit {
+                step_limit: 100,
+                stationary: true
+            }
+        );
+        assert_eq!(config.train.max_steps, None, "Defaults are filled in");
+
+        let round_trip = toml::to_string(&config).unwrap();
+        assert_eq!(config, ExperimentConfig::from_toml(&round_trip).unwrap());
+
+        let summary = config.build().unwrap().train().unwrap();
+        assert_eq!(summary.episodes, 50);
+        assert!(summary.eval.is_some());
+    }
+
+    #[test]
+    fn parse_names() {
+        assert_eq!("FrozenLake".parse(), Ok(EnvConfig::FrozenLake));
+        assert_eq!("windy_gridworld".parse(), Ok(EnvConfig::WindyGridworld));
+        assert!("MoonLander".parse::<EnvConfig>().is_err());
+        assert_eq!(
+            EnvConfig::WindyGridworld.name().parse(),
+            Ok(EnvConfig::WindyGridworld)
+        );
+
+        assert_eq!(
+            "q-learning".parse(),
+            ExperimentConfig::from_toml("env.name = \"frozen-lake\"\nalgo.name = \"q-learning\"")
+                .map(|config| config.algo)
+                .map_err(|e| e.to_string()),
+            "Defaults match the ones used in config files"
+        );
+
+        let config = ExperimentConfig::from_toml(
+            "env.name = \"frozen-lake\"\nalgo.name = \"ucb\"\ntrain.backend = \"cpu\"",
+        )
+        .unwrap();
+        assert_eq!(config.train.backend, BackendKind::Cpu);
+        assert_eq!(config.train.device().unwrap().kind(), BackendKind::Cpu);
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert!(ExperimentConfig::from_toml("[env]\nname = \"moon-lander\"").is_err());
+
+        let config = ExperimentConfig {
+            env: EnvConfig::FrozenLake,
+            algo: AlgoConfig::Ucb {
+                c: 1.0,
+                default_action_value: 0.0,
+            },
+            train: TrainConfig::default(),
+            logging: LoggingConfig::default(),
+            run_dir: None,
+            seed: None,
+            deterministic: false,
+        };
+        assert!(config.build().is_err(), "UCB needs index actions");
+    }
+}
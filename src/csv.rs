@@ -0,0 +1,52 @@
+/// The header of metrics files, written by the [`CsvSink`](crate::logger::CsvSink) and the viz export and read by
+/// [`read_metrics`](crate::report::read_metrics)
+pub(crate) const METRICS_HEADER: &str = "metric,episode,value";
+
+/// Quote `s` as a CSV cell if it contains a comma, quote or newline, doubling its quotes
+pub(crate) fn escape_csv(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Split a line written with [`escape_csv`] into its cells
+pub(crate) fn split_csv(line: &str) -> Vec<String> {
+    let mut cells = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                cells.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => cells.push(String::new()),
+            c => cells.last_mut().unwrap().push(c),
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_cells() {
+        for cell in ["plain", "a, b", "say \"hi\"", ""] {
+            assert_eq!(
+                split_csv(&escape_csv(cell)),
+                [cell],
+                "Round trip of {cell:?}"
+            );
+        }
+        assert_eq!(escape_csv("a, \"b\""), "\"a, \"\"b\"\"\"");
+        assert_eq!(
+            split_csv("a,\"b, \"\"c\"\"\",,d"),
+            ["a", "b, \"c\"", "", "d"]
+        );
+    }
+}
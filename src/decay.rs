@@ -1,18 +1,22 @@
+use crate::error::{Result, RlError};
+
 /// An implementation of a time-decaying value
 pub trait Decay {
     /// Calculate value at time `t`
     fn evaluate(&self, t: f32) -> f32;
 }
 
-// TODO: better error types
-fn validate(rate: f32, vi: f32, vf: f32) -> Result<(), String> {
+fn validate(rate: f32, vi: f32, vf: f32) -> Result<()> {
     ((rate >= 0.0 && vi > vf) || (rate < 0.0 && vi < vf))
         .then_some(())
-        .ok_or_else(|| String::from("`vi - vf` must have same sign as `rate`"))
+        .ok_or_else(|| {
+            RlError::InvalidHyperparameters(String::from("`vi - vf` must have same sign as `rate`"))
+        })
 }
 
 /// A constant value
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Constant {
     value: f32,
 }
@@ -31,6 +35,7 @@ impl Decay for Constant {
 
 /// v(t) = v<sub>f</sub> + (v<sub>i</sub> - v<sub>f</sub>) * e<sup>-rt</sup>
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Exponential {
     rate: f32,
     vi: f32,
@@ -38,7 +43,7 @@ pub struct Exponential {
 }
 
 impl Exponential {
-    pub fn new(rate: f32, vi: f32, vf: f32) -> Result<Self, String> {
+    pub fn new(rate: f32, vi: f32, vf: f32) -> Result<Self> {
         validate(rate, vi, vf)?;
         Ok(Self { rate, vi, vf })
     }
@@ -53,6 +58,7 @@ impl Decay for Exponential {
 
 /// v(t) = v<sub>f</sub> + (v<sub>i</sub> - v<sub>f</sub>) / (1 + rt)
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InverseTime {
     rate: f32,
     vi: f32,
@@ -60,7 +66,7 @@ pub struct InverseTime {
 }
 
 impl InverseTime {
-    pub fn new(rate: f32, vi: f32, vf: f32) -> Result<Self, String> {
+    pub fn new(rate: f32, vi: f32, vf: f32) -> Result<Self> {
         validate(rate, vi, vf)?;
         Ok(Self { rate, vi, vf })
     }
@@ -75,6 +81,7 @@ impl Decay for InverseTime {
 
 /// v(t) = max(v<sub>i</sub> - rt, v<sub>f</sub>)
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Linear {
     rate: f32,
     vi: f32,
@@ -82,7 +89,7 @@ pub struct Linear {
 }
 
 impl Linear {
-    pub fn new(rate: f32, vi: f32, vf: f32) -> Result<Self, String> {
+    pub fn new(rate: f32, vi: f32, vf: f32) -> Result<Self> {
         validate(rate, vi, vf)?;
         Ok(Self { rate, vi, vf })
     }
@@ -97,6 +104,7 @@ impl Decay for Linear {
 
 /// v(t) = max(v<sub>i</sub> * r<sup>floor(t/s)</sup>, v<sub>f</sub>)
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Step {
     rate: f32,
     vi: f32,
@@ -105,7 +113,7 @@ pub struct Step {
 }
 
 impl Step {
-    pub fn new(rate: f32, vi: f32, vf: f32, step: f32) -> Result<Self, String> {
+    pub fn new(rate: f32, vi: f32, vf: f32, step: f32) -> Result<Self> {
         validate(rate, vi, vf)?;
         Ok(Self { rate, vi, vf, step })
     }
@@ -0,0 +1,251 @@
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "backend-tch")]
+use burn::backend::{libtorch::LibTorchDevice, LibTorch};
+#[cfg(feature = "backend-wgpu")]
+use burn::backend::{wgpu::WgpuDevice, Wgpu};
+use burn::{
+    backend::{ndarray::NdArrayDevice, Autodiff, NdArray},
+    tensor::backend::AutodiffBackend,
+};
+
+use crate::error::{Result, RlError};
+
+/// The environment variable that overrides the backend chosen by [`auto`], e.g. `RL_BACKEND=cpu`
+pub const BACKEND_VAR: &str = "RL_BACKEND";
+
+/// A burn backend to train neural network agents on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum BackendKind {
+    /// The best backend compiled in, see [`auto`]
+    #[default]
+    Auto,
+    /// The wgpu backend on the best available GPU, requires the `backend-wgpu` feature
+    Wgpu,
+    /// The LibTorch backend on the first CUDA device, requires the `backend-tch` feature
+    Cuda,
+    /// The ndarray backend on the CPU, always available
+    Cpu,
+}
+
+impl BackendKind {
+    /// The backends [`Auto`](BackendKind::Auto) tries, from most to least preferred
+    pub const PREFERENCE: [BackendKind; 3] = [Self::Cuda, Self::Wgpu, Self::Cpu];
+
+    /// The name of the backend, as parsed by [`FromStr`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Wgpu => "wgpu",
+            Self::Cuda => "cuda",
+            Self::Cpu => "cpu",
+        }
+    }
+
+    /// The crate feature the backend requires, if any
+    pub fn feature(&self) -> Option<&'static str> {
+        match self {
+            Self::Wgpu => Some("backend-wgpu"),
+            Self::Cuda => Some("backend-tch"),
+            Self::Auto | Self::Cpu => None,
+        }
+    }
+
+    /// Whether the backend was compiled in
+    pub fn is_available(&self) -> bool {
+        match self {
+            Self::Wgpu => cfg!(feature = "backend-wgpu"),
+            Self::Cuda => cfg!(feature = "backend-tch"),
+            Self::Auto | Self::Cpu => true,
+        }
+    }
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for BackendKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "wgpu" | "gpu" => Ok(Self::Wgpu),
+            "cuda" | "tch" | "libtorch" => Ok(Self::Cuda),
+            "cpu" | "ndarray" => Ok(Self::Cpu),
+            _ => Err(format!(
+                "unknown backend `{s}`, expected one of auto, wgpu, cuda or cpu"
+            )),
+        }
+    }
+}
+
+/// A device of one of the compiled-in burn backends, selected at runtime
+///
+/// Agents are generic over their backend, so code that should run on any of them is written once as a
+/// [`WithBackend`] task and dispatched with [`run`](Device::run):
+///
+/// ```ignore
+/// struct Train;
+///
+/// impl WithBackend for Train {
+///     type Output = ();
+///
+///     fn run<B: AutodiffBackend>(self, device: &'static B::Device) {
+///         let model = ModelConfig::new(64, 128).init::<B>(device);
+///         let mut agent = DQNAgent::new(model, DQNAgentConfig::default(), device).unwrap();
+///         // ...
+///     }
+/// }
+///
+/// device::auto().run(Train);
+/// ```
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Device {
+    /// A device of the wgpu backend
+    #[cfg(feature = "backend-wgpu")]
+    Wgpu(WgpuDevice),
+    /// A device of the LibTorch backend
+    #[cfg(feature = "backend-tch")]
+    Cuda(LibTorchDevice),
+    /// A device of the ndarray backend
+    Cpu(NdArrayDevice),
+}
+
+impl Device {
+    /// The kind of backend of the device
+    pub fn kind(&self) -> BackendKind {
+        match self {
+            #[cfg(feature = "backend-wgpu")]
+            Self::Wgpu(_) => BackendKind::Wgpu,
+            #[cfg(feature = "backend-tch")]
+            Self::Cuda(_) => BackendKind::Cuda,
+            Self::Cpu(_) => BackendKind::Cpu,
+        }
+    }
+
+    /// Run `task` with the autodiff backend of the device
+    ///
+    /// Agents hold a `&'static` reference to their device, so the device is leaked. Call this once per run, not in
+    /// a loop.
+    pub fn run<T: WithBackend>(self, task: T) -> T::Output {
+        match self {
+            #[cfg(feature = "backend-wgpu")]
+            Self::Wgpu(device) => task.run::<Autodiff<Wgpu>>(Box::leak(Box::new(device))),
+            #[cfg(feature = "backend-tch")]
+            Self::Cuda(device) => task.run::<Autodiff<LibTorch>>(Box::leak(Box::new(device))),
+            Self::Cpu(device) => task.run::<Autodiff<NdArray>>(Box::leak(Box::new(device))),
+        }
+    }
+}
+
+/// Code that is generic over the backend it runs on, see [`Device::run`]
+pub trait WithBackend {
+    /// The result of the task
+    type Output;
+
+    /// Run the task on `device` of the backend `B`
+    fn run<B: AutodiffBackend>(self, device: &'static B::Device) -> Self::Output;
+}
+
+/// Select the best available device
+///
+/// The backend is read from the [`BACKEND_VAR`] environment variable if it is set, and otherwise is the first
+/// compiled-in backend of [`BackendKind::PREFERENCE`]: CUDA with `backend-tch`, the GPU with `backend-wgpu` and the
+/// CPU otherwise. Availability is decided by the enabled features, the devices themselves are only opened when the
+/// first tensor is created on them.
+///
+/// **Panics** if [`BACKEND_VAR`] names an unknown backend or one that wasn't compiled in
+pub fn auto() -> Device {
+    let kind = match std::env::var(BACKEND_VAR) {
+        Ok(kind) => kind
+            .parse()
+            .unwrap_or_else(|e| panic!("Invalid {BACKEND_VAR}: {e}")),
+        Err(_) => BackendKind::Auto,
+    };
+    select(kind).unwrap_or_else(|e| panic!("Invalid {BACKEND_VAR}: {e}"))
+}
+
+/// Select a device of the backend `kind`, where [`BackendKind::Auto`] picks the first compiled-in backend of
+/// [`BackendKind::PREFERENCE`]
+///
+/// ### Returns
+/// - [`RlError::UnavailableBackend`] if the feature of the backend isn't enabled
+/// - The default device of the backend otherwise
+pub fn select(kind: BackendKind) -> Result<Device> {
+    match kind {
+        BackendKind::Auto => {
+            let kind = BackendKind::PREFERENCE
+                .into_iter()
+                .find(BackendKind::is_available)
+                .expect("The CPU backend is always available");
+            select(kind)
+        }
+        #[cfg(feature = "backend-wgpu")]
+        BackendKind::Wgpu => Ok(Device::Wgpu(WgpuDevice::BestAvailable)),
+        #[cfg(feature = "backend-tch")]
+        BackendKind::Cuda => Ok(Device::Cuda(LibTorchDevice::Cuda(0))),
+        BackendKind::Cpu => Ok(Device::Cpu(NdArrayDevice::Cpu)),
+        #[allow(unreachable_patterns)]
+        kind => Err(RlError::UnavailableBackend {
+            backend: kind.name(),
+            feature: kind.feature().unwrap_or_default(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use burn::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn backend_kind() {
+        for kind in [
+            BackendKind::Auto,
+            BackendKind::Wgpu,
+            BackendKind::Cuda,
+            BackendKind::Cpu,
+        ] {
+            assert_eq!(kind.to_string().parse::<BackendKind>(), Ok(kind));
+        }
+        assert_eq!("LibTorch".parse(), Ok(BackendKind::Cuda));
+        assert!("tpu".parse::<BackendKind>().is_err());
+    }
+
+    #[test]
+    fn select_device() {
+        assert_eq!(select(BackendKind::Cpu).unwrap().kind(), BackendKind::Cpu);
+        assert_eq!(
+            select(BackendKind::Wgpu).is_ok(),
+            cfg!(feature = "backend-wgpu")
+        );
+        let auto = select(BackendKind::Auto).unwrap().kind();
+        assert!(auto.is_available() && auto != BackendKind::Auto);
+
+        struct Sum;
+
+        impl WithBackend for Sum {
+            type Output = f32;
+
+            fn run<B: AutodiffBackend>(self, device: &'static B::Device) -> f32 {
+                Tensor::<B, 1>::from_floats([1.0, 2.0], device)
+                    .sum()
+                    .into_scalar()
+                    .elem()
+            }
+        }
+
+        assert_eq!(select(BackendKind::Cpu).unwrap().run(Sum), 3.0);
+    }
+}
@@ -0,0 +1,371 @@
+use std::f32::consts::PI;
+
+use burn::{
+    prelude::*,
+    tensor::{activation, Distribution, ElementConversion},
+};
+
+/// Keeps the arguments of logarithms away from 0
+const EPS: f32 = 1e-6;
+
+/// A batch of categorical distributions over discrete actions, parametrized by unnormalized log probabilities
+///
+/// Samples are drawn from the burn backend's random number generator, which is seeded by
+/// [`Seeds::apply_backend`](crate::seed::Seeds::apply_backend).
+///
+/// ```ignore
+/// let dist = Categorical::new(policy_net.forward(states));
+/// let actions = dist.sample();
+/// let loss = -(dist.log_prob(actions) * advantages).mean() - dist.entropy().mean() * 0.01;
+/// ```
+///
+/// ### Generics
+/// - `B` - A burn backend
+#[derive(Debug, Clone)]
+pub struct Categorical<B: Backend> {
+    log_probs: Tensor<B, 2>,
+}
+
+impl<B: Backend> Categorical<B> {
+    /// Create the distributions from `logits` of shape `[batch, actions]`
+    pub fn new(logits: Tensor<B, 2>) -> Self {
+        Self {
+            log_probs: activation::log_softmax(logits, 1),
+        }
+    }
+
+    /// The normalized log probability of every action, with shape `[batch, actions]`
+    pub fn log_probs(&self) -> Tensor<B, 2> {
+        self.log_probs.clone()
+    }
+
+    /// The probability of every action, with shape `[batch, actions]`
+    pub fn probs(&self) -> Tensor<B, 2> {
+        self.log_probs.clone().exp()
+    }
+
+    /// Sample an action from each distribution, with shape `[batch, 1]`
+    ///
+    /// Samples are the most likely actions after adding Gumbel noise to the log probabilities, which is equivalent
+    /// to sampling from the distribution but runs on the device.
+    pub fn sample(&self) -> Tensor<B, 2, Int> {
+        let uniform = Tensor::random(
+            self.log_probs.dims(),
+            Distribution::Uniform(0.0, 1.0),
+            &self.log_probs.device(),
+        )
+        .clamp(EPS, 1.0 - EPS);
+        let gumbel = uniform.log().neg().log().neg();
+        (self.log_probs.clone() + gumbel).argmax(1)
+    }
+
+    /// The most likely action of each distribution, with shape `[batch, 1]`
+    pub fn mode(&self) -> Tensor<B, 2, Int> {
+        self.log_probs.clone().argmax(1)
+    }
+
+    /// The log probability of `actions` of shape `[batch, 1]`, with shape `[batch, 1]`
+    pub fn log_prob(&self, actions: Tensor<B, 2, Int>) -> Tensor<B, 2> {
+        self.log_probs.clone().gather(1, actions)
+    }
+
+    /// The entropy of each distribution, with shape `[batch, 1]`
+    pub fn entropy(&self) -> Tensor<B, 2> {
+        (self.probs() * self.log_probs.clone()).sum_dim(1).neg()
+    }
+
+    /// The KL divergence `KL(self || other)` of each distribution from the one in `other`, with shape `[batch, 1]`
+    ///
+    /// With `self` the policy before an update and `other` the policy after it, this is how far the update moved
+    /// the policy.
+    pub fn kl_divergence(&self, other: &Self) -> Tensor<B, 2> {
+        (self.probs() * (self.log_probs.clone() - other.log_probs.clone())).sum_dim(1)
+    }
+}
+
+/// A batch of Gaussian distributions over continuous actions with independent dimensions
+///
+/// Policies with bounded actions, e.g. SAC, sample with [`sample_squashed`](DiagGaussian::sample_squashed), which
+/// squashes the samples into `(-1, 1)` with tanh and corrects their log probabilities for the change of variables.
+/// Squashed actions can be decoded with [`FromTensor`](crate::traits::FromTensor) into
+/// [`ContinuousAction`](crate::env::ContinuousAction)s and scaled to the action bounds of the environment.
+///
+/// ```ignore
+/// let (mean, log_std) = actor.forward(states);
+/// let (actions, log_probs) = DiagGaussian::new(mean, log_std.clamp(-20.0, 2.0)).sample_squashed();
+/// ```
+///
+/// ### Generics
+/// - `B` - A burn backend
+#[derive(Debug, Clone)]
+pub struct DiagGaussian<B: Backend> {
+    mean: Tensor<B, 2>,
+    log_std: Tensor<B, 2>,
+}
+
+impl<B: Backend> DiagGaussian<B> {
+    /// Create the distributions from their means and the logarithms of their standard deviations, both with shape
+    /// `[batch, dims]`
+    ///
+    /// **Panics** if the shapes differ
+    pub fn new(mean: Tensor<B, 2>, log_std: Tensor<B, 2>) -> Self {
+        assert_eq!(
+            mean.dims(),
+            log_std.dims(),
+            "The mean and log standard deviation must have the same shape"
+        );
+        Self { mean, log_std }
+    }
+
+    /// The means of the distributions, with shape `[batch, dims]`
+    pub fn mean(&self) -> Tensor<B, 2> {
+        self.mean.clone()
+    }
+
+    /// The standard deviations of the distributions, with shape `[batch, dims]`
+    pub fn std(&self) -> Tensor<B, 2> {
+        self.log_std.clone().exp()
+    }
+
+    /// Sample from each distribution, with shape `[batch, dims]`
+    ///
+    /// Samples are reparametrized as `mean + std * noise`, so gradients flow back to the mean and standard deviation.
+    pub fn sample(&self) -> Tensor<B, 2> {
+        let noise = Tensor::random(
+            self.mean.dims(),
+            Distribution::Normal(0.0, 1.0),
+            &self.mean.device(),
+        );
+        self.mean.clone() + self.std() * noise
+    }
+
+    /// The log probability of `x` of shape `[batch, dims]`, summed over the dimensions, with shape `[batch, 1]`
+    pub fn log_prob(&self, x: Tensor<B, 2>) -> Tensor<B, 2> {
+        let z = (x - self.mean.clone()) / self.std();
+        (z.powf_scalar(2.0) * -0.5 - self.log_std.clone() - 0.5 * (2.0 * PI).ln()).sum_dim(1)
+    }
+
+    /// The entropy of each distribution, with shape `[batch, 1]`
+    pub fn entropy(&self) -> Tensor<B, 2> {
+        (self.log_std.clone() + 0.5 * (1.0 + (2.0 * PI).ln())).sum_dim(1)
+    }
+
+    /// The KL divergence `KL(self || other)` of each distribution from the one in `other`, summed over the
+    /// dimensions, with shape `[batch, 1]`
+    pub fn kl_divergence(&self, other: &Self) -> Tensor<B, 2> {
+        let variance_ratio = (self.log_std.clone() - other.log_std.clone())
+            .mul_scalar(2.0)
+            .exp();
+        let mean_term = ((self.mean.clone() - other.mean.clone()) / other.std()).powf_scalar(2.0);
+        ((variance_ratio + mean_term - 1.0) * 0.5 + other.log_std.clone() - self.log_std.clone())
+            .sum_dim(1)
+    }
+
+    /// Sample from each distribution and squash the samples into `(-1, 1)` with tanh
+    ///
+    /// **Returns** the squashed samples with shape `[batch, dims]` and their log probabilities with shape
+    /// `[batch, 1]`
+    pub fn sample_squashed(&self) -> (Tensor<B, 2>, Tensor<B, 2>) {
+        let sample = self.sample();
+        let action = activation::tanh(sample.clone());
+        let log_prob = self.log_prob(sample) - squash_correction(action.clone());
+        (action, log_prob)
+    }
+
+    /// The log probability of `action` of shape `[batch, dims]` in `(-1, 1)`, squashed with tanh as in
+    /// [`sample_squashed`](DiagGaussian::sample_squashed), with shape `[batch, 1]`
+    ///
+    /// Actions are clamped away from ±1 first, where the inverse of tanh is infinite.
+    pub fn log_prob_squashed(&self, action: Tensor<B, 2>) -> Tensor<B, 2> {
+        let action = action.clamp(-1.0 + EPS, 1.0 - EPS);
+        // atanh(a) = ln((1 + a) / (1 - a)) / 2
+        let sample = ((action.clone() + 1.0) / (action.clone().neg() + 1.0)).log() * 0.5;
+        self.log_prob(sample) - squash_correction(action)
+    }
+
+    /// The squashed means of the distributions, the deterministic actions of a squashed policy
+    pub fn mode_squashed(&self) -> Tensor<B, 2> {
+        activation::tanh(self.mean.clone())
+    }
+}
+
+/// The entropy of a stochastic policy and its KL divergence from the policy before each update, the first signs of
+/// a collapsing policy
+///
+/// Agents [record](PolicyStats::record) every update and report the statistics with their
+/// [`metrics`](crate::traits::Agent::metrics) as `policy_entropy`, `policy_kl` and `policy_kl_max`, so every
+/// stochastic-policy agent reports them under the same names, e.g. the
+/// [`SoftQAgent`](crate::algo::tabular::soft_q::SoftQAgent) and the
+/// [`DreamerAgent`](crate::algo::dreamer::DreamerAgent).
+///
+/// ```ignore
+/// let old = Categorical::new(policy_net.forward(states.clone()));
+/// // ... optimize the policy ...
+/// let new = Categorical::new(policy_net.forward(states));
+/// self.policy_stats.record_categorical(&old, &new);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PolicyStats {
+    updates: u32,
+    entropy: f64,
+    kl: f64,
+    kl_max: f64,
+}
+
+impl PolicyStats {
+    /// Create statistics without updates
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an update after which the policy has a mean `entropy` and moved by a mean KL divergence of `kl`
+    pub fn record(&mut self, entropy: f64, kl: f64) {
+        self.updates += 1;
+        self.entropy += entropy;
+        self.kl += kl;
+        self.kl_max = self.kl_max.max(kl);
+    }
+
+    /// Record an update from the `old` to the `new` policy in a batch of states
+    pub fn record_categorical<B: Backend>(&mut self, old: &Categorical<B>, new: &Categorical<B>) {
+        self.record(mean(new.entropy()), mean(old.kl_divergence(new)));
+    }
+
+    /// Record an update from the `old` to the `new` policy in a batch of states
+    pub fn record_gaussian<B: Backend>(&mut self, old: &DiagGaussian<B>, new: &DiagGaussian<B>) {
+        self.record(mean(new.entropy()), mean(old.kl_divergence(new)));
+    }
+
+    /// The mean entropy and KL divergence and the largest KL divergence of the recorded updates, resetting the
+    /// statistics
+    ///
+    /// **Returns** no metrics if no update was recorded
+    pub fn take(&mut self) -> Vec<(&'static str, f64)> {
+        let Self {
+            updates,
+            entropy,
+            kl,
+            kl_max,
+        } = std::mem::take(self);
+        if updates == 0 {
+            return Vec::new();
+        }
+        vec![
+            ("policy_entropy", entropy / updates as f64),
+            ("policy_kl", kl / updates as f64),
+            ("policy_kl_max", kl_max),
+        ]
+    }
+}
+
+/// The mean of the values of `tensor`
+fn mean<B: Backend>(tensor: Tensor<B, 2>) -> f64 {
+    tensor.mean().into_scalar().elem::<f64>()
+}
+
+/// The log determinant of the Jacobian of tanh at the samples that were squashed to `action`,
+/// `sum(ln(1 - action²))`, with shape `[batch, 1]`
+fn squash_correction<B: Backend>(action: Tensor<B, 2>) -> Tensor<B, 2> {
+    (action.powf_scalar(2.0).neg() + 1.0 + EPS).log().sum_dim(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use burn::backend::{ndarray::NdArrayDevice, NdArray as B};
+
+    use super::*;
+
+    fn assert_close(tensor: Tensor<B, 2>, expected: &[f32]) {
+        let values = tensor.into_data().value;
+        assert_eq!(values.len(), expected.len());
+        for (value, close_to) in values.iter().zip(expected) {
+            assert!(
+                (value - close_to).abs() < 1e-4,
+                "{values:?} != {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn categorical() {
+        let device = NdArrayDevice::Cpu;
+        let logits = Tensor::<B, 2>::from_floats([[0.0, 0.0], [0.0, 30.0]], &device);
+        let dist = Categorical::new(logits);
+
+        assert_close(dist.probs(), &[0.5, 0.5, 0.0, 1.0]);
+        let actions = Tensor::from_ints([[1], [1]], &device);
+        assert_close(dist.log_prob(actions), &[-(2.0f32.ln()), 0.0]);
+        assert_close(dist.entropy(), &[2.0f32.ln(), 0.0]);
+        assert_eq!(dist.mode().into_data().value[1], 1);
+
+        B::seed(0);
+        let samples = Categorical::new(Tensor::<B, 2>::zeros([1000, 2], &device)).sample();
+        let ones = samples.sum().into_scalar();
+        assert!((400..600).contains(&ones), "{ones} of 1000 samples are 1");
+        let samples = dist.sample().into_data().value;
+        assert_eq!(samples[1], 1, "Samples the certain action");
+    }
+
+    #[test]
+    fn diag_gaussian() {
+        let device = NdArrayDevice::Cpu;
+        let mean = Tensor::<B, 2>::from_floats([[0.0, 1.0]], &device);
+        let log_std = Tensor::<B, 2>::from_floats([[0.0, 2.0f32.ln()]], &device);
+        let dist = DiagGaussian::new(mean, log_std);
+
+        // ln N(0; 0, 1) + ln N(1; 1, 2)
+        let expected = -(2.0 * PI).ln() - 2.0f32.ln();
+        assert_close(dist.log_prob(dist.mean()), &[expected]);
+        assert_close(dist.entropy(), &[1.0 + (2.0 * PI).ln() + 2.0f32.ln()]);
+
+        B::seed(0);
+        let dist = DiagGaussian::new(
+            Tensor::zeros([4, 2], &device),
+            Tensor::zeros([4, 2], &device),
+        );
+        let (action, log_prob) = dist.sample_squashed();
+        let values = action.clone().into_data().value;
+        assert!(
+            values.iter().all(|a| a.abs() < 1.0),
+            "Squashed into (-1, 1)"
+        );
+        let expected = log_prob.into_data().value;
+        assert_close(dist.log_prob_squashed(action), &expected);
+    }
+
+    #[test]
+    fn policy_stats() {
+        let device = NdArrayDevice::Cpu;
+        let old = Categorical::new(Tensor::<B, 2>::from_floats([[0.0, 0.0]], &device));
+        let new = Categorical::new(Tensor::<B, 2>::from_floats([[0.0, 3.0f32.ln()]], &device));
+        // KL([1/2, 1/2] || [1/4, 3/4]) = (ln 2 + ln 2/3) / 2
+        let kl = (2.0f32.ln() + (2.0f32 / 3.0).ln()) / 2.0;
+        assert_close(old.kl_divergence(&new), &[kl]);
+        assert_close(old.kl_divergence(&old), &[0.0]);
+
+        let old = DiagGaussian::new(
+            Tensor::<B, 2>::from_floats([[0.0]], &device),
+            Tensor::<B, 2>::from_floats([[0.0]], &device),
+        );
+        let new = DiagGaussian::new(
+            Tensor::<B, 2>::from_floats([[1.0]], &device),
+            Tensor::<B, 2>::from_floats([[2.0f32.ln()]], &device),
+        );
+        // ln 2 + (1 + 1) / (2 * 4) - 1/2
+        assert_close(old.kl_divergence(&new), &[2.0f32.ln() - 0.25]);
+
+        let mut stats = PolicyStats::new();
+        assert!(stats.take().is_empty());
+        stats.record(1.0, 0.25);
+        stats.record(0.5, 0.75);
+        assert_eq!(
+            stats.take(),
+            [
+                ("policy_entropy", 0.75),
+                ("policy_kl", 0.5),
+                ("policy_kl_max", 0.75)
+            ]
+        );
+        assert!(stats.take().is_empty(), "Reset after taking");
+    }
+}
@@ -0,0 +1,216 @@
+/// A bucket of consecutive points, represented by its minimum and maximum
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bucket {
+    min: (f64, f64),
+    max: (f64, f64),
+    count: usize,
+}
+
+impl Bucket {
+    fn new(point: (f64, f64)) -> Self {
+        Self {
+            min: point,
+            max: point,
+            count: 1,
+        }
+    }
+
+    fn push(&mut self, point: (f64, f64)) {
+        if point.1 < self.min.1 {
+            self.min = point;
+        }
+        if point.1 > self.max.1 {
+            self.max = point;
+        }
+        self.count += 1;
+    }
+
+    fn merge(self, other: Self) -> Self {
+        Self {
+            min: if other.min.1 < self.min.1 {
+                other.min
+            } else {
+                self.min
+            },
+            max: if other.max.1 > self.max.1 {
+                other.max
+            } else {
+                self.max
+            },
+            count: self.count + other.count,
+        }
+    }
+
+    /// Push the points representing this bucket, ordered by x, along with the number of raw points each stands for
+    fn extend_representatives(&self, points: &mut Vec<(f64, f64)>, weights: &mut Vec<usize>) {
+        if self.count == 1 || self.min == self.max {
+            points.push(self.min);
+            weights.push(self.count);
+            return;
+        }
+
+        let (first, second) = if self.min.0 <= self.max.0 {
+            (self.min, self.max)
+        } else {
+            (self.max, self.min)
+        };
+        points.extend([first, second]);
+        weights.extend([self.count / 2, self.count - self.count / 2]);
+    }
+}
+
+/// A growing series of `(x, y)` points that is decimated to a bounded size
+///
+/// Points are grouped into buckets of consecutive points and each bucket is represented by its minimum and maximum
+/// (min/max decimation), which preserves the envelope of the series. Once the bucket capacity is reached, adjacent
+/// buckets are merged and the bucket size doubles, so memory use is bounded by the capacity no matter how many points
+/// are pushed.
+///
+/// Until the capacity is first reached, every point is kept as is.
+#[derive(Debug, Clone)]
+pub struct Decimated {
+    buckets: Vec<Bucket>,
+    bucket_size: usize,
+    capacity: usize,
+    len: usize,
+    points: Vec<(f64, f64)>,
+    weights: Vec<usize>,
+    last_start: usize,
+}
+
+impl Decimated {
+    /// Create an empty series holding at most `capacity` buckets
+    ///
+    /// **Panics** if `capacity` is less than 2
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity >= 2, "`capacity` is at least 2");
+        Self {
+            buckets: Vec::with_capacity(capacity),
+            bucket_size: 1,
+            capacity,
+            len: 0,
+            points: Vec::new(),
+            weights: Vec::new(),
+            last_start: 0,
+        }
+    }
+
+    /// The number of raw points pushed so far
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no points have been pushed yet
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of raw points represented by each bucket
+    pub fn bucket_size(&self) -> usize {
+        self.bucket_size
+    }
+
+    /// Add a point to the end of the series
+    pub fn push(&mut self, point: (f64, f64)) {
+        self.len += 1;
+
+        if let Some(last) = self.buckets.last_mut() {
+            if last.count < self.bucket_size {
+                last.push(point);
+                self.points.truncate(self.last_start);
+                self.weights.truncate(self.last_start);
+                last.extend_representatives(&mut self.points, &mut self.weights);
+                return;
+            }
+        }
+
+        if self.buckets.len() == self.capacity {
+            self.compact();
+        }
+
+        let bucket = Bucket::new(point);
+        self.last_start = self.points.len();
+        bucket.extend_representatives(&mut self.points, &mut self.weights);
+        self.buckets.push(bucket);
+    }
+
+    /// The representative points of the series, ordered by bucket
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    /// The number of raw points each of the [`points`](Decimated::points) stands for
+    pub fn weights(&self) -> &[usize] {
+        &self.weights
+    }
+
+    /// Merge adjacent buckets, halving the number of buckets
+    fn compact(&mut self) {
+        self.buckets = self
+            .buckets
+            .chunks(2)
+            .map(|pair| match *pair {
+                [a, b] => a.merge(b),
+                [a] => a,
+                _ => unreachable!("chunks are never empty"),
+            })
+            .collect();
+        self.bucket_size *= 2;
+
+        self.points.clear();
+        self.weights.clear();
+        for bucket in &self.buckets {
+            self.last_start = self.points.len();
+            bucket.extend_representatives(&mut self.points, &mut self.weights);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimated_functional() {
+        let mut series = Decimated::new(4);
+        for i in 0..4 {
+            series.push((i as f64, i as f64));
+        }
+
+        assert_eq!(series.len(), 4, "length correct");
+        assert_eq!(
+            series.points(),
+            [(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (3.0, 3.0)],
+            "points are kept as is below capacity"
+        );
+        assert_eq!(series.weights(), [1, 1, 1, 1], "weights correct");
+
+        series.push((4.0, -1.0));
+        assert_eq!(series.bucket_size(), 2, "bucket size doubled");
+        assert_eq!(
+            series.points(),
+            [(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (3.0, 3.0), (4.0, -1.0)],
+            "buckets represented by min and max"
+        );
+        assert_eq!(series.weights(), [1, 1, 1, 1, 1], "weights correct");
+
+        series.push((5.0, 10.0));
+        assert_eq!(series.points().len(), 6, "partial bucket updated in place");
+        assert_eq!(series.points()[5], (5.0, 10.0), "new max in last bucket");
+
+        for i in 6..100 {
+            series.push((i as f64, 0.0));
+        }
+        assert_eq!(series.len(), 100, "all raw points counted");
+        assert!(series.points().len() <= 8, "memory stays bounded");
+        assert_eq!(
+            series.weights().iter().sum::<usize>(),
+            100,
+            "weights account for every raw point"
+        );
+        assert!(
+            series.points().contains(&(5.0, 10.0)),
+            "global maximum survives decimation"
+        );
+    }
+}
@@ -1,5 +1,7 @@
+mod decimated;
 mod ring_buffer;
 mod sum_tree;
 
+pub use decimated::Decimated;
 pub use ring_buffer::RingBuffer;
 pub use sum_tree::SumTree;
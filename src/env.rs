@@ -4,13 +4,18 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
-use crate::util::summary_from_keys;
+use crate::{reward::Reward, util::summary_from_keys};
 
 /// Represents a Markov decision process, defining the dynamics of an environment
 /// in which an agent can operate.
 ///
 /// This base trait represents the common case of a discrete-time MDP with one agent.
-pub trait Environment {
+///
+/// ### Generics
+/// - `R` - The [`Reward`] of a step, `f32` unless stated otherwise, e.g. `f64` for double precision accounting or
+///   `[f32; N]` for `N` objectives. Agents learn from `f32` rewards, so other environments are trained on through
+///   [`Scalarized`](crate::reward::Scalarized).
+pub trait Environment<R: Reward = f32> {
     /// A representation of the state of the environment to be passed to an agent
     ///
     /// This should be a relatively simple data type
@@ -30,7 +35,7 @@ pub trait Environment {
     /// Update the environment in response to a an action taken by an agent, producing a new state and associated reward
     ///
     /// **Returns** `(next_state, reward)`
-    fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32);
+    fn step(&mut self, action: Self::Action) -> (Option<Self::State>, R);
 
     /// Reset the environment to an initial state
     ///
@@ -44,16 +49,65 @@ pub trait Environment {
     fn is_active(&self) -> bool {
         true
     }
+
+    /// The discount factor γ that returns in this environment are meant to be discounted with, e.g. `1` for short
+    /// episodic tasks or a value close to `1` for tasks with long horizons
+    ///
+    /// The [`Trainer`](crate::train::Trainer) sets it as the agent's `gamma` [hyperparameter](crate::traits::Hyperparam)
+    /// when training starts and whenever it changes, e.g. between curriculum stages.
+    ///
+    /// **Default:** `None`, leaving the discount factor to the agent's configuration
+    fn discount(&self) -> Option<f32> {
+        None
+    }
 }
 
 /// An [Environment] with a discrete action space
-pub trait DiscreteActionSpace: Environment {
+pub trait DiscreteActionSpace<R: Reward = f32>: Environment<R> {
     /// Get the available actions for the current state
     ///
     /// The returned slice should never be empty, instead specify an action that represents doing nothing if necessary.
     fn actions(&self) -> Vec<Self::Action>;
 }
 
+/// A discrete action type with `COUNT` actions, indexed contiguously from `0`
+///
+/// Derived for fieldless enums with `#[derive(DiscreteAction)]` from the `derive` feature, which indexes the variants
+/// in declaration order and also implements `From<i32>` and `Into<[i32; 1]>`, the conversions of
+/// [`DQNAgent`](crate::algo::dqn::DQNAgent) actions.
+///
+/// ```ignore
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DiscreteAction)]
+/// enum Move {
+///     Left,
+///     Right,
+/// }
+///
+/// assert_eq!(Move::COUNT, 2);
+/// assert_eq!(Move::Right.index(), 1);
+/// ```
+pub trait DiscreteAction: Sized {
+    /// The number of actions
+    const COUNT: usize;
+
+    /// The index of the action, in `0..COUNT`
+    fn index(&self) -> usize;
+
+    /// The action at `index`, or `None` if it isn't in `0..COUNT`
+    fn from_index(index: usize) -> Option<Self>;
+
+    /// Every action in the order of their indices, e.g. for [`DiscreteActionSpace::actions`]
+    fn all() -> Vec<Self> {
+        (0..Self::COUNT)
+            .map(|index| Self::from_index(index).expect("Indices below `COUNT` are valid"))
+            .collect()
+    }
+}
+
+/// Derive [`DiscreteAction`] for fieldless enums
+#[cfg(feature = "derive")]
+pub use rl_derive::DiscreteAction;
+
 /// An [Environment] with a discrete state space
 pub trait DiscreteStateSpace: Environment {
     /// Get all possible states in the environment
@@ -66,6 +120,24 @@ pub trait DeterministicModel: Environment {
     fn model(&self, state: Self::State, action: Self::Action) -> (Option<Self::State>, f32);
 }
 
+/// An [Environment] whose steps split into a deterministic effect of the action and a random response of the
+/// environment, e.g. the tile that appears after a move in 2048 or the opponent's roll and move in backgammon
+///
+/// The afterstate is the state between the two. Many state action pairs lead to the same afterstate, e.g. different
+/// moves to the same position, so learning the values of afterstates instead of actions generalizes between them.
+/// See [`AfterstateAgent`](crate::algo::tabular::afterstate::AfterstateAgent).
+pub trait AfterstateEnvironment: Environment {
+    /// The state after the effect of an action, before the response of the environment
+    type Afterstate: Clone + Debug;
+
+    /// Get the afterstate of taking `action` in `state` and the reward of the action alone
+    ///
+    /// This is a model of the environment like [`DeterministicModel::model`], so it must only depend on `state` and
+    /// `action`, not on the current state of the environment. The rest of the reward returned by
+    /// [`step`](Environment::step) is attributed to the response of the environment.
+    fn afterstate(&self, state: &Self::State, action: &Self::Action) -> (Self::Afterstate, f32);
+}
+
 /// An [Environment] with known dynamics
 pub trait KnownDynamics: Environment {
     /// The dynamics of the environment
@@ -83,6 +155,94 @@ pub trait KnownDynamics: Environment {
     ) -> f32;
 }
 
+/// An [Environment] with a cost signal next to the reward, for learning under safety constraints, e.g. on the number
+/// of collisions or the resources used
+///
+/// Costs are kept apart from rewards so that agents can keep their expected total under a budget instead of trading
+/// them off against rewards at a fixed rate, see [`Constrained`](crate::algo::constrained::Constrained).
+pub trait ConstrainedEnvironment: Environment {
+    /// The cost of the last step, `0` before the first step of an episode
+    fn cost(&self) -> f32;
+}
+
+/// A discrete state encoded as a one-hot feature vector of length `N`, to feed tabular environments to neural agents
+///
+/// Converted to tensors of shape `[batch, N]` with [`ToTensor`](crate::traits::ToTensor), so an environment with
+/// this as its state can be used with e.g. [`DQNAgent`](crate::algo::dqn::DQNAgent) and compared to tabular agents.
+///
+/// ```ignore
+/// // A 4x12 gridworld with (row, col) positions
+/// let state = OneHot::<48>::from_indices([row, col], [4, 12]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OneHot<const N: usize>(usize);
+
+impl<const N: usize> OneHot<N> {
+    /// Encode the state `index`
+    ///
+    /// **Panics** if `index` is not less than `N`
+    pub fn new(index: usize) -> Self {
+        assert!(index < N, "Index {index} out of range for {N} states");
+        Self(index)
+    }
+
+    /// Encode a tuple of indices, numbering the states of a grid with dimensions `shape` in row-major order
+    ///
+    /// **Panics** if an index is out of range for its dimension, or the grid doesn't have `N` states
+    pub fn from_indices<const K: usize>(indices: [usize; K], shape: [usize; K]) -> Self {
+        assert_eq!(
+            shape.iter().product::<usize>(),
+            N,
+            "Shape {shape:?} doesn't have {N} states"
+        );
+        let index = indices.iter().zip(shape).fold(0, |index, (&i, len)| {
+            assert!(
+                i < len,
+                "Indices {indices:?} out of range for shape {shape:?}"
+            );
+            index * len + i
+        });
+        Self(index)
+    }
+
+    /// The index of the state, the position of the one in the feature vector
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+impl<const N: usize> From<usize> for OneHot<N> {
+    fn from(index: usize) -> Self {
+        Self::new(index)
+    }
+}
+
+/// A continuous action of `A` values in `[-1, 1]`, e.g. the tanh output of a policy network
+///
+/// Decoded from network outputs with [`FromTensor`](crate::traits::FromTensor), which clamps them to `[-1, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContinuousAction<const A: usize>(pub [f32; A]);
+
+impl<const A: usize> ContinuousAction<A> {
+    /// Map every value from `[-1, 1]` to the interval `[low[i], high[i]]` of the environment's action space
+    pub fn scale(&self, low: [f32; A], high: [f32; A]) -> [f32; A] {
+        std::array::from_fn(|i| low[i] + (self.0[i] + 1.0) / 2.0 * (high[i] - low[i]))
+    }
+}
+
+impl<const A: usize> From<ContinuousAction<A>> for [f32; A] {
+    fn from(action: ContinuousAction<A>) -> Self {
+        action.0
+    }
+}
+
+/// An [Environment] that can be rendered as text, e.g. for the render panel in [viz](crate::viz)
+pub trait Render: Environment {
+    /// Render the current state of the environment as a multiline ASCII frame
+    fn render(&self) -> String;
+}
+
 /// A format for reporting training results to [viz](crate::viz)
 ///
 /// Functionally a wrapper around a [BTreeMap] such that values are always returned in the same order.
@@ -132,8 +292,10 @@ impl DerefMut for Report {
 pub(crate) mod tests {
     use super::*;
 
+    #[cfg(feature = "train")]
     pub(crate) struct MockEnv;
 
+    #[cfg(feature = "train")]
     impl Environment for MockEnv {
         type State = i32;
         type Action = i32;
@@ -151,6 +313,42 @@ pub(crate) mod tests {
         }
     }
 
+    #[test]
+    fn one_hot() {
+        assert_eq!(OneHot::<5>::new(3).index(), 3);
+        assert_eq!(OneHot::<48>::from_indices([2, 7], [4, 12]).index(), 31);
+        assert_eq!(OneHot::<24>::from_indices([1, 2, 3], [2, 3, 4]).index(), 23);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn one_hot_out_of_range() {
+        OneHot::<48>::from_indices([4, 0], [4, 12]);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derive_discrete_action() {
+        #[derive(Debug, Clone, Copy, PartialEq, DiscreteAction)]
+        enum Move {
+            Left,
+            Stay = 5,
+            Right,
+        }
+
+        assert_eq!(Move::COUNT, 3);
+        assert_eq!(
+            Move::Stay.index(),
+            1,
+            "Indices follow the declaration order"
+        );
+        assert_eq!(Move::from_index(2), Some(Move::Right));
+        assert_eq!(Move::from_index(3), None);
+        assert_eq!(Move::all(), [Move::Left, Move::Stay, Move::Right]);
+        assert_eq!(Move::from(0), Move::Left);
+        assert_eq!(<[i32; 1]>::from(Move::Right), [2]);
+    }
+
     #[test]
     fn report_functional() {
         let mut report = Report::new(vec!["c", "a", "b"]);
@@ -0,0 +1,140 @@
+use std::{error::Error, fmt, io};
+
+/// The errors of the crate
+///
+/// Invalid hyperparameters are reported when agents, memories and schedules are constructed, and non-finite values
+/// when they show up during training, so they can be handled instead of panicking deep inside a run. I/O errors of
+/// metric sinks and checkpoints are passed through as [`RlError::Io`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RlError {
+    /// A hyperparameter is outside of the interval `[low, high]`, or NaN
+    OutOfRange {
+        name: &'static str,
+        value: f64,
+        low: f64,
+        high: f64,
+    },
+    /// Hyperparameters that don't fit together, e.g. a decay whose rate moves away from its final value
+    InvalidHyperparameters(String),
+    /// A value that must be finite was NaN or infinite, e.g. the return of a diverging agent
+    NonFinite { name: &'static str, episode: u64 },
+    /// A batch of rows with different lengths, which can't be converted to a tensor
+    RaggedBatch {
+        index: usize,
+        len: usize,
+        expected: usize,
+    },
+    /// A backend was requested whose crate feature isn't enabled
+    UnavailableBackend {
+        backend: &'static str,
+        feature: &'static str,
+    },
+    /// An I/O error, e.g. of a metric sink or while saving a checkpoint
+    Io(io::Error),
+}
+
+/// A [`Result`](std::result::Result) with [`RlError`] as the error
+pub type Result<T, E = RlError> = std::result::Result<T, E>;
+
+impl fmt::Display for RlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfRange {
+                name,
+                value,
+                low,
+                high,
+            } => write!(
+                f,
+                "invalid value {value} for `{name}`, must be in the interval [{low}, {high}]"
+            ),
+            Self::InvalidHyperparameters(message) => {
+                write!(f, "invalid hyperparameters: {message}")
+            }
+            Self::NonFinite { name, episode } => {
+                write!(f, "`{name}` is not finite in episode {episode}")
+            }
+            Self::RaggedBatch {
+                index,
+                len,
+                expected,
+            } => write!(
+                f,
+                "row {index} of the batch has length {len}, expected {expected} like the first row"
+            ),
+            Self::UnavailableBackend { backend, feature } => write!(
+                f,
+                "the {backend} backend is not available, enable the `{feature}` feature"
+            ),
+            Self::Io(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for RlError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RlError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// I/O errors are unwrapped, other errors become [`InvalidInput`](io::ErrorKind::InvalidInput) or
+/// [`InvalidData`](io::ErrorKind::InvalidData) errors, for code that reports through [`io::Result`]
+impl From<RlError> for io::Error {
+    fn from(e: RlError) -> Self {
+        match e {
+            RlError::Io(e) => e,
+            e @ (RlError::NonFinite { .. } | RlError::RaggedBatch { .. }) => {
+                io::Error::new(io::ErrorKind::InvalidData, e)
+            }
+            e @ RlError::UnavailableBackend { .. } => io::Error::new(io::ErrorKind::Unsupported, e),
+            e => io::Error::new(io::ErrorKind::InvalidInput, e),
+        }
+    }
+}
+
+/// Check that the hyperparameter `name` is in the interval `[low, high]`
+#[cfg(feature = "train")]
+pub(crate) fn check_interval(name: &'static str, value: f32, low: f32, high: f32) -> Result<()> {
+    match (low..=high).contains(&value) {
+        true => Ok(()),
+        false => Err(RlError::OutOfRange {
+            name,
+            value: value as f64,
+            low: low as f64,
+            high: high as f64,
+        }),
+    }
+}
+
+#[cfg(all(test, feature = "train"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_interval_functional() {
+        assert!(check_interval("alpha", 0.5, 0.0, 1.0).is_ok());
+        assert!(check_interval("alpha", 1.0, 0.0, 1.0).is_ok());
+        assert!(check_interval("alpha", f32::NAN, 0.0, 1.0).is_err());
+
+        let e = check_interval("gamma", 1.5, 0.0, 1.0).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "invalid value 1.5 for `gamma`, must be in the interval [0, 1]"
+        );
+        assert_eq!(
+            io::Error::from(e).kind(),
+            io::ErrorKind::InvalidInput,
+            "Converts for io::Result code"
+        );
+    }
+}
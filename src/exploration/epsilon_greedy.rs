@@ -1,25 +1,58 @@
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
-use crate::decay::Decay;
+use crate::{
+    decay::Decay,
+    seed::{self, Stream},
+};
 
 use super::Choice;
 
 /// Epsilon greedy exploration policy with time-decaying epsilon threshold
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EpsilonGreedy<D: Decay> {
     epsilon: D,
+    #[cfg_attr(feature = "serde", serde(default))]
+    floor: f32,
 }
 
 impl<D: Decay> EpsilonGreedy<D> {
     /// Initialize epsilon greedy policy with a decay strategy
     pub fn new(decay: D) -> Self {
-        Self { epsilon: decay }
+        Self {
+            epsilon: decay,
+            floor: 0.0,
+        }
+    }
+
+    /// Never explore with a probability below `floor`, whatever the decay strategy
+    pub fn with_floor(mut self, floor: f32) -> Self {
+        self.set_floor(floor);
+        self
+    }
+
+    /// The lowest probability of exploring
+    pub fn floor(&self) -> f32 {
+        self.floor
+    }
+
+    /// Change the lowest probability of exploring, e.g. to explore more in a long run that got stuck
+    ///
+    /// **Panics** if `floor` is not in `[0, 1]`
+    pub fn set_floor(&mut self, floor: f32) {
+        assert!((0.0..=1.0).contains(&floor), "floor must be in [0, 1]");
+        self.floor = floor;
+    }
+
+    /// The probability of exploring in the given episode
+    pub fn epsilon(&self, episode: u64) -> f32 {
+        self.epsilon.evaluate(episode as f32).max(self.floor)
     }
 
     /// Invoke epsilon greedy policy for current episode
-    pub fn choose(&self, episode: u32) -> Choice {
-        let epsilon = self.epsilon.evaluate(episode as f32);
-        if thread_rng().gen::<f32>() > epsilon {
+    pub fn choose(&self, episode: u64) -> Choice {
+        let epsilon = self.epsilon(episode);
+        if seed::rng(Stream::Exploration).gen::<f32>() > epsilon {
             Choice::Exploit
         } else {
             Choice::Explore
@@ -39,4 +72,12 @@ mod tests {
 
         exploration.choose(12);
     }
+
+    #[test]
+    fn epsilon_floor() {
+        let mut exploration = EpsilonGreedy::new(decay::Constant::new(0.01));
+        assert_eq!(exploration.epsilon(0), 0.01);
+        exploration.set_floor(0.2);
+        assert_eq!(exploration.epsilon(0), 0.2);
+    }
 }
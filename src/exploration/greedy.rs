@@ -0,0 +1,75 @@
+use std::cmp::Ordering;
+
+use rand::Rng;
+
+use crate::seed::{self, Stream};
+
+/// Find the maximum of `items` with respect to `compare`, like [`Iterator::max_by`], but choose uniformly at random
+/// between equal maxima
+///
+/// [`Iterator::max_by`] always returns the last maximum, which biases greedy agents towards the same actions while
+/// their values are still equal, e.g. before any of them are learned. Ties are broken with the
+/// [`Stream::Exploration`] generator, so they are reproducible with [`Seeds`](crate::seed::Seeds).
+///
+/// ```ignore
+/// let action = max_by_random(env.actions(), |a, b| q(a).total_cmp(&q(b)));
+/// ```
+///
+/// **Returns** `None` if `items` is empty
+pub fn max_by_random<T>(
+    items: impl IntoIterator<Item = T>,
+    mut compare: impl FnMut(&T, &T) -> Ordering,
+) -> Option<T> {
+    let mut rng = seed::rng(Stream::Exploration);
+    let mut max = None;
+    let mut ties = 0;
+    for item in items {
+        let Some(current) = &max else {
+            max = Some(item);
+            ties = 1;
+            continue;
+        };
+        match compare(&item, current) {
+            Ordering::Greater => {
+                max = Some(item);
+                ties = 1;
+            }
+            Ordering::Equal => {
+                // Reservoir sampling, so each of the ties is kept with probability 1 / ties
+                ties += 1;
+                if rng.gen_range(0..ties) == 0 {
+                    max = Some(item);
+                }
+            }
+            Ordering::Less => (),
+        }
+    }
+    max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_ties() {
+        assert_eq!(max_by_random(Vec::<f32>::new(), f32::total_cmp), None);
+        assert_eq!(max_by_random([1.0, 3.0, 2.0], f32::total_cmp), Some(3.0));
+
+        let mut counts = [0; 4];
+        for _ in 0..4000 {
+            let (i, _) = max_by_random([1.0, 2.0, 2.0, 2.0].into_iter().enumerate(), |a, b| {
+                a.1.total_cmp(&b.1)
+            })
+            .unwrap();
+            counts[i] += 1;
+        }
+        assert_eq!(counts[0], 0);
+        assert!(
+            counts[1..]
+                .iter()
+                .all(|&count| (1100..1600).contains(&count)),
+            "Ties are chosen uniformly, {counts:?}"
+        );
+    }
+}
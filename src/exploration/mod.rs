@@ -5,11 +5,13 @@ pub enum Choice {
 }
 
 mod epsilon_greedy;
+mod greedy;
 mod softmax;
 mod thompson;
 mod ucb;
 
 pub use epsilon_greedy::EpsilonGreedy;
+pub use greedy::max_by_random;
 pub use softmax::Softmax;
 // pub use thompson::Thompson;
 pub use ucb::UCB;
@@ -1,14 +1,15 @@
 use std::ops::AddAssign;
 
 use burn::tensor::{self, backend::Backend, Tensor};
-use rand::{
-    distributions::{uniform::SampleUniform, Distribution, WeightedIndex},
-    thread_rng,
-};
+use rand::distributions::{uniform::SampleUniform, Distribution, WeightedIndex};
 
-use crate::decay::Decay;
+use crate::{
+    decay::Decay,
+    seed::{self, Stream},
+};
 
 /// Softmax exploration policy (also known as Boltzmann exploration) with time-decaying temperature
+#[derive(Debug, Clone)]
 pub struct Softmax<D: Decay> {
     temperature: D,
 }
@@ -19,14 +20,21 @@ impl<D: Decay> Softmax<D> {
         Self { temperature: decay }
     }
 
+    /// The temperature at time `t`
+    pub fn temperature(&self, t: f32) -> f32 {
+        self.temperature.evaluate(t)
+    }
+
     /// Invoke softmax exploration policy at time `t` with provided Q values
     pub fn choose(&self, t: f32, q_values: &[f32]) -> usize {
         let tau = self.temperature.evaluate(t);
-        let exponentials = q_values.iter().map(|x| (x / tau).exp());
+        // Shifting by the largest value keeps the exponentials finite at low temperatures
+        let max = q_values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let exponentials = q_values.iter().map(|x| ((x - max) / tau).exp());
         let sum: f32 = exponentials.clone().sum();
         let weights = exponentials.map(|x| x / sum);
         let dist = WeightedIndex::new(weights).expect("`q_values` is not empty");
-        dist.sample(&mut thread_rng())
+        dist.sample(&mut seed::rng(Stream::Exploration))
     }
 
     /// Invoke softmax exploration policy at time `t` with provided 1D [Tensor] of Q values
@@ -41,6 +49,6 @@ impl<D: Decay> Softmax<D> {
             .iter_dim(0)
             .map(|t| t.into_scalar());
         let dist = WeightedIndex::new(weights).expect("`tensor` is not empty");
-        dist.sample(&mut thread_rng())
+        dist.sample(&mut seed::rng(Stream::Exploration))
     }
 }
@@ -1,5 +1,7 @@
 use burn::tensor::{backend::Backend, Tensor};
 
+use super::max_by_random;
+
 /// Upper confidence bound exploration policy
 pub struct UCB<const A: usize> {
     c: f32,
@@ -21,11 +23,11 @@ impl<const A: usize> UCB<A> {
     /// Invoke UCB policy at time `t` with provided Q values
     pub fn choose(&mut self, t: f32, q_values: &[f32; A]) -> usize {
         let k = self.c * t.log10().sqrt();
-        let choice = q_values
+        let bounds = q_values
             .iter()
             .enumerate()
-            .map(|(i, x)| (i, x + k * self.counter[i].powf(-0.5)))
-            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(i, x)| (i, x + k * self.counter[i].powf(-0.5)));
+        let choice = max_by_random(bounds, |(_, a), (_, b)| a.total_cmp(b))
             .map(|(i, _)| i)
             .expect("`q_values` is not empty");
 
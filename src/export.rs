@@ -0,0 +1,296 @@
+use std::{collections::HashMap, hash::Hash, io, path::PathBuf};
+
+use burn::{
+    prelude::*,
+    record::{FullPrecisionSettings, NamedMpkBytesRecorder, NamedMpkFileRecorder, Recorder},
+};
+
+use crate::traits::ToTensor;
+
+/// A network that maps a batch of observations to one score per action, e.g. Q values or logits
+///
+/// Implemented for every backend, unlike [`DQNModel`](crate::algo::dqn::DQNModel), so exported networks can run
+/// without autodiff. Models used for training usually implement both, with the same forward pass.
+///
+/// ### Generics
+/// - `B` - A burn backend
+/// - `D` - The dimension of the input tensor
+pub trait PolicyNet<B: Backend, const D: usize>: Module<B> {
+    /// Forward pass through the network
+    fn forward(&self, input: Tensor<B, D>) -> Tensor<B, 2>;
+}
+
+/// Save the parameters of `net` to `<path>.mpk` for inference with a [`PolicyRunner`]
+///
+/// The network is saved without the agent that trained it, as a burn record in the named MessagePack format with
+/// full precision, which loads on any backend. burn has no ONNX export, so networks that need to run outside of burn
+/// have to be converted from their records.
+///
+/// Exported policies don't need the training half of the crate. Depending on it with `default-features = false`
+/// leaves out the `train` feature, and with it autodiff, the agents, the trainer and their dependencies, keeping
+/// only this module, the [`Environment`](crate::env::Environment) traits and [`ToTensor`].
+pub fn export_net<B: Backend, M: Module<B>>(net: M, path: impl Into<PathBuf>) -> io::Result<()> {
+    let recorder = NamedMpkFileRecorder::<FullPrecisionSettings>::new();
+    net.save_file(path.into(), &recorder)
+        .map_err(recorder_error)
+}
+
+/// Runs an exported [`PolicyNet`], choosing the action with the highest score
+///
+/// ```ignore
+/// agent.export("policy")?;
+/// // On the target, with the same architecture on a backend without autodiff
+/// let net = ModelConfig::new(64, 128).init::<NdArray>(&device);
+/// let runner = PolicyRunner::load(net, "policy", device)?;
+/// let action = runner.act([0.0, 0.1, -0.02, 0.3]);
+/// ```
+///
+/// ### Generics
+/// - `B` - A burn backend
+/// - `M` - The [`PolicyNet`]
+/// - `D` - The dimension of the input tensor
+#[derive(Debug, Clone)]
+pub struct PolicyRunner<B: Backend, M, const D: usize> {
+    net: M,
+    device: B::Device,
+}
+
+impl<B, M, const D: usize> PolicyRunner<B, M, D>
+where
+    B: Backend,
+    M: PolicyNet<B, D>,
+{
+    /// Run `net` as it is
+    pub fn new(net: M, device: B::Device) -> Self {
+        Self { net, device }
+    }
+
+    /// Load the parameters exported to `<path>.mpk` by [`export_net`] into `net`, which must have the same
+    /// architecture as the exported network
+    pub fn load(net: M, path: impl Into<PathBuf>, device: B::Device) -> io::Result<Self> {
+        let recorder = NamedMpkFileRecorder::<FullPrecisionSettings>::new();
+        let net = net
+            .load_file(path.into(), &recorder, &device)
+            .map_err(recorder_error)?;
+        Ok(Self { net, device })
+    }
+
+    /// Load parameters exported by [`export_net`] from memory into `net`, e.g. the contents of the `.mpk` file
+    /// embedded in the binary with [`include_bytes!`], for targets without a filesystem
+    pub fn from_bytes(net: M, bytes: Vec<u8>, device: B::Device) -> io::Result<Self> {
+        let recorder = NamedMpkBytesRecorder::<FullPrecisionSettings>::new();
+        let record = Recorder::<B>::load(&recorder, bytes, &device).map_err(recorder_error)?;
+        let net = net.load_record(record);
+        Ok(Self { net, device })
+    }
+
+    /// The network
+    pub fn net(&self) -> &M {
+        &self.net
+    }
+
+    /// The score of every action for `observation`
+    pub fn scores<S>(&self, observation: S) -> Vec<f32>
+    where
+        Vec<S>: ToTensor<B, D, Float>,
+    {
+        let input = vec![observation].to_tensor(&self.device);
+        self.net.forward(input).into_data().convert::<f32>().value
+    }
+
+    /// The index of the action with the highest score for `observation`
+    ///
+    /// Unlike the greedy policies of training, which break ties with the seeded
+    /// [`max_by_random`](crate::exploration::max_by_random), ties go to the lowest index: exported policies run
+    /// without the `train` feature and its random number generators, and a deployed policy should always answer the
+    /// same observation with the same action.
+    pub fn act<S>(&self, observation: S) -> usize
+    where
+        Vec<S>: ToTensor<B, D, Float>,
+    {
+        let scores = self.scores(observation);
+        (0..scores.len())
+            .reduce(|best, action| match scores[action] > scores[best] {
+                true => action,
+                false => best,
+            })
+            .unwrap_or(0)
+    }
+}
+
+/// A greedy tabular policy, mapping every visited state to an action
+///
+/// Created from a trained agent, e.g. with
+/// [`QTableAgent::greedy_policy`](crate::algo::tabular::q_table::QTableAgent::greedy_policy), and serialized as a
+/// list of `[state, action]` pairs. The policy runs without the agent, so a trained policy can be embedded in another
+/// program as data:
+///
+/// ```ignore
+/// agent.greedy_policy(&env).save("frozen_lake.json")?;
+///
+/// // In another program
+/// let policy = TablePolicy::<FrozenLakeState, FrozenLakeAction>::from_json(include_str!("frozen_lake.json"))?;
+/// let action = policy.act(&state);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TablePolicy<S: Eq + Hash, A> {
+    table: HashMap<S, A>,
+}
+
+impl<S: Eq + Hash, A: Clone> TablePolicy<S, A> {
+    /// A policy choosing `table[state]` in each state
+    pub fn new(table: HashMap<S, A>) -> Self {
+        Self { table }
+    }
+
+    /// The action for `state`, or `None` if the state was never visited during training
+    pub fn act(&self, state: &S) -> Option<A> {
+        self.table.get(state).cloned()
+    }
+
+    /// The number of states with an action
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Whether the policy has no states
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<S: Eq + Hash, A: Clone> TablePolicy<S, A> {
+    /// Write the policy to a JSON file at `path`
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> io::Result<()>
+    where
+        S: serde::Serialize,
+        A: serde::Serialize,
+    {
+        crate::traits::checkpoint::write_json(path.as_ref(), self)
+    }
+
+    /// Read a policy written by [`save`](TablePolicy::save)
+    pub fn load(path: impl AsRef<std::path::Path>) -> io::Result<Self>
+    where
+        S: serde::de::DeserializeOwned,
+        A: serde::de::DeserializeOwned,
+    {
+        crate::traits::checkpoint::read_json(path.as_ref())
+    }
+
+    /// The policy as a JSON string, in the format of [`save`](TablePolicy::save)
+    pub fn to_json(&self) -> io::Result<String>
+    where
+        S: serde::Serialize,
+        A: serde::Serialize,
+    {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Read a policy from a JSON string, e.g. a file saved with [`save`](TablePolicy::save) and embedded with
+    /// [`include_str`]
+    pub fn from_json(json: &str) -> io::Result<Self>
+    where
+        S: serde::de::DeserializeOwned,
+        A: serde::de::DeserializeOwned,
+    {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+/// A list of `[state, action]` pairs, since JSON objects only have string keys
+#[cfg(feature = "serde")]
+impl<S, A> serde::Serialize for TablePolicy<S, A>
+where
+    S: Eq + Hash + serde::Serialize,
+    A: serde::Serialize,
+{
+    fn serialize<T: serde::Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+        serializer.collect_seq(&self.table)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, S, A> serde::Deserialize<'de> for TablePolicy<S, A>
+where
+    S: Eq + Hash + serde::Deserialize<'de>,
+    A: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pairs = Vec::<(S, A)>::deserialize(deserializer)?;
+        Ok(Self {
+            table: pairs.into_iter().collect(),
+        })
+    }
+}
+
+fn recorder_error(e: burn::record::RecorderError) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use burn::{
+        backend::{ndarray::NdArrayDevice, NdArray as B},
+        nn::{Linear, LinearConfig},
+    };
+
+    use super::*;
+
+    #[derive(Module, Debug)]
+    struct Net<B: Backend> {
+        fc: Linear<B>,
+    }
+
+    impl<B: Backend> PolicyNet<B, 2> for Net<B> {
+        fn forward(&self, input: Tensor<B, 2>) -> Tensor<B, 2> {
+            self.fc.forward(input)
+        }
+    }
+
+    fn net() -> Net<B> {
+        Net {
+            fc: LinearConfig::new(3, 2).init(&NdArrayDevice::Cpu),
+        }
+    }
+
+    #[test]
+    fn policy_runner() {
+        let path = std::env::temp_dir().join("rl_export_policy");
+        let mut trained = net();
+        trained.fc.weight = trained.fc.weight.map(|w| w.zeros_like());
+        trained.fc.bias = trained
+            .fc
+            .bias
+            .map(|b| b.map(|b| Tensor::from_floats([-1.0, 1.0], &b.device())));
+        export_net(trained, &path).unwrap();
+
+        let runner = PolicyRunner::<B, _, 2>::load(net(), &path, NdArrayDevice::Cpu).unwrap();
+        assert_eq!(runner.scores([0.5f32, 0.2, 0.1]), [-1.0, 1.0]);
+        assert_eq!(runner.act([0.5f32, 0.2, 0.1]), 1);
+
+        let bytes = std::fs::read(path.with_extension("mpk")).unwrap();
+        std::fs::remove_file(path.with_extension("mpk")).unwrap();
+        let runner = PolicyRunner::<B, _, 2>::from_bytes(net(), bytes, NdArrayDevice::Cpu).unwrap();
+        assert_eq!(runner.act([0.5f32, 0.2, 0.1]), 1, "Loads from memory");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn table_policy() {
+        let path = std::env::temp_dir().join("rl_export_table_policy.json");
+        let policy = TablePolicy::new(HashMap::from([(0u32, 1u8), (5, 2)]));
+        policy.save(&path).unwrap();
+
+        let loaded = TablePolicy::<u32, u8>::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, policy);
+        assert_eq!(loaded.act(&5), Some(2));
+        assert_eq!(loaded.act(&1), None, "Unvisited state");
+
+        let json = policy.to_json().unwrap();
+        assert_eq!(TablePolicy::<u32, u8>::from_json(&json).unwrap(), policy);
+        assert!(TablePolicy::<u32, u8>::from_json("{}").is_err());
+    }
+}
@@ -0,0 +1,358 @@
+use burn::prelude::*;
+
+use crate::{
+    env::{DiscreteActionSpace, Environment},
+    error::{Result, RlError},
+    traits::{Features, ToTensor},
+};
+
+/// An encoding of the states `S` of an environment as feature vectors of a fixed size
+///
+/// Encodings are separate from the environment, so one environment can be paired with several of them, e.g. raw
+/// features for a neural agent and a [`TileCoding`] for a linear one. [`Featurized`] wraps an environment to give its
+/// agent the encoded states, so the encoding is chosen where the agent and its environment are constructed. Learned
+/// encodings implement this trait around their network.
+///
+/// ### Generics
+/// - `S` - The encoded states
+pub trait Featurize<S> {
+    /// The number of features of every state
+    fn size(&self) -> usize;
+
+    /// Append the features of `state` to `out`
+    fn featurize(&self, state: &S, out: &mut Vec<f32>);
+
+    /// The features of `state`
+    fn features(&self, state: &S) -> FeatureVector {
+        let mut out = Vec::with_capacity(self.size());
+        self.featurize(state, &mut out);
+        FeatureVector(out)
+    }
+}
+
+/// The features of a state, as encoded by a [`Featurize`]
+///
+/// Batches convert to tensors of shape `[batch, features]`, so they can be the states of neural agents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureVector(pub Vec<f32>);
+
+impl<B: Backend> ToTensor<B, 2, Float> for Vec<FeatureVector> {
+    /// **Panics** if the vectors have different lengths
+    fn to_tensor(self, device: &B::Device) -> Tensor<B, 2> {
+        let rows = self.len();
+        let size = self.first().map_or(0, |features| features.0.len());
+        assert!(
+            self.iter().all(|features| features.0.len() == size),
+            "Feature vectors of a batch have the same length"
+        );
+        let values = self
+            .into_iter()
+            .flat_map(|features| features.0)
+            .collect::<Vec<_>>();
+        Tensor::<B, 1>::from_floats(values.as_slice(), device).reshape([rows, size])
+    }
+}
+
+/// The [`Features`] of the state itself
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Raw;
+
+impl<S: Features> Featurize<S> for Raw {
+    fn size(&self) -> usize {
+        S::SIZE
+    }
+
+    fn featurize(&self, state: &S, out: &mut Vec<f32>) {
+        state.features(out);
+    }
+}
+
+/// A one-hot encoding of discrete states, from the index of each state
+///
+/// ```ignore
+/// // The positions of a 4x12 gridworld
+/// let encoding = OneHotEncoding::new(48, |&(row, col): &(usize, usize)| row * 12 + col);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct OneHotEncoding<F> {
+    size: usize,
+    index: F,
+}
+
+impl<F> OneHotEncoding<F> {
+    /// Encode `size` states, where `index` maps each state to a distinct index in `0..size`
+    pub fn new(size: usize, index: F) -> Self {
+        Self { size, index }
+    }
+}
+
+impl<S, F: Fn(&S) -> usize> Featurize<S> for OneHotEncoding<F> {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    /// **Panics** if the index of `state` is not in `0..size`
+    fn featurize(&self, state: &S, out: &mut Vec<f32>) {
+        let index = (self.index)(state);
+        assert!(
+            index < self.size,
+            "State index {index} out of range for a one-hot encoding of size {}",
+            self.size
+        );
+        let start = out.len();
+        out.resize(start + self.size, 0.0);
+        out[start + index] = 1.0;
+    }
+}
+
+/// A tile coding of continuous [`Features`], the classic sparse binary encoding for linear function approximation
+///
+/// Every tiling partitions the box between `low` and `high` into a grid of `tiles` tiles per dimension, shifted by a
+/// fraction of a tile from the other tilings, and each state activates the one tile it falls into in each tiling.
+/// Nearby states share most active tiles, so values learned for one generalize to its neighbors, see Sutton and
+/// Barto, 2018, *Reinforcement Learning: An Introduction*, section 9.5.4. Features outside of the box are clamped to
+/// it.
+#[derive(Debug, Clone)]
+pub struct TileCoding {
+    low: Vec<f32>,
+    high: Vec<f32>,
+    tiles: usize,
+    tilings: usize,
+}
+
+impl TileCoding {
+    /// A tile coding of the features between `low` and `high` with `tilings` tilings of `tiles` tiles per dimension
+    ///
+    /// Each tiling has `tiles + 1` tiles per dimension to cover the box after its shift, so a state has
+    /// `tilings * (tiles + 1)^dims` features, of which `tilings` are `1`.
+    ///
+    /// **Returns** [`RlError::InvalidHyperparameters`] if `low` and `high` have different lengths, `low` is not below
+    /// `high` in every dimension, or there are no tiles or tilings
+    pub fn new(low: Vec<f32>, high: Vec<f32>, tiles: usize, tilings: usize) -> Result<Self> {
+        if low.len() != high.len() || low.iter().zip(&high).any(|(low, high)| low >= high) {
+            return Err(RlError::InvalidHyperparameters(format!(
+                "tile coding needs `low` below `high` in every dimension, got {low:?} and {high:?}"
+            )));
+        }
+        if tiles == 0 || tilings == 0 {
+            return Err(RlError::InvalidHyperparameters(String::from(
+                "tile coding needs at least one tile and tiling",
+            )));
+        }
+        Ok(Self {
+            low,
+            high,
+            tiles,
+            tilings,
+        })
+    }
+
+    /// The number of tiles of one tiling
+    fn tiles_per_tiling(&self) -> usize {
+        (self.tiles + 1).pow(self.low.len() as u32)
+    }
+
+    /// The index of the active tile of every tiling for `values`
+    pub fn active_tiles(&self, values: &[f32]) -> Vec<usize> {
+        assert_eq!(
+            values.len(),
+            self.low.len(),
+            "Tile coding of {} features",
+            self.low.len()
+        );
+        (0..self.tilings)
+            .map(|tiling| {
+                let offset = tiling as f32 / self.tilings as f32;
+                let tile = values.iter().zip(self.low.iter().zip(&self.high)).fold(
+                    0,
+                    |tile, (&value, (&low, &high))| {
+                        let scaled =
+                            (value.clamp(low, high) - low) / (high - low) * self.tiles as f32;
+                        let coordinate = ((scaled + offset) as usize).min(self.tiles);
+                        tile * (self.tiles + 1) + coordinate
+                    },
+                );
+                tiling * self.tiles_per_tiling() + tile
+            })
+            .collect()
+    }
+}
+
+impl<S: Features> Featurize<S> for TileCoding {
+    fn size(&self) -> usize {
+        self.tilings * self.tiles_per_tiling()
+    }
+
+    /// **Panics** if the state doesn't have as many features as the box of the tile coding has dimensions
+    fn featurize(&self, state: &S, out: &mut Vec<f32>) {
+        let mut values = Vec::with_capacity(S::SIZE);
+        state.features(&mut values);
+        let start = out.len();
+        out.resize(start + Featurize::<S>::size(self), 0.0);
+        for tile in self.active_tiles(&values) {
+            out[start + tile] = 1.0;
+        }
+    }
+}
+
+/// An [`Environment`] whose states are encoded by a [`Featurize`], so its agent learns from the features without
+/// changing the environment
+///
+/// ```ignore
+/// let encoding = TileCoding::new(vec![-1.2, -0.07], vec![0.6, 0.07], 8, 8)?;
+/// let env = Featurized::new(MountainCar::new(), encoding);
+/// ```
+///
+/// ### Generics
+/// - `E` - The wrapped [`Environment`]
+/// - `F` - The encoding of its states
+#[derive(Debug, Clone)]
+pub struct Featurized<E, F> {
+    env: E,
+    featurizer: F,
+}
+
+impl<E, F> Featurized<E, F>
+where
+    E: Environment,
+    F: Featurize<E::State>,
+{
+    /// Wrap `env`, encoding its states with `featurizer`
+    pub fn new(env: E, featurizer: F) -> Self {
+        Self { env, featurizer }
+    }
+
+    /// The number of features of the states
+    pub fn size(&self) -> usize {
+        self.featurizer.size()
+    }
+
+    /// The encoding of the states
+    pub fn featurizer(&self) -> &F {
+        &self.featurizer
+    }
+
+    /// The wrapped environment
+    pub fn inner(&self) -> &E {
+        &self.env
+    }
+
+    /// Unwrap the environment
+    pub fn into_inner(self) -> E {
+        self.env
+    }
+}
+
+impl<E, F> Environment for Featurized<E, F>
+where
+    E: Environment,
+    F: Featurize<E::State>,
+{
+    type State = FeatureVector;
+    type Action = E::Action;
+
+    fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
+        let (next_state, reward) = self.env.step(action);
+        (
+            next_state.map(|state| self.featurizer.features(&state)),
+            reward,
+        )
+    }
+
+    fn reset(&mut self) -> Self::State {
+        let state = self.env.reset();
+        self.featurizer.features(&state)
+    }
+
+    fn random_action(&self) -> Self::Action {
+        self.env.random_action()
+    }
+
+    fn is_active(&self) -> bool {
+        self.env.is_active()
+    }
+
+    fn discount(&self) -> Option<f32> {
+        self.env.discount()
+    }
+}
+
+impl<E, F> DiscreteActionSpace for Featurized<E, F>
+where
+    E: DiscreteActionSpace,
+    F: Featurize<E::State>,
+{
+    fn actions(&self) -> Vec<Self::Action> {
+        self.env.actions()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use burn::backend::{ndarray::NdArrayDevice, NdArray as B};
+
+    use super::*;
+
+    /// A walk on the line from `0`, ending past `2`
+    struct Walk(f32);
+
+    impl Environment for Walk {
+        type State = [f32; 1];
+        type Action = f32;
+
+        fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
+            self.0 += action;
+            ((self.0 <= 2.0).then_some([self.0]), 1.0)
+        }
+
+        fn reset(&mut self) -> Self::State {
+            self.0 = 0.0;
+            [self.0]
+        }
+
+        fn random_action(&self) -> Self::Action {
+            1.0
+        }
+    }
+
+    #[test]
+    fn encodings() {
+        assert_eq!(Raw.features(&[1.0f32, 2.0]), FeatureVector(vec![1.0, 2.0]));
+
+        let one_hot = OneHotEncoding::new(4, |&(row, col): &(usize, usize)| row * 2 + col);
+        assert_eq!(
+            one_hot.features(&(1, 0)),
+            FeatureVector(vec![0.0, 0.0, 1.0, 0.0])
+        );
+
+        let tiles = TileCoding::new(vec![0.0], vec![1.0], 2, 2).unwrap();
+        assert_eq!(Featurize::<[f32; 1]>::size(&tiles), 6);
+        // The second tiling is shifted by a quarter of the box
+        assert_eq!(tiles.active_tiles(&[0.1]), [0, 3]);
+        assert_eq!(tiles.active_tiles(&[0.3]), [0, 4]);
+        assert_eq!(tiles.active_tiles(&[2.0]), [2, 5], "Clamped to the box");
+        let features = tiles.features(&[0.3f32]).0;
+        assert_eq!(features.iter().sum::<f32>(), 2.0);
+        assert!(TileCoding::new(vec![1.0], vec![0.0], 2, 2).is_err());
+
+        let tiles = TileCoding::new(vec![0.0, 0.0], vec![1.0, 1.0], 2, 1).unwrap();
+        assert_eq!(tiles.active_tiles(&[0.9, 0.1]), [3]);
+    }
+
+    #[test]
+    fn featurized_env() {
+        let mut env = Featurized::new(
+            Walk(0.0),
+            OneHotEncoding::new(3, |&[x]: &[f32; 1]| x as usize),
+        );
+        assert_eq!(env.size(), 3);
+        let first = env.reset();
+        let (second, _) = env.step(1.0);
+        assert_eq!(first, FeatureVector(vec![1.0, 0.0, 0.0]));
+        assert_eq!(second, Some(FeatureVector(vec![0.0, 1.0, 0.0])));
+        assert_eq!(env.step(2.0).0, None);
+
+        let batch: Tensor<B, 2> = vec![first, second.unwrap()].to_tensor(&NdArrayDevice::Cpu);
+        assert_eq!(batch.dims(), [2, 3]);
+    }
+}
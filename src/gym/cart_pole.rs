@@ -3,10 +3,10 @@ use gym_rs::core::{ActionReward, Env};
 use gym_rs::envs::classical_control::cartpole::{CartPoleEnv, CartPoleObservation};
 use gym_rs::utils::renderer::RenderMode;
 use rand::seq::IteratorRandom;
-use rand::thread_rng;
 use strum::{EnumIter, FromRepr, IntoEnumIterator, VariantArray};
 
 use crate::env::{DiscreteActionSpace, Environment, Report};
+use crate::seed::{self, Stream};
 use crate::traits::ToTensor;
 
 fn obs2arr(observation: CartPoleObservation) -> [f32; 4] {
@@ -37,7 +37,6 @@ impl From<CPAction> for i32 {
     }
 }
 
-
 /// The classic CartPole reinforcement learning environment
 ///
 /// This implementation is a thin wrapper around [gym_rs](https://github.com/MathisWellmann/gym-rs)
@@ -61,7 +60,9 @@ impl Environment for CartPole {
     type Action = CPAction;
 
     fn random_action(&self) -> Self::Action {
-        CPAction::iter().choose(&mut thread_rng()).unwrap()
+        CPAction::iter()
+            .choose(&mut seed::rng(Stream::Env))
+            .unwrap()
     }
 
     fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
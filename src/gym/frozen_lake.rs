@@ -1,6 +1,9 @@
-use rand::{seq::IteratorRandom, thread_rng};
+use rand::seq::IteratorRandom;
 
-use crate::env::{DiscreteActionSpace, Environment, Report};
+use crate::{
+    env::{DiscreteActionSpace, Environment, Render, Report},
+    seed::{self, Stream},
+};
 
 /// The possible types of squares in the [`FrozenLake`] grid
 #[derive(PartialEq)]
@@ -13,6 +16,7 @@ pub enum Square {
 
 /// Actions for the [`FrozenLake`] environment, representing taking a step in a direction
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FLAction {
     Left = 0,
     Down = 1,
@@ -99,7 +103,7 @@ impl Environment for FrozenLake {
     fn random_action(&self) -> Self::Action {
         self.actions()
             .into_iter()
-            .choose(&mut thread_rng())
+            .choose(&mut seed::rng(Stream::Env))
             .expect("There is always at least one available action in this environment")
     }
 
@@ -129,3 +133,26 @@ impl Environment for FrozenLake {
         self.pos
     }
 }
+
+impl Render for FrozenLake {
+    fn render(&self) -> String {
+        self.map
+            .chunks(4)
+            .enumerate()
+            .map(|(row, squares)| {
+                squares
+                    .iter()
+                    .enumerate()
+                    .map(|(col, square)| match square {
+                        _ if row * 4 + col == self.pos => '@',
+                        Square::Frozen => '.',
+                        Square::Hole => 'O',
+                        Square::Start => 'S',
+                        Square::Goal => 'G',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
@@ -1,14 +1,18 @@
 use std::collections::{HashSet, VecDeque};
 
-use rand::{seq::IteratorRandom, thread_rng, Rng};
+use rand::{seq::IteratorRandom, Rng};
 use strum::{EnumIter, FromRepr, IntoEnumIterator, VariantArray};
 
-use crate::env::{DiscreteActionSpace, Environment, Report};
+use crate::{
+    env::{DiscreteActionSpace, Environment, Render, Report},
+    seed::{self, Stream},
+};
 
 /// Position coordinates in the field with 1 unit of padding as a death zone
 type Pos = (usize, usize);
 
 #[derive(EnumIter, VariantArray, FromRepr, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Dir {
     Up = 0,
     Right = 1,
@@ -23,7 +27,7 @@ pub struct Snake {
 
 impl Snake {
     fn new(field_size: usize) -> Self {
-        let mut rng = thread_rng();
+        let mut rng = seed::rng(Stream::Env);
         Self {
             body: VecDeque::from([(
                 rng.gen_range(3..(field_size - 1)),
@@ -101,7 +105,7 @@ impl<const S: usize> GrassyField<S> {
 
         self.food = vacant
             .into_iter()
-            .choose(&mut thread_rng())
+            .choose(&mut seed::rng(Stream::Env))
             .unwrap_or((1, 1));
     }
 
@@ -155,7 +159,7 @@ impl<const S: usize> Environment for GrassyField<S> {
     }
 
     fn random_action(&self) -> Self::Action {
-        Dir::iter().choose(&mut thread_rng()).unwrap()
+        Dir::iter().choose(&mut seed::rng(Stream::Env)).unwrap()
     }
 
     fn reset(&mut self) -> Self::State {
@@ -193,6 +197,32 @@ impl<const S: usize> Environment for GrassyField<S> {
     }
 }
 
+impl<const S: usize> Render for GrassyField<S> {
+    fn render(&self) -> String {
+        (0..S + 2)
+            .map(|y| {
+                (0..S + 2)
+                    .map(|x| {
+                        let pos = (x, y);
+                        if !self.is_in_bounds(pos) {
+                            '#'
+                        } else if pos == self.snake.head() {
+                            '@'
+                        } else if self.snake.body.contains(&pos) {
+                            'o'
+                        } else if pos == self.food {
+                            '*'
+                        } else {
+                            ' '
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
 fn step_dir(pos: Pos, dir: Dir) -> Pos {
     let t = dir as isize;
     (
@@ -1,7 +1,10 @@
 use rand::Rng;
 use rand_distr::{Distribution, Normal};
 
-use crate::env::{DiscreteActionSpace, Environment, Report};
+use crate::{
+    env::{DiscreteActionSpace, Environment, Report},
+    seed::{self, Stream},
+};
 
 /// The K-armed bandit problem is a simple environment with 1 state and K actions. Each action has a reward
 /// that is sampled from a normal distribution with a standard deviation of 1. The means of the reward distributions are sampled
@@ -47,7 +50,7 @@ impl<const K: usize> Environment for KArmedBandit<K> {
 
     fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
         assert!(action < K, "Invalid action: {}", action);
-        let reward = self.arms[action].sample(&mut rand::thread_rng());
+        let reward = self.arms[action].sample(&mut seed::rng(Stream::Env));
         self.report
             .entry("reward")
             .and_modify(|x| *x += reward as f64);
@@ -55,7 +58,7 @@ impl<const K: usize> Environment for KArmedBandit<K> {
         self.rewards.push(reward);
 
         if !self.is_stationary {
-            let mut rng = rand::thread_rng();
+            let mut rng = seed::rng(Stream::Env);
             let dist = Normal::<f32>::new(0.0, 0.01).unwrap();
             self.arms = self.arms.map(|arm| {
                 let mean = arm.mean() + dist.sample(&mut rng);
@@ -81,7 +84,7 @@ impl<const K: usize> Environment for KArmedBandit<K> {
     }
 
     fn random_action(&self) -> Self::Action {
-        rand::thread_rng().gen_range(0..K)
+        seed::rng(Stream::Env).gen_range(0..K)
     }
 }
 
@@ -92,7 +95,7 @@ impl<const K: usize> DiscreteActionSpace for KArmedBandit<K> {
 }
 
 fn generate_arms<const K: usize>() -> [Normal<f32>; K] {
-    let mut rng = rand::thread_rng();
+    let mut rng = seed::rng(Stream::Env);
     let dist = Normal::<f32>::new(0.0, 1.0).unwrap();
 
     std::array::from_fn(|_| {
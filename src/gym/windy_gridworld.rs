@@ -1,11 +1,15 @@
 use rand::seq::IteratorRandom;
 use strum::{EnumIter, VariantArray};
 
-use crate::env::{DiscreteActionSpace, Environment, Report};
+use crate::{
+    env::{DiscreteActionSpace, Environment, Render, Report},
+    seed::{self, Stream},
+};
 
 pub type Pos = (i32, i32);
 
 #[derive(EnumIter, VariantArray, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Action {
     Up,
     Left,
@@ -18,6 +22,23 @@ pub enum Action {
     Stay,
 }
 
+impl Action {
+    /// The change of the position `(x, y)` by the action, before the wind
+    pub fn delta(self) -> Pos {
+        match self {
+            Action::Up => (0, -1),
+            Action::Left => (-1, 0),
+            Action::Down => (0, 1),
+            Action::Right => (1, 0),
+            Action::UpLeft => (-1, -1),
+            Action::DownLeft => (-1, 1),
+            Action::DownRight => (1, 1),
+            Action::UpRight => (1, -1),
+            Action::Stay => (0, 0),
+        }
+    }
+}
+
 pub struct WindyGridworld {
     pos: Pos,
     goal: Pos,
@@ -34,6 +55,11 @@ impl WindyGridworld {
             report: Report::new(vec!["steps"]),
         }
     }
+
+    /// The position of the goal
+    pub fn goal(&self) -> Pos {
+        self.goal
+    }
 }
 
 impl Environment for WindyGridworld {
@@ -46,18 +72,7 @@ impl Environment for WindyGridworld {
         let wind = self.currents[self.pos.0 as usize];
         self.pos.1 += wind;
 
-        let change = match action {
-            Action::Up => (0, -1),
-            Action::Left => (-1, 0),
-            Action::Down => (0, 1),
-            Action::Right => (1, 0),
-            Action::UpLeft => (-1, -1),
-            Action::DownLeft => (-1, 1),
-            Action::DownRight => (1, 1),
-            Action::UpRight => (1, -1),
-            Action::Stay => (0, 0),
-        };
-
+        let change = action.delta();
         self.pos.0 += change.0;
         self.pos.1 += change.1;
         self.pos = (self.pos.0.clamp(0, 9), self.pos.1.clamp(0, 7));
@@ -77,7 +92,7 @@ impl Environment for WindyGridworld {
     fn random_action(&self) -> Self::Action {
         self.actions()
             .into_iter()
-            .choose(&mut rand::thread_rng())
+            .choose(&mut seed::rng(Stream::Env))
             .expect("Iterator is not empty")
     }
 }
@@ -87,3 +102,24 @@ impl DiscreteActionSpace for WindyGridworld {
         Action::VARIANTS.to_vec()
     }
 }
+
+impl Render for WindyGridworld {
+    fn render(&self) -> String {
+        let mut rows = (0..8)
+            .map(|y| {
+                (0..10)
+                    .map(|x| match (x, y) {
+                        pos if pos == self.pos => '@',
+                        pos if pos == self.goal => 'G',
+                        _ => '.',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+
+        // Wind strength of each column
+        rows.push(self.currents.iter().map(|c| c.to_string()).collect());
+
+        rows.join("\n")
+    }
+}
@@ -1,27 +1,125 @@
 /// Implemented RL algorithms
+#[cfg(feature = "train")]
 pub mod algo;
 
+/// Scripted policies as reference lines for learning curves
+#[cfg(feature = "train")]
+pub mod baselines;
+
+/// Learning performance benchmarks
+#[cfg(feature = "config")]
+pub mod bench;
+
+/// Experiment config files
+#[cfg(feature = "config")]
+pub mod config;
+
 /// Implementations of strategies for time-decaying hyperparameters
+#[cfg(feature = "train")]
 pub mod decay;
 
+/// Action distributions over burn tensors for stochastic policies
+#[cfg(feature = "train")]
+pub mod distributions;
+
 /// Data structures
+#[cfg(feature = "train")]
 pub mod ds;
 
+/// Runtime selection of burn backends and devices
+#[cfg(feature = "train")]
+pub mod device;
+
 /// Environment
 pub mod env;
 
+/// The crate's error type
+pub mod error;
+
 /// Exploration policies
+#[cfg(feature = "train")]
 pub mod exploration;
 
+/// Exported policies for inference
+pub mod export;
+
+/// State encodings separate from the environment: raw features, one-hot and tile coding
+pub mod features;
+
+/// Loss functions shared by the agents
+#[cfg(feature = "train")]
+pub mod losses;
+
+/// Metric loggers for external tools
+#[cfg(feature = "train")]
+pub mod logger;
+
 /// Experience replay
+#[cfg(feature = "train")]
 pub mod memory;
 
+/// Neural network utilities
+#[cfg(feature = "train")]
+pub mod nn;
+
+/// Learned models of environment dynamics and rewards with their uncertainty, for planning and model-based agents
+#[cfg(feature = "train")]
+pub mod model;
+
+/// Multi-objective training with scheduled scalarization weights and Pareto fronts
+#[cfg(feature = "train")]
+pub mod multi_objective;
+
+/// Observation and reward normalization, and PopArt value target normalization
+#[cfg(feature = "train")]
+pub mod normalize;
+
+/// Reward types beyond `f32`, e.g. for double precision or multiple objectives
+pub mod reward;
+
+/// Interquartile means, optimality gaps and performance profiles across runs, summarized for papers and READMEs
+#[cfg(feature = "train")]
+pub mod report;
+
+/// Risk measures of return distributions, for risk-sensitive distributional agents
+#[cfg(feature = "train")]
+pub mod risk;
+
+/// Seeds for reproducible training runs
+#[cfg(feature = "train")]
+pub mod seed;
+
+/// Running statistics: mean and variance, exponential moving averages and windowed quantiles, along with the mean,
+/// quantiles and interquartile mean of slices
+pub mod stats;
+
+/// Hyperparameter sweeps
+#[cfg(feature = "config")]
+pub mod sweep;
+
+/// Deterministic environments and assertions for testing agents
+#[cfg(feature = "train")]
+pub mod testing;
+
+/// Training loops
+#[cfg(feature = "train")]
+pub mod train;
+
 /// Library traits
 pub mod traits;
 
+/// Environment wrappers that perturb actions, for evaluating the robustness of policies
+#[cfg(feature = "train")]
+pub mod wrappers;
+
 /// Probabilistic models
+#[cfg(feature = "train")]
 mod prob;
 
+/// Shared CSV format of the files written and read by the crate
+#[cfg(feature = "train")]
+mod csv;
+
 /// Training visualization TUI
 #[cfg(feature = "viz")]
 pub mod viz;
@@ -31,3 +129,7 @@ pub mod viz;
 pub mod gym;
 
 mod util;
+
+// Lets derived code refer to `::rl` in the crate's own tests
+#[cfg(all(test, feature = "derive"))]
+extern crate self as rl;
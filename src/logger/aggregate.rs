@@ -0,0 +1,224 @@
+use std::{io, path::Path};
+
+use super::MetricSink;
+use crate::stats::RunningMeanVar;
+
+/// The episode metric with the number of steps of the episode, as reported by the [`Trainer`](crate::train::Trainer)
+const STEPS_METRIC: &str = "steps";
+
+/// How the values of a metric are turned into the points passed on by an [`Aggregate`] sink
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Aggregation {
+    /// One point per episode
+    ///
+    /// Values logged with [`log_scalar`](MetricSink::log_scalar) during an episode, e.g. a loss for every update,
+    /// are averaged into one point reported with the next episode.
+    #[default]
+    Episode,
+    /// One point per `n` values, their mean, reported at the episode or step of the last of them
+    Mean(u64),
+    /// One point per value at the environment step it was logged at
+    ///
+    /// Episode metrics are placed at the total number of steps so far, counted from the `steps` metric of every
+    /// episode.
+    Step,
+}
+
+/// A [`MetricSink`] that aggregates metrics before passing them on to another sink
+///
+/// The aggregation of each metric is chosen when the sink is set up, so training code can report raw values and
+/// leave smoothing noisy metrics to the metrics layer. Metrics without an aggregation are reported per episode.
+///
+/// ```ignore
+/// let sink = Aggregate::new(CsvSink::new("metrics.csv")?)
+///     .with_aggregation("return", Aggregation::Mean(10))
+///     .with_aggregation("loss", Aggregation::Step);
+/// let trainer = Trainer::new(env, agent).with_sink(sink);
+/// ```
+pub struct Aggregate<S: MetricSink> {
+    sink: S,
+    aggregations: Vec<(String, Aggregation)>,
+    means: Vec<(String, RunningMeanVar)>,
+    steps: u64,
+}
+
+impl<S: MetricSink> Aggregate<S> {
+    /// Wrap `sink`, reporting every metric per episode until configured otherwise
+    pub fn new(sink: S) -> Self {
+        Self {
+            sink,
+            aggregations: Vec::new(),
+            means: Vec::new(),
+            steps: 0,
+        }
+    }
+
+    /// Aggregate the metric called `name` with `aggregation`, replacing any previous aggregation of it
+    ///
+    /// **Panics** if `aggregation` is a mean of zero values
+    pub fn with_aggregation(mut self, name: impl Into<String>, aggregation: Aggregation) -> Self {
+        assert!(
+            aggregation != Aggregation::Mean(0),
+            "A mean aggregates at least one value"
+        );
+        let name = name.into();
+        self.aggregations.retain(|(metric, _)| *metric != name);
+        self.aggregations.push((name, aggregation));
+        self
+    }
+
+    /// The aggregation of the metric called `name`
+    pub fn aggregation(&self, name: &str) -> Aggregation {
+        aggregation_of(&self.aggregations, name)
+    }
+
+    /// The wrapped sink
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+
+    /// Add `value` to the running mean of `name`
+    ///
+    /// **Returns** the mean and resets it once it has `n` values
+    fn push_mean(&mut self, name: &str, value: f64, n: u64) -> Option<f64> {
+        let ix = match self.means.iter().position(|(metric, _)| metric == name) {
+            Some(ix) => ix,
+            None => {
+                self.means.push((name.to_string(), RunningMeanVar::new()));
+                self.means.len() - 1
+            }
+        };
+
+        let stats = &mut self.means[ix].1;
+        stats.push(value);
+        (stats.count() >= n).then(|| std::mem::take(stats).mean())
+    }
+}
+
+impl<S: MetricSink> MetricSink for Aggregate<S> {
+    fn log_scalar(&mut self, name: &str, value: f64, step: u64) -> io::Result<()> {
+        match self.aggregation(name) {
+            Aggregation::Episode => {
+                self.push_mean(name, value, u64::MAX);
+                Ok(())
+            }
+            Aggregation::Mean(n) => match self.push_mean(name, value, n) {
+                Some(mean) => self.sink.log_scalar(name, mean, step),
+                None => Ok(()),
+            },
+            Aggregation::Step => self.sink.log_scalar(name, value, step),
+        }
+    }
+
+    fn log_episode(&mut self, episode: u64, metrics: &[(&str, f64)]) -> io::Result<()> {
+        if let Some(&(_, steps)) = metrics.iter().find(|(name, _)| *name == STEPS_METRIC) {
+            self.steps += steps as u64;
+        }
+
+        let mut points = Vec::new();
+        for &(name, value) in metrics {
+            match self.aggregation(name) {
+                Aggregation::Episode => points.push((name.to_string(), value)),
+                Aggregation::Mean(n) => {
+                    if let Some(mean) = self.push_mean(name, value, n) {
+                        points.push((name.to_string(), mean));
+                    }
+                }
+                Aggregation::Step => self.sink.log_scalar(name, value, self.steps)?,
+            }
+        }
+
+        // Values logged during the episode of metrics that are reported per episode
+        for (name, stats) in &mut self.means {
+            let pending = stats.count() > 0
+                && aggregation_of(&self.aggregations, name) == Aggregation::Episode
+                && !points.iter().any(|(metric, _)| metric == name);
+            if pending {
+                points.push((name.clone(), std::mem::take(stats).mean()));
+            }
+        }
+
+        if points.is_empty() {
+            return Ok(());
+        }
+        let points = points
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+            .collect::<Vec<_>>();
+        self.sink.log_episode(episode, &points)
+    }
+
+    fn log_config(&mut self, params: &[(&str, String)]) -> io::Result<()> {
+        self.sink.log_config(params)
+    }
+
+    fn log_artifact(&mut self, path: &Path) -> io::Result<()> {
+        self.sink.log_artifact(path)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.sink.flush()
+    }
+}
+
+fn aggregation_of(aggregations: &[(String, Aggregation)], name: &str) -> Aggregation {
+    aggregations
+        .iter()
+        .find(|(metric, _)| metric == name)
+        .map_or(Aggregation::Episode, |&(_, aggregation)| aggregation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Collect(Vec<(String, f64, u64)>);
+
+    impl MetricSink for Collect {
+        fn log_scalar(&mut self, name: &str, value: f64, step: u64) -> io::Result<()> {
+            self.0.push((name.to_string(), value, step));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn aggregate_metrics() {
+        let mut sink = Aggregate::new(Collect::default())
+            .with_aggregation("return", Aggregation::Mean(2))
+            .with_aggregation("eps", Aggregation::Step);
+
+        for episode in 0..4 {
+            sink.log_scalar("loss", episode as f64, 100 + episode)
+                .unwrap();
+            sink.log_scalar("loss", episode as f64 + 1.0, 101 + episode)
+                .unwrap();
+            sink.log_episode(
+                episode,
+                &[("return", episode as f64), ("steps", 10.0), ("eps", 0.5)],
+            )
+            .unwrap();
+        }
+
+        let points = sink.into_inner().0;
+        let of = |metric: &str| {
+            points
+                .iter()
+                .filter(|(name, _, _)| name == metric)
+                .map(|&(_, value, step)| (step, value))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(of("return"), [(1, 0.5), (3, 2.5)], "mean of two episodes");
+        assert_eq!(of("steps"), [(0, 10.0), (1, 10.0), (2, 10.0), (3, 10.0)]);
+        assert_eq!(
+            of("eps"),
+            [(10, 0.5), (20, 0.5), (30, 0.5), (40, 0.5)],
+            "placed at the total steps"
+        );
+        assert_eq!(
+            of("loss"),
+            [(0, 0.5), (1, 1.5), (2, 2.5), (3, 3.5)],
+            "values of each episode averaged"
+        );
+    }
+}
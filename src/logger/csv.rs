@@ -0,0 +1,96 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use super::MetricSink;
+use crate::csv::{escape_csv, METRICS_HEADER};
+
+/// A [`MetricSink`] that appends metrics to a CSV file with the columns `metric,episode,value`
+///
+/// The `episode` column holds the step passed to the sink, which is the episode for
+/// [`log_episode`](MetricSink::log_episode). The columns match the metrics exported from the viz TUI, so files from
+/// either source can be loaded the same way.
+pub struct CsvSink {
+    writer: BufWriter<File>,
+}
+
+impl CsvSink {
+    /// Create the file at `path`, overwriting it if it exists, and write the header
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{METRICS_HEADER}")?;
+        Ok(Self { writer })
+    }
+
+    /// Continue the file at `path` from `episode`, dropping the rows of that and later episodes
+    ///
+    /// Used when a run is resumed from a checkpoint, so episodes that ran after the checkpoint aren't reported twice.
+    pub fn resume(path: impl AsRef<Path>, episode: u64) -> io::Result<Self> {
+        let contents = fs::read_to_string(&path)?;
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{METRICS_HEADER}")?;
+        for line in contents.lines().skip(1) {
+            let row_episode = line.rsplit(',').nth(1).and_then(|e| e.parse().ok());
+            if row_episode.is_some_and(|e: u64| e < episode) {
+                writeln!(writer, "{line}")?;
+            }
+        }
+        Ok(Self { writer })
+    }
+}
+
+impl MetricSink for CsvSink {
+    fn log_scalar(&mut self, name: &str, value: f64, step: u64) -> io::Result<()> {
+        writeln!(self.writer, "{},{step},{value}", escape_csv(name))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl Drop for CsvSink {
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn csv_sink_functional() {
+        let path = std::env::temp_dir().join("rl_csv_sink_functional.csv");
+
+        let mut sink = CsvSink::new(&path).unwrap();
+        sink.log_episode(0, &[("reward", 1.5), ("steps", 10.0)])
+            .unwrap();
+        sink.log_scalar("loss, smoothed", 0.25, 3).unwrap();
+        sink.flush().unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+
+        assert_eq!(
+            contents, "metric,episode,value\nreward,0,1.5\nsteps,0,10\n\"loss, smoothed\",3,0.25\n",
+            "rows written"
+        );
+
+        drop(sink);
+        let mut sink = CsvSink::resume(&path, 3).unwrap();
+        sink.log_scalar("reward", 2.0, 3).unwrap();
+        sink.flush().unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            contents, "metric,episode,value\nreward,0,1.5\nsteps,0,10\nreward,3,2\n",
+            "later rows dropped on resume"
+        );
+    }
+}
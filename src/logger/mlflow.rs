@@ -0,0 +1,198 @@
+use std::{
+    fs, io,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+
+use super::MetricSink;
+
+/// The maximum number of metrics MLflow accepts in a single `log-batch` request
+const MAX_BATCH_METRICS: usize = 1000;
+
+/// A [`MetricSink`] that records a run on an MLflow tracking server through its REST API
+///
+/// Metrics are buffered and sent in batches when [`flush`](MetricSink::flush) is called or the buffer is full.
+/// The run is marked as finished when the sink is dropped.
+///
+/// Artifacts are uploaded through the artifact proxy, so the server must be started with artifact serving enabled
+/// (the default for `mlflow server`).
+///
+/// ### Example
+/// ```no_run
+/// use rl::logger::{MetricSink, MlflowSink};
+///
+/// let mut sink = MlflowSink::new("http://localhost:5000", "cartpole", Some("dqn-baseline")).unwrap();
+/// sink.log_config(&[("gamma", 0.99.to_string())]).unwrap();
+/// sink.log_scalar("reward", 21.0, 0).unwrap();
+/// ```
+pub struct MlflowSink {
+    tracking_uri: String,
+    experiment_id: String,
+    run_id: String,
+    metrics: Vec<Value>,
+}
+
+impl MlflowSink {
+    /// Start a new run in the experiment called `experiment`, creating the experiment if it doesn't exist
+    ///
+    /// ### Arguments
+    /// - `tracking_uri` - The base URL of the tracking server, e.g. `http://localhost:5000`
+    /// - `experiment` - The name of the experiment
+    /// - `run_name` - An optional display name for the run
+    pub fn new(tracking_uri: &str, experiment: &str, run_name: Option<&str>) -> io::Result<Self> {
+        let tracking_uri = tracking_uri.trim_end_matches('/').to_string();
+
+        let experiment_id = match get(
+            &tracking_uri,
+            "experiments/get-by-name",
+            &[("experiment_name", experiment)],
+        ) {
+            Ok(response) => json_str(&response["experiment"]["experiment_id"])?,
+            Err(_) => {
+                let response = post(
+                    &tracking_uri,
+                    "experiments/create",
+                    json!({ "name": experiment }),
+                )?;
+                json_str(&response["experiment_id"])?
+            }
+        };
+
+        let mut body = json!({
+            "experiment_id": experiment_id,
+            "start_time": timestamp_ms(),
+        });
+        if let Some(run_name) = run_name {
+            body["run_name"] = json!(run_name);
+        }
+        let response = post(&tracking_uri, "runs/create", body)?;
+        let run_id = json_str(&response["run"]["info"]["run_id"])?;
+
+        Ok(Self {
+            tracking_uri,
+            experiment_id,
+            run_id,
+            metrics: Vec::new(),
+        })
+    }
+
+    /// The id of the run on the tracking server
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    fn log_batch(&self, metrics: &[Value], params: &[Value]) -> io::Result<()> {
+        post(
+            &self.tracking_uri,
+            "runs/log-batch",
+            json!({
+                "run_id": self.run_id,
+                "metrics": metrics,
+                "params": params,
+            }),
+        )
+        .map(|_| ())
+    }
+}
+
+impl MetricSink for MlflowSink {
+    fn log_scalar(&mut self, name: &str, value: f64, step: u64) -> io::Result<()> {
+        // non-finite values can't be represented in JSON
+        if !value.is_finite() {
+            return Ok(());
+        }
+
+        self.metrics.push(json!({
+            "key": name,
+            "value": value,
+            "timestamp": timestamp_ms(),
+            "step": step,
+        }));
+
+        if self.metrics.len() >= MAX_BATCH_METRICS {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn log_config(&mut self, params: &[(&str, String)]) -> io::Result<()> {
+        let params = params
+            .iter()
+            .map(|(key, value)| json!({ "key": key, "value": value }))
+            .collect::<Vec<_>>();
+        self.log_batch(&[], &params)
+    }
+
+    fn log_artifact(&mut self, path: &Path) -> io::Result<()> {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid artifact path"))?;
+        let url = format!(
+            "{}/api/2.0/mlflow-artifacts/artifacts/{}/{}/artifacts/{name}",
+            self.tracking_uri, self.experiment_id, self.run_id
+        );
+
+        ureq::put(&url)
+            .send_bytes(&fs::read(path)?)
+            .map(|_| ())
+            .map_err(io::Error::other)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.metrics.is_empty() {
+            return Ok(());
+        }
+
+        let metrics = std::mem::take(&mut self.metrics);
+        self.log_batch(&metrics, &[])
+    }
+}
+
+impl Drop for MlflowSink {
+    fn drop(&mut self) {
+        let _ = self.flush();
+        let _ = post(
+            &self.tracking_uri,
+            "runs/update",
+            json!({
+                "run_id": self.run_id,
+                "status": "FINISHED",
+                "end_time": timestamp_ms(),
+            }),
+        );
+    }
+}
+
+fn get(tracking_uri: &str, endpoint: &str, query: &[(&str, &str)]) -> io::Result<Value> {
+    let mut request = ureq::get(&format!("{tracking_uri}/api/2.0/mlflow/{endpoint}"));
+    for (key, value) in query {
+        request = request.query(key, value);
+    }
+
+    request.call().map_err(io::Error::other)?.into_json()
+}
+
+fn post(tracking_uri: &str, endpoint: &str, body: Value) -> io::Result<Value> {
+    ureq::post(&format!("{tracking_uri}/api/2.0/mlflow/{endpoint}"))
+        .send_json(body)
+        .map_err(io::Error::other)?
+        .into_json()
+}
+
+fn json_str(value: &Value) -> io::Result<String> {
+    value
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "unexpected MLflow response"))
+}
+
+fn timestamp_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
@@ -0,0 +1,81 @@
+use std::{io, path::Path};
+
+/// Per-metric aggregation of values before they reach another sink
+pub mod aggregate;
+/// CSV metric file writer
+pub mod csv;
+/// MLflow tracking server client
+#[cfg(feature = "mlflow")]
+pub mod mlflow;
+/// Plain text metric printer
+pub mod stdout;
+/// TensorBoard event file writer
+pub mod tensorboard;
+
+pub use aggregate::{Aggregate, Aggregation};
+pub use csv::CsvSink;
+#[cfg(feature = "mlflow")]
+pub use mlflow::MlflowSink;
+pub use stdout::StdoutSink;
+pub use tensorboard::TensorBoardWriter;
+
+/// A destination for training metrics, e.g. the viz TUI, a file or an experiment tracking server
+///
+/// Training code that reports through this trait works with any sink, so visualization and tracking are chosen by
+/// the caller rather than hard-wired into the training loop.
+pub trait MetricSink {
+    /// Record a scalar `value` for the metric called `name` at `step`
+    fn log_scalar(&mut self, name: &str, value: f64, step: u64) -> io::Result<()>;
+
+    /// Record the metrics of a finished episode
+    ///
+    /// **Default:** calls [`log_scalar`](MetricSink::log_scalar) for each metric with `episode` as the step
+    fn log_episode(&mut self, episode: u64, metrics: &[(&str, f64)]) -> io::Result<()> {
+        metrics
+            .iter()
+            .try_for_each(|&(name, value)| self.log_scalar(name, value, episode))
+    }
+
+    /// Record the hyperparameters of the run
+    ///
+    /// **Default:** ignored
+    fn log_config(&mut self, _params: &[(&str, String)]) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Upload a file produced by the run, e.g. a model checkpoint or exported metrics
+    ///
+    /// **Default:** ignored
+    fn log_artifact(&mut self, _path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Make sure all recorded data has been written
+    ///
+    /// **Default:** does nothing
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<S: MetricSink + ?Sized> MetricSink for Box<S> {
+    fn log_scalar(&mut self, name: &str, value: f64, step: u64) -> io::Result<()> {
+        (**self).log_scalar(name, value, step)
+    }
+
+    fn log_episode(&mut self, episode: u64, metrics: &[(&str, f64)]) -> io::Result<()> {
+        (**self).log_episode(episode, metrics)
+    }
+
+    fn log_config(&mut self, params: &[(&str, String)]) -> io::Result<()> {
+        (**self).log_config(params)
+    }
+
+    fn log_artifact(&mut self, path: &Path) -> io::Result<()> {
+        (**self).log_artifact(path)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
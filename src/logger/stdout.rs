@@ -0,0 +1,41 @@
+use std::io::{self, Stdout, Write};
+
+use super::MetricSink;
+
+/// A [`MetricSink`] that prints metrics to stdout as `key=value` lines
+///
+/// Each episode is printed on one line, e.g. `episode=42 reward=1.2500 steps=17.0000`.
+pub struct StdoutSink {
+    out: Stdout,
+}
+
+impl StdoutSink {
+    pub fn new() -> Self {
+        Self { out: io::stdout() }
+    }
+}
+
+impl Default for StdoutSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricSink for StdoutSink {
+    fn log_scalar(&mut self, name: &str, value: f64, step: u64) -> io::Result<()> {
+        writeln!(self.out, "step={step} {name}={value:.4}")
+    }
+
+    fn log_episode(&mut self, episode: u64, metrics: &[(&str, f64)]) -> io::Result<()> {
+        let mut out = self.out.lock();
+        write!(out, "episode={episode}")?;
+        for (name, value) in metrics {
+            write!(out, " {name}={value:.4}")?;
+        }
+        writeln!(out)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
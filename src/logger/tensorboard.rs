@@ -0,0 +1,217 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::MetricSink;
+
+/// Writes scalar summaries to a TensorBoard event file
+///
+/// Creates a file named `events.out.tfevents.<timestamp>.<host>` in the log directory, in the same TFEvents format
+/// written by TensorFlow and PyTorch, so runs can be compared in TensorBoard with `tensorboard --logdir <dir>`.
+///
+/// ### Example
+/// ```no_run
+/// use rl::logger::TensorBoardWriter;
+///
+/// let mut writer = TensorBoardWriter::new("runs/cartpole").unwrap();
+/// writer.add_scalar("reward", 21.0, 0).unwrap();
+/// writer.flush().unwrap();
+/// ```
+pub struct TensorBoardWriter {
+    writer: BufWriter<File>,
+}
+
+impl TensorBoardWriter {
+    /// Create a new event file in `logdir`, creating the directory if necessary
+    pub fn new(logdir: impl AsRef<Path>) -> io::Result<Self> {
+        let logdir = logdir.as_ref();
+        fs::create_dir_all(logdir)?;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| String::from("localhost"));
+        let file = File::create(logdir.join(format!("events.out.tfevents.{timestamp}.{host}")))?;
+
+        let mut writer = Self {
+            writer: BufWriter::new(file),
+        };
+        writer.write_event(&encode_event(wall_time(), 0, Some("brain.Event:2"), None))?;
+        writer.flush()?;
+
+        Ok(writer)
+    }
+
+    /// Record a scalar `value` for the series `tag` at `step`
+    pub fn add_scalar(&mut self, tag: &str, value: f32, step: u64) -> io::Result<()> {
+        let summary = encode_scalar_summary(tag, value);
+        let event = encode_event(wall_time(), step as i64, None, Some(&summary));
+        self.write_event(&event)
+    }
+
+    /// Flush buffered events to disk
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Write a serialized `Event` as a TFRecord: length, length checksum, data, data checksum
+    fn write_event(&mut self, data: &[u8]) -> io::Result<()> {
+        let len = (data.len() as u64).to_le_bytes();
+        self.writer.write_all(&len)?;
+        self.writer.write_all(&masked_crc32c(&len).to_le_bytes())?;
+        self.writer.write_all(data)?;
+        self.writer.write_all(&masked_crc32c(data).to_le_bytes())
+    }
+}
+
+impl MetricSink for TensorBoardWriter {
+    fn log_scalar(&mut self, name: &str, value: f64, step: u64) -> io::Result<()> {
+        self.add_scalar(name, value as f32, step)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        TensorBoardWriter::flush(self)
+    }
+}
+
+impl Drop for TensorBoardWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+fn wall_time() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+/// Serialize an `Event` protobuf message
+///
+/// ```proto
+/// message Event {
+///   double wall_time = 1;
+///   int64 step = 2;
+///   oneof what {
+///     string file_version = 3;
+///     Summary summary = 5;
+///   }
+/// }
+/// ```
+fn encode_event(
+    wall_time: f64,
+    step: i64,
+    file_version: Option<&str>,
+    summary: Option<&[u8]>,
+) -> Vec<u8> {
+    let mut buf = Vec::new();
+
+    buf.push(0x09);
+    buf.extend(wall_time.to_le_bytes());
+
+    buf.push(0x10);
+    encode_varint(&mut buf, step as u64);
+
+    if let Some(file_version) = file_version {
+        buf.push(0x1a);
+        encode_bytes(&mut buf, file_version.as_bytes());
+    }
+
+    if let Some(summary) = summary {
+        buf.push(0x2a);
+        encode_bytes(&mut buf, summary);
+    }
+
+    buf
+}
+
+/// Serialize a `Summary` protobuf message holding a single simple value
+///
+/// ```proto
+/// message Summary {
+///   message Value {
+///     string tag = 1;
+///     float simple_value = 2;
+///   }
+///   repeated Value value = 1;
+/// }
+/// ```
+fn encode_scalar_summary(tag: &str, value: f32) -> Vec<u8> {
+    let mut summary_value = Vec::new();
+    summary_value.push(0x0a);
+    encode_bytes(&mut summary_value, tag.as_bytes());
+    summary_value.push(0x15);
+    summary_value.extend(value.to_le_bytes());
+
+    let mut buf = Vec::new();
+    buf.push(0x0a);
+    encode_bytes(&mut buf, &summary_value);
+    buf
+}
+
+/// Length-delimited protobuf field payload
+fn encode_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    encode_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+/// CRC-32C (Castagnoli)
+fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0x82f6_3b78 & mask);
+        }
+    }
+    !crc
+}
+
+/// The masked CRC used by TFRecord files
+fn masked_crc32c(data: &[u8]) -> u32 {
+    let crc = crc32c(data);
+    ((crc >> 15) | (crc << 17)).wrapping_add(0xa282_ead8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32c_functional() {
+        assert_eq!(crc32c(b""), 0, "empty input");
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283, "check value");
+    }
+
+    #[test]
+    fn varint_functional() {
+        let mut buf = Vec::new();
+        encode_varint(&mut buf, 1);
+        encode_varint(&mut buf, 300);
+        assert_eq!(buf, [0x01, 0xac, 0x02], "varints encoded");
+    }
+
+    #[test]
+    fn scalar_summary_functional() {
+        let summary = encode_scalar_summary("r", 1.0);
+        assert_eq!(
+            summary,
+            [0x0a, 0x08, 0x0a, 0x01, b'r', 0x15, 0x00, 0x00, 0x80, 0x3f],
+            "summary encoded"
+        );
+    }
+}
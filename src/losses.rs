@@ -0,0 +1,158 @@
+use burn::prelude::*;
+
+/// The loss between predicted values and their targets in value-based agents
+///
+/// Losses are computed elementwise by [`forward`](ValueLoss::forward), so agents can weight them, e.g. with
+/// importance sampling weights or masks, before reducing them.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValueLoss {
+    /// The squared error
+    #[default]
+    Mse,
+    /// The [Huber loss](huber), which is less sensitive to large temporal difference errors than the squared error
+    Huber { delta: f32 },
+}
+
+impl ValueLoss {
+    /// The elementwise loss of `pred` against `target`, with the shape of `pred`
+    pub fn forward<B: Backend, const D: usize>(
+        &self,
+        pred: Tensor<B, D>,
+        target: Tensor<B, D>,
+    ) -> Tensor<B, D> {
+        match self {
+            Self::Mse => (pred - target).powf_scalar(2.0),
+            Self::Huber { delta } => huber(pred, target, *delta),
+        }
+    }
+}
+
+/// The elementwise Huber loss of `pred` against `target`, with the shape of `pred`
+///
+/// The loss is `x² / 2` for errors `x` up to `delta` and grows linearly as `delta * (|x| - delta / 2)` above,
+/// so large errors have gradients of at most `delta`. With `delta` `1` it is the smooth L1 loss.
+pub fn huber<B: Backend, const D: usize>(
+    pred: Tensor<B, D>,
+    target: Tensor<B, D>,
+    delta: f32,
+) -> Tensor<B, D> {
+    huber_error(pred - target, delta)
+}
+
+/// The elementwise Huber loss of `error`
+fn huber_error<B: Backend, const D: usize>(error: Tensor<B, D>, delta: f32) -> Tensor<B, D> {
+    let error = error.abs();
+    let quadratic = error.clone().clamp_max(delta);
+    let linear = error - quadratic.clone();
+    quadratic.powf_scalar(2.0) * 0.5 + linear * delta
+}
+
+/// The quantile Huber loss of QR-DQN between predicted quantiles and samples of the target distribution
+///
+/// Quantile `i` of `n` is the midpoint `(2i + 1) / 2n`. Every predicted quantile is compared to every target sample,
+/// and errors above or below the quantile are weighted asymmetrically, so each prediction converges to its quantile
+/// of the target distribution.
+///
+/// ### Arguments
+/// - `pred` - The predicted quantiles, with shape `[batch, n]`
+/// - `target` - The samples of the target distribution, e.g. the bootstrapped quantiles of the next state, with shape
+///   `[batch, m]`
+/// - `kappa` - The threshold of the [Huber loss](huber) of each error
+///
+/// **Returns** the loss of each row, summed over the quantiles and averaged over the target samples, with shape
+/// `[batch, 1]`
+pub fn quantile_huber<B: Backend>(
+    pred: Tensor<B, 2>,
+    target: Tensor<B, 2>,
+    kappa: f32,
+) -> Tensor<B, 2> {
+    let [batch, n] = pred.dims();
+    let device = pred.device();
+    // Broadcast to errors of shape [batch, n, m]
+    let error = target.unsqueeze_dim::<3>(1) - pred.unsqueeze_dim::<3>(2);
+
+    let taus = (0..n)
+        .map(|i| (2 * i + 1) as f32 / (2 * n) as f32)
+        .collect::<Vec<_>>();
+    let taus = Tensor::<B, 1>::from_floats(taus.as_slice(), &device).reshape([1, n, 1]);
+
+    // Errors below the prediction are weighted with 1 - tau and errors above with tau
+    let below = error.clone().lower_elem(0.0).float();
+    let weights = (taus - below).abs();
+    let loss = weights * huber_error(error, kappa) / kappa;
+    loss.mean_dim(2).sum_dim(1).reshape([batch, 1])
+}
+
+/// The clipped value loss of PPO, the elementwise maximum of the squared errors of the new value and of the new
+/// value clipped to within `clip` of the old value
+///
+/// Clipping keeps value updates close to the values the returns were computed with, like the clipped policy
+/// objective does for the policy.
+///
+/// ### Arguments
+/// - `value` - The values predicted by the network being trained
+/// - `old_value` - The values predicted when the experiences were collected
+/// - `returns` - The targets of the values
+/// - `clip` - The largest change of the value that reduces the loss
+///
+/// **Returns** the elementwise loss, with the shape of `value`
+pub fn clipped_value<B: Backend, const D: usize>(
+    value: Tensor<B, D>,
+    old_value: Tensor<B, D>,
+    returns: Tensor<B, D>,
+    clip: f32,
+) -> Tensor<B, D> {
+    let clipped = old_value.clone() + (value.clone() - old_value).clamp(-clip, clip);
+    let unclipped = (value - returns.clone()).powf_scalar(2.0);
+    let clipped = (clipped - returns).powf_scalar(2.0);
+    unclipped
+        .clone()
+        .mask_where(clipped.clone().greater(unclipped), clipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use burn::backend::{ndarray::NdArrayDevice, NdArray as B};
+
+    use super::*;
+
+    fn tensor<const N: usize>(values: [f32; N]) -> Tensor<B, 2> {
+        Tensor::<B, 1>::from_floats(values, &NdArrayDevice::Cpu).reshape([1, N])
+    }
+
+    #[test]
+    fn huber_loss() {
+        let loss = huber(tensor([0.0, 0.5, 3.0, -3.0]), tensor([0.0; 4]), 1.0);
+        assert_eq!(loss.into_data().value, [0.0, 0.125, 2.5, 2.5]);
+
+        let loss = ValueLoss::Huber { delta: 2.0 }.forward(tensor([3.0]), tensor([0.0]));
+        assert_eq!(loss.into_data().value, [4.0]);
+        let loss = ValueLoss::Mse.forward(tensor([3.0]), tensor([0.0]));
+        assert_eq!(loss.into_data().value, [9.0]);
+    }
+
+    #[test]
+    fn quantile_huber_loss() {
+        // Quantiles 0.25 and 0.75 against one sample between them, with errors of 1 below and above
+        let loss = quantile_huber(tensor([0.0, 2.0]), tensor([1.0]), 1.0);
+        assert_eq!(loss.into_data().value, [0.25 * 0.5 + 0.25 * 0.5]);
+
+        // With the predictions swapped, the sample is below the 0.25 quantile and above the 0.75 quantile
+        let loss = quantile_huber(tensor([2.0, 0.0]), tensor([1.0]), 1.0);
+        assert_eq!(loss.into_data().value, [0.75 * 0.5 + 0.75 * 0.5]);
+    }
+
+    #[test]
+    fn clipped_value_loss() {
+        let loss = clipped_value(
+            tensor([3.0, 0.5]),
+            tensor([0.0, 0.0]),
+            tensor([0.0, 2.0]),
+            1.0,
+        );
+        // The value 3 is clipped to 1, which is closer to the return, so the larger unclipped error counts.
+        // The value 0.5 is within the clip range, so both errors are the same.
+        assert_eq!(loss.into_data().value, [9.0, 2.25]);
+    }
+}
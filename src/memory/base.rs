@@ -1,6 +1,10 @@
-use rand::{seq::SliceRandom, thread_rng};
+use rand::seq::SliceRandom;
 
-use crate::{ds::RingBuffer, env::Environment};
+use crate::{
+    ds::RingBuffer,
+    env::Environment,
+    seed::{self, Stream},
+};
 
 use super::{Exp, ExpBatch};
 
@@ -29,6 +33,16 @@ impl<E: Environment> ReplayMemory<E> {
         }
     }
 
+    /// The number of experiences stored in the memory
+    pub fn len(&self) -> usize {
+        self.memory.len()
+    }
+
+    /// Whether no experiences are stored in the memory
+    pub fn is_empty(&self) -> bool {
+        self.memory.len() == 0
+    }
+
     /// Add a new experience to the memory
     pub fn push(&mut self, exp: Exp<E>) {
         self.memory.push(exp);
@@ -44,7 +58,7 @@ impl<E: Environment> ReplayMemory<E> {
             Some(
                 self.memory
                     .view()
-                    .choose_multiple(&mut thread_rng(), self.batch_size)
+                    .choose_multiple(&mut seed::rng(Stream::Agent), self.batch_size)
                     .collect(),
             )
         } else {
@@ -62,7 +76,7 @@ impl<E: Environment> ReplayMemory<E> {
             let experiences = self
                 .memory
                 .view()
-                .choose_multiple(&mut thread_rng(), self.batch_size)
+                .choose_multiple(&mut seed::rng(Stream::Agent), self.batch_size)
                 .cloned();
             let batch = ExpBatch::from_iter(experiences, self.batch_size);
             Some(batch)
@@ -1,6 +1,8 @@
 use std::fmt::Debug;
 
-use crate::env::Environment;
+use burn::{prelude::*, tensor::BasicOps};
+
+use crate::{env::Environment, traits::ToTensor};
 
 /// Represents a single experience or transition in the environment
 pub struct Exp<E: Environment> {
@@ -69,6 +71,104 @@ impl<E: Environment> ExpBatch<E> {
     }
 }
 
+/// A batch of experiences as tensors, with one row per experience
+///
+/// ### Generics
+/// - `B` - A burn backend
+/// - `D` - The dimension of the state tensors
+/// - `K` - The kind of the action tensor, e.g. `Int` for discrete actions
+#[derive(Debug, Clone)]
+pub struct TensorBatch<B: Backend, const D: usize, K: BasicOps<B>> {
+    /// The states before taking the actions
+    pub states: Tensor<B, D>,
+    /// The actions taken, with shape `[batch, A]`
+    pub actions: Tensor<B, 2, K>,
+    /// The rewards received, with shape `[batch, 1]`
+    pub rewards: Tensor<B, 2>,
+    /// The states after taking the actions
+    ///
+    /// Terminal next states are filled with the state before the action, so the tensor has a row for every
+    /// experience. Values computed from them are masked out by [`targets`](TensorBatch::targets).
+    pub next_states: Tensor<B, D>,
+    /// Whether each next state is non-terminal, with shape `[batch, 1]`
+    pub non_terminal: Tensor<B, 2, Bool>,
+}
+
+impl<B: Backend, const D: usize, K: BasicOps<B>> TensorBatch<B, D, K> {
+    /// The bootstrapped targets `reward + gamma * next_value` of the experiences, without the values of terminal
+    /// next states
+    ///
+    /// ### Arguments
+    /// - `next_values` - The value of each of the [`next_states`](TensorBatch::next_states), with shape `[batch, 1]`
+    /// - `gamma` - The discount factor
+    pub fn targets(&self, next_values: Tensor<B, 2>, gamma: f32) -> Tensor<B, 2> {
+        let next_values = next_values.mask_fill(self.non_terminal.clone().bool_not(), 0.0);
+        self.rewards.clone() + next_values * gamma
+    }
+}
+
+/// A trait for converting batches of [experiences](Exp) to a [`TensorBatch`]
+///
+/// ### Generics
+/// - `E` - The [`Environment`] of the experiences
+pub trait Collate<E: Environment> {
+    /// Convert the experiences to tensors on `device`
+    ///
+    /// Actions are converted to `A` first, e.g. `[i32; 1]` for discrete actions that are `Into<[i32; 1]>`.
+    fn collate<B, const D: usize, K, A>(self, device: &B::Device) -> TensorBatch<B, D, K>
+    where
+        B: Backend,
+        K: BasicOps<B>,
+        Vec<E::State>: ToTensor<B, D, Float>,
+        E::Action: Into<A>,
+        Vec<A>: ToTensor<B, 2, K>;
+}
+
+impl<E: Environment> Collate<E> for ExpBatch<E> {
+    fn collate<B, const D: usize, K, A>(self, device: &B::Device) -> TensorBatch<B, D, K>
+    where
+        B: Backend,
+        K: BasicOps<B>,
+        Vec<E::State>: ToTensor<B, D, Float>,
+        E::Action: Into<A>,
+        Vec<A>: ToTensor<B, 2, K>,
+    {
+        let non_terminal = self
+            .next_states
+            .iter()
+            .map(Option::is_some)
+            .collect::<Vec<_>>();
+        let next_states = self
+            .next_states
+            .into_iter()
+            .zip(&self.states)
+            .map(|(next_state, state)| next_state.unwrap_or_else(|| state.clone()))
+            .collect::<Vec<_>>();
+        let actions = self.actions.into_iter().map(Into::into).collect::<Vec<_>>();
+
+        TensorBatch {
+            states: self.states.to_tensor(device),
+            actions: actions.to_tensor(device),
+            rewards: Tensor::<B, 1>::from_floats(self.rewards.as_slice(), device).unsqueeze_dim(1),
+            next_states: next_states.to_tensor(device),
+            non_terminal: non_terminal.to_tensor(device),
+        }
+    }
+}
+
+impl<E: Environment> Collate<E> for &[Exp<E>] {
+    fn collate<B, const D: usize, K, A>(self, device: &B::Device) -> TensorBatch<B, D, K>
+    where
+        B: Backend,
+        K: BasicOps<B>,
+        Vec<E::State>: ToTensor<B, D, Float>,
+        E::Action: Into<A>,
+        Vec<A>: ToTensor<B, 2, K>,
+    {
+        ExpBatch::from_iter(self.iter().cloned(), self.len()).collate(device)
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use crate::env::tests::MockEnv;
@@ -118,4 +218,62 @@ pub(crate) mod tests {
             "Next states constructed correctly"
         );
     }
+
+    #[test]
+    fn collate() {
+        use burn::backend::{ndarray::NdArrayDevice, NdArray as B};
+
+        struct ArrayEnv;
+
+        impl Environment for ArrayEnv {
+            type State = [f32; 2];
+            type Action = [i32; 1];
+
+            fn step(&mut self, _action: Self::Action) -> (Option<Self::State>, f32) {
+                (None, 0.0)
+            }
+
+            fn reset(&mut self) -> Self::State {
+                [0.0; 2]
+            }
+
+            fn random_action(&self) -> Self::Action {
+                [0]
+            }
+        }
+
+        let experiences: [Exp<ArrayEnv>; 2] = [
+            Exp {
+                state: [0.0, 1.0],
+                action: [1],
+                reward: 1.0,
+                next_state: Some([2.0, 3.0]),
+            },
+            Exp {
+                state: [4.0, 5.0],
+                action: [0],
+                reward: -1.0,
+                next_state: None,
+            },
+        ];
+        let batch = experiences
+            .as_slice()
+            .collate::<B, 2, Int, [i32; 1]>(&NdArrayDevice::Cpu);
+
+        assert_eq!(batch.states.dims(), [2, 2]);
+        assert_eq!(batch.actions.to_data().value, [1, 0]);
+        assert_eq!(
+            batch.next_states.to_data().value,
+            [2.0, 3.0, 4.0, 5.0],
+            "Terminal next states are filled with the state"
+        );
+        assert_eq!(batch.non_terminal.to_data().value, [true, false]);
+
+        let next_values = Tensor::from_floats([[10.0], [20.0]], &NdArrayDevice::Cpu);
+        assert_eq!(
+            batch.targets(next_values, 0.5).into_data().value,
+            [6.0, -1.0],
+            "Values of terminal next states are masked out"
+        );
+    }
 }
@@ -1,10 +1,12 @@
 mod base;
 mod exp;
 mod prioritized;
+mod sequence;
 
 pub use base::ReplayMemory;
 pub use exp::*;
 pub use prioritized::PrioritizedReplayMemory;
+pub use sequence::{SequenceBatch, SequenceReplayMemory};
 
 use crate::env::Environment;
 
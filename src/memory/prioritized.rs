@@ -1,12 +1,11 @@
-use rand::{
-    distributions::{Distribution, Uniform},
-    thread_rng,
-};
+use rand::distributions::{Distribution, Uniform};
 
 use crate::{
     decay::{self, Decay},
     ds::{RingBuffer, SumTree},
     env::Environment,
+    error::{check_interval, Result},
+    seed::{self, Stream},
 };
 
 use super::{Exp, ExpBatch};
@@ -43,20 +42,35 @@ impl<E: Environment> PrioritizedReplayMemory<E> {
     ///   - A sensible default is `0.5`
     /// - `num_episodes` - the number of episodes the associated agent will train for
     ///   - Needed to set up annealing of the beta hyperparameter
+    ///
+    /// **Returns** an [`RlError::OutOfRange`](crate::error::RlError::OutOfRange) if `alpha` is negative or `beta_0`
+    /// is not in the interval `[0, 1)`
     pub fn new(
         capacity: usize,
         batch_size: usize,
         alpha: f32,
         beta_0: f32,
         num_episodes: usize,
-    ) -> Self {
-        Self {
+    ) -> Result<Self> {
+        check_interval("alpha", alpha, 0.0, f32::INFINITY)?;
+        check_interval("beta_0", beta_0, 0.0, 1.0 - f32::EPSILON)?;
+        Ok(Self {
             memory: RingBuffer::new(capacity),
             priorities: SumTree::new(capacity),
             alpha,
-            beta: decay::Linear::new((beta_0 - 1.0) / num_episodes as f32, beta_0, 1.0).unwrap(),
+            beta: decay::Linear::new((beta_0 - 1.0) / num_episodes as f32, beta_0, 1.0)?,
             batch_size,
-        }
+        })
+    }
+
+    /// The number of experiences stored in the memory
+    pub fn len(&self) -> usize {
+        self.memory.len()
+    }
+
+    /// Whether no experiences are stored in the memory
+    pub fn is_empty(&self) -> bool {
+        self.memory.len() == 0
     }
 
     /// Add a new experience to the memory
@@ -70,6 +84,11 @@ impl<E: Environment> PrioritizedReplayMemory<E> {
     fn compute_weights(&self, episode: usize, probs: Vec<f32>) -> Vec<f32> {
         let beta = self.beta.evaluate(episode as f32);
         let n = self.memory.len() as f32;
+        tracing::trace!(
+            beta,
+            size = self.memory.len(),
+            "importance sampling weights"
+        );
 
         let weights = probs.into_iter().map(|p| (n * p).powf(-beta));
         let w_max = weights.clone().reduce(f32::max).unwrap();
@@ -96,7 +115,7 @@ impl<E: Environment> PrioritizedReplayMemory<E> {
 
         let total_priority = self.priorities.sum();
 
-        let mut rng = thread_rng();
+        let mut rng = seed::rng(Stream::Agent);
         let dist = Uniform::new(0.0, total_priority);
 
         let mut batch = Vec::with_capacity(self.batch_size);
@@ -167,7 +186,7 @@ mod tests {
     #[test]
     fn prioritized_replay_memory_functional() {
         let experiences = create_mock_exp_vec(8);
-        let mut memory = PrioritizedReplayMemory::new(8, 4, 1.0, 0.5, 16);
+        let mut memory = PrioritizedReplayMemory::new(8, 4, 1.0, 0.5, 16).unwrap();
 
         assert!(
             memory.sample(0).is_none(),
@@ -0,0 +1,345 @@
+use std::{collections::VecDeque, ops::Range};
+
+use burn::{prelude::*, tensor::BasicOps};
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    env::Environment,
+    seed::{self, Stream},
+    traits::ToTensor,
+};
+
+use super::{Collate, Exp};
+
+/// A replay memory for recurrent agents, storing whole episodes and sampling sequences of consecutive experiences
+///
+/// Sequences never cross an episode boundary, so a recurrent network can start each one from a fresh hidden state.
+/// Episodes end with a terminal experience or a call to [`end_episode`](SequenceReplayMemory::end_episode), e.g.
+/// when they are truncated. The oldest episodes are dropped once more than `capacity` experiences are stored.
+///
+/// ### Type Parameters:
+/// - `E` - Environment
+#[derive(Debug, Clone)]
+pub struct SequenceReplayMemory<E: Environment> {
+    episodes: VecDeque<Vec<Exp<E>>>,
+    current: Vec<Exp<E>>,
+    len: usize,
+    capacity: usize,
+    pub batch_size: usize,
+    pub sequence_length: usize,
+}
+
+impl<E: Environment> SequenceReplayMemory<E> {
+    /// Construct a new `SequenceReplayMemory`
+    ///
+    /// ### Arguments
+    /// - `capacity` - The maximum number of experiences stored
+    /// - `batch_size` - The number of sequences in a batch
+    /// - `sequence_length` - The number of experiences in a sequence
+    pub fn new(capacity: usize, batch_size: usize, sequence_length: usize) -> Self {
+        Self {
+            episodes: VecDeque::new(),
+            current: Vec::new(),
+            len: 0,
+            capacity,
+            batch_size,
+            sequence_length,
+        }
+    }
+
+    /// The number of experiences stored in the memory
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no experiences are stored in the memory
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Add a new experience to the current episode, ending it if the experience is terminal
+    pub fn push(&mut self, exp: Exp<E>) {
+        let terminal = exp.next_state.is_none();
+        self.current.push(exp);
+        self.len += 1;
+        if terminal {
+            self.end_episode();
+        }
+        while self.len > self.capacity {
+            match self.episodes.pop_front() {
+                Some(episode) => self.len -= episode.len(),
+                None => {
+                    self.current.remove(0);
+                    self.len -= 1;
+                }
+            }
+        }
+    }
+
+    /// End the current episode, so the next experience starts a new one
+    pub fn end_episode(&mut self) {
+        if !self.current.is_empty() {
+            self.episodes.push_back(std::mem::take(&mut self.current));
+        }
+    }
+
+    /// Sample a random batch of sequences from the memory
+    ///
+    /// Episodes are chosen in proportion to their length and sequences start at a random step. Sequences of episodes
+    /// shorter than `sequence_length` hold the whole episode.
+    ///
+    /// ### Returns
+    /// - `None` if there are less experiences stored than can fill a batch
+    /// - `Some(sequences)` otherwise
+    pub fn sample(&self) -> Option<Vec<&[Exp<E>]>> {
+        if self.len < self.batch_size {
+            return None;
+        }
+        let episodes = self
+            .episodes
+            .iter()
+            .chain([&self.current])
+            .filter(|episode| !episode.is_empty())
+            .collect::<Vec<_>>();
+        let mut rng = seed::rng(Stream::Agent);
+        let sequences = (0..self.batch_size)
+            .map(|_| {
+                let episode = *episodes
+                    .choose_weighted(&mut rng, |episode| episode.len())
+                    .expect("The memory is not empty");
+                let start = rng.gen_range(0..=episode.len().saturating_sub(self.sequence_length));
+                let end = (start + self.sequence_length).min(episode.len());
+                &episode[start..end]
+            })
+            .collect();
+        Some(sequences)
+    }
+}
+
+/// A batch of sequences of experiences as tensors of shape `[batch, length, ...]`
+///
+/// Sequences shorter than the length of the batch are padded at the end, and the padding is left out by the
+/// [`weights`](SequenceBatch::weights).
+///
+/// ### Generics
+/// - `B` - A burn backend
+/// - `K` - The kind of the action tensor, e.g. `Int` for discrete actions
+#[derive(Debug, Clone)]
+pub struct SequenceBatch<B: Backend, K: BasicOps<B>> {
+    /// The states before taking the actions, with shape `[batch, length, features]`
+    pub states: Tensor<B, 3>,
+    /// The actions taken, with shape `[batch, length, A]`
+    pub actions: Tensor<B, 3, K>,
+    /// The rewards received, with shape `[batch, length, 1]`
+    pub rewards: Tensor<B, 3>,
+    /// The states after taking the actions, with terminal next states filled with the state before the action
+    pub next_states: Tensor<B, 3>,
+    /// Whether each next state is non-terminal, with shape `[batch, length, 1]`
+    pub non_terminal: Tensor<B, 3, Bool>,
+    /// The number of experiences in each sequence before the padding
+    pub lengths: Vec<usize>,
+}
+
+impl<B: Backend, K: BasicOps<B>> SequenceBatch<B, K> {
+    /// Convert sequences of experiences to tensors on `device`
+    ///
+    /// Actions are converted to `A` first, as in [`Collate::collate`].
+    ///
+    /// ### Arguments
+    /// - `sequences` - The sequences, e.g. sampled with [`SequenceReplayMemory::sample`]
+    /// - `length` - The length of the batch, to which shorter sequences are padded
+    ///
+    /// **Panics** if a sequence is empty or longer than `length`
+    pub fn new<E, A>(sequences: &[&[Exp<E>]], length: usize, device: &B::Device) -> Self
+    where
+        E: Environment,
+        Vec<E::State>: ToTensor<B, 2, Float>,
+        E::Action: Into<A>,
+        Vec<A>: ToTensor<B, 2, K>,
+    {
+        let lengths = sequences
+            .iter()
+            .map(|sequence| sequence.len())
+            .collect::<Vec<_>>();
+        assert!(
+            lengths.iter().all(|&len| (1..=length).contains(&len)),
+            "Sequences must have between 1 and {length} experiences"
+        );
+        let padded = sequences
+            .iter()
+            .flat_map(|sequence| {
+                let last = &sequence[sequence.len() - 1];
+                sequence
+                    .iter()
+                    .chain(std::iter::repeat(last).take(length - sequence.len()))
+                    .cloned()
+            })
+            .collect::<Vec<_>>();
+
+        let batch = padded.as_slice().collate::<B, 2, K, A>(device);
+        let shape = |tensor_dims: [usize; 2]| [sequences.len(), length, tensor_dims[1]];
+        Self {
+            states: batch.states.clone().reshape(shape(batch.states.dims())),
+            actions: batch.actions.clone().reshape(shape(batch.actions.dims())),
+            rewards: batch.rewards.reshape([sequences.len(), length, 1]),
+            next_states: batch
+                .next_states
+                .clone()
+                .reshape(shape(batch.next_states.dims())),
+            non_terminal: batch.non_terminal.reshape([sequences.len(), length, 1]),
+            lengths,
+        }
+    }
+
+    /// The bootstrapped targets `reward + gamma * next_value` of every step, without the values of terminal next
+    /// states
+    ///
+    /// ### Arguments
+    /// - `next_values` - The value of each of the [`next_states`](SequenceBatch::next_states), with shape
+    ///   `[batch, length, 1]`
+    /// - `gamma` - The discount factor
+    pub fn targets(&self, next_values: Tensor<B, 3>, gamma: f32) -> Tensor<B, 3> {
+        let next_values = next_values.mask_fill(self.non_terminal.clone().bool_not(), 0.0);
+        self.rewards.clone() + next_values * gamma
+    }
+
+    /// The steps in `range` of every sequence, e.g. to split off the first steps that warm up the hidden state of a
+    /// recurrent network
+    ///
+    /// **Panics** if the range is empty or ends after the length of the batch
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        let [batch, length, _] = self.rewards.dims();
+        assert!(
+            range.start < range.end && range.end <= length,
+            "Invalid range {range:?} of sequences of length {length}"
+        );
+        let slice = |dims: [usize; 3]| [0..batch, range.clone(), 0..dims[2]];
+        Self {
+            states: self.states.clone().slice(slice(self.states.dims())),
+            actions: self.actions.clone().slice(slice(self.actions.dims())),
+            rewards: self.rewards.clone().slice(slice(self.rewards.dims())),
+            next_states: self
+                .next_states
+                .clone()
+                .slice(slice(self.next_states.dims())),
+            non_terminal: self
+                .non_terminal
+                .clone()
+                .slice(slice(self.non_terminal.dims())),
+            lengths: self
+                .lengths
+                .iter()
+                .map(|len| len.clamp(range.start, range.end) - range.start)
+                .collect(),
+        }
+    }
+
+    /// The weight of every step in a loss, `1` for experiences and `0` for the padding, with shape
+    /// `[batch, length, 1]`
+    pub fn weights(&self) -> Tensor<B, 3> {
+        let [batch, length, _] = self.rewards.dims();
+        let weights = self
+            .lengths
+            .iter()
+            .flat_map(|&len| (0..length).map(move |step| (step < len) as u8 as f32))
+            .collect::<Vec<_>>();
+        Tensor::<B, 1>::from_floats(weights.as_slice(), &self.rewards.device())
+            .reshape([batch, length, 1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use burn::backend::{ndarray::NdArrayDevice, NdArray as B};
+
+    use super::*;
+    use crate::env::tests::MockEnv;
+
+    fn exp(state: i32, terminal: bool) -> Exp<MockEnv> {
+        Exp {
+            state,
+            action: state,
+            reward: 1.0,
+            next_state: (!terminal).then_some(state + 1),
+        }
+    }
+
+    #[test]
+    fn sequences_stay_in_episodes() {
+        let mut memory = SequenceReplayMemory::new(8, 16, 3);
+        for (state, terminal) in [(0, false), (1, true), (10, false), (11, false), (12, false)] {
+            memory.push(exp(state, terminal));
+        }
+        assert!(
+            memory.sample().is_none(),
+            "sample none when too few experiences"
+        );
+        memory.batch_size = 4;
+
+        for sequence in memory.sample().unwrap() {
+            let states = sequence.iter().map(|exp| exp.state).collect::<Vec<_>>();
+            assert!(
+                states == [0, 1] || states == [10, 11, 12],
+                "Sequence {states:?} crosses an episode boundary"
+            );
+        }
+
+        memory.end_episode();
+        for state in 20..24 {
+            memory.push(exp(state, false));
+        }
+        assert_eq!(memory.len(), 7, "The oldest episode is dropped");
+    }
+
+    #[test]
+    fn sequence_batch() {
+        struct ArrayEnv;
+
+        impl Environment for ArrayEnv {
+            type State = [f32; 1];
+            type Action = [i32; 1];
+
+            fn step(&mut self, _action: Self::Action) -> (Option<Self::State>, f32) {
+                (None, 0.0)
+            }
+
+            fn reset(&mut self) -> Self::State {
+                [0.0]
+            }
+
+            fn random_action(&self) -> Self::Action {
+                [0]
+            }
+        }
+
+        let exp = |state: f32, terminal: bool| Exp::<ArrayEnv> {
+            state: [state],
+            action: [state as i32],
+            reward: 1.0,
+            next_state: (!terminal).then_some([state + 1.0]),
+        };
+        let long = [exp(0.0, false), exp(1.0, false), exp(2.0, false)];
+        let short = [exp(5.0, true)];
+        let batch =
+            SequenceBatch::<B, Int>::new::<_, [i32; 1]>(&[&long, &short], 3, &NdArrayDevice::Cpu);
+
+        assert_eq!(batch.states.dims(), [2, 3, 1]);
+        assert_eq!(
+            batch.next_states.to_data().value,
+            [1.0, 2.0, 3.0, 5.0, 5.0, 5.0]
+        );
+        assert_eq!(batch.actions.to_data().value, [0, 1, 2, 5, 5, 5]);
+        assert_eq!(
+            batch.weights().into_data().value,
+            [1.0, 1.0, 1.0, 1.0, 0.0, 0.0]
+        );
+        let rest = batch.slice(1..3);
+        assert_eq!(rest.states.to_data().value, [1.0, 2.0, 5.0, 5.0]);
+        assert_eq!(rest.lengths, [2, 0]);
+        assert_eq!(
+            rest.weights().into_data().value,
+            [1.0, 1.0, 0.0, 0.0],
+            "Padding is left out"
+        );
+    }
+}
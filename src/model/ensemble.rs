@@ -0,0 +1,315 @@
+use burn::{
+    optim::{adaptor::OptimizerAdaptor, AdamW, AdamWConfig, GradientsParams, Optimizer},
+    prelude::*,
+    tensor::{backend::AutodiffBackend, ElementConversion},
+};
+
+use crate::{
+    error::{check_interval, Result, RlError},
+    memory::TensorBatch,
+    nn::builders::{Activation, Mlp, MlpConfig},
+};
+
+/// Configuration for an [`EnsembleModel`]
+#[derive(Debug, Clone)]
+pub struct EnsembleModelConfig {
+    /// The sizes of the hidden layers of every member
+    ///
+    /// **Default:** `[200, 200]`
+    pub hidden: Vec<usize>,
+    /// The activation function after each hidden layer
+    ///
+    /// **Default:** [`Activation::Relu`]
+    pub activation: Activation,
+    /// The number of independently initialized members, `1` for a single network without uncertainty estimates
+    ///
+    /// **Default:** `5`
+    pub ensemble_size: usize,
+    /// The learning rate of the optimizer of every member
+    ///
+    /// **Default:** `1e-3`
+    pub lr: f32,
+}
+
+impl Default for EnsembleModelConfig {
+    fn default() -> Self {
+        Self {
+            hidden: vec![200, 200],
+            activation: Activation::Relu,
+            ensemble_size: 5,
+            lr: 1e-3,
+        }
+    }
+}
+
+/// The predictions of an [`EnsembleModel`] for a batch of state action pairs
+#[derive(Debug, Clone)]
+pub struct Prediction<B: Backend> {
+    /// The mean next state of the members, with shape `[batch, S]`
+    pub next_states: Tensor<B, 2>,
+    /// The mean reward of the members, with shape `[batch, 1]`
+    pub rewards: Tensor<B, 2>,
+    /// The standard deviation of the members' next states, with shape `[batch, S]`
+    pub next_state_std: Tensor<B, 2>,
+    /// The standard deviation of the members' rewards, with shape `[batch, 1]`
+    pub reward_std: Tensor<B, 2>,
+}
+
+impl<B: Backend> Prediction<B> {
+    /// The disagreement of the members about each prediction, the largest standard deviation of any of its outputs,
+    /// with shape `[batch, 1]`
+    ///
+    /// Members agree where they were trained and diverge away from the data, so this approximates the epistemic
+    /// uncertainty of the model, e.g. to penalize rewards of uncertain predictions as in MOPO or to end simulated
+    /// rollouts early.
+    pub fn uncertainty(&self) -> Tensor<B, 2> {
+        Tensor::cat(
+            vec![self.next_state_std.clone(), self.reward_std.clone()],
+            1,
+        )
+        .max_dim(1)
+    }
+}
+
+type MemberOptimizer<B> = OptimizerAdaptor<AdamW<<B as AutodiffBackend>::InnerBackend>, Mlp<B>, B>;
+
+/// A learned model of the one-step dynamics and rewards of an environment with feature vectors as states, made of an
+/// ensemble of [`Mlp`]s
+///
+/// Every member maps a state and an action to the change of the state and the reward, and is trained on the same
+/// batches from its own initialization. Predicting the change rather than the next state keeps the targets small for
+/// environments that change gradually. The spread of the members' predictions gives their
+/// [uncertainty](Prediction::uncertainty), see Chua et al., 2018, *Deep Reinforcement Learning in a Handful of Trials
+/// using Probabilistic Dynamics Models*.
+///
+/// Terminal transitions only train the reward, since they have no next state.
+///
+/// ```ignore
+/// let mut model = EnsembleModel::<Autodiff<NdArray>>::new(4, 1, EnsembleModelConfig::default(), &device)?;
+/// let batch = ExpBatch::from_iter(experiences, 256).collate::<_, 2, Float, [f32; 1]>(&device);
+/// let loss = model.learn(&batch);
+/// let prediction = model.predict(states, actions);
+/// ```
+///
+/// ### Generics
+/// - `B` - A burn backend with autodiff
+pub struct EnsembleModel<B: AutodiffBackend> {
+    members: Vec<Mlp<B>>,
+    optimizers: Vec<MemberOptimizer<B>>,
+    state_size: usize,
+    lr: f32,
+    device: B::Device,
+}
+
+impl<B: AutodiffBackend> EnsembleModel<B> {
+    /// Initialize an untrained model on `device`
+    ///
+    /// ### Arguments
+    /// - `state_size` - The number of features of a state
+    /// - `action_size` - The number of values of an action, e.g. the number of actions of one-hot encoded discrete
+    ///   actions
+    /// - `config` - The configuration of the ensemble
+    /// - `device` - The device of the networks
+    ///
+    /// ### Returns
+    /// - [`RlError::OutOfRange`] if `lr` is negative
+    /// - [`RlError::InvalidHyperparameters`] if the ensemble has no members
+    pub fn new(
+        state_size: usize,
+        action_size: usize,
+        config: EnsembleModelConfig,
+        device: &B::Device,
+    ) -> Result<Self> {
+        check_interval("lr", config.lr, 0.0, f32::INFINITY)?;
+        if config.ensemble_size == 0 {
+            return Err(RlError::InvalidHyperparameters(String::from(
+                "an ensemble needs at least one member",
+            )));
+        }
+
+        let sizes = [
+            &[state_size + action_size][..],
+            &config.hidden,
+            &[state_size + 1],
+        ]
+        .concat();
+        let mlp = MlpConfig::new(&sizes).with_activation(config.activation);
+        Ok(Self {
+            members: (0..config.ensemble_size)
+                .map(|_| mlp.init(device))
+                .collect(),
+            optimizers: (0..config.ensemble_size)
+                .map(|_| AdamWConfig::new().init())
+                .collect(),
+            state_size,
+            lr: config.lr,
+            device: device.clone(),
+        })
+    }
+
+    /// The number of members
+    pub fn ensemble_size(&self) -> usize {
+        self.members.len()
+    }
+
+    /// The device of the networks
+    pub fn device(&self) -> &B::Device {
+        &self.device
+    }
+
+    /// Train every member for one step on `batch`, with actions as floats of shape `[batch, A]`
+    ///
+    /// **Returns** the mean squared error of the members, averaged over them
+    pub fn learn(&mut self, batch: &TensorBatch<B, 2, Float>) -> f64 {
+        let [rows, _] = batch.states.dims();
+        let input = Tensor::cat(vec![batch.states.clone(), batch.actions.clone()], 1);
+        let deltas = batch.next_states.clone() - batch.states.clone();
+        let non_terminal = batch.non_terminal.clone().float();
+
+        let mut total = 0.0;
+        let members = std::mem::take(&mut self.members);
+        self.members = members
+            .into_iter()
+            .zip(&mut self.optimizers)
+            .map(|(member, optimizer)| {
+                let output = member.forward(input.clone());
+                let delta = output.clone().slice([0..rows, 0..self.state_size]);
+                let reward = output.slice([0..rows, self.state_size..self.state_size + 1]);
+
+                let state_loss =
+                    ((delta - deltas.clone()).powf_scalar(2.0) * non_terminal.clone()).mean();
+                let reward_loss = (reward - batch.rewards.clone()).powf_scalar(2.0).mean();
+                let loss = state_loss + reward_loss;
+                total += loss.clone().into_scalar().elem::<f64>();
+
+                let grads = GradientsParams::from_grads(loss.backward(), &member);
+                optimizer.step(self.lr.into(), member, grads)
+            })
+            .collect();
+
+        total / self.members.len() as f64
+    }
+
+    /// Predict the next states and rewards of `actions` in `states`, with the spread of the members
+    ///
+    /// ### Arguments
+    /// - `states` - The states, with shape `[batch, S]`
+    /// - `actions` - The actions, with shape `[batch, A]`
+    pub fn predict(&self, states: Tensor<B, 2>, actions: Tensor<B, 2>) -> Prediction<B> {
+        let outputs = (0..self.members.len())
+            .map(|i| {
+                let (next_states, rewards) =
+                    self.predict_member(i, states.clone(), actions.clone());
+                Tensor::cat(vec![next_states, rewards], 1)
+            })
+            .collect::<Vec<_>>();
+        let [rows, size] = outputs[0].dims();
+        let outputs = Tensor::stack::<3>(outputs, 0);
+        let mean = outputs.clone().mean_dim(0);
+        let std = (outputs - mean.clone())
+            .powf_scalar(2.0)
+            .mean_dim(0)
+            .sqrt()
+            .reshape([rows, size]);
+        let mean = mean.reshape([rows, size]);
+
+        Prediction {
+            next_states: mean.clone().slice([0..rows, 0..self.state_size]),
+            rewards: mean.slice([0..rows, self.state_size..size]),
+            next_state_std: std.clone().slice([0..rows, 0..self.state_size]),
+            reward_std: std.slice([0..rows, self.state_size..size]),
+        }
+    }
+
+    /// Predict the next states and rewards with the member at `index`, e.g. a random member for every step of a
+    /// simulated rollout
+    ///
+    /// **Returns** `(next_states, rewards)` with shapes `[batch, S]` and `[batch, 1]`, detached from the graph
+    ///
+    /// **Panics** if there is no member at `index`
+    pub fn predict_member(
+        &self,
+        index: usize,
+        states: Tensor<B, 2>,
+        actions: Tensor<B, 2>,
+    ) -> (Tensor<B, 2>, Tensor<B, 2>) {
+        let [rows, _] = states.dims();
+        let input = Tensor::cat(vec![states.clone(), actions], 1);
+        let output = self.members[index].forward(input).detach();
+        let next_states = states + output.clone().slice([0..rows, 0..self.state_size]);
+        let rewards = output.slice([0..rows, self.state_size..self.state_size + 1]);
+        (next_states, rewards)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use burn::backend::{ndarray::NdArrayDevice, Autodiff, NdArray};
+
+    use super::*;
+    use crate::{seed::Seeds, traits::ToTensor};
+
+    type B = Autodiff<NdArray>;
+
+    #[test]
+    fn ensemble_model_learns_dynamics() {
+        Seeds::new(0).apply();
+        let device = NdArrayDevice::Cpu;
+        B::seed(0);
+        let mut model = EnsembleModel::<B>::new(
+            1,
+            1,
+            EnsembleModelConfig {
+                hidden: vec![32],
+                ensemble_size: 3,
+                lr: 1e-2,
+                ..Default::default()
+            },
+            &device,
+        )
+        .unwrap();
+
+        // s' = s + a with reward s, on states and actions in [-1, 1]
+        let tensor = |values: &[f32]| {
+            Tensor::<B, 1>::from_floats(values, &device).reshape([values.len(), 1])
+        };
+        let states = (0..32)
+            .map(|i| (i % 8) as f32 / 3.5 - 1.0)
+            .collect::<Vec<_>>();
+        let actions = (0..32)
+            .map(|i| (i / 8) as f32 / 1.5 - 1.0)
+            .collect::<Vec<_>>();
+        let next_states = states
+            .iter()
+            .zip(&actions)
+            .map(|(s, a)| s + a)
+            .collect::<Vec<_>>();
+        let batch = TensorBatch {
+            states: tensor(&states),
+            actions: tensor(&actions),
+            rewards: tensor(&states),
+            next_states: tensor(&next_states),
+            non_terminal: vec![true; 32].to_tensor(&device),
+        };
+
+        let first = model.learn(&batch);
+        let last = (0..500).map(|_| model.learn(&batch)).last().unwrap();
+        assert!(last < first / 10.0, "loss {first} -> {last}");
+
+        let prediction = model.predict(tensor(&[0.5]), tensor(&[-0.25]));
+        let next_state = prediction.next_states.into_scalar();
+        assert!((next_state - 0.25).abs() < 0.1, "{next_state}");
+        assert_eq!(prediction.uncertainty().dims(), [1, 1]);
+
+        assert!(EnsembleModel::<B>::new(
+            1,
+            1,
+            EnsembleModelConfig {
+                ensemble_size: 0,
+                ..Default::default()
+            },
+            &device
+        )
+        .is_err());
+    }
+}
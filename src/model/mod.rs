@@ -0,0 +1,7 @@
+/// Ensembles of neural networks modeling environments with feature vectors as states
+mod ensemble;
+/// Count-based models of discrete environments
+mod tabular;
+
+pub use ensemble::{EnsembleModel, EnsembleModelConfig, Prediction};
+pub use tabular::TabularModel;
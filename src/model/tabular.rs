@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use rand::distributions::{Distribution, WeightedIndex};
+
+use crate::{
+    algo::tabular::Hashable,
+    env::Environment,
+    memory::Exp,
+    seed::{self, Stream},
+    stats::RunningMeanVar,
+};
+
+/// The observed outcomes of one state action pair
+#[derive(Debug, Clone)]
+struct Outcomes<S> {
+    /// The next states in the order they were first observed, with their counts
+    next_states: Vec<(Option<S>, u64)>,
+    rewards: RunningMeanVar,
+}
+
+impl<S> Default for Outcomes<S> {
+    fn default() -> Self {
+        Self {
+            next_states: Vec::new(),
+            rewards: RunningMeanVar::default(),
+        }
+    }
+}
+
+/// A maximum likelihood model of a discrete environment, counting the transitions of every state action pair
+///
+/// Transition probabilities are the observed frequencies of next states and rewards are the observed means, so the
+/// model handles stochastic environments, unlike remembering the last outcome. The uncertainty of a state action pair
+/// shrinks with its visits, e.g. for the exploration bonuses of MBIE-EB or R-max.
+///
+/// ### Generics
+/// - `E` - The [`Environment`] that is modeled, with discrete states and actions as for a
+///   [`QTableAgent`](crate::algo::tabular::q_table::QTableAgent)
+#[derive(Debug, Clone)]
+pub struct TabularModel<E>
+where
+    E: Environment,
+    E::State: Hashable,
+    E::Action: Hashable,
+{
+    outcomes: HashMap<(E::State, E::Action), Outcomes<E::State>>,
+}
+
+impl<E> Default for TabularModel<E>
+where
+    E: Environment,
+    E::State: Hashable,
+    E::Action: Hashable,
+{
+    fn default() -> Self {
+        Self {
+            outcomes: HashMap::new(),
+        }
+    }
+}
+
+impl<E> TabularModel<E>
+where
+    E: Environment,
+    E::State: Hashable,
+    E::Action: Hashable,
+{
+    /// Create a model without observations
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count the transition of `experience`
+    pub fn learn(&mut self, experience: &Exp<E>) {
+        let outcomes = self
+            .outcomes
+            .entry((experience.state, experience.action))
+            .or_default();
+        match outcomes
+            .next_states
+            .iter_mut()
+            .find(|(next_state, _)| *next_state == experience.next_state)
+        {
+            Some((_, count)) => *count += 1,
+            None => outcomes.next_states.push((experience.next_state, 1)),
+        }
+        outcomes.rewards.push(experience.reward as f64);
+    }
+
+    /// The number of modeled state action pairs
+    pub fn len(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    /// Whether nothing was observed yet
+    pub fn is_empty(&self) -> bool {
+        self.outcomes.is_empty()
+    }
+
+    /// The number of observed transitions of `(state, action)`
+    pub fn visits(&self, state: E::State, action: E::Action) -> u64 {
+        self.outcomes
+            .get(&(state, action))
+            .map_or(0, |outcomes| outcomes.rewards.count())
+    }
+
+    /// The probability of each observed next state of `(state, action)`, `None` for terminal states
+    pub fn transitions(&self, state: E::State, action: E::Action) -> Vec<(Option<E::State>, f64)> {
+        let Some(outcomes) = self.outcomes.get(&(state, action)) else {
+            return Vec::new();
+        };
+        let visits = outcomes.rewards.count() as f64;
+        outcomes
+            .next_states
+            .iter()
+            .map(|&(next_state, count)| (next_state, count as f64 / visits))
+            .collect()
+    }
+
+    /// The mean reward of `(state, action)`, `None` if it wasn't observed
+    pub fn reward(&self, state: E::State, action: E::Action) -> Option<f64> {
+        self.outcomes
+            .get(&(state, action))
+            .map(|outcomes| outcomes.rewards.mean())
+    }
+
+    /// Sample a next state of `(state, action)` from the observed frequencies, with the mean reward
+    ///
+    /// **Returns** `None` if `(state, action)` wasn't observed
+    pub fn sample(&self, state: E::State, action: E::Action) -> Option<(Option<E::State>, f32)> {
+        let outcomes = self.outcomes.get(&(state, action))?;
+        let dist = WeightedIndex::new(outcomes.next_states.iter().map(|&(_, count)| count))
+            .expect("Observed pairs have next states");
+        let (next_state, _) = outcomes.next_states[dist.sample(&mut seed::rng(Stream::Agent))];
+        Some((next_state, outcomes.rewards.mean() as f32))
+    }
+
+    /// The uncertainty of the model about `(state, action)`, `1 / sqrt(n + 1)` after `n` visits
+    ///
+    /// It is `1` for pairs that were never visited and shrinks like the standard error of the estimates.
+    pub fn uncertainty(&self, state: E::State, action: E::Action) -> f64 {
+        1.0 / (self.visits(state, action) as f64 + 1.0).sqrt()
+    }
+
+    /// The modeled state action pairs, in arbitrary order
+    pub fn pairs(&self) -> impl Iterator<Item = (E::State, E::Action)> + '_ {
+        self.outcomes.keys().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Corridor, CorridorAction};
+
+    fn exp(
+        state: usize,
+        action: CorridorAction,
+        next_state: Option<usize>,
+        reward: f32,
+    ) -> Exp<Corridor> {
+        Exp {
+            state,
+            action,
+            reward,
+            next_state,
+        }
+    }
+
+    #[test]
+    fn tabular_model() {
+        let mut model = TabularModel::<Corridor>::new();
+        assert_eq!(model.uncertainty(0, CorridorAction::Right), 1.0);
+        assert!(model.sample(0, CorridorAction::Right).is_none());
+
+        model.learn(&exp(0, CorridorAction::Right, Some(1), 0.0));
+        model.learn(&exp(0, CorridorAction::Right, Some(0), 1.0));
+        model.learn(&exp(0, CorridorAction::Right, Some(1), 2.0));
+        model.learn(&exp(1, CorridorAction::Right, None, 1.0));
+
+        assert_eq!(model.len(), 2);
+        assert_eq!(model.visits(0, CorridorAction::Right), 3);
+        assert_eq!(model.uncertainty(0, CorridorAction::Right), 0.5);
+        assert_eq!(model.reward(0, CorridorAction::Right), Some(1.0));
+        let transitions = model.transitions(0, CorridorAction::Right);
+        assert_eq!(transitions[0].0, Some(1));
+        assert!((transitions[0].1 - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(model.transitions(1, CorridorAction::Right), [(None, 1.0)]);
+        assert_eq!(model.sample(1, CorridorAction::Right), Some((None, 1.0)));
+    }
+}
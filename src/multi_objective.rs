@@ -0,0 +1,322 @@
+use std::marker::PhantomData;
+
+use crate::{
+    decay::Decay,
+    env::{DiscreteActionSpace, Environment},
+    reward::Reward,
+    traits::Agent,
+};
+
+/// An [`Environment`] that scalarizes the rewards of a multi-objective environment with a weighted sum, whose
+/// weights follow schedules over the episodes
+///
+/// Scheduled weights move the agent along the trade-off between objectives during training, e.g. from mostly
+/// collecting treasure to also avoiding damage. For fixed weights, use [`decay::Constant`](crate::decay::Constant)
+/// schedules or [`Scalarized::weighted`](crate::reward::Scalarized::weighted).
+///
+/// ```ignore
+/// // The weight of the treasure drops faster than the one of the time penalty
+/// let weights = vec![decay::Linear::new(1e-3, 1.0, 0.2)?, decay::Linear::new(1e-4, 1.0, 0.8)?];
+/// let mut env = MultiObjective::new(DeepSeaTreasure::new(), weights);
+/// ```
+///
+/// ### Generics
+/// - `E` - The wrapped [`Environment`]
+/// - `R` - The [`Reward`] of `E`, with one value per objective
+/// - `D` - The [`Decay`] schedule of each weight, evaluated at the episode index
+#[derive(Debug, Clone)]
+pub struct MultiObjective<E, R, D: Decay> {
+    env: E,
+    weights: Vec<D>,
+    episode: Option<u64>,
+    episode_return: Vec<f64>,
+    reward: PhantomData<fn() -> R>,
+}
+
+impl<E, R, D> MultiObjective<E, R, D>
+where
+    E: Environment<R>,
+    R: Reward + AsRef<[f32]>,
+    D: Decay,
+{
+    /// Wrap `env` with one weight schedule per objective
+    pub fn new(env: E, weights: Vec<D>) -> Self {
+        let objectives = weights.len();
+        Self {
+            env,
+            weights,
+            episode: None,
+            episode_return: vec![0.0; objectives],
+            reward: PhantomData,
+        }
+    }
+
+    /// The weights of the objectives in the current episode
+    pub fn weights(&self) -> Vec<f32> {
+        let episode = self.episode.unwrap_or_default() as f32;
+        self.weights
+            .iter()
+            .map(|weight| weight.evaluate(episode))
+            .collect()
+    }
+
+    /// The return of each objective in the current episode
+    pub fn episode_return(&self) -> &[f64] {
+        &self.episode_return
+    }
+
+    /// The index of the current episode, counting every [`reset`](Environment::reset)
+    pub fn episode(&self) -> u64 {
+        self.episode.unwrap_or_default()
+    }
+
+    /// The wrapped environment
+    pub fn inner(&self) -> &E {
+        &self.env
+    }
+}
+
+/// **Panics** while stepping if a reward doesn't have one value per weight
+impl<E, R, D> Environment for MultiObjective<E, R, D>
+where
+    E: Environment<R>,
+    R: Reward + AsRef<[f32]>,
+    D: Decay,
+{
+    type State = E::State;
+    type Action = E::Action;
+
+    fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
+        let (next_state, reward) = self.env.step(action);
+        let reward = reward.as_ref();
+        assert_eq!(
+            reward.len(),
+            self.weights.len(),
+            "A reward has {} objectives, but there are {} weights",
+            reward.len(),
+            self.weights.len()
+        );
+        let weights = self.weights();
+        let mut scalar = 0.0;
+        for ((ret, r), w) in self.episode_return.iter_mut().zip(reward).zip(weights) {
+            *ret += *r as f64;
+            scalar += r * w;
+        }
+        (next_state, scalar)
+    }
+
+    fn reset(&mut self) -> Self::State {
+        self.episode = Some(self.episode.map_or(0, |episode| episode + 1));
+        self.episode_return.fill(0.0);
+        self.env.reset()
+    }
+
+    fn random_action(&self) -> Self::Action {
+        self.env.random_action()
+    }
+
+    fn is_active(&self) -> bool {
+        self.env.is_active()
+    }
+
+    fn discount(&self) -> Option<f32> {
+        self.env.discount()
+    }
+}
+
+impl<E, R, D> DiscreteActionSpace for MultiObjective<E, R, D>
+where
+    E: DiscreteActionSpace<R>,
+    R: Reward + AsRef<[f32]>,
+    D: Decay,
+{
+    fn actions(&self) -> Vec<Self::Action> {
+        self.env.actions()
+    }
+}
+
+/// Run `episodes` episodes of the greedy [`policy`](Agent::policy) of `agent`, without learning
+///
+/// Episodes are truncated after the agent's [`max_episode_steps`](Agent::max_episode_steps). The episodes advance
+/// the weight schedules of `env`, but the returns don't depend on the weights.
+///
+/// **Returns** the mean return of each objective, a point to add to a [`ParetoFront`]
+pub fn evaluate_objectives<E, R, D, A>(
+    agent: &A,
+    env: &mut MultiObjective<E, R, D>,
+    episodes: u64,
+) -> Vec<f64>
+where
+    E: Environment<R>,
+    R: Reward + AsRef<[f32]>,
+    D: Decay,
+    A: Agent<MultiObjective<E, R, D>>,
+{
+    let max_steps = agent.max_episode_steps();
+    let mut total = vec![0.0; env.weights.len()];
+    for _ in 0..episodes {
+        let mut next_state = Some(env.reset());
+        agent.reset_policy();
+        let mut steps = 0;
+        while let Some(state) = next_state {
+            if max_steps.is_some_and(|max| steps >= max) {
+                break;
+            }
+            let action = agent.policy(env, &state);
+            next_state = env.step(action).0;
+            steps += 1;
+        }
+        for (total, ret) in total.iter_mut().zip(env.episode_return()) {
+            *total += ret;
+        }
+    }
+    total
+        .into_iter()
+        .map(|total| total / episodes.max(1) as f64)
+        .collect()
+}
+
+/// Whether `a` Pareto dominates `b` when every objective is maximized: `a` is at least as good in every objective
+/// and better in at least one
+///
+/// **Panics** if the points have different numbers of objectives
+pub fn dominates(a: &[f64], b: &[f64]) -> bool {
+    assert_eq!(
+        a.len(),
+        b.len(),
+        "Points must have the same number of objectives"
+    );
+    a.iter().zip(b).all(|(a, b)| a >= b) && a.iter().zip(b).any(|(a, b)| a > b)
+}
+
+/// The Pareto front of a set of points, the ones that no other point [dominates]
+///
+/// Each point is the mean return of every objective of a policy, e.g. from [`evaluate_objectives`], labelled with
+/// what produced it, e.g. the weights or the [`Trial`](crate::sweep::Trial) of a sweep. Every objective is
+/// maximized.
+///
+/// ### Generics
+/// - `T` - The label of a point
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParetoFront<T> {
+    points: Vec<(T, Vec<f64>)>,
+}
+
+impl<T> Default for ParetoFront<T> {
+    fn default() -> Self {
+        Self { points: Vec::new() }
+    }
+}
+
+impl<T> ParetoFront<T> {
+    /// Create an empty front
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a point to the front if no point of the front dominates it, removing the points it dominates
+    ///
+    /// **Returns** whether the point was added
+    ///
+    /// **Panics** if the point has a different number of objectives than the points of the front
+    pub fn insert(&mut self, label: T, objectives: Vec<f64>) -> bool {
+        if self
+            .points
+            .iter()
+            .any(|(_, point)| dominates(point, &objectives))
+        {
+            return false;
+        }
+        self.points
+            .retain(|(_, point)| !dominates(&objectives, point));
+        self.points.push((label, objectives));
+        true
+    }
+
+    /// The points of the front with their labels, in the order they were added
+    pub fn points(&self) -> &[(T, Vec<f64>)] {
+        &self.points
+    }
+
+    /// The number of points of the front
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Whether the front has no points
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decay, testing::FixedAgent};
+
+    /// One step, where each action collects a reward in one objective
+    struct Choice;
+
+    impl Environment<[f32; 2]> for Choice {
+        type State = ();
+        type Action = usize;
+
+        fn step(&mut self, action: Self::Action) -> (Option<Self::State>, [f32; 2]) {
+            let mut reward = [0.0; 2];
+            reward[action] = 1.0;
+            (None, reward)
+        }
+
+        fn reset(&mut self) -> Self::State {}
+
+        fn random_action(&self) -> Self::Action {
+            0
+        }
+    }
+
+    #[test]
+    fn scheduled_weights() {
+        let weights = vec![
+            decay::Linear::new(0.5, 1.0, 0.0).unwrap(),
+            decay::Linear::new(0.25, 1.0, 0.5).unwrap(),
+        ];
+        let mut env = MultiObjective::new(Choice, weights);
+
+        env.reset();
+        assert_eq!(env.weights(), [1.0, 1.0]);
+        assert_eq!(env.step(0), (None, 1.0));
+        assert_eq!(env.episode_return(), [1.0, 0.0]);
+        env.reset();
+        env.reset();
+        assert_eq!(env.episode(), 2);
+        assert_eq!(env.weights(), [0.0, 0.5]);
+        assert_eq!(env.step(0), (None, 0.0), "The weights have moved");
+
+        let returns = evaluate_objectives(&FixedAgent(0), &mut env, 3);
+        assert_eq!(returns, [1.0, 0.0]);
+    }
+
+    #[test]
+    fn pareto_front() {
+        assert!(dominates(&[1.0, 1.0], &[1.0, 0.0]));
+        assert!(!dominates(&[1.0, 0.0], &[1.0, 0.0]));
+        assert!(!dominates(&[2.0, 0.0], &[1.0, 1.0]));
+
+        let mut front = ParetoFront::new();
+        assert!(front.insert("a", vec![1.0, 0.0]));
+        assert!(front.insert("b", vec![0.0, 1.0]));
+        assert!(
+            front.insert("c", vec![0.5, 0.5]),
+            "A trade-off between a and b"
+        );
+        assert_eq!(front.len(), 3);
+        assert!(!front.insert("d", vec![0.0, 0.5]), "Dominated by b");
+        assert!(front.insert("e", vec![1.0, 1.0]));
+        let labels = front
+            .points()
+            .iter()
+            .map(|(label, _)| *label)
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["e"], "e dominates every other point");
+    }
+}
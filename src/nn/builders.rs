@@ -0,0 +1,340 @@
+use burn::{
+    module::Ignored,
+    nn::{
+        conv::{Conv2d, Conv2dConfig},
+        Initializer, Linear, LinearConfig,
+    },
+    prelude::*,
+    tensor::{activation, backend::AutodiffBackend},
+};
+
+use crate::{algo::dqn::DQNModel, export::PolicyNet};
+
+/// The activation function applied after each hidden layer of the built networks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Activation {
+    /// max(0, x)
+    #[default]
+    Relu,
+    /// tanh(x)
+    Tanh,
+    /// 1 / (1 + e<sup>-x</sup>)
+    Sigmoid,
+    /// x Φ(x), where Φ is the standard normal CDF
+    Gelu,
+}
+
+impl Activation {
+    /// Apply the activation function to `x`
+    pub fn apply<B: Backend, const D: usize>(&self, x: Tensor<B, D>) -> Tensor<B, D> {
+        match self {
+            Self::Relu => activation::relu(x),
+            Self::Tanh => activation::tanh(x),
+            Self::Sigmoid => activation::sigmoid(x),
+            Self::Gelu => activation::gelu(x),
+        }
+    }
+}
+
+/// Configuration for an [`Mlp`]
+#[derive(Debug, Clone)]
+pub struct MlpConfig {
+    sizes: Vec<usize>,
+    activation: Activation,
+    initializer: Option<Initializer>,
+}
+
+impl MlpConfig {
+    /// Configure a network with the layer sizes `sizes`, from the input size to the output size
+    ///
+    /// **Default:** [`Activation::Relu`] and burn's default initialization of [`Linear`] layers
+    ///
+    /// **Panics** if there are less than two sizes
+    pub fn new(sizes: &[usize]) -> Self {
+        assert!(
+            sizes.len() >= 2,
+            "An MLP needs at least an input and an output size"
+        );
+        Self {
+            sizes: sizes.to_vec(),
+            activation: Activation::default(),
+            initializer: None,
+        }
+    }
+
+    /// Set the activation function after each hidden layer
+    pub fn with_activation(mut self, activation: Activation) -> Self {
+        self.activation = activation;
+        self
+    }
+
+    /// Set the initialization of the weights and biases of every layer
+    pub fn with_initializer(mut self, initializer: Initializer) -> Self {
+        self.initializer = Some(initializer);
+        self
+    }
+
+    /// Build the network on `device`
+    pub fn init<B: Backend>(&self, device: &B::Device) -> Mlp<B> {
+        let layers = self
+            .sizes
+            .windows(2)
+            .map(|sizes| linear(sizes[0], sizes[1], &self.initializer, device))
+            .collect();
+        Mlp {
+            layers,
+            activation: Ignored(self.activation),
+        }
+    }
+}
+
+/// A fully connected network, mapping inputs of shape `[batch, in]` to outputs of shape `[batch, out]`
+///
+/// ```ignore
+/// // Q values of the 2 actions of CartPole from its 4 observations
+/// let net = Mlp::<B>::new(&[4, 128, 128, 2], &device);
+/// let agent = DQNAgent::new(net, config, &device)?;
+/// ```
+#[derive(Module, Debug)]
+pub struct Mlp<B: Backend> {
+    layers: Vec<Linear<B>>,
+    activation: Ignored<Activation>,
+}
+
+impl<B: Backend> Mlp<B> {
+    /// Build a network with the layer sizes `sizes` and the defaults of [`MlpConfig`]
+    ///
+    /// **Panics** if there are less than two sizes
+    pub fn new(sizes: &[usize], device: &B::Device) -> Self {
+        MlpConfig::new(sizes).init(device)
+    }
+
+    /// Forward pass through the network
+    pub fn forward(&self, input: Tensor<B, 2>) -> Tensor<B, 2> {
+        let last = self.layers.len() - 1;
+        self.layers
+            .iter()
+            .enumerate()
+            .fold(input, |x, (i, layer)| match i < last {
+                true => self.activation.apply(layer.forward(x)),
+                false => layer.forward(x),
+            })
+    }
+}
+
+impl<B: Backend> PolicyNet<B, 2> for Mlp<B> {
+    fn forward(&self, input: Tensor<B, 2>) -> Tensor<B, 2> {
+        Mlp::forward(self, input)
+    }
+}
+
+impl<B: AutodiffBackend> DQNModel<B, 2> for Mlp<B> {
+    fn forward(&self, input: Tensor<B, 2>) -> Tensor<B, 2> {
+        Mlp::forward(self, input)
+    }
+}
+
+/// A convolutional layer of a [`Cnn`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ConvLayer {
+    channels: usize,
+    kernel: usize,
+    stride: usize,
+}
+
+/// Configuration for a [`Cnn`]
+#[derive(Debug, Clone)]
+pub struct CnnConfig {
+    input: [usize; 3],
+    outputs: usize,
+    convs: Vec<ConvLayer>,
+    hidden: Vec<usize>,
+    activation: Activation,
+    initializer: Option<Initializer>,
+}
+
+impl CnnConfig {
+    /// Configure a network for inputs of shape `[channels, height, width]` with `outputs` outputs
+    ///
+    /// **Default:** no convolutions or hidden layers, [`Activation::Relu`] and burn's default initialization
+    pub fn new(input: [usize; 3], outputs: usize) -> Self {
+        Self {
+            input,
+            outputs,
+            convs: Vec::new(),
+            hidden: Vec::new(),
+            activation: Activation::default(),
+            initializer: None,
+        }
+    }
+
+    /// Add a convolution with `channels` output channels and a square kernel after the existing ones
+    ///
+    /// Convolutions are unpadded, so each one shrinks the height and width to `(size - kernel) / stride + 1`.
+    ///
+    /// **Panics** if the stride is 0
+    pub fn with_conv(mut self, channels: usize, kernel: usize, stride: usize) -> Self {
+        assert!(stride > 0, "Stride must be positive");
+        self.convs.push(ConvLayer {
+            channels,
+            kernel,
+            stride,
+        });
+        self
+    }
+
+    /// Set the sizes of the hidden layers of the fully connected head after the convolutions
+    pub fn with_hidden(mut self, sizes: &[usize]) -> Self {
+        self.hidden = sizes.to_vec();
+        self
+    }
+
+    /// Set the activation function after each convolution and hidden layer
+    pub fn with_activation(mut self, activation: Activation) -> Self {
+        self.activation = activation;
+        self
+    }
+
+    /// Set the initialization of the weights and biases of every layer
+    pub fn with_initializer(mut self, initializer: Initializer) -> Self {
+        self.initializer = Some(initializer);
+        self
+    }
+
+    /// Build the network on `device`
+    ///
+    /// **Panics** if a kernel is larger than its input
+    pub fn init<B: Backend>(&self, device: &B::Device) -> Cnn<B> {
+        let [mut channels, mut height, mut width] = self.input;
+        let mut convs = Vec::with_capacity(self.convs.len());
+        for conv in &self.convs {
+            assert!(
+                conv.kernel <= height && conv.kernel <= width,
+                "Kernel of size {} is larger than its {height}x{width} input",
+                conv.kernel
+            );
+            let mut config = Conv2dConfig::new([channels, conv.channels], [conv.kernel; 2])
+                .with_stride([conv.stride; 2]);
+            if let Some(initializer) = &self.initializer {
+                config = config.with_initializer(initializer.clone());
+            }
+            convs.push(config.init(device));
+            channels = conv.channels;
+            height = (height - conv.kernel) / conv.stride + 1;
+            width = (width - conv.kernel) / conv.stride + 1;
+        }
+
+        let mut sizes = vec![channels * height * width];
+        sizes.extend(&self.hidden);
+        sizes.push(self.outputs);
+        let mut head = MlpConfig::new(&sizes).with_activation(self.activation);
+        head.initializer = self.initializer.clone();
+
+        Cnn {
+            convs,
+            head: head.init(device),
+            activation: Ignored(self.activation),
+        }
+    }
+}
+
+/// A convolutional network with a fully connected head, mapping inputs of shape `[batch, channels, height, width]`
+/// to outputs of shape `[batch, outputs]`
+///
+/// Image batches converted with [`ToTensor`](crate::traits::ToTensor) have the channels last, so they are permuted
+/// with `permute([0, 3, 1, 2])` first.
+///
+/// ```ignore
+/// // The convolutions of the Atari DQN for 4 stacked 84x84 frames
+/// let net = CnnConfig::new([4, 84, 84], actions)
+///     .with_conv(32, 8, 4)
+///     .with_conv(64, 4, 2)
+///     .with_conv(64, 3, 1)
+///     .with_hidden(&[512])
+///     .init::<B>(&device);
+/// ```
+#[derive(Module, Debug)]
+pub struct Cnn<B: Backend> {
+    convs: Vec<Conv2d<B>>,
+    head: Mlp<B>,
+    activation: Ignored<Activation>,
+}
+
+impl<B: Backend> Cnn<B> {
+    /// Forward pass through the network
+    pub fn forward(&self, input: Tensor<B, 4>) -> Tensor<B, 2> {
+        let x = self
+            .convs
+            .iter()
+            .fold(input, |x, conv| self.activation.apply(conv.forward(x)));
+        self.head.forward(x.flatten(1, 3))
+    }
+}
+
+impl<B: Backend> PolicyNet<B, 4> for Cnn<B> {
+    fn forward(&self, input: Tensor<B, 4>) -> Tensor<B, 2> {
+        Cnn::forward(self, input)
+    }
+}
+
+impl<B: AutodiffBackend> DQNModel<B, 4> for Cnn<B> {
+    fn forward(&self, input: Tensor<B, 4>) -> Tensor<B, 2> {
+        Cnn::forward(self, input)
+    }
+}
+
+/// A linear layer from `input` to `output` features, with `initializer` if set
+fn linear<B: Backend>(
+    input: usize,
+    output: usize,
+    initializer: &Option<Initializer>,
+    device: &B::Device,
+) -> Linear<B> {
+    let mut config = LinearConfig::new(input, output);
+    if let Some(initializer) = initializer {
+        config = config.with_initializer(initializer.clone());
+    }
+    config.init(device)
+}
+
+#[cfg(test)]
+mod tests {
+    use burn::backend::{ndarray::NdArrayDevice, NdArray as B};
+
+    use super::*;
+
+    #[test]
+    fn mlp() {
+        let device = NdArrayDevice::Cpu;
+        let net = MlpConfig::new(&[4, 8, 8, 2])
+            .with_activation(Activation::Tanh)
+            .init::<B>(&device);
+        assert_eq!(net.layers.len(), 3);
+        let output = net.forward(Tensor::zeros([5, 4], &device));
+        assert_eq!(output.dims(), [5, 2]);
+
+        let net = MlpConfig::new(&[3, 2])
+            .with_initializer(Initializer::Constant { value: 0.5 })
+            .init::<B>(&device);
+        let output = net.forward(Tensor::ones([1, 3], &device));
+        assert_eq!(
+            output.into_data().value,
+            [2.0, 2.0],
+            "No activation after the last layer"
+        );
+    }
+
+    #[test]
+    fn cnn() {
+        let device = NdArrayDevice::Cpu;
+        let net = CnnConfig::new([2, 12, 10], 3)
+            .with_conv(4, 4, 2)
+            .with_conv(8, 3, 1)
+            .with_hidden(&[16])
+            .init::<B>(&device);
+        assert_eq!(net.head.layers[0].weight.dims(), [8 * 3 * 2, 16]);
+        let output = net.forward(Tensor::zeros([5, 2, 12, 10], &device));
+        assert_eq!(output.dims(), [5, 3]);
+    }
+}
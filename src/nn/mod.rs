@@ -0,0 +1,298 @@
+/// Builders for common network architectures
+pub mod builders;
+/// Recurrent networks that carry a hidden state through sequences of observations
+pub mod recurrent;
+
+use std::collections::HashMap;
+
+use burn::{
+    module::{AutodiffModule, Module, ModuleMapper, ModuleVisitor, ParamId},
+    optim::GradientsParams,
+    prelude::*,
+    tensor::{backend::AutodiffBackend, ElementConversion},
+};
+
+use crate::error::{check_interval, Result, RlError};
+
+/// How a [`TargetNetwork`] follows its online network
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TargetUpdate {
+    /// Copy the parameters of the online network every `interval` steps
+    Hard { interval: u64 },
+    /// Move the parameters towards those of the online network every `interval` steps with Polyak averaging
+    ///
+    /// θ′ ← τθ + (1 − τ)θ′
+    Soft { tau: f32, interval: u64 },
+}
+
+/// A slowly updated copy of an online network, used to compute stable bootstrapped targets
+///
+/// Value-based and actor-critic agents keep one per network they bootstrap from. The agent calls
+/// [`step`](TargetNetwork::step) after each learning step and the target network is updated according to its
+/// [`TargetUpdate`]. Parameters are matched by their ids, so the target network must be created from the online
+/// network it follows, which works for any burn [`Module`].
+///
+/// ```ignore
+/// let mut target = TargetNetwork::new(&policy_net, TargetUpdate::Soft { tau: 5e-3, interval: 1 })?;
+/// let next_q_values = target.net().forward(next_states).detach();
+/// // ...optimizer step on the policy net...
+/// target.step(&policy_net);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TargetNetwork<M> {
+    net: M,
+    update: TargetUpdate,
+    steps: u64,
+}
+
+impl<M> TargetNetwork<M> {
+    /// Create a target network as a copy of `online`
+    ///
+    /// **Returns** an [`RlError`] if the interval is 0 or `tau` is not in the interval `[0, 1]`
+    pub fn new(online: &M, update: TargetUpdate) -> Result<Self>
+    where
+        M: Clone,
+    {
+        let interval = match update {
+            TargetUpdate::Hard { interval } => interval,
+            TargetUpdate::Soft { tau, interval } => {
+                check_interval("tau", tau, 0.0, 1.0)?;
+                interval
+            }
+        };
+        if interval == 0 {
+            return Err(RlError::InvalidHyperparameters(String::from(
+                "the target update interval must be positive",
+            )));
+        }
+
+        Ok(Self {
+            net: online.clone(),
+            update,
+            steps: 0,
+        })
+    }
+
+    /// The target network
+    pub fn net(&self) -> &M {
+        &self.net
+    }
+
+    /// The target network, e.g. to load it from a checkpoint
+    pub fn net_mut(&mut self) -> &mut M {
+        &mut self.net
+    }
+
+    /// How the target network is updated
+    pub fn update(&self) -> TargetUpdate {
+        self.update
+    }
+
+    /// The number of steps counted so far
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Set the number of steps counted so far, e.g. when restoring an agent from a checkpoint
+    pub fn set_steps(&mut self, steps: u64) {
+        self.steps = steps;
+    }
+
+    /// Count a learning step of `online` and update the target network if the interval has elapsed
+    ///
+    /// **Returns** whether the target network was updated
+    pub fn step<B: Backend>(&mut self, online: &M) -> bool
+    where
+        M: Module<B>,
+    {
+        self.steps += 1;
+        match self.update {
+            TargetUpdate::Hard { interval } if self.steps % interval == 0 => {
+                self.hard_update(online);
+                true
+            }
+            TargetUpdate::Soft { tau, interval } if self.steps % interval == 0 => {
+                self.soft_update::<B>(online, tau);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Replace the target network with a copy of `online`
+    pub fn hard_update(&mut self, online: &M)
+    where
+        M: Clone,
+    {
+        self.net = online.clone();
+    }
+
+    /// Move every parameter of the target network towards the matching parameter of `online`
+    ///
+    /// θ′ ← τθ + (1 − τ)θ′
+    pub fn soft_update<B: Backend>(&mut self, online: &M, tau: f32)
+    where
+        M: Module<B>,
+    {
+        let mut params = Params(HashMap::new());
+        online.visit(&mut params);
+        self.net = self.net.clone().map(&mut Polyak {
+            online: params.0,
+            tau,
+        });
+    }
+}
+
+/// Collects the float parameters of a module by id, flattened
+struct Params<B: Backend>(HashMap<ParamId, Tensor<B, 1>>);
+
+impl<B: Backend> ModuleVisitor<B> for Params<B> {
+    fn visit_float<const D: usize>(&mut self, id: &ParamId, tensor: &Tensor<B, D>) {
+        let len = tensor.shape().num_elements();
+        self.0.insert(id.clone(), tensor.clone().reshape([len]));
+    }
+}
+
+/// Averages the float parameters of a module with those of the online network
+struct Polyak<B: Backend> {
+    online: HashMap<ParamId, Tensor<B, 1>>,
+    tau: f32,
+}
+
+impl<B: Backend> ModuleMapper<B> for Polyak<B> {
+    fn map_float<const D: usize>(&mut self, id: &ParamId, tensor: Tensor<B, D>) -> Tensor<B, D> {
+        match self.online.remove(id) {
+            Some(online) => {
+                let online = online.reshape(tensor.shape());
+                // Detached so the target network doesn't grow an autodiff graph over the updates
+                (tensor * (1.0 - self.tau) + online * self.tau).detach()
+            }
+            None => tensor,
+        }
+    }
+}
+
+/// The global L2 norm of the gradients of `module`'s float parameters, e.g. to diagnose exploding gradients
+///
+/// Parameters without a gradient are skipped.
+pub fn grad_norm<B: AutodiffBackend, M: AutodiffModule<B>>(
+    module: &M,
+    grads: &GradientsParams,
+) -> f32 {
+    let mut norm = GradNorm { grads, sum: 0.0 };
+    module.visit(&mut norm);
+    norm.sum.sqrt()
+}
+
+/// The global L2 norm of `module`'s float parameters, e.g. to diagnose diverging weights
+pub fn param_norm<B: Backend, M: Module<B>>(module: &M) -> f32 {
+    let mut norm = ParamNorm { sum: 0.0 };
+    module.visit(&mut norm);
+    norm.sum.sqrt()
+}
+
+/// Sums the squared gradients of the visited parameters
+struct GradNorm<'a> {
+    grads: &'a GradientsParams,
+    sum: f32,
+}
+
+impl<B: AutodiffBackend> ModuleVisitor<B> for GradNorm<'_> {
+    fn visit_float<const D: usize>(&mut self, id: &ParamId, _tensor: &Tensor<B, D>) {
+        if let Some(grad) = self.grads.get::<B::InnerBackend, D>(id) {
+            self.sum += grad.powf_scalar(2.0).sum().into_scalar().elem::<f32>();
+        }
+    }
+}
+
+/// Sums the squares of the visited parameters
+struct ParamNorm {
+    sum: f32,
+}
+
+impl<B: Backend> ModuleVisitor<B> for ParamNorm {
+    fn visit_float<const D: usize>(&mut self, _id: &ParamId, tensor: &Tensor<B, D>) {
+        let squares = tensor.clone().detach().powf_scalar(2.0);
+        self.sum += squares.sum().into_scalar().elem::<f32>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use burn::{
+        backend::{ndarray::NdArrayDevice, Autodiff, NdArray as B},
+        nn::{Linear, LinearConfig},
+    };
+
+    use super::*;
+
+    fn weights(net: &Linear<B>) -> Vec<f32> {
+        net.weight.val().into_data().value
+    }
+
+    fn shifted(net: &Linear<B>, delta: f32) -> Linear<B> {
+        let mut net = net.clone();
+        net.weight = net.weight.map(|w| w + delta);
+        net
+    }
+
+    #[test]
+    fn soft_update() {
+        let online = LinearConfig::new(3, 2).init::<B>(&NdArrayDevice::Cpu);
+        let update = TargetUpdate::Soft {
+            tau: 0.25,
+            interval: 2,
+        };
+        let mut target = TargetNetwork::new(&online, update).unwrap();
+
+        let online = shifted(&online, 1.0);
+        assert!(!target.step(&online), "Updates every second step");
+        assert!(target.step(&online));
+
+        for (t, o) in weights(target.net()).iter().zip(weights(&online)) {
+            assert!((t - (o - 0.75)).abs() < 1e-6, "θ′ ← τθ + (1 − τ)θ′");
+        }
+    }
+
+    #[test]
+    fn hard_update() {
+        let online = LinearConfig::new(3, 2).init::<B>(&NdArrayDevice::Cpu);
+        let mut target = TargetNetwork::new(&online, TargetUpdate::Hard { interval: 3 }).unwrap();
+
+        let online = shifted(&online, 1.0);
+        target.step(&online);
+        target.step(&online);
+        assert_ne!(weights(target.net()), weights(&online));
+        assert!(target.step(&online));
+        assert_eq!(weights(target.net()), weights(&online));
+        assert_eq!(target.steps(), 3);
+    }
+
+    #[test]
+    fn invalid_updates() {
+        let online = LinearConfig::new(3, 2).init::<B>(&NdArrayDevice::Cpu);
+        assert!(TargetNetwork::new(&online, TargetUpdate::Hard { interval: 0 }).is_err());
+        let update = TargetUpdate::Soft {
+            tau: 1.5,
+            interval: 1,
+        };
+        assert!(TargetNetwork::new(&online, update).is_err());
+    }
+
+    #[test]
+    fn norms() {
+        let device = NdArrayDevice::Cpu;
+        let net = LinearConfig::new(3, 2).init::<Autodiff<B>>(&device);
+        let mut fixed = net.clone();
+        fixed.weight = fixed.weight.map(|w| w.zeros_like() + 1.0);
+        fixed.bias = fixed.bias.map(|b| b.map(|b| b.zeros_like()));
+        assert!((param_norm(&fixed) - 6f32.sqrt()).abs() < 1e-6);
+
+        // The gradient of the summed output is the input for every weight and 1 for every bias
+        let input = Tensor::<Autodiff<B>, 2>::ones([1, 3], &device);
+        let loss = net.forward(input).sum();
+        let grads = GradientsParams::from_grads(loss.backward(), &net);
+        assert!((grad_norm(&net, &grads) - 8f32.sqrt()).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,607 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    env::{DiscreteActionSpace, Environment},
+    error::{check_interval, Result, RlError},
+    stats::RunningMeanVar,
+    traits::Checkpoint,
+};
+
+/// Whether a [`Normalizer`] updates its statistics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Update the statistics with every observation before normalizing it
+    #[default]
+    Train,
+    /// Normalize with frozen statistics, so evaluations don't shift them
+    Eval,
+}
+
+/// Running mean and variance of observations, used to normalize them to zero mean and unit variance
+///
+/// Clones share their statistics, so the environment wrapper that normalizes observations and the
+/// [`Trainer`](crate::train::Trainer) that saves them with checkpoints and switches the [`Mode`] for evaluations
+/// hold the same normalizer, see [`with_normalizer`](crate::train::Trainer::with_normalizer). Statistics are kept
+/// per dimension with Welford's algorithm and the dimension is fixed by the first observation.
+///
+/// ```ignore
+/// let normalizer = Normalizer::new();
+/// let env = MyEnv::new(normalizer.clone());
+/// // In `MyEnv::step` and `MyEnv::reset`
+/// self.normalizer.normalize(&mut observation);
+/// let trainer = Trainer::new(env, agent).with_normalizer(normalizer);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Normalizer {
+    stats: Arc<Mutex<Stats>>,
+    clip: f32,
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    mode: Mode,
+    dims: Vec<RunningMeanVar>,
+}
+
+impl Normalizer {
+    /// Added to the variance to avoid dividing by zero
+    const EPSILON: f64 = 1e-8;
+
+    /// Create a normalizer without statistics in [`Mode::Train`]
+    ///
+    /// **Default:** normalized values are clipped to `[-10, 10]`
+    pub fn new() -> Self {
+        Self {
+            stats: Arc::default(),
+            clip: 10.0,
+        }
+    }
+
+    /// Clip normalized values to `[-clip, clip]`
+    ///
+    /// **Returns** an [`RlError`] if `clip` is not positive
+    pub fn with_clip(mut self, clip: f32) -> Result<Self> {
+        check_clip(clip)?;
+        self.clip = clip;
+        Ok(self)
+    }
+
+    /// Normalize `observation` in place, after adding it to the statistics in [`Mode::Train`]
+    ///
+    /// **Panics** if the dimension of `observation` differs from the first observation
+    pub fn normalize(&self, observation: &mut [f32]) {
+        let mut stats = self.stats.lock().unwrap();
+        if stats.dims.is_empty() {
+            stats.dims = vec![RunningMeanVar::new(); observation.len()];
+        }
+        assert_eq!(
+            observation.len(),
+            stats.dims.len(),
+            "Observation dimension changed"
+        );
+
+        if stats.mode == Mode::Train {
+            for (x, dim) in observation.iter().zip(&mut stats.dims) {
+                dim.push(*x as f64);
+            }
+        }
+
+        for (x, dim) in observation.iter_mut().zip(&stats.dims) {
+            let z = (*x as f64 - dim.mean()) / scale(dim);
+            *x = (z as f32).clamp(-self.clip, self.clip);
+        }
+    }
+
+    /// Whether the statistics are updated
+    pub fn mode(&self) -> Mode {
+        self.stats.lock().unwrap().mode
+    }
+
+    /// Set whether the statistics are updated, for every clone of this normalizer
+    pub fn set_mode(&self, mode: Mode) {
+        self.stats.lock().unwrap().mode = mode;
+    }
+
+    /// The number of observations in the statistics
+    pub fn count(&self) -> u64 {
+        let stats = self.stats.lock().unwrap();
+        stats.dims.first().map_or(0, RunningMeanVar::count)
+    }
+
+    /// The mean of each dimension, empty before the first observation
+    pub fn mean(&self) -> Vec<f64> {
+        let stats = self.stats.lock().unwrap();
+        stats.dims.iter().map(RunningMeanVar::mean).collect()
+    }
+
+    /// The variance of each dimension, empty before the first observation
+    pub fn var(&self) -> Vec<f64> {
+        let stats = self.stats.lock().unwrap();
+        stats.dims.iter().map(RunningMeanVar::var).collect()
+    }
+
+    /// The path the statistics saved with the checkpoint at `path` are written to, `<path>.normalizer`
+    pub(crate) fn path(path: &Path) -> PathBuf {
+        let mut path = path.as_os_str().to_owned();
+        path.push(".normalizer");
+        path.into()
+    }
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The standard deviation values are divided by, which is never zero
+fn scale(stats: &RunningMeanVar) -> f64 {
+    (stats.var() + Normalizer::EPSILON).sqrt()
+}
+
+/// The statistics are saved as a text file of `count`, `mean` and `var` lines
+///
+/// The mode is not saved, loading keeps the current one.
+impl Checkpoint for Normalizer {
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let join = |values: Vec<f64>| {
+            values
+                .iter()
+                .map(f64::to_string)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let contents = format!(
+            "count {}\nmean {}\nvar {}\n",
+            self.count(),
+            join(self.mean()),
+            join(self.var())
+        );
+        fs::write(path, contents)
+    }
+
+    fn load(&mut self, path: &Path) -> io::Result<()> {
+        let contents = fs::read_to_string(path)?;
+        let invalid = |line: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid normalizer entry `{line}`"),
+            )
+        };
+        let mut count = None;
+        let mut mean = None;
+        let mut var = None;
+        for line in contents.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let values = || {
+                value
+                    .split_whitespace()
+                    .map(str::parse)
+                    .collect::<Result<Vec<f64>, _>>()
+                    .map_err(|_| invalid(line))
+            };
+            match key {
+                "count" => count = Some(value.parse::<u64>().map_err(|_| invalid(line))?),
+                "mean" => mean = Some(values()?),
+                "var" => var = Some(values()?),
+                _ => return Err(invalid(line)),
+            }
+        }
+
+        let (Some(count), Some(mean), Some(var)) = (count, mean, var) else {
+            return Err(invalid(&contents));
+        };
+        if mean.len() != var.len() {
+            return Err(invalid(&contents));
+        }
+        let mut stats = self.stats.lock().unwrap();
+        stats.dims = mean
+            .into_iter()
+            .zip(var)
+            .map(|(mean, var)| RunningMeanVar::from_parts(count, mean, var))
+            .collect();
+        Ok(())
+    }
+}
+
+/// An [`Environment`] that divides the rewards of `E` by a running estimate of the standard deviation of the
+/// discounted return
+///
+/// The rewards are not centered, since shifting them would change which policies are optimal. Scaling them to a
+/// return of roughly unit variance keeps value targets and gradients in the same range across environments, which
+/// stabilizes training on environments with large reward magnitudes. The rewards the agent sees, and so the returns
+/// the [`Trainer`](crate::train::Trainer) logs, are scaled. The unscaled return of the current episode is kept in
+/// [`episode_return`](RewardNormalize::episode_return). Evaluations should report unscaled returns, so their
+/// environment is wrapped with [`unscaled`](RewardNormalize::unscaled), see
+/// [`with_eval`](crate::train::Trainer::with_eval).
+///
+/// ```
+/// use rl::{baselines::RandomAgent, normalize::RewardNormalize, testing::Corridor, train::Trainer};
+///
+/// let env = RewardNormalize::new(Corridor::new(5)).with_gamma(0.99)?;
+/// let eval_env = RewardNormalize::unscaled(Corridor::new(5));
+/// let trainer = Trainer::new(env, RandomAgent).with_eval(10, 5, eval_env);
+/// # Ok::<(), rl::error::RlError>(())
+/// ```
+///
+/// ### Generics
+/// - `E` - The wrapped [`Environment`]
+#[derive(Debug, Clone)]
+pub struct RewardNormalize<E> {
+    env: E,
+    mode: Mode,
+    scaled: bool,
+    gamma: f64,
+    clip: f32,
+    stat: RunningMeanVar,
+    discounted: f64,
+    episode_return: f64,
+}
+
+impl<E: Environment> RewardNormalize<E> {
+    /// Wrap `env` without statistics in [`Mode::Train`]
+    ///
+    /// **Default:** the return is discounted by `0.99` and scaled rewards are clipped to `[-10, 10]`
+    pub fn new(env: E) -> Self {
+        Self {
+            env,
+            mode: Mode::Train,
+            scaled: true,
+            gamma: 0.99,
+            clip: 10.0,
+            stat: RunningMeanVar::new(),
+            discounted: 0.0,
+            episode_return: 0.0,
+        }
+    }
+
+    /// Wrap `env` without changing its rewards or collecting statistics, e.g. for the evaluation environment of a
+    /// training environment wrapped with [`new`](RewardNormalize::new)
+    pub fn unscaled(env: E) -> Self {
+        Self {
+            scaled: false,
+            ..Self::new(env)
+        }
+    }
+
+    /// Discount the return whose standard deviation scales the rewards by `gamma`, usually the discount factor of
+    /// the agent
+    ///
+    /// **Returns** an [`RlError`] if `gamma` is not in `[0, 1]`
+    pub fn with_gamma(mut self, gamma: f32) -> Result<Self> {
+        check_interval("gamma", gamma, 0.0, 1.0)?;
+        self.gamma = gamma as f64;
+        Ok(self)
+    }
+
+    /// Clip scaled rewards to `[-clip, clip]`
+    ///
+    /// **Returns** an [`RlError`] if `clip` is not positive
+    pub fn with_clip(mut self, clip: f32) -> Result<Self> {
+        check_clip(clip)?;
+        self.clip = clip;
+        Ok(self)
+    }
+
+    /// Whether the statistics are updated
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Set whether the statistics are updated
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+    }
+
+    /// The number of steps in the statistics
+    pub fn count(&self) -> u64 {
+        self.stat.count()
+    }
+
+    /// The current estimate of the standard deviation of the discounted return, which rewards are divided by
+    pub fn return_std(&self) -> f64 {
+        scale(&self.stat)
+    }
+
+    /// The sum of the unscaled rewards of the current episode
+    pub fn episode_return(&self) -> f64 {
+        self.episode_return
+    }
+
+    /// The wrapped environment
+    pub fn inner(&self) -> &E {
+        &self.env
+    }
+
+    /// Unwrap the environment
+    pub fn into_inner(self) -> E {
+        self.env
+    }
+}
+
+impl<E: Environment> Environment for RewardNormalize<E> {
+    type State = E::State;
+    type Action = E::Action;
+
+    fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
+        let (next_state, reward) = self.env.step(action);
+        self.episode_return += reward as f64;
+        if !self.scaled {
+            return (next_state, reward);
+        }
+        if self.mode == Mode::Train {
+            self.discounted = self.gamma * self.discounted + reward as f64;
+            self.stat.push(self.discounted);
+        }
+        let scaled = (reward as f64 / scale(&self.stat)) as f32;
+        (next_state, scaled.clamp(-self.clip, self.clip))
+    }
+
+    fn reset(&mut self) -> Self::State {
+        self.discounted = 0.0;
+        self.episode_return = 0.0;
+        self.env.reset()
+    }
+
+    fn random_action(&self) -> Self::Action {
+        self.env.random_action()
+    }
+
+    fn is_active(&self) -> bool {
+        self.env.is_active()
+    }
+
+    fn discount(&self) -> Option<f32> {
+        self.env.discount()
+    }
+}
+
+impl<E: DiscreteActionSpace> DiscreteActionSpace for RewardNormalize<E> {
+    fn actions(&self) -> Vec<Self::Action> {
+        self.env.actions()
+    }
+}
+
+/// Check that the bound `clip` of clipped values is positive
+fn check_clip(clip: f32) -> Result<()> {
+    match clip > 0.0 {
+        true => Ok(()),
+        false => Err(RlError::InvalidHyperparameters(format!(
+            "`clip` must be positive, got {clip}"
+        ))),
+    }
+}
+
+/// Adaptively rescaled value targets, as in PopArt ("Preserving Outputs Precisely, while Adaptively Rescaling
+/// Targets", van Hasselt et al. 2016)
+///
+/// A value network predicts targets normalized with running statistics instead of the raw targets, whose scale can
+/// change by orders of magnitude during training. Whenever the statistics are updated, the last linear layer of the
+/// network is rescaled with the returned [`Rescale`], so its unnormalized predictions are preserved exactly.
+///
+/// ```ignore
+/// let rescale = popart.update(&targets);
+/// // The parameters of the value network's last linear layer, written back to the network afterwards
+/// rescale.apply(&mut head_weights, &mut head_biases);
+/// let loss = mse(model.forward(states), popart.normalize_all(&targets));
+/// let value = popart.denormalize(prediction);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PopArt {
+    stat: RunningMeanVar,
+}
+
+/// How to update the output layer `y = w·x + b` of a network after the statistics of a [`PopArt`] changed:
+/// `w' = scale·w` and `b' = scale·b + shift`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rescale {
+    /// The old standard deviation over the new one
+    pub scale: f32,
+    /// The difference of the old and new mean over the new standard deviation
+    pub shift: f32,
+}
+
+impl Rescale {
+    /// Rescale the weights and biases of an output layer in place
+    pub fn apply(&self, weights: &mut [f32], biases: &mut [f32]) {
+        weights.iter_mut().for_each(|w| *w *= self.scale);
+        biases
+            .iter_mut()
+            .for_each(|b| *b = *b * self.scale + self.shift);
+    }
+}
+
+impl PopArt {
+    /// Create a normalizer without statistics, which leaves targets unchanged
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a batch of targets to the statistics
+    ///
+    /// **Returns** the [`Rescale`] of the output layer that preserves its unnormalized outputs
+    pub fn update(&mut self, targets: &[f32]) -> Rescale {
+        let (old_mean, old_std) = (self.mean(), self.std());
+        for &target in targets {
+            self.stat.push(target as f64);
+        }
+        let (mean, std) = (self.mean(), self.std());
+        Rescale {
+            scale: (old_std / std) as f32,
+            shift: ((old_mean - mean) / std) as f32,
+        }
+    }
+
+    /// Normalize a target with the current statistics
+    pub fn normalize(&self, target: f32) -> f32 {
+        ((target as f64 - self.mean()) / self.std()) as f32
+    }
+
+    /// Map a normalized prediction back to the scale of the targets
+    pub fn denormalize(&self, prediction: f32) -> f32 {
+        (prediction as f64 * self.std() + self.mean()) as f32
+    }
+
+    /// Normalize a batch of targets, see [`normalize`](PopArt::normalize)
+    pub fn normalize_all(&self, targets: &[f32]) -> Vec<f32> {
+        targets.iter().map(|&t| self.normalize(t)).collect()
+    }
+
+    /// The mean of the targets, `0` before the first update
+    pub fn mean(&self) -> f64 {
+        self.stat.mean()
+    }
+
+    /// The standard deviation of the targets, `1` before the first update
+    pub fn std(&self) -> f64 {
+        match self.stat.count() {
+            0 => 1.0,
+            _ => scale(&self.stat),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, b) in actual.iter().zip(expected) {
+            assert!((a - b).abs() < 1e-9, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn running_stats() {
+        let normalizer = Normalizer::new();
+        for x in [1.0, 2.0, 3.0, 6.0] {
+            normalizer.normalize(&mut [x, -x]);
+        }
+        assert_eq!(normalizer.count(), 4);
+        assert_close(&normalizer.mean(), &[3.0, -3.0]);
+        assert_close(&normalizer.var(), &[3.5, 3.5]);
+
+        assert!(normalizer.clone().with_clip(0.0).is_err());
+        let clipped = normalizer.clone().with_clip(5.0).unwrap();
+        clipped.set_mode(Mode::Eval);
+        assert_eq!(normalizer.mode(), Mode::Eval, "Clones share statistics");
+        let mut observation = [3.0 + 3.5f32.sqrt(), 100.0];
+        clipped.normalize(&mut observation);
+        assert!((observation[0] - 1.0).abs() < 1e-4);
+        assert_eq!(observation[1], 5.0, "Normalized values are clipped");
+    }
+
+    #[test]
+    fn eval_mode() {
+        let normalizer = Normalizer::new();
+        normalizer.normalize(&mut [1.0]);
+        normalizer.normalize(&mut [3.0]);
+
+        normalizer.set_mode(Mode::Eval);
+        let mut observation = [3.0];
+        normalizer.normalize(&mut observation);
+        assert!((observation[0] - 1.0).abs() < 1e-4);
+        assert_eq!(normalizer.count(), 2, "Statistics are frozen");
+        assert_eq!(normalizer.mean(), [2.0]);
+    }
+
+    #[test]
+    fn save_load() {
+        let path = std::env::temp_dir().join("rl_normalizer");
+        let normalizer = Normalizer::new();
+        for x in [0.1, 0.7, -2.3] {
+            normalizer.normalize(&mut [x, x * x]);
+        }
+        normalizer.save(&path).unwrap();
+
+        let mut loaded = Normalizer::new();
+        loaded.set_mode(Mode::Eval);
+        loaded.load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.count(), 3);
+        assert_eq!(loaded.mean(), normalizer.mean());
+        assert_close(&loaded.var(), &normalizer.var());
+        assert_eq!(loaded.mode(), Mode::Eval, "Loading keeps the mode");
+    }
+
+    /// Rewards of 100 every step until the tenth
+    struct Large(u8);
+
+    impl Environment for Large {
+        type State = u8;
+        type Action = ();
+
+        fn step(&mut self, _: Self::Action) -> (Option<Self::State>, f32) {
+            self.0 += 1;
+            ((self.0 < 10).then_some(self.0), 100.0)
+        }
+
+        fn reset(&mut self) -> Self::State {
+            self.0 = 0;
+            0
+        }
+
+        fn random_action(&self) -> Self::Action {}
+    }
+
+    #[test]
+    fn reward_normalize() {
+        assert!(RewardNormalize::new(Large(0)).with_gamma(1.5).is_err());
+        assert!(RewardNormalize::new(Large(0)).with_clip(f32::NAN).is_err());
+        let mut env = RewardNormalize::new(Large(0)).with_gamma(0.9).unwrap();
+        let mut rewards = Vec::new();
+        for _ in 0..20 {
+            let mut next_state = Some(env.reset());
+            while next_state.is_some() {
+                let (next, reward) = env.step(());
+                rewards.push(reward);
+                next_state = next;
+            }
+        }
+        assert_eq!(env.count(), 200);
+        assert_eq!(
+            env.episode_return(),
+            1000.0,
+            "The episode return is unscaled"
+        );
+        let last = *rewards.last().unwrap();
+        assert!((last as f64 - 100.0 / env.return_std()).abs() < 1e-4);
+        assert!(last < 1.0, "Rewards are scaled down, {last}");
+
+        env.set_mode(Mode::Eval);
+        env.reset();
+        env.step(());
+        assert_eq!(env.count(), 200, "Statistics are frozen");
+        assert_eq!(env.into_inner().0, 1);
+
+        let mut eval_env = RewardNormalize::unscaled(Large(0));
+        eval_env.reset();
+        assert_eq!(eval_env.step(()).1, 100.0, "Rewards are unchanged");
+        assert_eq!(eval_env.count(), 0);
+    }
+
+    #[test]
+    fn popart_preserves_outputs() {
+        let mut popart = PopArt::new();
+        assert_eq!(popart.normalize(5.0), 5.0);
+        popart.update(&[1.0, 3.0]);
+
+        // A linear output layer with one input
+        let (mut weights, mut biases) = (vec![0.5], vec![0.25]);
+        let x = 2.0;
+        let before = popart.denormalize(weights[0] * x + biases[0]);
+
+        let rescale = popart.update(&[100.0, 300.0, -50.0]);
+        rescale.apply(&mut weights, &mut biases);
+        let after = popart.denormalize(weights[0] * x + biases[0]);
+        assert!((before - after).abs() < 1e-3, "{before} != {after}");
+
+        let targets = popart.normalize_all(&[popart.mean() as f32]);
+        assert!(targets[0].abs() < 1e-6);
+    }
+}
@@ -0,0 +1,460 @@
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    csv::{escape_csv, split_csv},
+    stats::{iqm, mean, median},
+    train::{evaluate::bootstrap, Estimate},
+};
+
+/// The number of evenly spaced thresholds of a performance profile, if none are set
+const THRESHOLDS: usize = 21;
+
+/// Aggregate performance across runs, as recommended by Agarwal et al., 2021, *Deep Reinforcement Learning at the
+/// Edge of the Statistical Precipice*, for tables and plots in papers and READMEs
+///
+/// Every run is reduced to one score, the mean of the last values of a metric in its metrics file, and the runs are
+/// grouped by name, e.g. by algorithm. Each group is summarized by the interquartile mean (IQM), mean and median of
+/// its scores, plus the optimality gap if there is a [target](Report::with_target), each with a 95% bootstrap
+/// confidence interval over the runs. Performance profiles give the fraction of the runs of each group that score
+/// above a range of thresholds, so the whole distribution of scores can be compared rather than a single statistic.
+///
+/// ```ignore
+/// let mut report = Report::new("eval_return_mean").with_target(500.0);
+/// for seed in 0..10 {
+///     report.add_run("dqn", format!("runs/dqn-{seed}"))?;
+///     report.add_run("ppo", format!("runs/ppo-{seed}"))?;
+/// }
+/// println!("{report}");
+/// report.write_csv("summary.csv")?;
+/// report.write_profile_csv("profile.csv")?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Report {
+    metric: String,
+    last: usize,
+    target: Option<f64>,
+    thresholds: Option<Vec<f64>>,
+    groups: Vec<(String, Vec<f64>)>,
+}
+
+impl Report {
+    /// An empty report that scores runs by the final value of `metric`
+    pub fn new(metric: impl Into<String>) -> Self {
+        Self {
+            metric: metric.into(),
+            last: 1,
+            target: None,
+            thresholds: None,
+            groups: Vec::new(),
+        }
+    }
+
+    /// Score runs by the mean of the last `values` values of the metric, to smooth noisy metrics like training
+    /// returns
+    ///
+    /// **Default:** `1`
+    pub fn with_last(mut self, values: usize) -> Self {
+        self.last = values.max(1);
+        self
+    }
+
+    /// Report the optimality gap, the mean amount by which the scores fall short of `target`
+    ///
+    /// Scores above the target count as reaching it, so a group can't make up for runs that fail with runs that
+    /// exceed it, unlike with the mean.
+    pub fn with_target(mut self, target: f64) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// The thresholds of the performance profiles
+    ///
+    /// **Default:** 21 evenly spaced thresholds from the lowest to the highest score of any run
+    pub fn with_thresholds(mut self, thresholds: Vec<f64>) -> Self {
+        self.thresholds = Some(thresholds);
+        self
+    }
+
+    /// Add the run whose metrics are at `path` to `group`
+    ///
+    /// `path` is a metrics file as read by [`read_metrics`], or a [run directory](crate::train::RunDir) with a
+    /// `metrics.csv`.
+    ///
+    /// **Returns** an error of kind [`InvalidData`](io::ErrorKind::InvalidData) if the file doesn't have a finite
+    /// value of the metric
+    pub fn add_run(&mut self, group: &str, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let path = if fs::metadata(path)?.is_dir() {
+            path.join("metrics.csv")
+        } else {
+            path.to_path_buf()
+        };
+
+        let values = read_metrics(&path)?
+            .into_iter()
+            .find(|(name, _)| *name == self.metric)
+            .map(|(_, points)| {
+                points
+                    .into_iter()
+                    .map(|(_, value)| value)
+                    .filter(|value| value.is_finite())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if values.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("no values of `{}` in {}", self.metric, path.display()),
+            ));
+        }
+
+        self.add_score(
+            group,
+            mean(&values[values.len().saturating_sub(self.last)..]),
+        );
+        Ok(())
+    }
+
+    /// Add a run with `score` to `group`, e.g. the return of one seed of an [`evaluate`](crate::train::evaluate)
+    pub fn add_score(&mut self, group: &str, score: f64) {
+        match self.groups.iter_mut().find(|(name, _)| name == group) {
+            Some((_, scores)) => scores.push(score),
+            None => self.groups.push((group.to_string(), vec![score])),
+        }
+    }
+
+    /// The scores of the runs of every group, in the order the groups were added
+    pub fn scores(&self) -> &[(String, Vec<f64>)] {
+        &self.groups
+    }
+
+    /// The statistics of every group, in the order the groups were added
+    pub fn summaries(&self) -> Vec<GroupSummary> {
+        self.groups
+            .iter()
+            .map(|(name, scores)| {
+                let optimality_gap = self.target.map(|target| {
+                    let gaps = scores
+                        .iter()
+                        .map(|score| (target - score).max(0.0))
+                        .collect::<Vec<_>>();
+                    bootstrap(&gaps, [mean])[0]
+                });
+                let [iqm, mean, median] = bootstrap(scores, [iqm, mean, median]);
+                GroupSummary {
+                    name: name.clone(),
+                    runs: scores.len(),
+                    iqm,
+                    mean,
+                    median,
+                    optimality_gap,
+                }
+            })
+            .collect()
+    }
+
+    /// The performance profile of every group, in the order the groups were added
+    pub fn profiles(&self) -> Vec<PerformanceProfile> {
+        let thresholds = self.thresholds.clone().unwrap_or_else(|| {
+            let scores = self.groups.iter().flat_map(|(_, scores)| scores);
+            let low = scores.clone().copied().fold(f64::INFINITY, f64::min);
+            let high = scores.copied().fold(f64::NEG_INFINITY, f64::max);
+            if low > high {
+                return Vec::new();
+            }
+            (0..THRESHOLDS)
+                .map(|i| low + (high - low) * i as f64 / (THRESHOLDS - 1) as f64)
+                .collect()
+        });
+
+        self.groups
+            .iter()
+            .map(|(name, scores)| PerformanceProfile {
+                name: name.clone(),
+                fractions: thresholds
+                    .iter()
+                    .map(|&threshold| {
+                        let above = scores.iter().filter(|&&score| score > threshold).count();
+                        above as f64 / scores.len() as f64
+                    })
+                    .collect(),
+                thresholds: thresholds.clone(),
+            })
+            .collect()
+    }
+
+    /// The statistics as a markdown table, with `value [low, high]` cells
+    ///
+    /// The optimality gap column is only included if there is a target.
+    pub fn to_markdown(&self) -> String {
+        let mut table = String::from("| run | runs | IQM | mean | median |");
+        let mut separator = String::from("|---|---|---|---|---|");
+        if self.target.is_some() {
+            table.push_str(" optimality gap |");
+            separator.push_str("---|");
+        }
+        table.push('\n');
+        table.push_str(&separator);
+        table.push('\n');
+
+        for summary in self.summaries() {
+            let mut cells = vec![summary.name.clone(), summary.runs.to_string()];
+            cells.extend(
+                [summary.iqm, summary.mean, summary.median]
+                    .into_iter()
+                    .chain(summary.optimality_gap)
+                    .map(|estimate| {
+                        format!(
+                            "{} [{}, {}]",
+                            format_stat(estimate.value),
+                            format_stat(estimate.low),
+                            format_stat(estimate.high)
+                        )
+                    }),
+            );
+            table.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+        table
+    }
+
+    /// Write the statistics to a CSV file at `path`
+    ///
+    /// The columns are the group and its number of runs, then every statistic with the bounds of its confidence
+    /// interval, e.g. `iqm`, `iqm_low` and `iqm_high`. The optimality gap is left empty without a target.
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let mut header = vec![String::from("run"), String::from("runs")];
+        for name in ["iqm", "mean", "median", "optimality_gap"] {
+            header.extend([
+                name.to_string(),
+                format!("{name}_low"),
+                format!("{name}_high"),
+            ]);
+        }
+        writeln!(writer, "{}", header.join(","))?;
+
+        for summary in self.summaries() {
+            let mut row = vec![escape_csv(&summary.name), summary.runs.to_string()];
+            for estimate in [
+                Some(summary.iqm),
+                Some(summary.mean),
+                Some(summary.median),
+                summary.optimality_gap,
+            ] {
+                match estimate {
+                    Some(estimate) => row.extend(
+                        [estimate.value, estimate.low, estimate.high].map(|v| v.to_string()),
+                    ),
+                    None => row.extend([String::new(), String::new(), String::new()]),
+                }
+            }
+            writeln!(writer, "{}", row.join(","))?;
+        }
+        writer.flush()
+    }
+
+    /// Write the performance profiles to a CSV file at `path`, with the columns `run,threshold,fraction`
+    pub fn write_profile_csv(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "run,threshold,fraction")?;
+        for profile in self.profiles() {
+            let name = escape_csv(&profile.name);
+            for (threshold, fraction) in profile.thresholds.iter().zip(&profile.fractions) {
+                writeln!(writer, "{name},{threshold},{fraction}")?;
+            }
+        }
+        writer.flush()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_markdown())
+    }
+}
+
+/// The statistics of the scores of a group of runs in a [`Report`], with their 95% bootstrap confidence intervals
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupSummary {
+    /// The name of the group
+    pub name: String,
+    /// The number of runs
+    pub runs: usize,
+    /// The interquartile mean of the scores, the mean of the middle half
+    pub iqm: Estimate,
+    /// The mean of the scores
+    pub mean: Estimate,
+    /// The median of the scores
+    pub median: Estimate,
+    /// The mean shortfall of the scores from the [target](Report::with_target), if there is one
+    pub optimality_gap: Option<Estimate>,
+}
+
+/// The fraction of the runs of a group in a [`Report`] that score above each threshold
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerformanceProfile {
+    /// The name of the group
+    pub name: String,
+    /// The thresholds, shared by the profiles of all groups
+    pub thresholds: Vec<f64>,
+    /// The fraction of the runs with a score above each threshold
+    pub fractions: Vec<f64>,
+}
+
+/// Format a statistic with about three significant digits
+fn format_stat(value: f64) -> String {
+    match value.abs() {
+        v if v == 0.0 || !v.is_finite() => value.to_string(),
+        v if v >= 100.0 => format!("{value:.0}"),
+        v => {
+            let decimals = (2 - v.log10().floor() as i32).max(0) as usize;
+            format!("{value:.decimals$}")
+        }
+    }
+}
+
+/// Read named series of `(episode, value)` points from a metrics file, as written by a
+/// [`CsvSink`](crate::logger::CsvSink) or exported from the viz TUI
+///
+/// The format is chosen from the file extension: JSON lines for `.json` and `.jsonl`, CSV with the header
+/// `metric,episode,value` otherwise. Series are returned in the order they first appear in the file. Missing values
+/// are read as `NaN`.
+pub fn read_metrics(path: &Path) -> io::Result<Vec<(String, Vec<(f64, f64)>)>> {
+    let reader = BufReader::new(File::open(path)?);
+
+    let is_json = matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("json" | "jsonl")
+    );
+
+    let mut series: Vec<(String, Vec<(f64, f64)>)> = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() || (!is_json && i == 0) {
+            continue;
+        }
+
+        let (name, point) = if is_json {
+            parse_json_line(&line)
+        } else {
+            parse_csv_line(&line)
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("malformed metrics on line {}: {line}", i + 1),
+            )
+        })?;
+
+        match series.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, points)) => points.push(point),
+            None => series.push((name, vec![point])),
+        }
+    }
+
+    Ok(series)
+}
+
+fn parse_csv_line(line: &str) -> Option<(String, (f64, f64))> {
+    let [name, x, y]: [String; 3] = split_csv(line).try_into().ok()?;
+    Some((name, (x.trim().parse().ok()?, y.trim().parse().ok()?)))
+}
+
+fn parse_json_line(line: &str) -> Option<(String, (f64, f64))> {
+    let rest = &line[line.find("\"metric\":\"")? + 10..];
+
+    let mut name = String::new();
+    let mut chars = rest.chars();
+    loop {
+        match chars.next()? {
+            '\\' => name.push(chars.next()?),
+            '"' => break,
+            c => name.push(c),
+        }
+    }
+
+    let x = json_field(line, "episode")?;
+    let y = json_field(line, "value")?;
+
+    Some((name, (x, y)))
+}
+
+fn json_field(line: &str, key: &str) -> Option<f64> {
+    let pattern = format!("\"{key}\":");
+    let rest = &line[line.find(&pattern)? + pattern.len()..];
+    let value = rest[..rest.find([',', '}'])?].trim();
+
+    match value {
+        "null" => Some(f64::NAN),
+        _ => value.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logger::{CsvSink, MetricSink};
+
+    #[test]
+    fn report_functional() {
+        let dir = std::env::temp_dir().join("rl_report_functional");
+        fs::create_dir_all(&dir).unwrap();
+        let mut report = Report::new("return").with_last(2).with_target(10.0);
+        for (i, last) in [4.0, 8.0, 12.0, 16.0].into_iter().enumerate() {
+            let path = dir.join(format!("run-{i}.csv"));
+            let mut sink = CsvSink::new(&path).unwrap();
+            sink.log_episode(0, &[("return", 0.0), ("steps", 5.0)])
+                .unwrap();
+            sink.log_episode(1, &[("return", last - 2.0)]).unwrap();
+            sink.log_episode(2, &[("return", last + 2.0)]).unwrap();
+            drop(sink);
+            report.add_run("a", &path).unwrap();
+        }
+        for score in [0.0, 10.0] {
+            report.add_score("b, c", score);
+        }
+        assert!(Report::new("loss")
+            .add_run("a", dir.join("run-0.csv"))
+            .is_err());
+
+        assert_eq!(report.scores()[0].1, [4.0, 8.0, 12.0, 16.0]);
+        let summaries = report.summaries();
+        assert_eq!(summaries[0].runs, 4);
+        assert_eq!(summaries[0].iqm.value, 10.0, "Mean of 8 and 12");
+        assert_eq!(summaries[0].mean.value, 10.0);
+        assert_eq!(
+            summaries[0].optimality_gap.unwrap().value,
+            2.5,
+            "Mean of 6, 2, 0 and 0"
+        );
+        assert_eq!(summaries[1].optimality_gap.unwrap().value, 5.0);
+        for estimate in [summaries[0].iqm, summaries[0].mean, summaries[0].median] {
+            assert!(estimate.low <= estimate.value && estimate.value <= estimate.high);
+        }
+
+        let profiles = report.with_thresholds(vec![0.0, 8.0, 20.0]).profiles();
+        assert_eq!(profiles[0].fractions, [1.0, 0.5, 0.0]);
+        assert_eq!(profiles[1].fractions, [0.5, 0.5, 0.0]);
+
+        let mut report = Report::new("return");
+        report.add_score("b, c", 1.0);
+        assert_eq!(report.profiles()[0].thresholds.len(), THRESHOLDS);
+        let markdown = report.to_markdown();
+        assert!(markdown.starts_with("| run | runs | IQM | mean | median |\n"));
+        assert!(markdown.contains("| b, c | 1 | 1.00 [1.00, 1.00] |"));
+
+        let path = dir.join("summary.csv");
+        report.write_csv(&path).unwrap();
+        let csv = fs::read_to_string(&path).unwrap();
+        assert!(csv.lines().nth(1).unwrap().starts_with("\"b, c\",1,1,1,1,"));
+        assert!(csv.lines().nth(1).unwrap().ends_with(",,,"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,246 @@
+use std::fmt::Debug;
+
+use crate::env::{DiscreteActionSpace, Environment};
+
+/// The reward type of an [`Environment`]
+///
+/// Implemented for `f32` and `f64`, and elementwise for arrays and vectors of rewards, e.g. `[f32; 2]` for two
+/// objectives.
+pub trait Reward: Clone + Debug {
+    /// The reward that adds nothing, with the shape of `self`
+    fn zero_like(&self) -> Self;
+
+    /// Add `other` to `self`, e.g. to sum the rewards of an episode
+    fn accumulate(&mut self, other: &Self);
+}
+
+impl Reward for f32 {
+    fn zero_like(&self) -> Self {
+        0.0
+    }
+
+    fn accumulate(&mut self, other: &Self) {
+        *self += other;
+    }
+}
+
+impl Reward for f64 {
+    fn zero_like(&self) -> Self {
+        0.0
+    }
+
+    fn accumulate(&mut self, other: &Self) {
+        *self += other;
+    }
+}
+
+impl<R: Reward, const N: usize> Reward for [R; N] {
+    fn zero_like(&self) -> Self {
+        std::array::from_fn(|i| self[i].zero_like())
+    }
+
+    fn accumulate(&mut self, other: &Self) {
+        self.iter_mut()
+            .zip(other)
+            .for_each(|(reward, other)| reward.accumulate(other));
+    }
+}
+
+/// **Panics** in [`accumulate`](Reward::accumulate) if the vectors have different lengths
+impl<R: Reward> Reward for Vec<R> {
+    fn zero_like(&self) -> Self {
+        self.iter().map(Reward::zero_like).collect()
+    }
+
+    fn accumulate(&mut self, other: &Self) {
+        assert_eq!(
+            self.len(),
+            other.len(),
+            "Rewards must have the same number of objectives"
+        );
+        self.iter_mut()
+            .zip(other)
+            .for_each(|(reward, other)| reward.accumulate(other));
+    }
+}
+
+/// An [`Environment`] that maps the rewards of an environment with another [`Reward`] type to the `f32` rewards
+/// agents learn from
+///
+/// The rewards of the current episode are summed in their own type, so e.g. `f64` returns keep their precision and
+/// the return of every objective can be reported.
+///
+/// ```ignore
+/// // Two objectives, weighted 3:1
+/// let env = Scalarized::weighted(TreasureHunt::new(), vec![0.75, 0.25]);
+/// let mut agent = QTableAgent::new(QTableAgentConfig::default())?;
+/// agent.go(&mut env);
+/// println!("Objectives: {:?}", env.episode_return());
+/// ```
+///
+/// ### Generics
+/// - `E` - The wrapped [`Environment`]
+/// - `R` - The [`Reward`] of `E`
+/// - `F` - The function from a reward to an `f32`
+#[derive(Debug, Clone)]
+pub struct Scalarized<E, R, F> {
+    env: E,
+    scalarize: F,
+    episode_return: Option<R>,
+}
+
+impl<E, R, F> Scalarized<E, R, F>
+where
+    E: Environment<R>,
+    R: Reward,
+    F: Fn(&R) -> f32,
+{
+    /// Wrap `env`, mapping its rewards with `scalarize`
+    pub fn new(env: E, scalarize: F) -> Self {
+        Self {
+            env,
+            scalarize,
+            episode_return: None,
+        }
+    }
+
+    /// The sum of the rewards of the current episode, `None` before its first step
+    pub fn episode_return(&self) -> Option<&R> {
+        self.episode_return.as_ref()
+    }
+
+    /// The wrapped environment
+    pub fn inner(&self) -> &E {
+        &self.env
+    }
+
+    /// Unwrap the environment
+    pub fn into_inner(self) -> E {
+        self.env
+    }
+}
+
+impl<E, R> Scalarized<E, R, fn(&R) -> f32>
+where
+    E: Environment<R>,
+    R: Reward + AsRef<[f32]>,
+{
+    /// Wrap `env`, mapping its rewards to the weighted sum of their objectives
+    ///
+    /// **Panics** while stepping if a reward doesn't have one objective per weight
+    pub fn weighted(env: E, weights: Vec<f32>) -> Scalarized<E, R, impl Fn(&R) -> f32> {
+        Scalarized::new(env, move |reward: &R| {
+            let reward = reward.as_ref();
+            assert_eq!(
+                reward.len(),
+                weights.len(),
+                "A reward has {} objectives, but there are {} weights",
+                reward.len(),
+                weights.len()
+            );
+            reward.iter().zip(&weights).map(|(r, w)| r * w).sum()
+        })
+    }
+}
+
+impl<E, R, F> Environment for Scalarized<E, R, F>
+where
+    E: Environment<R>,
+    R: Reward,
+    F: Fn(&R) -> f32,
+{
+    type State = E::State;
+    type Action = E::Action;
+
+    fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
+        let (next_state, reward) = self.env.step(action);
+        match &mut self.episode_return {
+            Some(ret) => ret.accumulate(&reward),
+            None => self.episode_return = Some(reward.clone()),
+        }
+        (next_state, (self.scalarize)(&reward))
+    }
+
+    fn reset(&mut self) -> Self::State {
+        self.episode_return = None;
+        self.env.reset()
+    }
+
+    fn random_action(&self) -> Self::Action {
+        self.env.random_action()
+    }
+
+    fn is_active(&self) -> bool {
+        self.env.is_active()
+    }
+
+    fn discount(&self) -> Option<f32> {
+        self.env.discount()
+    }
+}
+
+impl<E, R, F> DiscreteActionSpace for Scalarized<E, R, F>
+where
+    E: DiscreteActionSpace<R>,
+    R: Reward,
+    F: Fn(&R) -> f32,
+{
+    fn actions(&self) -> Vec<Self::Action> {
+        self.env.actions()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two steps with a reward for each of two objectives
+    struct TwoObjectives {
+        steps: u8,
+    }
+
+    impl Environment<[f32; 2]> for TwoObjectives {
+        type State = u8;
+        type Action = bool;
+
+        fn step(&mut self, action: Self::Action) -> (Option<Self::State>, [f32; 2]) {
+            self.steps += 1;
+            let reward = if action { [1.0, 0.0] } else { [0.0, 2.0] };
+            ((self.steps < 2).then_some(self.steps), reward)
+        }
+
+        fn reset(&mut self) -> Self::State {
+            self.steps = 0;
+            0
+        }
+
+        fn random_action(&self) -> Self::Action {
+            false
+        }
+    }
+
+    impl DiscreteActionSpace<[f32; 2]> for TwoObjectives {
+        fn actions(&self) -> Vec<Self::Action> {
+            vec![false, true]
+        }
+    }
+
+    #[test]
+    fn scalarized() {
+        let mut env = Scalarized::weighted(TwoObjectives { steps: 0 }, vec![0.5, 0.25]);
+        assert_eq!(env.actions(), [false, true]);
+
+        env.reset();
+        assert_eq!(env.episode_return(), None);
+        assert_eq!(env.step(true), (Some(1), 0.5));
+        assert_eq!(env.step(false), (None, 0.5));
+        assert_eq!(env.episode_return(), Some(&[1.0, 2.0]));
+        env.reset();
+        assert_eq!(env.episode_return(), None, "Returns are per episode");
+
+        let mut precise = vec![0.1f64; 3];
+        precise.accumulate(&vec![0.2; 3]);
+        assert_eq!(precise, [0.1 + 0.2; 3]);
+        assert_eq!(precise.zero_like(), [0.0; 3]);
+    }
+}
@@ -0,0 +1,115 @@
+use burn::prelude::*;
+
+use crate::error::{check_interval, Result, RlError};
+
+/// How a distributional agent turns the distribution of returns of an action into the value it maximizes
+///
+/// Distributions are given as `n` equally likely quantiles, as learned by the
+/// [`QuantileAgent`](crate::algo::tabular::quantile::QuantileAgent), which maximizes the risk set in its
+/// [config](crate::algo::tabular::quantile::QuantileAgentConfig::risk), or predicted by QR-DQN and trained with
+/// [`quantile_huber`](crate::losses::quantile_huber).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Risk {
+    /// The mean of the returns, the expected return maximized by risk-neutral agents
+    #[default]
+    Neutral,
+    /// The conditional value-at-risk of the returns at level `alpha`, the mean of their worst `alpha` fraction
+    ///
+    /// Maximizing it makes the agent risk-averse, preferring actions whose bad outcomes are least bad over actions
+    /// that are better on average, e.g. to limit losses in trading or operations. `alpha` is in `(0, 1]`, with `1`
+    /// being the mean.
+    Cvar { alpha: f32 },
+}
+
+impl Risk {
+    /// The conditional value-at-risk at level `alpha`
+    ///
+    /// ### Returns
+    /// - [`RlError::OutOfRange`] if `alpha` is not in the interval `[0, 1]`
+    /// - [`RlError::InvalidHyperparameters`] if `alpha` is zero
+    pub fn cvar(alpha: f32) -> Result<Self> {
+        check_interval("alpha", alpha, 0.0, 1.0)?;
+        if alpha == 0.0 {
+            return Err(RlError::InvalidHyperparameters(String::from(
+                "CVaR needs an `alpha` above 0",
+            )));
+        }
+        Ok(Self::Cvar { alpha })
+    }
+
+    /// The value of the distribution of equally likely `quantiles`, in any order
+    ///
+    /// **Panics** if `quantiles` is empty
+    pub fn value(&self, quantiles: &[f32]) -> f32 {
+        assert!(!quantiles.is_empty(), "A distribution has quantiles");
+        let n = quantiles.len() as f32;
+        match *self {
+            Self::Neutral => quantiles.iter().sum::<f32>() / n,
+            Self::Cvar { alpha } => {
+                let mut sorted = quantiles.to_vec();
+                sorted.sort_by(f32::total_cmp);
+
+                // The worst `alpha` of the probability mass covers whole quantiles and part of the next one
+                let mass = alpha * n;
+                let whole = (mass.floor() as usize).min(sorted.len());
+                let partial = sorted
+                    .get(whole)
+                    .map_or(0.0, |&q| (mass - whole as f32) * q);
+                (sorted[..whole].iter().sum::<f32>() + partial) / mass
+            }
+        }
+    }
+
+    /// The value of each action from its quantiles, e.g. to choose greedy actions
+    ///
+    /// ### Arguments
+    /// - `quantiles` - The quantiles of the returns of each action, with shape `[batch, actions, n]`
+    ///
+    /// **Returns** the values with shape `[batch, actions]`
+    pub fn action_values<B: Backend>(&self, quantiles: Tensor<B, 3>) -> Tensor<B, 2> {
+        let [batch, actions, n] = quantiles.dims();
+        match self {
+            Self::Neutral => quantiles.mean_dim(2).reshape([batch, actions]),
+            Self::Cvar { .. } => {
+                // CVaR needs the quantiles in order, which the network doesn't guarantee
+                let device = quantiles.device();
+                let values = quantiles
+                    .into_data()
+                    .convert::<f32>()
+                    .value
+                    .chunks(n)
+                    .map(|quantiles| self.value(quantiles))
+                    .collect::<Vec<_>>();
+                Tensor::<B, 1>::from_floats(values.as_slice(), &device).reshape([batch, actions])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use burn::backend::{ndarray::NdArrayDevice, NdArray as B};
+
+    use super::*;
+
+    #[test]
+    fn cvar() {
+        let quantiles = [4.0, -2.0, 2.0, 0.0];
+        assert_eq!(Risk::Neutral.value(&quantiles), 1.0);
+        assert_eq!(Risk::cvar(1.0).unwrap().value(&quantiles), 1.0);
+        assert_eq!(Risk::cvar(0.25).unwrap().value(&quantiles), -2.0);
+        assert_eq!(Risk::cvar(0.5).unwrap().value(&quantiles), -1.0);
+        // Half of the second quantile: (-2 + 0.5 * 0) / 1.5
+        assert!((Risk::cvar(0.375).unwrap().value(&quantiles) + 4.0 / 3.0).abs() < 1e-6);
+        assert!(Risk::cvar(0.0).is_err());
+
+        // A safe action and a riskier one that is better on average
+        let quantiles = Tensor::<B, 1>::from_floats([1.0, 1.0, -3.0, 7.0], &NdArrayDevice::Cpu)
+            .reshape([1, 2, 2]);
+        let neutral = Risk::Neutral.action_values(quantiles.clone());
+        assert_eq!(neutral.into_data().value, [1.0, 2.0]);
+        let averse = Risk::cvar(0.5).unwrap().action_values(quantiles);
+        assert_eq!(averse.into_data().value, [1.0, -3.0]);
+    }
+}
@@ -0,0 +1,277 @@
+use std::{cell::RefCell, hash::Hasher};
+
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+/// The independent random number streams used by the library
+///
+/// Each stream has its own generator per thread, so e.g. changing how often an agent explores doesn't change the
+/// environment's randomness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    /// Environment dynamics, resets and random actions
+    Env,
+    /// Agent internals, e.g. replay memory sampling
+    Agent,
+    /// Exploration policies
+    Exploration,
+}
+
+thread_local! {
+    static GENERATORS: RefCell<[StdRng; 3]> = RefCell::new([
+        StdRng::from_entropy(),
+        StdRng::from_entropy(),
+        StdRng::from_entropy(),
+    ]);
+}
+
+/// Get the generator of `stream` on the current thread
+///
+/// Generators are seeded from entropy until [`Seeds::apply`] is called on the thread.
+pub fn rng(stream: Stream) -> StreamRng {
+    StreamRng(stream)
+}
+
+/// A handle to the generator of a [`Stream`] on the current thread, returned by [`rng`]
+#[derive(Debug, Clone, Copy)]
+pub struct StreamRng(Stream);
+
+impl StreamRng {
+    fn with<T>(&self, f: impl FnOnce(&mut StdRng) -> T) -> T {
+        GENERATORS.with(|generators| f(&mut generators.borrow_mut()[self.0 as usize]))
+    }
+}
+
+impl RngCore for StreamRng {
+    fn next_u32(&mut self) -> u32 {
+        self.with(|rng| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.with(|rng| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.with(|rng| rng.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.with(|rng| rng.try_fill_bytes(dest))
+    }
+}
+
+/// Seeds for every random [`Stream`], making training runs reproducible
+///
+/// ```
+/// use rl::seed::{self, Seeds, Stream};
+/// use rand::Rng;
+///
+/// Seeds::new(7).apply();
+/// let a: u64 = seed::rng(Stream::Env).gen();
+/// Seeds::new(7).apply();
+/// let b: u64 = seed::rng(Stream::Env).gen();
+/// assert_eq!(a, b);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Seeds {
+    /// The seed of [`Stream::Env`]
+    pub env: u64,
+    /// The seed of [`Stream::Agent`], also used for burn backends
+    pub agent: u64,
+    /// The seed of [`Stream::Exploration`]
+    pub exploration: u64,
+    /// Whether to also pin the determinism of burn backends, see [`apply_backend`](Seeds::apply_backend)
+    pub deterministic: bool,
+}
+
+impl Seeds {
+    /// Derive the seeds of every stream from a single `seed`
+    pub fn new(seed: u64) -> Self {
+        let env = splitmix64(seed);
+        let agent = splitmix64(env);
+        let exploration = splitmix64(agent);
+        Self {
+            env,
+            agent,
+            exploration,
+            deterministic: false,
+        }
+    }
+
+    /// Also pin the determinism of burn backends
+    pub fn deterministic(self) -> Self {
+        Self {
+            deterministic: true,
+            ..self
+        }
+    }
+
+    /// Derive independent seeds for worker `index`, e.g. an actor thread
+    pub fn fork(&self, index: u64) -> Self {
+        self.mix(splitmix64(index.wrapping_add(1)))
+    }
+
+    /// Derive the seeds a [`Trainer`](crate::train::Trainer) reseeds with at a checkpoint after `episodes` episodes
+    ///
+    /// Resumed runs start from the same seeds, so they draw the same numbers as if they were never interrupted.
+    pub fn resumed(&self, episodes: u64) -> Self {
+        self.mix(splitmix64(!episodes))
+    }
+
+    /// Mix `offset` into every seed
+    fn mix(&self, offset: u64) -> Self {
+        Self {
+            env: splitmix64(self.env ^ offset),
+            agent: splitmix64(self.agent ^ offset),
+            exploration: splitmix64(self.exploration ^ offset),
+            deterministic: self.deterministic,
+        }
+    }
+
+    /// Reseed the generators of every stream on the current thread
+    pub fn apply(&self) {
+        GENERATORS.with(|generators| {
+            *generators.borrow_mut() = [
+                StdRng::seed_from_u64(self.env),
+                StdRng::seed_from_u64(self.agent),
+                StdRng::seed_from_u64(self.exploration),
+            ];
+        });
+    }
+
+    /// Seed the random number generator of the burn backend `B`, which initializes network parameters
+    ///
+    /// Call this before the networks are created. burn 0.13 has no other determinism switches, so in
+    /// [deterministic](Seeds::deterministic) mode, backends whose kernels run in a nondeterministic order (e.g.
+    /// some GPU reductions) can still vary between runs, and a CPU backend such as `NdArray` should be used.
+    pub fn apply_backend<B: burn::tensor::backend::Backend>(&self) {
+        B::seed(self.agent);
+    }
+
+    /// The seeds as run parameters, e.g. for [`MetricSink::log_config`](crate::logger::MetricSink::log_config)
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("seed.env", self.env.to_string()),
+            ("seed.agent", self.agent.to_string()),
+            ("seed.exploration", self.exploration.to_string()),
+            ("deterministic", self.deterministic.to_string()),
+            ("rl_version", env!("CARGO_PKG_VERSION").to_string()),
+        ]
+    }
+
+    /// A JSON object of the seeds, written next to checkpoints
+    pub(crate) fn to_json(self) -> String {
+        format!(
+            "{{\"env\":{},\"agent\":{},\"exploration\":{},\"deterministic\":{}}}",
+            self.env, self.agent, self.exploration, self.deterministic
+        )
+    }
+}
+
+/// A [`Hasher`] with a fixed algorithm, for values derived from hashes that have to be the same in every run
+///
+/// The algorithm of `std`'s `DefaultHasher` may change between Rust releases. This is FNV-1a over the little-endian
+/// bytes of the hashed values, with `usize` and `isize` widened to 64 bits so hashes don't depend on the platform,
+/// finished with [`splitmix64`] to spread the bits.
+#[derive(Debug, Clone)]
+pub(crate) struct StableHasher(u64);
+
+impl StableHasher {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    pub(crate) fn new() -> Self {
+        Self(Self::OFFSET)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        splitmix64(self.0)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+}
+
+/// The SplitMix64 mixing function, which turns similar seeds into unrelated ones
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    fn draw(stream: Stream) -> Vec<u32> {
+        (0..4).map(|_| rng(stream).gen()).collect()
+    }
+
+    #[test]
+    fn seeds_reproducible() {
+        let seeds = Seeds::new(42);
+        seeds.apply();
+        let env = draw(Stream::Env);
+        let exploration = draw(Stream::Exploration);
+
+        seeds.apply();
+        draw(Stream::Agent);
+        assert_eq!(
+            draw(Stream::Exploration),
+            exploration,
+            "Streams are independent"
+        );
+        assert_eq!(draw(Stream::Env), env, "Same seeds, same numbers");
+
+        Seeds::new(43).apply();
+        assert_ne!(draw(Stream::Env), env);
+
+        assert_ne!(seeds.fork(0), seeds.fork(1));
+        assert_eq!(seeds.fork(0), Seeds::new(42).fork(0));
+        assert_ne!(seeds.fork(2), seeds.resumed(2));
+    }
+
+    #[test]
+    fn stable_hashes() {
+        use std::hash::Hash;
+
+        let hash = |seed: u64, key: usize| {
+            let mut hasher = StableHasher::new();
+            seed.hash(&mut hasher);
+            key.hash(&mut hasher);
+            hasher.finish()
+        };
+        // Pinned, so a change of the algorithm shows up here instead of in different initial values
+        assert_eq!(hash(0, 3), 0xdf36_eba4_62c5_e38c);
+        assert_eq!(hash(1, 3), 0x4d01_5828_1c9e_7647);
+        assert_eq!(hash(0, 4), 0x2e1a_aa9f_2011_d517);
+    }
+}
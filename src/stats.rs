@@ -0,0 +1,273 @@
+use std::collections::VecDeque;
+
+/// Running mean and variance of a stream of values with Welford's algorithm
+///
+/// Numerically stable in a single pass, unlike summing values and their squares. Statistics of separate streams are
+/// combined with [`merge`](RunningMeanVar::merge), e.g. from parallel actors.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RunningMeanVar {
+    count: u64,
+    mean: f64,
+    m2: f64,
+}
+
+impl RunningMeanVar {
+    /// Create empty statistics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restore statistics of `count` values from their mean and population variance, e.g. read from a file
+    pub fn from_parts(count: u64, mean: f64, var: f64) -> Self {
+        Self {
+            count,
+            mean,
+            m2: var * count as f64,
+        }
+    }
+
+    /// Add a value
+    pub fn push(&mut self, x: f64) {
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    /// Add the values of `other`, as if they had been pushed to `self`
+    pub fn merge(&mut self, other: &Self) {
+        if other.count == 0 {
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * (self.count * other.count) as f64 / count as f64;
+        self.count = count;
+    }
+
+    /// The number of values
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The mean of the values, `0` without values
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// The population variance of the values, `0` without values
+    pub fn var(&self) -> f64 {
+        self.m2 / self.count.max(1) as f64
+    }
+
+    /// The population standard deviation of the values, `0` without values
+    pub fn std(&self) -> f64 {
+        self.var().sqrt()
+    }
+}
+
+impl FromIterator<f64> for RunningMeanVar {
+    fn from_iter<I: IntoIterator<Item = f64>>(iter: I) -> Self {
+        let mut stats = Self::new();
+        iter.into_iter().for_each(|x| stats.push(x));
+        stats
+    }
+}
+
+/// An exponential moving average, which smooths a noisy metric while following its trend
+///
+/// Each new value moves the average by `alpha` times its difference from the average, so older values are forgotten
+/// with a time constant of about `1 / alpha` values. The first value initializes the average.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ema {
+    alpha: f64,
+    value: Option<f64>,
+}
+
+impl Ema {
+    /// Create an average without values
+    ///
+    /// **Panics** if `alpha` is not in `(0, 1]`
+    pub fn new(alpha: f64) -> Self {
+        assert!(alpha > 0.0 && alpha <= 1.0, "alpha must be in (0, 1]");
+        Self { alpha, value: None }
+    }
+
+    /// Add a value
+    ///
+    /// **Returns** the new average
+    pub fn push(&mut self, x: f64) -> f64 {
+        let value = match self.value {
+            Some(value) => value + self.alpha * (x - value),
+            None => x,
+        };
+        self.value = Some(value);
+        value
+    }
+
+    /// The average, `None` before the first value
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+/// The most recent values of a stream, with their mean and quantiles
+///
+/// Keeps at most `capacity` values, dropping the oldest ones, e.g. for the mean return of the last 100 episodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Window {
+    values: VecDeque<f64>,
+    capacity: usize,
+}
+
+impl Window {
+    /// Create an empty window of `capacity` values
+    ///
+    /// **Panics** if `capacity` is zero
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "A window holds at least one value");
+        Self {
+            values: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Add a value, dropping the oldest one if the window is full
+    pub fn push(&mut self, x: f64) {
+        if self.values.len() == self.capacity {
+            self.values.pop_front();
+        }
+        self.values.push_back(x);
+    }
+
+    /// The number of values in the window
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the window has no values
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Whether the window holds `capacity` values
+    pub fn is_full(&self) -> bool {
+        self.values.len() == self.capacity
+    }
+
+    /// The values, from oldest to newest
+    pub fn values(&self) -> impl Iterator<Item = f64> + '_ {
+        self.values.iter().copied()
+    }
+
+    /// The mean of the values, `None` if the window is empty
+    pub fn mean(&self) -> Option<f64> {
+        (!self.is_empty()).then(|| self.values.iter().sum::<f64>() / self.len() as f64)
+    }
+
+    /// The `q` quantile of the values, interpolating linearly between the closest ones, `None` if the window is empty
+    ///
+    /// **Panics** if `q` is not in `[0, 1]`
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        assert!((0.0..=1.0).contains(&q), "q must be in [0, 1]");
+        let mut sorted = self.values.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable_by(f64::total_cmp);
+        (!sorted.is_empty()).then(|| quantile(&sorted, q))
+    }
+
+    /// The median of the values, `None` if the window is empty
+    pub fn median(&self) -> Option<f64> {
+        self.quantile(0.5)
+    }
+}
+
+/// The mean of `values`, `NaN` if there are none
+pub fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// The `q` quantile of `sorted`, interpolating linearly between the closest values
+///
+/// **Panics** if `sorted` is empty
+pub fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let (low, high) = (position.floor() as usize, position.ceil() as usize);
+    sorted[low] + (sorted[high] - sorted[low]) * (position - low as f64)
+}
+
+/// The median of `sorted`
+///
+/// **Panics** if `sorted` is empty
+pub fn median(sorted: &[f64]) -> f64 {
+    quantile(sorted, 0.5)
+}
+
+/// The mean of `sorted` without its lowest and highest quarter, the mean of all values if there are less than four
+pub fn iqm(sorted: &[f64]) -> f64 {
+    let cut = sorted.len() / 4;
+    mean(&sorted[cut..sorted.len() - cut])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn running_mean_var() {
+        let stats = [1.0, 2.0, 3.0, 6.0].into_iter().collect::<RunningMeanVar>();
+        assert_eq!(stats.count(), 4);
+        assert_eq!(stats.mean(), 3.0);
+        assert_eq!(stats.var(), 3.5);
+
+        let mut merged = [1.0, 2.0].into_iter().collect::<RunningMeanVar>();
+        merged.merge(&[3.0, 6.0].into_iter().collect());
+        assert_eq!(merged.count(), 4);
+        assert!((merged.mean() - 3.0).abs() < 1e-12);
+        assert!((merged.var() - 3.5).abs() < 1e-12);
+
+        let restored = RunningMeanVar::from_parts(4, 3.0, 3.5);
+        assert_eq!(restored.var(), 3.5);
+        assert_eq!(RunningMeanVar::new().std(), 0.0);
+    }
+
+    #[test]
+    fn ema() {
+        let mut ema = Ema::new(0.5);
+        assert_eq!(ema.value(), None);
+        assert_eq!(
+            ema.push(4.0),
+            4.0,
+            "The first value initializes the average"
+        );
+        assert_eq!(ema.push(0.0), 2.0);
+        assert_eq!(ema.push(2.0), 2.0);
+    }
+
+    #[test]
+    fn window() {
+        let mut window = Window::new(4);
+        assert_eq!(window.mean(), None);
+        assert_eq!(window.median(), None);
+        for x in [10.0, 4.0, 1.0, 3.0, 2.0] {
+            window.push(x);
+        }
+        assert!(window.is_full());
+        assert_eq!(window.values().collect::<Vec<_>>(), [4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(window.mean(), Some(2.5));
+        assert_eq!(window.median(), Some(2.5));
+        assert_eq!(window.quantile(0.0), Some(1.0));
+        assert_eq!(window.quantile(1.0), Some(4.0));
+        assert_eq!(window.quantile(1.0 / 3.0), Some(2.0));
+    }
+
+    #[test]
+    fn slices() {
+        let sorted = [1.0, 2.0, 4.0, 8.0, 9.0];
+        assert_eq!(mean(&sorted), 4.8);
+        assert_eq!(median(&sorted), 4.0);
+        assert_eq!(quantile(&sorted, 0.125), 1.5);
+        assert_eq!(iqm(&sorted), 14.0 / 3.0, "Mean of 2, 4 and 8");
+        assert_eq!(iqm(&sorted[..3]), 7.0 / 3.0);
+    }
+}
@@ -0,0 +1,138 @@
+use std::{
+    ops::ControlFlow,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    error::{Result, RlError},
+    train::Evaluation,
+};
+
+/// Asynchronous successive halving (ASHA), which stops trials whose evaluations fall behind the others
+///
+/// Trials pass rungs after `min_episodes * reduction_factor^k` episodes. At each rung, a trial continues only if its
+/// best mean evaluation return so far is in the top `1 / reduction_factor` of the scores recorded at that rung.
+/// Decisions are made as soon as a trial reaches a rung, without waiting for the other trials, so early trials are
+/// never stopped and most of the compute goes to the promising ones.
+///
+/// Rungs are checked at the first evaluation after their episode count, so evaluation must be enabled in the
+/// config of the sweep, with an interval no longer than `min_episodes`.
+#[derive(Debug)]
+pub struct Asha {
+    min_episodes: u64,
+    reduction_factor: u64,
+    rungs: Mutex<Vec<Vec<f64>>>,
+}
+
+impl Asha {
+    /// ### Arguments
+    /// - `min_episodes` - The number of episodes every trial runs before the first rung
+    /// - `reduction_factor` - The inverse of the fraction of trials that continue at each rung, usually 3 or 4
+    ///
+    /// **Returns** an [`RlError::InvalidHyperparameters`] error if `min_episodes` is 0 or `reduction_factor` is less
+    /// than 2
+    pub fn new(min_episodes: u64, reduction_factor: u64) -> Result<Self> {
+        if min_episodes == 0 {
+            return Err(RlError::InvalidHyperparameters(String::from(
+                "`min_episodes` must be positive",
+            )));
+        }
+        if reduction_factor < 2 {
+            return Err(RlError::InvalidHyperparameters(String::from(
+                "`reduction_factor` must be at least 2",
+            )));
+        }
+        Ok(Self {
+            min_episodes,
+            reduction_factor,
+            rungs: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// The number of episodes after which trials reach `rung`
+    fn milestone(&self, rung: u32) -> u64 {
+        self.reduction_factor
+            .saturating_pow(rung)
+            .saturating_mul(self.min_episodes)
+    }
+
+    /// Record the `score` of a trial reaching `rung`
+    ///
+    /// **Returns** whether the trial continues
+    fn promote(&self, rung: u32, score: f64) -> bool {
+        let mut rungs = self.rungs.lock().unwrap();
+        let rung = rung as usize;
+        if rungs.len() <= rung {
+            rungs.resize(rung + 1, Vec::new());
+        }
+        let scores = &mut rungs[rung];
+        scores.push(score);
+
+        let keep = scores.len() / self.reduction_factor as usize;
+        if keep == 0 {
+            return true;
+        }
+        let mut sorted = scores.clone();
+        sorted.sort_by(|a, b| b.total_cmp(a));
+        score >= sorted[keep - 1]
+    }
+
+    /// A callback for one trial, passed to [`Experiment::with_eval_callback`](crate::config::Experiment::with_eval_callback)
+    pub(super) fn callback(
+        self: &Arc<Self>,
+    ) -> impl FnMut(u64, Evaluation) -> ControlFlow<String> + 'static {
+        let asha = Arc::clone(self);
+        let mut rung = 0;
+        let mut best = f64::NEG_INFINITY;
+        move |episode, eval| {
+            best = best.max(eval.mean);
+            let episodes = episode + 1;
+            while episodes >= asha.milestone(rung) {
+                if !asha.promote(rung, best) {
+                    return ControlFlow::Break(format!(
+                        "pruned at rung {rung} after {episodes} episodes"
+                    ));
+                }
+                rung += 1;
+            }
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(mean: f64) -> Evaluation {
+        Evaluation { mean, std: 0.0 }
+    }
+
+    #[test]
+    fn asha_promotion() {
+        assert!(Asha::new(10, 1).is_err());
+        let asha = Arc::new(Asha::new(10, 2).unwrap());
+        assert_eq!(asha.milestone(0), 10);
+        assert_eq!(asha.milestone(2), 40);
+
+        let mut first = asha.callback();
+        assert!(first(4, eval(1.0)).is_continue(), "Before the first rung");
+        assert!(first(9, eval(1.0)).is_continue(), "Alone at the rung");
+
+        let mut worse = asha.callback();
+        assert!(worse(9, eval(0.5)).is_break(), "Not in the top half");
+
+        let mut better = asha.callback();
+        assert!(better(9, eval(2.0)).is_continue(), "In the top half");
+        assert!(
+            better(19, eval(0.0)).is_continue(),
+            "Scored by its best evaluation"
+        );
+
+        let mut late = asha.callback();
+        assert!(
+            late(39, eval(0.0)).is_break(),
+            "Checked at every rung it passed"
+        );
+    }
+}
@@ -0,0 +1,764 @@
+mod asha;
+mod pbt;
+mod tpe;
+
+pub use asha::Asha;
+pub use pbt::Pbt;
+
+#[cfg(feature = "viz")]
+use std::sync::mpsc::Sender;
+use std::{
+    fmt, fs,
+    hash::Hasher,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde_json::Value;
+
+#[cfg(feature = "viz")]
+use crate::viz::{RunSender, Update};
+use crate::{
+    config::{Experiment, ExperimentConfig},
+    csv::escape_csv,
+    error::{check_interval, RlError},
+    multi_objective::ParetoFront,
+    seed::StableHasher,
+    train::TrainSummary,
+};
+
+/// The values a swept parameter can take
+#[derive(Debug, Clone, PartialEq)]
+pub enum Param {
+    /// Each of the listed values
+    Values(Vec<Value>),
+    /// Integers from `low` to `high`, inclusive
+    IntRange { low: i64, high: i64 },
+    /// Floats drawn uniformly from `low..high`, not for [grid search](Search::Grid)
+    Uniform { low: f64, high: f64 },
+    /// Floats whose logarithm is drawn uniformly from `ln(low)..ln(high)`, not for [grid search](Search::Grid)
+    ///
+    /// Suited to parameters spanning orders of magnitude, e.g. learning rates.
+    LogUniform { low: f64, high: f64 },
+}
+
+impl Param {
+    /// A parameter taking each of `values`, e.g. `Param::values([0.1, 0.5, 0.9])`
+    pub fn values<T: Into<Value>>(values: impl IntoIterator<Item = T>) -> Self {
+        Self::Values(values.into_iter().map(Into::into).collect())
+    }
+
+    /// Every value of the parameter, or `None` if it is continuous
+    fn grid(&self) -> Option<Vec<Value>> {
+        match self {
+            Self::Values(values) => Some(values.clone()),
+            Self::IntRange { low, high } => Some((*low..=*high).map(Value::from).collect()),
+            Self::Uniform { .. } | Self::LogUniform { .. } => None,
+        }
+    }
+
+    /// Draw a random value of the parameter
+    fn sample(&self, rng: &mut impl Rng) -> Value {
+        match *self {
+            Self::Values(ref values) => values.choose(rng).cloned().unwrap_or(Value::Null),
+            Self::IntRange { low, high } => rng.gen_range(low..=high).into(),
+            Self::Uniform { low, high } => rng.gen_range(low..high).into(),
+            Self::LogUniform { low, high } => rng.gen_range(low.ln()..high.ln()).exp().into(),
+        }
+    }
+}
+
+/// How the trials of a [`Sweep`] are chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Search {
+    /// One trial for every combination of parameter values
+    Grid,
+    /// `trials` trials with independently sampled parameter values
+    Random { trials: usize },
+    /// `trials` trials suggested by a tree-structured Parzen estimator, which models the parameter values of the
+    /// best finished trials and proposes values likely to do as well
+    ///
+    /// The first `startup` trials are sampled randomly to seed the model. Trials are scored by their best
+    /// evaluation, so evaluation must be enabled in the config of the sweep.
+    Tpe { trials: usize, startup: usize },
+}
+
+/// Trains an experiment with different parameter values and compares the results
+///
+/// Parameters are addressed by their dotted path in the [`ExperimentConfig`], e.g. `algo.alpha`,
+/// `algo.epsilon.rate` or `train.episodes`. Trials run in parallel, each on its own thread with its own environment
+/// and agent. Poor trials can be stopped early with [`with_asha`](Sweep::with_asha). If the base config has a
+/// `seed`, random and TPE searches draw their parameter values from it, so the same sweep suggests the same values.
+///
+/// ### Example
+/// ```no_run
+/// use rl::{config::ExperimentConfig, sweep::{Param, Search, Sweep}};
+///
+/// let base = ExperimentConfig::from_file("frozen_lake.toml").unwrap();
+/// let results = Sweep::new(base)
+///     .with_param("algo.alpha", Param::values([0.1, 0.5, 0.9]))
+///     .with_param("algo.gamma", Param::Uniform { low: 0.9, high: 0.999 })
+///     .with_search(Search::Random { trials: 20 })
+///     .with_out_dir("runs/sweep")
+///     .run()
+///     .unwrap();
+///
+/// println!("{results}");
+/// ```
+pub struct Sweep {
+    base: ExperimentConfig,
+    params: Vec<(String, Param)>,
+    search: Search,
+    threads: usize,
+    out_dir: Option<PathBuf>,
+    asha: Option<Arc<Asha>>,
+    #[cfg(feature = "viz")]
+    viz: Option<Sender<Update>>,
+}
+
+impl Sweep {
+    /// Create a sweep over variations of `base`
+    ///
+    /// **Default:** grid search with one thread per available core
+    pub fn new(base: ExperimentConfig) -> Self {
+        Self {
+            base,
+            params: Vec::new(),
+            search: Search::Grid,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            out_dir: None,
+            asha: None,
+            #[cfg(feature = "viz")]
+            viz: None,
+        }
+    }
+
+    /// Vary the config value at the dotted `path`
+    pub fn with_param(mut self, path: impl Into<String>, param: Param) -> Self {
+        self.params.push((path.into(), param));
+        self
+    }
+
+    /// Set how trials are chosen
+    pub fn with_search(mut self, search: Search) -> Self {
+        self.search = search;
+        self
+    }
+
+    /// Set the number of trials run at the same time
+    ///
+    /// [`run`](Sweep::run) returns an [`InvalidInput`](io::ErrorKind::InvalidInput) error if `threads` is 0
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Write the outputs of each trial to `dir/trial-<index>` and the comparison of all trials to `dir/results.csv`
+    ///
+    /// Each trial directory holds its `config.toml` and `metrics.csv`, plus its checkpoints and TensorBoard events
+    /// if the base config enables them. Without an output directory, trials write no files, since they would all
+    /// write to the same paths of the base config.
+    pub fn with_out_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = Some(dir.into());
+        self
+    }
+
+    /// Stop trials whose evaluations fall behind the others with asynchronous successive halving
+    pub fn with_asha(mut self, asha: Asha) -> Self {
+        self.asha = Some(Arc::new(asha));
+        self
+    }
+
+    /// Show every trial as a separate run in the viz dashboard, through the [`Sender`] returned by
+    /// [`viz::init`](crate::viz::init)
+    ///
+    /// To compare configurations over several seeds, sweep `seed` as well and enable
+    /// [`VizConfig::seed_bands`](crate::viz::VizConfig::seed_bands), which plots the mean and spread of the trials of
+    /// each configuration.
+    #[cfg(feature = "viz")]
+    pub fn with_viz(mut self, tx: Sender<Update>) -> Self {
+        self.viz = Some(tx);
+        self
+    }
+
+    /// The parameter values of every trial, or of the random startup trials of a [TPE](Search::Tpe) search
+    ///
+    /// **Returns** an error if grid search is used with a continuous parameter
+    fn trial_params(&self) -> io::Result<Vec<Vec<(String, Value)>>> {
+        match self.search {
+            Search::Grid => {
+                let mut trials = vec![Vec::new()];
+                for (path, param) in &self.params {
+                    let values = param.grid().ok_or_else(|| {
+                        invalid_input(format!(
+                            "`{path}` is continuous and can't be used in a grid search"
+                        ))
+                    })?;
+                    trials = trials
+                        .into_iter()
+                        .flat_map(|trial| {
+                            values.iter().map(move |value| {
+                                let mut trial = trial.clone();
+                                trial.push((path.clone(), value.clone()));
+                                trial
+                            })
+                        })
+                        .collect();
+                }
+                Ok(trials)
+            }
+            Search::Random { trials } => {
+                let mut rng = search_rng(self.base.seed, 0);
+                Ok((0..trials).map(|_| self.sample(&mut rng)).collect())
+            }
+            Search::Tpe { trials, startup } => {
+                let mut rng = search_rng(self.base.seed, 0);
+                Ok((0..trials.min(startup))
+                    .map(|_| self.sample(&mut rng))
+                    .collect())
+            }
+        }
+    }
+
+    /// Draw random values for every parameter
+    fn sample(&self, rng: &mut impl Rng) -> Vec<(String, Value)> {
+        self.params
+            .iter()
+            .map(|(path, param)| (path.clone(), param.sample(rng)))
+            .collect()
+    }
+
+    /// Suggest the parameter values of the next trial of a [TPE](Search::Tpe) search from the finished `trials`
+    fn suggest(&self, trials: &[Trial]) -> Vec<(String, Value)> {
+        // Each suggestion has its own generator, so it doesn't depend on the order in which threads ask for them
+        let mut rng = search_rng(self.base.seed, trials.len() as u64);
+        let startup = match self.search {
+            Search::Tpe { startup, .. } => startup.max(1),
+            _ => 1,
+        };
+        let observations = trials
+            .iter()
+            .filter_map(|trial| Some((trial.params.as_slice(), trial.score()?)))
+            .collect::<Vec<_>>();
+        if observations.len() < startup {
+            self.sample(&mut rng)
+        } else {
+            tpe::suggest(&self.params, &observations, &mut rng)
+        }
+    }
+
+    /// The config of trial `index`, with `params` applied and its outputs moved to its own directory
+    fn trial_config(
+        &self,
+        index: usize,
+        params: &[(String, Value)],
+    ) -> io::Result<ExperimentConfig> {
+        let mut config = apply_params(&self.base, params)?;
+        match &self.out_dir {
+            Some(out_dir) => {
+                let dir = out_dir.join(format!("trial-{index}"));
+                let logging = &mut config.logging;
+                logging.csv = Some(dir.join("metrics.csv"));
+                if logging.tensorboard.is_some() {
+                    logging.tensorboard = Some(dir.join("tensorboard"));
+                }
+                if let Some(checkpoint) = &mut config.train.checkpoint {
+                    checkpoint.dir = dir.join("checkpoints");
+                }
+                if config.run_dir.is_some() {
+                    config.run_dir = Some(dir);
+                }
+            }
+            None => {
+                config.logging.csv = None;
+                config.logging.tensorboard = None;
+                config.train.checkpoint = None;
+                config.run_dir = None;
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Run every trial and collect the results
+    ///
+    /// Trials that fail, e.g. because their config is rejected by [`ExperimentConfig::build`], are recorded with
+    /// their error instead of stopping the sweep.
+    ///
+    /// **Returns** an error if there are no threads, a parameter path or value doesn't fit the config, or the output
+    /// directory can't be written
+    pub fn run(self) -> io::Result<SweepResults> {
+        check_interval("threads", self.threads as f32, 1.0, f32::INFINITY)?;
+        let planned = self.trial_params()?;
+        let total = match self.search {
+            Search::Tpe { trials, .. } => trials,
+            _ => planned.len(),
+        };
+        // Catch bad paths and values before anything is trained
+        for (index, params) in planned.iter().enumerate() {
+            self.trial_config(index, params)?;
+        }
+
+        let trials = Mutex::new(Vec::with_capacity(total));
+        thread::scope(|s| {
+            for _ in 0..self.threads.min(total) {
+                s.spawn(|| {
+                    while let Some((trial, ready)) = self.start_trial(&trials, &planned, total) {
+                        let result = ready.and_then(|()| self.run_trial(&trial));
+                        trials.lock().unwrap()[trial.index].result =
+                            result.map_err(|e| Arc::new(e.into()));
+                    }
+                });
+            }
+        });
+
+        let results = SweepResults {
+            trials: trials.into_inner().unwrap(),
+        };
+        if let Some(dir) = &self.out_dir {
+            fs::create_dir_all(dir)?;
+            results.write_csv(dir.join("results.csv"))?;
+        }
+
+        Ok(results)
+    }
+
+    /// Add the next trial to `trials` and write its config to its output directory
+    ///
+    /// **Returns** the trial and whether it is ready to run, or `None` once all `total` trials have started
+    fn start_trial(
+        &self,
+        trials: &Mutex<Vec<Trial>>,
+        planned: &[Vec<(String, Value)>],
+        total: usize,
+    ) -> Option<(Trial, io::Result<()>)> {
+        let mut trials = trials.lock().unwrap();
+        let index = trials.len();
+        if index >= total {
+            return None;
+        }
+
+        let params = match planned.get(index) {
+            Some(params) => params.clone(),
+            None => self.suggest(&trials),
+        };
+        let config = self.trial_config(index, &params).and_then(|config| {
+            if let Some(dir) = &self.out_dir {
+                let dir = dir.join(format!("trial-{index}"));
+                fs::create_dir_all(&dir)?;
+                config.write_toml(dir.join("config.toml"))?;
+            }
+            Ok(config)
+        });
+        let (config, ready) = match config {
+            Ok(config) => (config, Ok(())),
+            Err(e) => (self.base.clone(), Err(e)),
+        };
+
+        let trial = Trial {
+            index,
+            params,
+            config,
+            result: Err(Arc::new(
+                io::Error::other("the trial is still running").into(),
+            )),
+        };
+        trials.push(trial.clone());
+        Some((trial, ready))
+    }
+
+    /// Build and train the experiment of `trial`
+    fn run_trial(&self, trial: &Trial) -> io::Result<TrainSummary> {
+        let mut experiment: Box<dyn Experiment> = trial.config.build()?;
+        #[cfg(feature = "viz")]
+        if let Some(tx) = &self.viz {
+            experiment = experiment.with_sink(Box::new(RunSender::new(tx.clone(), trial.name())));
+        }
+        if let Some(asha) = &self.asha {
+            experiment = experiment.with_eval_callback(Box::new(asha.callback()));
+        }
+        Ok(experiment.train()?)
+    }
+}
+
+/// One run of a [`Sweep`]
+#[derive(Debug, Clone)]
+pub struct Trial {
+    /// The position of the trial in the sweep
+    pub index: usize,
+    /// The swept parameter paths and the values used in this trial
+    pub params: Vec<(String, Value)>,
+    /// The full config of the trial
+    pub config: ExperimentConfig,
+    /// The training summary, or the error that stopped the trial
+    ///
+    /// The error is shared so trials can be cloned.
+    pub result: Result<TrainSummary, Arc<RlError>>,
+}
+
+impl Trial {
+    /// A short description of the parameter values, e.g. `algo.alpha=0.5 algo.gamma=0.9`
+    pub fn name(&self) -> String {
+        let params = self
+            .params
+            .iter()
+            .map(|(path, value)| format!("{path}={}", display_value(value)))
+            .collect::<Vec<_>>();
+        if params.is_empty() {
+            format!("trial-{}", self.index)
+        } else {
+            params.join(" ")
+        }
+    }
+
+    /// The mean return of the best evaluation, if the trial succeeded and evaluation is enabled
+    pub fn score(&self) -> Option<f64> {
+        let summary = self.result.as_ref().ok()?;
+        summary.best.map(|(_, eval)| eval.mean)
+    }
+}
+
+/// The outcome of [`Sweep::run`]
+///
+/// Displays as a table comparing the trials.
+#[derive(Debug, Clone)]
+pub struct SweepResults {
+    /// Every trial, in order
+    pub trials: Vec<Trial>,
+}
+
+impl SweepResults {
+    /// The trial with the highest [score](Trial::score), if any trial has one
+    pub fn best(&self) -> Option<&Trial> {
+        self.trials
+            .iter()
+            .filter_map(|trial| Some((trial, trial.score()?)))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(trial, _)| trial)
+    }
+
+    /// The Pareto front of the trials, e.g. of the mean return of each objective of their final policies
+    ///
+    /// `objectives` maps a trial to the point it reached, or `None` to leave it out, e.g. if it failed.
+    pub fn pareto_front(
+        &self,
+        objectives: impl Fn(&Trial) -> Option<Vec<f64>>,
+    ) -> ParetoFront<&Trial> {
+        let mut front = ParetoFront::new();
+        for trial in &self.trials {
+            if let Some(point) = objectives(trial) {
+                front.insert(trial, point);
+            }
+        }
+        front
+    }
+
+    /// The column names of the comparison and one row of cells per trial
+    fn table(&self) -> (Vec<String>, Vec<Vec<String>>) {
+        let params = self
+            .trials
+            .first()
+            .map(|trial| trial.params.iter().map(|(path, _)| path.clone()).collect())
+            .unwrap_or_else(Vec::new);
+
+        let mut header = vec!["trial".to_string()];
+        header.extend(params);
+        header.extend(
+            [
+                "episodes",
+                "steps",
+                "best_episode",
+                "eval_mean",
+                "eval_std",
+                "status",
+            ]
+            .map(String::from),
+        );
+
+        let rows = self
+            .trials
+            .iter()
+            .map(|trial| {
+                let mut row = vec![trial.index.to_string()];
+                row.extend(trial.params.iter().map(|(_, value)| display_value(value)));
+                match &trial.result {
+                    Ok(summary) => {
+                        let best = summary.best;
+                        row.extend([
+                            summary.episodes.to_string(),
+                            summary.steps.to_string(),
+                            best.map_or(String::new(), |(episode, _)| episode.to_string()),
+                            best.map_or(String::new(), |(_, eval)| eval.mean.to_string()),
+                            best.map_or(String::new(), |(_, eval)| eval.std.to_string()),
+                            summary
+                                .stopped
+                                .clone()
+                                .unwrap_or_else(|| "done".to_string()),
+                        ]);
+                    }
+                    Err(e) => {
+                        row.extend(std::iter::repeat(String::new()).take(5));
+                        row.push(format!("failed: {e}"));
+                    }
+                }
+                row
+            })
+            .collect();
+
+        (header, rows)
+    }
+
+    /// Write the comparison of the trials to a CSV file at `path`
+    ///
+    /// The columns are the trial index, one per swept parameter, then the episode and step counts, the best
+    /// evaluation and how the trial ended.
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        let (header, rows) = self.table();
+        for row in std::iter::once(header).chain(rows) {
+            let cells = row.iter().map(|cell| escape_csv(cell)).collect::<Vec<_>>();
+            writeln!(writer, "{}", cells.join(","))?;
+        }
+        writer.flush()
+    }
+}
+
+impl fmt::Display for SweepResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (header, rows) = self.table();
+        let widths = header
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                rows.iter()
+                    .map(|row| row[i].chars().count())
+                    .fold(name.chars().count(), usize::max)
+            })
+            .collect::<Vec<_>>();
+
+        for row in std::iter::once(&header).chain(&rows) {
+            let cells = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, &width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", cells.join("  ").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// A copy of `base` with the value of every parameter path replaced
+fn apply_params(
+    base: &ExperimentConfig,
+    params: &[(String, Value)],
+) -> io::Result<ExperimentConfig> {
+    let mut value = serde_json::to_value(base)?;
+    for (path, param) in params {
+        set_path(&mut value, path, param.clone())?;
+    }
+    serde_json::from_value(value).map_err(invalid_input)
+}
+
+/// Replace the value at the dotted `path` in `config`, creating tables for missing optional sections
+fn set_path(config: &mut Value, path: &str, value: Value) -> io::Result<()> {
+    let mut target = config;
+    for key in path.split('.') {
+        if target.is_null() {
+            *target = Value::Object(Default::default());
+        }
+        target = target
+            .as_object_mut()
+            .ok_or_else(|| invalid_input(format!("`{path}` is not a config field")))?
+            .entry(key)
+            .or_insert(Value::Null);
+    }
+    *target = value;
+    Ok(())
+}
+
+/// The generator of the random choices of a search, derived from `seed` and `stream` or from entropy without a seed
+fn search_rng(seed: Option<u64>, stream: u64) -> StdRng {
+    match seed {
+        Some(seed) => {
+            let mut hasher = StableHasher::new();
+            hasher.write_u64(seed);
+            hasher.write_u64(stream);
+            StdRng::seed_from_u64(hasher.finish())
+        }
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// Show strings without quotes
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn invalid_input(message: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> ExperimentConfig {
+        ExperimentConfig::from_toml(
+            r#"
+            env.name = "frozen-lake"
+            algo.name = "q-learning"
+
+            [train]
+            episodes = 20
+            eval = { interval = 10, episodes = 2 }
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn grid_search() {
+        let sweep = Sweep::new(base())
+            .with_param("algo.alpha", Param::values([0.1, 0.5]))
+            .with_param("train.episodes", Param::IntRange { low: 10, high: 12 });
+
+        let params = sweep.trial_params().unwrap();
+        assert_eq!(params.len(), 6, "Every combination");
+
+        let config = sweep.trial_config(5, &params[5]).unwrap();
+        assert_eq!(config.train.episodes, 12);
+        assert!(
+            matches!(config.algo, crate::config::AlgoConfig::QLearning { alpha, .. } if alpha == 0.5)
+        );
+
+        let continuous = sweep.with_param(
+            "algo.gamma",
+            Param::Uniform {
+                low: 0.9,
+                high: 1.0,
+            },
+        );
+        assert!(continuous.trial_params().is_err(), "Can't enumerate floats");
+    }
+
+    #[test]
+    fn random_search() {
+        let sweep = Sweep::new(base())
+            .with_param(
+                "algo.gamma",
+                Param::LogUniform {
+                    low: 0.5,
+                    high: 0.9,
+                },
+            )
+            .with_search(Search::Random { trials: 4 });
+
+        let params = sweep.trial_params().unwrap();
+        assert_eq!(params.len(), 4);
+        for trial in &params {
+            let gamma = trial[0].1.as_f64().unwrap();
+            assert!((0.5..0.9).contains(&gamma));
+        }
+
+        let seeded = |seed| {
+            Sweep::new(ExperimentConfig {
+                seed: Some(seed),
+                ..base()
+            })
+            .with_param(
+                "algo.gamma",
+                Param::Uniform {
+                    low: 0.5,
+                    high: 0.9,
+                },
+            )
+            .with_search(Search::Random { trials: 4 })
+        };
+        let params = seeded(1).trial_params().unwrap();
+        assert_eq!(params, seeded(1).trial_params().unwrap(), "Reproducible");
+        assert_ne!(params, seeded(2).trial_params().unwrap());
+        assert_eq!(seeded(1).suggest(&[]), seeded(1).suggest(&[]));
+    }
+
+    #[test]
+    fn invalid_paths() {
+        let sweep = Sweep::new(base());
+        let params = [("algo.beta".to_string(), Value::from(1.0))];
+        assert!(sweep.trial_config(0, &params).is_err(), "Unknown field");
+        let params = [("algo.name.x".to_string(), Value::from(1.0))];
+        assert!(sweep.trial_config(0, &params).is_err(), "Not a table");
+    }
+
+    #[test]
+    fn run_sweep() {
+        let dir = std::env::temp_dir().join("rl_run_sweep");
+        let results = Sweep::new(base())
+            .with_param("algo.alpha", Param::values([0.1, 0.5, 0.9]))
+            .with_param("env.name", Param::values(["frozen-lake", "moon-lander"]))
+            .with_threads(2)
+            .with_out_dir(&dir)
+            .run();
+        assert!(results.is_err(), "Unknown environment");
+        let results = Sweep::new(base()).with_threads(0).run();
+        assert_eq!(
+            results.unwrap_err().kind(),
+            io::ErrorKind::InvalidInput,
+            "No threads"
+        );
+
+        let results = Sweep::new(base())
+            .with_param("algo.alpha", Param::values([0.1, 0.5, 0.9]))
+            .with_threads(2)
+            .with_out_dir(&dir)
+            .run()
+            .unwrap();
+
+        assert_eq!(results.trials.len(), 3);
+        assert!(results.trials.iter().all(|trial| trial.result.is_ok()));
+        assert!(results.best().is_some());
+        assert_eq!(results.to_string().lines().count(), 4, "Header and rows");
+
+        let csv = fs::read_to_string(dir.join("results.csv")).unwrap();
+        assert!(csv.starts_with("trial,algo.alpha,episodes,"));
+        assert!(dir.join("trial-2/config.toml").exists());
+        assert!(dir.join("trial-2/metrics.csv").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tpe_with_asha() {
+        let results = Sweep::new(base())
+            .with_param("train.episodes", Param::values([40]))
+            .with_param(
+                "algo.alpha",
+                Param::LogUniform {
+                    low: 0.01,
+                    high: 1.0,
+                },
+            )
+            .with_search(Search::Tpe {
+                trials: 6,
+                startup: 2,
+            })
+            .with_asha(Asha::new(10, 2).unwrap())
+            .with_threads(2)
+            .run()
+            .unwrap();
+
+        assert_eq!(results.trials.len(), 6);
+        for trial in &results.trials {
+            let summary = trial.result.as_ref().unwrap();
+            match &summary.stopped {
+                Some(reason) => assert!(reason.starts_with("pruned"), "{reason}"),
+                None => assert_eq!(summary.episodes, 40),
+            }
+        }
+    }
+}
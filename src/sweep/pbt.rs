@@ -0,0 +1,514 @@
+use std::{
+    fs,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use rand::{seq::SliceRandom, Rng};
+use serde_json::Value;
+
+use super::{apply_params, display_value, invalid_input, search_rng, Param, SweepResults, Trial};
+use crate::{
+    config::{CheckpointConfig, ExperimentConfig},
+    csv::escape_csv,
+    error::{check_interval, RlError},
+    train::TrainSummary,
+};
+
+/// Population-based training (PBT), which trains a population of agents in parallel and periodically replaces the
+/// worst members with perturbed copies of the best ones
+///
+/// Training is split into generations of [`interval`](Pbt::with_interval) episodes. After each generation, members
+/// are ranked by their latest evaluation. Each member in the bottom fraction *exploits* a random member of the top
+/// fraction by copying its run directory, so it resumes from that member's latest checkpoint, and *explores* by
+/// perturbing the copied parameter values. Unlike a [`Sweep`](super::Sweep), the parameters change during training,
+/// so a member ends with a schedule of values rather than a single one.
+///
+/// Each member is written to `dir/member-<index>` with the layout of [`RunDir`](crate::train::RunDir), and
+/// `dir/pbt.csv` records the parameters, score and source of every member after every generation. Evaluation must be
+/// enabled in the base config, with an interval that divides the PBT interval so every generation ends with an
+/// evaluation. Early stopping is disabled for members. Seeded members that copy another member continue with its
+/// random streams, and the initial values and the perturbations are drawn from the seed of the base config, so a
+/// seeded run is reproducible.
+///
+/// ### Example
+/// ```no_run
+/// use rl::{config::ExperimentConfig, sweep::{Param, Pbt}};
+///
+/// let base = ExperimentConfig::from_file("frozen_lake.toml").unwrap();
+/// let results = Pbt::new(base, "runs/pbt")
+///     .with_param("algo.alpha", Param::LogUniform { low: 0.01, high: 1.0 })
+///     .with_population(8)
+///     .with_interval(500)
+///     .run()
+///     .unwrap();
+///
+/// println!("{results}");
+/// ```
+pub struct Pbt {
+    base: ExperimentConfig,
+    params: Vec<(String, Param)>,
+    population: usize,
+    interval: u64,
+    truncation: f64,
+    resample_probability: f64,
+    perturbation: f64,
+    threads: usize,
+    out_dir: PathBuf,
+}
+
+/// A member of the population
+#[derive(Debug)]
+struct Member {
+    index: usize,
+    params: Vec<(String, Value)>,
+    result: Result<TrainSummary, Arc<RlError>>,
+    /// Whether the member still trains, i.e. it wasn't stopped before the end of a generation
+    active: bool,
+}
+
+impl Member {
+    /// The mean return of the latest evaluation
+    fn score(&self) -> Option<f64> {
+        self.result.as_ref().ok()?.eval.map(|eval| eval.mean)
+    }
+}
+
+impl Pbt {
+    /// Create a population-based training of variations of `base`, written to `dir`
+    ///
+    /// **Default:** 8 members trained in generations of 100 episodes, where the bottom 25% copy the top 25%, each
+    /// parameter is resampled with probability 0.25 or else perturbed by 20%, with one thread per available core
+    pub fn new(base: ExperimentConfig, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base,
+            params: Vec::new(),
+            population: 8,
+            interval: 100,
+            truncation: 0.25,
+            resample_probability: 0.25,
+            perturbation: 0.2,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            out_dir: dir.into(),
+        }
+    }
+
+    /// Vary the config value at the dotted `path`, which initial values are sampled from and perturbed within
+    pub fn with_param(mut self, path: impl Into<String>, param: Param) -> Self {
+        self.params.push((path.into(), param));
+        self
+    }
+
+    /// Set the number of members
+    ///
+    /// [`run`](Pbt::run) returns an error if `population` is less than 2
+    pub fn with_population(mut self, population: usize) -> Self {
+        self.population = population;
+        self
+    }
+
+    /// Set the number of training episodes between exploit and explore steps
+    ///
+    /// [`run`](Pbt::run) returns an error if `interval` is 0
+    pub fn with_interval(mut self, interval: u64) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set the fraction of the population at the bottom that copies the same fraction at the top
+    ///
+    /// [`run`](Pbt::run) returns an error if `truncation` is not in the interval `[0, 0.5]`
+    pub fn with_truncation(mut self, truncation: f64) -> Self {
+        self.truncation = truncation;
+        self
+    }
+
+    /// Set the probability that exploring resamples a parameter instead of perturbing it
+    ///
+    /// [`run`](Pbt::run) returns an error if `probability` is not in the interval `[0, 1]`
+    pub fn with_resample_probability(mut self, probability: f64) -> Self {
+        self.resample_probability = probability;
+        self
+    }
+
+    /// Set the relative change of perturbed parameters, which are multiplied by `1 - perturbation` or
+    /// `1 + perturbation`
+    ///
+    /// Parameters with listed [values](Param::Values) move to a neighboring value instead.
+    ///
+    /// [`run`](Pbt::run) returns an error if `perturbation` is not in the interval `[0, 1]`
+    pub fn with_perturbation(mut self, perturbation: f64) -> Self {
+        self.perturbation = perturbation;
+        self
+    }
+
+    /// Set the number of members trained at the same time
+    ///
+    /// [`run`](Pbt::run) returns an error if `threads` is 0
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// The run directory of member `index`
+    fn member_dir(&self, index: usize) -> PathBuf {
+        self.out_dir.join(format!("member-{index}"))
+    }
+
+    /// The config of member `index` with `params` applied, training until `episodes` episodes
+    fn member_config(
+        &self,
+        index: usize,
+        params: &[(String, Value)],
+        episodes: u64,
+    ) -> io::Result<ExperimentConfig> {
+        let mut config = apply_params(&self.base, params)?;
+        let dir = self.member_dir(index);
+        config.train.episodes = episodes;
+        config.train.early_stopping = None;
+        config.train.checkpoint = Some(CheckpointConfig {
+            interval: self.interval,
+            dir: dir.join("checkpoints"),
+        });
+        let logging = &mut config.logging;
+        logging.csv = None;
+        if logging.tensorboard.is_some() {
+            logging.tensorboard = Some(dir.join("tensorboard"));
+        }
+        config.seed = self.base.seed.map(|seed| seed.wrapping_add(index as u64));
+        config.run_dir = Some(dir);
+        Ok(config)
+    }
+
+    /// Train every generation, exploiting and exploring in between
+    ///
+    /// Members that fail are recorded with their error and replaced like the worst members.
+    ///
+    /// **Returns** the final parameters and training summary of every member as [trials](Trial), or an error if
+    /// a setting is out of range, evaluation is not enabled, a parameter path or value doesn't fit the config, or
+    /// the output directory can't be written
+    pub fn run(self) -> io::Result<SweepResults> {
+        check_interval("population", self.population as f32, 2.0, f32::INFINITY)?;
+        check_interval("interval", self.interval as f32, 1.0, f32::INFINITY)?;
+        check_interval("truncation", self.truncation as f32, 0.0, 0.5)?;
+        check_interval(
+            "resample_probability",
+            self.resample_probability as f32,
+            0.0,
+            1.0,
+        )?;
+        check_interval("perturbation", self.perturbation as f32, 0.0, 1.0)?;
+        check_interval("threads", self.threads as f32, 1.0, f32::INFINITY)?;
+        if self.base.train.eval.is_none() {
+            return Err(invalid_input(
+                "population-based training ranks members by evaluation, so `train.eval` must be set",
+            ));
+        }
+
+        let mut rng = search_rng(self.base.seed, 0);
+        let mut members = (0..self.population)
+            .map(|index| Member {
+                index,
+                params: self
+                    .params
+                    .iter()
+                    .map(|(path, param)| (path.clone(), param.sample(&mut rng)))
+                    .collect(),
+                result: Err(Arc::new(
+                    io::Error::other("the member has not started").into(),
+                )),
+                active: true,
+            })
+            .collect::<Vec<_>>();
+        // Catch bad paths and values before anything is trained
+        for member in &members {
+            self.member_config(member.index, &member.params, self.interval)?;
+        }
+        fs::create_dir_all(&self.out_dir)?;
+
+        let mut history = BufWriter::new(fs::File::create(self.out_dir.join("pbt.csv"))?);
+        let mut header = vec!["generation".to_string(), "member".to_string()];
+        header.extend(self.params.iter().map(|(path, _)| path.clone()));
+        header.extend(["score".to_string(), "source".to_string()]);
+        write_row(&mut history, &header)?;
+
+        let episodes = self.base.train.episodes;
+        let generations = episodes.div_ceil(self.interval);
+        for generation in 0..generations {
+            let end = ((generation + 1) * self.interval).min(episodes);
+            self.train_generation(&mut members, generation, end);
+
+            let rows = members
+                .iter()
+                .map(|member| {
+                    let mut row = vec![generation.to_string(), member.index.to_string()];
+                    row.extend(member.params.iter().map(|(_, value)| display_value(value)));
+                    row.push(
+                        member
+                            .score()
+                            .map_or(String::new(), |score| score.to_string()),
+                    );
+                    row
+                })
+                .collect::<Vec<_>>();
+            let mut sources = vec![None; members.len()];
+            if generation + 1 < generations {
+                for (member, source) in self.exploit(&members, &mut rng) {
+                    copy_dir(&self.member_dir(source), &self.member_dir(member))?;
+                    members[member].params = self.explore(&members[source].params, &mut rng);
+                    sources[member] = Some(source);
+                    tracing::info!(generation, member, source, "pbt exploit");
+                }
+            }
+            for (mut row, source) in rows.into_iter().zip(sources) {
+                row.push(source.map_or(String::new(), |source| source.to_string()));
+                write_row(&mut history, &row)?;
+            }
+            history.flush()?;
+        }
+
+        let trials = members
+            .into_iter()
+            .map(|member| {
+                Ok(Trial {
+                    index: member.index,
+                    config: self.member_config(member.index, &member.params, episodes)?,
+                    params: member.params,
+                    result: member.result,
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        let results = SweepResults { trials };
+        results.write_csv(self.out_dir.join("results.csv"))?;
+        Ok(results)
+    }
+
+    /// Train the active members until `episodes` episodes, resuming them after the first generation
+    fn train_generation(&self, members: &mut [Member], generation: u64, episodes: u64) {
+        let queue = Mutex::new(
+            members
+                .iter_mut()
+                .filter(|member| member.active)
+                .collect::<Vec<_>>(),
+        );
+        thread::scope(|s| {
+            for _ in 0..self.threads.min(self.population) {
+                s.spawn(|| loop {
+                    let next = queue.lock().unwrap().pop();
+                    let Some(member) = next else {
+                        break;
+                    };
+                    member.result = self
+                        .train_member(member, generation, episodes)
+                        .map_err(|e| Arc::new(e.into()));
+                    if let Ok(summary) = &member.result {
+                        member.active = summary.episodes >= episodes;
+                    }
+                });
+            }
+        });
+    }
+
+    /// Build the experiment of `member` and train it until `episodes` episodes
+    fn train_member(
+        &self,
+        member: &Member,
+        generation: u64,
+        episodes: u64,
+    ) -> io::Result<TrainSummary> {
+        let config = self.member_config(member.index, &member.params, episodes)?;
+        let mut experiment = config.build()?;
+        if generation > 0 {
+            experiment = experiment.resume(&self.member_dir(member.index))?;
+        }
+        Ok(experiment.train()?)
+    }
+
+    /// Choose which members copy which, by ranking the active members by their score
+    ///
+    /// **Returns** pairs of the member to replace and the member it copies
+    fn exploit(&self, members: &[Member], rng: &mut impl Rng) -> Vec<(usize, usize)> {
+        let mut ranked = members
+            .iter()
+            .filter(|member| member.active)
+            .map(|member| (member.index, member.score()))
+            .collect::<Vec<_>>();
+        ranked.sort_by(|(_, a), (_, b)| {
+            let (a, b) = (
+                a.unwrap_or(f64::NEG_INFINITY),
+                b.unwrap_or(f64::NEG_INFINITY),
+            );
+            b.total_cmp(&a)
+        });
+
+        let n = (ranked.len() as f64 * self.truncation) as usize;
+        let top = ranked[..n]
+            .iter()
+            .filter(|(_, score)| score.is_some())
+            .map(|&(index, _)| index)
+            .collect::<Vec<_>>();
+        if top.is_empty() {
+            return Vec::new();
+        }
+        ranked[ranked.len() - n..]
+            .iter()
+            .map(|&(index, _)| (index, *top.choose(rng).unwrap()))
+            .collect()
+    }
+
+    /// Resample or perturb every parameter value in `params`
+    fn explore(&self, params: &[(String, Value)], rng: &mut impl Rng) -> Vec<(String, Value)> {
+        self.params
+            .iter()
+            .zip(params)
+            .map(|((path, param), (_, value))| {
+                let value = if rng.gen_bool(self.resample_probability) {
+                    param.sample(rng)
+                } else {
+                    perturb(param, value, self.perturbation, rng)
+                };
+                (path.clone(), value)
+            })
+            .collect()
+    }
+}
+
+/// Scale `value` up or down by `perturbation` within the range of `param`, or move it to a neighboring listed value
+fn perturb(param: &Param, value: &Value, perturbation: f64, rng: &mut impl Rng) -> Value {
+    let up = rng.gen_bool(0.5);
+    let factor = if up {
+        1.0 + perturbation
+    } else {
+        1.0 - perturbation
+    };
+    match *param {
+        Param::Values(ref values) => {
+            let i = values.iter().position(|v| v == value).unwrap_or(0);
+            let i = if up {
+                (i + 1).min(values.len().saturating_sub(1))
+            } else {
+                i.saturating_sub(1)
+            };
+            values.get(i).cloned().unwrap_or(Value::Null)
+        }
+        Param::IntRange { low, high } => {
+            let x = value.as_i64().unwrap_or(low);
+            let mut y = (x as f64 * factor).round() as i64;
+            // Small integers would otherwise never change
+            if y == x {
+                y = if up { x + 1 } else { x - 1 };
+            }
+            y.clamp(low, high).into()
+        }
+        Param::Uniform { low, high } | Param::LogUniform { low, high } => {
+            let x = value.as_f64().unwrap_or(low);
+            (x * factor).clamp(low, high).into()
+        }
+    }
+}
+
+/// Replace the directory `to` with a copy of `from`
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    if to.exists() {
+        fs::remove_dir_all(to)?;
+    }
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let path = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &path)?;
+        } else {
+            fs::copy(entry.path(), path)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_row(writer: &mut impl Write, row: &[String]) -> io::Result<()> {
+    let cells = row.iter().map(|cell| escape_csv(cell)).collect::<Vec<_>>();
+    writeln!(writer, "{}", cells.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perturbation() {
+        let mut rng = rand::thread_rng();
+        let values = Param::values([1, 2, 3]);
+        for _ in 0..10 {
+            let x = perturb(&values, &Value::from(2), 0.2, &mut rng);
+            assert!(x == 1 || x == 3, "Moves to a neighbor");
+        }
+        assert_ne!(
+            perturb(
+                &Param::IntRange { low: 0, high: 5 },
+                &Value::from(2),
+                0.2,
+                &mut rng
+            ),
+            2
+        );
+        let uniform = Param::Uniform {
+            low: 0.0,
+            high: 1.0,
+        };
+        for _ in 0..10 {
+            let x = perturb(&uniform, &Value::from(0.9), 0.2, &mut rng);
+            let x = x.as_f64().unwrap();
+            assert!(
+                (x - 0.72).abs() < 1e-9 || x == 1.0,
+                "Scaled and clamped, got {x}"
+            );
+        }
+    }
+
+    #[test]
+    fn population() {
+        let dir = std::env::temp_dir().join("rl_pbt");
+        let base = ExperimentConfig::from_toml(
+            r#"
+            env.name = "frozen-lake"
+            algo.name = "q-learning"
+
+            [train]
+            episodes = 35
+            eval = { interval = 10, episodes = 2 }
+            "#,
+        )
+        .unwrap();
+        let results = Pbt::new(base, &dir)
+            .with_param(
+                "algo.alpha",
+                Param::LogUniform {
+                    low: 0.01,
+                    high: 1.0,
+                },
+            )
+            .with_population(4)
+            .with_interval(10)
+            .with_threads(2)
+            .run()
+            .unwrap();
+
+        assert_eq!(results.trials.len(), 4);
+        for trial in &results.trials {
+            let summary = trial.result.as_ref().unwrap();
+            assert_eq!(summary.episodes, 35, "Resumed after every generation");
+        }
+
+        let history = fs::read_to_string(dir.join("pbt.csv")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let rows = history.lines().skip(1).collect::<Vec<_>>();
+        assert_eq!(rows.len(), 4 * 4, "Every member after every generation");
+        assert_eq!(
+            rows.iter().filter(|row| !row.ends_with(',')).count(),
+            3,
+            "One member copies another between generations"
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use rand_distr::StandardNormal;
+use serde_json::Value;
+
+use super::Param;
+
+/// The fraction of observations modelled as good
+const GAMMA: f64 = 0.25;
+/// The number of candidates drawn from the good model, of which the most promising is suggested
+const CANDIDATES: usize = 24;
+
+/// Suggest parameter values with a tree-structured Parzen estimator (TPE)
+///
+/// The observations are split into the best [`GAMMA`] and the rest, and each parameter gets a density estimate `l`
+/// of its good values and `g` of the others. Of [`CANDIDATES`] values drawn from `l`, the one maximizing `l / g`
+/// is suggested. Parameters are modelled independently.
+///
+/// ### Arguments
+/// - `params` - The swept parameters
+/// - `observations` - The values and scores of finished trials, higher is better
+///
+/// **Panics** if `observations` is empty
+pub(super) fn suggest(
+    params: &[(String, Param)],
+    observations: &[(&[(String, Value)], f64)],
+    rng: &mut impl Rng,
+) -> Vec<(String, Value)> {
+    assert!(!observations.is_empty(), "TPE needs observations");
+
+    let mut sorted = observations.to_vec();
+    sorted.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    let n_good = ((GAMMA * sorted.len() as f64).ceil() as usize).max(1);
+    let (good, bad) = sorted.split_at(n_good);
+
+    params
+        .iter()
+        .enumerate()
+        .map(|(i, (path, param))| {
+            let values = |trials: &[(&[(String, Value)], f64)]| {
+                trials
+                    .iter()
+                    .filter_map(|(params, _)| params.get(i).map(|(_, value)| value))
+                    .collect::<Vec<_>>()
+            };
+            let value = sample(param, &values(good), &values(bad), rng);
+            (path.clone(), value)
+        })
+        .collect()
+}
+
+/// Draw candidates for `param` from the density of the `good` values and pick the one most likely to be good
+fn sample(param: &Param, good: &[&Value], bad: &[&Value], rng: &mut impl Rng) -> Value {
+    match param {
+        Param::Values(values) => {
+            let weights = |observed: &[&Value]| {
+                values
+                    .iter()
+                    .map(|value| {
+                        let count = observed.iter().filter(|&&v| v == value).count();
+                        (count + 1) as f64 / (observed.len() + values.len()) as f64
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let (l, g) = (weights(good), weights(bad));
+            let Ok(dist) = WeightedIndex::new(&l) else {
+                return param.sample(rng);
+            };
+            let best = (0..CANDIDATES)
+                .map(|_| dist.sample(rng))
+                .max_by(|&a, &b| (l[a] / g[a]).total_cmp(&(l[b] / g[b])))
+                .unwrap_or(0);
+            values[best].clone()
+        }
+        &Param::IntRange { low, high } => {
+            let x = continuous(
+                low as f64 - 0.5,
+                high as f64 + 0.5,
+                &floats(good, |x| x),
+                &floats(bad, |x| x),
+                rng,
+            );
+            Value::from((x.round() as i64).clamp(low, high))
+        }
+        &Param::Uniform { low, high } => Value::from(continuous(
+            low,
+            high,
+            &floats(good, |x| x),
+            &floats(bad, |x| x),
+            rng,
+        )),
+        &Param::LogUniform { low, high } => Value::from(
+            continuous(
+                low.ln(),
+                high.ln(),
+                &floats(good, f64::ln),
+                &floats(bad, f64::ln),
+                rng,
+            )
+            .exp(),
+        ),
+    }
+}
+
+/// The numeric `values`, mapped with `f`
+fn floats(values: &[&Value], f: fn(f64) -> f64) -> Vec<f64> {
+    values.iter().filter_map(|v| v.as_f64()).map(f).collect()
+}
+
+/// Pick a value in `low..high` from candidates drawn from the Parzen estimator of `good`
+fn continuous(low: f64, high: f64, good: &[f64], bad: &[f64], rng: &mut impl Rng) -> f64 {
+    let l = Parzen::new(low, high, good);
+    let g = Parzen::new(low, high, bad);
+    (0..CANDIDATES)
+        .map(|_| l.sample(rng))
+        .max_by(|&a, &b| (l.pdf(a) / g.pdf(a)).total_cmp(&(l.pdf(b) / g.pdf(b))))
+        .unwrap_or(low)
+}
+
+/// A mixture of a uniform prior over `low..high` and a Gaussian around every observation
+struct Parzen<'a> {
+    low: f64,
+    high: f64,
+    points: &'a [f64],
+    bandwidth: f64,
+}
+
+impl<'a> Parzen<'a> {
+    fn new(low: f64, high: f64, points: &'a [f64]) -> Self {
+        Self {
+            low,
+            high,
+            points,
+            bandwidth: (high - low) / (1.0 + points.len() as f64).sqrt(),
+        }
+    }
+
+    fn pdf(&self, x: f64) -> f64 {
+        let prior = 1.0 / (self.high - self.low);
+        let kernels = self
+            .points
+            .iter()
+            .map(|&p| {
+                let z = (x - p) / self.bandwidth;
+                (-0.5 * z * z).exp() / (self.bandwidth * (2.0 * std::f64::consts::PI).sqrt())
+            })
+            .sum::<f64>();
+        (prior + kernels) / (1 + self.points.len()) as f64
+    }
+
+    fn sample(&self, rng: &mut impl Rng) -> f64 {
+        let component = rng.gen_range(0..=self.points.len());
+        match self.points.get(component) {
+            Some(&p) => {
+                let noise: f64 = rng.sample(StandardNormal);
+                (p + noise * self.bandwidth).clamp(self.low, self.high)
+            }
+            None => rng.gen_range(self.low..self.high),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tpe_prefers_good_regions() {
+        let params = [
+            (
+                "x".to_string(),
+                Param::Uniform {
+                    low: 0.0,
+                    high: 10.0,
+                },
+            ),
+            ("c".to_string(), Param::values(["a", "b", "c"])),
+        ];
+        // Scores peak at x = 8 and c = "b"
+        let trials = (0..40)
+            .map(|i| {
+                let x = i as f64 / 4.0;
+                let c = ["a", "b", "c"][i % 3];
+                let score = -(x - 8.0).abs() + if c == "b" { 5.0 } else { 0.0 };
+                (
+                    vec![("x".to_string(), x.into()), ("c".to_string(), c.into())],
+                    score,
+                )
+            })
+            .collect::<Vec<(Vec<(String, Value)>, f64)>>();
+        let observations = trials
+            .iter()
+            .map(|(params, score)| (params.as_slice(), *score))
+            .collect::<Vec<_>>();
+
+        let mut rng = rand::thread_rng();
+        let suggestions = (0..50)
+            .map(|_| suggest(&params, &observations, &mut rng))
+            .collect::<Vec<_>>();
+
+        let near = suggestions
+            .iter()
+            .filter(|s| (s[0].1.as_f64().unwrap() - 8.0).abs() < 2.5)
+            .count();
+        assert!(near > 35, "{near} of 50 suggestions near the optimum");
+        let b = suggestions.iter().filter(|s| s[1].1 == "b").count();
+        assert!(b > 35, "{b} of 50 suggestions in the best category");
+    }
+}
@@ -0,0 +1,414 @@
+use rand::{seq::IteratorRandom, Rng};
+
+use crate::{
+    env::{
+        AfterstateEnvironment, ContinuousAction, DiscreteActionSpace, DiscreteStateSpace,
+        Environment,
+    },
+    seed::{self, Stream},
+    traits::Agent,
+};
+
+/// The step limit of the greedy episodes run by the assertions, so policies that never finish an episode fail
+/// instead of hanging the test
+pub const MAX_STEPS: u64 = 10_000;
+
+/// The actions of [`TwoState`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TwoStateAction {
+    /// Collect the reward of the current state, ending the episode
+    Stay,
+    /// Move to the other state
+    Switch,
+}
+
+/// A deterministic MDP with states `0` and `1`, where the larger reward is one step away
+///
+/// Episodes start in state `0`. Staying ends the episode with a reward of `0.5` in state `0` and `1` in state `1`,
+/// switching moves to the other state without a reward. For discount factors above `0.5` the optimal policy
+/// switches in state `0` and stays in state `1`, so an agent has to pass up the immediate reward.
+#[derive(Debug, Clone, Default)]
+pub struct TwoState {
+    state: u8,
+}
+
+impl TwoState {
+    /// Create the MDP in state `0`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The return of an episode following the optimal policy
+    pub fn optimal_return(&self) -> f32 {
+        1.0
+    }
+}
+
+impl Environment for TwoState {
+    type State = u8;
+    type Action = TwoStateAction;
+
+    fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
+        match action {
+            TwoStateAction::Stay => (None, [0.5, 1.0][self.state as usize]),
+            TwoStateAction::Switch => {
+                self.state = 1 - self.state;
+                (Some(self.state), 0.0)
+            }
+        }
+    }
+
+    fn reset(&mut self) -> Self::State {
+        self.state = 0;
+        self.state
+    }
+
+    fn random_action(&self) -> Self::Action {
+        self.actions()
+            .into_iter()
+            .choose(&mut seed::rng(Stream::Env))
+            .expect("There are always two actions")
+    }
+}
+
+impl DiscreteActionSpace for TwoState {
+    fn actions(&self) -> Vec<Self::Action> {
+        vec![TwoStateAction::Stay, TwoStateAction::Switch]
+    }
+}
+
+impl DiscreteStateSpace for TwoState {
+    fn states(&self) -> Vec<Self::State> {
+        vec![0, 1]
+    }
+}
+
+/// The actions of [`Corridor`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CorridorAction {
+    /// Move one cell to the left, or stay at the left end
+    Left,
+    /// Move one cell to the right
+    Right,
+}
+
+/// A deterministic corridor of cells `0..length`, from the left end to the goal at the right end
+///
+/// Episodes start in cell `0` and end on reaching the last cell. Every step gives a reward of `-1`, so the optimal
+/// policy moves right in every cell.
+#[derive(Debug, Clone)]
+pub struct Corridor {
+    pos: usize,
+    length: usize,
+}
+
+impl Corridor {
+    /// Create a corridor of `length` cells
+    ///
+    /// **Panics** if `length` is less than `2`
+    pub fn new(length: usize) -> Self {
+        assert!(length >= 2, "A corridor needs at least 2 cells");
+        Self { pos: 0, length }
+    }
+
+    /// The return of an episode following the optimal policy
+    pub fn optimal_return(&self) -> f32 {
+        -((self.length - 1) as f32)
+    }
+}
+
+impl Default for Corridor {
+    fn default() -> Self {
+        Self::new(5)
+    }
+}
+
+impl Environment for Corridor {
+    type State = usize;
+    type Action = CorridorAction;
+
+    fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
+        self.pos = match action {
+            CorridorAction::Left => self.pos.saturating_sub(1),
+            CorridorAction::Right => self.pos + 1,
+        };
+        match self.pos + 1 < self.length {
+            true => (Some(self.pos), -1.0),
+            false => (None, -1.0),
+        }
+    }
+
+    fn reset(&mut self) -> Self::State {
+        self.pos = 0;
+        self.pos
+    }
+
+    fn random_action(&self) -> Self::Action {
+        self.actions()
+            .into_iter()
+            .choose(&mut seed::rng(Stream::Env))
+            .expect("There are always two actions")
+    }
+}
+
+impl DiscreteActionSpace for Corridor {
+    fn actions(&self) -> Vec<Self::Action> {
+        vec![CorridorAction::Left, CorridorAction::Right]
+    }
+}
+
+impl DiscreteStateSpace for Corridor {
+    fn states(&self) -> Vec<Self::State> {
+        (0..self.length - 1).collect()
+    }
+}
+
+/// The afterstate is the cell after the move, and the whole reward belongs to the move
+impl AfterstateEnvironment for Corridor {
+    type Afterstate = usize;
+
+    fn afterstate(&self, state: &Self::State, action: &Self::Action) -> (Self::Afterstate, f32) {
+        let pos = match action {
+            CorridorAction::Left => state.saturating_sub(1),
+            CorridorAction::Right => state + 1,
+        };
+        (pos, -1.0)
+    }
+}
+
+/// A point on a line that should be moved to the origin within 10 steps
+///
+/// Actions in `[-1, 1]` move the point by half their value, and the position is clamped to `[-2, 2]`. Every step is
+/// rewarded with the negative distance of the new position to the origin, and episodes end after 10 steps. Episodes
+/// start at a fixed position, or uniformly at random in `[-2, 2]` to learn a policy for the whole line.
+#[derive(Debug, Clone)]
+pub struct Point {
+    x: f32,
+    step: u32,
+    start: Option<f32>,
+}
+
+impl Point {
+    /// Create the line with episodes starting at `x`
+    pub fn new(x: f32) -> Self {
+        Self {
+            x,
+            step: 0,
+            start: Some(x),
+        }
+    }
+
+    /// Create the line with episodes starting uniformly at random in `[-2, 2]`
+    pub fn random() -> Self {
+        Self {
+            x: 0.0,
+            step: 0,
+            start: None,
+        }
+    }
+
+    /// The current position
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+
+    /// The next state and reward of moving from `state` with `action`, without the step limit, e.g. as the ground
+    /// truth model of a planning agent
+    pub fn model(state: &[f32; 1], action: &ContinuousAction<1>) -> (Option<[f32; 1]>, f32) {
+        Self::new(state[0]).step(*action)
+    }
+}
+
+impl Environment for Point {
+    type State = [f32; 1];
+    type Action = ContinuousAction<1>;
+
+    fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
+        self.x = (self.x + 0.5 * action.0[0].clamp(-1.0, 1.0)).clamp(-2.0, 2.0);
+        self.step += 1;
+        ((self.step < 10).then_some([self.x]), -self.x.abs())
+    }
+
+    fn reset(&mut self) -> Self::State {
+        self.x = self
+            .start
+            .unwrap_or_else(|| seed::rng(Stream::Env).gen_range(-2.0..=2.0));
+        self.step = 0;
+        [self.x]
+    }
+
+    fn random_action(&self) -> Self::Action {
+        ContinuousAction([seed::rng(Stream::Env).gen_range(-1.0..=1.0)])
+    }
+}
+
+/// Run one episode in `env` following the greedy [`policy`](Agent::policy) of `agent`, without learning
+///
+/// **Returns** the return of the episode, or `None` if it didn't end within [`MAX_STEPS`] steps
+pub fn greedy_return<E: Environment, A: Agent<E>>(agent: &A, env: &mut E) -> Option<f32> {
+    let mut state = env.reset();
+    agent.reset_policy();
+    let mut total = 0.0;
+    for _ in 0..MAX_STEPS {
+        let action = agent.policy(env, &state);
+        let (next, reward) = env.step(action);
+        total += reward;
+        match next {
+            Some(next) => state = next,
+            None => return Some(total),
+        }
+    }
+    None
+}
+
+/// Train `agent` in `env` and assert that its greedy policy then reaches the `expected` return
+///
+/// Runs are reproducible if the seeds are applied first, e.g. with `Seeds::new(0).apply()`.
+///
+/// ### Arguments
+/// - `agent` - The agent to train
+/// - `env` - The environment to train and evaluate in
+/// - `episodes` - The number of training episodes
+/// - `expected` - The return of the greedy episode after training, e.g. the optimal return of the environment
+/// - `tolerance` - The largest accepted difference from `expected`
+///
+/// **Panics** if the return differs from `expected` by more than `tolerance`, or the greedy episode doesn't end
+#[track_caller]
+pub fn assert_converges_to<E: Environment, A: Agent<E>>(
+    agent: &mut A,
+    env: &mut E,
+    episodes: u64,
+    expected: f32,
+    tolerance: f32,
+) {
+    for _ in 0..episodes {
+        agent.go(env);
+    }
+    match greedy_return(agent, env) {
+        Some(total) => assert!(
+            (total - expected).abs() <= tolerance,
+            "greedy return {total} after {episodes} episodes, expected {expected} ± {tolerance}"
+        ),
+        None => {
+            panic!("greedy episode didn't end within {MAX_STEPS} steps after {episodes} episodes")
+        }
+    }
+}
+
+/// Assert that the greedy [`policy`](Agent::policy) of `agent` chooses the expected action in every listed state
+///
+/// **Panics** with every state where the action differs
+#[track_caller]
+pub fn assert_policy_equals<E, A>(agent: &A, env: &E, expected: &[(E::State, E::Action)])
+where
+    E: Environment,
+    E::Action: PartialEq,
+    A: Agent<E>,
+{
+    let mismatches = expected
+        .iter()
+        .filter_map(|(state, action)| {
+            let chosen = agent.policy(env, state);
+            (chosen != *action).then(|| format!("{state:?}: chose {chosen:?}, expected {action:?}"))
+        })
+        .collect::<Vec<_>>();
+    assert!(
+        mismatches.is_empty(),
+        "policy differs in {} of {} states\n{}",
+        mismatches.len(),
+        expected.len(),
+        mismatches.join("\n")
+    );
+}
+
+/// An agent that always chooses the same action, e.g. to check an environment's returns
+pub use crate::baselines::ConstantAgent as FixedAgent;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        algo::tabular::{
+            q_table::{QTableAgent, QTableAgentConfig},
+            Hashable,
+        },
+        decay,
+        exploration::EpsilonGreedy,
+        seed::Seeds,
+    };
+
+    fn q_table<E>() -> QTableAgent<E>
+    where
+        E: Environment + DiscreteActionSpace,
+        E::State: Hashable,
+        E::Action: Hashable,
+    {
+        QTableAgent::new(QTableAgentConfig {
+            exploration: EpsilonGreedy::new(decay::Exponential::new(0.01, 1.0, 0.1).unwrap()),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn fixed_returns() {
+        let mut env = TwoState::new();
+        let stay = FixedAgent(TwoStateAction::Stay);
+        assert_eq!(greedy_return(&stay, &mut env), Some(0.5));
+        let switch = FixedAgent(TwoStateAction::Switch);
+        assert_eq!(greedy_return(&switch, &mut env), None, "Never ends");
+
+        let mut env = Corridor::new(4);
+        let right = FixedAgent(CorridorAction::Right);
+        assert_eq!(greedy_return(&right, &mut env), Some(env.optimal_return()));
+        assert_eq!(env.optimal_return(), -3.0);
+    }
+
+    #[test]
+    fn q_table_converges() {
+        Seeds::new(0).apply();
+        let mut env = TwoState::new();
+        let expected = env.optimal_return();
+        let mut agent = q_table();
+        assert_converges_to(&mut agent, &mut env, 500, expected, 0.0);
+        let optimal = [(0, TwoStateAction::Switch), (1, TwoStateAction::Stay)];
+        assert_policy_equals(&agent, &env, &optimal);
+
+        let mut env = Corridor::default();
+        let expected = env.optimal_return();
+        let mut agent = q_table();
+        assert_converges_to(&mut agent, &mut env, 500, expected, 0.0);
+        let optimal = env
+            .states()
+            .into_iter()
+            .map(|state| (state, CorridorAction::Right))
+            .collect::<Vec<_>>();
+        assert_policy_equals(&agent, &env, &optimal);
+    }
+
+    #[test]
+    fn f64_q_table_converges() {
+        Seeds::new(0).apply();
+        let mut env = Corridor::default();
+        let expected = env.optimal_return();
+        let mut agent = QTableAgent::<_, f64>::with_precision(QTableAgentConfig {
+            exploration: EpsilonGreedy::new(decay::Exponential::new(0.01, 1.0, 0.1).unwrap()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_converges_to(&mut agent, &mut env, 500, expected, 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "policy differs in 1 of 2 states")]
+    fn policy_mismatch() {
+        let env = Corridor::new(3);
+        assert_policy_equals(
+            &FixedAgent(CorridorAction::Left),
+            &env,
+            &[(0, CorridorAction::Left), (1, CorridorAction::Right)],
+        );
+    }
+}
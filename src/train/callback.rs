@@ -0,0 +1,75 @@
+use std::{ops::ControlFlow, path::Path};
+
+use super::Evaluation;
+use crate::{env::Environment, memory::Exp};
+
+/// Hooks into the [`Trainer`](super::Trainer) loop for custom logging, curriculum switching, early stopping and
+/// similar extensions
+///
+/// Every hook does nothing by default, so implementations only override the ones they need. Hooks returning
+/// [`ControlFlow::Break`] stop training with the given reason, which is reported in
+/// [`TrainSummary::stopped`](super::TrainSummary::stopped).
+///
+/// ### Generics
+/// - `E` - The [`Environment`] being trained in
+pub trait Callback<E: Environment> {
+    /// Called after every training step, before the agent learns from `experience`
+    ///
+    /// ### Arguments
+    /// - `env` - The environment after the step
+    /// - `step` - The total number of steps taken, including this one
+    /// - `experience` - The transition of this step
+    fn on_step(&mut self, _env: &mut E, _step: u64, _experience: &Exp<E>) -> ControlFlow<String> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called after every training episode, once its metrics have been reported
+    ///
+    /// ### Arguments
+    /// - `env` - The environment, e.g. to change its parameters for the next episode
+    /// - `episode` - The index of the finished episode
+    /// - `metrics` - The metrics reported for the episode
+    fn on_episode_end(
+        &mut self,
+        _env: &mut E,
+        _episode: u64,
+        _metrics: &[(&str, f64)],
+    ) -> ControlFlow<String> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called after every evaluation, once its metrics have been reported
+    ///
+    /// ### Arguments
+    /// - `episode` - The index of the training episode after which the evaluation ran
+    /// - `eval` - The result of the evaluation
+    fn on_eval(&mut self, _episode: u64, _eval: Evaluation) -> ControlFlow<String> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called at the start of every evaluation episode, after the environment is reset
+    ///
+    /// ### Arguments
+    /// - `env` - The evaluation environment
+    /// - `eval_episode` - The index of the episode within the evaluation
+    /// - `state` - The initial state
+    fn on_eval_episode_start(&mut self, _env: &E, _eval_episode: u64, _state: &E::State) {}
+
+    /// Called after every step of an evaluation episode
+    ///
+    /// ### Arguments
+    /// - `env` - The evaluation environment after the step
+    /// - `experience` - The transition of this step
+    fn on_eval_step(&mut self, _env: &E, _experience: &Exp<E>) {}
+
+    /// Called at the end of every evaluation episode, whether it terminated or was truncated
+    fn on_eval_episode_end(&mut self, _env: &E) {}
+
+    /// Called after the agent learns from an experience, with the total number of steps taken
+    fn on_train_batch(&mut self, _step: u64) -> ControlFlow<String> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called after a checkpoint of the agent has been written to `path` at the end of `episode`
+    fn on_checkpoint(&mut self, _path: &Path, _episode: u64) {}
+}
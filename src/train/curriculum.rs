@@ -0,0 +1,179 @@
+use std::fmt::{self, Debug};
+
+/// When a [`Curriculum`] moves on from a stage
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Advance {
+    /// After this many training episodes in the stage
+    Episodes(u64),
+    /// Once the mean return of an evaluation in the stage reaches this value
+    ///
+    /// Evaluations only run if they are enabled with [`Trainer::with_eval`](super::Trainer::with_eval).
+    EvalReturn(f64),
+}
+
+/// A sequence of environment settings of increasing difficulty, advanced as training progresses
+///
+/// Each stage changes the environment with a function, e.g. to grow a gridworld or increase the mass of a pole,
+/// and advances to the next stage once its [`Advance`] condition is met. The last stage lasts until training ends.
+/// Used with [`Trainer::with_curriculum`](super::Trainer::with_curriculum), which applies every stage to both the
+/// training and evaluation environments.
+///
+/// ```ignore
+/// let curriculum = Curriculum::new()
+///     .with_stage("light", Advance::EvalReturn(195.0), |env: &mut CartPole| env.set_pole_mass(0.1))
+///     .with_stage("heavy", Advance::Episodes(500), |env| env.set_pole_mass(0.5));
+/// ```
+///
+/// ### Generics
+/// - `E` - The environment the stages change
+pub struct Curriculum<E> {
+    stages: Vec<Stage<E>>,
+    current: usize,
+    entered: u64,
+}
+
+struct Stage<E> {
+    name: String,
+    advance: Advance,
+    apply: Box<dyn FnMut(&mut E)>,
+}
+
+impl<E> Curriculum<E> {
+    /// Create a curriculum without stages
+    pub fn new() -> Self {
+        Self {
+            stages: Vec::new(),
+            current: 0,
+            entered: 0,
+        }
+    }
+
+    /// Add a stage after the existing ones
+    ///
+    /// ### Arguments
+    /// - `name` - The name of the stage, for logging
+    /// - `advance` - When to move on to the next stage, ignored for the last stage
+    /// - `apply` - Changes the environment to the settings of the stage
+    pub fn with_stage(
+        mut self,
+        name: impl Into<String>,
+        advance: Advance,
+        apply: impl FnMut(&mut E) + 'static,
+    ) -> Self {
+        self.stages.push(Stage {
+            name: name.into(),
+            advance,
+            apply: Box::new(apply),
+        });
+        self
+    }
+
+    /// The index of the current stage
+    pub fn stage(&self) -> usize {
+        self.current
+    }
+
+    /// The name of the current stage
+    ///
+    /// **Panics** if the curriculum has no stages
+    pub fn name(&self) -> &str {
+        &self.stages[self.current].name
+    }
+
+    /// The number of stages
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Whether the curriculum has no stages
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// The current stage and the number of training episodes before it was entered, to resume from
+    pub(super) fn progress(&self) -> (usize, u64) {
+        (self.current, self.entered)
+    }
+
+    /// Continue from the `stage` entered after `entered` training episodes
+    pub(super) fn restore(&mut self, (stage, entered): (usize, u64)) {
+        self.current = stage.min(self.stages.len().saturating_sub(1));
+        self.entered = entered;
+    }
+
+    /// Change `env` to the settings of the current stage
+    pub(super) fn apply(&mut self, env: &mut E) {
+        if let Some(stage) = self.stages.get_mut(self.current) {
+            (stage.apply)(env);
+        }
+    }
+
+    /// Move on to the next stage if the current one is complete
+    ///
+    /// ### Arguments
+    /// - `episodes` - The number of training episodes finished
+    /// - `eval` - The mean return of the evaluation after the last episode, if one ran
+    ///
+    /// **Returns** whether the curriculum advanced
+    pub(super) fn advance(&mut self, episodes: u64, eval: Option<f64>) -> bool {
+        if self.current + 1 >= self.stages.len() {
+            return false;
+        }
+        let complete = match self.stages[self.current].advance {
+            Advance::Episodes(n) => episodes - self.entered >= n,
+            Advance::EvalReturn(target) => eval.is_some_and(|mean| mean >= target),
+        };
+        if complete {
+            self.current += 1;
+            self.entered = episodes;
+        }
+        complete
+    }
+}
+
+impl<E> Default for Curriculum<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> Debug for Curriculum<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stages = self
+            .stages
+            .iter()
+            .map(|stage| (&stage.name, stage.advance))
+            .collect::<Vec<_>>();
+        f.debug_struct("Curriculum")
+            .field("stages", &stages)
+            .field("current", &self.current)
+            .field("entered", &self.entered)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advance() {
+        let mut curriculum = Curriculum::new()
+            .with_stage("a", Advance::Episodes(3), |x: &mut u32| *x = 1)
+            .with_stage("b", Advance::EvalReturn(10.0), |x| *x = 2)
+            .with_stage("c", Advance::Episodes(1), |x| *x = 3);
+
+        assert!(!curriculum.advance(2, Some(100.0)));
+        assert!(curriculum.advance(3, None), "Advances after 3 episodes");
+        assert!(!curriculum.advance(4, None));
+        assert!(!curriculum.advance(5, Some(9.0)));
+        assert!(curriculum.advance(6, Some(10.0)), "Advances at the target");
+        assert!(!curriculum.advance(100, None), "The last stage never ends");
+        assert_eq!(curriculum.name(), "c");
+
+        let mut x = 0;
+        curriculum.restore((1, 3));
+        curriculum.apply(&mut x);
+        assert_eq!((curriculum.stage(), x), (1, 2));
+    }
+}
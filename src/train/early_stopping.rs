@@ -0,0 +1,78 @@
+/// Configuration for stopping training once evaluations reach a target or stop improving
+///
+/// Used with [`Trainer::with_early_stopping`](super::Trainer::with_early_stopping), which requires evaluation to be
+/// enabled.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct EarlyStopping {
+    /// Stop once the mean evaluation return reaches this value
+    ///
+    /// **Default:** `None`
+    pub target: Option<f64>,
+    /// Stop after this many evaluations in a row without improvement
+    ///
+    /// **Default:** `Some(10)`
+    pub patience: Option<u64>,
+    /// The amount by which the mean evaluation return must exceed the best so far to count as an improvement
+    ///
+    /// **Default:** `0.0`
+    pub min_delta: f64,
+}
+
+impl Default for EarlyStopping {
+    fn default() -> Self {
+        Self {
+            target: None,
+            patience: Some(10),
+            min_delta: 0.0,
+        }
+    }
+}
+
+impl EarlyStopping {
+    /// Decide whether to stop after an evaluation
+    ///
+    /// ### Arguments
+    /// - `mean` - The mean return of the evaluation
+    /// - `evals_since_best` - The number of evaluations since the best one, 0 if this evaluation is the best
+    ///
+    /// **Returns** the reason to stop, if training should stop
+    pub(super) fn check(&self, mean: f64, evals_since_best: u64) -> Option<String> {
+        if let Some(target) = self.target.filter(|&target| mean >= target) {
+            return Some(format!(
+                "mean evaluation return {mean} reached target {target}"
+            ));
+        }
+        self.patience
+            .filter(|&patience| evals_since_best >= patience)
+            .map(|patience| format!("no improvement in {patience} evaluations"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn early_stopping_check() {
+        let early_stopping = EarlyStopping {
+            target: Some(10.0),
+            patience: Some(2),
+            ..Default::default()
+        };
+
+        assert_eq!(early_stopping.check(5.0, 1), None);
+        assert!(early_stopping.check(10.0, 0).is_some(), "Target reached");
+        assert!(early_stopping.check(5.0, 2).is_some(), "Out of patience");
+
+        let never = EarlyStopping {
+            patience: None,
+            ..Default::default()
+        };
+        assert_eq!(never.check(1e9, 1000), None);
+    }
+}
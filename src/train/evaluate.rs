@@ -0,0 +1,217 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    env::Environment,
+    seed::Seeds,
+    stats::{iqm, mean, median, quantile},
+    traits::Agent,
+};
+
+/// The number of resamples of the bootstrap confidence intervals
+const RESAMPLES: usize = 2000;
+
+/// A statistic of the returns of an [`evaluate`] run, with its 95% bootstrap confidence interval
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Estimate {
+    /// The statistic of the returns of every seed
+    pub value: f64,
+    /// The lower bound of the confidence interval
+    pub low: f64,
+    /// The upper bound of the confidence interval
+    pub high: f64,
+}
+
+/// The returns of greedy evaluations over several seeds, summarized as recommended by Agarwal et al., 2021, *Deep
+/// Reinforcement Learning at the Edge of the Statistical Precipice*
+///
+/// Returns vary a lot between seeds, so a single run says little about an agent. The interquartile mean (IQM)
+/// ignores the best and worst quarter of the seeds, which makes it robust to outliers like the median while using
+/// more of the data.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeedEvaluation {
+    /// The mean return of each seed, in the order of the seeds
+    pub returns: Vec<f64>,
+    /// The mean of the seeds' returns
+    pub mean: Estimate,
+    /// The median of the seeds' returns
+    pub median: Estimate,
+    /// The interquartile mean of the seeds' returns, the mean of the middle half
+    pub iqm: Estimate,
+}
+
+/// Run the greedy [`policy`](Agent::policy) of `agent` for `episodes` episodes with each of `seeds` seeds, in
+/// parallel and without learning
+///
+/// Every seed `i` runs on its own thread in an environment created with `env_factory(i)`, after
+/// [`Seeds::new(i)`](Seeds::new) was applied to the thread, so the evaluation is reproducible. Episodes are truncated
+/// after the agent's [`max_episode_steps`](Agent::max_episode_steps). The confidence intervals are percentile
+/// intervals over resamples of the seeds, drawn from a fixed seed so the same returns always give the same
+/// intervals.
+///
+/// The agent is shared between the threads, so it has to be [`Sync`]. Agents that keep the state of their policy in
+/// a `RefCell`, e.g. recurrent agents, can be evaluated one seed at a time with
+/// [`Trainer::evaluate`](super::Trainer::evaluate) instead.
+///
+/// ```ignore
+/// let evaluation = evaluate(&agent, |_| CartPole::new(RenderMode::None), 10, 20);
+/// println!("IQM {:.1} [{:.1}, {:.1}]", evaluation.iqm.value, evaluation.iqm.low, evaluation.iqm.high);
+/// ```
+///
+/// **Panics** if `seeds` is zero
+pub fn evaluate<E, A, F>(agent: &A, env_factory: F, episodes: u64, seeds: u64) -> SeedEvaluation
+where
+    E: Environment,
+    A: Agent<E> + Sync,
+    F: Fn(u64) -> E + Sync,
+{
+    assert!(seeds > 0, "Evaluations need at least one seed");
+    let threads = thread::available_parallelism()
+        .map_or(1, |threads| threads.get() as u64)
+        .min(seeds);
+    let next = AtomicU64::new(0);
+    let returns = Mutex::new(vec![0.0; seeds as usize]);
+    thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| loop {
+                let seed = next.fetch_add(1, Ordering::Relaxed);
+                if seed >= seeds {
+                    break;
+                }
+                Seeds::new(seed).apply();
+                let mut env = env_factory(seed);
+                let ret = run_episodes(agent, &mut env, episodes);
+                returns.lock().unwrap()[seed as usize] = ret;
+            });
+        }
+    });
+    summarize(returns.into_inner().unwrap())
+}
+
+/// The mean return of `episodes` greedy episodes of `agent` in `env`
+fn run_episodes<E: Environment, A: Agent<E>>(agent: &A, env: &mut E, episodes: u64) -> f64 {
+    let max_steps = agent.max_episode_steps();
+    let mut total = 0.0;
+    for _ in 0..episodes {
+        let mut next_state = Some(env.reset());
+        agent.reset_policy();
+        let mut steps = 0;
+        while let Some(state) = next_state {
+            if max_steps.is_some_and(|max| steps >= max) {
+                break;
+            }
+            let action = agent.policy(env, &state);
+            let (next, reward) = env.step(action);
+            next_state = next;
+            total += reward as f64;
+            steps += 1;
+        }
+    }
+    total / episodes.max(1) as f64
+}
+
+/// Summarize the returns of every seed with bootstrap confidence intervals
+fn summarize(returns: Vec<f64>) -> SeedEvaluation {
+    let [mean, median, iqm] = bootstrap(&returns, [mean, median, iqm]);
+    SeedEvaluation {
+        returns,
+        mean,
+        median,
+        iqm,
+    }
+}
+
+/// Estimate `statistics` of `values` with 95% percentile bootstrap confidence intervals
+///
+/// The statistics are computed from sorted values. Resamples are drawn from a fixed seed, so the same values always
+/// give the same intervals.
+///
+/// **Panics** if `values` is empty
+pub(crate) fn bootstrap<const N: usize>(
+    values: &[f64],
+    statistics: [fn(&[f64]) -> f64; N],
+) -> [Estimate; N] {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut resampled = [(); N].map(|_| Vec::with_capacity(RESAMPLES));
+    let mut sample = vec![0.0; values.len()];
+    for _ in 0..RESAMPLES {
+        for value in &mut sample {
+            *value = values[rng.gen_range(0..values.len())];
+        }
+        sample.sort_unstable_by(f64::total_cmp);
+        for (statistic, resampled) in statistics.iter().zip(&mut resampled) {
+            resampled.push(statistic(&sample));
+        }
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable_by(f64::total_cmp);
+    let mut resampled = resampled.into_iter();
+    statistics.map(|statistic| {
+        let mut resampled = resampled.next().unwrap();
+        resampled.sort_unstable_by(f64::total_cmp);
+        Estimate {
+            value: statistic(&sorted),
+            low: quantile(&resampled, 0.025),
+            high: quantile(&resampled, 0.975),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FixedAgent;
+
+    /// Episodes of one step with a reward of `seed²`
+    struct Seeded(u64);
+
+    impl Environment for Seeded {
+        type State = ();
+        type Action = ();
+
+        fn step(&mut self, _action: Self::Action) -> (Option<Self::State>, f32) {
+            (None, (self.0 * self.0) as f32)
+        }
+
+        fn reset(&mut self) -> Self::State {}
+
+        fn random_action(&self) -> Self::Action {}
+    }
+
+    #[test]
+    fn evaluate_seeds() {
+        let evaluation = evaluate(&FixedAgent(()), Seeded, 3, 8);
+        assert_eq!(
+            evaluation.returns,
+            [0.0, 1.0, 4.0, 9.0, 16.0, 25.0, 36.0, 49.0]
+        );
+        assert_eq!(evaluation.mean.value, 17.5);
+        assert_eq!(evaluation.median.value, 12.5);
+        assert_eq!(evaluation.iqm.value, 13.5, "Mean of 4, 9, 16 and 25");
+        for estimate in [evaluation.mean, evaluation.median, evaluation.iqm] {
+            assert!(estimate.low <= estimate.value && estimate.value <= estimate.high);
+            assert!(estimate.low < estimate.high);
+        }
+        assert_eq!(evaluate(&FixedAgent(()), Seeded, 3, 8), evaluation);
+
+        let single = evaluate(&FixedAgent(()), |_| Seeded(2), 1, 1);
+        assert_eq!(
+            single.iqm,
+            Estimate {
+                value: 4.0,
+                low: 4.0,
+                high: 4.0
+            }
+        );
+    }
+}
@@ -0,0 +1,1640 @@
+mod callback;
+mod curriculum;
+mod early_stopping;
+pub(crate) mod evaluate;
+mod parallel;
+mod run_dir;
+mod sample_efficiency;
+#[cfg(feature = "serde")]
+mod trajectory;
+
+pub use callback::Callback;
+pub use curriculum::{Advance, Curriculum};
+pub use early_stopping::EarlyStopping;
+pub use evaluate::{evaluate, Estimate, SeedEvaluation};
+pub use parallel::{Actor, ParallelAgent, ParallelTrainer};
+pub use run_dir::RunDir;
+pub use sample_efficiency::SampleEfficiency;
+use sample_efficiency::StepsToThreshold;
+#[cfg(feature = "serde")]
+pub use trajectory::{Trajectory, TrajectoryRecorder, TrajectoryStep};
+
+#[cfg(feature = "viz")]
+use std::sync::mpsc::{Receiver, Sender};
+use std::{
+    io,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+};
+
+use run_dir::{Manifest, Progress, Status};
+
+#[cfg(feature = "viz")]
+use crate::viz::{Control, Update};
+use crate::{
+    decay::Decay,
+    env::{Environment, Render},
+    error::{check_interval, Result, RlError},
+    logger::{CsvSink, MetricSink},
+    memory::Exp,
+    normalize::{Mode, Normalizer},
+    seed::Seeds,
+    stats::RunningMeanVar,
+    traits::{Agent, Checkpoint},
+};
+
+/// A function that extracts extra metrics from the environment at the end of each training episode
+type EpisodeMetrics<E> = Box<dyn FnMut(&mut E) -> Vec<(&'static str, f64)>>;
+
+/// Where and how often the [`Trainer`] saves checkpoints of the agent
+struct Checkpoints<A> {
+    interval: u64,
+    dir: PathBuf,
+    save: SaveFn<A>,
+}
+
+/// A function that saves a [`Checkpoint`] of the agent
+type SaveFn<A> = fn(&A, &Path) -> io::Result<()>;
+
+/// The directory the [`Trainer`] writes the run to, and what it has written so far
+struct Run<A> {
+    dir: RunDir,
+    save: SaveFn<A>,
+    manifest: Manifest,
+    started: bool,
+}
+
+/// The outcome of [`Trainer::train`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrainSummary {
+    /// The number of training episodes that were run
+    pub episodes: u64,
+    /// The total number of environment steps taken during training
+    pub steps: u64,
+    /// The result of the last evaluation, if evaluation is enabled
+    pub eval: Option<Evaluation>,
+    /// The index of the episode after which the best evaluation ran, and its result
+    pub best: Option<(u64, Evaluation)>,
+    /// Why training stopped before the episode limit, if it did
+    pub stopped: Option<String>,
+    /// The [sample efficiency](SampleEfficiency) thresholds that were reached, with the total number of steps taken
+    /// when they were first reached
+    pub steps_to_threshold: Vec<(f64, u64)>,
+}
+
+/// The returns of the greedy episodes run by [`Trainer::evaluate`]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Evaluation {
+    /// The mean return
+    pub mean: f64,
+    /// The standard deviation of the returns
+    pub std: f64,
+}
+
+/// Runs the interaction loop between an [`Agent`] and its [`Environment`]
+///
+/// Episode and step limits, periodic evaluation and metric reporting are configured with the `with_*` methods, so
+/// the same loop is used for every agent.
+///
+/// After each training episode, `return`, `steps` and the `total_steps` of the run are reported to every sink along
+/// with the agent's [`metrics`](Agent::metrics) and the metrics of
+/// [`with_episode_metrics`](Trainer::with_episode_metrics). Evaluations report `eval_return_mean` and
+/// `eval_return_std`.
+///
+/// ### Generics
+/// - `E` - The [`Environment`] to train in
+/// - `A` - The [`Agent`] to train
+pub struct Trainer<E: Environment, A: Agent<E>> {
+    env: E,
+    agent: A,
+    episodes: u64,
+    max_steps: Option<u64>,
+    max_episode_steps: Option<u64>,
+    eval_interval: Option<u64>,
+    eval_episodes: u64,
+    eval_env: Option<E>,
+    early_stopping: Option<EarlyStopping>,
+    sample_efficiency: Option<SampleEfficiency>,
+    curriculum: Option<Curriculum<E>>,
+    gamma: Option<Box<dyn Decay>>,
+    gamma_applied: Option<f32>,
+    keep_best: Option<fn(&A) -> A>,
+    best_agent: Option<A>,
+    best_checkpoint: Option<(PathBuf, SaveFn<A>)>,
+    episode_metrics: Option<EpisodeMetrics<E>>,
+    sinks: Vec<Box<dyn MetricSink>>,
+    callbacks: Vec<Box<dyn Callback<E>>>,
+    checkpoints: Option<Checkpoints<A>>,
+    seeds: Option<Seeds>,
+    normalizer: Option<Normalizer>,
+    run: Option<Run<A>>,
+    resume_from: Option<Progress>,
+    render: Option<fn(&E) -> String>,
+    #[cfg(feature = "viz")]
+    viz: Option<Sender<Update>>,
+    #[cfg(feature = "viz")]
+    control: Option<Receiver<Control>>,
+}
+
+impl<E: Environment, A: Agent<E>> Trainer<E, A> {
+    /// Create a trainer for `agent` in `env`
+    ///
+    /// **Default:** 1000 episodes without step limits, evaluation or sinks
+    pub fn new(env: E, agent: A) -> Self {
+        Self {
+            env,
+            agent,
+            episodes: 1000,
+            max_steps: None,
+            max_episode_steps: None,
+            eval_interval: None,
+            eval_episodes: 10,
+            eval_env: None,
+            early_stopping: None,
+            sample_efficiency: None,
+            curriculum: None,
+            gamma: None,
+            gamma_applied: None,
+            keep_best: None,
+            best_agent: None,
+            best_checkpoint: None,
+            episode_metrics: None,
+            sinks: Vec::new(),
+            callbacks: Vec::new(),
+            checkpoints: None,
+            seeds: None,
+            normalizer: None,
+            run: None,
+            resume_from: None,
+            render: None,
+            #[cfg(feature = "viz")]
+            viz: None,
+            #[cfg(feature = "viz")]
+            control: None,
+        }
+    }
+
+    /// Set the number of training episodes
+    pub fn with_episodes(mut self, episodes: u64) -> Self {
+        self.episodes = episodes;
+        self
+    }
+
+    /// Stop training once `steps` environment steps have been taken in total
+    ///
+    /// The episode in progress is cut short when the limit is reached.
+    pub fn with_max_steps(mut self, steps: u64) -> Self {
+        self.max_steps = Some(steps);
+        self
+    }
+
+    /// Truncate episodes after `steps` steps, instead of the agent's [`max_episode_steps`](Agent::max_episode_steps)
+    ///
+    /// The last experience of a truncated episode keeps its next state, so the agent still bootstraps from it.
+    pub fn with_max_episode_steps(mut self, steps: u64) -> Self {
+        self.max_episode_steps = Some(steps);
+        self
+    }
+
+    /// Evaluate the agent's greedy [policy](Agent::policy) every `interval` training episodes
+    ///
+    /// Training returns include exploration noise, so evaluations give a better picture of what the agent has
+    /// learned. They run in their own environment so they don't disturb the training episodes.
+    ///
+    /// ### Arguments
+    /// - `interval` - The number of training episodes between evaluations
+    /// - `episodes` - The number of episodes each evaluation runs
+    /// - `env` - The environment to evaluate in, e.g. a copy of the training environment with a fixed seed so
+    ///   evaluations are comparable
+    ///
+    /// [`train`](Trainer::train) returns [`RlError::OutOfRange`] if `interval` or `episodes` is 0
+    pub fn with_eval(mut self, interval: u64, episodes: u64, env: E) -> Self {
+        self.eval_interval = Some(interval);
+        self.eval_episodes = episodes;
+        self.eval_env = Some(env);
+        self
+    }
+
+    /// Stop training when evaluations reach a target or stop improving
+    ///
+    /// Evaluations only run if they are enabled with [`with_eval`](Trainer::with_eval).
+    pub fn with_early_stopping(mut self, early_stopping: EarlyStopping) -> Self {
+        self.early_stopping = Some(early_stopping);
+        self
+    }
+
+    /// Report the total number of steps until the mean training return first reaches each threshold of
+    /// `sample_efficiency`
+    pub fn with_sample_efficiency(mut self, sample_efficiency: SampleEfficiency) -> Self {
+        self.sample_efficiency = Some(sample_efficiency);
+        self
+    }
+
+    /// Change the training and evaluation environments according to the stages of `curriculum`
+    ///
+    /// The first stage is applied when training starts and later ones as soon as the previous stage is complete,
+    /// after the episode or evaluation that completed it. Every training episode reports the index of the stage it
+    /// ran in as `curriculum_stage`, so stage transitions show up next to the other metrics.
+    /// The current stage is saved with the run progress, so [`resume`](Trainer::resume) continues in it.
+    ///
+    /// [`train`](Trainer::train) returns [`RlError::InvalidHyperparameters`] if `curriculum` has no stages
+    pub fn with_curriculum(mut self, curriculum: Curriculum<E>) -> Self {
+        self.curriculum = Some(curriculum);
+        self
+    }
+
+    /// Schedule the agent's discount factor over the training episodes, instead of taking it from the environment's
+    /// [`discount`](Environment::discount)
+    ///
+    /// The value of `schedule` at the index of each episode is set as the agent's `gamma`
+    /// [hyperparameter](Agent::set_hyperparam) before the episode and reported as the `gamma` metric, e.g. to start
+    /// with short horizons that learn quickly and lengthen them as training progresses.
+    pub fn with_gamma(mut self, schedule: impl Decay + 'static) -> Self {
+        self.gamma = Some(Box::new(schedule));
+        self
+    }
+
+    /// Report extra metrics taken from the environment after each training episode, e.g. its [`Report`](crate::env::Report)
+    pub fn with_episode_metrics(
+        mut self,
+        metrics: impl FnMut(&mut E) -> Vec<(&'static str, f64)> + 'static,
+    ) -> Self {
+        self.episode_metrics = Some(Box::new(metrics));
+        self
+    }
+
+    /// Report metrics to `sink`
+    pub fn with_sink(mut self, sink: impl MetricSink + 'static) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    /// Invoke the hooks of `callback` during training
+    ///
+    /// Callbacks are invoked in the order they were added.
+    pub fn with_callback(mut self, callback: impl Callback<E> + 'static) -> Self {
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+
+    /// Reseed the random number generators of the training thread with `seeds` when training starts
+    ///
+    /// The seeds are logged to every sink as run parameters and written to `seeds.json` in the checkpoint directory.
+    pub fn with_seeds(mut self, seeds: Seeds) -> Self {
+        self.seeds = Some(seeds);
+        self
+    }
+
+    /// Keep the observation statistics of `normalizer` with the agent
+    ///
+    /// `normalizer` is a clone of the one the environment normalizes its observations with. It is switched to
+    /// [`Mode::Eval`] during evaluations so they don't shift the statistics, and back to [`Mode::Train`] for training
+    /// episodes. Its statistics are saved to `<checkpoint>.normalizer` next to every checkpoint and the final agent,
+    /// and restored by [`resume`](Trainer::resume) if the normalizer is set before resuming.
+    pub fn with_normalizer(mut self, normalizer: Normalizer) -> Self {
+        self.normalizer = Some(normalizer);
+        self
+    }
+
+    /// Report metrics and step counts to the viz dashboard through the [`Sender`] returned by [`viz::init`](crate::viz::init)
+    ///
+    /// Unlike other sinks, a closed dashboard does not stop training.
+    #[cfg(feature = "viz")]
+    pub fn with_viz(mut self, tx: Sender<Update>) -> Self {
+        self.viz = Some(tx);
+        self
+    }
+
+    /// Stop training early when a [`Control::Stop`] is received, e.g. from a viz [`Alert`](crate::viz::Alert), and
+    /// apply the [`Control::Nudge`]s of the agent's [hyperparameters](Agent::hyperparams) sent from the dashboard
+    ///
+    /// Controls are applied between episodes. The agent's hyperparameters are sent to the dashboard set with
+    /// [`with_viz`](Trainer::with_viz) when training starts and after every change.
+    #[cfg(feature = "viz")]
+    pub fn with_control(mut self, rx: Receiver<Control>) -> Self {
+        self.control = Some(rx);
+        self
+    }
+
+    /// Get the agent
+    pub fn agent(&self) -> &A {
+        &self.agent
+    }
+
+    /// Get the copy of the agent taken at its best evaluation, if [`with_best_agent`](Trainer::with_best_agent) is set
+    pub fn best_agent(&self) -> Option<&A> {
+        self.best_agent.as_ref()
+    }
+
+    /// Get the environment
+    pub fn env(&self) -> &E {
+        &self.env
+    }
+
+    /// Take back the environment and the agent
+    pub fn into_inner(self) -> (E, A) {
+        (self.env, self.agent)
+    }
+
+    /// Train the agent until the episode or step limit is reached, or a stop is requested
+    ///
+    /// **Returns** a [`TrainSummary`], the first error reported by a sink or raised while saving a checkpoint, an
+    /// [`RlError::OutOfRange`] error if evaluations are set up without episodes or evaluations or checkpoints have
+    /// an interval of 0, an [`RlError::InvalidHyperparameters`] error if the curriculum has no stages, or an
+    /// [`RlError::NonFinite`] error if the return of an episode is NaN or infinite
+    pub fn train(&mut self) -> Result<TrainSummary> {
+        self.validate()?;
+        self.start_run()?;
+        let result = self.train_loop();
+        #[cfg(feature = "viz")]
+        if let (Some(tx), Err(e)) = (&self.viz, &result) {
+            let _ = tx.send(Update::Failed(e.to_string()));
+        }
+        self.finish_run(&result)?;
+        result
+    }
+
+    /// Check the settings of the builder methods, which are only validated when training starts
+    fn validate(&self) -> Result<()> {
+        if let Some(interval) = self.eval_interval {
+            check_interval("eval_interval", interval as f32, 1.0, f32::INFINITY)?;
+            check_interval(
+                "eval_episodes",
+                self.eval_episodes as f32,
+                1.0,
+                f32::INFINITY,
+            )?;
+        }
+        if let Some(checkpoints) = &self.checkpoints {
+            check_interval(
+                "checkpoint_interval",
+                checkpoints.interval as f32,
+                1.0,
+                f32::INFINITY,
+            )?;
+        }
+        if self.curriculum.as_ref().is_some_and(Curriculum::is_empty) {
+            return Err(RlError::InvalidHyperparameters(String::from(
+                "a curriculum needs at least one stage",
+            )));
+        }
+        Ok(())
+    }
+
+    /// Run training episodes until a limit is reached or a stop is requested
+    fn train_loop(&mut self) -> Result<TrainSummary> {
+        let Progress {
+            mut summary,
+            mut evals_since_best,
+            curriculum,
+        } = self.resume_from.take().unwrap_or_default();
+
+        if let Some(progress) = curriculum {
+            if let Some(curriculum) = &mut self.curriculum {
+                curriculum.restore(progress);
+            }
+        }
+        self.apply_curriculum();
+        let mut steps_to_threshold = self
+            .sample_efficiency
+            .as_ref()
+            .map(|config| StepsToThreshold::new(config, &summary.steps_to_threshold));
+
+        if let Some(seeds) = &self.seeds {
+            match summary.episodes {
+                0 => seeds.apply(),
+                episodes => seeds.resumed(episodes).apply(),
+            }
+            let params = seeds.params();
+            self.sinks
+                .iter_mut()
+                .try_for_each(|sink| sink.log_config(&params))?;
+        }
+
+        #[cfg(feature = "viz")]
+        self.send_hyperparams();
+
+        while summary.episodes < self.episodes {
+            if self.max_steps.is_some_and(|max| summary.steps >= max) {
+                break;
+            }
+
+            let episode = summary.episodes;
+            let steps_before = summary.steps;
+            let gamma = self.apply_gamma(episode);
+            let ret = self.train_episode(&mut summary);
+            if !ret.is_finite() {
+                return Err(RlError::NonFinite {
+                    name: "return",
+                    episode,
+                });
+            }
+            let steps = summary.steps - steps_before;
+            summary.episodes += 1;
+
+            let mut metrics = vec![
+                ("return", ret),
+                ("steps", steps as f64),
+                ("total_steps", summary.steps as f64),
+            ];
+            metrics.extend(self.agent.metrics());
+            if let Some(gamma) = gamma {
+                metrics.push(("gamma", gamma as f64));
+            }
+            if let Some(curriculum) = &self.curriculum {
+                metrics.push(("curriculum_stage", curriculum.stage() as f64));
+            }
+            if let Some(episode_metrics) = &mut self.episode_metrics {
+                metrics.extend(episode_metrics(&mut self.env));
+            }
+            self.report(episode, &metrics)?;
+            if let Some(tracker) = &mut steps_to_threshold {
+                for (threshold, steps) in tracker.push(ret, summary.steps) {
+                    tracing::info!(episode, threshold, steps, "threshold reached");
+                    summary.steps_to_threshold.push((threshold, steps));
+                    let name = SampleEfficiency::metric(threshold);
+                    self.report(episode, &[(name.as_str(), steps as f64)])?;
+                }
+            }
+            #[cfg(feature = "viz")]
+            if let Some(tx) = &self.viz {
+                let _ = tx.send(Update::Step(summary.steps));
+            }
+
+            let mut eval_mean = None;
+            if self
+                .eval_interval
+                .is_some_and(|interval| summary.episodes % interval == 0)
+            {
+                let render = self.render.filter(|_| self.run.is_some());
+                let (eval, frames) = self.run_eval(self.eval_episodes, render);
+                if !frames.is_empty() {
+                    self.write_frames(episode, &frames)?;
+                }
+                summary.eval = Some(eval);
+                eval_mean = Some(eval.mean);
+                tracing::info!(episode, mean = eval.mean, std = eval.std, "evaluation");
+                self.report(
+                    episode,
+                    &[
+                        ("eval_return_mean", eval.mean),
+                        ("eval_return_std", eval.std),
+                    ],
+                )?;
+
+                let min_delta = self.early_stopping.as_ref().map_or(0.0, |e| e.min_delta);
+                let improved = match summary.best {
+                    Some((_, best)) => eval.mean > best.mean + min_delta,
+                    None => true,
+                };
+                if improved {
+                    summary.best = Some((episode, eval));
+                    evals_since_best = 0;
+                    self.keep_best(episode)?;
+                } else {
+                    evals_since_best += 1;
+                }
+
+                if let Some(early_stopping) = &self.early_stopping {
+                    summary.stopped = summary
+                        .stopped
+                        .take()
+                        .or(early_stopping.check(eval.mean, evals_since_best));
+                }
+                if summary.stopped.is_none() {
+                    summary.stopped = self
+                        .callbacks
+                        .iter_mut()
+                        .find_map(|callback| stop_reason(callback.on_eval(episode, eval)));
+                }
+            }
+
+            if let Some(curriculum) = &mut self.curriculum {
+                if curriculum.advance(summary.episodes, eval_mean) {
+                    tracing::info!(
+                        episode,
+                        stage = curriculum.stage(),
+                        name = curriculum.name(),
+                        "curriculum advanced"
+                    );
+                    self.apply_curriculum();
+                }
+            }
+
+            if let Some(checkpoints) = &self.checkpoints {
+                if summary.episodes % checkpoints.interval == 0 {
+                    let path = checkpoints.dir.join(format!("episode-{episode}"));
+                    std::fs::create_dir_all(&checkpoints.dir)?;
+                    if let Some(seeds) = self.seeds {
+                        std::fs::write(checkpoints.dir.join("seeds.json"), seeds.to_json())?;
+                    }
+                    (checkpoints.save)(&self.agent, &path)?;
+                    self.save_normalizer(&path)?;
+                    tracing::info!(episode, path = %path.display(), "checkpoint saved");
+                    for callback in &mut self.callbacks {
+                        callback.on_checkpoint(&path, episode);
+                    }
+                    if let Some(seeds) = self.seeds {
+                        seeds.resumed(summary.episodes).apply();
+                    }
+                    if let Some(run) = &mut self.run {
+                        run.manifest.checkpoints.push(path);
+                        run.manifest.write(&run.dir, Status::Running)?;
+                        let progress = Progress {
+                            summary: summary.clone(),
+                            evals_since_best,
+                            curriculum: self.curriculum.as_ref().map(Curriculum::progress),
+                        };
+                        run.manifest.write_progress(&run.dir, &progress)?;
+                    }
+                }
+            }
+
+            if summary.stopped.is_none() {
+                summary.stopped = self.callbacks.iter_mut().find_map(|callback| {
+                    stop_reason(callback.on_episode_end(&mut self.env, episode, &metrics))
+                });
+            }
+            if let Some(reason) = &summary.stopped {
+                tracing::info!(episode, reason = %reason, "training stopped");
+                break;
+            }
+
+            #[cfg(feature = "viz")]
+            if let Some(reason) = self.apply_controls() {
+                summary.stopped = Some(reason);
+                break;
+            }
+        }
+
+        self.sinks.iter_mut().try_for_each(|sink| sink.flush())?;
+
+        Ok(summary)
+    }
+
+    /// Apply the [`Control`] messages received since the last episode
+    ///
+    /// **Returns** the reason to stop training, if a stop was requested
+    #[cfg(feature = "viz")]
+    fn apply_controls(&mut self) -> Option<String> {
+        let rx = self.control.as_ref()?;
+        let mut changed = false;
+        while let Ok(control) = rx.try_recv() {
+            match control {
+                Control::Stop { reason } => return Some(reason),
+                Control::Nudge { name, steps } => {
+                    let hyperparams = self.agent.hyperparams();
+                    let Some(param) = hyperparams.iter().find(|param| param.name == name) else {
+                        tracing::warn!(name = %name, "the agent has no such hyperparameter");
+                        continue;
+                    };
+                    let value = param.nudged(steps);
+                    if self.agent.set_hyperparam(&name, value) {
+                        tracing::info!(name = %name, value, "hyperparameter changed");
+                        changed = true;
+                    } else {
+                        tracing::warn!(name = %name, value, "hyperparameter value rejected");
+                    }
+                }
+            }
+        }
+
+        if changed {
+            self.send_hyperparams();
+        }
+        None
+    }
+
+    /// Send the agent's current hyperparameters to the dashboard, if it has any
+    #[cfg(feature = "viz")]
+    fn send_hyperparams(&self) {
+        let hyperparams = self.agent.hyperparams();
+        if let (Some(tx), false) = (&self.viz, hyperparams.is_empty()) {
+            let _ = tx.send(Update::Hyperparams(hyperparams));
+        }
+    }
+
+    /// Run the agent's greedy [policy](Agent::policy) for `episodes` episodes without learning
+    ///
+    /// Episodes run in the evaluation environment passed to [`with_eval`](Trainer::with_eval), or in the training
+    /// environment if there is none. They are truncated the same way as during training.
+    pub fn evaluate(&mut self, episodes: u64) -> Evaluation {
+        self.run_eval(episodes, None).0
+    }
+
+    /// Run `episodes` greedy episodes, rendering every state of the first one with `render` if given
+    ///
+    /// **Returns** the evaluation and the rendered frames
+    fn run_eval(
+        &mut self,
+        episodes: u64,
+        render: Option<fn(&E) -> String>,
+    ) -> (Evaluation, Vec<String>) {
+        if let Some(normalizer) = &self.normalizer {
+            normalizer.set_mode(Mode::Eval);
+        }
+        let max_episode_steps = self.max_episode_steps.or(self.agent.max_episode_steps());
+        let env = self.eval_env.as_mut().unwrap_or(&mut self.env);
+        let mut frames = Vec::new();
+        let stats = (0..episodes)
+            .map(|i| {
+                let render = render.filter(|_| i == 0);
+                let mut ret = 0.0;
+                let mut steps = 0;
+                let mut next_state = Some(env.reset());
+                self.agent.reset_policy();
+                frames.extend(render.map(|render| render(env)));
+                if let Some(state) = &next_state {
+                    for callback in &mut self.callbacks {
+                        callback.on_eval_episode_start(env, i, state);
+                    }
+                }
+                while let Some(state) = next_state {
+                    if max_episode_steps.is_some_and(|max| steps >= max) {
+                        break;
+                    }
+                    let action = self.agent.policy(env, &state);
+                    let (next, reward) = env.step(action.clone());
+                    next_state = next;
+                    ret += reward as f64;
+                    steps += 1;
+                    frames.extend(render.map(|render| render(env)));
+                    if !self.callbacks.is_empty() {
+                        let experience = Exp {
+                            state,
+                            action,
+                            next_state: next_state.clone(),
+                            reward,
+                        };
+                        for callback in &mut self.callbacks {
+                            callback.on_eval_step(env, &experience);
+                        }
+                    }
+                }
+                for callback in &mut self.callbacks {
+                    callback.on_eval_episode_end(env);
+                }
+                ret
+            })
+            .collect::<RunningMeanVar>();
+
+        let eval = Evaluation {
+            mean: stats.mean(),
+            std: stats.std(),
+        };
+        (eval, frames)
+    }
+
+    /// Run one training episode, counting its steps in `summary`
+    ///
+    /// The episode ends early if a callback requests a stop, which is recorded in `summary`.
+    ///
+    /// **Returns** the return of the episode
+    fn train_episode(&mut self, summary: &mut TrainSummary) -> f64 {
+        let _span = tracing::info_span!("episode", episode = summary.episodes).entered();
+        if let Some(normalizer) = &self.normalizer {
+            normalizer.set_mode(Mode::Train);
+        }
+        let max_episode_steps = self.max_episode_steps.or(self.agent.max_episode_steps());
+        let mut ret = 0.0;
+        let mut steps = 0;
+        let mut next_state = Some(self.env.reset());
+
+        while let Some(state) = next_state {
+            if max_episode_steps.is_some_and(|max| steps >= max)
+                || self.max_steps.is_some_and(|max| summary.steps >= max)
+            {
+                break;
+            }
+
+            let action = self.agent.act(&self.env, &state);
+            let (next, reward) = self.env.step(action.clone());
+            next_state = next;
+            ret += reward as f64;
+            steps += 1;
+            summary.steps += 1;
+
+            let experience = Exp {
+                state,
+                action,
+                next_state: next_state.clone(),
+                reward,
+            };
+            let stop = self.callbacks.iter_mut().find_map(|callback| {
+                stop_reason(callback.on_step(&mut self.env, summary.steps, &experience))
+            });
+
+            self.agent.learn(&self.env, experience);
+            let stop = stop.or_else(|| {
+                self.callbacks
+                    .iter_mut()
+                    .find_map(|callback| stop_reason(callback.on_train_batch(summary.steps)))
+            });
+
+            if stop.is_some() {
+                summary.stopped = stop;
+                break;
+            }
+        }
+
+        self.agent.on_episode_end();
+        tracing::debug!(ret, steps, "episode finished");
+
+        ret
+    }
+
+    /// Change the training and evaluation environments to the current stage of the curriculum, if there is one
+    fn apply_curriculum(&mut self) {
+        let Some(curriculum) = &mut self.curriculum else {
+            return;
+        };
+        curriculum.apply(&mut self.env);
+        if let Some(env) = &mut self.eval_env {
+            curriculum.apply(env);
+        }
+    }
+
+    /// Set the agent's discount factor for `episode` from the schedule of [`with_gamma`](Trainer::with_gamma) or
+    /// the environment, if it changed
+    ///
+    /// **Returns** the scheduled discount factor, if there is a schedule
+    fn apply_gamma(&mut self, episode: u64) -> Option<f32> {
+        let scheduled = self
+            .gamma
+            .as_ref()
+            .map(|schedule| schedule.evaluate(episode as f32));
+        let gamma = scheduled.or_else(|| self.env.discount())?;
+        if self.gamma_applied != Some(gamma) {
+            if self.agent.set_hyperparam("gamma", gamma as f64) {
+                tracing::debug!(episode, gamma, "discount factor changed");
+            } else if self.gamma_applied.is_none() {
+                tracing::warn!(gamma, "the agent rejected the discount factor");
+            }
+            self.gamma_applied = Some(gamma);
+        }
+        scheduled
+    }
+
+    /// Keep a copy or checkpoint of the agent after a new best evaluation at the end of `episode`
+    fn keep_best(&mut self, episode: u64) -> io::Result<()> {
+        if let Some(clone) = self.keep_best {
+            self.best_agent = Some(clone(&self.agent));
+        }
+
+        if let Some((path, save)) = &self.best_checkpoint {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            save(&self.agent, path)?;
+            self.save_normalizer(path)?;
+            for callback in &mut self.callbacks {
+                callback.on_checkpoint(path, episode);
+            }
+            if let Some(run) = &mut self.run {
+                run.manifest.best = true;
+                run.manifest.write(&run.dir, Status::Running)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create the run directory, if there is one, and redirect metrics and checkpoints into it
+    ///
+    /// The metrics file and manifest are only created by the first call, so training can be continued.
+    fn start_run(&mut self) -> io::Result<()> {
+        let Some(run) = &mut self.run else {
+            return Ok(());
+        };
+        if let Some(checkpoints) = &mut self.checkpoints {
+            checkpoints.dir = run.dir.checkpoints();
+        }
+        if let Some((path, _)) = &mut self.best_checkpoint {
+            *path = run.dir.best();
+        }
+        if run.started {
+            return Ok(());
+        }
+
+        std::fs::create_dir_all(run.dir.root())?;
+        run.manifest.seeds = self.seeds;
+        run.manifest.write(&run.dir, Status::Running)?;
+        self.sinks.push(Box::new(CsvSink::new(run.dir.metrics())?));
+        run.started = true;
+        Ok(())
+    }
+
+    /// Save the final agent to the run directory, if there is one, and record how training ended
+    fn finish_run(&mut self, result: &Result<TrainSummary>) -> io::Result<()> {
+        let Some(run) = &mut self.run else {
+            return Ok(());
+        };
+        match result {
+            Ok(summary) => {
+                (run.save)(&self.agent, &run.dir.model())?;
+                if let Some(normalizer) = &self.normalizer {
+                    normalizer.save(&Normalizer::path(&run.dir.model()))?;
+                }
+                run.manifest.model = true;
+                run.manifest.write(&run.dir, Status::Finished(summary))
+            }
+            // The training error is more useful than one writing the manifest
+            Err(e) => {
+                let _ = run.manifest.write(&run.dir, Status::Failed(e));
+                Ok(())
+            }
+        }
+    }
+
+    /// Save the statistics of the normalizer, if there is one, next to the checkpoint at `path`
+    fn save_normalizer(&self, path: &Path) -> io::Result<()> {
+        match &self.normalizer {
+            Some(normalizer) => normalizer.save(&Normalizer::path(path)),
+            None => Ok(()),
+        }
+    }
+
+    /// Write the `frames` rendered during the evaluation after `episode` to the run directory
+    fn write_frames(&mut self, episode: u64, frames: &[String]) -> io::Result<()> {
+        let Some(run) = &mut self.run else {
+            return Ok(());
+        };
+        let path = run.dir.eval_frames(episode);
+        std::fs::create_dir_all(run.dir.frames())?;
+        std::fs::write(&path, frames.join("\n\n") + "\n")?;
+        run.manifest.frames.push(path);
+        Ok(())
+    }
+
+    /// Send `metrics` to every sink
+    fn report(&mut self, episode: u64, metrics: &[(&str, f64)]) -> io::Result<()> {
+        #[cfg(feature = "viz")]
+        if let Some(tx) = &mut self.viz {
+            let _ = tx.log_episode(episode, metrics);
+        }
+
+        self.sinks
+            .iter_mut()
+            .try_for_each(|sink| sink.log_episode(episode, metrics))
+    }
+}
+
+impl<E: Environment, A: Agent<E> + Clone> Trainer<E, A> {
+    /// Keep a copy of the agent whenever an evaluation improves on the best so far
+    ///
+    /// The copy is available from [`best_agent`](Trainer::best_agent), so the final agent doesn't have to be
+    /// whatever the last noisy episode produced. Evaluations only run if they are enabled with
+    /// [`with_eval`](Trainer::with_eval).
+    pub fn with_best_agent(mut self) -> Self {
+        self.keep_best = Some(A::clone);
+        self
+    }
+}
+
+impl<E: Environment, A: Agent<E> + Checkpoint> Trainer<E, A> {
+    /// Save a [`Checkpoint`] of the agent every `interval` episodes
+    ///
+    /// Checkpoints are written to `dir/episode-<episode>`, where `<episode>` is the index of the last finished
+    /// episode. `dir` is created if it does not exist.
+    ///
+    /// [`train`](Trainer::train) returns [`RlError::OutOfRange`] if `interval` is 0
+    pub fn with_checkpoints(mut self, interval: u64, dir: impl Into<PathBuf>) -> Self {
+        self.checkpoints = Some(Checkpoints {
+            interval,
+            dir: dir.into(),
+            save: A::save,
+        });
+        self
+    }
+
+    /// Save a [`Checkpoint`] of the agent to `path` whenever an evaluation improves on the best so far
+    ///
+    /// Evaluations only run if they are enabled with [`with_eval`](Trainer::with_eval).
+    pub fn with_best_checkpoint(mut self, path: impl Into<PathBuf>) -> Self {
+        self.best_checkpoint = Some((path.into(), A::save));
+        self
+    }
+
+    /// Write everything the run produces to `dir`, with the layout of [`RunDir`]
+    ///
+    /// When training starts, the directory and its `manifest.json` are created, metrics are written to
+    /// `metrics.csv`, and the checkpoints enabled with [`with_checkpoints`](Trainer::with_checkpoints) and
+    /// [`with_best_checkpoint`](Trainer::with_best_checkpoint) are redirected into the directory. When training
+    /// ends, the final agent is saved to `model` and the manifest records the outcome.
+    pub fn with_run_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.run = Some(Run {
+            dir: RunDir::new(dir),
+            save: A::save,
+            manifest: Manifest::default(),
+            started: false,
+        });
+        self
+    }
+
+    /// Continue the run in `dir` from its latest periodic checkpoint
+    ///
+    /// The run must have been written by a trainer with the same configuration, including
+    /// [`with_run_dir`](Trainer::with_run_dir) and [`with_checkpoints`](Trainer::with_checkpoints). The agent is
+    /// restored from the checkpoint along with everything its [`Checkpoint`] holds, such as exploration schedules
+    /// and, for agents that save it, the replay memory. Training continues with the episode after the checkpoint,
+    /// keeping the step count, best evaluation and early stopping patience, and the random number generators are
+    /// reseeded as they were at the checkpoint so a seeded run continues as if it was never interrupted. Metrics
+    /// of episodes after the checkpoint are dropped from `metrics.csv` before new ones are appended.
+    ///
+    /// The episode limit still counts from the start of the run. A copy of the best agent kept by
+    /// [`with_best_agent`](Trainer::with_best_agent) is not restored, but the best checkpoint is kept.
+    ///
+    /// **Returns** an error if the run has no periodic checkpoint or it can't be loaded
+    pub fn resume(mut self, dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = RunDir::new(dir);
+        let (manifest, progress) = Manifest::read_progress(&dir)?;
+        let Some(checkpoint) = manifest.checkpoints.last() else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "the run has no checkpoint to resume from",
+            ));
+        };
+        self.agent.load(checkpoint)?;
+        if let Some(normalizer) = &mut self.normalizer {
+            normalizer.load(&Normalizer::path(checkpoint))?;
+        }
+
+        self.sinks.push(Box::new(CsvSink::resume(
+            dir.metrics(),
+            progress.summary.episodes,
+        )?));
+        self.seeds = manifest.seeds.or(self.seeds);
+        self.resume_from = Some(progress);
+        self.run = Some(Run {
+            dir,
+            save: A::save,
+            manifest,
+            started: true,
+        });
+        Ok(self)
+    }
+}
+
+impl<E: Render, A: Agent<E>> Trainer<E, A> {
+    /// Render every state of the first episode of each evaluation to `frames/eval-<episode>.txt` in the run
+    /// directory, with frames separated by blank lines
+    ///
+    /// Nothing is rendered without a run directory, see [`with_run_dir`](Trainer::with_run_dir).
+    pub fn with_eval_frames(mut self) -> Self {
+        self.render = Some(E::render);
+        self
+    }
+}
+
+/// The reason to stop training, if a callback requested it
+fn stop_reason(flow: ControlFlow<String>) -> Option<String> {
+    match flow {
+        ControlFlow::Break(reason) => Some(reason),
+        ControlFlow::Continue(()) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decay;
+
+    /// Counts down from a starting state, rewarding every step
+    pub(super) struct Countdown {
+        pub(super) state: u32,
+        pub(super) start: u32,
+    }
+
+    impl Environment for Countdown {
+        type State = u32;
+        type Action = ();
+
+        fn step(&mut self, _action: Self::Action) -> (Option<Self::State>, f32) {
+            self.state -= 1;
+            ((self.state > 0).then_some(self.state), 1.0)
+        }
+
+        fn reset(&mut self) -> Self::State {
+            self.state = self.start;
+            self.state
+        }
+
+        fn random_action(&self) -> Self::Action {}
+
+        fn discount(&self) -> Option<f32> {
+            Some(0.9)
+        }
+    }
+
+    impl Render for Countdown {
+        fn render(&self) -> String {
+            self.state.to_string()
+        }
+    }
+
+    #[derive(Clone, Default)]
+    pub(super) struct CountingAgent {
+        learned: u64,
+        episodes: u64,
+        gamma: Option<f32>,
+    }
+
+    impl Agent<Countdown> for CountingAgent {
+        fn act(&mut self, _env: &Countdown, _state: &u32) {}
+
+        fn learn(&mut self, _env: &Countdown, _experience: Exp<Countdown>) {
+            self.learned += 1;
+        }
+
+        fn on_episode_end(&mut self) {
+            self.episodes += 1;
+        }
+
+        fn metrics(&mut self) -> Vec<(&'static str, f64)> {
+            vec![("learned", self.learned as f64)]
+        }
+
+        fn set_hyperparam(&mut self, name: &str, value: f64) -> bool {
+            if name != "gamma" {
+                return false;
+            }
+            self.gamma = Some(value as f32);
+            true
+        }
+
+        fn policy(&self, _env: &Countdown, _state: &u32) {}
+    }
+
+    /// Collects every reported metric
+    #[derive(Clone, Default)]
+    struct Collect(std::rc::Rc<std::cell::RefCell<Vec<(String, u64, f64)>>>);
+
+    impl MetricSink for Collect {
+        fn log_scalar(&mut self, name: &str, value: f64, step: u64) -> io::Result<()> {
+            self.0.borrow_mut().push((name.to_string(), step, value));
+            Ok(())
+        }
+    }
+
+    /// Stops training after a number of steps or episodes
+    #[derive(Default)]
+    struct StopAfter {
+        steps: Option<u64>,
+        episodes: Option<u64>,
+        evals: Option<u64>,
+        batches: u64,
+    }
+
+    impl Callback<Countdown> for StopAfter {
+        fn on_step(
+            &mut self,
+            _env: &mut Countdown,
+            step: u64,
+            _experience: &Exp<Countdown>,
+        ) -> ControlFlow<String> {
+            match self.steps {
+                Some(steps) if step >= steps => ControlFlow::Break("steps".to_string()),
+                _ => ControlFlow::Continue(()),
+            }
+        }
+
+        fn on_episode_end(
+            &mut self,
+            env: &mut Countdown,
+            episode: u64,
+            _metrics: &[(&str, f64)],
+        ) -> ControlFlow<String> {
+            // Lengthen the episodes as training progresses
+            env.start += 1;
+            match self.episodes {
+                Some(episodes) if episode + 1 >= episodes => {
+                    ControlFlow::Break(format!("batches={}", self.batches))
+                }
+                _ => ControlFlow::Continue(()),
+            }
+        }
+
+        fn on_eval(&mut self, episode: u64, eval: Evaluation) -> ControlFlow<String> {
+            match self.evals.as_mut() {
+                Some(0) => ControlFlow::Break(format!("eval={} after {episode}", eval.mean)),
+                Some(evals) => {
+                    *evals -= 1;
+                    ControlFlow::Continue(())
+                }
+                None => ControlFlow::Continue(()),
+            }
+        }
+
+        fn on_train_batch(&mut self, _step: u64) -> ControlFlow<String> {
+            self.batches += 1;
+            ControlFlow::Continue(())
+        }
+    }
+
+    /// Writes the number of finished episodes
+    impl Checkpoint for CountingAgent {
+        fn save(&self, path: &Path) -> io::Result<()> {
+            std::fs::write(path, self.episodes.to_string())
+        }
+
+        fn load(&mut self, path: &Path) -> io::Result<()> {
+            self.episodes = std::fs::read_to_string(path)?
+                .parse()
+                .map_err(io::Error::other)?;
+            Ok(())
+        }
+    }
+
+    /// Records the checkpoints that were written
+    #[derive(Clone, Default)]
+    struct Saved(std::rc::Rc<std::cell::RefCell<Vec<(PathBuf, u64)>>>);
+
+    impl Callback<Countdown> for Saved {
+        fn on_checkpoint(&mut self, path: &Path, episode: u64) {
+            self.0.borrow_mut().push((path.to_path_buf(), episode));
+        }
+    }
+
+    fn trainer(start: u32) -> Trainer<Countdown, CountingAgent> {
+        Trainer::new(Countdown { state: 0, start }, CountingAgent::default())
+    }
+
+    #[test]
+    fn episode_limit() {
+        let mut trainer = trainer(5).with_episodes(3);
+        let summary = trainer.train().unwrap();
+
+        assert_eq!(summary.episodes, 3);
+        assert_eq!(summary.steps, 15);
+        assert_eq!(trainer.agent().learned, 15, "Learns from every step");
+        assert_eq!(trainer.agent().episodes, 3, "Every episode is ended");
+    }
+
+    #[test]
+    fn step_limits() {
+        let summary = trainer(5)
+            .with_episodes(10)
+            .with_max_episode_steps(3)
+            .train()
+            .unwrap();
+        assert_eq!(summary.steps, 30, "Episodes are truncated");
+
+        let summary = trainer(5)
+            .with_episodes(10)
+            .with_max_steps(12)
+            .train()
+            .unwrap();
+        assert_eq!(summary.steps, 12, "Training stops at the step limit");
+        assert_eq!(summary.episodes, 3, "The last episode is cut short");
+    }
+
+    #[test]
+    fn metrics_and_eval() {
+        let sink = Collect::default();
+        let summary = trainer(4)
+            .with_episodes(4)
+            .with_eval(2, 3, Countdown { state: 0, start: 6 })
+            .with_episode_metrics(|env| vec![("start", env.start as f64)])
+            .with_sample_efficiency(SampleEfficiency::new(vec![4.0, 100.0]).with_window(2))
+            .with_sink(sink.clone())
+            .train()
+            .unwrap();
+
+        assert_eq!(
+            summary.eval,
+            Some(Evaluation {
+                mean: 6.0,
+                std: 0.0
+            }),
+            "Evaluates in the evaluation environment"
+        );
+
+        let metrics = sink.0.borrow();
+        let names = metrics
+            .iter()
+            .filter(|(_, episode, _)| *episode == 1)
+            .map(|(name, _, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "return",
+                "steps",
+                "total_steps",
+                "learned",
+                "start",
+                "steps_to_4",
+                "eval_return_mean",
+                "eval_return_std"
+            ]
+        );
+        assert_eq!(
+            summary.steps_to_threshold,
+            [(4.0, 8)],
+            "The mean return of the first two episodes reaches 4"
+        );
+        assert_eq!(
+            metrics
+                .iter()
+                .filter(|(name, ..)| name == "eval_return_mean")
+                .count(),
+            2,
+            "Evaluates every 2 episodes"
+        );
+
+        let result = trainer(4)
+            .with_eval(0, 3, Countdown { state: 0, start: 6 })
+            .train();
+        assert!(matches!(
+            result,
+            Err(RlError::OutOfRange {
+                name: "eval_interval",
+                ..
+            })
+        ));
+        let result = trainer(4).with_checkpoints(0, "unused").train();
+        assert!(matches!(
+            result,
+            Err(RlError::OutOfRange {
+                name: "checkpoint_interval",
+                ..
+            })
+        ));
+        let result = trainer(4).with_curriculum(Curriculum::new()).train();
+        assert!(matches!(result, Err(RlError::InvalidHyperparameters(_))));
+        assert!(!Path::new("unused").exists());
+    }
+
+    #[test]
+    fn callbacks() {
+        let summary = trainer(2)
+            .with_episodes(10)
+            .with_callback(StopAfter {
+                episodes: Some(3),
+                ..Default::default()
+            })
+            .train()
+            .unwrap();
+        assert_eq!(summary.episodes, 3, "Stops at the end of an episode");
+        assert_eq!(
+            summary.steps,
+            2 + 3 + 4,
+            "Callbacks can change the environment"
+        );
+        assert_eq!(summary.stopped.as_deref(), Some("batches=9"));
+
+        let summary = trainer(5)
+            .with_episodes(10)
+            .with_callback(StopAfter {
+                steps: Some(7),
+                ..Default::default()
+            })
+            .train()
+            .unwrap();
+        assert_eq!(summary.steps, 7, "Stops in the middle of an episode");
+        assert_eq!(summary.episodes, 2);
+        assert_eq!(summary.stopped.as_deref(), Some("steps"));
+
+        let summary = trainer(3)
+            .with_episodes(10)
+            .with_eval(2, 1, Countdown { state: 0, start: 3 })
+            .with_callback(StopAfter {
+                evals: Some(1),
+                ..Default::default()
+            })
+            .train()
+            .unwrap();
+        assert_eq!(summary.episodes, 4, "Stops after the second evaluation");
+        assert_eq!(summary.stopped.as_deref(), Some("eval=3 after 3"));
+    }
+
+    #[test]
+    fn checkpoints() {
+        let dir = std::env::temp_dir().join("rl_trainer_checkpoints");
+        let saved = Saved::default();
+        trainer(2)
+            .with_episodes(5)
+            .with_checkpoints(2, &dir)
+            .with_callback(saved.clone())
+            .with_seeds(Seeds::new(1))
+            .train()
+            .unwrap();
+        assert!(dir.join("seeds.json").exists(), "Seeds are recorded");
+
+        let saved = saved.0.borrow();
+        assert_eq!(
+            *saved,
+            [(dir.join("episode-1"), 1), (dir.join("episode-3"), 3)],
+            "Saves every 2 episodes"
+        );
+
+        let mut agent = CountingAgent::default();
+        agent.load(&saved[1].0).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            agent.episodes, 4,
+            "Checkpoints are taken after the episode ends"
+        );
+    }
+
+    #[test]
+    fn run_dir() {
+        let dir = RunDir::new(std::env::temp_dir().join("rl_trainer_run_dir"));
+        trainer(2)
+            .with_episodes(4)
+            .with_eval(2, 1, Countdown { state: 0, start: 2 })
+            .with_eval_frames()
+            .with_checkpoints(2, "unused")
+            .with_best_checkpoint("unused/best")
+            .with_run_dir(dir.root())
+            .train()
+            .unwrap();
+
+        assert!(dir.metrics().exists());
+        assert!(
+            dir.checkpoints().join("episode-3").exists() && dir.best().exists(),
+            "Checkpoints are redirected"
+        );
+        assert!(!Path::new("unused").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.eval_frames(1)).unwrap(),
+            "2\n\n1\n\n0\n",
+            "Renders every state of the first evaluation episode"
+        );
+        let mut agent = CountingAgent::default();
+        agent.load(&dir.model()).unwrap();
+        assert_eq!(agent.episodes, 4, "Saves the final agent");
+
+        let manifest = std::fs::read_to_string(dir.manifest()).unwrap();
+        std::fs::remove_dir_all(dir.root()).unwrap();
+        for field in [
+            r#""status": "finished""#,
+            r#""config": null"#,
+            r#""checkpoints": ["checkpoints/episode-1", "checkpoints/episode-3"]"#,
+            r#""frames": ["frames/eval-1.txt", "frames/eval-3.txt"]"#,
+            r#""summary": {"episodes": 4, "steps": 8"#,
+        ] {
+            assert!(manifest.contains(field), "{field} in {manifest}");
+        }
+    }
+
+    #[test]
+    fn resume() {
+        let dir = RunDir::new(std::env::temp_dir().join("rl_trainer_resume"));
+        let trainer = |episodes| {
+            trainer(2)
+                .with_episodes(episodes)
+                .with_eval(2, 1, Countdown { state: 0, start: 2 })
+                .with_checkpoints(2, "unused")
+        };
+        // Interrupted after 5 episodes, the latest checkpoint is after 4
+        trainer(5)
+            .with_seeds(Seeds::new(3))
+            .with_run_dir(dir.root())
+            .train()
+            .unwrap();
+
+        let mut resumed = trainer(8).resume(dir.root()).unwrap();
+        let summary = resumed.train().unwrap();
+        assert_eq!(
+            summary.episodes, 8,
+            "Counts episodes from the start of the run"
+        );
+        assert_eq!(summary.steps, 16);
+        assert_eq!(
+            summary.best.map(|(episode, _)| episode),
+            Some(1),
+            "Keeps the best evaluation"
+        );
+        assert_eq!(resumed.agent().episodes, 8, "Restores the agent");
+
+        let metrics = std::fs::read_to_string(dir.metrics()).unwrap();
+        let progress = std::fs::read_to_string(dir.progress()).unwrap();
+        std::fs::remove_dir_all(dir.root()).unwrap();
+        let episodes = metrics
+            .lines()
+            .filter_map(|line| line.strip_prefix("return,"))
+            .map(|row| row.split(',').next().unwrap().parse().unwrap())
+            .collect::<Vec<u64>>();
+        assert_eq!(
+            episodes,
+            (0..8).collect::<Vec<_>>(),
+            "Appends to the metrics without repeating episodes"
+        );
+        assert!(progress.contains("seeds "), "Keeps the seeds");
+        assert!(progress.contains("checkpoint checkpoints/episode-7"));
+    }
+
+    #[test]
+    fn curriculum() {
+        let curriculum = Curriculum::new()
+            .with_stage("short", Advance::Episodes(2), |env: &mut Countdown| {
+                env.start = 2
+            })
+            .with_stage("long", Advance::EvalReturn(4.0), |env| env.start = 4)
+            .with_stage("longest", Advance::Episodes(1), |env| env.start = 6);
+        let sink = Collect::default();
+        let summary = trainer(1)
+            .with_episodes(5)
+            .with_eval(1, 1, Countdown { state: 0, start: 1 })
+            .with_curriculum(curriculum)
+            .with_sink(sink.clone())
+            .train()
+            .unwrap();
+
+        assert_eq!(
+            summary.steps,
+            2 + 2 + 4 + 6 + 6,
+            "Advances after 2 episodes, then after reaching the evaluation target"
+        );
+        assert_eq!(
+            summary.eval.map(|eval| eval.mean),
+            Some(6.0),
+            "Stages are applied to the evaluation environment"
+        );
+        let stages = sink
+            .0
+            .borrow()
+            .iter()
+            .filter(|(name, ..)| name == "curriculum_stage")
+            .map(|(_, _, stage)| *stage)
+            .collect::<Vec<_>>();
+        assert_eq!(stages, [0.0, 0.0, 1.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn gamma_schedule() {
+        let mut trainer = trainer(2).with_episodes(2);
+        trainer.train().unwrap();
+        assert_eq!(
+            trainer.agent().gamma,
+            Some(0.9),
+            "Taken from the environment"
+        );
+
+        let sink = Collect::default();
+        let mut trainer = trainer(2)
+            .with_episodes(4)
+            .with_gamma(decay::Linear::new(0.25, 1.0, 0.5).unwrap())
+            .with_sink(sink.clone());
+        trainer.train().unwrap();
+        assert_eq!(trainer.agent().gamma, Some(0.5));
+        let gammas = sink
+            .0
+            .borrow()
+            .iter()
+            .filter(|(name, ..)| name == "gamma")
+            .map(|(_, _, gamma)| *gamma)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            gammas,
+            [1.0, 0.75, 0.5, 0.5],
+            "The schedule overrides the environment"
+        );
+    }
+
+    #[test]
+    fn normalizer() {
+        /// Normalizes the countdown as its observation
+        struct Normalized(Countdown, Normalizer);
+
+        impl Environment for Normalized {
+            type State = u32;
+            type Action = ();
+
+            fn step(&mut self, action: ()) -> (Option<u32>, f32) {
+                let (next, reward) = self.0.step(action);
+                self.1.normalize(&mut [self.0.state as f32]);
+                (next, reward)
+            }
+
+            fn reset(&mut self) -> u32 {
+                let state = self.0.reset();
+                self.1.normalize(&mut [state as f32]);
+                state
+            }
+
+            fn random_action(&self) {}
+        }
+
+        struct Idle;
+
+        impl Agent<Normalized> for Idle {
+            fn act(&mut self, _env: &Normalized, _state: &u32) {}
+
+            fn learn(&mut self, _env: &Normalized, _experience: Exp<Normalized>) {}
+
+            fn policy(&self, _env: &Normalized, _state: &u32) {}
+        }
+
+        impl Checkpoint for Idle {
+            fn save(&self, path: &Path) -> io::Result<()> {
+                std::fs::write(path, "")
+            }
+
+            fn load(&mut self, _path: &Path) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let dir = RunDir::new(std::env::temp_dir().join("rl_trainer_normalizer"));
+        let normalizer = Normalizer::new();
+        let env = |start| Normalized(Countdown { state: 0, start }, normalizer.clone());
+        Trainer::new(env(2), Idle)
+            .with_episodes(4)
+            .with_eval(2, 5, env(2))
+            .with_checkpoints(2, "unused")
+            .with_normalizer(normalizer.clone())
+            .with_run_dir(dir.root())
+            .train()
+            .unwrap();
+        assert_eq!(
+            normalizer.count(),
+            4 * 3,
+            "Evaluations don't update the statistics"
+        );
+        assert_eq!(normalizer.mode(), Mode::Eval);
+
+        let checkpoint = Normalizer::path(&dir.checkpoints().join("episode-3"));
+        assert!(Normalizer::path(&dir.model()).exists());
+        let mut resumed = Normalizer::new();
+        resumed.load(&checkpoint).unwrap();
+        std::fs::remove_dir_all(dir.root()).unwrap();
+        assert_eq!(resumed.count(), 12, "Saved with checkpoints");
+        assert_eq!(resumed.mean(), normalizer.mean());
+    }
+
+    #[test]
+    fn non_finite_return() {
+        /// Rewards NaN, like a diverging simulation
+        struct Nan;
+
+        impl Environment for Nan {
+            type State = ();
+            type Action = ();
+
+            fn step(&mut self, _action: ()) -> (Option<()>, f32) {
+                (None, f32::NAN)
+            }
+
+            fn reset(&mut self) {}
+
+            fn random_action(&self) {}
+        }
+
+        struct Idle;
+
+        impl Agent<Nan> for Idle {
+            fn act(&mut self, _env: &Nan, _state: &()) {}
+
+            fn learn(&mut self, _env: &Nan, _experience: Exp<Nan>) {}
+
+            fn policy(&self, _env: &Nan, _state: &()) {}
+        }
+
+        let result = Trainer::new(Nan, Idle).train();
+        assert!(
+            matches!(
+                result,
+                Err(RlError::NonFinite {
+                    name: "return",
+                    episode: 0
+                })
+            ),
+            "{result:?}"
+        );
+    }
+
+    #[test]
+    fn early_stopping() {
+        let eval_env = || Countdown { state: 0, start: 6 };
+
+        let mut early = trainer(2)
+            .with_episodes(100)
+            .with_eval(2, 1, eval_env())
+            .with_early_stopping(EarlyStopping {
+                patience: Some(2),
+                ..Default::default()
+            })
+            .with_best_agent();
+        let summary = early.train().unwrap();
+        assert_eq!(summary.episodes, 6, "Stops when evaluations plateau");
+        assert_eq!(summary.best.map(|(episode, _)| episode), Some(1));
+        assert_eq!(
+            early.best_agent().map(|agent| agent.episodes),
+            Some(2),
+            "Keeps a copy of the best agent"
+        );
+
+        let summary = trainer(2)
+            .with_episodes(100)
+            .with_eval(2, 1, eval_env())
+            .with_early_stopping(EarlyStopping {
+                target: Some(6.0),
+                ..Default::default()
+            })
+            .train()
+            .unwrap();
+        assert_eq!(summary.episodes, 2, "Stops when the target is reached");
+    }
+}
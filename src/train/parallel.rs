@@ -0,0 +1,490 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, SyncSender},
+    },
+    thread,
+};
+
+use super::{SampleEfficiency, StepsToThreshold, TrainSummary};
+use crate::{
+    env::Environment,
+    error::{check_interval, Result, RlError},
+    logger::MetricSink,
+    memory::Exp,
+    seed::Seeds,
+    traits::Agent,
+};
+
+/// A snapshot of an agent's policy that chooses actions in an actor thread of a [`ParallelTrainer`]
+///
+/// ### Generics
+/// - `E` - The [`Environment`] the actor runs in
+pub trait Actor<E: Environment> {
+    /// Choose an action in `state`, including exploration
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action;
+}
+
+/// An agent whose acting and learning can be split across threads, see [`ParallelTrainer`]
+///
+/// ### Generics
+/// - `E` - The [`Environment`] the agent learns in
+pub trait ParallelAgent<E: Environment>: Agent<E> {
+    /// The policy snapshot sent to actor threads
+    type Actor: Actor<E> + Send;
+
+    /// Take a snapshot of the current policy
+    fn actor(&self) -> Self::Actor;
+
+    /// Store an experience collected by an actor, without learning from it
+    fn remember(&mut self, experience: Exp<E>);
+
+    /// Perform one learning step on the stored experiences
+    fn train_step(&mut self);
+}
+
+/// A message from an actor thread to the learner
+enum Message<E: Environment> {
+    Step(Exp<E>),
+    EpisodeEnd { ret: f64, steps: u64 },
+}
+
+/// Trains an agent with several actor threads collecting experience and a single learner
+///
+/// Each actor runs episodes in its own environment with a snapshot of the agent's policy, and sends its transitions
+/// to the learner through a bounded queue. The learner stores them in the agent's replay memory and performs a
+/// learning step after every batch of received transitions, sending fresh policy snapshots to the actors every
+/// [`sync_interval`](ParallelTrainer::with_sync_interval) learning steps. Acting and learning overlap, which improves
+/// wall-clock throughput when environment steps or inference are expensive.
+///
+/// After each finished episode, `return`, `steps` and the `total_steps` received from all actors are reported to
+/// every sink along with the learner's [`metrics`](Agent::metrics), in the order episodes finish.
+///
+/// ### Generics
+/// - `E` - The [`Environment`] to train in, created in each actor thread
+/// - `A` - The [`ParallelAgent`] to train
+/// - `F` - The function creating the environments
+pub struct ParallelTrainer<E, A, F>
+where
+    E: Environment,
+    A: ParallelAgent<E>,
+    F: Fn() -> E + Sync,
+{
+    new_env: F,
+    agent: A,
+    actors: usize,
+    episodes: u64,
+    max_episode_steps: Option<u64>,
+    sync_interval: u64,
+    queue_capacity: usize,
+    seeds: Option<Seeds>,
+    sample_efficiency: Option<SampleEfficiency>,
+    sinks: Vec<Box<dyn MetricSink>>,
+}
+
+impl<E, A, F> ParallelTrainer<E, A, F>
+where
+    E: Environment,
+    E::State: Send,
+    E::Action: Send,
+    A: ParallelAgent<E>,
+    F: Fn() -> E + Sync,
+{
+    /// Create a trainer for `agent` with actors in environments created by `new_env`
+    ///
+    /// **Default:** 4 actors, 1000 episodes, a policy sync every 100 learning steps and a queue of 1024 transitions
+    pub fn new(new_env: F, agent: A) -> Self {
+        Self {
+            new_env,
+            agent,
+            actors: 4,
+            episodes: 1000,
+            max_episode_steps: None,
+            sync_interval: 100,
+            queue_capacity: 1024,
+            seeds: None,
+            sample_efficiency: None,
+            sinks: Vec::new(),
+        }
+    }
+
+    /// Set the number of actor threads
+    ///
+    /// [`train`](ParallelTrainer::train) returns [`RlError::OutOfRange`] if `actors` is 0
+    pub fn with_actors(mut self, actors: usize) -> Self {
+        self.actors = actors;
+        self
+    }
+
+    /// Set the total number of training episodes, across all actors
+    pub fn with_episodes(mut self, episodes: u64) -> Self {
+        self.episodes = episodes;
+        self
+    }
+
+    /// Truncate episodes after `steps` steps
+    pub fn with_max_episode_steps(mut self, steps: u64) -> Self {
+        self.max_episode_steps = Some(steps);
+        self
+    }
+
+    /// Send a fresh policy snapshot to the actors every `steps` learning steps
+    ///
+    /// [`train`](ParallelTrainer::train) returns [`RlError::OutOfRange`] if `steps` is 0
+    pub fn with_sync_interval(mut self, steps: u64) -> Self {
+        self.sync_interval = steps;
+        self
+    }
+
+    /// Set the number of transitions that can wait for the learner before actors block
+    pub fn with_queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = capacity;
+        self
+    }
+
+    /// Seed the learner thread with `seeds` and actor `i` with [`seeds.fork(i)`](Seeds::fork)
+    ///
+    /// The order in which transitions from different actors arrive still depends on thread scheduling, so runs are
+    /// only reproducible with a single actor.
+    pub fn with_seeds(mut self, seeds: Seeds) -> Self {
+        self.seeds = Some(seeds);
+        self
+    }
+
+    /// Report the total number of steps of all actors until the mean training return first reaches each threshold of
+    /// `sample_efficiency`
+    pub fn with_sample_efficiency(mut self, sample_efficiency: SampleEfficiency) -> Self {
+        self.sample_efficiency = Some(sample_efficiency);
+        self
+    }
+
+    /// Report episode metrics to `sink`
+    pub fn with_sink(mut self, sink: impl MetricSink + 'static) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    /// The agent being trained
+    pub fn agent(&self) -> &A {
+        &self.agent
+    }
+
+    /// Take back the agent
+    pub fn into_agent(self) -> A {
+        self.agent
+    }
+
+    /// Train the agent until the episode limit is reached
+    ///
+    /// **Returns** a [`TrainSummary`], the first error reported by a sink, an [`RlError::OutOfRange`] error if there
+    /// are no actors or the sync interval is 0, or an [`RlError::NonFinite`] error if the return of an episode is NaN
+    /// or infinite
+    pub fn train(&mut self) -> Result<TrainSummary> {
+        check_interval("actors", self.actors as f32, 1.0, f32::INFINITY)?;
+        check_interval(
+            "sync_interval",
+            self.sync_interval as f32,
+            1.0,
+            f32::INFINITY,
+        )?;
+        let Self {
+            new_env,
+            agent,
+            actors,
+            episodes,
+            max_episode_steps,
+            sync_interval,
+            queue_capacity,
+            seeds,
+            sample_efficiency,
+            sinks,
+        } = self;
+
+        if let Some(seeds) = seeds {
+            seeds.apply();
+            let params = seeds.params();
+            sinks
+                .iter_mut()
+                .try_for_each(|sink| sink.log_config(&params))?;
+        }
+
+        let mut summary = TrainSummary::default();
+        let mut steps_to_threshold = sample_efficiency
+            .as_ref()
+            .map(|config| StepsToThreshold::new(config, &[]));
+        let stop = AtomicBool::new(false);
+        let (tx, rx) = mpsc::sync_channel(*queue_capacity);
+        let (snapshot_txs, snapshot_rxs): (Vec<_>, Vec<_>) =
+            (0..*actors).map(|_| mpsc::channel()).unzip();
+
+        let result = thread::scope(|s| {
+            for (i, snapshots) in snapshot_rxs.into_iter().enumerate() {
+                let (tx, actor, stop) = (tx.clone(), agent.actor(), &stop);
+                let (new_env, max_episode_steps) = (&*new_env, *max_episode_steps);
+                let seeds = seeds.map(|seeds| seeds.fork(i as u64));
+                s.spawn(move || {
+                    if let Some(seeds) = seeds {
+                        seeds.apply();
+                    }
+                    run_actor(new_env(), actor, snapshots, tx, stop, max_episode_steps)
+                });
+            }
+            drop(tx);
+
+            let mut learner_steps = 0;
+            let result = loop {
+                if summary.episodes >= *episodes {
+                    break Ok(());
+                }
+                // Every actor has stopped, e.g. after a panic
+                let Ok(message) = rx.recv() else {
+                    break Ok(());
+                };
+
+                let mut reported = Ok(());
+                for message in std::iter::once(message).chain(rx.try_iter()) {
+                    match message {
+                        Message::Step(experience) => {
+                            agent.remember(experience);
+                            summary.steps += 1;
+                        }
+                        Message::EpisodeEnd { ret, steps } => {
+                            agent.on_episode_end();
+                            let episode = summary.episodes;
+                            tracing::debug!(episode, ret, steps, "episode finished");
+                            let mut metrics = vec![
+                                ("return", ret),
+                                ("steps", steps as f64),
+                                ("total_steps", summary.steps as f64),
+                            ];
+                            metrics.extend(agent.metrics());
+                            let mut reached = Vec::new();
+                            if let Some(tracker) = &mut steps_to_threshold {
+                                reached = tracker.push(ret, summary.steps);
+                                summary.steps_to_threshold.extend(&reached);
+                            }
+                            reported = match ret.is_finite() {
+                                true => sinks
+                                    .iter_mut()
+                                    .try_for_each(|sink| {
+                                        sink.log_episode(episode, &metrics)?;
+                                        reached.iter().try_for_each(|&(threshold, steps)| {
+                                            let name = SampleEfficiency::metric(threshold);
+                                            sink.log_scalar(&name, steps as f64, episode)
+                                        })
+                                    })
+                                    .map_err(RlError::from),
+                                false => Err(RlError::NonFinite {
+                                    name: "return",
+                                    episode,
+                                }),
+                            };
+                            summary.episodes += 1;
+                            if reported.is_err() || summary.episodes >= *episodes {
+                                break;
+                            }
+                        }
+                    }
+                }
+                if reported.is_err() {
+                    break reported;
+                }
+
+                agent.train_step();
+                learner_steps += 1;
+                if learner_steps % *sync_interval == 0 {
+                    for tx in &snapshot_txs {
+                        let _ = tx.send(agent.actor());
+                    }
+                }
+            };
+
+            // Unblock actors waiting on a full queue so they can see the stop flag
+            stop.store(true, Ordering::Relaxed);
+            drop(rx);
+            result
+        });
+
+        result?;
+        sinks.iter_mut().try_for_each(|sink| sink.flush())?;
+
+        Ok(summary)
+    }
+}
+
+/// Run episodes with `actor` and send their transitions to the learner until `stop` is set or the learner hangs up
+///
+/// The actor is replaced by the latest snapshot received on `snapshots` before every step.
+fn run_actor<E: Environment, A: Actor<E>>(
+    mut env: E,
+    mut actor: A,
+    snapshots: Receiver<A>,
+    tx: SyncSender<Message<E>>,
+    stop: &AtomicBool,
+    max_episode_steps: Option<u64>,
+) {
+    while !stop.load(Ordering::Relaxed) {
+        let mut ret = 0.0;
+        let mut steps = 0;
+        let mut next_state = Some(env.reset());
+
+        while let Some(state) = next_state {
+            if max_episode_steps.is_some_and(|max| steps >= max) {
+                break;
+            }
+            if let Some(latest) = snapshots.try_iter().last() {
+                actor = latest;
+            }
+
+            let action = actor.act(&env, &state);
+            let (next, reward) = env.step(action.clone());
+            next_state = next;
+            ret += reward as f64;
+            steps += 1;
+
+            let experience = Exp {
+                state,
+                action,
+                next_state: next_state.clone(),
+                reward,
+            };
+            if tx.send(Message::Step(experience)).is_err() {
+                return;
+            }
+        }
+
+        if tx.send(Message::EpisodeEnd { ret, steps }).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    use super::{super::tests::Countdown, *};
+
+    /// Counts remembered experiences, learning steps and episodes
+    #[derive(Default)]
+    struct Learner {
+        remembered: u64,
+        train_steps: u64,
+        episodes: u64,
+    }
+
+    impl Agent<Countdown> for Learner {
+        fn act(&mut self, _env: &Countdown, _state: &u32) {}
+
+        fn learn(&mut self, _env: &Countdown, experience: Exp<Countdown>) {
+            self.remember(experience);
+            self.train_step();
+        }
+
+        fn on_episode_end(&mut self) {
+            self.episodes += 1;
+        }
+
+        fn policy(&self, _env: &Countdown, _state: &u32) {}
+    }
+
+    struct Snapshot;
+
+    impl Actor<Countdown> for Snapshot {
+        fn act(&mut self, _env: &Countdown, _state: &u32) {}
+    }
+
+    impl ParallelAgent<Countdown> for Learner {
+        type Actor = Snapshot;
+
+        fn actor(&self) -> Snapshot {
+            Snapshot
+        }
+
+        fn remember(&mut self, _experience: Exp<Countdown>) {
+            self.remembered += 1;
+        }
+
+        fn train_step(&mut self) {
+            self.train_steps += 1;
+        }
+    }
+
+    /// Collects every reported metric across threads
+    #[derive(Clone, Default)]
+    struct Collect(Arc<Mutex<Vec<(String, u64)>>>);
+
+    impl MetricSink for Collect {
+        fn log_scalar(&mut self, name: &str, _value: f64, step: u64) -> io::Result<()> {
+            self.0.lock().unwrap().push((name.to_string(), step));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn parallel_trainer() {
+        let sink = Collect::default();
+        let mut trainer =
+            ParallelTrainer::new(|| Countdown { state: 0, start: 5 }, Learner::default())
+                .with_actors(3)
+                .with_episodes(20)
+                .with_max_episode_steps(4)
+                .with_sync_interval(2)
+                .with_queue_capacity(8)
+                .with_seeds(Seeds::new(0))
+                .with_sample_efficiency(SampleEfficiency::new(vec![4.0]).with_window(5))
+                .with_sink(sink.clone());
+        let summary = trainer.train().unwrap();
+
+        assert_eq!(summary.episodes, 20);
+        assert!(
+            summary.steps >= 20 * 4,
+            "Episodes are truncated, extra steps may arrive"
+        );
+
+        let agent = trainer.agent();
+        assert_eq!(agent.episodes, 20, "Every reported episode is ended");
+        assert_eq!(agent.remembered, summary.steps);
+        assert!(agent.train_steps > 0);
+
+        let returns = sink.0.lock().unwrap();
+        let episodes = returns
+            .iter()
+            .filter(|(name, _)| name == "return")
+            .map(|&(_, episode)| episode)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            episodes,
+            (0..20).collect::<Vec<_>>(),
+            "Episodes are numbered in order"
+        );
+        assert!(returns.contains(&("steps_to_4".to_string(), 4)));
+        let [(threshold, steps)] = summary.steps_to_threshold[..] else {
+            panic!("{:?}", summary.steps_to_threshold);
+        };
+        assert_eq!(threshold, 4.0);
+        assert!(steps >= 5 * 4, "Counts the steps of all actors");
+
+        let new_env = || Countdown { state: 0, start: 5 };
+        let result = ParallelTrainer::new(new_env, Learner::default())
+            .with_actors(0)
+            .train();
+        assert!(matches!(
+            result,
+            Err(RlError::OutOfRange { name: "actors", .. })
+        ));
+        let result = ParallelTrainer::new(new_env, Learner::default())
+            .with_sync_interval(0)
+            .train();
+        assert!(matches!(
+            result,
+            Err(RlError::OutOfRange {
+                name: "sync_interval",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,357 @@
+use std::{
+    fmt::Write,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use super::{Evaluation, TrainSummary};
+use crate::{error::RlError, seed::Seeds};
+
+/// The layout of the directory a [`Trainer`](super::Trainer) writes a run to, see
+/// [`with_run_dir`](super::Trainer::with_run_dir)
+///
+/// ```text
+/// <root>/
+/// ├── manifest.json   what the run produced and how it ended
+/// ├── config.toml     the experiment config, if the run was built from one
+/// ├── metrics.csv     every reported metric
+/// ├── checkpoints/    periodic checkpoints, named episode-<episode>
+/// ├── best            the checkpoint of the best evaluation
+/// ├── model           the final agent
+/// ├── *.normalizer    observation statistics next to every checkpoint, if the trainer has a normalizer
+/// ├── frames/         rendered evaluation episodes, named eval-<episode>.txt
+/// └── progress.txt    the trainer state at the latest periodic checkpoint, to resume from
+/// ```
+///
+/// `manifest.json` lists only what was actually written, with paths relative to the root so run directories can
+/// be moved:
+///
+/// ```json
+/// {
+///   "rl_version": "0.1.0",
+///   "status": "finished",
+///   "error": null,
+///   "config": "config.toml",
+///   "metrics": "metrics.csv",
+///   "checkpoints": ["checkpoints/episode-99"],
+///   "best": "best",
+///   "model": "model",
+///   "frames": ["frames/eval-99.txt"],
+///   "seeds": {"env": 1, "agent": 2, "exploration": 3, "deterministic": false},
+///   "summary": {
+///     "episodes": 100, "steps": 2000, "eval_mean": 0.8, "best_episode": 99, "stopped": null,
+///     "steps_to_threshold": {"0.5": 1200}
+///   }
+/// }
+/// ```
+///
+/// `status` is `running` until training returns, then `finished` or `failed` along with the `error`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunDir {
+    root: PathBuf,
+}
+
+impl RunDir {
+    const MANIFEST: &'static str = "manifest.json";
+    const CONFIG: &'static str = "config.toml";
+    const METRICS: &'static str = "metrics.csv";
+    const CHECKPOINTS: &'static str = "checkpoints";
+    const BEST: &'static str = "best";
+    const MODEL: &'static str = "model";
+    const FRAMES: &'static str = "frames";
+    const PROGRESS: &'static str = "progress.txt";
+
+    /// A run directory at `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The root of the run directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The path of the manifest
+    pub fn manifest(&self) -> PathBuf {
+        self.root.join(Self::MANIFEST)
+    }
+
+    /// The path of the experiment config, written by whoever builds the trainer
+    pub fn config(&self) -> PathBuf {
+        self.root.join(Self::CONFIG)
+    }
+
+    /// The path of the metrics CSV file
+    pub fn metrics(&self) -> PathBuf {
+        self.root.join(Self::METRICS)
+    }
+
+    /// The directory of the periodic checkpoints
+    pub fn checkpoints(&self) -> PathBuf {
+        self.root.join(Self::CHECKPOINTS)
+    }
+
+    /// The path of the best checkpoint
+    pub fn best(&self) -> PathBuf {
+        self.root.join(Self::BEST)
+    }
+
+    /// The path of the final agent
+    pub fn model(&self) -> PathBuf {
+        self.root.join(Self::MODEL)
+    }
+
+    /// The directory of the rendered evaluation episodes
+    pub fn frames(&self) -> PathBuf {
+        self.root.join(Self::FRAMES)
+    }
+
+    /// The path of the trainer state at the latest periodic checkpoint
+    pub fn progress(&self) -> PathBuf {
+        self.root.join(Self::PROGRESS)
+    }
+
+    /// The path of the frames rendered during the evaluation after `episode`
+    pub(super) fn eval_frames(&self, episode: u64) -> PathBuf {
+        self.frames().join(format!("eval-{episode}.txt"))
+    }
+
+    /// `path` relative to the root, with `/` as separator
+    fn relative(&self, path: &Path) -> String {
+        let path = path.strip_prefix(&self.root).unwrap_or(path);
+        path.to_string_lossy().replace('\\', "/")
+    }
+}
+
+/// How a run ended, or that it is still running
+pub(super) enum Status<'a> {
+    Running,
+    Finished(&'a TrainSummary),
+    Failed(&'a RlError),
+}
+
+/// What has been written to a [`RunDir`] so far
+#[derive(Debug, Default)]
+pub(super) struct Manifest {
+    pub(super) checkpoints: Vec<PathBuf>,
+    pub(super) frames: Vec<PathBuf>,
+    pub(super) best: bool,
+    pub(super) model: bool,
+    pub(super) seeds: Option<Seeds>,
+}
+
+impl Manifest {
+    /// Write the manifest of `dir` with the given `status`
+    pub(super) fn write(&self, dir: &RunDir, status: Status) -> io::Result<()> {
+        let optional = |present: bool, name: &str| match present {
+            true => json_string(name),
+            false => "null".to_string(),
+        };
+        let list = |paths: &[PathBuf]| {
+            let paths = paths
+                .iter()
+                .map(|path| json_string(&dir.relative(path)))
+                .collect::<Vec<_>>();
+            format!("[{}]", paths.join(", "))
+        };
+
+        let (status, error, summary) = match status {
+            Status::Running => ("running", None, None),
+            Status::Finished(summary) => ("finished", None, Some(summary)),
+            Status::Failed(error) => ("failed", Some(error.to_string()), None),
+        };
+
+        let mut json = String::from("{\n");
+        let mut field = |name: &str, value: String| {
+            let _ = writeln!(json, "  \"{name}\": {value},");
+        };
+        field("rl_version", json_string(env!("CARGO_PKG_VERSION")));
+        field("status", json_string(status));
+        field(
+            "error",
+            error.as_deref().map_or("null".to_string(), json_string),
+        );
+        field("config", optional(dir.config().exists(), RunDir::CONFIG));
+        field("metrics", json_string(RunDir::METRICS));
+        field("checkpoints", list(&self.checkpoints));
+        field("best", optional(self.best, RunDir::BEST));
+        field("model", optional(self.model, RunDir::MODEL));
+        field("frames", list(&self.frames));
+        field(
+            "seeds",
+            self.seeds.map_or("null".to_string(), Seeds::to_json),
+        );
+        field("summary", summary.map_or("null".to_string(), summary_json));
+        json.truncate(json.len() - 2);
+        json.push_str("\n}\n");
+
+        fs::write(dir.manifest(), json)
+    }
+
+    /// Write the trainer `progress` at the latest periodic checkpoint, along with what has been written so far
+    pub(super) fn write_progress(&self, dir: &RunDir, progress: &Progress) -> io::Result<()> {
+        let summary = &progress.summary;
+        let mut lines = vec![
+            format!("episodes {}", summary.episodes),
+            format!("steps {}", summary.steps),
+            format!("evals_since_best {}", progress.evals_since_best),
+            format!("best_checkpoint {}", self.best),
+        ];
+        if let Some(eval) = summary.eval {
+            lines.push(format!("eval {} {}", eval.mean, eval.std));
+        }
+        if let Some((episode, best)) = summary.best {
+            lines.push(format!("best {episode} {} {}", best.mean, best.std));
+        }
+        for (threshold, steps) in &summary.steps_to_threshold {
+            lines.push(format!("steps_to_threshold {threshold} {steps}"));
+        }
+        if let Some((stage, entered)) = progress.curriculum {
+            lines.push(format!("curriculum {stage} {entered}"));
+        }
+        if let Some(seeds) = self.seeds {
+            lines.push(format!(
+                "seeds {} {} {} {}",
+                seeds.env, seeds.agent, seeds.exploration, seeds.deterministic
+            ));
+        }
+        for path in &self.checkpoints {
+            lines.push(format!("checkpoint {}", dir.relative(path)));
+        }
+        for path in &self.frames {
+            lines.push(format!("frames {}", dir.relative(path)));
+        }
+
+        fs::write(dir.progress(), lines.join("\n") + "\n")
+    }
+
+    /// Read what had been written to the run in `dir` and the trainer progress at its latest periodic checkpoint
+    pub(super) fn read_progress(dir: &RunDir) -> io::Result<(Self, Progress)> {
+        let contents = fs::read_to_string(dir.progress())?;
+        let mut manifest = Self::default();
+        let mut progress = Progress::default();
+
+        for line in contents.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let summary = &mut progress.summary;
+            match key {
+                "episodes" => summary.episodes = parse(value)?,
+                "steps" => summary.steps = parse(value)?,
+                "evals_since_best" => progress.evals_since_best = parse(value)?,
+                "best_checkpoint" => manifest.best = parse(value)?,
+                "eval" => {
+                    let [mean, std] = fields(value)?;
+                    summary.eval = Some(Evaluation {
+                        mean: parse(mean)?,
+                        std: parse(std)?,
+                    });
+                }
+                "best" => {
+                    let [episode, mean, std] = fields(value)?;
+                    let eval = Evaluation {
+                        mean: parse(mean)?,
+                        std: parse(std)?,
+                    };
+                    summary.best = Some((parse(episode)?, eval));
+                }
+                "steps_to_threshold" => {
+                    let [threshold, steps] = fields(value)?;
+                    summary
+                        .steps_to_threshold
+                        .push((parse(threshold)?, parse(steps)?));
+                }
+                "curriculum" => {
+                    let [stage, entered] = fields(value)?;
+                    progress.curriculum = Some((parse(stage)?, parse(entered)?));
+                }
+                "seeds" => {
+                    let [env, agent, exploration, deterministic] = fields(value)?;
+                    manifest.seeds = Some(Seeds {
+                        env: parse(env)?,
+                        agent: parse(agent)?,
+                        exploration: parse(exploration)?,
+                        deterministic: parse(deterministic)?,
+                    });
+                }
+                "checkpoint" => manifest.checkpoints.push(dir.root.join(value)),
+                "frames" => manifest.frames.push(dir.root.join(value)),
+                _ => return Err(invalid_data(format!("unknown progress entry `{key}`"))),
+            }
+        }
+
+        Ok((manifest, progress))
+    }
+}
+
+/// The state of the [`Trainer`](super::Trainer) at a periodic checkpoint, from which a resumed run continues
+#[derive(Debug, Default)]
+pub(super) struct Progress {
+    pub(super) summary: TrainSummary,
+    pub(super) evals_since_best: u64,
+    /// The stage of the curriculum and the number of episodes before it was entered, if there is a curriculum
+    pub(super) curriculum: Option<(usize, u64)>,
+}
+
+/// Parse the value of a progress entry
+fn parse<T: FromStr>(value: &str) -> io::Result<T> {
+    value
+        .parse()
+        .map_err(|_| invalid_data(format!("invalid progress value `{value}`")))
+}
+
+/// Split the value of a progress entry into `N` fields
+fn fields<const N: usize>(value: &str) -> io::Result<[&str; N]> {
+    let fields = value.split(' ').collect::<Vec<_>>();
+    fields
+        .try_into()
+        .map_err(|_| invalid_data(format!("expected {N} fields in `{value}`")))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// A JSON object of the main results in `summary`
+fn summary_json(summary: &TrainSummary) -> String {
+    let number = |x: Option<f64>| match x {
+        Some(x) if x.is_finite() => x.to_string(),
+        _ => "null".to_string(),
+    };
+    format!(
+        "{{\"episodes\": {}, \"steps\": {}, \"eval_mean\": {}, \"best_episode\": {}, \"stopped\": {}, \
+         \"steps_to_threshold\": {{{}}}}}",
+        summary.episodes,
+        summary.steps,
+        number(summary.eval.map(|eval| eval.mean)),
+        summary
+            .best
+            .map_or("null".to_string(), |(episode, _)| episode.to_string()),
+        summary.stopped.as_deref().map_or("null".to_string(), json_string),
+        summary
+            .steps_to_threshold
+            .iter()
+            .map(|(threshold, steps)| format!("{}: {steps}", json_string(&threshold.to_string())))
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+/// `s` as a quoted JSON string
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
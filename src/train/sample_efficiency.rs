@@ -0,0 +1,137 @@
+use crate::stats::Window;
+
+/// Configuration for reporting the number of environment steps until the training returns first reach thresholds
+///
+/// Used with [`Trainer::with_sample_efficiency`](super::Trainer::with_sample_efficiency) and
+/// [`ParallelTrainer::with_sample_efficiency`](super::ParallelTrainer::with_sample_efficiency). Once the mean return
+/// of the last [`window`](SampleEfficiency::window) training episodes first reaches a threshold, the total number of
+/// steps taken so far, across all environments, is reported as the metric `steps_to_<threshold>`, e.g.
+/// `steps_to_195`, and recorded in [`TrainSummary::steps_to_threshold`](super::TrainSummary::steps_to_threshold).
+/// Comparing these step counts between algorithms compares their sample efficiency.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct SampleEfficiency {
+    /// The mean training returns at which to report the number of steps
+    ///
+    /// **Default:** `[]`
+    pub thresholds: Vec<f64>,
+    /// The number of most recent training episodes whose mean return is compared to the thresholds, no threshold is
+    /// reached before the window is full
+    ///
+    /// **Default:** `100`
+    pub window: usize,
+}
+
+impl Default for SampleEfficiency {
+    fn default() -> Self {
+        Self {
+            thresholds: Vec::new(),
+            window: 100,
+        }
+    }
+}
+
+impl SampleEfficiency {
+    /// Report the steps until the mean return of the last 100 training episodes reaches each of `thresholds`
+    pub fn new(thresholds: Vec<f64>) -> Self {
+        Self {
+            thresholds,
+            ..Default::default()
+        }
+    }
+
+    /// Set the number of most recent training episodes whose mean return is compared to the thresholds
+    ///
+    /// **Panics** if `episodes` is 0
+    pub fn with_window(mut self, episodes: usize) -> Self {
+        assert!(episodes > 0, "The window needs at least one episode");
+        self.window = episodes;
+        self
+    }
+
+    /// The name of the metric reporting the steps to `threshold`
+    pub fn metric(threshold: f64) -> String {
+        format!("steps_to_{threshold}")
+    }
+}
+
+/// Tracks the mean return of recent episodes for a [`SampleEfficiency`]
+#[derive(Debug, Clone)]
+pub(super) struct StepsToThreshold {
+    thresholds: Vec<f64>,
+    returns: Window,
+}
+
+impl StepsToThreshold {
+    /// Track the thresholds of `config` that aren't in `reached` yet, e.g. thresholds reached before a resume
+    pub(super) fn new(config: &SampleEfficiency, reached: &[(f64, u64)]) -> Self {
+        Self {
+            thresholds: config
+                .thresholds
+                .iter()
+                .copied()
+                .filter(|threshold| !reached.iter().any(|(r, _)| r == threshold))
+                .collect(),
+            returns: Window::new(config.window.max(1)),
+        }
+    }
+
+    /// Add the return of a finished episode, after which `steps` steps have been taken in total
+    ///
+    /// **Returns** the thresholds reached for the first time with their step count
+    pub(super) fn push(&mut self, ret: f64, steps: u64) -> Vec<(f64, u64)> {
+        self.returns.push(ret);
+        let Some(mean) = self.returns.mean().filter(|_| self.returns.is_full()) else {
+            return Vec::new();
+        };
+        let (reached, remaining) = self
+            .thresholds
+            .iter()
+            .partition::<Vec<f64>, _>(|&&threshold| mean >= threshold);
+        self.thresholds = remaining;
+        reached
+            .into_iter()
+            .map(|threshold| (threshold, steps))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_to_threshold() {
+        let config = SampleEfficiency::new(vec![0.5, 1.0, 2.0]).with_window(2);
+        let mut tracker = StepsToThreshold::new(&config, &[]);
+        assert!(tracker.push(1.0, 10).is_empty(), "The window isn't full");
+        assert_eq!(tracker.push(0.0, 20), [(0.5, 20)]);
+        assert!(tracker.push(1.0, 25).is_empty(), "Already reached");
+        assert_eq!(tracker.push(1.0, 30), [(1.0, 30)]);
+
+        let mut resumed = StepsToThreshold::new(&config, &[(0.5, 20)]);
+        resumed.push(1.0, 40);
+        assert_eq!(resumed.push(1.0, 50), [(1.0, 50)]);
+        let steps_to = |threshold, window| {
+            let config = SampleEfficiency::new(vec![threshold]).with_window(window);
+            let mut tracker = StepsToThreshold::new(&config, &[]);
+            let mut steps = 0;
+            [(0.0, 10), (1.0, 10), (1.0, 5), (0.0, 5)]
+                .into_iter()
+                .find_map(|(ret, episode_steps)| {
+                    steps += episode_steps;
+                    tracker.push(ret, steps).first().map(|&(_, steps)| steps)
+                })
+        };
+        assert_eq!(steps_to(1.0, 2), Some(25));
+        assert_eq!(steps_to(0.5, 2), Some(20));
+        assert_eq!(steps_to(0.75, 4), None);
+
+        assert_eq!(SampleEfficiency::metric(195.0), "steps_to_195");
+        assert_eq!(SampleEfficiency::metric(0.5), "steps_to_0.5");
+    }
+}
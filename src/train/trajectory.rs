@@ -0,0 +1,245 @@
+use std::{
+    io,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+};
+
+use super::{Callback, Evaluation};
+use crate::{
+    env::{Environment, Render},
+    memory::Exp,
+    traits::checkpoint,
+};
+
+/// A recorded evaluation episode, as written by [`TrajectoryRecorder`]
+///
+/// States and actions are kept as their `Debug` representation, so any environment can be recorded and replayed
+/// without knowing its types, e.g. with `rl replay <file>`.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Trajectory {
+    /// The index of the evaluation among those seen by the recorder
+    pub evaluation: u64,
+    /// The index of the episode within the evaluation
+    pub eval_episode: u64,
+    /// The initial state
+    pub initial_state: String,
+    /// The rendered initial state, if the recorder renders frames
+    pub initial_frame: Option<String>,
+    /// The steps of the episode
+    pub steps: Vec<TrajectoryStep>,
+}
+
+/// A step of a [`Trajectory`]
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TrajectoryStep {
+    /// The action taken
+    pub action: String,
+    /// The reward of the step
+    pub reward: f32,
+    /// The next state, `None` if the episode terminated
+    pub next_state: Option<String>,
+    /// The rendered environment after the step, if the recorder renders frames
+    pub frame: Option<String>,
+}
+
+impl Trajectory {
+    /// The sum of the rewards of the episode
+    pub fn episode_return(&self) -> f64 {
+        self.steps.iter().map(|step| step.reward as f64).sum()
+    }
+
+    /// The number of steps of the episode
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Whether the episode has no steps
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Write the trajectory to a JSON file at `path`
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        checkpoint::write_json(path, self)
+    }
+
+    /// Read a trajectory previously written by [`save`](Trajectory::save)
+    pub fn load(path: &Path) -> io::Result<Self> {
+        checkpoint::read_json(path)
+    }
+}
+
+/// A [`Callback`] that saves evaluation episodes to disk for debugging agent behavior
+///
+/// Every evaluation episode is written to `<dir>/eval-<evaluation>-<episode>.json` as soon as it ends, see
+/// [`Trajectory`]. Evaluations are counted from zero in the order the recorder sees them. A failed write is logged
+/// and stops training at the end of the evaluation.
+///
+/// ```ignore
+/// let trainer = Trainer::new(env, agent)
+///     .with_eval(100, 5, eval_env)
+///     .with_callback(TrajectoryRecorder::new("trajectories").with_frames().with_episodes_per_eval(1));
+/// ```
+///
+/// ### Generics
+/// - `E` - The [`Environment`] being evaluated
+#[derive(Debug, Clone)]
+pub struct TrajectoryRecorder<E: Environment> {
+    dir: PathBuf,
+    render: Option<fn(&E) -> String>,
+    episodes_per_eval: Option<u64>,
+    evaluation: Option<u64>,
+    current: Option<Trajectory>,
+    written: Vec<PathBuf>,
+    error: Option<String>,
+}
+
+impl<E: Environment> TrajectoryRecorder<E> {
+    /// Record every evaluation episode to `dir`, without frames
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            render: None,
+            episodes_per_eval: None,
+            evaluation: None,
+            current: None,
+            written: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Only record the first `episodes` episodes of each evaluation
+    pub fn with_episodes_per_eval(mut self, episodes: u64) -> Self {
+        self.episodes_per_eval = Some(episodes);
+        self
+    }
+
+    /// The files written so far, in order
+    pub fn written(&self) -> &[PathBuf] {
+        &self.written
+    }
+
+    /// Write the current trajectory, if there is one
+    fn write(&mut self) -> io::Result<()> {
+        let Some(trajectory) = self.current.take() else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(format!(
+            "eval-{}-{}.json",
+            trajectory.evaluation, trajectory.eval_episode
+        ));
+        trajectory.save(&path)?;
+        self.written.push(path);
+        Ok(())
+    }
+}
+
+impl<E: Render> TrajectoryRecorder<E> {
+    /// Also record the [rendered](Render::render) environment after every reset and step
+    pub fn with_frames(mut self) -> Self {
+        self.render = Some(E::render);
+        self
+    }
+}
+
+impl<E: Environment> Callback<E> for TrajectoryRecorder<E> {
+    fn on_eval_episode_start(&mut self, env: &E, eval_episode: u64, state: &E::State) {
+        if eval_episode == 0 {
+            self.evaluation = Some(self.evaluation.map_or(0, |evaluation| evaluation + 1));
+        }
+        if self
+            .episodes_per_eval
+            .is_some_and(|episodes| eval_episode >= episodes)
+        {
+            return;
+        }
+        self.current = Some(Trajectory {
+            evaluation: self.evaluation.unwrap_or_default(),
+            eval_episode,
+            initial_state: format!("{state:?}"),
+            initial_frame: self.render.map(|render| render(env)),
+            steps: Vec::new(),
+        });
+    }
+
+    fn on_eval_step(&mut self, env: &E, experience: &Exp<E>) {
+        if let Some(trajectory) = &mut self.current {
+            trajectory.steps.push(TrajectoryStep {
+                action: format!("{:?}", experience.action),
+                reward: experience.reward,
+                next_state: experience.next_state.as_ref().map(|s| format!("{s:?}")),
+                frame: self.render.map(|render| render(env)),
+            });
+        }
+    }
+
+    fn on_eval_episode_end(&mut self, _env: &E) {
+        if let Err(e) = self.write() {
+            tracing::error!(dir = %self.dir.display(), "failed to write trajectory: {e}");
+            self.error.get_or_insert(e.to_string());
+        }
+    }
+
+    fn on_eval(&mut self, _episode: u64, _eval: Evaluation) -> ControlFlow<String> {
+        match self.error.take() {
+            Some(e) => ControlFlow::Break(format!("failed to write trajectory: {e}")),
+            None => ControlFlow::Continue(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            tests::{Countdown, CountingAgent},
+            Trainer,
+        },
+        *,
+    };
+
+    #[test]
+    fn record_trajectories() {
+        let dir = std::env::temp_dir().join("rl_trajectories");
+        let _ = std::fs::remove_dir_all(&dir);
+        let recorder = TrajectoryRecorder::new(&dir)
+            .with_frames()
+            .with_episodes_per_eval(1);
+        Trainer::new(Countdown { state: 0, start: 2 }, CountingAgent::default())
+            .with_episodes(4)
+            .with_eval(2, 3, Countdown { state: 0, start: 3 })
+            .with_callback(recorder)
+            .train()
+            .unwrap();
+
+        let mut files = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(
+            files,
+            ["eval-0-0.json", "eval-1-0.json"],
+            "Only the first episode of each evaluation is recorded"
+        );
+
+        let trajectory = Trajectory::load(&dir.join("eval-1-0.json")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(trajectory.evaluation, 1);
+        assert_eq!(trajectory.initial_state, "3");
+        assert_eq!(trajectory.initial_frame.as_deref(), Some("3"));
+        assert_eq!(trajectory.len(), 3);
+        assert_eq!(trajectory.episode_return(), 3.0);
+        assert_eq!(
+            trajectory.steps[0],
+            TrajectoryStep {
+                action: "()".to_string(),
+                reward: 1.0,
+                next_state: Some("2".to_string()),
+                frame: Some("2".to_string()),
+            }
+        );
+        assert_eq!(trajectory.steps[2].next_state, None);
+    }
+}
@@ -0,0 +1,275 @@
+use crate::{env::Environment, memory::Exp};
+
+/// The statistics of an episode run by [`Agent::go`], e.g. to log learning curves
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EpisodeSummary {
+    /// The number of steps taken
+    pub steps: u64,
+    /// The undiscounted sum of the rewards
+    pub total_reward: f32,
+    /// Whether the episode reached the agent's [`max_episode_steps`](Agent::max_episode_steps) in a non-terminal
+    /// state, instead of terminating
+    pub truncated: bool,
+}
+
+/// How far one nudge moves a [`Hyperparam`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+    /// Add or subtract this amount, e.g. for probabilities
+    Add(f64),
+    /// Multiply or divide by this factor, for values spanning orders of magnitude like learning rates
+    Scale(f64),
+}
+
+/// A hyperparameter that an agent can change while training, see [`Agent::hyperparams`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hyperparam {
+    /// The name of the hyperparameter, e.g. `lr`
+    pub name: &'static str,
+    /// The current value
+    pub value: f64,
+    /// How far one nudge moves the value
+    pub step: Step,
+}
+
+impl Hyperparam {
+    /// The discount factor `gamma`, nudged in steps of `0.005`
+    ///
+    /// Agents that discount returns support it, so the [`Trainer`](crate::train::Trainer) can set it from
+    /// [`Environment::discount`] or a schedule.
+    pub fn gamma(value: f32) -> Self {
+        Self {
+            name: "gamma",
+            value: value as f64,
+            step: Step::Add(0.005),
+        }
+    }
+
+    /// The value after `steps` nudges, downwards if `steps` is negative
+    pub fn nudged(&self, steps: i32) -> f64 {
+        match self.step {
+            Step::Add(amount) => self.value + amount * steps as f64,
+            Step::Scale(factor) => self.value * factor.powi(steps),
+        }
+    }
+}
+
+/// A trait for agents that learn by interacting with an [`Environment`]
+///
+/// Training loops, evaluators and benchmarks can be written once against this trait instead of against each agent
+///
+/// ### Generics
+/// - `E` - The [`Environment`] in which the agent acts
+pub trait Agent<E: Environment> {
+    /// Choose an action in `state` while training, following the agent's exploration strategy
+    fn act(&mut self, env: &E, state: &E::State) -> E::Action;
+
+    /// Learn from an experience
+    ///
+    /// `env` is the environment *after* the step that produced `experience`
+    fn learn(&mut self, env: &E, experience: Exp<E>);
+
+    /// Called once after each episode, e.g. to advance exploration schedules
+    ///
+    /// **Default:** does nothing
+    fn on_episode_end(&mut self) {}
+
+    /// Take the diagnostics collected since the last call, e.g. the mean loss and gradient norm of learning steps
+    ///
+    /// The [`Trainer`](crate::train::Trainer) reports them with the metrics of each episode. Agents with stochastic
+    /// policies include the entropy and KL divergence of their updates collected with
+    /// [`PolicyStats`](crate::distributions::PolicyStats).
+    ///
+    /// **Default:** no diagnostics
+    fn metrics(&mut self) -> Vec<(&'static str, f64)> {
+        Vec::new()
+    }
+
+    /// The hyperparameters that can be changed while training, with their current values, e.g. to tune a long run
+    /// from the viz dashboard
+    ///
+    /// **Default:** none
+    fn hyperparams(&self) -> Vec<Hyperparam> {
+        Vec::new()
+    }
+
+    /// Change the hyperparameter called `name` to `value`, taking effect from the next step
+    ///
+    /// **Returns** `false` if the agent has no such hyperparameter or `value` is invalid for it, leaving it unchanged
+    ///
+    /// **Default:** there are no hyperparameters to change
+    fn set_hyperparam(&mut self, _name: &str, _value: f64) -> bool {
+        false
+    }
+
+    /// Called before each episode that follows the greedy [`policy`](Agent::policy), e.g. to reset the hidden state
+    /// of a recurrent agent, which it keeps with interior mutability
+    ///
+    /// **Default:** does nothing
+    fn reset_policy(&self) {}
+
+    /// Choose the greedy action in `state`, without exploration
+    fn policy(&self, env: &E, state: &E::State) -> E::Action;
+
+    /// The step limit of the episodes run by [`go`](Agent::go), for environments that can run indefinitely or
+    /// agents that can get stuck
+    ///
+    /// The [`Trainer`](crate::train::Trainer) uses it when no limit is set with
+    /// [`with_max_episode_steps`](crate::train::Trainer::with_max_episode_steps).
+    ///
+    /// **Default:** `None`, no limit
+    fn max_episode_steps(&self) -> Option<u64> {
+        None
+    }
+
+    /// Run the agent in the given environment for one training episode
+    ///
+    /// The episode is truncated after [`max_episode_steps`](Agent::max_episode_steps). The last experience of a
+    /// truncated episode keeps its next state, so the agent still bootstraps from it.
+    ///
+    /// **Returns** the statistics of the episode
+    fn go(&mut self, env: &mut E) -> EpisodeSummary {
+        let max_steps = self.max_episode_steps();
+        let mut summary = EpisodeSummary::default();
+        let mut next_state = Some(env.reset());
+        while let Some(state) = next_state {
+            if max_steps.is_some_and(|max| summary.steps >= max) {
+                summary.truncated = true;
+                break;
+            }
+            let action = self.act(env, &state);
+            let (next, reward) = env.step(action.clone());
+            next_state = next;
+            summary.steps += 1;
+            summary.total_reward += reward;
+
+            self.learn(
+                env,
+                Exp {
+                    state,
+                    action,
+                    next_state: next_state.clone(),
+                    reward,
+                },
+            );
+        }
+
+        self.on_episode_end();
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::tests::MockEnv;
+
+    #[derive(Default)]
+    struct CountingAgent {
+        acted: usize,
+        learned: usize,
+        episodes: usize,
+    }
+
+    impl Agent<MockEnv> for CountingAgent {
+        fn act(&mut self, env: &MockEnv, state: &i32) -> i32 {
+            self.acted += 1;
+            self.policy(env, state)
+        }
+
+        fn learn(&mut self, _env: &MockEnv, experience: Exp<MockEnv>) {
+            assert_eq!(experience.next_state, None);
+            self.learned += 1;
+        }
+
+        fn on_episode_end(&mut self) {
+            self.episodes += 1;
+        }
+
+        fn policy(&self, env: &MockEnv, _state: &i32) -> i32 {
+            env.random_action()
+        }
+    }
+
+    #[test]
+    fn go_truncates_episodes() {
+        struct Endless;
+
+        impl Environment for Endless {
+            type State = ();
+            type Action = ();
+
+            fn step(&mut self, _action: Self::Action) -> (Option<Self::State>, f32) {
+                (Some(()), 1.0)
+            }
+
+            fn reset(&mut self) -> Self::State {}
+
+            fn random_action(&self) -> Self::Action {}
+        }
+
+        struct Limited(u64);
+
+        impl Agent<Endless> for Limited {
+            fn act(&mut self, _env: &Endless, _state: &()) {}
+
+            fn learn(&mut self, _env: &Endless, experience: Exp<Endless>) {
+                assert!(experience.next_state.is_some());
+                self.0 += 1;
+            }
+
+            fn policy(&self, _env: &Endless, _state: &()) {}
+
+            fn max_episode_steps(&self) -> Option<u64> {
+                Some(5)
+            }
+        }
+
+        let mut agent = Limited(0);
+        let summary = agent.go(&mut Endless);
+        assert!(summary.truncated);
+        assert_eq!(summary.steps, 5);
+        assert_eq!(summary.total_reward, 5.0);
+        assert_eq!(agent.0, 5);
+    }
+
+    #[test]
+    fn go_runs_one_episode() {
+        let mut agent = CountingAgent::default();
+        let mut env = MockEnv;
+
+        let summary = agent.go(&mut env);
+        agent.go(&mut env);
+
+        assert_eq!(summary.steps, 1);
+        assert!(!summary.truncated);
+        assert_eq!(agent.acted, 2, "Acts once per step");
+        assert_eq!(agent.learned, 2, "Learns once per step");
+        assert_eq!(
+            agent.episodes, 2,
+            "Episode end is signalled once per episode"
+        );
+    }
+
+    #[test]
+    fn nudge_hyperparams() {
+        let lr = Hyperparam {
+            name: "lr",
+            value: 1e-3,
+            step: Step::Scale(2.0),
+        };
+        assert_eq!(lr.nudged(1), 2e-3);
+        assert_eq!(lr.nudged(-2), 2.5e-4);
+
+        let floor = Hyperparam {
+            name: "epsilon_floor",
+            value: 0.0,
+            step: Step::Add(0.25),
+        };
+        assert_eq!(
+            floor.nudged(2),
+            0.5,
+            "Additive steps move values away from zero"
+        );
+    }
+}
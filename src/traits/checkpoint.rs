@@ -0,0 +1,31 @@
+use std::{io, path::Path};
+
+/// A trait for agents whose learned state can be saved and restored, so interrupted runs can resume exactly
+///
+/// A checkpoint holds everything that changes during training, e.g. learned values, network and optimizer
+/// parameters, exploration schedules and step counters. It does not hold the configuration the agent was
+/// constructed with, so a checkpoint is loaded into an agent created with the same configuration.
+pub trait Checkpoint {
+    /// Save the agent's state to `path`
+    ///
+    /// Depending on the agent, `path` is either a single file or a directory of files.
+    fn save(&self, path: &Path) -> io::Result<()>;
+
+    /// Restore the agent's state from a checkpoint previously written to `path` by [`save`](Checkpoint::save)
+    fn load(&mut self, path: &Path) -> io::Result<()>;
+}
+
+/// Write `value` to a JSON file at `path`
+#[cfg(feature = "serde")]
+pub(crate) fn write_json<T: serde::Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let writer = io::BufWriter::new(std::fs::File::create(path)?);
+    serde_json::to_writer(writer, value)?;
+    Ok(())
+}
+
+/// Read a value from a JSON file at `path`
+#[cfg(feature = "serde")]
+pub(crate) fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> io::Result<T> {
+    let reader = io::BufReader::new(std::fs::File::open(path)?);
+    Ok(serde_json::from_reader(reader)?)
+}
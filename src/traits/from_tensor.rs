@@ -0,0 +1,120 @@
+use burn::prelude::*;
+
+use crate::env::ContinuousAction;
+
+/// A trait for converting network outputs back to items, the inverse of [`ToTensor`](super::ToTensor)
+///
+/// Commonly implemented for `Vec<T>` to convert a tensor with one row per item to a batch of `T`, e.g. to decode the
+/// actions of a batch of states
+pub trait FromTensor<B: Backend, const D: usize, K: BasicOps<B>> {
+    fn from_tensor(tensor: Tensor<B, D, K>) -> Self;
+}
+
+/// Discrete actions from scores, e.g. Q values or logits, choosing the index of the highest score in each row
+///
+/// Indices are converted to actions with `From<i32>`, like the actions of [`DQNAgent`](crate::algo::dqn::DQNAgent).
+impl<B, T> FromTensor<B, 2, Float> for Vec<T>
+where
+    B: Backend,
+    T: From<i32>,
+{
+    fn from_tensor(tensor: Tensor<B, 2, Float>) -> Self {
+        tensor
+            .argmax(1)
+            .into_data()
+            .convert::<i32>()
+            .value
+            .into_iter()
+            .map(T::from)
+            .collect()
+    }
+}
+
+/// Continuous actions from rows of `A` values, clamped to `[-1, 1]`
+impl<B, const A: usize> FromTensor<B, 2, Float> for Vec<ContinuousAction<A>>
+where
+    B: Backend,
+{
+    fn from_tensor(tensor: Tensor<B, 2, Float>) -> Self {
+        let values = tensor.clamp(-1.0, 1.0).into_data().convert::<f32>().value;
+        values
+            .chunks_exact(A)
+            .map(|row| ContinuousAction(row.try_into().expect("Chunks have length A")))
+            .collect()
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl<B, K> FromTensor<B, 1, K> for ndarray::Array1<K::Elem>
+where
+    B: Backend,
+    K: BasicOps<B>,
+{
+    fn from_tensor(tensor: Tensor<B, 1, K>) -> Self {
+        ndarray::Array1::from_vec(tensor.into_data().value)
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl<B, K> FromTensor<B, 2, K> for ndarray::Array2<K::Elem>
+where
+    B: Backend,
+    K: BasicOps<B>,
+{
+    fn from_tensor(tensor: Tensor<B, 2, K>) -> Self {
+        let [rows, cols] = tensor.dims();
+        ndarray::Array2::from_shape_vec((rows, cols), tensor.into_data().value)
+            .expect("The data of a tensor matches its shape")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use burn::backend::{ndarray::NdArrayDevice, NdArray as B};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Move {
+        Left,
+        Right,
+    }
+
+    impl From<i32> for Move {
+        fn from(value: i32) -> Self {
+            match value {
+                0 => Move::Left,
+                _ => Move::Right,
+            }
+        }
+    }
+
+    #[test]
+    fn discrete_impl() {
+        let scores = Tensor::<B, 2>::from_floats([[0.1, 0.7], [2.0, -1.0]], &NdArrayDevice::Cpu);
+        let actions = Vec::<Move>::from_tensor(scores);
+        assert_eq!(actions, [Move::Right, Move::Left], "argmax of each row");
+    }
+
+    #[test]
+    fn continuous_impl() {
+        let outputs = Tensor::<B, 2>::from_floats([[0.5, -3.0], [1.5, 0.0]], &NdArrayDevice::Cpu);
+        let actions = Vec::<ContinuousAction<2>>::from_tensor(outputs);
+        assert_eq!(
+            actions,
+            [ContinuousAction([0.5, -1.0]), ContinuousAction([1.0, 0.0])],
+            "Values are clamped"
+        );
+        assert_eq!(actions[0].scale([0.0, -2.0], [4.0, 2.0]), [3.0, -2.0]);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ndarray_impl() {
+        let outputs = Tensor::<B, 2>::from_floats([[0.5, -3.0], [1.5, 0.0]], &NdArrayDevice::Cpu);
+        let array = ndarray::Array2::from_tensor(outputs.clone());
+        assert_eq!(array, ndarray::array![[0.5, -3.0], [1.5, 0.0]]);
+        let array = ndarray::Array1::from_tensor(outputs.flatten::<1>(0, 1));
+        assert_eq!(array, ndarray::array![0.5, -3.0, 1.5, 0.0]);
+    }
+}
@@ -1,3 +1,15 @@
+#[cfg(feature = "train")]
+pub mod agent;
+pub mod checkpoint;
+pub mod from_tensor;
 pub mod to_tensor;
 
-pub use to_tensor::ToTensor;
+#[cfg(feature = "train")]
+pub use agent::{Agent, EpisodeSummary, Hyperparam, Step};
+pub use checkpoint::Checkpoint;
+pub use from_tensor::FromTensor;
+pub use to_tensor::{Features, Observation, ToTensor, TryToTensor};
+
+/// Derive [`ToTensor`] for observation structs
+#[cfg(feature = "derive")]
+pub use rl_derive::ToTensor;
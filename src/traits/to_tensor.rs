@@ -3,15 +3,49 @@ use burn::{
     tensor::{BasicOps, DataSerialize, Element},
 };
 
+use crate::{
+    env::OneHot,
+    error::{Result, RlError},
+};
+
 /// A trait for converting items to tensors
 ///
 /// Commonly implemented for `Vec<T>` to convert batches of `T` to a tensor of dimension `D`
 ///
+/// The implementations for batches of numbers convert them to the element type of the backend, so e.g. `f32`
+/// observations and `i32` actions work with `f64` backends or backends with `i64` integers.
+///
 /// See implementations of this for [`CartPole`](crate::gym::CartPole) as an example of how to implement this trait
 pub trait ToTensor<B: Backend, const D: usize, K: BasicOps<B>> {
     fn to_tensor(self, device: &B::Device) -> Tensor<B, D, K>;
 }
 
+/// A trait for converting items to tensors when their shape is only known at runtime and may be invalid
+///
+/// Implemented for `Vec<Vec<E>>`, where the inner lengths must all be equal
+pub trait TryToTensor<B: Backend, const D: usize, K: BasicOps<B>> {
+    /// **Returns** an [`RlError::RaggedBatch`] if the items don't form a rectangular tensor
+    fn try_to_tensor(self, device: &B::Device) -> Result<Tensor<B, D, K>>;
+}
+
+/// A fixed number of `f32` features, appended in order to build observation tensors
+///
+/// Implemented for `f32` and arrays of features, and derived for observation structs with `#[derive(ToTensor)]`
+/// from the `derive` feature.
+pub trait Features {
+    /// The number of features
+    const SIZE: usize;
+
+    /// Append the features to `out`
+    fn features(&self, out: &mut Vec<f32>);
+}
+
+/// A marker for observation structs deriving `ToTensor`, whose batches convert to tensors of shape `[batch, SIZE]`
+///
+/// Implemented by the derive instead of writing `ToTensor` for `Vec<T>`, which the orphan rules don't allow outside
+/// of this crate.
+pub trait Observation: Features {}
+
 // Implementations from burn
 
 /// Marker trait to restrict blanket implementations
@@ -41,15 +75,33 @@ where
 
 // Implementations
 
+impl Features for f32 {
+    const SIZE: usize = 1;
+
+    fn features(&self, out: &mut Vec<f32>) {
+        out.push(*self);
+    }
+}
+
+impl<T: Features, const N: usize> Features for [T; N] {
+    const SIZE: usize = N * T::SIZE;
+
+    fn features(&self, out: &mut Vec<f32>) {
+        for item in self {
+            item.features(out);
+        }
+    }
+}
+
 impl<B, E, K> ToTensor<B, 1, K> for Vec<E>
 where
     B: Backend,
     E: Element,
-    K: BasicOps<B, Elem = E>,
+    K: BasicOps<B>,
 {
     fn to_tensor(self, device: &<B as Backend>::Device) -> Tensor<B, 1, K> {
         let len = self.len();
-        Tensor::from_data(Data::new(self, [len].into()), device)
+        Tensor::from_data(Data::new(self, [len].into()).convert::<K::Elem>(), device)
     }
 }
 
@@ -57,15 +109,186 @@ impl<B, E, K, const A: usize> ToTensor<B, 2, K> for Vec<[E; A]>
 where
     B: Backend,
     E: Element,
-    K: BasicOps<B, Elem = E>,
+    K: BasicOps<B>,
 {
     fn to_tensor(self, device: &B::Device) -> Tensor<B, 2, K> {
+        let len = self.len();
+        let data = Data::new(self.into_iter().flatten().collect(), [len * A].into());
+        Tensor::from_data(data.convert::<K::Elem>(), device).reshape([-1, A as i32])
+    }
+}
+
+/// Batches of 2D observations, e.g. grayscale images or stacked frames, as tensors of shape `[batch, H, W]`
+impl<B, E, K, const H: usize, const W: usize> ToTensor<B, 3, K> for Vec<[[E; W]; H]>
+where
+    B: Backend,
+    E: Element,
+    K: BasicOps<B>,
+{
+    fn to_tensor(self, device: &B::Device) -> Tensor<B, 3, K> {
+        let len = self.len();
+        let data = Data::new(
+            self.into_iter().flatten().flatten().collect(),
+            [len, H, W].into(),
+        );
+        Tensor::from_data(data.convert::<K::Elem>(), device)
+    }
+}
+
+/// Batches of 3D observations, e.g. images with `C` channels, as tensors of shape `[batch, H, W, C]`
+///
+/// Convolutions in burn expect channels first, so swap the dimensions with `permute([0, 3, 1, 2])`.
+impl<B, E, K, const H: usize, const W: usize, const C: usize> ToTensor<B, 4, K>
+    for Vec<[[[E; C]; W]; H]>
+where
+    B: Backend,
+    E: Element,
+    K: BasicOps<B>,
+{
+    fn to_tensor(self, device: &B::Device) -> Tensor<B, 4, K> {
         let len = self.len();
         let data = Data::new(
-            self.into_iter().flatten().collect(),
-            [len * A].into(),
+            self.into_iter().flatten().flatten().flatten().collect(),
+            [len, H, W, C].into(),
         );
-        Tensor::from_data(data, device).reshape([-1, A as i32])
+        Tensor::from_data(data.convert::<K::Elem>(), device)
+    }
+}
+
+impl<B, E, K> TryToTensor<B, 2, K> for Vec<Vec<E>>
+where
+    B: Backend,
+    E: Element,
+    K: BasicOps<B>,
+{
+    fn try_to_tensor(self, device: &B::Device) -> Result<Tensor<B, 2, K>> {
+        let len = self.len();
+        let width = self.first().map_or(0, Vec::len);
+        if let Some((index, row)) = self.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(RlError::RaggedBatch {
+                index,
+                len: row.len(),
+                expected: width,
+            });
+        }
+        let data = Data::new(self.into_iter().flatten().collect(), [len, width].into());
+        Ok(Tensor::from_data(data.convert::<K::Elem>(), device))
+    }
+}
+
+/// **Panics** if the inner lengths differ, see [`TryToTensor`] to handle this instead
+impl<B, E, K> ToTensor<B, 2, K> for Vec<Vec<E>>
+where
+    B: Backend,
+    E: Element,
+    K: BasicOps<B>,
+{
+    fn to_tensor(self, device: &B::Device) -> Tensor<B, 2, K> {
+        self.try_to_tensor(device).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+/// Masks as column tensors of shape `[batch, 1]`, e.g. of non-terminal next states for `mask_where` and `mask_fill`
+/// on value tensors
+impl<B: Backend> ToTensor<B, 2, Bool> for Vec<bool> {
+    fn to_tensor(self, device: &B::Device) -> Tensor<B, 2, Bool> {
+        let len = self.len();
+        Tensor::from_data(Data::new(self, [len, 1].into()), device)
+    }
+}
+
+/// Indices as column tensors of shape `[batch, 1]`, e.g. of the actions taken for `gather` on Q values
+impl<B: Backend> ToTensor<B, 2, Int> for Vec<usize> {
+    fn to_tensor(self, device: &B::Device) -> Tensor<B, 2, Int> {
+        let len = self.len();
+        let indices = self.into_iter().map(|i| i as i64).collect();
+        Tensor::from_data(
+            Data::new(indices, [len, 1].into()).convert::<B::IntElem>(),
+            device,
+        )
+    }
+}
+
+impl<B, T> ToTensor<B, 2, Float> for Vec<T>
+where
+    B: Backend,
+    T: Observation,
+{
+    fn to_tensor(self, device: &B::Device) -> Tensor<B, 2, Float> {
+        let len = self.len();
+        let mut values = Vec::with_capacity(len * T::SIZE);
+        for observation in &self {
+            observation.features(&mut values);
+        }
+        Tensor::from_data(
+            Data::new(values, [len, T::SIZE].into()).convert::<B::FloatElem>(),
+            device,
+        )
+    }
+}
+
+impl<B, const N: usize> ToTensor<B, 2, Float> for Vec<OneHot<N>>
+where
+    B: Backend,
+{
+    fn to_tensor(self, device: &B::Device) -> Tensor<B, 2, Float> {
+        let len = self.len();
+        let mut values = vec![0f32; len * N];
+        for (row, state) in self.iter().enumerate() {
+            values[row * N + state.index()] = 1.0;
+        }
+        Tensor::from_data(
+            Data::new(values, [len, N].into()).convert::<B::FloatElem>(),
+            device,
+        )
+    }
+}
+
+// Implementations for ndarray
+
+#[cfg(feature = "ndarray")]
+impl<B, E, K> ToTensor<B, 1, K> for ndarray::Array1<E>
+where
+    B: Backend,
+    E: Element,
+    K: BasicOps<B>,
+{
+    fn to_tensor(self, device: &B::Device) -> Tensor<B, 1, K> {
+        let len = self.len();
+        Tensor::from_data(
+            Data::new(self.into_iter().collect(), [len].into()).convert::<K::Elem>(),
+            device,
+        )
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl<B, E, K> ToTensor<B, 2, K> for ndarray::Array2<E>
+where
+    B: Backend,
+    E: Element,
+    K: BasicOps<B>,
+{
+    fn to_tensor(self, device: &B::Device) -> Tensor<B, 2, K> {
+        let (rows, cols) = self.dim();
+        let data = Data::new(self.into_iter().collect(), [rows, cols].into());
+        Tensor::from_data(data.convert::<K::Elem>(), device)
+    }
+}
+
+/// **Panics** if the lengths of the arrays differ, like `Vec<Vec<E>>`
+#[cfg(feature = "ndarray")]
+impl<B, E, K> ToTensor<B, 2, K> for Vec<ndarray::Array1<E>>
+where
+    B: Backend,
+    E: Element,
+    K: BasicOps<B>,
+{
+    fn to_tensor(self, device: &B::Device) -> Tensor<B, 2, K> {
+        self.into_iter()
+            .map(|row| row.into_iter().collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+            .to_tensor(device)
     }
 }
 
@@ -100,4 +323,152 @@ mod tests {
             "valid tensor constructed from `Vec<[E; A]>`"
         );
     }
+
+    #[test]
+    fn vec_image_impl() {
+        let device = NdArrayDevice::Cpu;
+        let frames = vec![[[1f32, 2.0], [3.0, 4.0], [5.0, 6.0]]; 4];
+        let t: Tensor<B, 3> = frames.to_tensor(&device);
+        assert_eq!(t.dims(), [4, 3, 2]);
+        let t2: Tensor<B, 2> = [[1f32, 2.0], [3.0, 4.0], [5.0, 6.0]].to_tensor(&device);
+        assert!(
+            t.slice([3..4])
+                .squeeze::<2>(0)
+                .equal(t2)
+                .all()
+                .into_scalar(),
+            "valid tensor constructed from `Vec<[[E; W]; H]>`"
+        );
+
+        let images = vec![[[[0f32, 1.0, 2.0]; 5]; 2]; 3];
+        let t: Tensor<B, 4> = images.to_tensor(&device);
+        assert_eq!(t.dims(), [3, 2, 5, 3]);
+        assert_eq!(
+            t.sum_dim(3).flatten::<1>(0, 3).into_data().value,
+            vec![3.0; 30],
+            "Channels are the last dimension"
+        );
+    }
+
+    #[test]
+    fn mask_impls() {
+        let device = NdArrayDevice::Cpu;
+        let mask: Tensor<B, 2, Bool> = vec![true, false, true].to_tensor(&device);
+        assert_eq!(mask.dims(), [3, 1]);
+        let values = Tensor::<B, 2>::from_floats([[1.0], [2.0], [3.0]], &device);
+        assert_eq!(
+            values.mask_fill(mask.bool_not(), 0.0).into_data().value,
+            [1.0, 0.0, 3.0]
+        );
+
+        let indices: Tensor<B, 2, Int> = vec![1usize, 0].to_tensor(&device);
+        let q_values = Tensor::<B, 2>::from_floats([[1.0, 2.0], [3.0, 4.0]], &device);
+        assert_eq!(q_values.gather(1, indices).into_data().value, [2.0, 3.0]);
+    }
+
+    #[test]
+    fn element_conversion() {
+        let device = NdArrayDevice::Cpu;
+        let states: Tensor<burn::backend::NdArray<f64>, 2> =
+            vec![[1f32, 2.0], [3.0, 4.0]].to_tensor(&device);
+        assert_eq!(states.into_data().value, [1.0f64, 2.0, 3.0, 4.0]);
+
+        // The integers of the ndarray backend are i64
+        let actions: Tensor<B, 2, Int> = vec![[1i32], [0]].to_tensor(&device);
+        assert_eq!(actions.into_data().value, [1i64, 0]);
+    }
+
+    #[test]
+    fn one_hot_impl() {
+        let device = NdArrayDevice::Cpu;
+        let states = vec![OneHot::<3>::new(2), OneHot::new(0)];
+        let t1: Tensor<B, 2> = states.to_tensor(&device);
+
+        let t2: Tensor<B, 2> = [[0f32, 0.0, 1.0], [1.0, 0.0, 0.0]].to_tensor(&device);
+        assert!(
+            t1.equal(t2).all().into_scalar(),
+            "valid tensor constructed from `Vec<OneHot<N>>`"
+        );
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derive_impl() {
+        #[derive(crate::traits::ToTensor)]
+        struct Position([f32; 2]);
+
+        #[derive(crate::traits::ToTensor)]
+        struct Observation {
+            position: Position,
+            velocity: [f32; 2],
+            fuel: f32,
+        }
+
+        let observation = |x| Observation {
+            position: Position([x, 1.0]),
+            velocity: [2.0, 3.0],
+            fuel: 4.0,
+        };
+        let device = NdArrayDevice::Cpu;
+        let t1: Tensor<B, 1> = observation(0.0).to_tensor(&device);
+        let t2: Tensor<B, 1> = [0f32, 1.0, 2.0, 3.0, 4.0].to_tensor(&device);
+        assert!(
+            t1.equal(t2).all().into_scalar(),
+            "Fields are flattened in declaration order"
+        );
+
+        let t1: Tensor<B, 2> = vec![observation(0.0), observation(5.0)].to_tensor(&device);
+        let t2: Tensor<B, 2> =
+            [[0f32, 1.0, 2.0, 3.0, 4.0], [5.0, 1.0, 2.0, 3.0, 4.0]].to_tensor(&device);
+        assert!(
+            t1.equal(t2).all().into_scalar(),
+            "valid tensor constructed from `Vec` of derived observations"
+        );
+    }
+
+    #[test]
+    fn vec_vec_impl() {
+        let device = NdArrayDevice::Cpu;
+        let x = vec![vec![1f32, 2.0, 3.0], vec![4.0, 5.0, 6.0]];
+        let t1: Tensor<B, 2> = x.to_tensor(&device);
+
+        let t2: Tensor<B, 2> = [[1f32, 2.0, 3.0], [4.0, 5.0, 6.0]].to_tensor(&device);
+        assert!(
+            t1.equal(t2).all().into_scalar(),
+            "valid tensor constructed from `Vec<Vec<E>>`"
+        );
+
+        let ragged = vec![vec![1f32, 2.0], vec![3.0, 4.0], vec![5.0]];
+        let e = TryToTensor::<B, 2, Float>::try_to_tensor(ragged, &device).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "row 2 of the batch has length 1, expected 2 like the first row"
+        );
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ndarray_impl() {
+        let device = NdArrayDevice::Cpu;
+        let x = ndarray::array![[1f32, 2.0, 3.0], [4.0, 5.0, 6.0]];
+        let expected: Tensor<B, 2> = [[1f32, 2.0, 3.0], [4.0, 5.0, 6.0]].to_tensor(&device);
+
+        let t: Tensor<B, 2> = x.clone().to_tensor(&device);
+        assert!(t.equal(expected.clone()).all().into_scalar());
+        let t: Tensor<B, 2> = x.t().to_owned().to_tensor(&device);
+        assert!(
+            t.equal(expected.clone().transpose()).all().into_scalar(),
+            "Arrays are converted in logical order"
+        );
+        let t: Tensor<B, 2> = x
+            .rows()
+            .into_iter()
+            .map(|row| row.to_owned())
+            .collect::<Vec<_>>()
+            .to_tensor(&device);
+        assert!(
+            t.equal(expected).all().into_scalar(),
+            "valid tensor constructed from `Vec<Array1<E>>`"
+        );
+    }
 }
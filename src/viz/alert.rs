@@ -0,0 +1,164 @@
+use std::{fmt, sync::mpsc::Sender};
+
+use crate::stats::Window;
+
+/// Messages sent from the viz dashboard back to the training loop
+///
+/// Receive them by passing a [`Sender`] as [`VizConfig::control`](super::VizConfig::control) and polling the
+/// matching receiver with `try_recv` between episodes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Control {
+    /// Stop training early, e.g. because an [`Alert`] with [`Alert::stop`] was triggered
+    Stop { reason: String },
+    /// Nudge the agent's hyperparameter called `name` by `steps` of its [`Step`](crate::traits::Step), downwards if
+    /// `steps` is negative, see [`Agent::hyperparams`](crate::traits::Agent::hyperparams)
+    Nudge { name: String, steps: i32 },
+}
+
+/// A condition on the values of a metric
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// The value is NaN or infinite
+    NonFinite,
+    /// The value is at least the threshold
+    AtLeast(f64),
+    /// The value is at most the threshold
+    AtMost(f64),
+    /// The mean of the last `window` values is at least the threshold
+    MeanAtLeast { window: usize, threshold: f64 },
+    /// The mean of the last `window` values is at most the threshold
+    MeanAtMost { window: usize, threshold: f64 },
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::NonFinite => write!(f, "is NaN"),
+            Condition::AtLeast(threshold) => write!(f, "≥ {threshold}"),
+            Condition::AtMost(threshold) => write!(f, "≤ {threshold}"),
+            Condition::MeanAtLeast { window, threshold } => {
+                write!(f, "mean over last {window} ≥ {threshold}")
+            }
+            Condition::MeanAtMost { window, threshold } => {
+                write!(f, "mean over last {window} ≤ {threshold}")
+            }
+        }
+    }
+}
+
+/// A notification triggered the first time a metric meets a [`Condition`]
+///
+/// ### Example
+/// ```
+/// use rl::viz::{Alert, Condition};
+///
+/// let solved = Alert::new("reward", Condition::MeanAtLeast { window: 100, threshold: 475.0 }).stop();
+/// let diverged = Alert::new("loss", Condition::NonFinite).stop();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    metric: String,
+    condition: Condition,
+    stop: bool,
+    recent: Window,
+    triggered: bool,
+}
+
+impl Alert {
+    /// Create an alert on the metric called `metric`
+    ///
+    /// Metrics are named as in exported metrics files, i.e. `plot` or `plot/series`.
+    pub fn new(metric: impl Into<String>, condition: Condition) -> Self {
+        let window = match condition {
+            Condition::MeanAtLeast { window, .. } | Condition::MeanAtMost { window, .. } => window,
+            _ => 1,
+        };
+        Self {
+            metric: metric.into(),
+            condition,
+            stop: false,
+            recent: Window::new(window.max(1)),
+            triggered: false,
+        }
+    }
+
+    /// Also send [`Control::Stop`] when this alert is triggered
+    pub fn stop(mut self) -> Self {
+        self.stop = true;
+        self
+    }
+
+    /// Check a new value of the metric, returning `true` if this alert is triggered for the first time
+    fn check(&mut self, value: f64) -> bool {
+        if self.triggered {
+            return false;
+        }
+
+        self.triggered = match self.condition {
+            Condition::NonFinite => !value.is_finite(),
+            Condition::AtLeast(threshold) => value >= threshold,
+            Condition::AtMost(threshold) => value <= threshold,
+            Condition::MeanAtLeast { threshold, .. } => self
+                .push_recent(value)
+                .is_some_and(|mean| mean >= threshold),
+            Condition::MeanAtMost { threshold, .. } => self
+                .push_recent(value)
+                .is_some_and(|mean| mean <= threshold),
+        };
+
+        self.triggered
+    }
+
+    /// Add a value to the window, returning the mean once the window is full
+    fn push_recent(&mut self, value: f64) -> Option<f64> {
+        self.recent.push(value);
+        self.recent.mean().filter(|_| self.recent.is_full())
+    }
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.metric, self.condition)
+    }
+}
+
+/// A set of alerts on the metrics of one run, which sends [`Control`] messages when stopping alerts are triggered
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Alerts {
+    alerts: Vec<Alert>,
+    control: Option<Sender<Control>>,
+}
+
+impl Alerts {
+    pub fn new(alerts: Vec<Alert>, control: Option<Sender<Control>>) -> Self {
+        Self { alerts, control }
+    }
+
+    /// Check a new value of `metric` against every alert, returning a message for each newly triggered alert
+    pub fn observe(&mut self, metric: &str, value: f64) -> Vec<String> {
+        let mut messages = Vec::new();
+
+        for alert in self.alerts.iter_mut().filter(|a| a.metric == metric) {
+            if !alert.check(value) {
+                continue;
+            }
+
+            let mut message = format!("Alert: {alert} (value {value:.4})");
+            if alert.stop {
+                let sent = self.control.as_ref().is_some_and(|control| {
+                    control
+                        .send(Control::Stop {
+                            reason: message.clone(),
+                        })
+                        .is_ok()
+                });
+                if sent {
+                    message.push_str(", stopping training");
+                }
+            }
+            messages.push(message);
+        }
+
+        messages
+    }
+}
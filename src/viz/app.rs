@@ -1,66 +1,293 @@
 use std::{
+    cell::Cell,
     io,
-    sync::mpsc::{Receiver, TryRecvError},
-    time::Duration,
+    mem::{self, Discriminant},
+    path::{Path, PathBuf},
+    sync::mpsc::{Receiver, Sender, TryRecvError},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use super::{
-    components::{help::render_help, Component, Logs, Plots},
-    util::event_keycode,
+    alert::{Alerts, Control},
+    batch::{plot_and_series, MetricBatch},
+    components::{
+        error::render_error, help::render_help, notification::render_notification, plot::PlotStyle,
+        policy_map::PolicySlice, q_heatmap::QSnapshot, Component, Logs, Plots, PolicyMap, Progress,
+        QHeatmap, RenderPanel, Tuning,
+    },
+    export::write_metrics,
+    util::{event_keycode, tab_at},
 };
 use crossterm::event::{
     self,
     Event::{self},
-    KeyCode,
+    KeyCode, MouseButton, MouseEventKind,
 };
 use ratatui::{prelude::*, widgets::*};
 
+use crate::{report::read_metrics, traits::Hyperparam};
+
+#[cfg(feature = "plot-image")]
+use super::image::{file_stem, write_styled_plot, ImageFormat};
 use super::tui;
 
-const TABS: [&str; 2] = ["Plots", "Logs"];
+const TABS: [&str; 5] = ["Plots", "Logs", "Render", "Q-Values", "Policy"];
 
 #[derive(Default)]
 pub enum AppMode {
     #[default]
     Train,
-    Error(&'static str),
+    /// Training ended and dropped its sender, the plots can still be browsed
+    Finished,
+    Error(String),
     Quit,
 }
 
-/// Format for updating plot data
-pub struct Update {
-    pub episode: u16,
-    pub data: Vec<f64>,
+/// Messages sent from the training loop to the TUI
+pub enum Update {
+    /// Plot data for an episode, ordered the same as the plot names passed to [`init`](super::init)
+    ///
+    /// Prefer [`Update::Batch`], which names every value.
+    Episode { episode: u64, data: Vec<f64> },
+    /// Values of named metrics, decoupled from the order of the plots, see [`MetricBatch`]
+    Batch(MetricBatch),
+    /// A value for a named series of a plot, e.g. to show train and eval returns on the same axes
+    ///
+    /// Plots and series that don't exist yet are created when they first receive a value. A series named after its
+    /// plot is the plot's primary series.
+    Series {
+        episode: u64,
+        plot: String,
+        series: String,
+        value: f64,
+    },
+    /// The total number of environment steps taken so far, drives the progress gauge if it is step-based
+    Step(u64),
+    /// A frame of the environment, usually produced by [`Render::render`](crate::env::Render::render)
+    Frame(String),
+    /// A snapshot of the learned values of a gridworld, displayed as a heatmap
+    QSnapshot(QSnapshot),
+    /// The greedy actions of an agent on a 2D slice of a continuous state space, displayed as a heatmap
+    PolicySlice(PolicySlice),
+    /// The current values of the agent's hyperparameters, which can be nudged from the tuning popup
+    ///
+    /// Sent by the [`Trainer`](crate::train::Trainer) when training starts and after every change.
+    Hyperparams(Vec<Hyperparam>),
+    /// Training stopped with an error, which is shown on the error screen
+    ///
+    /// Sent by the [`Trainer`](crate::train::Trainer) when [`train`](crate::train::Trainer::train) fails. A channel
+    /// that disconnects without it is a training run that finished.
+    Failed(String),
+    /// An update belonging to the run called `run`, e.g. one configuration of a hyperparameter sweep
+    ///
+    /// Each run has its own plots and progress, selected with the number keys. Runs are created when they first
+    /// receive an update. See [`RunSender`](super::RunSender).
+    Tagged { run: String, update: Box<Update> },
+}
+
+/// The shortest time between redraws, about 30 frames per second
+const DEFAULT_FRAME_INTERVAL: Duration = Duration::from_millis(33);
+/// How often the dashboard is redrawn when nothing changed, e.g. to expire notifications
+const IDLE_REDRAW_INTERVAL: Duration = Duration::from_secs(1);
+/// How long to wait for input before checking for updates again
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// The longest time spent processing updates between two frames, so input and drawing stay responsive
+const UPDATE_BUDGET: Duration = Duration::from_millis(25);
+
+/// The name of the run that receives untagged updates
+const MAIN_RUN: &str = "main";
+/// How long alert notifications are shown
+const NOTIFICATION_DURATION: Duration = Duration::from_secs(5);
+
+/// The plots and progress of a single training run
+struct Run {
+    name: String,
+    progress: Progress,
+    plots: Plots,
+    alerts: Alerts,
+    received: bool,
+}
+
+impl Run {
+    fn new(
+        name: &str,
+        plots: &[&'static str],
+        episodes: u64,
+        steps: Option<u64>,
+        styles: &[(String, PlotStyle)],
+        alerts: Alerts,
+    ) -> Self {
+        let mut plots = Plots::new(plots, episodes);
+        plots.set_styles(styles.to_vec());
+        Self {
+            name: String::from(name),
+            progress: Progress::new(episodes, steps),
+            plots,
+            alerts,
+            received: false,
+        }
+    }
 }
 
 /// The root TUI component which holds the main app state and runs the render loop
 pub struct App {
     state: AppMode,
-    episode: u16,
-    total_episodes: u16,
+    plot_names: Vec<&'static str>,
+    total_episodes: u64,
+    total_steps: Option<u64>,
     selected_tab: usize,
     show_help: bool,
-    plots: Plots,
+    runs: Vec<Run>,
+    selected_run: usize,
+    baseline: Vec<(String, Vec<(f64, f64)>)>,
+    plot_styles: Vec<(String, PlotStyle)>,
+    seed_bands: bool,
+    alerts: Alerts,
+    notification: Option<(String, Instant)>,
     logs: Logs,
+    render_panel: RenderPanel,
+    q_heatmap: QHeatmap,
+    policy_map: PolicyMap,
+    tuning: Tuning,
+    frame_interval: Duration,
+    skipped_updates: u64,
+    lagging: bool,
+    tabs_area: Cell<Rect>,
+    runs_area: Cell<Rect>,
+    #[cfg(feature = "plot-image")]
+    image_export: Option<(PathBuf, ImageFormat)>,
 }
 
 impl App {
-    pub fn new(plots: &[&'static str], episodes: u16) -> Self {
+    pub fn new(plots: &[&'static str], episodes: u64) -> Self {
         Self {
             state: Default::default(),
-            episode: 0,
+            plot_names: plots.to_vec(),
             total_episodes: episodes,
+            total_steps: None,
             selected_tab: 0,
             show_help: false,
-            plots: Plots::new(plots.to_vec(), episodes),
+            runs: vec![Run::new(
+                MAIN_RUN,
+                plots,
+                episodes,
+                None,
+                &[],
+                Alerts::default(),
+            )],
+            selected_run: 0,
+            baseline: Vec::new(),
+            plot_styles: Vec::new(),
+            seed_bands: false,
+            alerts: Alerts::default(),
+            notification: None,
             logs: Logs::new(),
+            render_panel: RenderPanel::new(),
+            q_heatmap: QHeatmap::new(),
+            policy_map: PolicyMap::new(),
+            tuning: Tuning::new(),
+            frame_interval: DEFAULT_FRAME_INTERVAL,
+            skipped_updates: 0,
+            lagging: false,
+            tabs_area: Cell::default(),
+            runs_area: Cell::default(),
+            #[cfg(feature = "plot-image")]
+            image_export: None,
+        }
+    }
+
+    /// Make the progress gauge step-based, tracking the [`Update::Step`] count against `steps`
+    pub fn with_total_steps(mut self, steps: u64) -> Self {
+        self.total_steps = Some(steps);
+        for run in &mut self.runs {
+            run.progress.set_total_steps(self.total_steps);
+        }
+        self
+    }
+
+    /// Title, label and scale the axes of the plots named in `styles`, see [`PlotStyle`]
+    pub fn with_plot_styles(mut self, styles: Vec<(String, PlotStyle)>) -> Self {
+        for run in &mut self.runs {
+            run.plots.set_styles(styles.clone());
         }
+        self.plot_styles = styles;
+        self
+    }
+
+    /// Aggregate runs whose names differ only in a `seed=<n>` part into an extra run per group
+    ///
+    /// The extra run is named after the other parts of the run names, e.g. `algo.alpha=0.5 (all seeds)`, and plots
+    /// the mean of every metric over the seeds with a band showing their spread, see [`PlotStyle::band`].
+    pub fn with_seed_bands(mut self) -> Self {
+        self.seed_bands = true;
+        self
+    }
+
+    /// Render the plots as images to `dir` when the TUI exits
+    #[cfg(feature = "plot-image")]
+    pub fn with_image_export(mut self, dir: impl Into<PathBuf>, format: ImageFormat) -> Self {
+        self.image_export = Some((dir.into(), format));
+        self
+    }
+
+    /// Redraw at most once per `interval` while updates arrive, trading smoothness for CPU time left to training
+    pub fn with_frame_interval(mut self, interval: Duration) -> Self {
+        self.frame_interval = interval;
+        self
+    }
+
+    /// Send hyperparameter nudges from the tuning popup through `control`
+    pub(super) fn with_control(mut self, control: Sender<Control>) -> Self {
+        self.tuning.set_control(control);
+        self
+    }
+
+    /// Check every run's metrics against `alerts`
+    pub(super) fn with_alerts(mut self, alerts: Alerts) -> Self {
+        for run in &mut self.runs {
+            run.alerts = alerts.clone();
+        }
+        self.alerts = alerts;
+        self
     }
 
     fn handle_ui_event(&mut self, event: &Event) {
+        if let AppMode::Error(_) = self.state {
+            match event_keycode(event) {
+                // Training has stopped, but what it reported can still be browsed
+                Some(KeyCode::Char('r')) => self.state = AppMode::Train,
+                Some(KeyCode::Char('q')) => self.state = AppMode::Quit,
+                _ => (),
+            }
+            return;
+        }
+
+        if let Event::Mouse(mouse) = event {
+            if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+                if let Some(tab) = tab_at(self.tabs_area.get(), TABS, mouse.column, mouse.row) {
+                    self.selected_tab = tab;
+                    return;
+                }
+                if let Some(run) = tab_at(
+                    self.runs_area.get(),
+                    self.run_titles().iter().map(String::as_str),
+                    mouse.column,
+                    mouse.row,
+                ) {
+                    self.selected_run = run;
+                    return;
+                }
+            }
+        }
+
+        if self.tuning.handle_ui_event(event) {
+            return;
+        }
+
         let handled = match self.selected_tab {
             1 => self.logs.handle_ui_event(event),
-            _ => self.plots.handle_ui_event(event),
+            2 => self.render_panel.handle_ui_event(event),
+            _ => self.runs[self.selected_run].plots.handle_ui_event(event),
         };
 
         if handled {
@@ -81,46 +308,427 @@ impl App {
             KeyCode::Char('h') => {
                 self.show_help ^= true;
             }
+            KeyCode::Char('t') => {
+                self.tuning.toggle();
+            }
+            KeyCode::Char('e') => {
+                self.export_metrics();
+            }
+            #[cfg(feature = "plot-image")]
+            KeyCode::Char('i') => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let dir = PathBuf::from(format!("plots-{timestamp}"));
+
+                match self.export_images(&dir, ImageFormat::Png) {
+                    Ok(()) => {
+                        log::info!(target: "tui", "Exported plot images to {}", dir.display())
+                    }
+                    Err(e) => log::error!(target: "tui", "Failed to export plot images: {e}"),
+                }
+            }
+            KeyCode::Char(c @ '1'..='9') => {
+                let run = c as usize - '1' as usize;
+                if run < self.runs.len() {
+                    self.selected_run = run;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Write all collected plot data to `path`
+    ///
+    /// The format is chosen from the file extension: `.json` or `.jsonl` writes JSON lines, anything else writes CSV
+    /// with the columns `metric,episode,value`. Long series are written in their decimated form, as shown in the plots.
+    /// If there are several runs, metric names are prefixed with the run name, e.g. `lr=0.01/reward`.
+    pub fn dump_metrics(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let prefix = self.runs.len() > 1;
+        let series = self.runs.iter().flat_map(|run| {
+            run.plots.series().map(move |(name, data)| {
+                let name = if prefix {
+                    format!("{}/{name}", run.name)
+                } else {
+                    name
+                };
+                (name, data)
+            })
+        });
+
+        write_metrics(path.as_ref(), series)
+    }
+
+    /// Load metrics exported from a previous run and show them as faded baselines under the live plots
+    ///
+    /// Accepts the same formats written by [`App::dump_metrics`]. The baseline is shown for every run. Metrics that
+    /// don't match any plot are ignored.
+    pub fn load_baseline(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.baseline = read_metrics(path.as_ref())?;
+        for (name, points) in &self.baseline {
+            let mut matched = false;
+            for run in &mut self.runs {
+                matched |= run.plots.set_baseline(name, points);
+            }
+            if !matched {
+                log::warn!(target: "tui", "No plot for baseline metric {name}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the run called `name`, or the main run if `None`, creating it if necessary
+    ///
+    /// The initial main run is replaced by the first tagged run if it never received an update.
+    fn run_mut(&mut self, name: Option<&str>) -> &mut Run {
+        let name = name.unwrap_or(MAIN_RUN);
+        if let Some(ix) = self.runs.iter().position(|run| run.name == name) {
+            return &mut self.runs[ix];
+        }
+
+        let mut run = Run::new(
+            name,
+            &self.plot_names,
+            self.total_episodes,
+            self.total_steps,
+            &self.plot_styles,
+            self.alerts.clone(),
+        );
+        for (metric, points) in &self.baseline {
+            run.plots.set_baseline(metric, points);
+        }
+
+        if self.runs.len() == 1 && !self.runs[0].received {
+            self.runs[0] = run;
+        } else {
+            self.runs.push(run);
+        }
+
+        self.runs.last_mut().unwrap()
+    }
+
+    /// Tab titles of the runs, numbered by their selection key
+    fn run_titles(&self) -> Vec<String> {
+        if self.runs.len() < 2 {
+            return Vec::new();
+        }
+
+        self.runs
+            .iter()
+            .enumerate()
+            .map(|(i, run)| format!("{} {}", i + 1, run.name))
+            .collect()
+    }
+
+    fn apply_update(&mut self, update: Update, run: Option<&str>) {
+        match update {
+            Update::Episode { episode, data } => {
+                let names = self.plot_names.clone();
+                let run = self.run_mut(run);
+                run.received = true;
+                run.progress.set_episode(episode);
+                run.plots.update(episode, &data);
+
+                let messages = names
+                    .iter()
+                    .zip(&data)
+                    .flat_map(|(name, &value)| run.alerts.observe(name, value))
+                    .map(|message| format_notification(&run.name, message))
+                    .collect();
+                self.notify(messages);
+            }
+            Update::Batch(batch) => {
+                let run = self.run_mut(run);
+                run.received = true;
+                if let Some(episode) = batch.last_episode() {
+                    let latest = run.progress.episode().max(episode);
+                    run.progress.set_episode(latest);
+                }
+
+                let mut messages = Vec::new();
+                for (metric, points) in batch.iter() {
+                    let (plot, series) = plot_and_series(metric);
+                    for &(episode, value) in points {
+                        run.plots
+                            .update_series(plot, series, (episode as f64, value));
+                        messages.extend(
+                            run.alerts
+                                .observe(metric, value)
+                                .into_iter()
+                                .map(|message| format_notification(&run.name, message)),
+                        );
+                    }
+                }
+                self.notify(messages);
+            }
+            Update::Series {
+                episode,
+                plot,
+                series,
+                value,
+            } => {
+                let run = self.run_mut(run);
+                run.received = true;
+                let latest = run.progress.episode().max(episode);
+                run.progress.set_episode(latest);
+                run.plots
+                    .update_series(&plot, &series, (episode as f64, value));
+
+                let metric = if plot == series {
+                    plot
+                } else {
+                    format!("{plot}/{series}")
+                };
+                let messages = run
+                    .alerts
+                    .observe(&metric, value)
+                    .into_iter()
+                    .map(|message| format_notification(&run.name, message))
+                    .collect();
+                self.notify(messages);
+            }
+            Update::Step(step) => {
+                let run = self.run_mut(run);
+                run.received = true;
+                run.progress.set_step(step);
+            }
+            Update::Frame(frame) => self.render_panel.push(frame),
+            Update::QSnapshot(snapshot) => self.q_heatmap.update(snapshot),
+            Update::PolicySlice(slice) => self.policy_map.update(slice),
+            Update::Hyperparams(params) => self.tuning.update(params),
+            Update::Failed(message) => self.state = AppMode::Error(message),
+            Update::Tagged { run, update } => {
+                if let Some(group) = seed_group(&run).filter(|_| self.seed_bands) {
+                    self.update_bands(&group, &update);
+                }
+                self.apply_update(*update, Some(&run))
+            }
+        }
+    }
+
+    /// Add the metric values of `update` to the bands of the run called `group`, which aggregates runs over seeds
+    fn update_bands(&mut self, group: &str, update: &Update) {
+        let names = self.plot_names.clone();
+        let run = self.run_mut(Some(group));
+        run.received = true;
+        let mut band = |plot: &str, series: &str, episode: u64, value: f64| {
+            let latest = run.progress.episode().max(episode);
+            run.progress.set_episode(latest);
+            run.plots.update_band(plot, series, (episode as f64, value));
+        };
+
+        match update {
+            Update::Episode { episode, data } => {
+                for (name, &value) in names.iter().zip(data) {
+                    band(*name, *name, *episode, value);
+                }
+            }
+            Update::Batch(batch) => {
+                for (metric, points) in batch.iter() {
+                    let (plot, series) = plot_and_series(metric);
+                    for &(episode, value) in points {
+                        band(plot, series, episode, value);
+                    }
+                }
+            }
+            Update::Series {
+                episode,
+                plot,
+                series,
+                value,
+            } => band(plot, series, *episode, *value),
             _ => (),
         }
     }
 
+    /// Log alert messages and show the most recent one as a notification
+    fn notify(&mut self, messages: Vec<String>) {
+        for message in messages {
+            log::warn!(target: "tui", "{message}");
+            self.notification = Some((message, Instant::now()));
+        }
+    }
+
+    /// Render every plot to an image file in `dir`, creating the directory if necessary
+    ///
+    /// Files are named after the plot, prefixed with the run name if there are several runs.
+    #[cfg(feature = "plot-image")]
+    pub fn export_images(&self, dir: impl AsRef<Path>, format: ImageFormat) -> io::Result<()> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+
+        for run in &self.runs {
+            for (name, plot) in run.plots.iter() {
+                let stem = if self.runs.len() > 1 {
+                    file_stem(&format!("{}_{name}", run.name))
+                } else {
+                    file_stem(name)
+                };
+                let path = dir.join(format!("{stem}.{}", format.extension()));
+                let series = plot.series().collect::<Vec<_>>();
+                write_styled_plot(&path, format, name, plot.style(), &series)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Dump metrics to a timestamped CSV file in the working directory and log the outcome
+    fn export_metrics(&self) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let path = PathBuf::from(format!("metrics-{timestamp}.csv"));
+
+        match self.dump_metrics(&path) {
+            Ok(()) => log::info!(target: "tui", "Exported metrics to {}", path.display()),
+            Err(e) => log::error!(target: "tui", "Failed to export metrics: {e}"),
+        }
+    }
+
     /// Initialize the terminal and run the main loop
     ///
-    /// Restores the terminal on exit
+    /// Restores the terminal on exit, even if the main loop fails. Panics are handled by the hook installed in
+    /// [`tui::init`].
     pub fn run(&mut self, rx: Receiver<Update>) -> io::Result<()> {
         let mut terminal = tui::init()?;
+        let result = self.main_loop(&mut terminal, &rx);
+        tui::restore()?;
+
+        #[cfg(feature = "plot-image")]
+        if let Some((dir, format)) = &self.image_export {
+            self.export_images(dir, *format)?;
+        }
 
+        result
+    }
+
+    /// Redraw when updates or input arrive, at most once per [`frame_interval`](App::with_frame_interval), and
+    /// otherwise once per [`IDLE_REDRAW_INTERVAL`]
+    fn main_loop(&mut self, terminal: &mut tui::Tui, rx: &Receiver<Update>) -> io::Result<()> {
+        let mut dirty = true;
+        let mut last_draw = Instant::now();
         loop {
             match self.state {
-                AppMode::Train => {
-                    loop {
-                        match rx.try_recv() {
-                            Ok(update) => {
-                                self.episode = update.episode;
-                                self.plots.update(update)
-                            }
-                            Err(TryRecvError::Empty) => break,
-                            Err(TryRecvError::Disconnected) => {
-                                self.state = AppMode::Error("Channel disconnected.");
-                                break;
-                            }
-                        };
-                    }
+                AppMode::Train => dirty |= self.receive_updates(rx),
+                AppMode::Finished | AppMode::Error(_) => (),
+                AppMode::Quit => break,
+            }
 
-                    terminal.draw(|frame| frame.render_widget(&*self, frame.size()))?;
+            dirty |= self.render_panel.tick();
+            // Log messages arrive through the logger rather than the channel
+            dirty |= self.selected_tab == 1;
 
-                    if event::poll(Duration::from_millis(16))? {
-                        let event = event::read()?;
-                        self.handle_ui_event(&event);
-                    }
+            let elapsed = last_draw.elapsed();
+            if (dirty && elapsed >= self.frame_interval) || elapsed >= IDLE_REDRAW_INTERVAL {
+                terminal.draw(|frame| frame.render_widget(&*self, frame.size()))?;
+                last_draw = Instant::now();
+                dirty = false;
+            }
+
+            let timeout = if dirty {
+                self.frame_interval.saturating_sub(last_draw.elapsed())
+            } else {
+                POLL_INTERVAL
+            };
+            if event::poll(timeout)? {
+                let event = event::read()?;
+                self.handle_ui_event(&event);
+                dirty = true;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Process pending updates for at most [`UPDATE_BUDGET`], switching to the finished state if the channel was
+    /// disconnected
+    ///
+    /// Updates that are superseded by a newer one of the same kind, e.g. step counts and snapshots, are coalesced so
+    /// that only the latest is applied. Updates left in the channel when the budget runs out are processed in the next
+    /// frames, and the dashboard shows that it is lagging behind training.
+    ///
+    /// **Returns** whether anything changed
+    fn receive_updates(&mut self, rx: &Receiver<Update>) -> bool {
+        let start = Instant::now();
+        let mut latest: Vec<Update> = Vec::new();
+        let mut received = false;
+        self.lagging = loop {
+            if start.elapsed() >= UPDATE_BUDGET {
+                break true;
+            }
+            let update = match rx.try_recv() {
+                Ok(update) => update,
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => {
+                    self.state = AppMode::Finished;
+                    received = true;
+                    break false;
                 }
-                AppMode::Error(_) => todo!(),
-                AppMode::Quit => break,
+            };
+            received = true;
+
+            match coalescing_key(&update) {
+                Some(key) => match latest.iter_mut().find(|u| coalescing_key(u) == Some(key)) {
+                    Some(superseded) => {
+                        *superseded = update;
+                        self.skipped_updates += 1;
+                    }
+                    None => latest.push(update),
+                },
+                None => self.apply_update(update, None),
             }
+        };
+
+        for update in latest {
+            self.apply_update(update, None);
         }
+        received
+    }
+}
+
+/// The kind of `update` and the run it belongs to, if only the latest update of its kind and run is shown
+fn coalescing_key(update: &Update) -> Option<(Discriminant<Update>, Option<&str>)> {
+    match update {
+        Update::Step(_)
+        | Update::QSnapshot(_)
+        | Update::PolicySlice(_)
+        | Update::Hyperparams(_) => Some((mem::discriminant(update), None)),
+        Update::Tagged { run, update } => {
+            coalescing_key(update).map(|(kind, _)| (kind, Some(run.as_str())))
+        }
+        _ => None,
+    }
+}
+
+/// The name of the run aggregating `run` over seeds, if `run` is named with a seed, e.g. `seed=3 algo.alpha=0.5`
+fn seed_group(run: &str) -> Option<String> {
+    let parts = run.split_whitespace().collect::<Vec<_>>();
+    let others = parts
+        .iter()
+        .filter(|part| !part.starts_with("seed="))
+        .copied()
+        .collect::<Vec<_>>();
+    if others.len() == parts.len() {
+        return None;
+    }
 
-        tui::restore()
+    Some(if others.is_empty() {
+        String::from("all seeds")
+    } else {
+        format!("{} (all seeds)", others.join(" "))
+    })
+}
+
+/// Prefix an alert message with the run it was triggered in, unless it is the main run
+fn format_notification(run: &str, message: String) -> String {
+    if run == MAIN_RUN {
+        message
+    } else {
+        format!("[{run}] {message}")
     }
 }
 
@@ -135,21 +743,34 @@ impl WidgetRef for App {
         .areas(area);
 
         // Menu
-        let [tabs_area, _, help_area] = Layout::horizontal([
+        let [tabs_area, runs_area, help_area] = Layout::horizontal([
             Constraint::Length(TABS.join(" | ").len() as u16 + 4),
             Constraint::Fill(1),
             Constraint::Length("H - Help Screen".len() as u16 + 4),
         ])
         .areas(menu_area);
 
+        let tabs_block = Block::new().padding(Padding::uniform(1));
+        self.tabs_area.set(tabs_block.inner(tabs_area));
+
         Tabs::new(TABS)
-            .block(Block::new().padding(Padding::uniform(1)))
+            .block(tabs_block)
             .white()
             .bold()
             .highlight_style(Style::new().light_green())
             .select(self.selected_tab)
             .render(tabs_area, buf);
 
+        let runs_block = Block::new().padding(Padding::uniform(1));
+        self.runs_area.set(runs_block.inner(runs_area));
+
+        Tabs::new(self.run_titles())
+            .block(runs_block)
+            .white()
+            .highlight_style(Style::new().light_yellow())
+            .select(self.selected_run)
+            .render(runs_area, buf);
+
         Paragraph::new(Line::from(vec![
             Span::styled("H", Style::new().bold()),
             Span::raw(" - Help Screen"),
@@ -160,23 +781,65 @@ impl WidgetRef for App {
         // Main
         match self.selected_tab {
             1 => self.logs.render(main_area, buf),
-            _ => self.plots.render(main_area, buf),
+            2 => self.render_panel.render(main_area, buf),
+            3 => self.q_heatmap.render(main_area, buf),
+            4 => self.policy_map.render(main_area, buf),
+            _ => self.runs[self.selected_run].plots.render(main_area, buf),
         }
 
         // Progress
-        Gauge::default()
-            .block(
-                Block::bordered()
-                    .border_type(BorderType::Rounded)
-                    .title("Progress"),
-            )
-            .gauge_style(Color::Cyan)
-            .ratio((self.episode + 1) as f64 / self.total_episodes as f64)
+        self.runs[self.selected_run]
+            .progress
             .render(progress_area, buf);
 
+        // Backpressure and the end of training
+        let finished = matches!(self.state, AppMode::Finished);
+        if self.lagging || self.skipped_updates > 0 || finished {
+            let mut status = Vec::new();
+            if finished {
+                status.push(String::from("training finished"));
+            }
+            if self.lagging {
+                status.push(String::from("lagging behind training"));
+            }
+            if self.skipped_updates > 0 {
+                status.push(format!(
+                    "{} superseded updates skipped",
+                    self.skipped_updates
+                ));
+            }
+            let status_area = Rect {
+                x: progress_area.x + 2,
+                y: progress_area.bottom().saturating_sub(1),
+                width: progress_area.width.saturating_sub(4),
+                height: 1,
+            };
+            Line::from(format!(" {} ", status.join(", ")))
+                .light_yellow()
+                .alignment(Alignment::Right)
+                .render(status_area, buf);
+        }
+
+        // Notification
+        if let Some((message, shown)) = &self.notification {
+            if shown.elapsed() < NOTIFICATION_DURATION {
+                render_notification(main_area, buf, message);
+            }
+        }
+
+        // Tuning Popup
+        if self.tuning.is_visible() {
+            self.tuning.render(main_area, buf);
+        }
+
         // Help Popup
         if self.show_help {
             render_help(area, buf, self.selected_tab);
         }
+
+        // Error Popup
+        if let AppMode::Error(message) = &self.state {
+            render_error(area, buf, message);
+        }
     }
 }
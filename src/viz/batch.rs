@@ -0,0 +1,91 @@
+/// Values of named metrics, sent to the dashboard together in one [`Update::Batch`](super::Update::Batch)
+///
+/// Metrics are named as in exported metrics files: `plot` for the primary series of a plot, or `plot/series` for
+/// another series on the same axes, e.g. `return/eval`. Plots and series are created when they first receive a
+/// value, so producers don't need to know the order of the plots passed to [`init`](super::init).
+///
+/// ```ignore
+/// let mut batch = MetricBatch::new();
+/// batch.push("return", episode, ret);
+/// batch.push("return/eval", episode, eval.mean);
+/// tx.send(Update::Batch(batch))?;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricBatch {
+    metrics: Vec<(String, Vec<(u64, f64)>)>,
+}
+
+impl MetricBatch {
+    /// Create an empty batch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a batch of the metrics of one episode
+    pub fn from_episode(episode: u64, metrics: &[(&str, f64)]) -> Self {
+        let mut batch = Self::new();
+        for &(name, value) in metrics {
+            batch.push(name, episode, value);
+        }
+        batch
+    }
+
+    /// Add the value of `metric` at `episode`
+    pub fn push(&mut self, metric: &str, episode: u64, value: f64) -> &mut Self {
+        match self.metrics.iter_mut().find(|(name, _)| name == metric) {
+            Some((_, points)) => points.push((episode, value)),
+            None => self
+                .metrics
+                .push((metric.to_string(), vec![(episode, value)])),
+        }
+        self
+    }
+
+    /// Add the value of `metric` at `episode`, see [`push`](MetricBatch::push)
+    pub fn with(mut self, metric: &str, episode: u64, value: f64) -> Self {
+        self.push(metric, episode, value);
+        self
+    }
+
+    /// Add every value of `other`
+    pub fn extend(&mut self, other: MetricBatch) {
+        for (metric, points) in other.metrics {
+            for (episode, value) in points {
+                self.push(&metric, episode, value);
+            }
+        }
+    }
+
+    /// The metrics with their `(episode, value)` points, in the order they were first added
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[(u64, f64)])> {
+        self.metrics
+            .iter()
+            .map(|(name, points)| (name.as_str(), points.as_slice()))
+    }
+
+    /// The number of metrics
+    pub fn len(&self) -> usize {
+        self.metrics.len()
+    }
+
+    /// Whether the batch has no metrics
+    pub fn is_empty(&self) -> bool {
+        self.metrics.is_empty()
+    }
+
+    /// The latest episode of any value, `None` if the batch is empty
+    pub fn last_episode(&self) -> Option<u64> {
+        self.metrics
+            .iter()
+            .flat_map(|(_, points)| points.iter().map(|&(episode, _)| episode))
+            .max()
+    }
+}
+
+/// Split a metric name into its plot and series, where a plot's primary series is named after the plot
+pub(super) fn plot_and_series(metric: &str) -> (&str, &str) {
+    match metric.split_once('/') {
+        Some((plot, series)) => (plot, series),
+        None => (metric, metric),
+    }
+}
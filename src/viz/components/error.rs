@@ -0,0 +1,43 @@
+use ratatui::{prelude::*, widgets::*};
+
+pub fn render_error(area: Rect, buf: &mut Buffer, message: &str) {
+    let lines = vec![
+        Line::from(Span::from(message).light_red().bold()),
+        Line::default(),
+        Line::from(vec![
+            Span::from("  r  ").light_cyan().bold(),
+            Span::raw(" : Dismiss and keep browsing"),
+        ]),
+        Line::from(vec![
+            Span::from("  q  ").light_cyan().bold(),
+            Span::raw(" : Exit viz"),
+        ]),
+    ];
+
+    let [_, center_vert, _] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length((lines.len() + 4) as u16),
+        Constraint::Fill(1),
+    ])
+    .areas(area);
+
+    let [_, center, _] = Layout::horizontal([
+        Constraint::Fill(1),
+        Constraint::Length(60),
+        Constraint::Fill(1),
+    ])
+    .areas(center_vert);
+
+    Clear.render(center, buf);
+
+    Paragraph::new(lines)
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .border_style(Style::new().light_red())
+                .padding(Padding::horizontal(1))
+                .title("Error"),
+        )
+        .wrap(Wrap { trim: false })
+        .render(center, buf);
+}
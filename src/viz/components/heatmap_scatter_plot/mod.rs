@@ -5,6 +5,7 @@ use unicode_width::UnicodeWidthStr;
 
 use points::Points;
 use ratatui::{
+    layout::Flex,
     prelude::*,
     style::Styled,
     widgets::{canvas::Canvas, Block, LegendPosition, WidgetRef},
@@ -84,6 +85,8 @@ pub struct Dataset<'a> {
     name: Option<Line<'a>>,
     /// A reference to the actual data
     data: &'a [(f64, f64)],
+    /// Number of raw samples each point stands for, if the data is decimated
+    weights: Option<&'a [usize]>,
     /// Symbol used for each points of this dataset
     marker: symbols::Marker,
     /// Style used to plot this dataset
@@ -110,6 +113,13 @@ impl<'a> Dataset<'a> {
         self
     }
 
+    /// Set the number of raw samples each point stands for, so decimated data keeps its density
+    #[must_use = "method moves the value of self and returns the modified value"]
+    pub const fn weights(mut self, weights: &'a [usize]) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+
     /// See [`Dataset::marker`](ratatui::widgets::Dataset::marker)
     #[must_use = "method moves the value of self and returns the modified value"]
     pub const fn marker(mut self, marker: symbols::Marker) -> Self {
@@ -153,8 +163,11 @@ struct ChartLayout {
     graph_area: Rect,
 }
 
-/// The same as [`ratatui::widgets::Chart`], but limited to scatter plot, and colors each point on a gradient relative to
+/// The same as [`ratatui::widgets::Chart`], but limited to scatter plots, and colors each point on a gradient relative to
 /// the number of samples collapsed into that point.
+///
+/// Datasets are drawn in order, so later datasets are drawn on top of earlier ones. The legend is always placed in the
+/// top right corner of the graph.
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct HeatmapScatterPlot<'a> {
     /// A block to display around the widget eventually
@@ -163,8 +176,8 @@ pub struct HeatmapScatterPlot<'a> {
     x_axis: Axis<'a>,
     /// The vertical axis
     y_axis: Axis<'a>,
-    /// A reference to the dataset
-    dataset: Dataset<'a>,
+    /// The datasets to draw
+    datasets: Vec<Dataset<'a>>,
     /// The widget base style
     style: Style,
     /// Constraints used to determine whether the legend should be shown or not
@@ -176,13 +189,13 @@ pub struct HeatmapScatterPlot<'a> {
 
 impl<'a> HeatmapScatterPlot<'a> {
     /// See [`Chart::new`](ratatui::widgets::Chart::new)
-    pub fn new(dataset: Dataset<'a>) -> Self {
+    pub fn new(datasets: Vec<Dataset<'a>>) -> Self {
         Self {
             block: None,
             x_axis: Axis::default(),
             y_axis: Axis::default(),
             style: Style::default(),
-            dataset,
+            datasets,
             hidden_legend_constraints: (Constraint::Ratio(1, 4), Constraint::Ratio(1, 4)),
             legend_position: Some(LegendPosition::default()),
         }
@@ -234,6 +247,12 @@ impl<'a> HeatmapScatterPlot<'a> {
     }
 
     /// See [`Chart::layout`](ratatui::widgets::Chart::layout)
+    /// The area in which the data is plotted when the chart is rendered in `area`, excluding axes and labels
+    pub fn graph_area(&self, area: Rect) -> Option<Rect> {
+        self.layout(self.block.inner_if_some(area))
+            .map(|layout| layout.graph_area)
+    }
+
     fn layout(&self, area: Rect) -> Option<ChartLayout> {
         if area.height == 0 || area.width == 0 {
             return None;
@@ -290,45 +309,38 @@ impl<'a> HeatmapScatterPlot<'a> {
             }
         }
 
-        let legend_area = None;
-        // if let Some(legend_position) = self.legend_position {
-        //     let legends = self
-        //         .datasets
-        //         .iter()
-        //         .filter_map(|d| Some(d.name.as_ref()?.width() as u16));
-
-        //     if let Some(inner_width) = legends.clone().max() {
-        //         let legend_width = inner_width + 2;
-        //         let legend_height = legends.count() as u16 + 2;
-
-        //         let [max_legend_width] = Layout::horizontal([self.hidden_legend_constraints.0])
-        //             .flex(Flex::Start)
-        //             .areas(graph_area);
-
-        //         let [max_legend_height] = Layout::vertical([self.hidden_legend_constraints.1])
-        //             .flex(Flex::Start)
-        //             .areas(graph_area);
-
-        //         if inner_width > 0
-        //             && legend_width <= max_legend_width.width
-        //             && legend_height <= max_legend_height.height
-        //         {
-        //             legend_area = legend_position.layout(
-        //                 graph_area,
-        //                 legend_width,
-        //                 legend_height,
-        //                 title_x
-        //                     .and(self.x_axis.title.as_ref())
-        //                     .map(|t| t.width() as u16)
-        //                     .unwrap_or_default(),
-        //                 title_y
-        //                     .and(self.y_axis.title.as_ref())
-        //                     .map(|t| t.width() as u16)
-        //                     .unwrap_or_default(),
-        //             );
-        //         }
-        //     }
-        // }
+        let mut legend_area = None;
+        if self.legend_position.is_some() {
+            let legends = self
+                .datasets
+                .iter()
+                .filter_map(|d| Some(d.name.as_ref()?.width() as u16));
+
+            if let Some(inner_width) = legends.clone().max() {
+                let legend_width = inner_width + 2;
+                let legend_height = legends.count() as u16 + 2;
+
+                let [max_legend_width] = Layout::horizontal([self.hidden_legend_constraints.0])
+                    .flex(Flex::Start)
+                    .areas(graph_area);
+
+                let [max_legend_height] = Layout::vertical([self.hidden_legend_constraints.1])
+                    .flex(Flex::Start)
+                    .areas(graph_area);
+
+                if inner_width > 0
+                    && legend_width <= max_legend_width.width
+                    && legend_height <= max_legend_height.height
+                {
+                    legend_area = Some(Rect::new(
+                        graph_area.right() - legend_width,
+                        graph_area.top(),
+                        legend_width,
+                        legend_height,
+                    ));
+                }
+            }
+        }
         Some(ChartLayout {
             title_x,
             title_y,
@@ -534,12 +546,20 @@ impl WidgetRef for HeatmapScatterPlot<'_> {
             .background_color(self.style.bg.unwrap_or(Color::Reset))
             .x_bounds(self.x_axis.bounds)
             .y_bounds(self.y_axis.bounds)
-            .marker(self.dataset.marker)
+            .marker(
+                self.datasets
+                    .first()
+                    .map_or(symbols::Marker::Braille, |d| d.marker),
+            )
             .paint(|ctx| {
-                ctx.draw(&Points {
-                    coords: self.dataset.data,
-                    gradient: self.dataset.gradient,
-                });
+                for dataset in &self.datasets {
+                    ctx.draw(&Points {
+                        coords: dataset.data,
+                        weights: dataset.weights,
+                        gradient: dataset.gradient,
+                    });
+                    ctx.layer();
+                }
             })
             .render(graph_area, buf);
 
@@ -579,28 +599,28 @@ impl WidgetRef for HeatmapScatterPlot<'_> {
             buf.set_line(x, y, title, width);
         }
 
-        // if let Some(legend_area) = layout.legend_area {
-        //     buf.set_style(legend_area, original_style);
-        //     Block::bordered().render(legend_area, buf);
-
-        //     for (i, (dataset_name, dataset_style)) in self
-        //         .datasets
-        //         .iter()
-        //         .filter_map(|ds| Some((ds.name.as_ref()?, ds.style())))
-        //         .enumerate()
-        //     {
-        //         let name = dataset_name.clone().patch_style(dataset_style);
-        //         name.render(
-        //             Rect {
-        //                 x: legend_area.x + 1,
-        //                 y: legend_area.y + 1 + i as u16,
-        //                 width: legend_area.width - 2,
-        //                 height: 1,
-        //             },
-        //             buf,
-        //         );
-        //     }
-        // }
+        if let Some(legend_area) = layout.legend_area {
+            buf.set_style(legend_area, original_style);
+            Block::bordered().render(legend_area, buf);
+
+            for (i, (dataset_name, dataset_style)) in self
+                .datasets
+                .iter()
+                .filter_map(|ds| Some((ds.name.as_ref()?, Styled::style(ds))))
+                .enumerate()
+            {
+                let name = dataset_name.clone().patch_style(dataset_style);
+                name.render(
+                    Rect {
+                        x: legend_area.x + 1,
+                        y: legend_area.y + 1 + i as u16,
+                        width: legend_area.width - 2,
+                        height: 1,
+                    },
+                    buf,
+                );
+            }
+        }
     }
 }
 
@@ -12,6 +12,8 @@ use super::Hsl;
 pub struct Points<'a> {
     /// List of points to draw
     pub coords: &'a [(f64, f64)],
+    /// Number of raw samples each point stands for, `1` for all points if `None`
+    pub weights: Option<&'a [usize]>,
     /// Density gradient of the points
     pub gradient: (Hsl, Hsl),
 }
@@ -20,13 +22,14 @@ impl<'a> Shape for Points<'a> {
     fn draw(&self, painter: &mut Painter) {
         let mut density_map = HashMap::<(usize, usize), usize>::new();
         let mut grid_points = vec![];
-        for (x, y) in self.coords {
+        for (i, (x, y)) in self.coords.iter().enumerate() {
+            let weight = self.weights.map_or(1, |w| w[i]);
             if let Some((x, y)) = painter.get_point(*x, *y) {
                 grid_points.push((x, y));
                 density_map
                     .entry((x / 2, y / 4)) // assuming BrailleGrid for now
-                    .and_modify(|d| *d += 1)
-                    .or_insert(0);
+                    .and_modify(|d| *d += weight)
+                    .or_insert(weight.saturating_sub(1));
             }
         }
 
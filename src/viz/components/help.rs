@@ -1,7 +1,8 @@
 use ratatui::{prelude::*, widgets::*};
 
 pub fn render_help(area: Rect, buf: &mut Buffer, selected_tab: usize) {
-    let lines = vec![
+    #[allow(unused_mut)]
+    let mut lines = vec![
         vec![
             Span::from("  q  ").light_cyan().bold(),
             Span::raw(" : Stop training and exit viz"),
@@ -12,15 +13,47 @@ pub fn render_help(area: Rect, buf: &mut Buffer, selected_tab: usize) {
         ],
         vec![
             Span::from(" Tab ").light_cyan().bold(),
-            Span::raw(" : Switch tabs"),
+            Span::raw(" : Switch tabs, or click a tab"),
+        ],
+        vec![
+            Span::from("  e  ").light_cyan().bold(),
+            Span::raw(" : Export collected metrics to a CSV file"),
+        ],
+        vec![
+            Span::from(" 1-9 ").light_cyan().bold(),
+            Span::raw(" : Switch runs, or click a run"),
+        ],
+        vec![
+            Span::from("  t  ").light_cyan().bold(),
+            Span::raw(" : Toggle the popup for nudging the agent's hyperparameters"),
         ],
     ];
 
+    #[cfg(feature = "plot-image")]
+    lines.push(vec![
+        Span::from("  i  ").light_cyan().bold(),
+        Span::raw(" : Export plots as PNG images"),
+    ]);
+
     let additional_lines = match selected_tab {
-        0 => vec![vec![
-            Span::from("⬅ / ➡").light_cyan().bold(),
-            Span::raw(" : Switch plots"),
-        ]],
+        0 => vec![
+            vec![
+                Span::from("⬅ / ➡").light_cyan().bold(),
+                Span::raw(" : Switch plots"),
+            ],
+            vec![
+                Span::from("  b  ").light_cyan().bold(),
+                Span::raw(" : Toggle histogram of recent values for the selected plot"),
+            ],
+            vec![
+                Span::from("  l  ").light_cyan().bold(),
+                Span::raw(" : Toggle a logarithmic y axis for the selected plot"),
+            ],
+            vec![
+                Span::from("Drag ").light_cyan().bold(),
+                Span::raw(" : Zoom into a range of episodes, right click to reset"),
+            ],
+        ],
         1 => vec![
             vec![
                 Span::from("  s  ").light_cyan().bold(),
@@ -58,6 +91,20 @@ pub fn render_help(area: Rect, buf: &mut Buffer, selected_tab: usize) {
                 Span::from("Space").light_cyan().bold(),
                 Span::raw(" : Toggles hiding of targets, which have logfilter set to off"),
             ],
+            vec![
+                Span::from("Wheel").light_cyan().bold(),
+                Span::raw(" : Scroll log history"),
+            ],
+        ],
+        2 => vec![
+            vec![
+                Span::from("- / +").light_cyan().bold(),
+                Span::raw(" : Slow down/speed up playback"),
+            ],
+            vec![
+                Span::from("Space").light_cyan().bold(),
+                Span::raw(" : Skip queued frames and show the latest one"),
+            ],
         ],
         _ => vec![],
     };
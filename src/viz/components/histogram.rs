@@ -0,0 +1,106 @@
+use std::collections::VecDeque;
+
+use ratatui::{prelude::*, widgets::*};
+
+/// The maximum number of bins rendered, fewer are used if the area is too narrow
+const MAX_BINS: usize = 20;
+
+/// A histogram of the most recent values of a metric
+///
+/// Useful for spotting multimodal performance (e.g. an agent that either solves the environment or fails immediately),
+/// which a line plot tends to average away.
+pub struct Histogram {
+    values: VecDeque<f64>,
+    window: usize,
+}
+
+impl Histogram {
+    /// Create a histogram over the last `window` values
+    pub fn new(window: usize) -> Self {
+        Self {
+            values: VecDeque::with_capacity(window),
+            window,
+        }
+    }
+
+    /// Record a new value, dropping the oldest value if the window is full
+    ///
+    /// Non-finite values are ignored
+    pub fn push(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        if self.values.len() == self.window {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+    }
+
+    /// Sort the recorded values into at most `n` bins of equal width
+    ///
+    /// **Returns** the lower edge and the number of values of each bin
+    fn bins(&self, n: usize) -> Vec<(f64, u64)> {
+        if self.values.is_empty() || n == 0 {
+            return Vec::new();
+        }
+
+        let (min, max) = self
+            .values
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        let n = if max > min { n } else { 1 };
+        let width = (max - min) / n as f64;
+
+        let mut counts = vec![0; n];
+        for &v in &self.values {
+            let ix = if width > 0.0 {
+                (((v - min) / width) as usize).min(n - 1)
+            } else {
+                0
+            };
+            counts[ix] += 1;
+        }
+
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| (min + i as f64 * width, count))
+            .collect()
+    }
+}
+
+impl WidgetRef for Histogram {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(format!("Histogram (last {} values)", self.values.len()))
+            .padding(Padding::uniform(4));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let bins = self.bins(MAX_BINS.min(inner.width as usize / 4).max(1));
+        if bins.is_empty() {
+            return;
+        }
+
+        let bar_width = (inner.width / bins.len() as u16).saturating_sub(1).max(1);
+        let bars = bins
+            .iter()
+            .map(|&(edge, count)| {
+                Bar::default()
+                    .value(count)
+                    .text_value(count.to_string())
+                    .label(Line::from(format!("{edge:.2}")))
+            })
+            .collect::<Vec<_>>();
+
+        BarChart::default()
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(bar_width)
+            .bar_gap(1)
+            .bar_style(Style::new().cyan())
+            .value_style(Style::new().black().on_cyan())
+            .label_style(Style::new().dark_gray())
+            .render(inner, buf);
+    }
+}
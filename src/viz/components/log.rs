@@ -1,4 +1,4 @@
-use crossterm::event::{Event, KeyCode};
+use crossterm::event::{Event, KeyCode, MouseEventKind};
 use ratatui::{prelude::*, widgets::WidgetRef};
 use tui_logger::{TuiLoggerSmartWidget, TuiWidgetEvent, TuiWidgetState};
 
@@ -33,6 +33,17 @@ impl WidgetRef for Logs {
 
 impl Component for Logs {
     fn handle_ui_event(&mut self, event: &Event) -> bool {
+        if let Event::Mouse(mouse) = event {
+            let widget_event = match mouse.kind {
+                MouseEventKind::ScrollUp => TuiWidgetEvent::PrevPageKey,
+                MouseEventKind::ScrollDown => TuiWidgetEvent::NextPageKey,
+                _ => return false,
+            };
+
+            self.state.transition(widget_event);
+            return true;
+        }
+
         let Some(key) = event_keycode(event) else {
             return false;
         };
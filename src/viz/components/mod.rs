@@ -1,12 +1,25 @@
+pub mod error;
 pub mod heatmap_scatter_plot;
 pub mod help;
+pub mod histogram;
 pub mod log;
+pub mod notification;
 pub mod plot;
+pub mod policy_map;
+pub mod progress;
+pub mod q_heatmap;
+pub mod render;
+pub mod tuning;
 
 use crossterm::event::Event;
 pub use log::Logs;
 pub use plot::Plots;
+pub use policy_map::PolicyMap;
+pub use progress::Progress;
+pub use q_heatmap::QHeatmap;
 use ratatui::widgets::WidgetRef;
+pub use render::RenderPanel;
+pub use tuning::Tuning;
 
 pub trait Component: WidgetRef {
     fn handle_ui_event(&mut self, event: &Event) -> bool;
@@ -0,0 +1,22 @@
+use ratatui::{prelude::*, widgets::*};
+
+/// Render a short notification in the top right corner of `area`
+pub fn render_notification(area: Rect, buf: &mut Buffer, message: &str) {
+    let width = (message.chars().count() as u16 + 4).min(area.width);
+
+    let [_, top_right] =
+        Layout::horizontal([Constraint::Fill(1), Constraint::Length(width)]).areas(area);
+    let [notification, _] =
+        Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).areas(top_right);
+
+    Clear.render(notification, buf);
+
+    Paragraph::new(Span::from(message).light_yellow().bold())
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .border_style(Style::new().light_yellow())
+                .padding(Padding::horizontal(1)),
+        )
+        .render(notification, buf);
+}
@@ -1,43 +1,446 @@
 use super::{
     heatmap_scatter_plot::{Axis, Dataset, HeatmapScatterPlot, Hsl},
+    histogram::Histogram,
     Component,
 };
-use crossterm::event::{Event, KeyCode};
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+};
+
+use crossterm::event::{Event, KeyCode, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     prelude::*,
     style::Stylize,
     widgets::{Block, BorderType, Padding, Tabs, WidgetRef},
 };
 
-use crate::viz::{util::event_keycode, Update};
+use crate::{
+    ds::Decimated,
+    stats::RunningMeanVar,
+    viz::util::{event_keycode, tab_at},
+};
 
-pub struct Plot {
+/// The number of recent values binned in the histogram view of a plot
+const HISTOGRAM_WINDOW: usize = 100;
+
+/// The maximum number of min/max buckets kept per series, bounding memory and render time on long runs
+const MAX_BUCKETS: usize = 1024;
+
+/// Density gradients assigned to the series of a plot in order of creation
+const GRADIENTS: [(Hsl, Hsl); 5] = [
+    (Hsl(173.0, 96.0, 50.0), Hsl(352.0, 94.0, 50.0)),
+    (Hsl(45.0, 96.0, 55.0), Hsl(25.0, 96.0, 45.0)),
+    (Hsl(280.0, 80.0, 65.0), Hsl(300.0, 90.0, 45.0)),
+    (Hsl(210.0, 90.0, 65.0), Hsl(230.0, 90.0, 45.0)),
+    (Hsl(100.0, 80.0, 55.0), Hsl(130.0, 90.0, 35.0)),
+];
+
+/// Faded gradient of baseline series loaded from a previous run
+const BASELINE_GRADIENT: (Hsl, Hsl) = (Hsl(0.0, 0.0, 30.0), Hsl(0.0, 0.0, 55.0));
+
+/// SI prefixes used by [`TickFormat::Si`], from largest to smallest
+const SI_PREFIXES: [(f64, &str); 9] = [
+    (1e12, "T"),
+    (1e9, "G"),
+    (1e6, "M"),
+    (1e3, "k"),
+    (1.0, ""),
+    (1e-3, "m"),
+    (1e-6, "µ"),
+    (1e-9, "n"),
+    (1e-12, "p"),
+];
+
+/// The scale of the y axis of a plot
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Scale {
+    #[default]
+    Linear,
+    /// Logarithmic, e.g. for losses that fall over several orders of magnitude
+    ///
+    /// Values that are not positive are not drawn.
+    Log,
+}
+
+/// How the spread of a series aggregated over several runs is shaded around its mean, e.g. over seeds
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BandKind {
+    /// From the smallest to the largest value of the runs
+    MinMax,
+    /// One standard deviation of the runs on either side of the mean
+    #[default]
+    Std,
+}
+
+/// How the tick labels of an axis are formatted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickFormat {
+    /// A fixed number of decimals, e.g. `1234.57` with 2 decimals
+    Fixed(usize),
+    /// Three significant digits with an SI suffix, e.g. `1.23k` or `45.0µ`
+    Si,
+}
+
+impl Default for TickFormat {
+    fn default() -> Self {
+        Self::Fixed(2)
+    }
+}
+
+impl TickFormat {
+    /// Format a tick label for `value`
+    pub fn format(&self, value: f64) -> String {
+        match *self {
+            TickFormat::Fixed(decimals) => format!("{value:.decimals$}"),
+            TickFormat::Si => {
+                if value == 0.0 || !value.is_finite() {
+                    return format!("{value}");
+                }
+                let (scale, suffix) = SI_PREFIXES
+                    .iter()
+                    .find(|(scale, _)| value.abs() >= *scale)
+                    .unwrap_or(&SI_PREFIXES[SI_PREFIXES.len() - 1]);
+                let scaled = value / scale;
+                let decimals = (2 - scaled.abs().log10().floor() as i32).clamp(0, 2) as usize;
+                format!("{scaled:.decimals$}{suffix}")
+            }
+        }
+    }
+}
+
+/// How the axes of a plot are titled, labelled and scaled, see [`VizConfig::plot_styles`](crate::viz::VizConfig::plot_styles)
+///
+/// ```ignore
+/// let style = PlotStyle::default()
+///     .with_y_scale(Scale::Log)
+///     .with_y_ticks(TickFormat::Si);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotStyle {
+    /// The title of the x axis
+    ///
+    /// **Default:** `"Episode"`
     pub x_title: String,
-    pub y_title: String,
+    /// The title of the y axis, `None` to use the name of the plot
+    ///
+    /// **Default:** `None`
+    pub y_title: Option<String>,
+    /// The unit of the y values, shown after the y axis title, e.g. `"s"`
+    ///
+    /// **Default:** `None`
+    pub y_unit: Option<String>,
+    /// The format of the x tick labels
+    ///
+    /// **Default:** [`TickFormat::Fixed(0)`](TickFormat::Fixed)
+    pub x_ticks: TickFormat,
+    /// The format of the y tick labels
+    ///
+    /// **Default:** [`TickFormat::Fixed(2)`](TickFormat::Fixed)
+    pub y_ticks: TickFormat,
+    /// The scale of the y axis
+    ///
+    /// **Default:** [`Scale::Linear`]
+    pub y_scale: Scale,
+    /// How the spread of series aggregated over seeds is shaded, see
+    /// [`VizConfig::seed_bands`](crate::viz::VizConfig::seed_bands)
+    ///
+    /// **Default:** [`BandKind::Std`]
+    pub band: BandKind,
+}
+
+impl Default for PlotStyle {
+    fn default() -> Self {
+        Self {
+            x_title: String::from("Episode"),
+            y_title: None,
+            y_unit: None,
+            x_ticks: TickFormat::Fixed(0),
+            y_ticks: TickFormat::Fixed(2),
+            y_scale: Scale::Linear,
+            band: BandKind::Std,
+        }
+    }
+}
+
+impl PlotStyle {
+    /// Title the x axis `title`
+    pub fn with_x_title(mut self, title: impl Into<String>) -> Self {
+        self.x_title = title.into();
+        self
+    }
+
+    /// Title the y axis `title` instead of the name of the plot
+    pub fn with_y_title(mut self, title: impl Into<String>) -> Self {
+        self.y_title = Some(title.into());
+        self
+    }
+
+    /// Show `unit` after the y axis title
+    pub fn with_y_unit(mut self, unit: impl Into<String>) -> Self {
+        self.y_unit = Some(unit.into());
+        self
+    }
+
+    /// Format the x tick labels with `ticks`
+    pub fn with_x_ticks(mut self, ticks: TickFormat) -> Self {
+        self.x_ticks = ticks;
+        self
+    }
+
+    /// Format the y tick labels with `ticks`
+    pub fn with_y_ticks(mut self, ticks: TickFormat) -> Self {
+        self.y_ticks = ticks;
+        self
+    }
+
+    /// Use `scale` for the y axis
+    pub fn with_y_scale(mut self, scale: Scale) -> Self {
+        self.y_scale = scale;
+        self
+    }
+
+    /// Shade the spread of aggregated series with `band`
+    pub fn with_band(mut self, band: BandKind) -> Self {
+        self.band = band;
+        self
+    }
+
+    /// The title of the y axis of the plot called `plot`, including the unit
+    pub fn y_axis_title(&self, plot: &str) -> String {
+        let title = self.y_title.as_deref().unwrap_or(plot);
+        match &self.y_unit {
+            Some(unit) => format!("{title} [{unit}]"),
+            None => title.to_string(),
+        }
+    }
+
+    /// Map a y value to the plotted coordinate, `None` if it can't be shown on the scale
+    pub fn to_scale(&self, y: f64) -> Option<f64> {
+        match self.y_scale {
+            Scale::Linear => Some(y),
+            Scale::Log => (y > 0.0).then(|| y.log10()),
+        }
+    }
+
+    /// Format the tick label of a plotted y coordinate, undoing the scale
+    pub fn y_label(&self, y: f64) -> String {
+        match self.y_scale {
+            Scale::Linear => self.y_ticks.format(y),
+            Scale::Log => self.y_ticks.format(10f64.powf(y)),
+        }
+    }
+}
+
+/// A named series of points within a [`Plot`]
+struct Series {
+    name: String,
+    data: Decimated,
+    gradient: (Hsl, Hsl),
+}
+
+impl Series {
+    fn new(name: &str, index: usize) -> Self {
+        Self {
+            name: String::from(name),
+            data: Decimated::new(MAX_BUCKETS),
+            gradient: GRADIENTS[index % GRADIENTS.len()],
+        }
+    }
+
+    fn baseline(name: &str) -> Self {
+        Self {
+            name: format!("{name} (baseline)"),
+            data: Decimated::new(MAX_BUCKETS),
+            gradient: BASELINE_GRADIENT,
+        }
+    }
+}
+
+/// The mean of a series over several runs with a shaded band showing their spread, see [`BandKind`]
+struct Band {
+    name: String,
+    gradient: (Hsl, Hsl),
+    episodes: BTreeMap<u64, (RunningMeanVar, f64, f64)>,
+}
+
+impl Band {
+    fn new(name: &str, index: usize) -> Self {
+        Self {
+            name: String::from(name),
+            gradient: GRADIENTS[index % GRADIENTS.len()],
+            episodes: BTreeMap::new(),
+        }
+    }
+
+    /// Add the value of one run at an episode
+    fn push(&mut self, (x, y): (f64, f64)) {
+        let (stats, min, max) =
+            self.episodes
+                .entry(x as u64)
+                .or_insert((RunningMeanVar::new(), f64::MAX, f64::MIN));
+        stats.push(y);
+        *min = min.min(y);
+        *max = max.max(y);
+    }
+
+    /// The episode, mean, and lower and upper edge of the band at no more than `columns` evenly spaced episodes
+    fn columns(
+        &self,
+        kind: BandKind,
+        columns: usize,
+    ) -> impl Iterator<Item = (f64, f64, f64, f64)> + '_ {
+        let step = self.episodes.len().div_ceil(columns.max(1)).max(1);
+        self.episodes
+            .iter()
+            .step_by(step)
+            .map(move |(&x, (stats, min, max))| {
+                let (low, high) = match kind {
+                    BandKind::MinMax => (*min, *max),
+                    BandKind::Std => (stats.mean() - stats.std(), stats.mean() + stats.std()),
+                };
+                (x as f64, stats.mean(), low, high)
+            })
+    }
+
+    /// The faded gradient of the shaded band
+    fn fill_gradient(&self) -> (Hsl, Hsl) {
+        let (Hsl(h0, s0, _), Hsl(h1, s1, _)) = self.gradient;
+        (Hsl(h0, s0 / 2.0, 25.0), Hsl(h1, s1 / 2.0, 30.0))
+    }
+}
+
+/// A scatter plot of one or more series sharing the same axes
+///
+/// The first series is named after the plot and receives the values sent with [`Update::Episode`](crate::viz::Update::Episode).
+/// A legend is shown once additional series are added. Baseline series from a previous run are drawn faded beneath
+/// the live series. Series aggregated over several runs are drawn as their mean with a shaded band, see
+/// [`Plot::update_band`].
+///
+/// The axes are titled, labelled and scaled according to the plot's [`PlotStyle`].
+///
+/// Dragging across the chart with the mouse zooms into the selected episode range, right clicking resets the zoom.
+pub struct Plot {
+    name: String,
+    style: PlotStyle,
     x_bounds: [f64; 2],
     y_bounds: [f64; 2],
-    x_labels: Vec<String>,
-    y_labels: Vec<String>,
-    data: Vec<(f64, f64)>,
+    series: Vec<Series>,
+    baselines: Vec<Series>,
+    bands: Vec<Band>,
+    histogram: Histogram,
+    show_histogram: bool,
+    zoom: Option<[f64; 2]>,
+    drag_start: Option<u16>,
+    graph_area: Cell<Rect>,
+    /// The last rendered plot, reused until the plot changes or is resized
+    cache: RefCell<Option<Buffer>>,
 }
 
 impl Plot {
     pub fn new(y_label: &str) -> Self {
         Self {
-            x_title: String::from("Episode"),
-            y_title: String::from(y_label),
+            name: String::from(y_label),
+            style: PlotStyle::default(),
             x_bounds: [f64::MAX, f64::MIN],
             y_bounds: [f64::MAX, f64::MIN],
-            x_labels: Vec::new(),
-            y_labels: Vec::new(),
-            data: Vec::new(),
+            series: vec![Series::new(y_label, 0)],
+            baselines: Vec::new(),
+            bands: Vec::new(),
+            histogram: Histogram::new(HISTOGRAM_WINDOW),
+            show_histogram: false,
+            zoom: None,
+            drag_start: None,
+            graph_area: Cell::default(),
+            cache: RefCell::default(),
         }
     }
 
+    /// Iterate over the name and points of each series in this plot
+    ///
+    /// Series with more than [`MAX_BUCKETS`] points are decimated, keeping the minimum and maximum of each bucket.
+    pub fn series(&self) -> impl Iterator<Item = (&str, &[(f64, f64)])> {
+        self.series
+            .iter()
+            .map(|s| (s.name.as_str(), s.data.points()))
+    }
+
+    /// The axis titles, tick formats and scale of this plot
+    pub fn style(&self) -> &PlotStyle {
+        &self.style
+    }
+
+    /// Switch between the scatter plot and the histogram of recent values
+    pub fn toggle_histogram(&mut self) {
+        self.show_histogram ^= true;
+        self.invalidate();
+    }
+
+    /// Switch the y axis between a linear and a logarithmic scale
+    pub fn toggle_log_scale(&mut self) {
+        self.style.y_scale = match self.style.y_scale {
+            Scale::Linear => Scale::Log,
+            Scale::Log => Scale::Linear,
+        };
+        self.invalidate();
+    }
+
+    /// Drop the cached rendering after a change that affects how the plot looks
+    fn invalidate(&mut self) {
+        *self.cache.get_mut() = None;
+    }
+
+    /// Zoom into the episode range between two columns of the last rendered chart
+    fn zoom_to_columns(&mut self, from: u16, to: u16) {
+        let area = self.graph_area.get();
+        if area.width < 2 {
+            return;
+        }
+
+        let [lo, hi] = self.zoom.unwrap_or(self.x_bounds);
+        let x_at = |column: u16| {
+            let column = column.clamp(area.left(), area.right() - 1) - area.left();
+            lo + (hi - lo) * column as f64 / (area.width - 1) as f64
+        };
+
+        self.zoom = Some([x_at(from.min(to)), x_at(from.max(to))]);
+        self.invalidate();
+    }
+
+    /// Handle a mouse event, zooming on drag and resetting the zoom on right click
+    fn handle_mouse(&mut self, mouse: &MouseEvent) -> bool {
+        if self.show_histogram {
+            return false;
+        }
+
+        let area = self.graph_area.get();
+        let in_graph = (area.left()..area.right()).contains(&mouse.column)
+            && (area.top()..area.bottom()).contains(&mouse.row);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if in_graph => {
+                self.drag_start = Some(mouse.column);
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                let Some(start) = self.drag_start.take() else {
+                    return false;
+                };
+                if start.abs_diff(mouse.column) > 1 {
+                    self.zoom_to_columns(start, mouse.column);
+                }
+            }
+            MouseEventKind::Down(MouseButton::Right) if in_graph => {
+                self.zoom = None;
+                self.invalidate();
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
     /// Provide initial x bounds
     pub fn with_x_bounds(mut self, x_bounds: [f64; 2]) -> Self {
         self.x_bounds = x_bounds;
-        self.x_labels = self.x_bounds.iter().map(|x| format!("{x:.2}")).collect();
         self
     }
 
@@ -45,109 +448,408 @@ impl Plot {
     #[allow(unused)]
     pub fn with_y_bounds(mut self, y_bounds: [f64; 2]) -> Self {
         self.y_bounds = y_bounds;
-        self.y_labels = self.y_bounds.iter().map(|x| format!("{x:.2}")).collect();
         self
     }
 
+    /// Title, label and scale the axes with `style`
+    pub fn with_style(mut self, style: PlotStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Add a point to the primary series
     pub fn update(&mut self, point: (f64, f64)) {
-        let mut x_bounds_changed = false;
-        let mut y_bounds_changed = false;
-        if point.0 > self.x_bounds[1] {
-            self.x_bounds[1] = point.0;
-            x_bounds_changed = true;
-        }
-        if point.0 < self.x_bounds[0] {
-            self.x_bounds[0] = point.0;
-            x_bounds_changed = true;
+        self.invalidate();
+        self.update_bounds(point);
+        self.histogram.push(point.1);
+        self.series[0].data.push(point);
+    }
+
+    /// Add a point to the series called `name`, creating the series if necessary
+    pub fn update_series(&mut self, name: &str, point: (f64, f64)) {
+        self.invalidate();
+        self.update_bounds(point);
+
+        let ix = match self.series.iter().position(|s| s.name == name) {
+            Some(ix) => ix,
+            None => {
+                self.series.push(Series::new(name, self.series.len()));
+                self.series.len() - 1
+            }
+        };
+
+        if ix == 0 {
+            self.histogram.push(point.1);
         }
-        if point.1 < self.y_bounds[0] {
-            self.y_bounds[0] = point.1;
-            y_bounds_changed = true;
+        self.series[ix].data.push(point);
+    }
+
+    /// Add the value of one of several runs to the band called `name`, creating the band if necessary
+    ///
+    /// Values at the same episode are aggregated into the mean and spread drawn for that episode.
+    pub fn update_band(&mut self, name: &str, point: (f64, f64)) {
+        self.invalidate();
+        self.update_bounds(point);
+        let ix = match self.bands.iter().position(|b| b.name == name) {
+            Some(ix) => ix,
+            None => {
+                self.bands.push(Band::new(name, self.bands.len()));
+                self.bands.len() - 1
+            }
+        };
+        self.bands[ix].push(point);
+    }
+
+    /// Show `points` from a previous run as a faded baseline under the series called `name`
+    ///
+    /// Replaces any baseline previously set for the same series.
+    pub fn set_baseline(&mut self, name: &str, points: &[(f64, f64)]) {
+        self.invalidate();
+        let mut baseline = Series::baseline(name);
+        for &point in points {
+            self.update_bounds(point);
+            baseline.data.push(point);
         }
-        if point.1 > self.y_bounds[1] {
-            self.y_bounds[1] = point.1;
-            y_bounds_changed = true;
+
+        match self.baselines.iter_mut().find(|b| b.name == baseline.name) {
+            Some(existing) => *existing = baseline,
+            None => self.baselines.push(baseline),
         }
+    }
+
+    fn update_bounds(&mut self, point: (f64, f64)) {
+        self.x_bounds[0] = self.x_bounds[0].min(point.0);
+        self.x_bounds[1] = self.x_bounds[1].max(point.0);
+        self.y_bounds[0] = self.y_bounds[0].min(point.1);
+        self.y_bounds[1] = self.y_bounds[1].max(point.1);
+    }
 
-        if x_bounds_changed {
-            self.x_labels = self.x_bounds.iter().map(|x| format!("{x:.2}")).collect();
+    /// The plotted y bounds, which on a log scale span the positive values of the series
+    ///
+    /// A single positive value is padded by half a decade on either side.
+    fn scaled_y_bounds(&self, series: &[(Vec<(f64, f64)>, Vec<usize>)]) -> [f64; 2] {
+        if self.style.y_scale == Scale::Linear {
+            return self.y_bounds;
         }
-        if y_bounds_changed {
-            self.y_labels = self.y_bounds.iter().map(|x| format!("{x:.2}")).collect();
+        match series
+            .iter()
+            .flat_map(|(points, _)| points.iter().map(|&(_, y)| y))
+            .fold([f64::MAX, f64::MIN], |[lo, hi], y| [lo.min(y), hi.max(y)])
+        {
+            [lo, hi] if lo == hi => [lo - 0.5, hi + 0.5],
+            bounds => bounds,
         }
-
-        self.data.push(point);
     }
 }
 
 impl WidgetRef for Plot {
+    /// Copy the last rendering into `buf`, only drawing the plot again if it changed or `area` was resized
+    ///
+    /// Redrawing the braille canvas of a long run is the most expensive part of a frame, while most frames only
+    /// change other widgets.
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
-        let dataset = Dataset::default()
-            .marker(Marker::Braille)
-            .gradient((Hsl(173.0, 96.0, 50.0), Hsl(352.0, 94.0, 50.0)))
-            .data(&self.data);
+        let mut cache = self.cache.borrow_mut();
+        let cached = match cache.take() {
+            Some(cached) if cached.area == area => cached,
+            _ => {
+                let mut cached = Buffer::empty(area);
+                self.draw(area, &mut cached);
+                cached
+            }
+        };
+        buf.merge(&cached);
+        *cache = Some(cached);
+    }
+}
+
+impl Plot {
+    /// Render the plot into `buf`, bypassing the cache
+    fn draw(&self, area: Rect, buf: &mut Buffer) {
+        if self.show_histogram {
+            self.histogram.render_ref(area, buf);
+            return;
+        }
+
+        // Bands replace the primary series of aggregated plots, which then has no points
+        let series = self
+            .series
+            .iter()
+            .filter(|s| !s.data.points().is_empty() || self.bands.is_empty())
+            .collect::<Vec<_>>();
+        let show_legend = series.len() + self.baselines.len() + self.bands.len() > 1;
+        // Map the points to the y scale, dropping those that can't be shown along with their weights
+        let scaled = match self.style.y_scale {
+            Scale::Linear => Vec::new(),
+            Scale::Log => self
+                .baselines
+                .iter()
+                .chain(series.iter().copied())
+                .map(|s| {
+                    s.data
+                        .points()
+                        .iter()
+                        .zip(s.data.weights())
+                        .filter_map(|(&(x, y), &w)| Some(((x, self.style.to_scale(y)?), w)))
+                        .unzip()
+                })
+                .collect::<Vec<(Vec<(f64, f64)>, Vec<usize>)>>(),
+        };
+
+        let graph_area = self.graph_area.get();
+        let columns = self
+            .bands
+            .iter()
+            .map(|band| {
+                band.columns(self.style.band, 2 * graph_area.width.max(64) as usize)
+                    .filter_map(|(x, mean, low, high)| {
+                        let scale = |y| self.style.to_scale(y);
+                        Some((x, scale(mean)?, scale(low)?, scale(high)?))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let y_bounds = columns.iter().flatten().fold(
+            self.scaled_y_bounds(&scaled),
+            |[lo, hi], &(_, _, low, high)| [lo.min(low), hi.max(high)],
+        );
 
+        // Fill each band with a column of points spaced by the height of a braille dot
+        let dot = (y_bounds[1] - y_bounds[0]) / (4 * graph_area.height.max(16)) as f64;
+        let fills = columns
+            .iter()
+            .map(|columns| {
+                columns
+                    .iter()
+                    .flat_map(|&(x, _, low, high)| {
+                        let dots = if dot > 0.0 {
+                            ((high - low) / dot) as usize
+                        } else {
+                            0
+                        };
+                        (0..=dots).map(move |i| (x, low + i as f64 * dot))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let means = columns
+            .iter()
+            .map(|columns| {
+                columns
+                    .iter()
+                    .map(|&(x, mean, _, _)| (x, mean))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let ones = vec![1; fills.iter().chain(&means).map(Vec::len).max().unwrap_or(0)];
+
+        let series = self
+            .baselines
+            .iter()
+            .chain(series.iter().copied())
+            .enumerate()
+            .map(|(i, s)| {
+                let (data, weights) = match scaled.get(i) {
+                    Some((points, weights)) => (points.as_slice(), weights.as_slice()),
+                    None => (s.data.points(), s.data.weights()),
+                };
+                dataset(
+                    s.gradient,
+                    data,
+                    weights,
+                    show_legend.then_some(s.name.as_str()),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Shade the bands between the baselines and the live series, and draw their means on top
+        let mut datasets = series;
+        let live = datasets.split_off(self.baselines.len());
+        for (band, fill) in self.bands.iter().zip(&fills) {
+            datasets.push(dataset(
+                band.fill_gradient(),
+                fill,
+                &ones[..fill.len()],
+                None,
+            ));
+        }
+        datasets.extend(live);
+        for (band, mean) in self.bands.iter().zip(&means) {
+            datasets.push(dataset(
+                band.gradient,
+                mean,
+                &ones[..mean.len()],
+                show_legend.then_some(band.name.as_str()),
+            ));
+        }
+
+        let x_bounds = self.zoom.unwrap_or(self.x_bounds);
         let x_axis = Axis::default()
-            .title(self.x_title.as_str())
+            .title(self.style.x_title.as_str())
             .dark_gray()
             .labels(
-                self.x_labels
-                    .clone()
-                    .into_iter()
+                labels(x_bounds, |x| self.style.x_ticks.format(x))
                     .map(|l| l.bold())
                     .collect(),
             )
-            .bounds(self.x_bounds);
+            .bounds(x_bounds);
 
         let y_axis = Axis::default()
-            .title(self.y_title.as_str())
+            .title(self.style.y_axis_title(&self.name))
             .dark_gray()
             .labels(
-                self.y_labels
-                    .clone()
-                    .into_iter()
+                labels(y_bounds, |y| self.style.y_label(y))
                     .map(|l| l.bold())
                     .collect(),
             )
-            .bounds(self.y_bounds);
+            .bounds(y_bounds);
 
+        let mut notes = Vec::new();
+        if self.zoom.is_some() {
+            notes.push(String::from("zoomed, right click to reset"));
+        }
+        if self.style.y_scale == Scale::Log {
+            let total = self
+                .baselines
+                .iter()
+                .chain(&self.series)
+                .map(|s| s.data.points().len())
+                .sum::<usize>();
+            let shown = scaled.iter().map(|(points, _)| points.len()).sum::<usize>();
+            notes.push(match total - shown {
+                0 => String::from("log scale"),
+                hidden => format!("log scale, {hidden} values ≤ 0 hidden"),
+            });
+        }
         let block = Block::bordered()
             .border_type(BorderType::Rounded)
-            .title("Plots")
+            .title(if notes.is_empty() {
+                String::from("Plots")
+            } else {
+                format!("Plots ({})", notes.join("; "))
+            })
             .padding(Padding::uniform(4));
 
-        let chart = HeatmapScatterPlot::new(dataset)
+        let chart = HeatmapScatterPlot::new(datasets)
             .block(block)
             .x_axis(x_axis)
             .y_axis(y_axis);
 
+        self.graph_area
+            .set(chart.graph_area(area).unwrap_or_default());
         chart.render(area, buf);
     }
 }
 
+/// A braille dataset of `data` colored by `gradient`, named in the legend if `name` is given
+fn dataset<'a>(
+    gradient: (Hsl, Hsl),
+    data: &'a [(f64, f64)],
+    weights: &'a [usize],
+    name: Option<&'a str>,
+) -> Dataset<'a> {
+    let dataset = Dataset::default()
+        .marker(Marker::Braille)
+        .gradient(gradient)
+        .style(gradient.0)
+        .data(data)
+        .weights(weights);
+    match name {
+        Some(name) => dataset.name(name),
+        None => dataset,
+    }
+}
+
+/// Tick labels at both ends of `bounds`, none before the bounds are known
+fn labels(bounds: [f64; 2], format: impl Fn(f64) -> String) -> impl Iterator<Item = String> {
+    let known = bounds[0] <= bounds[1];
+    bounds.into_iter().filter(move |_| known).map(format)
+}
+
 pub struct Plots {
-    plot_names: Vec<&'static str>,
+    plot_names: Vec<String>,
     plots: Vec<Plot>,
+    styles: Vec<(String, PlotStyle)>,
     selected: usize,
+    episodes: u64,
+    tabs_area: Cell<Rect>,
 }
 
 impl Plots {
-    pub fn new(names: Vec<&'static str>, episodes: u16) -> Self {
+    pub fn new(names: &[&str], episodes: u64) -> Self {
         let plots = names
             .iter()
-            .map(|k| Plot::new(k).with_x_bounds([0.0, episodes.into()]))
+            .map(|k| Plot::new(k).with_x_bounds([0.0, episodes as f64]))
             .collect();
         Self {
-            plot_names: names,
+            plot_names: names.iter().map(|name| name.to_string()).collect(),
             plots,
+            styles: Vec::new(),
             selected: 0,
+            episodes,
+            tabs_area: Cell::default(),
+        }
+    }
+
+    /// Style the plots named in `styles`, including plots created later by [`Plots::update_series`]
+    pub fn set_styles(&mut self, styles: Vec<(String, PlotStyle)>) {
+        for (name, plot) in self.plot_names.iter().zip(&mut self.plots) {
+            if let Some((_, style)) = styles.iter().find(|(plot, _)| plot == name) {
+                plot.style = style.clone();
+                plot.invalidate();
+            }
         }
+        self.styles = styles;
     }
 
     pub fn len(&self) -> usize {
         self.plot_names.len()
     }
 
+    /// Iterate over each plot and its name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Plot)> {
+        self.plot_names.iter().map(String::as_str).zip(&self.plots)
+    }
+
+    /// Iterate over the name and data of each series of each plot
+    ///
+    /// Primary series are named after their plot, additional series are named `plot/series`.
+    pub fn series(&self) -> impl Iterator<Item = (String, &[(f64, f64)])> {
+        self.plot_names
+            .iter()
+            .zip(&self.plots)
+            .flat_map(|(plot_name, plot)| {
+                plot.series().enumerate().map(move |(i, (name, data))| {
+                    let name = match i {
+                        0 => plot_name.to_string(),
+                        _ => format!("{plot_name}/{name}"),
+                    };
+                    (name, data)
+                })
+            })
+    }
+
+    /// Show a metric from a previous run as a faded baseline
+    ///
+    /// `metric` is named as in [`Plots::series`]. Returns `false` if no plot matches the name.
+    pub fn set_baseline(&mut self, metric: &str, points: &[(f64, f64)]) -> bool {
+        let (plot, series) = match self.plot_names.iter().position(|name| name == metric) {
+            Some(ix) => (ix, metric),
+            None => {
+                let Some((plot, series)) = metric.split_once('/') else {
+                    return false;
+                };
+                let Some(ix) = self.plot_names.iter().position(|name| name == plot) else {
+                    return false;
+                };
+                (ix, series)
+            }
+        };
+
+        self.plots[plot].set_baseline(series, points);
+        true
+    }
+
     pub fn next_plot(&mut self) {
         self.selected = (self.selected + 1) % self.len()
     }
@@ -157,31 +859,89 @@ impl Plots {
         self.selected = (self.selected + len - 1) % len;
     }
 
-    pub fn update(&mut self, update: Update) {
-        let Update { episode, data } = update;
+    pub fn update(&mut self, episode: u64, data: &[f64]) {
         for (i, metric) in data.iter().enumerate() {
             self.plots[i].update((episode as f64, *metric));
         }
     }
+
+    /// Add a point to a named series of the plot called `plot`, creating the plot if necessary
+    pub fn update_series(&mut self, plot: &str, series: &str, point: (f64, f64)) {
+        self.plot_mut(plot).update_series(series, point);
+    }
+
+    /// Add the value of one of several runs to a named band of the plot called `plot`, creating the plot if
+    /// necessary, see [`Plot::update_band`]
+    pub fn update_band(&mut self, plot: &str, band: &str, point: (f64, f64)) {
+        self.plot_mut(plot).update_band(band, point);
+    }
+
+    /// Get the plot called `plot`, creating it with its style if necessary
+    fn plot_mut(&mut self, plot: &str) -> &mut Plot {
+        let ix = match self.plot_names.iter().position(|name| name == plot) {
+            Some(ix) => ix,
+            None => {
+                let style = self
+                    .styles
+                    .iter()
+                    .find(|(name, _)| name == plot)
+                    .map(|(_, style)| style.clone())
+                    .unwrap_or_default();
+                self.plot_names.push(plot.to_string());
+                self.plots.push(
+                    Plot::new(plot)
+                        .with_x_bounds([0.0, self.episodes as f64])
+                        .with_style(style),
+                );
+                self.plots.len() - 1
+            }
+        };
+
+        &mut self.plots[ix]
+    }
 }
 
 impl WidgetRef for Plots {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
-        Tabs::new(self.plot_names.iter().copied())
-            .block(Block::default().padding(Padding::uniform(2)))
+        // The plot is merged from its cache including blank cells, so draw the tabs over it
+        if !self.plots.is_empty() {
+            self.plots[self.selected].render(area, buf);
+        }
+
+        let tabs_block = Block::default().padding(Padding::uniform(2));
+        self.tabs_area.set(tabs_block.inner(area));
+
+        Tabs::new(self.plot_names.iter().map(String::as_str))
+            .block(tabs_block)
             .white()
             .highlight_style(Style::default().light_green())
             .select(self.selected)
             .render(area, buf);
-
-        if !self.plots.is_empty() {
-            self.plots[self.selected].render(area, buf);
-        }
     }
 }
 
 impl Component for Plots {
     fn handle_ui_event(&mut self, event: &Event) -> bool {
+        if let Event::Mouse(mouse) = event {
+            if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+                let tab = tab_at(
+                    self.tabs_area.get(),
+                    self.plot_names.iter().map(String::as_str),
+                    mouse.column,
+                    mouse.row,
+                );
+                if let Some(tab) = tab {
+                    self.selected = tab;
+                    return true;
+                }
+            }
+
+            return match self.plots.get_mut(self.selected) {
+                Some(plot) => plot.handle_mouse(mouse),
+                None => false,
+            };
+        }
+
         let Some(key) = event_keycode(event) else {
             return false;
         };
@@ -189,6 +949,12 @@ impl Component for Plots {
         match key {
             KeyCode::Left => self.prev_plot(),
             KeyCode::Right => self.next_plot(),
+            KeyCode::Char('b') if !self.plots.is_empty() => {
+                self.plots[self.selected].toggle_histogram()
+            }
+            KeyCode::Char('l') if !self.plots.is_empty() => {
+                self.plots[self.selected].toggle_log_scale()
+            }
             _ => return false,
         }
 
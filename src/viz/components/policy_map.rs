@@ -0,0 +1,316 @@
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{env::Environment, traits::Agent};
+
+/// The colors of the actions of a [`PolicySlice`], in order of first appearance
+const ACTION_HUES: [f64; 8] = [210.0, 30.0, 120.0, 0.0, 270.0, 60.0, 180.0, 330.0];
+
+/// One dimension of a [`PolicySlice`], `resolution` evenly spaced values from `low` to `high`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Axis {
+    name: String,
+    low: f32,
+    high: f32,
+    resolution: usize,
+}
+
+impl Axis {
+    /// Create an axis called `name`, e.g. the state dimension it sweeps
+    ///
+    /// **Panics** if `resolution` is zero or `low` is greater than `high`
+    pub fn new(name: impl Into<String>, low: f32, high: f32, resolution: usize) -> Self {
+        assert!(resolution > 0, "An axis has at least one value");
+        assert!(low <= high, "low must not be greater than high");
+        Self {
+            name: name.into(),
+            low,
+            high,
+            resolution,
+        }
+    }
+
+    /// The name of the axis
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The `i`th value of the axis
+    pub fn value(&self, i: usize) -> f32 {
+        match self.resolution {
+            1 => (self.low + self.high) / 2.0,
+            n => self.low + (self.high - self.low) * i as f32 / (n - 1) as f32,
+        }
+    }
+
+    /// The number of values of the axis
+    pub fn resolution(&self) -> usize {
+        self.resolution
+    }
+
+    /// The range of the axis, `(low, high)`
+    pub fn range(&self) -> (f32, f32) {
+        (self.low, self.high)
+    }
+}
+
+/// The greedy actions, and optionally values, of an agent on a 2D grid through a continuous state space
+///
+/// The grid sweeps two state dimensions while the others are held fixed, e.g. the pole angle and angular velocity
+/// of CartPole at zero cart position and velocity. The boundaries between the actions show what the agent has
+/// learned at a glance. Send it to the dashboard with [`Update::PolicySlice`](crate::viz::Update::PolicySlice) or
+/// export it with [`write_policy_slice`](crate::viz::image::write_policy_slice).
+///
+/// ```ignore
+/// let slice = PolicySlice::sweep(
+///     &agent,
+///     &env,
+///     Axis::new("angle", -0.2, 0.2, 40),
+///     Axis::new("angular velocity", -2.0, 2.0, 20),
+///     |angle, velocity| [0.0, 0.0, angle, velocity],
+/// );
+/// tx.send(Update::PolicySlice(slice))?;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicySlice {
+    x: Axis,
+    y: Axis,
+    actions: Vec<String>,
+    cells: Vec<(usize, Option<f64>)>,
+}
+
+impl PolicySlice {
+    /// Build a slice from a function of the coordinates of each cell to the label of its action and its value
+    ///
+    /// Actions with the same label share a color.
+    pub fn from_fn(
+        x: Axis,
+        y: Axis,
+        mut cell: impl FnMut(f32, f32) -> (String, Option<f64>),
+    ) -> Self {
+        let mut actions = Vec::new();
+        let mut cells = Vec::with_capacity(x.resolution * y.resolution);
+        for j in 0..y.resolution {
+            for i in 0..x.resolution {
+                let (action, value) = cell(x.value(i), y.value(j));
+                let index = match actions.iter().position(|a| *a == action) {
+                    Some(index) => index,
+                    None => {
+                        actions.push(action);
+                        actions.len() - 1
+                    }
+                };
+                cells.push((index, value));
+            }
+        }
+
+        Self {
+            x,
+            y,
+            actions,
+            cells,
+        }
+    }
+
+    /// Query the greedy [policy](Agent::policy) of `agent` at every cell, labelling actions by their `Debug` output
+    ///
+    /// ### Arguments
+    /// - `state_at` - Maps the coordinates of a cell to the state to query, filling in the other dimensions
+    pub fn sweep<E: Environment, A: Agent<E>>(
+        agent: &A,
+        env: &E,
+        x: Axis,
+        y: Axis,
+        state_at: impl Fn(f32, f32) -> E::State,
+    ) -> Self {
+        agent.reset_policy();
+        Self::from_fn(x, y, |x, y| {
+            let action = agent.policy(env, &state_at(x, y));
+            (format!("{action:?}"), None)
+        })
+    }
+
+    /// Add a value to every cell, e.g. the maximum Q value of its state, which shades the heatmap
+    pub fn with_values(mut self, value: impl Fn(f32, f32) -> f64) -> Self {
+        for j in 0..self.y.resolution {
+            for i in 0..self.x.resolution {
+                self.cells[j * self.x.resolution + i].1 =
+                    Some(value(self.x.value(i), self.y.value(j)));
+            }
+        }
+        self
+    }
+
+    /// The horizontal axis
+    pub fn x(&self) -> &Axis {
+        &self.x
+    }
+
+    /// The vertical axis
+    pub fn y(&self) -> &Axis {
+        &self.y
+    }
+
+    /// The labels of the actions, in order of first appearance
+    pub fn actions(&self) -> &[String] {
+        &self.actions
+    }
+
+    /// The index of the action and the value of the cell at `(i, j)`, with `j` indexing the vertical axis
+    ///
+    /// **Panics** if `(i, j)` is outside of the grid
+    pub fn get(&self, i: usize, j: usize) -> (usize, Option<f64>) {
+        assert!(
+            i < self.x.resolution && j < self.y.resolution,
+            "cell is within the grid"
+        );
+        self.cells[j * self.x.resolution + i]
+    }
+
+    /// The minimum and maximum value of the cells, if any have values
+    pub fn value_bounds(&self) -> Option<(f64, f64)> {
+        self.cells
+            .iter()
+            .filter_map(|(_, value)| *value)
+            .fold(None, |bounds, v| match bounds {
+                None => Some((v, v)),
+                Some((lo, hi)) => Some((v.min(lo), v.max(hi))),
+            })
+    }
+
+    /// The hue of the `action`th action, shared by the TUI and image export
+    pub(in crate::viz) fn hue(action: usize) -> f64 {
+        ACTION_HUES[action % ACTION_HUES.len()]
+    }
+
+    /// The lightness of a cell with `value`, from dark for the lowest value to light for the highest
+    pub(in crate::viz) fn lightness(&self, value: Option<f64>) -> f64 {
+        match (value, self.value_bounds()) {
+            (Some(value), Some((min, max))) if max > min => {
+                20.0 + 40.0 * (value - min) / (max - min)
+            }
+            _ => 40.0,
+        }
+    }
+}
+
+/// Displays the most recent [`PolicySlice`] as a heatmap of the greedy actions, with the highest `y` at the top
+pub struct PolicyMap {
+    slice: Option<PolicySlice>,
+}
+
+impl PolicyMap {
+    pub fn new() -> Self {
+        Self { slice: None }
+    }
+
+    pub fn update(&mut self, slice: PolicySlice) {
+        self.slice = Some(slice);
+    }
+}
+
+impl WidgetRef for PolicyMap {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title("Policy")
+            .padding(Padding::uniform(1));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let Some(slice) = &self.slice else {
+            Paragraph::new("Waiting for a policy slice...")
+                .dark_gray()
+                .render(inner, buf);
+            return;
+        };
+
+        let [legend_area, grid_area, x_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+        let legend = slice
+            .actions
+            .iter()
+            .enumerate()
+            .flat_map(|(i, action)| {
+                let color = Color::from_hsl(PolicySlice::hue(i), 70.0, 40.0);
+                [
+                    Span::styled("  ", Style::new().bg(color)),
+                    Span::raw(format!(" {action}   ")),
+                ]
+            })
+            .collect::<Vec<_>>();
+        Line::from(legend).render(legend_area, buf);
+
+        let (y_low, y_high) = slice.y.range();
+        let y_labels = [format!("{y_high:.2}"), format!("{y_low:.2}")];
+        let label_width = y_labels.iter().map(String::len).max().unwrap_or(0) as u16 + 1;
+        let [y_area, grid_area] =
+            Layout::horizontal([Constraint::Length(label_width), Constraint::Fill(1)])
+                .areas(grid_area);
+
+        let cell_width = (grid_area.width / slice.x.resolution as u16).min(4);
+        let cell_height = (grid_area.height / slice.y.resolution as u16).min(2);
+        if cell_width == 0 || cell_height == 0 {
+            Paragraph::new("Area too small")
+                .light_red()
+                .render(grid_area, buf);
+            return;
+        }
+
+        for j in 0..slice.y.resolution {
+            let row = (slice.y.resolution - 1 - j) as u16;
+            for i in 0..slice.x.resolution {
+                let (action, value) = slice.get(i, j);
+                let color = Color::from_hsl(PolicySlice::hue(action), 70.0, slice.lightness(value));
+                let cell_area = Rect::new(
+                    grid_area.x + i as u16 * cell_width,
+                    grid_area.y + row * cell_height,
+                    cell_width,
+                    cell_height,
+                );
+                Block::new().bg(color).render(cell_area, buf);
+            }
+        }
+
+        let grid_height = slice.y.resolution as u16 * cell_height;
+        Line::from(y_labels[0].as_str()).render(
+            Rect {
+                height: 1,
+                ..y_area
+            },
+            buf,
+        );
+        if grid_height > 1 {
+            Line::from(y_labels[1].as_str()).render(
+                Rect {
+                    y: y_area.y + grid_height - 1,
+                    height: 1,
+                    ..y_area
+                },
+                buf,
+            );
+        }
+
+        let (x_low, x_high) = slice.x.range();
+        let grid_width = slice.x.resolution as u16 * cell_width;
+        let x_area = Rect {
+            x: grid_area.x,
+            width: grid_width.min(x_area.width),
+            ..x_area
+        };
+        Line::from(format!("{x_low:.2}")).render(x_area, buf);
+        Line::from(format!("{} →", slice.x.name))
+            .alignment(Alignment::Center)
+            .render(x_area, buf);
+        Line::from(format!("{x_high:.2}"))
+            .alignment(Alignment::Right)
+            .render(x_area, buf);
+        Line::from(format!("↑ {}", slice.y.name))
+            .alignment(Alignment::Right)
+            .render(legend_area, buf);
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    prelude::*,
+    widgets::{block::Title, *},
+};
+
+/// The time span over which the current rate is measured
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+/// The minimum time between two rate samples, bounding the number of samples kept
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The progress of a run, with elapsed time, throughput and an ETA
+///
+/// Progress is measured in episodes, or in environment steps if a total number of steps is given. The throughput
+/// and ETA are computed from the rate over the last few seconds rather than the whole run, since episode lengths
+/// vary a lot during learning.
+pub struct Progress {
+    episode: u64,
+    step: u64,
+    total_episodes: u64,
+    total_steps: Option<u64>,
+    started: Option<Instant>,
+    samples: VecDeque<(Instant, u64, u64)>,
+}
+
+impl Progress {
+    pub fn new(total_episodes: u64, total_steps: Option<u64>) -> Self {
+        Self {
+            episode: 0,
+            step: 0,
+            total_episodes,
+            total_steps,
+            started: None,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Make progress step-based, tracking the step count against `steps`
+    pub fn set_total_steps(&mut self, steps: Option<u64>) {
+        self.total_steps = steps;
+    }
+
+    pub fn episode(&self) -> u64 {
+        self.episode
+    }
+
+    /// Record the most recent episode
+    pub fn set_episode(&mut self, episode: u64) {
+        self.episode = episode;
+        self.sample();
+    }
+
+    /// Record the total number of environment steps taken so far
+    pub fn set_step(&mut self, step: u64) {
+        self.step = step;
+        self.sample();
+    }
+
+    fn sample(&mut self) {
+        let now = Instant::now();
+        self.started.get_or_insert(now);
+
+        if let Some(&(last, _, _)) = self.samples.back() {
+            if now - last < SAMPLE_INTERVAL {
+                return;
+            }
+        }
+
+        self.samples.push_back((now, self.episode, self.step));
+        while self
+            .samples
+            .front()
+            .is_some_and(|&(t, _, _)| now - t > RATE_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// The recent number of episodes and steps per second
+    fn rates(&self) -> Option<(f64, f64)> {
+        let (&(t0, e0, s0), &(t1, e1, s1)) = (self.samples.front()?, self.samples.back()?);
+        let dt = (t1 - t0).as_secs_f64();
+        if dt <= 0.0 {
+            return None;
+        }
+
+        Some((
+            e1.saturating_sub(e0) as f64 / dt,
+            s1.saturating_sub(s0) as f64 / dt,
+        ))
+    }
+
+    /// Current and total progress in the unit the gauge is based on
+    fn progress(&self) -> (u64, u64) {
+        match self.total_steps {
+            Some(total) => (self.step, total),
+            None => (self.episode + 1, self.total_episodes),
+        }
+    }
+
+    fn stats(&self) -> String {
+        let mut stats = Vec::new();
+
+        let rates = self.rates();
+        if let Some((episodes_per_sec, steps_per_sec)) = rates {
+            if self.step > 0 {
+                stats.push(format!("{} steps/s", format_rate(steps_per_sec)));
+            }
+            stats.push(format!("{} eps/s", format_rate(episodes_per_sec)));
+        }
+
+        if let Some(started) = self.started {
+            stats.push(format!("elapsed {}", format_duration(started.elapsed())));
+        }
+
+        let (progress, total) = self.progress();
+        let rate = rates.map(|(episodes, steps)| match self.total_steps {
+            Some(_) => steps,
+            None => episodes,
+        });
+        if let Some(rate) = rate.filter(|&rate| rate > 0.0) {
+            let remaining = total.saturating_sub(progress) as f64 / rate;
+            stats.push(format!(
+                "ETA {}",
+                format_duration(Duration::from_secs_f64(remaining))
+            ));
+        }
+
+        stats.join(" | ")
+    }
+}
+
+impl WidgetRef for Progress {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let (progress, total) = self.progress();
+        let label = match self.total_steps {
+            Some(_) => format!("step {progress}/{total}"),
+            None => format!("episode {progress}/{total}"),
+        };
+
+        Gauge::default()
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title("Progress")
+                    .title(Title::from(self.stats()).alignment(Alignment::Right)),
+            )
+            .gauge_style(Color::Cyan)
+            .ratio((progress as f64 / total.max(1) as f64).min(1.0))
+            .label(label)
+            .render(area, buf);
+    }
+}
+
+fn format_rate(rate: f64) -> String {
+    if rate >= 1000.0 {
+        format!("{:.1}k", rate / 1000.0)
+    } else {
+        format!("{rate:.1}")
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
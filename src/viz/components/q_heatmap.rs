@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use ratatui::{prelude::*, widgets::*};
+
+/// The direction of the greedy action in a cell of a [`QSnapshot`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arrow {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+    Stay,
+}
+
+impl Arrow {
+    fn symbol(self) -> &'static str {
+        match self {
+            Arrow::Up => "↑",
+            Arrow::Down => "↓",
+            Arrow::Left => "←",
+            Arrow::Right => "→",
+            Arrow::UpLeft => "↖",
+            Arrow::UpRight => "↗",
+            Arrow::DownLeft => "↙",
+            Arrow::DownRight => "↘",
+            Arrow::Stay => "•",
+        }
+    }
+}
+
+/// A snapshot of the learned action values of a gridworld
+///
+/// Each cell holds the maximum Q value of its state and the direction of the greedy action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QSnapshot {
+    width: usize,
+    height: usize,
+    cells: Vec<Option<(f64, Arrow)>>,
+}
+
+impl QSnapshot {
+    /// Create an empty snapshot of a `width` by `height` grid
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![None; width * height],
+        }
+    }
+
+    /// Build a snapshot from a Q table such as [`QTableAgent::get_q_table`](crate::algo::tabular::q_table::QTableAgent::get_q_table)
+    ///
+    /// ### Arguments
+    /// - `cell` - Maps a state to its `(x, y)` position in the grid
+    /// - `arrow` - Maps an action to the arrow displayed when it is the greedy action
+    pub fn from_q_table<S, A>(
+        width: usize,
+        height: usize,
+        q_table: &HashMap<(S, A), f32>,
+        cell: impl Fn(&S) -> (usize, usize),
+        arrow: impl Fn(&A) -> Arrow,
+    ) -> Self {
+        let mut snapshot = Self::new(width, height);
+        for ((state, action), &value) in q_table {
+            let (x, y) = cell(state);
+            let value = value as f64;
+            let is_max = snapshot
+                .get(x, y)
+                .map_or(true, |(max_value, _)| value > max_value);
+            if is_max {
+                snapshot.set(x, y, value, arrow(action));
+            }
+        }
+
+        snapshot
+    }
+
+    /// Set the maximum Q value and greedy action of the cell at `(x, y)`
+    ///
+    /// **Panics** if `(x, y)` is outside of the grid
+    pub fn set(&mut self, x: usize, y: usize, value: f64, arrow: Arrow) {
+        assert!(x < self.width && y < self.height, "cell is within the grid");
+        self.cells[y * self.width + x] = Some((value, arrow));
+    }
+
+    /// Get the maximum Q value and greedy action of the cell at `(x, y)`, if it has been set
+    pub fn get(&self, x: usize, y: usize) -> Option<(f64, Arrow)> {
+        self.cells.get(y * self.width + x).copied().flatten()
+    }
+
+    fn value_bounds(&self) -> (f64, f64) {
+        self.cells
+            .iter()
+            .flatten()
+            .fold((f64::MAX, f64::MIN), |(lo, hi), &(v, _)| {
+                (lo.min(v), hi.max(v))
+            })
+    }
+}
+
+/// Displays the most recent [`QSnapshot`] as a heatmap with arrows for the greedy policy
+pub struct QHeatmap {
+    snapshot: Option<QSnapshot>,
+}
+
+impl QHeatmap {
+    pub fn new() -> Self {
+        Self { snapshot: None }
+    }
+
+    pub fn update(&mut self, snapshot: QSnapshot) {
+        self.snapshot = Some(snapshot);
+    }
+}
+
+impl WidgetRef for QHeatmap {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title("Q-Values")
+            .padding(Padding::uniform(1));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let Some(snapshot) = &self.snapshot else {
+            Paragraph::new("Waiting for a Q snapshot...")
+                .dark_gray()
+                .render(inner, buf);
+            return;
+        };
+
+        if snapshot.width == 0 || snapshot.height == 0 {
+            return;
+        }
+
+        let cell_width = (inner.width / snapshot.width as u16).min(8);
+        let cell_height = (inner.height / snapshot.height as u16).min(3);
+        if cell_width == 0 || cell_height == 0 {
+            Paragraph::new("Area too small")
+                .light_red()
+                .render(inner, buf);
+            return;
+        }
+
+        let (min, max) = snapshot.value_bounds();
+        let range = max - min;
+
+        for y in 0..snapshot.height {
+            for x in 0..snapshot.width {
+                let cell_area = Rect::new(
+                    inner.x + x as u16 * cell_width,
+                    inner.y + y as u16 * cell_height,
+                    cell_width,
+                    cell_height,
+                );
+
+                let Some((value, arrow)) = snapshot.get(x, y) else {
+                    Paragraph::new("·")
+                        .dark_gray()
+                        .alignment(Alignment::Center)
+                        .render(cell_area, buf);
+                    continue;
+                };
+
+                // Red for the lowest value up to green for the highest
+                let p = if range > 0.0 {
+                    (value - min) / range
+                } else {
+                    1.0
+                };
+                let bg = Color::from_hsl(120.0 * p, 70.0, 30.0);
+
+                let mut lines = vec![Line::from(arrow.symbol()).bold()];
+                if cell_height > 1 {
+                    lines.push(Line::from(format!("{value:.2}")));
+                }
+
+                Paragraph::new(lines)
+                    .style(Style::new().white().bg(bg))
+                    .alignment(Alignment::Center)
+                    .render(cell_area, buf);
+            }
+        }
+    }
+}
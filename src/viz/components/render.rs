@@ -0,0 +1,119 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{Event, KeyCode};
+use ratatui::{prelude::*, widgets::*};
+
+use crate::viz::util::event_keycode;
+
+use super::Component;
+
+/// The maximum number of frames waiting to be played back before the oldest ones are dropped
+const MAX_QUEUED_FRAMES: usize = 4096;
+/// The slowest playback speed
+const MAX_FRAME_DELAY: Duration = Duration::from_secs(2);
+
+/// Plays back frames of an environment implementing [`Render`](crate::env::Render)
+///
+/// Frames are queued as they arrive and displayed one at a time, no faster than the adjustable frame delay.
+pub struct RenderPanel {
+    frames: VecDeque<String>,
+    current: String,
+    delay: Duration,
+    last_frame: Instant,
+}
+
+impl RenderPanel {
+    pub fn new() -> Self {
+        Self {
+            frames: VecDeque::new(),
+            current: String::new(),
+            delay: Duration::from_millis(100),
+            last_frame: Instant::now(),
+        }
+    }
+
+    /// Queue a frame for playback
+    pub fn push(&mut self, frame: String) {
+        if self.frames.len() == MAX_QUEUED_FRAMES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    /// Advance playback if the frame delay has elapsed
+    ///
+    /// **Returns** whether a new frame is shown
+    pub fn tick(&mut self) -> bool {
+        if self.last_frame.elapsed() < self.delay {
+            return false;
+        }
+
+        match self.frames.pop_front() {
+            Some(frame) => {
+                self.current = frame;
+                self.last_frame = Instant::now();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Skip all queued frames and show the most recent one
+    fn skip_to_latest(&mut self) {
+        if let Some(frame) = self.frames.pop_back() {
+            self.current = frame;
+            self.frames.clear();
+            self.last_frame = Instant::now();
+        }
+    }
+
+    fn speed_up(&mut self) {
+        self.delay /= 2;
+    }
+
+    fn slow_down(&mut self) {
+        self.delay = (self.delay * 2)
+            .max(Duration::from_millis(1))
+            .min(MAX_FRAME_DELAY);
+    }
+}
+
+impl WidgetRef for RenderPanel {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let title = format!(
+            "Render ({} ms/frame, {} queued)",
+            self.delay.as_millis(),
+            self.frames.len()
+        );
+
+        Paragraph::new(self.current.as_str())
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(title)
+                    .padding(Padding::uniform(1)),
+            )
+            .alignment(Alignment::Center)
+            .render(area, buf);
+    }
+}
+
+impl Component for RenderPanel {
+    fn handle_ui_event(&mut self, event: &Event) -> bool {
+        let Some(key) = event_keycode(event) else {
+            return false;
+        };
+
+        match key {
+            KeyCode::Char('=') | KeyCode::Char('+') => self.speed_up(),
+            KeyCode::Char('-') | KeyCode::Char('_') => self.slow_down(),
+            KeyCode::Char(' ') => self.skip_to_latest(),
+            _ => return false,
+        }
+
+        true
+    }
+}
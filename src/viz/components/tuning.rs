@@ -0,0 +1,133 @@
+use std::sync::mpsc::Sender;
+
+use crossterm::event::{Event, KeyCode};
+use ratatui::{prelude::*, widgets::*};
+
+use super::Component;
+use crate::{
+    traits::Hyperparam,
+    viz::{util::event_keycode, Control},
+};
+
+/// A popup listing the agent's hyperparameters, which nudges the selected one up or down with the keys
+///
+/// Nudges are sent to the training loop as [`Control::Nudge`], which applies them between episodes and sends back
+/// the new values.
+pub struct Tuning {
+    params: Vec<Hyperparam>,
+    selected: usize,
+    visible: bool,
+    control: Option<Sender<Control>>,
+}
+
+impl Tuning {
+    pub fn new() -> Self {
+        Self {
+            params: Vec::new(),
+            selected: 0,
+            visible: false,
+            control: None,
+        }
+    }
+
+    /// Send nudges through `control`
+    pub fn set_control(&mut self, control: Sender<Control>) {
+        self.control = Some(control);
+    }
+
+    /// Show the current values of the hyperparameters
+    pub fn update(&mut self, params: Vec<Hyperparam>) {
+        self.selected = self.selected.min(params.len().saturating_sub(1));
+        self.params = params;
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible ^= true;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Ask the training loop to nudge the selected hyperparameter by `steps`
+    fn nudge(&self, steps: i32) {
+        let (Some(control), Some(param)) = (&self.control, self.params.get(self.selected)) else {
+            return;
+        };
+        let nudge = Control::Nudge {
+            name: param.name.to_string(),
+            steps,
+        };
+        if control.send(nudge).is_err() {
+            log::warn!(target: "tui", "Training loop stopped listening for hyperparameter changes");
+        }
+    }
+}
+
+impl Component for Tuning {
+    fn handle_ui_event(&mut self, event: &Event) -> bool {
+        if !self.visible {
+            return false;
+        }
+        let Some(key) = event_keycode(event) else {
+            return false;
+        };
+
+        match key {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.params.len().saturating_sub(1))
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') | KeyCode::Right => self.nudge(1),
+            KeyCode::Char('-') | KeyCode::Char('_') | KeyCode::Left => self.nudge(-1),
+            KeyCode::Esc | KeyCode::Char('t') => self.visible = false,
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+impl WidgetRef for Tuning {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let mut lines = if self.params.is_empty() {
+            vec![Line::from("The agent has no tunable hyperparameters").dark_gray()]
+        } else {
+            self.params
+                .iter()
+                .enumerate()
+                .map(|(i, param)| {
+                    let line = Line::from(format!("{:<16} {:.4e}", param.name, param.value));
+                    if i == self.selected {
+                        line.light_green().bold()
+                    } else {
+                        line
+                    }
+                })
+                .collect()
+        };
+        lines.push(Line::default());
+        lines.push(match self.control {
+            Some(_) => Line::from("⬆/⬇ select  -/+ nudge  Esc close").dark_gray(),
+            None => Line::from("Set VizConfig::control to send changes").light_red(),
+        });
+
+        let [_, popup] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(44)]).areas(area);
+        let [popup, _] = Layout::vertical([
+            Constraint::Length(lines.len() as u16 + 2),
+            Constraint::Fill(1),
+        ])
+        .areas(popup);
+
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .padding(Padding::horizontal(1))
+                    .title("Hyperparameters"),
+            )
+            .render(popup, buf);
+    }
+}
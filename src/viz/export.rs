@@ -0,0 +1,72 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::csv::{escape_csv, METRICS_HEADER};
+
+/// Write named series of `(episode, value)` points to `path`
+///
+/// The format is chosen from the file extension:
+/// - `.json` or `.jsonl` - one JSON object per line, e.g. `{"metric":"reward","episode":3,"value":1.5}`
+/// - anything else - CSV with the header `metric,episode,value`
+pub(crate) fn write_metrics<'a, S: AsRef<str>>(
+    path: &Path,
+    series: impl IntoIterator<Item = (S, &'a [(f64, f64)])>,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    let is_json = matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("json" | "jsonl")
+    );
+
+    if is_json {
+        for (name, points) in series {
+            for &(x, y) in points {
+                writeln!(
+                    writer,
+                    "{{\"metric\":\"{}\",\"episode\":{},\"value\":{}}}",
+                    escape_json(name.as_ref()),
+                    json_number(x),
+                    json_number(y)
+                )?;
+            }
+        }
+    } else {
+        writeln!(writer, "{METRICS_HEADER}")?;
+        for (name, points) in series {
+            for &(x, y) in points {
+                writeln!(writer, "{},{x},{y}", escape_csv(name.as_ref()))?;
+            }
+        }
+    }
+
+    writer.flush()
+}
+
+/// Escape `s` for a JSON string, including the control characters JSON doesn't allow unescaped
+pub(super) fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c < '\u{20}' => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub(super) fn json_number(x: f64) -> String {
+    if x.is_finite() {
+        x.to_string()
+    } else {
+        String::from("null")
+    }
+}
@@ -0,0 +1,177 @@
+use std::{
+    io::{self, Write},
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+use super::{alert::Alerts, Update};
+
+/// A text-only alternative to the TUI [`App`](super::app::App)
+///
+/// Receives the same [`Update`]s and periodically writes the most recent episode's metrics as a single
+/// `key=value` line, e.g. `episode=42/500 reward=1.2500 steps=17.0000`. Values sent with [`Update::Series`] or
+/// [`Update::Batch`] are included under the plot name, or `plot/series` for secondary series, and values of tagged
+/// runs are prefixed with the run name. The total step count is included once [`Update::Step`] is received. Triggered alerts are written
+/// immediately on their own line. Frames and Q snapshots are ignored.
+pub struct Headless {
+    names: Vec<&'static str>,
+    total_episodes: u64,
+    step: Option<u64>,
+    interval: Duration,
+    out: Box<dyn Write + Send>,
+    episode: u64,
+    latest: Vec<(String, f64)>,
+    changed: bool,
+    alerts: Alerts,
+    run_alerts: Vec<(String, Alerts)>,
+}
+
+impl Headless {
+    /// Create a headless sink writing to `out` every `interval`
+    pub fn new(
+        names: &[&'static str],
+        episodes: u64,
+        interval: Duration,
+        out: Box<dyn Write + Send>,
+    ) -> Self {
+        Self {
+            names: names.to_vec(),
+            total_episodes: episodes,
+            step: None,
+            interval,
+            out,
+            episode: 0,
+            latest: Vec::new(),
+            changed: false,
+            alerts: Alerts::default(),
+            run_alerts: Vec::new(),
+        }
+    }
+
+    /// Check the metrics of every run against `alerts`
+    pub(super) fn with_alerts(mut self, alerts: Alerts) -> Self {
+        self.alerts = alerts;
+        self
+    }
+
+    /// Receive updates until the channel is disconnected
+    ///
+    /// The most recent metrics are always written before returning.
+    pub fn run(&mut self, rx: Receiver<Update>) -> io::Result<()> {
+        let mut last_write = Instant::now();
+
+        loop {
+            let timeout = self.interval.saturating_sub(last_write.elapsed());
+            match rx.recv_timeout(timeout) {
+                Ok(update) => self.apply_update(update, "")?,
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            if last_write.elapsed() >= self.interval {
+                self.write_latest()?;
+                last_write = Instant::now();
+            }
+        }
+
+        self.write_latest()?;
+        self.out.flush()
+    }
+
+    /// Record the metrics of an update, prefixing their names with `prefix`
+    fn apply_update(&mut self, update: Update, prefix: &str) -> io::Result<()> {
+        match update {
+            Update::Episode { episode, data } => {
+                for (i, value) in data.into_iter().enumerate() {
+                    if let Some(&name) = self.names.get(i) {
+                        self.set(episode, &format!("{prefix}{name}"), value);
+                        self.check_alerts(prefix, name, value)?;
+                    }
+                }
+            }
+            Update::Batch(batch) => {
+                for (metric, points) in batch.iter() {
+                    for &(episode, value) in points {
+                        self.set(episode, &format!("{prefix}{metric}"), value);
+                        self.check_alerts(prefix, metric, value)?;
+                    }
+                }
+            }
+            Update::Series {
+                episode,
+                plot,
+                series,
+                value,
+            } => {
+                let metric = if plot == series {
+                    plot
+                } else {
+                    format!("{plot}/{series}")
+                };
+                self.set(episode, &format!("{prefix}{metric}"), value);
+                self.check_alerts(prefix, &metric, value)?;
+            }
+            Update::Step(step) => self.step = Some(step),
+            Update::Failed(message) => writeln!(self.out, "{prefix}error: {message}")?,
+            Update::Tagged { run, update } => {
+                return self.apply_update(*update, &format!("{run}/"))
+            }
+            Update::Frame(_)
+            | Update::QSnapshot(_)
+            | Update::PolicySlice(_)
+            | Update::Hyperparams(_) => (),
+        }
+
+        Ok(())
+    }
+
+    /// Check a value against the alerts of the run with the given name prefix, writing any triggered alerts
+    fn check_alerts(&mut self, prefix: &str, metric: &str, value: f64) -> io::Result<()> {
+        let ix = match self.run_alerts.iter().position(|(p, _)| p == prefix) {
+            Some(ix) => ix,
+            None => {
+                self.run_alerts
+                    .push((prefix.to_string(), self.alerts.clone()));
+                self.run_alerts.len() - 1
+            }
+        };
+
+        for message in self.run_alerts[ix].1.observe(metric, value) {
+            writeln!(self.out, "{prefix}{message}")?;
+        }
+
+        Ok(())
+    }
+
+    /// Record the most recent `value` of the metric called `name`
+    fn set(&mut self, episode: u64, name: &str, value: f64) {
+        self.episode = self.episode.max(episode);
+        self.changed = true;
+        match self.latest.iter_mut().find(|(n, _)| n == name) {
+            Some((_, latest)) => *latest = value,
+            None => self.latest.push((name.to_string(), value)),
+        }
+    }
+
+    fn write_latest(&mut self) -> io::Result<()> {
+        if !self.changed {
+            return Ok(());
+        }
+        self.changed = false;
+
+        write!(
+            self.out,
+            "episode={}/{}",
+            self.episode + 1,
+            self.total_episodes
+        )?;
+        if let Some(step) = self.step {
+            write!(self.out, " step={step}")?;
+        }
+        for (name, value) in &self.latest {
+            write!(self.out, " {name}={value:.4}")?;
+        }
+        writeln!(self.out)?;
+        self.out.flush()
+    }
+}
@@ -0,0 +1,282 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use plotters::{coord::Shift, prelude::*};
+
+use super::{PlotStyle, PolicySlice};
+use crate::report::read_metrics;
+
+/// The size of exported images in pixels
+const IMAGE_SIZE: (u32, u32) = (1024, 640);
+
+/// The file format of exported plot images
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    /// The file extension of this format, without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Svg => "svg",
+        }
+    }
+}
+
+/// Render a chart of named series of `(episode, value)` points to `path`
+///
+/// A legend is drawn if there is more than one series. Non-finite values are skipped.
+pub fn write_plot(
+    path: &Path,
+    format: ImageFormat,
+    title: &str,
+    series: &[(&str, &[(f64, f64)])],
+) -> io::Result<()> {
+    write_styled_plot(path, format, title, &PlotStyle::default(), series)
+}
+
+/// Render a chart like [`write_plot`], with the axis titles, tick formats and y scale of `style`
+///
+/// Values that can't be shown on the y scale are skipped, e.g. values that are not positive on a log scale.
+pub fn write_styled_plot(
+    path: &Path,
+    format: ImageFormat,
+    title: &str,
+    style: &PlotStyle,
+    series: &[(&str, &[(f64, f64)])],
+) -> io::Result<()> {
+    match format {
+        ImageFormat::Png => draw(
+            BitMapBackend::new(path, IMAGE_SIZE).into_drawing_area(),
+            title,
+            style,
+            series,
+        ),
+        ImageFormat::Svg => draw(
+            SVGBackend::new(path, IMAGE_SIZE).into_drawing_area(),
+            title,
+            style,
+            series,
+        ),
+    }
+}
+
+/// Render each plot in a metrics file exported from the TUI to an image in `dir`
+///
+/// Metrics named `plot/series` are drawn on the same chart as `plot`. Returns the paths of the written images.
+pub fn write_metrics_file(
+    metrics: &Path,
+    dir: &Path,
+    format: ImageFormat,
+) -> io::Result<Vec<PathBuf>> {
+    let mut plots: Vec<(String, Vec<(String, Vec<(f64, f64)>)>)> = Vec::new();
+    for (name, points) in read_metrics(metrics)? {
+        let plot = name.split_once('/').map_or(name.as_str(), |(plot, _)| plot);
+        match plots.iter_mut().find(|(p, _)| p == plot) {
+            Some((_, series)) => series.push((name, points)),
+            None => plots.push((plot.to_string(), vec![(name, points)])),
+        }
+    }
+
+    fs::create_dir_all(dir)?;
+    plots
+        .iter()
+        .map(|(plot, series)| {
+            let series = series
+                .iter()
+                .map(|(name, points)| (name.as_str(), points.as_slice()))
+                .collect::<Vec<_>>();
+            let path = dir.join(format!("{}.{}", file_stem(plot), format.extension()));
+            write_plot(&path, format, plot, &series).map(|_| path)
+        })
+        .collect()
+}
+
+/// Render a [`PolicySlice`] to `path` as a heatmap of the greedy actions with a legend
+///
+/// Cells are shaded by their values if the slice has any, from dark for the lowest to light for the highest.
+pub fn write_policy_slice(
+    path: &Path,
+    format: ImageFormat,
+    title: &str,
+    slice: &PolicySlice,
+) -> io::Result<()> {
+    match format {
+        ImageFormat::Png => draw_policy_slice(
+            BitMapBackend::new(path, IMAGE_SIZE).into_drawing_area(),
+            title,
+            slice,
+        ),
+        ImageFormat::Svg => draw_policy_slice(
+            SVGBackend::new(path, IMAGE_SIZE).into_drawing_area(),
+            title,
+            slice,
+        ),
+    }
+}
+
+/// Make `name` safe to use as a file name
+pub(super) fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' | '=' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+fn draw<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    title: &str,
+    style: &PlotStyle,
+    series: &[(&str, &[(f64, f64)])],
+) -> io::Result<()> {
+    // Points mapped to the y scale, skipping those that can't be drawn
+    let scaled = |data: &[(f64, f64)]| {
+        data.iter()
+            .filter_map(|&(x, y)| Some((x, style.to_scale(y)?)))
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+            .collect::<Vec<_>>()
+    };
+    let series = series
+        .iter()
+        .map(|&(name, data)| (name, scaled(data)))
+        .collect::<Vec<_>>();
+
+    let (mut x0, mut x1, mut y0, mut y1) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+    for &(x, y) in series.iter().flat_map(|(_, data)| data.iter()) {
+        x0 = x0.min(x);
+        x1 = x1.max(x);
+        y0 = y0.min(y);
+        y1 = y1.max(y);
+    }
+    if x0 > x1 {
+        (x0, x1, y0, y1) = (0.0, 1.0, 0.0, 1.0);
+    }
+    if x0 == x1 {
+        x1 += 1.0;
+    }
+    if y0 == y1 {
+        (y0, y1) = (y0 - 1.0, y1 + 1.0);
+    }
+
+    let result = (|| {
+        root.fill(&WHITE)?;
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(title, ("sans-serif", 24))
+            .margin(16)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(x0..x1, y0..y1)?;
+
+        chart
+            .configure_mesh()
+            .x_desc(style.x_title.as_str())
+            .y_desc(style.y_axis_title(title))
+            .x_label_formatter(&|&x| style.x_ticks.format(x))
+            .y_label_formatter(&|&y| style.y_label(y))
+            .draw()?;
+
+        for (i, (name, data)) in series.iter().enumerate() {
+            let color = Palette99::pick(i).to_rgba();
+            chart
+                .draw_series(LineSeries::new(data.iter().copied(), color.stroke_width(1)))?
+                .label(*name)
+                .legend(move |(x, y)| {
+                    PathElement::new([(x, y), (x + 16, y)], color.stroke_width(2))
+                });
+        }
+
+        if series.len() > 1 {
+            chart
+                .configure_series_labels()
+                .background_style(WHITE.mix(0.8))
+                .border_style(BLACK)
+                .draw()?;
+        }
+
+        root.present()
+    })();
+
+    result.map_err(|e| io::Error::other(e.to_string()))
+}
+
+fn draw_policy_slice<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    title: &str,
+    slice: &PolicySlice,
+) -> io::Result<()> {
+    let (x, y) = (slice.x(), slice.y());
+    let (x0, x1) = x.range();
+    let (y0, y1) = y.range();
+    // Each cell is centered on its value, so the chart extends half a cell past the range
+    let half = |(low, high): (f32, f32), n: usize| match n {
+        1 => 0.5,
+        n => (high - low) as f64 / (n - 1) as f64 / 2.0,
+    };
+    let (dx, dy) = (
+        half((x0, x1), x.resolution()),
+        half((y0, y1), y.resolution()),
+    );
+
+    let result = (|| {
+        root.fill(&WHITE)?;
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(title, ("sans-serif", 24))
+            .margin(16)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(
+                x0 as f64 - dx..x1 as f64 + dx,
+                y0 as f64 - dy..y1 as f64 + dy,
+            )?;
+
+        chart
+            .configure_mesh()
+            .disable_mesh()
+            .x_desc(x.name())
+            .y_desc(y.name())
+            .draw()?;
+
+        chart.draw_series((0..y.resolution()).flat_map(|j| {
+            (0..x.resolution()).map(move |i| {
+                let (action, value) = slice.get(i, j);
+                let color = HSLColor(
+                    PolicySlice::hue(action) / 360.0,
+                    0.7,
+                    slice.lightness(value) / 100.0,
+                );
+                let (cx, cy) = (x.value(i) as f64, y.value(j) as f64);
+                Rectangle::new([(cx - dx, cy - dy), (cx + dx, cy + dy)], color.filled())
+            })
+        }))?;
+
+        for (i, action) in slice.actions().iter().enumerate() {
+            let color = HSLColor(PolicySlice::hue(i) / 360.0, 0.7, 0.4);
+            chart
+                .draw_series(std::iter::empty::<Rectangle<(f64, f64)>>())?
+                .label(action)
+                .legend(move |(x, y)| {
+                    Rectangle::new([(x, y - 5), (x + 10, y + 5)], color.filled())
+                });
+        }
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+
+        root.present()
+    })();
+
+    result.map_err(|e| io::Error::other(e.to_string()))
+}
@@ -1,26 +1,169 @@
 use std::{
-    io,
-    sync::mpsc::{self, Sender},
+    env,
+    fs::File,
+    io::{self, IsTerminal, Write},
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, SendError, Sender},
     thread::{self, JoinHandle},
+    time::Duration,
 };
 
+use alert::Alerts;
 use app::App;
+use headless::Headless;
 
+use crate::logger::{MetricSink, TensorBoardWriter};
+
+/// Metric threshold alerts
+mod alert;
 /// Root TUI component
 pub mod app;
+/// Named metric updates
+mod batch;
 /// Components that make up the viz TUI
 mod components;
+/// Metric export
+mod export;
+/// Text-only metric sink
+pub mod headless;
+/// Plot image export
+#[cfg(feature = "plot-image")]
+pub mod image;
+/// Keyboard play of environments by humans, with recording of the episodes
+#[cfg(feature = "serde")]
+pub mod play;
+/// Playback of recorded evaluation episodes
+#[cfg(feature = "serde")]
+pub mod replay;
+/// Forwarding of `tracing` events into the log panel
+mod trace;
 /// Boilerplate
 mod tui;
 /// TUI utils
 mod util;
+/// Browser dashboard
+#[cfg(feature = "web-viz")]
+pub mod web;
 
+pub use alert::{Alert, Condition, Control};
 pub use app::Update;
+pub use batch::MetricBatch;
+pub use components::{
+    plot::{BandKind, PlotStyle, Scale, TickFormat},
+    policy_map::{Axis, PolicySlice},
+    q_heatmap::{Arrow, QSnapshot},
+};
+pub use trace::TuiLayer;
+
+/// Configuration for [`init_with_config`]
+#[derive(Debug, Clone)]
+pub struct VizConfig {
+    /// Periodically write metrics as text instead of rendering the TUI, see [`Headless`]
+    ///
+    /// **Default:** `true` if the `RL_VIZ_HEADLESS` environment variable is set or stdout is not a terminal
+    pub headless: bool,
+    /// The interval at which the headless sink writes the most recent metrics
+    ///
+    /// **Default:** `1s`
+    pub headless_interval: Duration,
+    /// A file for the headless sink to write to instead of stdout
+    ///
+    /// **Default:** the value of the `RL_VIZ_OUTPUT` environment variable, if set
+    pub headless_output: Option<PathBuf>,
+    /// Metrics exported from a previous run, shown as faded baselines under the live plots
+    ///
+    /// Ignored in headless mode.
+    ///
+    /// **Default:** the value of the `RL_VIZ_BASELINE` environment variable, if set
+    pub baseline: Option<PathBuf>,
+    /// The shortest time between redraws of the TUI
+    ///
+    /// The TUI only redraws when updates or input arrive, so a longer interval leaves more CPU time to training when
+    /// updates are frequent.
+    ///
+    /// **Default:** `33ms`, about 30 frames per second
+    pub frame_interval: Duration,
+    /// Track progress by the number of environment steps sent with [`Update::Step`] instead of by episode
+    ///
+    /// **Default:** `None`
+    pub total_steps: Option<u64>,
+    /// Axis titles, tick formats and scales of the plots with the given names, e.g. a log scale for `loss`
+    ///
+    /// Plots without a style use [`PlotStyle::default`].
+    ///
+    /// **Default:** none
+    pub plot_styles: Vec<(String, PlotStyle)>,
+    /// Aggregate runs whose names differ only in their seed, e.g. the trials of a sweep over `seed`, into an extra
+    /// run per group that plots the mean of every metric with a shaded band, see [`App::with_seed_bands`]
+    ///
+    /// **Default:** `false`
+    pub seed_bands: bool,
+    /// Alerts shown as notifications when a metric meets a condition
+    ///
+    /// **Default:** none
+    pub alerts: Vec<Alert>,
+    /// A channel for sending [`Control`] messages back to the training loop, e.g. to stop when an alert is triggered
+    /// or to nudge hyperparameters from the tuning popup
+    ///
+    /// **Default:** `None`
+    pub control: Option<Sender<Control>>,
+    /// A directory to also write episode metrics to as TensorBoard event files, see [`TensorBoardWriter`]
+    ///
+    /// **Default:** the value of the `RL_VIZ_TENSORBOARD` environment variable, if set
+    pub tensorboard: Option<PathBuf>,
+    /// Serve a browser dashboard on this address instead of rendering the TUI, see [`web::WebServer`]
+    ///
+    /// Takes precedence over [`headless`](VizConfig::headless). The bound address is logged as a `tracing` info event,
+    /// e.g. to find the port chosen for port `0`.
+    ///
+    /// **Default:** the value of the `RL_VIZ_WEB` environment variable parsed as a socket address, e.g. `0.0.0.0:8080`
+    #[cfg(feature = "web-viz")]
+    pub web: Option<std::net::SocketAddr>,
+    /// A directory to render the plots to as images when the TUI exits, see [`App::export_images`]
+    ///
+    /// **Default:** the value of the `RL_VIZ_IMAGES` environment variable, if set
+    #[cfg(feature = "plot-image")]
+    pub images: Option<PathBuf>,
+    /// The format of images rendered to [`images`](VizConfig::images)
+    ///
+    /// **Default:** [`ImageFormat::Png`](image::ImageFormat::Png)
+    #[cfg(feature = "plot-image")]
+    pub image_format: image::ImageFormat,
+}
+
+impl Default for VizConfig {
+    fn default() -> Self {
+        Self {
+            headless: env::var_os("RL_VIZ_HEADLESS").is_some() || !io::stdout().is_terminal(),
+            headless_interval: Duration::from_secs(1),
+            headless_output: env::var_os("RL_VIZ_OUTPUT").map(PathBuf::from),
+            baseline: env::var_os("RL_VIZ_BASELINE").map(PathBuf::from),
+            frame_interval: Duration::from_millis(33),
+            total_steps: None,
+            plot_styles: Vec::new(),
+            seed_bands: false,
+            alerts: Vec::new(),
+            control: None,
+            tensorboard: env::var_os("RL_VIZ_TENSORBOARD").map(PathBuf::from),
+            #[cfg(feature = "web-viz")]
+            web: env::var("RL_VIZ_WEB")
+                .ok()
+                .and_then(|addr| addr.parse().ok()),
+            #[cfg(feature = "plot-image")]
+            images: env::var_os("RL_VIZ_IMAGES").map(PathBuf::from),
+            #[cfg(feature = "plot-image")]
+            image_format: Default::default(),
+        }
+    }
+}
 
 /// Initialize the viz training dashboard TUI in a separate thread
 ///
 /// Sets up a global [logger](log) that sends log data to the TUI through the log macros
 ///
+/// Uses the default [`VizConfig`], so headless mode can be selected through environment variables without changing
+/// the training code.
+///
 /// ### Arguments
 /// - `plots` - The names of the plots to render in the TUI
 /// - `episodes` - The number of episodes to show on the x-axis
@@ -29,15 +172,225 @@ pub use app::Update;
 /// A tuple `(handle, tx)`
 /// - `handle` - The [JoinHandle] of the TUI thread
 /// - `tx` - A [mpsc::Sender] for transmitting plot data updates to the TUI
-pub fn init(plots: &[&'static str], episodes: u16) -> (JoinHandle<io::Result<()>>, Sender<Update>) {
+pub fn init(plots: &[&'static str], episodes: u64) -> (JoinHandle<io::Result<()>>, Sender<Update>) {
+    init_with_config(plots, episodes, VizConfig::default())
+}
+
+/// Initialize the viz training dashboard with a custom [`VizConfig`]
+///
+/// See [`init`]. In headless mode no logger is installed.
+pub fn init_with_config(
+    plots: &[&'static str],
+    episodes: u64,
+    config: VizConfig,
+) -> (JoinHandle<io::Result<()>>, Sender<Update>) {
+    let (tx, rx) = mpsc::channel();
+    let rx = match &config.tensorboard {
+        Some(logdir) => match TensorBoardWriter::new(logdir) {
+            Ok(writer) => tee_tensorboard(plots.to_vec(), writer, rx),
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to create TensorBoard writer");
+                rx
+            }
+        },
+        None => rx,
+    };
+
+    #[cfg(feature = "web-viz")]
+    if let Some(addr) = config.web {
+        let plots = plots.to_vec();
+        let handle = thread::spawn(move || {
+            let server = web::WebServer::bind(addr, &plots, episodes)?;
+            let addr = server.local_addr()?;
+            tracing::info!(%addr, "serving viz dashboard");
+            server.run(rx)
+        });
+
+        return (handle, tx);
+    }
+
+    if config.headless {
+        let plots = plots.to_vec();
+        let handle = thread::spawn(move || {
+            let out: Box<dyn Write + Send> = match config.headless_output {
+                Some(path) => Box::new(File::create(path)?),
+                None => Box::new(io::stdout()),
+            };
+            Headless::new(&plots, episodes, config.headless_interval, out)
+                .with_alerts(Alerts::new(config.alerts, config.control))
+                .run(rx)
+        });
+
+        return (handle, tx);
+    }
+
     tui_logger::init_logger(log::LevelFilter::Trace).unwrap();
     tui_logger::set_default_level(log::LevelFilter::Warn);
     tui_logger::set_level_for_target("tui", log::LevelFilter::Trace);
+    tui_logger::set_level_for_target(trace::TARGET, log::LevelFilter::Trace);
     tui_logger::move_events();
 
-    let mut app = App::new(plots, episodes);
-    let (tx, rx) = mpsc::channel();
+    let mut app = App::new(plots, episodes).with_frame_interval(config.frame_interval);
+    if let Some(steps) = config.total_steps {
+        app = app.with_total_steps(steps);
+    }
+    if config.seed_bands {
+        app = app.with_seed_bands();
+    }
+    if let Some(control) = &config.control {
+        app = app.with_control(control.clone());
+    }
+    app = app
+        .with_plot_styles(config.plot_styles)
+        .with_alerts(Alerts::new(config.alerts, config.control));
+    #[cfg(feature = "plot-image")]
+    if let Some(dir) = config.images {
+        app = app.with_image_export(dir, config.image_format);
+    }
+    if let Some(path) = config.baseline {
+        if let Err(e) = app.load_baseline(&path) {
+            log::warn!(target: "tui", "Failed to load baseline {}: {e}", path.display());
+        }
+    }
     let handle = thread::spawn(move || app.run(rx));
 
     (handle, tx)
 }
+
+/// A [`Sender`] that tags every update with a run id, so several concurrent runs can share one dashboard
+///
+/// ### Example
+/// ```no_run
+/// use rl::{logger::MetricSink, viz::{self, RunSender}};
+///
+/// let (handle, tx) = viz::init(&["reward"], 500);
+/// let mut runs = [0.1, 0.01].map(|lr| RunSender::new(tx.clone(), format!("lr={lr}")));
+/// runs[0].log_episode(0, &[("reward", 1.0)]).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RunSender {
+    run: String,
+    tx: Sender<Update>,
+}
+
+impl RunSender {
+    /// Wrap `tx` to tag updates with `run`
+    pub fn new(tx: Sender<Update>, run: impl Into<String>) -> Self {
+        Self {
+            run: run.into(),
+            tx,
+        }
+    }
+
+    /// Send an update for this run
+    pub fn send(&self, update: Update) -> Result<(), SendError<Update>> {
+        self.tx.send(Update::Tagged {
+            run: self.run.clone(),
+            update: Box::new(update),
+        })
+    }
+}
+
+impl MetricSink for RunSender {
+    fn log_scalar(&mut self, name: &str, value: f64, step: u64) -> io::Result<()> {
+        self.send(Update::Series {
+            episode: step,
+            plot: name.to_string(),
+            series: name.to_string(),
+            value,
+        })
+        .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "viz channel disconnected"))
+    }
+
+    fn log_episode(&mut self, episode: u64, metrics: &[(&str, f64)]) -> io::Result<()> {
+        self.send(Update::Batch(MetricBatch::from_episode(episode, metrics)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "viz channel disconnected"))
+    }
+}
+
+/// Report metrics to the viz dashboard through the [`Sender`] returned by [`init`]
+///
+/// Each metric is shown in the plot with the same name, which is created if necessary. The metrics of an episode are
+/// sent together as one [`Update::Batch`].
+impl MetricSink for Sender<Update> {
+    fn log_scalar(&mut self, name: &str, value: f64, step: u64) -> io::Result<()> {
+        self.send(Update::Series {
+            episode: step,
+            plot: name.to_string(),
+            series: name.to_string(),
+            value,
+        })
+        .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "viz channel disconnected"))
+    }
+
+    fn log_episode(&mut self, episode: u64, metrics: &[(&str, f64)]) -> io::Result<()> {
+        self.send(Update::Batch(MetricBatch::from_episode(episode, metrics)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "viz channel disconnected"))
+    }
+}
+
+/// Forward updates from `rx` to a new channel, writing episode metrics to `writer` along the way
+///
+/// Metrics keep being written after the receiving end of the returned channel is dropped, e.g. if the TUI is closed
+/// while training continues.
+fn tee_tensorboard(
+    plots: Vec<&'static str>,
+    mut writer: TensorBoardWriter,
+    rx: Receiver<Update>,
+) -> Receiver<Update> {
+    let (tee_tx, tee_rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        for update in rx {
+            if let Err(e) = write_tensorboard(&mut writer, &plots, &update, "") {
+                log::error!(target: "tui", "Failed to write TensorBoard event: {e}");
+            }
+
+            let _ = tee_tx.send(update);
+        }
+    });
+
+    tee_rx
+}
+
+/// Write the metrics of an update to `writer`, prefixing their tags with `prefix`
+fn write_tensorboard(
+    writer: &mut TensorBoardWriter,
+    plots: &[&'static str],
+    update: &Update,
+    prefix: &str,
+) -> io::Result<()> {
+    match update {
+        Update::Episode { episode, data } => {
+            for (name, &value) in plots.iter().zip(data) {
+                writer.add_scalar(&format!("{prefix}{name}"), value as f32, *episode)?;
+            }
+            writer.flush()
+        }
+        Update::Batch(batch) => {
+            for (metric, points) in batch.iter() {
+                for &(episode, value) in points {
+                    writer.add_scalar(&format!("{prefix}{metric}"), value as f32, episode)?;
+                }
+            }
+            writer.flush()
+        }
+        Update::Series {
+            episode,
+            plot,
+            series,
+            value,
+        } => {
+            let tag = if plot == series {
+                format!("{prefix}{plot}")
+            } else {
+                format!("{prefix}{plot}/{series}")
+            };
+            writer.add_scalar(&tag, *value as f32, *episode)
+        }
+        Update::Tagged { run, update } => {
+            write_tensorboard(writer, plots, update, &format!("{prefix}{run}/"))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,281 @@
+use std::{io, path::PathBuf, time::Duration};
+
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{tui, util::event_keycode};
+use crate::{
+    env::{DiscreteActionSpace, Environment, Render},
+    train::{Trajectory, TrajectoryStep},
+};
+
+/// The keys bound to the actions of [`DiscreteActionSpace`] environments by default, in order
+const DEFAULT_KEYS: [char; 10] = ['1', '2', '3', '4', '5', '6', '7', '8', '9', '0'];
+
+/// Lets a human drive an environment from the keyboard, e.g. to get a feel for a task or to record demonstrations
+///
+/// Shows the rendered environment, or the state if there is no renderer, next to the step, return and last action
+/// of the episode. Every bound key takes its action. After an episode ends, `enter` starts the next one, and `r`
+/// restarts the current episode at any time. Bindings take precedence over `r` and `q`, `esc` always quits.
+///
+/// With [`with_recording`](Play::with_recording), every finished episode is written to the directory as a
+/// [`Trajectory`], the format of evaluation episodes recorded by a
+/// [`TrajectoryRecorder`](crate::train::TrajectoryRecorder), so it can be played back with
+/// [`Replay`](super::replay::Replay) or `rl replay <file>`.
+///
+/// ```ignore
+/// Play::new(FrozenLake::new())
+///     .with_keys([
+///         (KeyCode::Left, FLAction::Left),
+///         (KeyCode::Down, FLAction::Down),
+///         (KeyCode::Right, FLAction::Right),
+///         (KeyCode::Up, FLAction::Up),
+///     ])
+///     .with_frames()
+///     .with_recording("demonstrations")
+///     .run()?;
+/// ```
+///
+/// ### Generics
+/// - `E` - The [`Environment`] to play
+pub struct Play<E: Environment> {
+    env: E,
+    keys: Vec<(KeyCode, E::Action)>,
+    render: Option<fn(&E) -> String>,
+    dir: Option<PathBuf>,
+    state: Option<E::State>,
+    trajectory: Trajectory,
+    episode: u64,
+    written: Vec<PathBuf>,
+    message: Option<String>,
+    quit: bool,
+}
+
+impl<E: DiscreteActionSpace> Play<E> {
+    /// Play `env`, with the keys `1` to `9` and `0` bound to the first ten of its
+    /// [actions](DiscreteActionSpace::actions)
+    pub fn new(env: E) -> Self {
+        let keys = DEFAULT_KEYS
+            .into_iter()
+            .map(KeyCode::Char)
+            .zip(env.actions())
+            .collect();
+        Self::from_keys(env, keys)
+    }
+}
+
+impl<E: Environment> Play<E> {
+    /// Play `env` with the actions of `keys`, for environments with continuous or otherwise unlisted actions
+    pub fn from_keys(env: E, keys: Vec<(KeyCode, E::Action)>) -> Self {
+        Self {
+            env,
+            keys,
+            render: None,
+            dir: None,
+            state: None,
+            trajectory: Trajectory::default(),
+            episode: 0,
+            written: Vec::new(),
+            message: None,
+            quit: false,
+        }
+    }
+
+    /// Replace the key bindings with `keys`
+    pub fn with_keys(mut self, keys: impl IntoIterator<Item = (KeyCode, E::Action)>) -> Self {
+        self.keys = keys.into_iter().collect();
+        self
+    }
+
+    /// Write every finished episode to `<dir>/play-<episode>.json`
+    pub fn with_recording(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// The files written so far, in order
+    pub fn written(&self) -> &[PathBuf] {
+        &self.written
+    }
+
+    /// Take back the environment
+    pub fn into_inner(self) -> E {
+        self.env
+    }
+
+    /// Run the TUI until the user quits
+    ///
+    /// **Returns** an error if the terminal can't be used. Failed writes of recorded episodes are shown in the TUI
+    /// instead.
+    pub fn run(&mut self) -> io::Result<()> {
+        self.reset();
+        let mut terminal = tui::init()?;
+        let result = self.main_loop(&mut terminal);
+        tui::restore()?;
+        result
+    }
+
+    fn main_loop(&mut self, terminal: &mut tui::Tui) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| frame.render_widget(&*self, frame.size()))?;
+
+            if event::poll(Duration::from_millis(16))? {
+                let event = event::read()?;
+                self.handle_event(&event);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        let Some(key) = event_keycode(event) else {
+            return;
+        };
+
+        let bound = self.keys.iter().find(|(k, _)| *k == key);
+        if let Some(action) = bound.map(|(_, action)| action.clone()) {
+            if self.state.is_some() {
+                self.step(action);
+            }
+            return;
+        }
+
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('r') => self.reset(),
+            KeyCode::Enter if self.state.is_none() => {
+                self.episode += 1;
+                self.reset();
+            }
+            _ => (),
+        }
+    }
+
+    /// Start the current episode from the beginning, discarding its steps
+    fn reset(&mut self) {
+        let state = self.env.reset();
+        self.trajectory = Trajectory {
+            evaluation: 0,
+            eval_episode: self.episode,
+            initial_state: format!("{state:?}"),
+            initial_frame: self.render.map(|render| render(&self.env)),
+            steps: Vec::new(),
+        };
+        self.state = Some(state);
+    }
+
+    fn step(&mut self, action: E::Action) {
+        let (next_state, reward) = self.env.step(action.clone());
+        self.trajectory.steps.push(TrajectoryStep {
+            action: format!("{action:?}"),
+            reward,
+            next_state: next_state.as_ref().map(|state| format!("{state:?}")),
+            frame: self.render.map(|render| render(&self.env)),
+        });
+        self.state = next_state;
+
+        if self.state.is_none() {
+            if let Err(e) = self.write() {
+                self.message = Some(format!("Failed to write the episode: {e}"));
+            }
+        }
+    }
+
+    /// Write the finished episode, if recording
+    fn write(&mut self) -> io::Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("play-{}.json", self.episode));
+        self.trajectory.save(&path)?;
+        self.message = Some(format!("Saved {}", path.display()));
+        self.written.push(path);
+        Ok(())
+    }
+
+    /// The rendered environment, falling back to the state
+    fn frame(&self) -> String {
+        match (&self.state, self.render) {
+            (_, Some(render)) => render(&self.env),
+            (Some(state), None) => format!("{state:?}"),
+            (None, None) => String::from("terminal"),
+        }
+    }
+}
+
+impl<E: Environment + Render> Play<E> {
+    /// Show the [rendered](Render::render) environment instead of the state, and record the frames
+    pub fn with_frames(mut self) -> Self {
+        self.render = Some(E::render);
+        self
+    }
+}
+
+impl<E: Environment> WidgetRef for Play<E> {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let [main_area, help_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let [frame_area, info_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(32)]).areas(main_area);
+
+        let title = match self.state {
+            Some(_) => format!("Episode {}", self.episode),
+            None => format!("Episode {} finished, enter to continue", self.episode),
+        };
+        Paragraph::new(self.frame())
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(title)
+                    .padding(Padding::uniform(1)),
+            )
+            .alignment(Alignment::Center)
+            .render(frame_area, buf);
+
+        let mut info = vec![
+            Line::from(format!("Step    {}", self.trajectory.len())),
+            Line::from(format!("Return  {:.3}", self.trajectory.episode_return())),
+        ];
+        if let Some(step) = self.trajectory.steps.last() {
+            info.push(Line::from(format!("Action  {}", step.action)));
+            info.push(Line::from(format!("Reward  {:.3}", step.reward)));
+        }
+        if let Some(message) = &self.message {
+            info.push(Line::default());
+            info.push(Line::from(message.as_str()).style(Style::default().dim()));
+        }
+        info.push(Line::default());
+        info.extend(
+            self.keys
+                .iter()
+                .map(|(key, action)| Line::from(format!("{:<7} {action:?}", key_name(*key)))),
+        );
+        Paragraph::new(info)
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title("Step"),
+            )
+            .render(info_area, buf);
+
+        Line::from("keys act  enter next episode  r restart  q quit")
+            .style(Style::default().dim())
+            .render(help_area, buf);
+    }
+}
+
+/// The label of `key` in the list of bindings
+fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => String::from("space"),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Left => String::from("←"),
+        KeyCode::Right => String::from("→"),
+        KeyCode::Up => String::from("↑"),
+        KeyCode::Down => String::from("↓"),
+        key => format!("{key:?}"),
+    }
+}
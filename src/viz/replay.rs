@@ -0,0 +1,195 @@
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{prelude::*, widgets::*};
+
+use super::{tui, util::event_keycode};
+use crate::train::Trajectory;
+
+/// The slowest playback speed
+const MAX_FRAME_DELAY: Duration = Duration::from_secs(2);
+
+/// Plays back a recorded [`Trajectory`] frame by frame
+///
+/// Shows the rendered frame of every step, or the state if the trajectory was recorded without frames, next to the
+/// action, reward and return so far. Playback is paused at the initial state.
+///
+/// Keys: `←`/`→` step, `space` play or pause, `+`/`-` change the speed, `home`/`end` jump to the start or end and
+/// `q` quits.
+pub struct Replay {
+    trajectory: Trajectory,
+    position: usize,
+    playing: bool,
+    delay: Duration,
+    last_frame: Instant,
+    quit: bool,
+}
+
+impl Replay {
+    /// Create a replay of `trajectory`, paused at its initial state
+    pub fn new(trajectory: Trajectory) -> Self {
+        Self {
+            trajectory,
+            position: 0,
+            playing: false,
+            delay: Duration::from_millis(200),
+            last_frame: Instant::now(),
+            quit: false,
+        }
+    }
+
+    /// Run the replay TUI until the user quits
+    pub fn run(&mut self) -> io::Result<()> {
+        let mut terminal = tui::init()?;
+        let result = self.main_loop(&mut terminal);
+        tui::restore()?;
+        result
+    }
+
+    fn main_loop(&mut self, terminal: &mut tui::Tui) -> io::Result<()> {
+        while !self.quit {
+            self.tick();
+            terminal.draw(|frame| frame.render_widget(&*self, frame.size()))?;
+
+            if event::poll(Duration::from_millis(16))? {
+                let event = event::read()?;
+                self.handle_event(&event);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Advance playback if it is playing and the frame delay has elapsed, pausing at the end
+    fn tick(&mut self) {
+        if !self.playing || self.last_frame.elapsed() < self.delay {
+            return;
+        }
+
+        if self.position < self.trajectory.len() {
+            self.position += 1;
+            self.last_frame = Instant::now();
+        } else {
+            self.playing = false;
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        let Some(key) = event_keycode(event) else {
+            return;
+        };
+
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.position = (self.position + 1).min(self.trajectory.len())
+            }
+            KeyCode::Left | KeyCode::Char('h') => self.position = self.position.saturating_sub(1),
+            KeyCode::Home | KeyCode::Char('g') => self.position = 0,
+            KeyCode::End | KeyCode::Char('G') => self.position = self.trajectory.len(),
+            KeyCode::Char(' ') => {
+                if self.position == self.trajectory.len() {
+                    self.position = 0;
+                }
+                self.playing = !self.playing;
+                self.last_frame = Instant::now();
+            }
+            KeyCode::Char('=') | KeyCode::Char('+') => self.delay /= 2,
+            KeyCode::Char('-') | KeyCode::Char('_') => {
+                self.delay = (self.delay * 2)
+                    .max(Duration::from_millis(1))
+                    .min(MAX_FRAME_DELAY)
+            }
+            _ => (),
+        }
+    }
+
+    /// The frame at the current position, falling back to the state
+    fn frame(&self) -> &str {
+        match self.position {
+            0 => self
+                .trajectory
+                .initial_frame
+                .as_deref()
+                .unwrap_or(&self.trajectory.initial_state),
+            i => {
+                let step = &self.trajectory.steps[i - 1];
+                step.frame
+                    .as_deref()
+                    .or(step.next_state.as_deref())
+                    .unwrap_or("terminal")
+            }
+        }
+    }
+}
+
+impl WidgetRef for Replay {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let [main_area, help_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let [frame_area, info_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(32)]).areas(main_area);
+
+        let title = format!(
+            "Evaluation {} episode {} ({} ms/frame{})",
+            self.trajectory.evaluation,
+            self.trajectory.eval_episode,
+            self.delay.as_millis(),
+            if self.playing { "" } else { ", paused" }
+        );
+        Paragraph::new(self.frame())
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(title)
+                    .padding(Padding::uniform(1)),
+            )
+            .alignment(Alignment::Center)
+            .render(frame_area, buf);
+
+        let ret = self.trajectory.steps[..self.position]
+            .iter()
+            .map(|step| step.reward as f64)
+            .sum::<f64>();
+        let mut info = vec![
+            Line::from(format!(
+                "Step    {}/{}",
+                self.position,
+                self.trajectory.len()
+            )),
+            Line::from(format!("Return  {ret:.3}")),
+        ];
+        if let Some(step) = self
+            .position
+            .checked_sub(1)
+            .map(|i| &self.trajectory.steps[i])
+        {
+            info.push(Line::from(format!("Action  {}", step.action)));
+            info.push(Line::from(format!("Reward  {:.3}", step.reward)));
+            info.push(Line::from(format!(
+                "State   {}",
+                step.next_state.as_deref().unwrap_or("terminal")
+            )));
+        } else {
+            info.push(Line::from(format!(
+                "State   {}",
+                self.trajectory.initial_state
+            )));
+        }
+        Paragraph::new(info)
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title("Step"),
+            )
+            .render(info_area, buf);
+
+        Line::from("←/→ step  space play/pause  +/- speed  home/end jump  q quit")
+            .style(Style::default().dim())
+            .render(help_area, buf);
+    }
+}
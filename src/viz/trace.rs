@@ -0,0 +1,124 @@
+use std::fmt::{self, Write};
+
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// The `log` target under which forwarded events show up in the TUI log panel
+pub(super) const TARGET: &str = "tracing";
+
+/// A [`tracing_subscriber`] layer that forwards events into the log panel of the viz TUI
+///
+/// Each event becomes one line with the names and fields of its enclosing spans, its message and its fields, e.g.
+/// `episode{episode=12}:batch{step=4096}: learning step loss=0.031 epsilon=0.42 buffer_size=4096`. Events are
+/// passed on through the [`log`] crate, so they only appear once the TUI is running.
+///
+/// ```ignore
+/// use tracing_subscriber::prelude::*;
+///
+/// tracing_subscriber::registry().with(rl::viz::TuiLayer::default()).init();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct TuiLayer {
+    level: Level,
+}
+
+impl TuiLayer {
+    /// Forward events up to `level`, e.g. [`Level::DEBUG`] to include the loss of every learning step
+    ///
+    /// **Default:** [`Level::INFO`]
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+}
+
+impl Default for TuiLayer {
+    fn default() -> Self {
+        Self { level: Level::INFO }
+    }
+}
+
+impl<S> Layer<S> for TuiLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(fields);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(fields) = span.extensions_mut().get_mut::<Fields>() {
+                values.record(fields);
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let level = *event.metadata().level();
+        if level > self.level {
+            return;
+        }
+
+        let mut line = String::new();
+        for span in ctx
+            .event_scope(event)
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+        {
+            let _ = write!(line, "{}", span.name());
+            if let Some(fields) = span.extensions().get::<Fields>() {
+                let _ = write!(line, "{{{}}}", fields.fields.trim_start());
+            }
+            line.push_str(": ");
+        }
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        line.push_str(&fields.message);
+        line.push_str(&fields.fields);
+
+        let level = match level {
+            Level::ERROR => log::Level::Error,
+            Level::WARN => log::Level::Warn,
+            Level::INFO => log::Level::Info,
+            Level::DEBUG => log::Level::Debug,
+            _ => log::Level::Trace,
+        };
+        log::log!(target: TARGET, level, "{}", line.trim_end());
+    }
+}
+
+/// The message and the formatted ` key=value` pairs of an event or span
+#[derive(Debug, Default)]
+struct Fields {
+    message: String,
+    fields: String,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message = value.to_owned(),
+            name => {
+                let _ = write!(self.fields, " {name}={value}");
+            }
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{value:?}"),
+            name => {
+                let _ = write!(self.fields, " {name}={value:?}");
+            }
+        }
+    }
+}
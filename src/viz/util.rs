@@ -1,4 +1,5 @@
 use crossterm::event::{Event, KeyCode, KeyEventKind};
+use ratatui::layout::Rect;
 
 /// Takes an event, checks if it is a key press event, and returns the [`KeyCode`]
 pub(super) fn event_keycode(event: &Event) -> Option<KeyCode> {
@@ -12,3 +13,34 @@ pub(super) fn event_keycode(event: &Event) -> Option<KeyCode> {
 
     Some(key.code)
 }
+
+/// Find the index of the [`Tabs`](ratatui::widgets::Tabs) title at `column`, `row`
+///
+/// Assumes the default single-space padding and single-width divider. `area` is the area the tabs were rendered in,
+/// excluding any block.
+pub(super) fn tab_at<'a>(
+    area: Rect,
+    titles: impl IntoIterator<Item = &'a str>,
+    column: u16,
+    row: u16,
+) -> Option<usize> {
+    if row != area.y || column < area.x {
+        return None;
+    }
+
+    let mut x = area.x;
+    for (i, title) in titles.into_iter().enumerate() {
+        let width = title.chars().count() as u16 + 2;
+        if column < x + width {
+            return Some(i);
+        }
+
+        // skip the divider
+        x += width + 1;
+        if column < x {
+            return None;
+        }
+    }
+
+    None
+}
@@ -0,0 +1,203 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{mpsc::Receiver, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use super::{
+    export::{escape_json, json_number},
+    Update,
+};
+
+/// The dashboard page, which subscribes to `/events` and draws the plots on canvases
+const INDEX_HTML: &str = include_str!("index.html");
+
+/// The maximum number of episode and metric events replayed to a newly connected browser
+const MAX_HISTORY: usize = 100_000;
+
+/// How long a write to a browser may block before the browser is dropped
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A browser-based alternative to the TUI [`App`](super::app::App) for machines without a TTY
+///
+/// Serves a dashboard page at `/` and streams the received [`Update`]s to it as server-sent events at `/events`.
+/// Browsers that connect mid-run are sent the plot names, the latest step and the history of the run first. Tagged
+/// runs are drawn as separate series on the same plots. Frames and Q snapshots are ignored.
+pub struct WebServer {
+    listener: TcpListener,
+    state: Arc<Mutex<State>>,
+}
+
+struct State {
+    /// The plot names and episode count, sent before anything else
+    init: String,
+    /// The latest step event of each run, `None` for the untagged one
+    steps: BTreeMap<Option<String>, String>,
+    history: VecDeque<String>,
+    clients: Vec<TcpStream>,
+}
+
+impl WebServer {
+    /// Bind the dashboard to `addr`
+    pub fn bind(addr: SocketAddr, plots: &[&'static str], episodes: u64) -> io::Result<Self> {
+        let plots = plots
+            .iter()
+            .map(|name| format!("\"{}\"", escape_json(name)))
+            .collect::<Vec<_>>()
+            .join(",");
+        let init = format!("{{\"type\":\"init\",\"plots\":[{plots}],\"episodes\":{episodes}}}");
+
+        let state = State {
+            init,
+            steps: BTreeMap::new(),
+            history: VecDeque::new(),
+            clients: Vec::new(),
+        };
+
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    /// The address the dashboard is served on
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serve the dashboard and forward updates to connected browsers until the channel is disconnected
+    pub fn run(self, rx: Receiver<Update>) -> io::Result<()> {
+        let listener = self.listener.try_clone()?;
+        let state = Arc::clone(&self.state);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = Arc::clone(&state);
+                thread::spawn(move || {
+                    if let Err(e) = handle_connection(stream, &state) {
+                        log::debug!("web viz connection failed: {e}");
+                    }
+                });
+            }
+        });
+
+        for update in rx {
+            let Some(event) = encode_update(&update) else {
+                continue;
+            };
+
+            let mut state = self.state.lock().unwrap();
+            state
+                .clients
+                .retain_mut(|client| write!(client, "data: {event}\n\n").is_ok());
+            // Only the latest step matters, so steps don't push episodes out of the history
+            if let Some(run) = step_run(&update) {
+                state.steps.insert(run, event);
+                continue;
+            }
+            if state.history.len() == MAX_HISTORY {
+                state.history.pop_front();
+            }
+            state.history.push_back(event);
+        }
+
+        Ok(())
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, state: &Mutex<State>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // drain the headers
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+    match path {
+        "/" | "/index.html" => write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{INDEX_HTML}",
+            INDEX_HTML.len()
+        ),
+        "/events" => {
+            stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n"
+            )?;
+
+            let mut state = state.lock().unwrap();
+            write!(stream, "data: {}\n\n", state.init)?;
+            for event in state.history.iter().chain(state.steps.values()) {
+                write!(stream, "data: {event}\n\n")?;
+            }
+            state.clients.push(stream);
+            Ok(())
+        }
+        _ => write!(
+            stream,
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        ),
+    }
+}
+
+/// The run of a step update, `Some(None)` for an untagged step and `None` for other updates
+fn step_run(update: &Update) -> Option<Option<String>> {
+    match update {
+        Update::Step(_) => Some(None),
+        Update::Tagged { run, update } => step_run(update).map(|_| Some(run.clone())),
+        _ => None,
+    }
+}
+
+/// Serialize an update as a JSON event for the dashboard page
+fn encode_update(update: &Update) -> Option<String> {
+    match update {
+        Update::Episode { episode, data } => {
+            let data = data
+                .iter()
+                .map(|&value| json_number(value))
+                .collect::<Vec<_>>()
+                .join(",");
+            Some(format!(
+                "{{\"type\":\"episode\",\"episode\":{episode},\"data\":[{data}]}}"
+            ))
+        }
+        Update::Batch(batch) => {
+            let metrics = batch
+                .iter()
+                .map(|(metric, points)| {
+                    let points = points
+                        .iter()
+                        .map(|&(episode, value)| format!("[{episode},{}]", json_number(value)))
+                        .collect::<Vec<_>>()
+                        .join(",");
+                    format!("\"{}\":[{points}]", escape_json(metric))
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            Some(format!("{{\"type\":\"batch\",\"metrics\":{{{metrics}}}}}"))
+        }
+        Update::Series {
+            episode,
+            plot,
+            series,
+            value,
+        } => Some(format!(
+            "{{\"type\":\"series\",\"episode\":{episode},\"plot\":\"{}\",\"series\":\"{}\",\"value\":{}}}",
+            escape_json(plot),
+            escape_json(series),
+            json_number(*value)
+        )),
+        Update::Step(step) => Some(format!("{{\"type\":\"step\",\"step\":{step}}}")),
+        Update::Tagged { run, update } => encode_update(update)
+            .map(|event| format!("{{\"run\":\"{}\",{}", escape_json(run), &event[1..])),
+        _ => None,
+    }
+}
@@ -0,0 +1,340 @@
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+
+use crate::{
+    env::{ContinuousAction, DiscreteActionSpace, Environment},
+    error::{check_interval, Result, RlError},
+    seed::{self, Stream},
+};
+
+/// An [`Environment`] that repeats the previously executed action instead of the chosen one with probability `p`
+///
+/// Sticky actions make deterministic environments stochastic, so a policy can't memorize a fixed sequence of actions
+/// and its evaluation measures how robust it is. The Atari evaluation protocol of Machado et al. (2018) uses
+/// `p = 0.25`. The first step of every episode always executes the chosen action. Randomness is drawn from
+/// [`Stream::Env`], so it is reproducible with [`Seeds`](crate::seed::Seeds).
+///
+/// ```
+/// use rl::{baselines::RandomAgent, testing::Corridor, train::Trainer, wrappers::StickyActions};
+///
+/// // Train without sticky actions and evaluate with them
+/// let env = StickyActions::new(Corridor::new(5), 0.0)?;
+/// let eval_env = StickyActions::new(Corridor::new(5), 0.25)?;
+/// let trainer = Trainer::new(env, RandomAgent).with_eval(10, 5, eval_env);
+/// # Ok::<(), rl::error::RlError>(())
+/// ```
+///
+/// ### Generics
+/// - `E` - The wrapped [`Environment`]
+#[derive(Debug, Clone)]
+pub struct StickyActions<E: Environment> {
+    env: E,
+    p: f64,
+    previous: Option<E::Action>,
+    repeats: u64,
+}
+
+impl<E: Environment> StickyActions<E> {
+    /// Wrap `env`, repeating the previous action with probability `p`
+    ///
+    /// **Returns** an [`RlError`] if `p` is not in `[0, 1]`
+    pub fn new(env: E, p: f64) -> Result<Self> {
+        check_interval("p", p as f32, 0.0, 1.0)?;
+        Ok(Self {
+            env,
+            p,
+            previous: None,
+            repeats: 0,
+        })
+    }
+
+    /// The number of steps that repeated the previous action instead of the chosen one, over all episodes
+    pub fn repeats(&self) -> u64 {
+        self.repeats
+    }
+
+    /// The wrapped environment
+    pub fn inner(&self) -> &E {
+        &self.env
+    }
+
+    /// Unwrap the environment
+    pub fn into_inner(self) -> E {
+        self.env
+    }
+}
+
+impl<E: Environment> Environment for StickyActions<E> {
+    type State = E::State;
+    type Action = E::Action;
+
+    fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
+        let action = match self.previous.take() {
+            Some(previous) if seed::rng(Stream::Env).gen_bool(self.p) => {
+                self.repeats += 1;
+                previous
+            }
+            _ => action,
+        };
+        self.previous = Some(action.clone());
+        self.env.step(action)
+    }
+
+    fn reset(&mut self) -> Self::State {
+        self.previous = None;
+        self.env.reset()
+    }
+
+    fn random_action(&self) -> Self::Action {
+        self.env.random_action()
+    }
+
+    fn is_active(&self) -> bool {
+        self.env.is_active()
+    }
+
+    fn discount(&self) -> Option<f32> {
+        self.env.discount()
+    }
+}
+
+impl<E: DiscreteActionSpace> DiscreteActionSpace for StickyActions<E> {
+    fn actions(&self) -> Vec<Self::Action> {
+        self.env.actions()
+    }
+}
+
+/// An [`Environment`] that perturbs the chosen action with probability `p` before executing it
+///
+/// Use [`random`](ActionNoise::random) to replace actions with random ones, [`gaussian`](ActionNoise::gaussian) to
+/// add noise to continuous actions, or [`new`](ActionNoise::new) for any other perturbation. Randomness is drawn
+/// from [`Stream::Env`], so it is reproducible with [`Seeds`](crate::seed::Seeds).
+///
+/// ```ignore
+/// // Evaluate a policy that slips to a random action 10% of the time
+/// let env = ActionNoise::random(GridWorld::new(), 0.1)?;
+/// ```
+///
+/// ### Generics
+/// - `E` - The wrapped [`Environment`]
+/// - `F` - The perturbation, from the environment and the chosen action to the executed action
+#[derive(Debug, Clone)]
+pub struct ActionNoise<E, F> {
+    env: E,
+    p: f64,
+    perturb: F,
+    perturbed: u64,
+}
+
+impl<E, F> ActionNoise<E, F>
+where
+    E: Environment,
+    F: Fn(&E, E::Action) -> E::Action,
+{
+    /// Wrap `env`, executing `perturb(env, action)` instead of `action` with probability `p`
+    ///
+    /// **Returns** an [`RlError`] if `p` is not in `[0, 1]`
+    pub fn new(env: E, p: f64, perturb: F) -> Result<Self> {
+        check_interval("p", p as f32, 0.0, 1.0)?;
+        Ok(Self {
+            env,
+            p,
+            perturb,
+            perturbed: 0,
+        })
+    }
+
+    /// The number of perturbed steps, over all episodes
+    pub fn perturbed(&self) -> u64 {
+        self.perturbed
+    }
+
+    /// The wrapped environment
+    pub fn inner(&self) -> &E {
+        &self.env
+    }
+
+    /// Unwrap the environment
+    pub fn into_inner(self) -> E {
+        self.env
+    }
+}
+
+impl<E: Environment> ActionNoise<E, fn(&E, E::Action) -> E::Action> {
+    /// Wrap `env`, executing a [random action](Environment::random_action) instead of the chosen one with
+    /// probability `p`
+    ///
+    /// **Returns** an [`RlError`] if `p` is not in `[0, 1]`
+    pub fn random(env: E, p: f64) -> Result<Self> {
+        Self::new(env, p, |env, _| env.random_action())
+    }
+}
+
+impl<E, const A: usize> ActionNoise<E, fn(&E, E::Action) -> E::Action>
+where
+    E: Environment<Action = ContinuousAction<A>>,
+{
+    /// Wrap `env`, adding gaussian noise with standard deviation `std` to every value of every action, clamped to
+    /// `[-1, 1]`
+    ///
+    /// **Returns** an [`RlError`] if `std` is negative or not finite
+    pub fn gaussian(
+        env: E,
+        std: f32,
+    ) -> Result<ActionNoise<E, impl Fn(&E, E::Action) -> E::Action>> {
+        check_interval("std", std, 0.0, f32::INFINITY)?;
+        let normal = Normal::new(0.0, std)
+            .map_err(|e| RlError::InvalidHyperparameters(format!("`std` {std}: {e}")))?;
+        ActionNoise::new(env, 1.0, move |_: &E, action: E::Action| {
+            let mut rng = seed::rng(Stream::Env);
+            ContinuousAction(
+                action
+                    .0
+                    .map(|value| (value + normal.sample(&mut rng)).clamp(-1.0, 1.0)),
+            )
+        })
+    }
+}
+
+impl<E, F> Environment for ActionNoise<E, F>
+where
+    E: Environment,
+    F: Fn(&E, E::Action) -> E::Action,
+{
+    type State = E::State;
+    type Action = E::Action;
+
+    fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
+        let action = if seed::rng(Stream::Env).gen_bool(self.p) {
+            self.perturbed += 1;
+            (self.perturb)(&self.env, action)
+        } else {
+            action
+        };
+        self.env.step(action)
+    }
+
+    fn reset(&mut self) -> Self::State {
+        self.env.reset()
+    }
+
+    fn random_action(&self) -> Self::Action {
+        self.env.random_action()
+    }
+
+    fn is_active(&self) -> bool {
+        self.env.is_active()
+    }
+
+    fn discount(&self) -> Option<f32> {
+        self.env.discount()
+    }
+}
+
+impl<E, F> DiscreteActionSpace for ActionNoise<E, F>
+where
+    E: DiscreteActionSpace,
+    F: Fn(&E, E::Action) -> E::Action,
+{
+    fn actions(&self) -> Vec<Self::Action> {
+        self.env.actions()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records the executed actions, whose random action is always `9`, with a discount factor of `0.5`
+    #[derive(Default)]
+    struct Recorder(Vec<u8>);
+
+    impl Environment for Recorder {
+        type State = ();
+        type Action = u8;
+
+        fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
+            self.0.push(action);
+            (Some(()), 0.0)
+        }
+
+        fn reset(&mut self) -> Self::State {}
+
+        fn random_action(&self) -> Self::Action {
+            9
+        }
+
+        fn discount(&self) -> Option<f32> {
+            Some(0.5)
+        }
+    }
+
+    #[test]
+    fn sticky_actions() {
+        let mut env = StickyActions::new(Recorder::default(), 1.0).unwrap();
+        env.reset();
+        for action in 0..3 {
+            env.step(action);
+        }
+        env.reset();
+        env.step(5);
+        env.step(6);
+        assert_eq!(env.repeats(), 3);
+        assert_eq!(
+            env.inner().0,
+            [0, 0, 0, 5, 5],
+            "The first action of an episode is executed"
+        );
+        assert_eq!(env.discount(), Some(0.5), "The discount is forwarded");
+        assert!(StickyActions::new(Recorder::default(), 1.5).is_err());
+
+        let mut env = StickyActions::new(Recorder::default(), 0.25).unwrap();
+        env.reset();
+        for action in 0..2000 {
+            env.step((action % 2) as u8);
+        }
+        assert!(
+            (400..600).contains(&env.repeats()),
+            "{} repeats",
+            env.repeats()
+        );
+    }
+
+    #[test]
+    fn action_noise() {
+        let mut env = ActionNoise::random(Recorder::default(), 0.0).unwrap();
+        env.step(1);
+        assert_eq!(env.perturbed(), 0);
+        let mut env = ActionNoise::random(env.into_inner(), 1.0).unwrap();
+        env.step(2);
+        assert_eq!(env.perturbed(), 1);
+        assert_eq!(env.inner().0, [1, 9]);
+        assert_eq!(env.discount(), Some(0.5));
+
+        struct Continuous(ContinuousAction<2>);
+
+        impl Environment for Continuous {
+            type State = ();
+            type Action = ContinuousAction<2>;
+
+            fn step(&mut self, action: Self::Action) -> (Option<Self::State>, f32) {
+                self.0 = action;
+                (None, 0.0)
+            }
+
+            fn reset(&mut self) -> Self::State {}
+
+            fn random_action(&self) -> Self::Action {
+                ContinuousAction([0.0; 2])
+            }
+        }
+
+        let env = Continuous(ContinuousAction([0.0; 2]));
+        assert!(ActionNoise::gaussian(env, f32::NAN).is_err());
+        let mut env = ActionNoise::gaussian(Continuous(ContinuousAction([0.0; 2])), 0.5).unwrap();
+        env.step(ContinuousAction([1.0, 0.0]));
+        let [a, b] = env.inner().0 .0;
+        assert!(a <= 1.0, "Actions are clamped, {a}");
+        assert!((-1.0..=1.0).contains(&b) && b != 0.0, "{b}");
+    }
+}